@@ -9,6 +9,7 @@
 
 DROP TABLE IF EXISTS omni_users;
 DROP TABLE IF EXISTS platforms;
+DROP TABLE IF EXISTS user_identities, oidc_login_states;
 DROP TABLE IF EXISTS users, user_meta, user_pii, user_sessions;
 
 CREATE TABLE users (
@@ -96,6 +97,35 @@ CREATE TABLE user_sessions (
     FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+-- External identities (OIDC subjects) linked to users for single sign-on
+CREATE TABLE user_identities (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    user_id BIGINT NOT NULL,
+    issuer VARCHAR(255) NOT NULL,
+    subject VARCHAR(255) NOT NULL,
+    email VARCHAR(255),
+    idp_groups JSON,
+    last_login_at DATETIME,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_issuer_subject (issuer, subject),
+    INDEX idx_user_identities_user_id (user_id),
+    FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Pending OIDC logins, keyed by the state parameter sent to the provider
+CREATE TABLE oidc_login_states (
+    state VARCHAR(64) NOT NULL,
+    nonce VARCHAR(64) NOT NULL,
+    code_verifier VARCHAR(128) NOT NULL,
+    redirect_to VARCHAR(2048),
+    expires_at DATETIME NOT NULL,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (state),
+    INDEX idx_oidc_login_states_expires_at (expires_at)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 
 CREATE TABLE platforms (
     id BIGINT NOT NULL AUTO_INCREMENT,
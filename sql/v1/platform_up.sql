@@ -13,14 +13,16 @@ DROP TABLE IF EXISTS resource_types, cost_metrics, cost_projections, cost_budget
     resource_pricing, cost_allocation_tags, storage_volumes, storage_snapshots,
     storage_migrations, storage_qos_policies, volume_qos_policy_assignments,
     storage_classes, backups, notifications, host_creds, metrics, allocations,
-    instance_logs, audit_logs, api_keys, config_vars, deployment_logs, rollbacks,
-    deployments, builds, tasks, autoscaling_rules, health_checks, network_policies,
-    service_bindings, routes, instances, domains, spaces, orgmember, permissions_role, 
+    instance_logs, app_events, audit_logs, audit_log_chain, audit_log_archives, audit_retention_policies, api_keys, org_invitations, config_vars, deployment_logs, rollbacks,
+    deployment_approvals, deployment_approval_policies, deployment_freeze_windows,
+    deployments, artifact_retention_policies, webhook_deliveries, app_webhooks, build_logs, builds, task_logs, task_schedules, tasks, autoscaling_decisions, autoscaling_rules, health_checks, network_policies,
+    service_bindings, routes, app_scheduling_policies, instances, worker_agents, worker_bootstrap_tokens, domains, spaces, orgmember, permissions_role, 
     role_user, permissions, roles, quotas, orgs, user_sessions, user_pii, user_meta, users, 
     data_services, nodes, workers, cost_summaries, usage_costs, provider_costs,
     regions, providers, providers_regions, user_notifications, role_notifications,
     notification_acknowledgments, alerts, alert_acknowledgments, alert_escalations,
-    alert_history, provider_audit_logs, apps;
+    alert_history, provider_audit_logs, pipeline_run_logs, pipeline_stage_approvals,
+    pipeline_run_stages, pipeline_runs, pipelines, apps;
 
 SET FOREIGN_KEY_CHECKS = 1;
 
@@ -28,6 +30,7 @@ SET FOREIGN_KEY_CHECKS = 1;
 
 CREATE TABLE roles (
     id BIGINT NOT NULL AUTO_INCREMENT,
+    org_id BIGINT, -- NULL for platform-wide roles, otherwise the owning organization
     name VARCHAR(255) NOT NULL,
     description TEXT,
     is_system_role TINYINT(1) DEFAULT 0,
@@ -35,7 +38,8 @@ CREATE TABLE roles (
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
     updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
     PRIMARY KEY (id),
-    UNIQUE KEY unique_name (name)
+    UNIQUE KEY unique_org_name (org_id, name),
+    KEY idx_roles_org_id (org_id)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 CREATE TABLE allocations (
@@ -179,6 +183,27 @@ CREATE TABLE orgmember (
     FOREIGN KEY (org_id) REFERENCES orgs(id) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+CREATE TABLE org_invitations (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    org_id BIGINT NOT NULL,
+    email VARCHAR(255) NOT NULL,
+    role ENUM('owner', 'admin', 'billing', 'member', 'guest') DEFAULT 'member',
+    token_hash VARCHAR(64) NOT NULL, -- SHA-256 of the invitation token; the token itself is never stored
+    status ENUM('pending', 'accepted', 'revoked') DEFAULT 'pending',
+    invited_by BIGINT NOT NULL,
+    accepted_by BIGINT,
+    expires_at DATETIME NOT NULL,
+    accepted_at DATETIME,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_token_hash (token_hash),
+    KEY idx_org_invitations_org_id (org_id),
+    KEY idx_org_invitations_email (email),
+    KEY idx_org_invitations_status (status),
+    FOREIGN KEY (org_id) REFERENCES orgs(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE api_keys (
     id BIGINT NOT NULL AUTO_INCREMENT,
     org_id BIGINT NOT NULL,
@@ -282,6 +307,38 @@ CREATE TABLE workers (
     FOREIGN KEY (region_id) REFERENCES regions(id) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+-- Tokens worker agents present to register themselves
+CREATE TABLE worker_bootstrap_tokens (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    description VARCHAR(255),
+    token_hash CHAR(64) NOT NULL COMMENT 'SHA-256 of the token, which is only shown once',
+    region_id BIGINT COMMENT 'region workers registering with this token must join; NULL for any',
+    max_uses INT COMMENT 'NULL for unlimited',
+    uses INT NOT NULL DEFAULT 0,
+    expires_at DATETIME,
+    revoked_at DATETIME,
+    last_used_at DATETIME,
+    created_by BIGINT, -- User ID
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_token_hash (token_hash),
+    FOREIGN KEY (region_id) REFERENCES regions(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Credentials and last report of the agent running on a worker
+CREATE TABLE worker_agents (
+    worker_id BIGINT NOT NULL,
+    token_hash CHAR(64) NOT NULL COMMENT 'SHA-256 of the credential the agent sends heartbeats with',
+    bootstrap_token_id BIGINT,
+    agent_version VARCHAR(50),
+    last_report JSON COMMENT 'usage and containers reported by the last heartbeat',
+    registered_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (worker_id),
+    FOREIGN KEY (worker_id) REFERENCES workers(id) ON DELETE CASCADE,
+    FOREIGN KEY (bootstrap_token_id) REFERENCES worker_bootstrap_tokens(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE data_services (
     id BIGINT NOT NULL AUTO_INCREMENT,
     region_id BIGINT NOT NULL,
@@ -336,6 +393,7 @@ CREATE TABLE apps (
     runtime VARCHAR(255),
     restart_policy ENUM('always', 'on-failure', 'no') DEFAULT 'always',
     maintenance_mode TINYINT(1) DEFAULT 0,
+    frozen_by_space TINYINT(1) DEFAULT 0,
     auto_scaling_enabled TINYINT(1) DEFAULT 0,
     status ENUM('started', 'stopped', 'crashed', 'starting', 'stopping', 'staged') DEFAULT 'stopped',
     deployment_strategy ENUM('rolling', 'blue-green', 'canary', 'recreate') DEFAULT 'rolling',
@@ -361,6 +419,20 @@ CREATE TABLE apps (
     FOREIGN KEY (region_id) REFERENCES regions(id)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+-- Placement rules the scheduler applies to the instances of an app
+CREATE TABLE app_scheduling_policies (
+    app_id BIGINT NOT NULL,
+    strategy ENUM('binpack', 'spread') COMMENT 'NULL uses the platform default',
+    anti_affinity ENUM('none', 'preferred', 'required') NOT NULL DEFAULT 'preferred',
+    node_selector JSON COMMENT 'worker labels an instance requires, as an object',
+    tolerations JSON COMMENT 'worker taints an instance tolerates, as an array',
+    updated_by BIGINT, -- User ID
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (app_id),
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE instances (
     id BIGINT NOT NULL AUTO_INCREMENT,
     app_id BIGINT NOT NULL,
@@ -372,6 +444,7 @@ CREATE TABLE instances (
     container_ip VARCHAR(45),
     allocation_id BIGINT,
     node_id BIGINT,
+    deployment_id BIGINT COMMENT 'deployment whose release the instance runs; NULL for the app''s current deployment',
     instance_index BIGINT NOT NULL,
     last_health_check DATETIME,
     health_status ENUM('healthy', 'degraded', 'critical', 'unknown') DEFAULT 'unknown',
@@ -395,6 +468,7 @@ CREATE TABLE instances (
     KEY idx_instances_status (status),
     KEY idx_instances_health_status (health_status),
     KEY idx_instances_node_id (node_id),
+    KEY idx_instances_deployment_id (deployment_id),
     FOREIGN KEY (region_id) REFERENCES regions(id),
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE,
     FOREIGN KEY (allocation_id) REFERENCES allocations(id),
@@ -597,10 +671,15 @@ CREATE TABLE builds (
     build_environment JSON,
     build_cache_key VARCHAR(255),
     log_url VARCHAR(255),
-    artifact_url VARCHAR(255),
+    artifact_url VARCHAR(512),
     artifact_checksum VARCHAR(255),
     artifact_size BIGINT,
     error_message TEXT,
+    builder_id VARCHAR(255) COMMENT 'builder holding the lease',
+    lease_token VARCHAR(64),
+    lease_expires_at DATETIME,
+    attempts INT NOT NULL DEFAULT 0,
+    auto_deploy_strategy VARCHAR(50) COMMENT 'deployed with this strategy once it succeeds; not deployed if NULL',
     started_at DATETIME,
     completed_at DATETIME,
     build_duration BIGINT COMMENT 'in seconds',
@@ -608,9 +687,67 @@ CREATE TABLE builds (
     PRIMARY KEY (id),
     KEY idx_builds_app_id (app_id),
     KEY idx_builds_status (status),
+    KEY idx_builds_lease_expires_at (status, lease_expires_at),
     KEY idx_builds_created_at (created_at),
     KEY idx_builds_source_version (source_version),
     KEY idx_builds_commit_sha (commit_sha),
+    KEY idx_builds_artifact_url (artifact_url),
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE build_logs (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    build_id BIGINT NOT NULL,
+    app_id BIGINT NOT NULL,
+    stream ENUM('stdout', 'stderr', 'system') NOT NULL DEFAULT 'stdout' COMMENT 'system lines are recorded by the orchestrator',
+    log_level ENUM('debug', 'info', 'warn', 'error', 'fatal') DEFAULT 'info',
+    message TEXT NOT NULL,
+    timestamp DATETIME(3) DEFAULT CURRENT_TIMESTAMP(3),
+    PRIMARY KEY (id),
+    KEY idx_build_logs_build_id (build_id, id),
+    KEY idx_build_logs_app_id (app_id),
+    FOREIGN KEY (build_id) REFERENCES builds(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE app_webhooks (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    app_id BIGINT NOT NULL,
+    secret VARCHAR(255) NOT NULL COMMENT 'signs deliveries; kept in the clear to verify HMAC signatures',
+    auto_deploy TINYINT(1) DEFAULT 0,
+    deployment_strategy ENUM('rolling', 'blue-green', 'canary', 'recreate') DEFAULT 'rolling',
+    enabled TINYINT(1) DEFAULT 1,
+    last_delivery_at DATETIME,
+    last_delivery_result VARCHAR(255),
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    created_by BIGINT, -- User ID
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_app_webhook_app (app_id),
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE webhook_deliveries (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    webhook_id BIGINT NOT NULL,
+    delivery_id VARCHAR(255) NOT NULL COMMENT 'X-GitHub-Delivery, X-Gitlab-Event-UUID or X-Gitea-Delivery',
+    received_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_webhook_delivery (webhook_id, delivery_id),
+    KEY idx_webhook_deliveries_received_at (received_at),
+    FOREIGN KEY (webhook_id) REFERENCES app_webhooks(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE artifact_retention_policies (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    app_id BIGINT NOT NULL,
+    keep_last BIGINT NOT NULL DEFAULT 10 COMMENT 'newest artifacts that are always kept',
+    max_age_days BIGINT COMMENT 'age after which older artifacts are deleted; right away if NULL',
+    enabled TINYINT(1) DEFAULT 1,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    created_by BIGINT, -- User ID
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_artifact_retention_app (app_id),
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
@@ -625,13 +762,21 @@ CREATE TABLE deployments (
     canary_percentage BIGINT DEFAULT 20,
     staged_instances BIGINT DEFAULT 0,
     total_instances BIGINT DEFAULT 0,
+    phase VARCHAR(50) COMMENT 'step of the strategy the deployment is in',
     environment_variables JSON,
     annotations JSON,
     labels JSON,
     started_at DATETIME,
+    paused_at DATETIME,
+    promoted_at DATETIME,
+    abort_requested_at DATETIME,
     completed_at DATETIME,
     deployment_duration BIGINT COMMENT 'in seconds',
     error_message TEXT,
+    approval_status ENUM('not_required', 'pending', 'approved', 'rejected') DEFAULT 'not_required',
+    required_approvals BIGINT DEFAULT 0,
+    approver_role_id BIGINT COMMENT 'role approvers must hold; any holder of deployments:approve if NULL',
+    freeze_override_reason TEXT COMMENT 'set when the deployment was created during a freeze window',
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
     created_by BIGINT, -- User ID
     PRIMARY KEY (id),
@@ -641,6 +786,7 @@ CREATE TABLE deployments (
     KEY idx_deployments_created_at (created_at),
     KEY idx_deployments_version (version),
     KEY idx_deployments_created_by (created_by),
+    KEY idx_deployments_approval_status (approval_status),
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE,
     FOREIGN KEY (build_id) REFERENCES builds(id),
     FOREIGN KEY (previous_deployment_id) REFERENCES deployments(id)
@@ -651,6 +797,7 @@ CREATE TABLE rollbacks (
     app_id BIGINT NOT NULL,
     from_deployment_id BIGINT NOT NULL,
     to_deployment_id BIGINT NOT NULL,
+    deployment_id BIGINT COMMENT 'deployment that redeploys the release of to_deployment_id',
     status ENUM('pending', 'in_progress', 'completed', 'failed') DEFAULT 'pending',
     reason TEXT,
     automatic TINYINT(1) DEFAULT 0,
@@ -667,27 +814,83 @@ CREATE TABLE rollbacks (
     KEY idx_rollbacks_created_at (created_at),
     KEY idx_rollbacks_created_by (created_by),
     KEY idx_rollbacks_automatic (automatic),
+    KEY idx_rollbacks_deployment_id (deployment_id),
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE,
     FOREIGN KEY (from_deployment_id) REFERENCES deployments(id),
-    FOREIGN KEY (to_deployment_id) REFERENCES deployments(id)
+    FOREIGN KEY (to_deployment_id) REFERENCES deployments(id),
+    FOREIGN KEY (deployment_id) REFERENCES deployments(id)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
 CREATE TABLE deployment_logs (
     id BIGINT NOT NULL AUTO_INCREMENT,
     deployment_id BIGINT NOT NULL,
     log_type ENUM('app', 'system', 'deployment', 'build') NOT NULL,
+    event_type VARCHAR(50) NOT NULL DEFAULT 'message' COMMENT 'such as phase_changed, instances_started or health_check_failed',
     log_level ENUM('debug', 'info', 'warn', 'error', 'fatal') DEFAULT 'info',
     message TEXT NOT NULL,
     metadata JSON,
     timestamp DATETIME DEFAULT CURRENT_TIMESTAMP,
     PRIMARY KEY (id),
-    KEY idx_deployment_logs_deployment_id (deployment_id),
+    KEY idx_deployment_logs_deployment_id (deployment_id, id),
+    KEY idx_deployment_logs_event_type (event_type),
     KEY idx_deployment_logs_timestamp (timestamp),
     KEY idx_deployment_logs_log_type (log_type),
     KEY idx_deployment_logs_log_level (log_level),
     FOREIGN KEY (deployment_id) REFERENCES deployments(id) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+CREATE TABLE deployment_approval_policies (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    app_id BIGINT COMMENT 'set for application policies, which take precedence over their space''s',
+    space_id BIGINT COMMENT 'set for space policies',
+    required_approvals BIGINT NOT NULL DEFAULT 1,
+    approver_role_id BIGINT COMMENT 'role approvers must hold; any holder of deployments:approve if NULL',
+    enabled TINYINT(1) DEFAULT 1,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    created_by BIGINT, -- User ID
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_approval_policy_app (app_id),
+    UNIQUE KEY unique_approval_policy_space (space_id),
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE,
+    FOREIGN KEY (space_id) REFERENCES spaces(id) ON DELETE CASCADE,
+    FOREIGN KEY (approver_role_id) REFERENCES roles(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE deployment_approvals (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    deployment_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    decision ENUM('approved', 'rejected') NOT NULL,
+    comment TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_deployment_approval (deployment_id, user_id),
+    KEY idx_deployment_approvals_user_id (user_id),
+    FOREIGN KEY (deployment_id) REFERENCES deployments(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE deployment_freeze_windows (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    org_id BIGINT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    reason TEXT,
+    recurrence ENUM('once', 'weekly') NOT NULL DEFAULT 'once',
+    starts_at DATETIME COMMENT 'start of a one-time window',
+    ends_at DATETIME COMMENT 'end of a one-time window',
+    start_day TINYINT COMMENT 'day a weekly window starts, 0 = Monday to 6 = Sunday',
+    start_time TIME COMMENT 'UTC time a weekly window starts',
+    end_day TINYINT COMMENT 'day a weekly window ends, 0 = Monday to 6 = Sunday',
+    end_time TIME COMMENT 'UTC time a weekly window ends',
+    enabled TINYINT(1) DEFAULT 1,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    created_by BIGINT, -- User ID
+    PRIMARY KEY (id),
+    KEY idx_deployment_freeze_windows_org_id (org_id),
+    FOREIGN KEY (org_id) REFERENCES orgs(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE config_vars (
     id BIGINT NOT NULL AUTO_INCREMENT,
     app_id BIGINT NOT NULL,
@@ -725,6 +928,10 @@ CREATE TABLE tasks (
     completed_at DATETIME,
     duration BIGINT COMMENT 'in seconds',
     error_message TEXT,
+    deployment_id BIGINT COMMENT 'deployment whose release the task ran',
+    schedule_id BIGINT COMMENT 'schedule that created the task, if any',
+    container_id VARCHAR(255) COMMENT 'cleared once the container is removed',
+    output_lines INT NOT NULL DEFAULT 0 COMMENT 'lines of container output already copied to task_logs',
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
     created_by BIGINT, -- User ID
     PRIMARY KEY (id),
@@ -733,10 +940,46 @@ CREATE TABLE tasks (
     KEY idx_tasks_created_at (created_at),
     KEY idx_tasks_created_by (created_by),
     KEY idx_tasks_node_id (node_id),
+    KEY idx_tasks_schedule_id (schedule_id),
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE,
     FOREIGN KEY (node_id) REFERENCES workers(id)
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+CREATE TABLE task_logs (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    task_id BIGINT NOT NULL,
+    stream ENUM('stdout', 'stderr', 'system') NOT NULL DEFAULT 'stdout' COMMENT 'system lines are recorded by the orchestrator',
+    message TEXT NOT NULL,
+    timestamp DATETIME(3) DEFAULT CURRENT_TIMESTAMP(3),
+    PRIMARY KEY (id),
+    KEY idx_task_logs_task_id (task_id, id),
+    FOREIGN KEY (task_id) REFERENCES tasks(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE task_schedules (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    app_id BIGINT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    command TEXT NOT NULL,
+    cron_expression VARCHAR(255) NOT NULL COMMENT 'five-field cron expression, evaluated in UTC',
+    memory_in_mb BIGINT,
+    disk_in_mb BIGINT,
+    cpu DOUBLE,
+    timeout_seconds BIGINT,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    allow_overlap BOOLEAN NOT NULL DEFAULT FALSE COMMENT 'start a task while the previous one has not finished',
+    next_run_at DATETIME,
+    last_run_at DATETIME,
+    last_task_id BIGINT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    created_by BIGINT, -- User ID
+    PRIMARY KEY (id),
+    KEY idx_task_schedules_app_id (app_id),
+    KEY idx_task_schedules_next_run_at (enabled, next_run_at),
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE health_checks (
     id BIGINT NOT NULL AUTO_INCREMENT,
     app_id BIGINT NOT NULL,
@@ -788,6 +1031,28 @@ CREATE TABLE autoscaling_rules (
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+-- Every evaluation of an autoscaling rule, kept for later review
+CREATE TABLE autoscaling_decisions (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    rule_id BIGINT,
+    app_id BIGINT NOT NULL,
+    decision ENUM('scale_out', 'scale_in', 'no_change', 'cooldown', 'insufficient_data', 'at_limit', 'superseded', 'failed') NOT NULL,
+    metric_name VARCHAR(255),
+    observed_values JSON COMMENT 'average of the metric in each evaluation period, newest first',
+    threshold_value DOUBLE,
+    comparison_operator VARCHAR(50),
+    previous_instances BIGINT,
+    desired_instances BIGINT,
+    reason TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    KEY idx_autoscaling_decisions_app_id (app_id, id),
+    KEY idx_autoscaling_decisions_rule_id (rule_id, created_at),
+    KEY idx_autoscaling_decisions_created_at (created_at),
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE,
+    FOREIGN KEY (rule_id) REFERENCES autoscaling_rules(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE network_policies (
     id BIGINT NOT NULL AUTO_INCREMENT,
     source_app_id BIGINT NOT NULL,
@@ -898,6 +1163,26 @@ CREATE TABLE instance_logs (
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci 
 ROW_FORMAT=COMPRESSED KEY_BLOCK_SIZE=8;
 
+-- Changes made to apps and instances by the orchestrator itself, such as
+-- the reconciler creating, restarting or terminating instances
+CREATE TABLE app_events (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    app_id BIGINT NOT NULL,
+    instance_id BIGINT,
+    event_type VARCHAR(64) NOT NULL,
+    severity ENUM('info', 'warning', 'error') DEFAULT 'info',
+    message TEXT NOT NULL,
+    source VARCHAR(64) NOT NULL DEFAULT 'reconciler',
+    metadata JSON,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    KEY idx_app_events_app_id_id (app_id, id),
+    KEY idx_app_events_instance_id (instance_id),
+    KEY idx_app_events_event_type (event_type),
+    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE,
+    FOREIGN KEY (instance_id) REFERENCES instances(id) ON DELETE SET NULL
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
 CREATE TABLE audit_logs (
     id BIGINT NOT NULL AUTO_INCREMENT,
     user_id BIGINT,
@@ -914,6 +1199,8 @@ CREATE TABLE audit_logs (
     request_id VARCHAR(255),
     status ENUM('success', 'failure', 'warning') DEFAULT 'success',
     created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    prev_hash CHAR(64) NOT NULL, -- entry_hash of the previous entry (all zeros for the first)
+    entry_hash CHAR(64), -- SHA-256 over the entry's content and prev_hash
     PRIMARY KEY (id),
     KEY idx_audit_logs_created_at (created_at),
     KEY idx_audit_logs_user_id (user_id),
@@ -921,11 +1208,51 @@ CREATE TABLE audit_logs (
     KEY idx_audit_logs_app_id (app_id),
     KEY idx_audit_logs_action (action),
     KEY idx_audit_logs_resource_type (resource_type),
-    KEY idx_audit_logs_status (status),
-    FOREIGN KEY (org_id) REFERENCES orgs(id) ON DELETE SET NULL,
-    FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE SET NULL
+    KEY idx_audit_logs_status (status)
+    -- No foreign keys: entries are hash chained and must never be modified,
+    -- so deleting an org or app must not rewrite their org_id or app_id.
 ) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
 
+-- Head of the audit log hash chain; a single row locked by every append
+CREATE TABLE audit_log_chain (
+    id TINYINT NOT NULL,
+    last_id BIGINT NOT NULL DEFAULT 0,
+    last_hash CHAR(64) NOT NULL,
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO audit_log_chain (id, last_id, last_hash) VALUES (1, 0, REPEAT('0', 64));
+
+-- Audit log entries moved out of audit_logs into archive files by the retention policy
+CREATE TABLE audit_log_archives (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    first_id BIGINT NOT NULL,
+    last_id BIGINT NOT NULL,
+    entry_count BIGINT NOT NULL,
+    first_prev_hash CHAR(64) NOT NULL,
+    last_hash CHAR(64) NOT NULL, -- anchors verification of the entries that remain
+    oldest_at DATETIME,
+    newest_at DATETIME,
+    path VARCHAR(1024) NOT NULL,
+    content_sha256 CHAR(64) NOT NULL,
+    signature VARCHAR(255), -- HMAC-SHA256 of the file; NULL if no signing key was configured
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_audit_log_archives_last_id (last_id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Retention policy of the platform's audit log; a single row
+CREATE TABLE audit_retention_policies (
+    id TINYINT NOT NULL,
+    retention_days INT, -- NULL keeps entries in audit_logs forever
+    updated_by BIGINT, -- User ID
+    updated_at DATETIME DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+    PRIMARY KEY (id)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+INSERT INTO audit_retention_policies (id, retention_days) VALUES (1, NULL);
+
 CREATE TABLE notifications (
     id BIGINT NOT NULL AUTO_INCREMENT,
     user_id BIGINT,
@@ -1407,4 +1734,161 @@ CREATE TABLE pipelines (
     KEY idx_pipelines_created_at (created_at),
     KEY idx_pipelines_updated_at (updated_at),
     FOREIGN KEY (app_id) REFERENCES apps(id) ON DELETE CASCADE
-) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
\ No newline at end of file
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE pipeline_runs (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    pipeline_id BIGINT NOT NULL,
+    app_id BIGINT NOT NULL,
+    status ENUM('running', 'succeeded', 'failed', 'canceled') DEFAULT 'running',
+    stages JSON NOT NULL COMMENT 'stage definitions of the pipeline when the run started',
+    build_id BIGINT COMMENT 'build carried through the stages; replaced by its copy when promoted',
+    commit_sha VARCHAR(255),
+    error_message TEXT,
+    started_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    completed_at DATETIME,
+    created_by BIGINT, -- User ID
+    PRIMARY KEY (id),
+    KEY idx_pipeline_runs_pipeline_id (pipeline_id, id),
+    KEY idx_pipeline_runs_status (status),
+    FOREIGN KEY (pipeline_id) REFERENCES pipelines(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE pipeline_run_stages (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    run_id BIGINT NOT NULL,
+    stage_index INT NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    stage_type ENUM('build', 'test', 'deploy', 'approval', 'promote') NOT NULL,
+    status ENUM('pending', 'running', 'succeeded', 'failed', 'canceled', 'skipped') DEFAULT 'pending',
+    attempts INT NOT NULL DEFAULT 0,
+    max_attempts INT NOT NULL DEFAULT 1,
+    build_id BIGINT,
+    task_id BIGINT,
+    deployment_id BIGINT,
+    next_attempt_at DATETIME COMMENT 'not started before this time',
+    waiting_reason VARCHAR(255) COMMENT 'why a pending stage has not started yet',
+    started_at DATETIME,
+    completed_at DATETIME,
+    error_message TEXT,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_pipeline_run_stage (run_id, stage_index),
+    FOREIGN KEY (run_id) REFERENCES pipeline_runs(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE pipeline_stage_approvals (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    stage_id BIGINT NOT NULL,
+    user_id BIGINT NOT NULL,
+    decision ENUM('approved', 'rejected') NOT NULL,
+    comment TEXT,
+    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+    PRIMARY KEY (id),
+    UNIQUE KEY unique_pipeline_stage_approver (stage_id, user_id),
+    FOREIGN KEY (stage_id) REFERENCES pipeline_run_stages(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+CREATE TABLE pipeline_run_logs (
+    id BIGINT NOT NULL AUTO_INCREMENT,
+    run_id BIGINT NOT NULL,
+    stage_id BIGINT COMMENT 'NULL for lines about the run as a whole',
+    log_level ENUM('debug', 'info', 'warn', 'error') DEFAULT 'info',
+    message TEXT NOT NULL,
+    timestamp DATETIME(3) DEFAULT CURRENT_TIMESTAMP(3),
+    PRIMARY KEY (id),
+    KEY idx_pipeline_run_logs_run_id (run_id, id),
+    KEY idx_pipeline_run_logs_stage_id (stage_id, id),
+    FOREIGN KEY (run_id) REFERENCES pipeline_runs(id) ON DELETE CASCADE
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci;
+
+-- Seed the permission catalog enforced by the API (see src/schemas/v1/api/rbac/permissions.rs)
+INSERT INTO permissions (name, description, resource_type, action)
+VALUES
+('platforms:read'     , 'View platforms'                         , 'platforms'    , 'read'),
+('platforms:write'    , 'Create and remove platforms'            , 'platforms'    , 'write'),
+('orgs:read'          , 'View organizations'                     , 'orgs'         , 'read'),
+('orgs:write'         , 'Create, update and delete organizations', 'orgs'         , 'write'),
+('apps:read'          , 'View applications'                      , 'apps'         , 'read'),
+('apps:write'         , 'Create and update applications'         , 'apps'         , 'write'),
+('apps:delete'        , 'Delete applications'                    , 'apps'         , 'delete'),
+('apps:control'       , 'Start, stop and scale applications'     , 'apps'         , 'control'),
+('instances:read'     , 'View application instances'             , 'instances'    , 'read'),
+('builds:read'        , 'View builds'                            , 'builds'       , 'read'),
+('builds:write'       , 'Create builds and upload releases'      , 'builds'       , 'write'),
+('builds:execute'     , 'Lease and run queued builds'            , 'builds'       , 'execute'),
+('deployments:read'   , 'View deployments'                       , 'deployments'  , 'read'),
+('deployments:write'  , 'Create, update and delete deployments'  , 'deployments'  , 'write'),
+('deployments:approve', 'Approve and reject deployments'         , 'deployments'  , 'approve'),
+('deployments:manage' , 'Manage approval policies and freezes'   , 'deployments'  , 'manage'),
+('deployments:override', 'Deploy during freeze windows'           , 'deployments'  , 'override'),
+('pipelines:read'     , 'View pipelines and their runs'          , 'pipelines'    , 'read'),
+('pipelines:write'    , 'Create, update and delete pipelines'    , 'pipelines'    , 'write'),
+('pipelines:run'      , 'Start, cancel and retry pipeline runs'  , 'pipelines'    , 'run'),
+('tasks:read'         , 'View tasks, their output and schedules' , 'tasks'        , 'read'),
+('tasks:write'        , 'Run and cancel tasks, manage schedules' , 'tasks'        , 'write'),
+('alerts:read'        , 'View alerts'                            , 'alerts'       , 'read'),
+('alerts:write'       , 'Create, acknowledge and resolve alerts' , 'alerts'       , 'write'),
+('notifications:read' , 'View notifications'                     , 'notifications', 'read'),
+('notifications:write', 'Create and manage notifications'        , 'notifications', 'write'),
+('audit_logs:read'    , 'View audit logs'                        , 'audit_logs'   , 'read'),
+('audit_logs:write'   , 'Write audit log entries'                , 'audit_logs'   , 'write'),
+('audit_logs:export'  , 'Export and verify the audit log'        , 'audit_logs'   , 'export'),
+('audit_logs:manage'  , 'Manage audit log retention and archives', 'audit_logs'   , 'manage'),
+('cost:read'          , 'View cost data, budgets and pricing'    , 'cost'         , 'read'),
+('cost:write'         , 'Manage cost data, budgets and pricing'  , 'cost'         , 'write'),
+('metrics:read'       , 'View metrics'                           , 'metrics'      , 'read'),
+('logs:read'          , 'View logs'                              , 'logs'         , 'read'),
+('logs:write'         , 'Ingest logs'                            , 'logs'         , 'write'),
+('storage:read'       , 'View storage classes and volumes'       , 'storage'      , 'read'),
+('providers:read'     , 'View providers'                         , 'providers'    , 'read'),
+('regions:read'       , 'View regions'                           , 'regions'      , 'read'),
+('workers:read'       , 'View workers'                           , 'workers'      , 'read'),
+('workers:write'      , 'Manage, drain and decommission workers' , 'workers'      , 'write'),
+('metadata:read'      , 'View system metadata'                   , 'metadata'     , 'read'),
+('metadata:write'     , 'Modify system metadata'                 , 'metadata'     , 'write'),
+('permissions:read'   , 'View roles and permissions'             , 'permissions'  , 'read'),
+('permissions:write'  , 'Manage roles and permissions'           , 'permissions'  , 'write'),
+('users:read'         , 'View users'                             , 'users'        , 'read'),
+('api_keys:read'      , 'View API keys'                          , 'api_keys'     , 'read'),
+('api_keys:write'     , 'Create, rotate and revoke API keys'     , 'api_keys'     , 'write'),
+('members:read'       , 'View organization members and invites'  , 'members'      , 'read'),
+('members:write'      , 'Invite, update and remove members'      , 'members'      , 'write'),
+('spaces:read'        , 'View spaces'                            , 'spaces'       , 'read'),
+('spaces:write'       , 'Manage, archive and delete spaces'      , 'spaces'       , 'write');
+
+-- Seed the built-in system roles. System roles cannot be modified or deleted through the API.
+INSERT INTO roles (name, description, is_system_role, scope)
+VALUES
+('owner'     , 'Full control, including organization settings and ownership' , 1 , 'organization'),
+('admin'     , 'Manage applications, members, roles and billing data'         , 1 , 'organization'),
+('developer' , 'Build, deploy and operate applications'                       , 1 , 'organization'),
+('viewer'    , 'Read-only access to applications and their telemetry'         , 1 , 'organization');
+
+INSERT INTO permissions_role (permissions_id, role_id)
+SELECT p.id, r.id FROM permissions p JOIN roles r ON r.name = 'owner' AND r.org_id IS NULL
+WHERE p.name LIKE '%:%';
+
+INSERT INTO permissions_role (permissions_id, role_id)
+SELECT p.id, r.id FROM permissions p JOIN roles r ON r.name = 'admin' AND r.org_id IS NULL
+WHERE p.name LIKE '%:%'
+  AND p.name NOT IN ('platforms:write', 'orgs:write', 'metadata:write', 'audit_logs:manage', 'workers:write');
+
+INSERT INTO permissions_role (permissions_id, role_id)
+SELECT p.id, r.id FROM permissions p JOIN roles r ON r.name = 'developer' AND r.org_id IS NULL
+WHERE p.name IN (
+    'orgs:read', 'apps:read', 'apps:write', 'apps:control', 'instances:read',
+    'builds:read', 'builds:write', 'deployments:read', 'deployments:write',
+    'pipelines:read', 'pipelines:write', 'pipelines:run', 'tasks:read', 'tasks:write',
+    'alerts:read', 'alerts:write', 'notifications:read', 'metrics:read', 'logs:read',
+    'storage:read', 'providers:read', 'regions:read', 'api_keys:read', 'api_keys:write',
+    'members:read', 'spaces:read'
+);
+
+INSERT INTO permissions_role (permissions_id, role_id)
+SELECT p.id, r.id FROM permissions p JOIN roles r ON r.name = 'viewer' AND r.org_id IS NULL
+WHERE p.name IN (
+    'orgs:read', 'apps:read', 'instances:read', 'builds:read', 'deployments:read',
+    'pipelines:read', 'tasks:read', 'alerts:read', 'notifications:read', 'metrics:read',
+    'logs:read', 'storage:read', 'providers:read', 'regions:read', 'members:read',
+    'spaces:read'
+);
@@ -0,0 +1,127 @@
+//! Artifact store backed by a local directory.
+
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use super::{ArtifactReader, ArtifactStore, CHUNK_SIZE};
+
+/// Keeps artifacts as files below a root directory and addresses them with
+/// `file://` URLs.
+pub struct LocalArtifactStore {
+    root: PathBuf,
+}
+
+impl LocalArtifactStore {
+    /// Creates the store, creating its root directory if needed.
+    pub fn new(root: &str) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(root).with_context(|| format!("Failed to create artifact directory {}", root))?;
+        let root = Path::new(root)
+            .canonicalize()
+            .with_context(|| format!("Failed to resolve artifact directory {}", root))?;
+        Ok(Self { root })
+    }
+
+    fn path_of_key(&self, key: &str) -> anyhow::Result<PathBuf> {
+        let relative = Path::new(key);
+        if !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            anyhow::bail!("Invalid artifact key '{}'", key);
+        }
+        Ok(self.root.join(relative))
+    }
+
+    fn path_of_url(&self, url: &str) -> anyhow::Result<PathBuf> {
+        let path = url
+            .strip_prefix("file://")
+            .map(PathBuf::from)
+            .with_context(|| format!("'{}' is not a local artifact URL", url))?;
+        let key = path
+            .strip_prefix(&self.root)
+            .with_context(|| format!("'{}' is outside the artifact directory", url))?;
+        self.path_of_key(&key.to_string_lossy())
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactStore for LocalArtifactStore {
+    fn describe(&self) -> String {
+        format!("local directory {}", self.root.display())
+    }
+
+    async fn put(&self, key: &str, source: &Path, checksum: &str, size: u64) -> anyhow::Result<String> {
+        let target = self.path_of_key(key)?;
+        if let Some(parent) = target.parent() {
+            tokio::fs::create_dir_all(parent)
+                .await
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        // Written next to the target and renamed once verified, so readers
+        // never see a partial artifact
+        let partial = target.with_extension("partial");
+        let mut input = tokio::fs::File::open(source)
+            .await
+            .with_context(|| format!("Failed to open {}", source.display()))?;
+        let mut output = tokio::fs::File::create(&partial)
+            .await
+            .with_context(|| format!("Failed to create {}", partial.display()))?;
+
+        let mut hasher = Sha256::new();
+        let mut buffer = vec![0u8; CHUNK_SIZE];
+        let mut written = 0u64;
+        let copied: anyhow::Result<()> = async {
+            loop {
+                let read = input.read(&mut buffer).await.context("Failed to read artifact")?;
+                if read == 0 {
+                    break;
+                }
+                hasher.update(&buffer[..read]);
+                output.write_all(&buffer[..read]).await.context("Failed to write artifact")?;
+                written += read as u64;
+            }
+            output.sync_all().await.context("Failed to flush artifact")?;
+            Ok(())
+        }
+        .await;
+
+        let actual = hex::encode(hasher.finalize());
+        let verified = copied.and_then(|_| {
+            if written != size || actual != checksum {
+                anyhow::bail!(
+                    "Stored artifact does not match the upload: {} bytes with SHA-256 {}, expected {} bytes with SHA-256 {}",
+                    written, actual, size, checksum
+                );
+            }
+            Ok(())
+        });
+        if let Err(e) = verified {
+            let _ = tokio::fs::remove_file(&partial).await;
+            return Err(e);
+        }
+
+        tokio::fs::rename(&partial, &target)
+            .await
+            .with_context(|| format!("Failed to move artifact to {}", target.display()))?;
+
+        Ok(format!("file://{}", target.display()))
+    }
+
+    async fn get(&self, url: &str) -> anyhow::Result<ArtifactReader> {
+        let path = self.path_of_url(url)?;
+        let file = tokio::fs::File::open(&path)
+            .await
+            .with_context(|| format!("Failed to open artifact {}", path.display()))?;
+        Ok(Box::pin(file))
+    }
+
+    async fn delete(&self, url: &str) -> anyhow::Result<()> {
+        let path = self.path_of_url(url)?;
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e).with_context(|| format!("Failed to delete artifact {}", path.display())),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Storage of release artifacts.
+//!
+//! Uploaded releases are kept in an [`ArtifactStore`] rather than on the
+//! disk of whichever node handled the upload, so that every node (and every
+//! worker) can fetch them. Artifacts are addressed by the URL the store
+//! returns when they are written, which is recorded in `builds.artifact_url`
+//! together with their SHA-256 checksum and size.
+//!
+//! # Backends
+//! - `local`: a directory on the local filesystem, which should be shared
+//!   storage when running a cluster
+//! - `s3`: a bucket of an S3-compatible object store such as AWS S3 or MinIO
+//!
+//! Artifacts of builds an application no longer needs are deleted according
+//! to its [retention policy](retention).
+
+pub mod local;
+pub mod retention;
+pub mod s3;
+
+pub use local::LocalArtifactStore;
+pub use s3::S3ArtifactStore;
+
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use anyhow::Context;
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::config::ArtifactsConfig;
+
+/// Size of the buffer files are hashed and copied with.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// A stream of the contents of a stored artifact.
+pub type ArtifactReader = Pin<Box<dyn AsyncRead + Send>>;
+
+/// A place release artifacts are kept.
+#[async_trait::async_trait]
+pub trait ArtifactStore: Send + Sync {
+    /// Describes the store for log messages.
+    fn describe(&self) -> String;
+
+    /// Stores the file at `source` under `key` and returns the URL it can be
+    /// fetched from. `checksum` is the hex-encoded SHA-256 of the file; the
+    /// write fails if the stored contents do not match it.
+    async fn put(&self, key: &str, source: &Path, checksum: &str, size: u64) -> anyhow::Result<String>;
+
+    /// Opens an artifact stored at `url` for reading.
+    async fn get(&self, url: &str) -> anyhow::Result<ArtifactReader>;
+
+    /// Deletes the artifact stored at `url`. Deleting an artifact that does
+    /// not exist succeeds.
+    async fn delete(&self, url: &str) -> anyhow::Result<()>;
+}
+
+/// Builds the artifact store selected in the configuration.
+pub fn store_from_config(config: &ArtifactsConfig) -> anyhow::Result<Arc<dyn ArtifactStore>> {
+    match config.backend.as_str() {
+        "local" => Ok(Arc::new(LocalArtifactStore::new(&config.local_dir)?)),
+        "s3" => {
+            let s3 = config
+                .s3
+                .as_ref()
+                .context("The s3 artifact backend needs an 'artifacts.s3' section")?;
+            Ok(Arc::new(S3ArtifactStore::new(s3)?))
+        }
+        other => anyhow::bail!("Unknown artifact backend '{}'; use 'local' or 's3'", other),
+    }
+}
+
+/// Key a release artifact of an application is stored under. Artifacts are
+/// named after their checksum, so uploading the same file twice stores it
+/// once.
+pub fn release_key(platform_id: i64, app_id: i64, version: &str, checksum: &str) -> String {
+    let mut version: String = version
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') { c } else { '_' })
+        .collect();
+    if version.chars().all(|c| c == '.') {
+        version = version.replace('.', "_");
+    }
+    format!("platforms/{}/apps/{}/releases/{}/{}.tar.gz", platform_id, app_id, version, checksum)
+}
+
+/// Reads a file in chunks and returns its hex-encoded SHA-256 and its size.
+pub async fn hash_file(path: &Path) -> anyhow::Result<(String, u64)> {
+    let mut file = tokio::fs::File::open(path)
+        .await
+        .with_context(|| format!("Failed to open {}", path.display()))?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut size = 0u64;
+
+    loop {
+        let read = file.read(&mut buffer).await.context("Failed to read artifact")?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        size += read as u64;
+    }
+
+    Ok((hex::encode(hasher.finalize()), size))
+}
@@ -0,0 +1,90 @@
+//! Deletion of release artifacts past their application's retention policy.
+
+use std::sync::Arc;
+
+use sqlx::{MySql, Pool};
+
+use super::ArtifactStore;
+use crate::schemas::v1::db::queries as db;
+use crate::DatabaseManager;
+
+/// Counts of the changes made by a retention pass.
+#[derive(Debug, Default, Clone)]
+pub struct RetentionSummary {
+    pub artifacts_deleted: usize,
+    pub bytes_freed: i64,
+    pub failures: usize,
+}
+
+/// Applies the artifact retention policies of every platform.
+pub async fn enforce_all_platforms(db_manager: &DatabaseManager, store: &Arc<dyn ArtifactStore>) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for artifact retention: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for artifact retention: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        match enforce_platform(&pool, store.as_ref()).await {
+            Ok(summary) if summary.artifacts_deleted > 0 || summary.failures > 0 => {
+                log::info!("Applied artifact retention on platform {}: {:?}", platform_id, summary);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to apply artifact retention on platform {}: {:#}", platform_id, e),
+        }
+    }
+}
+
+/// Deletes the artifacts of a platform's builds that have outlived their
+/// application's retention policy.
+///
+/// The build keeps its record, checksum and size; only its `artifact_url`
+/// is cleared. Stored artifacts shared with builds that are kept are left
+/// in place.
+pub async fn enforce_platform(pool: &Pool<MySql>, store: &dyn ArtifactStore) -> anyhow::Result<RetentionSummary> {
+    let mut summary = RetentionSummary::default();
+
+    for policy in db::artifact::list_enabled_retention_policies(pool).await? {
+        for build in db::artifact::list_expired_artifacts(pool, &policy).await? {
+            let url = match &build.artifact_url {
+                Some(url) => url,
+                None => continue,
+            };
+
+            match db::artifact::clear_build_artifact(pool, build.id, url).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    if let Err(e) = store.delete(url).await {
+                        summary.failures += 1;
+                        log::warn!("Failed to delete artifact {} of build {}: {:#}", url, build.id, e);
+                        continue;
+                    }
+                    summary.bytes_freed += build.artifact_size.unwrap_or(0);
+                }
+                Err(e) => {
+                    summary.failures += 1;
+                    log::error!("Failed to expire artifact of build {}: {:#}", build.id, e);
+                    continue;
+                }
+            }
+            summary.artifacts_deleted += 1;
+        }
+    }
+
+    Ok(summary)
+}
@@ -0,0 +1,220 @@
+//! Artifact store backed by an S3-compatible object store.
+//!
+//! Requests are signed with AWS Signature Version 4. Uploads are streamed
+//! from disk and carry the SHA-256 of the artifact as their signed payload
+//! hash, so the object store rejects any upload whose contents do not
+//! match it.
+
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::Utc;
+use futures::TryStreamExt;
+use hmac::{Hmac, Mac};
+use reqwest::{Method, Url};
+use sha2::{Digest, Sha256};
+use tokio_util::io::{ReaderStream, StreamReader};
+
+use super::{ArtifactReader, ArtifactStore};
+use crate::config::S3ArtifactsConfig;
+
+/// Payload hash of requests without a body.
+const EMPTY_PAYLOAD_SHA256: &str = "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855";
+
+/// Keeps artifacts as objects in a bucket and addresses them with `s3://`
+/// URLs.
+pub struct S3ArtifactStore {
+    client: reqwest::Client,
+    endpoint: Url,
+    bucket: String,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    path_style: bool,
+}
+
+impl S3ArtifactStore {
+    /// Creates the store. Credentials missing from the configuration are
+    /// taken from the `AWS_ACCESS_KEY_ID` and `AWS_SECRET_ACCESS_KEY`
+    /// environment variables.
+    pub fn new(config: &S3ArtifactsConfig) -> anyhow::Result<Self> {
+        let endpoint = Url::parse(&config.endpoint)
+            .with_context(|| format!("Invalid S3 endpoint '{}'", config.endpoint))?;
+        let access_key_id = config
+            .access_key_id
+            .clone()
+            .or_else(|| std::env::var("AWS_ACCESS_KEY_ID").ok())
+            .context("No S3 access key configured")?;
+        let secret_access_key = config
+            .secret_access_key
+            .clone()
+            .or_else(|| std::env::var("AWS_SECRET_ACCESS_KEY").ok())
+            .context("No S3 secret key configured")?;
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            endpoint,
+            bucket: config.bucket.clone(),
+            region: config.region.clone(),
+            access_key_id,
+            secret_access_key,
+            path_style: config.path_style,
+        })
+    }
+
+    fn object_url(&self, key: &str) -> anyhow::Result<Url> {
+        let mut url = self.endpoint.clone();
+        let key = uri_encode(key, false);
+        if self.path_style {
+            url.set_path(&format!("/{}/{}", uri_encode(&self.bucket, true), key));
+        } else {
+            let host = self.endpoint.host_str().context("S3 endpoint has no host")?;
+            url.set_host(Some(&format!("{}.{}", self.bucket, host)))
+                .context("Invalid S3 bucket host")?;
+            url.set_path(&format!("/{}", key));
+        }
+        Ok(url)
+    }
+
+    fn key_of_url<'u>(&self, url: &'u str) -> anyhow::Result<&'u str> {
+        url.strip_prefix("s3://")
+            .and_then(|rest| rest.strip_prefix(self.bucket.as_str()))
+            .and_then(|rest| rest.strip_prefix('/'))
+            .with_context(|| format!("'{}' is not an artifact URL of bucket {}", url, self.bucket))
+    }
+
+    /// Builds a signed request for an object. `payload_sha256` is the hex
+    /// SHA-256 of the request body.
+    fn signed_request(&self, method: Method, key: &str, payload_sha256: &str) -> anyhow::Result<reqwest::RequestBuilder> {
+        let url = self.object_url(key)?;
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or_default(), port),
+            None => url.host_str().unwrap_or_default().to_string(),
+        };
+
+        let now = Utc::now();
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+        let scope = format!("{}/{}/s3/aws4_request", date, self.region);
+        let signed_headers = "host;x-amz-content-sha256;x-amz-date";
+
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-content-sha256:{}\nx-amz-date:{}\n\n{}\n{}",
+            method.as_str(),
+            url.path(),
+            host,
+            payload_sha256,
+            amz_date,
+            signed_headers,
+            payload_sha256
+        );
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes()))
+        );
+
+        let mut key = hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), date.as_bytes());
+        for part in [self.region.as_str(), "s3", "aws4_request"] {
+            key = hmac_sha256(&key, part.as_bytes());
+        }
+        let signature = hex::encode(hmac_sha256(&key, string_to_sign.as_bytes()));
+
+        Ok(self
+            .client
+            .request(method, url)
+            .header("x-amz-content-sha256", payload_sha256)
+            .header("x-amz-date", amz_date)
+            .header(
+                "authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key_id, scope, signed_headers, signature
+                ),
+            ))
+    }
+}
+
+#[async_trait::async_trait]
+impl ArtifactStore for S3ArtifactStore {
+    fn describe(&self) -> String {
+        format!("S3 bucket {} at {}", self.bucket, self.endpoint)
+    }
+
+    async fn put(&self, key: &str, source: &Path, checksum: &str, size: u64) -> anyhow::Result<String> {
+        let file = tokio::fs::File::open(source)
+            .await
+            .with_context(|| format!("Failed to open {}", source.display()))?;
+
+        let response = self
+            .signed_request(Method::PUT, key, checksum)?
+            .header("content-length", size)
+            .header("content-type", "application/gzip")
+            .body(reqwest::Body::wrap_stream(ReaderStream::new(file)))
+            .send()
+            .await
+            .context("Failed to upload artifact")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Uploading artifact {} failed with {}: {}", key, status, body);
+        }
+
+        Ok(format!("s3://{}/{}", self.bucket, key))
+    }
+
+    async fn get(&self, url: &str) -> anyhow::Result<ArtifactReader> {
+        let key = self.key_of_url(url)?;
+        let response = self
+            .signed_request(Method::GET, key, EMPTY_PAYLOAD_SHA256)?
+            .send()
+            .await
+            .context("Failed to fetch artifact")?;
+
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Fetching artifact {} failed with {}", key, status);
+        }
+
+        let stream = response.bytes_stream().map_err(std::io::Error::other);
+        Ok(Box::pin(StreamReader::new(stream)))
+    }
+
+    async fn delete(&self, url: &str) -> anyhow::Result<()> {
+        let key = self.key_of_url(url)?;
+        let response = self
+            .signed_request(Method::DELETE, key, EMPTY_PAYLOAD_SHA256)?
+            .send()
+            .await
+            .context("Failed to delete artifact")?;
+
+        // S3 answers 204 whether or not the object existed
+        let status = response.status();
+        if !status.is_success() && status != reqwest::StatusCode::NOT_FOUND {
+            anyhow::bail!("Deleting artifact {} failed with {}", key, status);
+        }
+        Ok(())
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Percent-encodes a string as required for canonical S3 requests, leaving
+/// `/` alone unless `encode_slash` is set.
+fn uri_encode(value: &str, encode_slash: bool) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => encoded.push(byte as char),
+            b'/' if !encode_slash => encoded.push('/'),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
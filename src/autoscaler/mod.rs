@@ -0,0 +1,280 @@
+//! Autoscaling of applications by their `autoscaling_rules`.
+//!
+//! The leader evaluates every enabled rule of each running application that
+//! has `auto_scaling_enabled` set, at most once per the rule's period. A
+//! rule fires when its metric, averaged over each of its last
+//! `evaluation_periods` periods, satisfies the comparison in every one of
+//! them. When several rules of an application fire at once, scaling out
+//! wins over scaling in. Instance counts change through the same path as
+//! `PUT .../scale`, and nothing is changed while the application's cooldown
+//! since its last autoscaling is running.
+//!
+//! Every evaluation is recorded in `autoscaling_decisions` with the values
+//! it observed and the reason for its outcome.
+
+pub mod rule;
+
+use std::collections::BTreeMap;
+
+use anyhow::Context;
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use sqlx::{MySql, Pool};
+
+use crate::config::AutoscalerConfig;
+use crate::schemas::v1::api::apps::control::scale_app_to;
+use crate::schemas::v1::db::queries as db;
+use crate::DatabaseManager;
+use db::app_event::NewAppEvent;
+use db::autoscaling::{AutoscalingRule, NewAutoscalingDecision};
+use rule::Verdict;
+
+/// Counts of the decisions made by an autoscaling pass.
+#[derive(Debug, Default, Clone)]
+pub struct AutoscaleSummary {
+    pub evaluated: usize,
+    pub scaled_out: usize,
+    pub scaled_in: usize,
+    pub failures: usize,
+}
+
+/// Evaluates the autoscaling rules of every platform once.
+pub async fn autoscale_all_platforms(db_manager: &DatabaseManager, config: &AutoscalerConfig) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for autoscaling: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for autoscaling: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        match autoscale_platform(&pool, config).await {
+            Ok(Some(summary)) if summary.scaled_out + summary.scaled_in + summary.failures > 0 => {
+                log::info!("Autoscaled platform {}: {:?}", platform_id, summary);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to autoscale platform {}: {:#}", platform_id, e),
+        }
+    }
+}
+
+/// Evaluates the due autoscaling rules of a platform. Returns `None` if
+/// another node is autoscaling the platform.
+pub async fn autoscale_platform(pool: &Pool<MySql>, config: &AutoscalerConfig) -> anyhow::Result<Option<AutoscaleSummary>> {
+    // Named locks are server wide, so the lock name includes the database
+    let mut lock_conn = pool.acquire().await?;
+    let locked = sqlx::query_scalar::<_, Option<i64>>("SELECT GET_LOCK(CONCAT('omni_autoscaler:', DATABASE()), 0)")
+        .fetch_one(&mut *lock_conn)
+        .await
+        .context("Failed to acquire autoscaler lock")?;
+    if locked != Some(1) {
+        return Ok(None);
+    }
+
+    let result = run(pool, config).await;
+
+    if let Err(e) = sqlx::query("SELECT RELEASE_LOCK(CONCAT('omni_autoscaler:', DATABASE()))")
+        .execute(&mut *lock_conn)
+        .await
+    {
+        log::warn!("Failed to release autoscaler lock: {}", e);
+    }
+
+    result.map(Some)
+}
+
+async fn run(pool: &Pool<MySql>, config: &AutoscalerConfig) -> anyhow::Result<AutoscaleSummary> {
+    let mut summary = AutoscaleSummary::default();
+    let now = Utc::now();
+
+    let mut by_app: BTreeMap<i64, Vec<AutoscalingRule>> = BTreeMap::new();
+    for rule in db::autoscaling::list_rules_to_evaluate(pool).await? {
+        by_app.entry(rule.app_id).or_default().push(rule);
+    }
+
+    for (app_id, rules) in by_app {
+        if let Err(e) = autoscale_app(pool, app_id, &rules, now, &mut summary).await {
+            summary.failures += 1;
+            log::error!("Failed to autoscale app {}: {:#}", app_id, e);
+        }
+    }
+
+    if config.decision_retention_days > 0 {
+        db::autoscaling::prune_autoscaling_decisions(pool, config.decision_retention_days).await?;
+    }
+
+    Ok(summary)
+}
+
+/// A rule that is due, together with what it observed.
+struct Evaluation<'a> {
+    rule: &'a AutoscalingRule,
+    metric_name: String,
+    observed: Vec<Option<f64>>,
+    verdict: Verdict,
+}
+
+async fn autoscale_app(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    rules: &[AutoscalingRule],
+    now: DateTime<Utc>,
+    summary: &mut AutoscaleSummary,
+) -> anyhow::Result<()> {
+    let app = db::app::get_app_by_id(pool, app_id).await?;
+    let current = app.instances.unwrap_or(1);
+    let last_scaling = db::autoscaling::last_app_scaling(pool, app_id).await?;
+
+    let mut evaluations = Vec::new();
+    for rule in rules {
+        let period = Duration::seconds(rule.period_seconds.unwrap_or(60).max(1));
+        if let Some(last) = db::autoscaling::last_rule_evaluation(pool, rule.id).await? {
+            if now - last < period {
+                continue;
+            }
+        }
+
+        let metric_name = match rule::metric_name(rule) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        // Newest period first
+        let periods = rule.evaluation_periods.unwrap_or(1).max(1);
+        let mut observed = Vec::with_capacity(periods as usize);
+        for index in 0..periods {
+            let to = now - period * index as i32;
+            let from = to - period;
+            observed.push(db::autoscaling::average_app_metric(pool, app_id, &metric_name, from, to).await?);
+        }
+
+        let verdict = rule::evaluate(rule, &observed, current);
+        evaluations.push(Evaluation { rule, metric_name, observed, verdict });
+    }
+    summary.evaluated += evaluations.len();
+
+    // Scaling out wins over scaling in; among rules pulling the same way,
+    // the one asking for the biggest change wins
+    let chosen = evaluations
+        .iter()
+        .enumerate()
+        .filter_map(|(index, evaluation)| match evaluation.verdict {
+            Verdict::Breached { desired, .. } => Some((index, desired)),
+            _ => None,
+        })
+        .max_by_key(|(index, desired)| (*desired > current, (desired - current).abs(), std::cmp::Reverse(*index)))
+        .map(|(index, _)| index);
+
+    for (index, evaluation) in evaluations.iter().enumerate() {
+        let (decision, desired, reason) = match &evaluation.verdict {
+            Verdict::InsufficientData { missing_periods } => (
+                "insufficient_data",
+                None,
+                format!("no {} samples in {} of the evaluation periods", evaluation.metric_name, missing_periods),
+            ),
+            Verdict::NotBreached { reason } => ("no_change", None, reason.clone()),
+            Verdict::AtLimit { reason } => ("at_limit", None, reason.clone()),
+            Verdict::Breached { desired, reason } if Some(index) != chosen => (
+                "superseded",
+                Some(*desired),
+                format!("{}; another rule of the app took precedence", reason),
+            ),
+            Verdict::Breached { desired, reason } => {
+                let cooldown = Duration::seconds(evaluation.rule.cooldown_period_seconds.max(0));
+                match last_scaling {
+                    Some(last) if now - last < cooldown => (
+                        "cooldown",
+                        Some(*desired),
+                        format!("{}; in cooldown until {}", reason, (last + cooldown).to_rfc3339()),
+                    ),
+                    _ => scale(pool, app_id, current, *desired, reason, summary).await,
+                }
+            }
+        };
+
+        let record = NewAutoscalingDecision {
+            rule_id: evaluation.rule.id,
+            app_id,
+            decision,
+            metric_name: evaluation.metric_name.clone(),
+            observed_values: json!(evaluation.observed),
+            threshold_value: evaluation.rule.threshold_value,
+            comparison_operator: evaluation
+                .rule
+                .comparison_operator
+                .clone()
+                .unwrap_or_else(|| "GreaterThanOrEqualToThreshold".to_string()),
+            previous_instances: current,
+            desired_instances: desired,
+            reason,
+        };
+        if let Err(e) = db::autoscaling::insert_autoscaling_decision(pool, &record).await {
+            log::warn!("Failed to record autoscaling decision of rule {}: {:#}", evaluation.rule.id, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Changes the instance count of an application, returning the decision
+/// to record.
+async fn scale(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    current: i64,
+    desired: i64,
+    reason: &str,
+    summary: &mut AutoscaleSummary,
+) -> (&'static str, Option<i64>, String) {
+    match scale_app_to(pool, app_id, desired).await {
+        Ok(Some(_)) => {
+            let decision = if desired > current {
+                summary.scaled_out += 1;
+                "scale_out"
+            } else {
+                summary.scaled_in += 1;
+                "scale_in"
+            };
+            log::info!("Autoscaled app {} from {} to {} instances: {}", app_id, current, desired, reason);
+
+            let event = NewAppEvent {
+                app_id,
+                instance_id: None,
+                event_type: "app_autoscaled",
+                severity: "info",
+                message: format!("Autoscaled from {} to {} instances: {}", current, desired, reason),
+                source: "autoscaler",
+                metadata: Some(json!({ "previous_instances": current, "desired_instances": desired })),
+            };
+            if let Err(e) = db::app_event::insert_app_event(pool, &event).await {
+                log::warn!("Failed to record app_autoscaled event of app {}: {:#}", app_id, e);
+            }
+
+            (decision, Some(desired), format!("{}; scaled from {} to {} instances", reason, current, desired))
+        }
+        Ok(None) => (
+            "failed",
+            Some(desired),
+            format!("{}; another operation on the app was in progress", reason),
+        ),
+        Err((_, body)) => {
+            summary.failures += 1;
+            let message = body["message"].as_str().unwrap_or("unknown error").to_string();
+            ("failed", Some(desired), format!("{}; scaling failed: {}", reason, message))
+        }
+    }
+}
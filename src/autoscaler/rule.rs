@@ -0,0 +1,124 @@
+use crate::schemas::v1::db::queries::autoscaling::AutoscalingRule;
+
+/// Metric types a rule can watch.
+pub const METRIC_TYPES: [&str; 6] = ["cpu", "memory", "http_throughput", "http_latency", "queue_depth", "custom"];
+
+/// How a metric is compared with a rule's threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    GreaterThanOrEqual,
+    GreaterThan,
+    LessThan,
+    LessThanOrEqual,
+}
+
+impl Comparison {
+    /// Names of the operators, as stored in `autoscaling_rules`.
+    pub const NAMES: [&'static str; 4] = [
+        "GreaterThanOrEqualToThreshold",
+        "GreaterThanThreshold",
+        "LessThanThreshold",
+        "LessThanOrEqualToThreshold",
+    ];
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "GreaterThanOrEqualToThreshold" => Some(Comparison::GreaterThanOrEqual),
+            "GreaterThanThreshold" => Some(Comparison::GreaterThan),
+            "LessThanThreshold" => Some(Comparison::LessThan),
+            "LessThanOrEqualToThreshold" => Some(Comparison::LessThanOrEqual),
+            _ => None,
+        }
+    }
+
+    pub fn holds(self, value: f64, threshold: f64) -> bool {
+        match self {
+            Comparison::GreaterThanOrEqual => value >= threshold,
+            Comparison::GreaterThan => value > threshold,
+            Comparison::LessThan => value < threshold,
+            Comparison::LessThanOrEqual => value <= threshold,
+        }
+    }
+
+    fn symbol(self) -> &'static str {
+        match self {
+            Comparison::GreaterThanOrEqual => ">=",
+            Comparison::GreaterThan => ">",
+            Comparison::LessThan => "<",
+            Comparison::LessThanOrEqual => "<=",
+        }
+    }
+}
+
+/// Name under which the metric a rule watches is recorded in `metrics`.
+pub fn metric_name(rule: &AutoscalingRule) -> Option<String> {
+    let name = match rule.metric_type.as_str() {
+        "cpu" => "cpu_usage",
+        "memory" => "memory_usage",
+        "http_throughput" => "http_requests_per_second",
+        "http_latency" => "http_latency_ms",
+        "queue_depth" => "queue_depth",
+        "custom" => return rule.custom_metric_name.clone().filter(|name| !name.is_empty()),
+        _ => return None,
+    };
+    Some(name.to_string())
+}
+
+/// Outcome of comparing a rule with the metric averages of its periods.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Verdict {
+    /// Some period has no samples, so the rule cannot be judged
+    InsufficientData { missing_periods: usize },
+    /// Not every period crossed the threshold
+    NotBreached { reason: String },
+    /// Every period crossed the threshold; the app should run `desired` instances
+    Breached { desired: i64, reason: String },
+    /// The threshold was crossed but the app is already at its limit
+    AtLimit { reason: String },
+}
+
+/// Judges a rule given the average of its metric in each of its evaluation
+/// periods, newest first, and the application's current instance count.
+///
+/// The rule fires only if the comparison holds in every period. It then
+/// moves the instance count to `target_instances` if set, or by
+/// `scaling_adjustment` otherwise, within `min_instances..=max_instances`.
+pub fn evaluate(rule: &AutoscalingRule, observed: &[Option<f64>], current: i64) -> Verdict {
+    let comparison = rule
+        .comparison_operator
+        .as_deref()
+        .and_then(Comparison::parse)
+        .unwrap_or(Comparison::GreaterThanOrEqual);
+
+    let missing_periods = observed.iter().filter(|value| value.is_none()).count();
+    if observed.is_empty() || missing_periods > 0 {
+        return Verdict::InsufficientData { missing_periods: missing_periods.max(1) };
+    }
+
+    let values: Vec<f64> = observed.iter().flatten().copied().collect();
+    let described = values.iter().map(|value| format!("{:.2}", value)).collect::<Vec<_>>().join(", ");
+    let condition = format!("{} {}", comparison.symbol(), rule.threshold_value);
+    if !values.iter().all(|value| comparison.holds(*value, rule.threshold_value)) {
+        return Verdict::NotBreached {
+            reason: format!("not every period was {} (observed {})", condition, described),
+        };
+    }
+
+    let proposed = match rule.target_instances {
+        Some(target) => target,
+        None => current + rule.scaling_adjustment.unwrap_or(1),
+    };
+    let desired = proposed.clamp(rule.min_instances, rule.max_instances.max(rule.min_instances));
+    let reason = format!("{} for {} period(s) (observed {})", condition, values.len(), described);
+
+    if desired == current {
+        Verdict::AtLimit {
+            reason: format!(
+                "{}, but the app already runs {} instances (allowed {} to {})",
+                reason, current, rule.min_instances, rule.max_instances
+            ),
+        }
+    } else {
+        Verdict::Breached { desired, reason }
+    }
+}
@@ -0,0 +1,50 @@
+//! Queue of builds run by builders.
+//!
+//! Builds waiting in `pending` are leased by builders through the API: a
+//! builder receives the oldest build that its application's
+//! `max_concurrent_builds` and its organization's `concurrent_builds_limit`
+//! allow to start, keeps the lease alive with heartbeats and reports the
+//! result when done. If a builder stops sending heartbeats its lease
+//! expires, and the leader puts the build back in the queue until it has
+//! been attempted `max_attempts` times.
+
+use crate::config::BuildsConfig;
+use crate::schemas::v1::db::queries as db;
+use crate::DatabaseManager;
+
+/// Requeues or fails the builds of every platform whose lease expired.
+pub async fn expire_leases_all_platforms(db_manager: &DatabaseManager, config: &BuildsConfig) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for build leases: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for build leases: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        match db::build_queue::expire_build_leases(&pool, platform_id, config.max_attempts).await {
+            Ok((requeued, failed)) if requeued > 0 || failed > 0 => {
+                log::warn!(
+                    "Build leases expired on platform {}: {} builds requeued, {} failed",
+                    platform_id, requeued, failed
+                );
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to expire build leases of platform {}: {:#}", platform_id, e),
+        }
+    }
+}
@@ -23,6 +23,668 @@ pub struct ServerConfig {
     
     /// List of other server instances in the cluster
     pub instances: Vec<Instance>,
+
+    /// Email addresses of system administrators.
+    ///
+    /// System administrators pass every permission check on every platform
+    /// and are the only users allowed to call routes that are not scoped to
+    /// a platform (such as creating platforms). This is how the first
+    /// administrator is bootstrapped before any roles have been assigned.
+    #[serde(default)]
+    pub admin_emails: Vec<String>,
+
+    /// OpenID Connect single sign-on settings.
+    ///
+    /// When absent, users can only sign in with email and password.
+    #[serde(default)]
+    pub oidc: Option<OidcConfig>,
+
+    /// Audit log export and archiving settings
+    #[serde(default)]
+    pub audit: AuditConfig,
+
+    /// Container runtime instances are executed on
+    #[serde(default)]
+    pub runtime: RuntimeConfig,
+
+    /// Desired-state reconciliation of applications and instances
+    #[serde(default)]
+    pub reconciler: ReconcilerConfig,
+
+    /// Placement of instances on workers
+    #[serde(default)]
+    pub scheduler: SchedulerConfig,
+
+    /// Worker agents and detection of workers that stopped reporting
+    #[serde(default)]
+    pub workers: WorkersConfig,
+
+    /// Rule-based autoscaling of applications
+    #[serde(default)]
+    pub autoscaler: AutoscalerConfig,
+
+    /// Execution of deployments
+    #[serde(default)]
+    pub deployments: DeploymentsConfig,
+
+    /// Storage and retention of release artifacts
+    #[serde(default)]
+    pub artifacts: ArtifactsConfig,
+
+    /// Queue of builds run by builders
+    #[serde(default)]
+    pub builds: BuildsConfig,
+
+    /// Execution of pipeline runs
+    #[serde(default)]
+    pub pipelines: PipelinesConfig,
+
+    /// Execution of one-off and scheduled tasks
+    #[serde(default)]
+    pub tasks: TasksConfig,
+}
+
+/// Configuration of audit log exports and retention archiving.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    /// Secret used to sign exports and archive files with HMAC-SHA256.
+    ///
+    /// Exports are refused while no key is configured, and archive files
+    /// are written unsigned.
+    #[serde(default)]
+    pub signing_key: Option<String>,
+
+    /// Directory archive files are staged in, one subdirectory per
+    /// platform, before they are moved to the artifact store.
+    #[serde(default = "default_audit_archive_dir")]
+    pub archive_dir: String,
+
+    /// Seconds between runs of the archiver
+    #[serde(default = "default_audit_archive_interval")]
+    pub archive_interval_seconds: u64,
+}
+
+impl Default for AuditConfig {
+    fn default() -> Self {
+        Self {
+            signing_key: None,
+            archive_dir: default_audit_archive_dir(),
+            archive_interval_seconds: default_audit_archive_interval(),
+        }
+    }
+}
+
+fn default_audit_archive_dir() -> String {
+    "audit_archive".to_string()
+}
+
+fn default_audit_archive_interval() -> u64 {
+    3600
+}
+
+/// Configuration of the container runtime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeConfig {
+    /// Runtime driver used to run instances: `docker` for the Docker
+    /// Engine, or `fake`, which keeps containers in memory without running
+    /// anything.
+    #[serde(default = "default_runtime_driver")]
+    pub driver: String,
+
+    /// Unix socket of the Docker Engine API
+    #[serde(default = "default_docker_socket")]
+    pub docker_socket: String,
+
+    /// Seconds a container is given to exit after being asked to stop,
+    /// before it is killed
+    #[serde(default = "default_stop_timeout")]
+    pub stop_timeout_seconds: u64,
+}
+
+impl Default for RuntimeConfig {
+    fn default() -> Self {
+        Self {
+            driver: default_runtime_driver(),
+            docker_socket: default_docker_socket(),
+            stop_timeout_seconds: default_stop_timeout(),
+        }
+    }
+}
+
+fn default_runtime_driver() -> String {
+    "fake".to_string()
+}
+
+fn default_docker_socket() -> String {
+    "/var/run/docker.sock".to_string()
+}
+
+fn default_stop_timeout() -> u64 {
+    10
+}
+
+/// Configuration of the reconciler, which runs on the cluster leader and
+/// converges instances towards the desired state of their applications.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconcilerConfig {
+    /// Whether the reconciler runs at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Seconds between reconciliation passes
+    #[serde(default = "default_reconcile_interval")]
+    pub interval_seconds: u64,
+
+    /// Seconds to wait before the first restart of a crashed instance. The
+    /// delay doubles with every further restart.
+    #[serde(default = "default_restart_backoff")]
+    pub restart_backoff_seconds: u64,
+
+    /// Upper bound of the delay between restarts of a crashed instance
+    #[serde(default = "default_max_restart_backoff")]
+    pub max_restart_backoff_seconds: u64,
+}
+
+impl Default for ReconcilerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_seconds: default_reconcile_interval(),
+            restart_backoff_seconds: default_restart_backoff(),
+            max_restart_backoff_seconds: default_max_restart_backoff(),
+        }
+    }
+}
+
+fn default_reconcile_interval() -> u64 {
+    15
+}
+
+fn default_restart_backoff() -> u64 {
+    10
+}
+
+fn default_max_restart_backoff() -> u64 {
+    300
+}
+
+/// Configuration of the scheduler, which places new instances on workers.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SchedulerConfig {
+    /// How workers are scored when an application does not choose:
+    /// `binpack` fills up the busiest workers first, `spread` prefers the
+    /// least loaded ones
+    #[serde(default = "default_scheduler_strategy")]
+    pub strategy: String,
+
+    /// CPU cores reserved for instances of applications without an allocation
+    #[serde(default = "default_instance_cpu")]
+    pub default_cpu: f64,
+
+    /// Memory in MB reserved for instances of applications without an allocation
+    #[serde(default = "default_instance_memory")]
+    pub default_memory_mb: f64,
+
+    /// Disk space in MB reserved for instances of applications without an allocation
+    #[serde(default = "default_instance_disk")]
+    pub default_disk_mb: f64,
+}
+
+impl Default for SchedulerConfig {
+    fn default() -> Self {
+        Self {
+            strategy: default_scheduler_strategy(),
+            default_cpu: default_instance_cpu(),
+            default_memory_mb: default_instance_memory(),
+            default_disk_mb: default_instance_disk(),
+        }
+    }
+}
+
+fn default_scheduler_strategy() -> String {
+    "binpack".to_string()
+}
+
+fn default_instance_cpu() -> f64 {
+    0.25
+}
+
+fn default_instance_memory() -> f64 {
+    256.0
+}
+
+fn default_instance_disk() -> f64 {
+    1024.0
+}
+
+/// Configuration of the worker agent protocol.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkersConfig {
+    /// How often agents are told to send a heartbeat, in seconds
+    #[serde(default = "default_heartbeat_interval")]
+    pub heartbeat_interval_seconds: u64,
+
+    /// Seconds without a heartbeat after which a worker is marked unreachable
+    #[serde(default = "default_unreachable_after")]
+    pub unreachable_after_seconds: u64,
+
+    /// How often the leader checks for unreachable and fully drained workers, in seconds
+    #[serde(default = "default_worker_monitor_interval")]
+    pub monitor_interval_seconds: u64,
+}
+
+impl Default for WorkersConfig {
+    fn default() -> Self {
+        Self {
+            heartbeat_interval_seconds: default_heartbeat_interval(),
+            unreachable_after_seconds: default_unreachable_after(),
+            monitor_interval_seconds: default_worker_monitor_interval(),
+        }
+    }
+}
+
+fn default_heartbeat_interval() -> u64 {
+    10
+}
+
+fn default_unreachable_after() -> u64 {
+    60
+}
+
+fn default_worker_monitor_interval() -> u64 {
+    15
+}
+
+/// Configuration of the autoscaler.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AutoscalerConfig {
+    /// Whether the leader evaluates autoscaling rules
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// How often the leader looks for rules that are due, in seconds. Each
+    /// rule is still evaluated at most once per its own period.
+    #[serde(default = "default_autoscaler_interval")]
+    pub interval_seconds: u64,
+
+    /// Days autoscaling decisions are kept; 0 keeps them forever
+    #[serde(default = "default_decision_retention")]
+    pub decision_retention_days: u32,
+}
+
+impl Default for AutoscalerConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_seconds: default_autoscaler_interval(),
+            decision_retention_days: default_decision_retention(),
+        }
+    }
+}
+
+fn default_autoscaler_interval() -> u64 {
+    15
+}
+
+fn default_decision_retention() -> u32 {
+    30
+}
+
+/// Configuration of the deployment executor.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentsConfig {
+    /// Whether the leader executes deployments
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// How often deployments in progress are advanced by a step, in seconds
+    #[serde(default = "default_deployments_interval")]
+    pub interval_seconds: u64,
+
+    /// Share of an application's instances a rolling deployment replaces
+    /// per step, in percent
+    #[serde(default = "default_rolling_batch_percentage")]
+    pub rolling_batch_percentage: u32,
+
+    /// Seconds a new instance has to start before its deployment fails
+    #[serde(default = "default_instance_ready_timeout")]
+    pub instance_ready_timeout_seconds: u64,
+
+    /// Whether deployments are rolled back automatically when their
+    /// instances fail health checks or their error rate is too high during
+    /// the bake window
+    #[serde(default = "default_true")]
+    pub automatic_rollback: bool,
+
+    /// Seconds after a deployment completes during which it is watched for
+    /// automatic rollback
+    #[serde(default = "default_bake_window")]
+    pub bake_window_seconds: u64,
+
+    /// Metric holding an application's error rate, in percent
+    #[serde(default = "default_error_rate_metric")]
+    pub error_rate_metric: String,
+
+    /// Error rate in percent above which a baking deployment is rolled
+    /// back; 0 disables the check
+    #[serde(default = "default_max_error_rate")]
+    pub max_error_rate: f64,
+}
+
+impl Default for DeploymentsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_seconds: default_deployments_interval(),
+            rolling_batch_percentage: default_rolling_batch_percentage(),
+            instance_ready_timeout_seconds: default_instance_ready_timeout(),
+            automatic_rollback: true,
+            bake_window_seconds: default_bake_window(),
+            error_rate_metric: default_error_rate_metric(),
+            max_error_rate: default_max_error_rate(),
+        }
+    }
+}
+
+fn default_deployments_interval() -> u64 {
+    5
+}
+
+fn default_rolling_batch_percentage() -> u32 {
+    25
+}
+
+fn default_instance_ready_timeout() -> u64 {
+    300
+}
+
+fn default_bake_window() -> u64 {
+    600
+}
+
+fn default_error_rate_metric() -> String {
+    "http_error_rate".to_string()
+}
+
+fn default_max_error_rate() -> f64 {
+    5.0
+}
+
+/// Configuration of the release artifact store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactsConfig {
+    /// Where artifacts are stored: "local" or "s3"
+    #[serde(default = "default_artifacts_backend")]
+    pub backend: String,
+
+    /// Directory the local backend stores artifacts in
+    #[serde(default = "default_artifacts_dir")]
+    pub local_dir: String,
+
+    /// Bucket the s3 backend stores artifacts in
+    #[serde(default)]
+    pub s3: Option<S3ArtifactsConfig>,
+
+    /// Whether the leader deletes artifacts past their application's
+    /// retention policy
+    #[serde(default = "default_true")]
+    pub retention_enabled: bool,
+
+    /// How often retention policies are applied, in seconds
+    #[serde(default = "default_retention_interval")]
+    pub retention_interval_seconds: u64,
+}
+
+impl Default for ArtifactsConfig {
+    fn default() -> Self {
+        Self {
+            backend: default_artifacts_backend(),
+            local_dir: default_artifacts_dir(),
+            s3: None,
+            retention_enabled: true,
+            retention_interval_seconds: default_retention_interval(),
+        }
+    }
+}
+
+/// Bucket of an S3-compatible object store such as AWS S3 or MinIO.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct S3ArtifactsConfig {
+    /// Base URL of the object store, e.g. "https://s3.us-east-1.amazonaws.com"
+    /// or "http://localhost:9000"
+    pub endpoint: String,
+
+    pub bucket: String,
+
+    #[serde(default = "default_s3_region")]
+    pub region: String,
+
+    /// Access key; taken from AWS_ACCESS_KEY_ID when unset
+    #[serde(default)]
+    pub access_key_id: Option<String>,
+
+    /// Secret key; taken from AWS_SECRET_ACCESS_KEY when unset
+    #[serde(default)]
+    pub secret_access_key: Option<String>,
+
+    /// Whether the bucket is addressed in the path rather than the host
+    /// name, as MinIO expects
+    #[serde(default = "default_true")]
+    pub path_style: bool,
+}
+
+fn default_artifacts_backend() -> String {
+    "local".to_string()
+}
+
+fn default_artifacts_dir() -> String {
+    "artifacts".to_string()
+}
+
+fn default_retention_interval() -> u64 {
+    3600
+}
+
+fn default_s3_region() -> String {
+    "us-east-1".to_string()
+}
+
+/// Configuration of the build queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildsConfig {
+    /// Whether the leader requeues builds whose builder stopped sending
+    /// heartbeats
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// How often the leader looks for expired build leases, in seconds
+    #[serde(default = "default_builds_interval")]
+    pub interval_seconds: u64,
+
+    /// Seconds a lease lasts without a heartbeat from its builder
+    #[serde(default = "default_lease_duration")]
+    pub lease_seconds: u64,
+
+    /// Times a build is leased before a lost lease or retryable failure
+    /// fails it
+    #[serde(default = "default_max_build_attempts")]
+    pub max_attempts: u32,
+}
+
+impl Default for BuildsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_seconds: default_builds_interval(),
+            lease_seconds: default_lease_duration(),
+            max_attempts: default_max_build_attempts(),
+        }
+    }
+}
+
+fn default_builds_interval() -> u64 {
+    15
+}
+
+fn default_lease_duration() -> u64 {
+    120
+}
+
+fn default_max_build_attempts() -> u32 {
+    3
+}
+
+/// Configuration of the pipeline runner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelinesConfig {
+    /// Whether the leader executes pipeline runs
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// How often runs in progress are advanced, in seconds
+    #[serde(default = "default_pipelines_interval")]
+    pub interval_seconds: u64,
+}
+
+impl Default for PipelinesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_seconds: default_pipelines_interval(),
+        }
+    }
+}
+
+fn default_pipelines_interval() -> u64 {
+    10
+}
+
+/// Configuration of the task runner.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TasksConfig {
+    /// Whether the leader launches tasks and fires task schedules
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// How often tasks are launched, checked on and timed out, in seconds
+    #[serde(default = "default_tasks_interval")]
+    pub interval_seconds: u64,
+
+    /// Most lines of output kept per task; later lines are dropped
+    #[serde(default = "default_tasks_max_output_lines")]
+    pub max_output_lines: u64,
+
+    /// Seconds a stopped task container is given to exit before it is
+    /// killed
+    #[serde(default = "default_tasks_stop_timeout")]
+    pub stop_timeout_seconds: u64,
+}
+
+impl Default for TasksConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_seconds: default_tasks_interval(),
+            max_output_lines: default_tasks_max_output_lines(),
+            stop_timeout_seconds: default_tasks_stop_timeout(),
+        }
+    }
+}
+
+fn default_tasks_interval() -> u64 {
+    5
+}
+
+fn default_tasks_max_output_lines() -> u64 {
+    10_000
+}
+
+fn default_tasks_stop_timeout() -> u64 {
+    10
+}
+
+/// Configuration of the OpenID Connect identity provider used for single
+/// sign-on.
+///
+/// The server acts as a relying party using the authorization code flow
+/// with PKCE. Provider endpoints and signing keys are discovered from
+/// `<issuer>/.well-known/openid-configuration`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcConfig {
+    /// Issuer URL of the identity provider, as it appears in the `iss` claim
+    pub issuer: String,
+
+    /// Client ID registered with the identity provider
+    pub client_id: String,
+
+    /// Client secret, if the client is confidential
+    #[serde(default)]
+    pub client_secret: Option<String>,
+
+    /// Callback URL registered with the identity provider; it must point at
+    /// `/api/v1/auth/oidc/callback` on this server
+    pub redirect_uri: String,
+
+    /// Scopes requested during login
+    #[serde(default = "default_oidc_scopes")]
+    pub scopes: Vec<String>,
+
+    /// Name of the ID token claim listing the user's groups
+    #[serde(default = "default_oidc_groups_claim")]
+    pub groups_claim: String,
+
+    /// Whether users signing in for the first time are created automatically
+    #[serde(default = "default_true")]
+    pub auto_provision: bool,
+
+    /// Allowed clock skew, in seconds, when checking token lifetimes
+    #[serde(default = "default_oidc_leeway")]
+    pub leeway_seconds: u64,
+
+    /// Mapping of identity provider groups to organization memberships
+    #[serde(default)]
+    pub group_mappings: Vec<OidcGroupMapping>,
+
+    /// Whether memberships of mapped organizations are removed when the user
+    /// is no longer in any group mapped to them
+    #[serde(default)]
+    pub remove_unmapped_members: bool,
+}
+
+/// Grants membership of an organization to members of an identity provider
+/// group.
+///
+/// When a user matches several mappings for the same organization, the most
+/// privileged role wins.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OidcGroupMapping {
+    /// Group name as it appears in the groups claim
+    pub group: String,
+
+    /// Platform the organization belongs to
+    pub platform_id: i64,
+
+    /// Organization to join
+    pub org_id: i64,
+
+    /// Membership role: `owner`, `admin`, `billing`, `member` or `guest`
+    pub role: String,
+}
+
+fn default_oidc_scopes() -> Vec<String> {
+    vec!["openid".to_string(), "email".to_string(), "profile".to_string()]
+}
+
+fn default_oidc_groups_claim() -> String {
+    "groups".to_string()
+}
+
+fn default_oidc_leeway() -> u64 {
+    60
+}
+
+fn default_true() -> bool {
+    true
 }
 
 /// Represents an instance of the server in the cluster.
@@ -54,6 +716,19 @@ impl Default for ServerConfig {
                 port: 8000,
                 address: "example.com".to_string(),
             }],
+            admin_emails: Vec::new(),
+            oidc: None,
+            audit: AuditConfig::default(),
+            runtime: RuntimeConfig::default(),
+            reconciler: ReconcilerConfig::default(),
+            scheduler: SchedulerConfig::default(),
+            workers: WorkersConfig::default(),
+            autoscaler: AutoscalerConfig::default(),
+            deployments: DeploymentsConfig::default(),
+            artifacts: ArtifactsConfig::default(),
+            builds: BuildsConfig::default(),
+            pipelines: PipelinesConfig::default(),
+            tasks: TasksConfig::default(),
         }
     }
 }
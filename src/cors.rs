@@ -19,7 +19,11 @@ impl Fairing for CORS {
         ));
         response.set_header(Header::new(
             "Access-Control-Allow-Headers",
-            "Authorization, Content-Type, Accept, Origin, X-Requested-With",
+            "Authorization, Content-Type, Accept, Origin, X-Requested-With, X-Request-Id",
+        ));
+        response.set_header(Header::new(
+            "Access-Control-Expose-Headers",
+            "X-Request-Id, Content-Disposition, X-Audit-Content-SHA256, X-Audit-Signature, X-Audit-Signature-Algorithm",
         ));
         response.set_header(Header::new("Access-Control-Allow-Credentials", "true"));
         response.set_header(Header::new("Access-Control-Max-Age", "86400"));
@@ -0,0 +1,119 @@
+//! Deployment of builds queued by repository pushes once they succeed,
+//! and of builds carried through pipelines.
+
+use chrono::Utc;
+use libomni::types::db::v1 as types;
+use sqlx::{MySql, Pool};
+use types::build::Build;
+use types::deployment::Deployment;
+
+use super::freeze;
+use crate::schemas::v1::db::queries as db;
+use db::deployment::DeploymentGates;
+
+/// What became of a succeeded build that was to be deployed automatically.
+#[derive(Debug, Clone)]
+pub enum AutoDeployOutcome {
+    /// The build was not queued for an automatic deployment.
+    NotRequested,
+    /// Deployments of the application are frozen by the named window.
+    Frozen(String),
+    /// The build is being deployed, once approved if a policy requires it.
+    Created(Deployment),
+}
+
+/// Deploys a succeeded build if it was queued with an automatic deployment.
+///
+/// Freeze windows are never overridden: a build that succeeds during a
+/// freeze is left to be deployed by hand. Approval policies apply as they
+/// do to deployments created through the API. The outcome is noted in the
+/// build's log.
+pub async fn deploy_succeeded_build(pool: &Pool<MySql>, build: &Build) -> anyhow::Result<AutoDeployOutcome> {
+    let strategy = match db::build::get_build_auto_deploy_strategy(pool, build.id).await? {
+        Some(strategy) => strategy,
+        None => return Ok(AutoDeployOutcome::NotRequested),
+    };
+
+    let (deployment, required_approvals) = match deploy_build(pool, build, &strategy, None).await? {
+        BuildDeployment::Frozen(window) => {
+            let message = format!("Not deployed automatically: deployments are frozen by '{}'", window);
+            note(pool, build.id, "warn", &message).await;
+            return Ok(AutoDeployOutcome::Frozen(window));
+        }
+        BuildDeployment::Created { deployment, required_approvals } => (deployment, required_approvals),
+    };
+
+    let message = if required_approvals > 0 {
+        format!(
+            "Created {} deployment {}; it starts once it has {} approval(s)",
+            strategy, deployment.id, required_approvals
+        )
+    } else {
+        format!("Created {} deployment {}", strategy, deployment.id)
+    };
+    note(pool, build.id, "info", &message).await;
+
+    Ok(AutoDeployOutcome::Created(deployment))
+}
+
+/// Result of deploying a build without overriding freeze windows.
+#[derive(Debug, Clone)]
+pub enum BuildDeployment {
+    /// Deployments of the application are frozen by the named window.
+    Frozen(String),
+    /// The build is being deployed, once it has `required_approvals`
+    /// approvals if a policy requires them.
+    Created { deployment: Deployment, required_approvals: i64 },
+}
+
+/// Deploys a succeeded build to the application it belongs to, unless a
+/// freeze window is active. The application's approval policy applies.
+pub async fn deploy_build(
+    pool: &Pool<MySql>,
+    build: &Build,
+    strategy: &str,
+    created_by: Option<i64>,
+) -> anyhow::Result<BuildDeployment> {
+    let windows = db::freeze_window::list_app_freeze_windows(pool, build.app_id).await?;
+    if let Some(window) = freeze::active_window(&windows, Utc::now()) {
+        return Ok(BuildDeployment::Frozen(window.name.clone()));
+    }
+
+    let gates = match db::deployment_approval::resolve_approval_policy(pool, build.app_id).await? {
+        Some(policy) if policy.enabled != Some(false) => DeploymentGates {
+            required_approvals: policy.required_approvals,
+            approver_role_id: policy.approver_role_id,
+            freeze_override_reason: None,
+        },
+        _ => DeploymentGates::default(),
+    };
+    let previous = db::deployment::get_current_deployment(pool, build.app_id).await?;
+    let version = build
+        .source_version
+        .clone()
+        .unwrap_or_else(|| format!("build-{}", build.id));
+
+    let deployment = db::deployment::create_deployment(
+        pool,
+        build.app_id,
+        build.id,
+        &version,
+        strategy,
+        previous.map(|previous| previous.id),
+        None,
+        None,
+        None,
+        None,
+        &gates,
+        created_by,
+    )
+    .await?;
+
+    Ok(BuildDeployment::Created { deployment, required_approvals: gates.required_approvals })
+}
+
+async fn note(pool: &Pool<MySql>, build_id: i64, level: &str, message: &str) {
+    if let Err(e) = db::build_log::record_system_line(pool, build_id, level, message).await {
+        log::warn!("Failed to record auto deploy of build {}: {:#}", build_id, e);
+    }
+}
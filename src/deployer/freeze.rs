@@ -0,0 +1,43 @@
+//! Evaluation of deployment freeze windows.
+
+use chrono::{DateTime, Datelike, NaiveTime, Timelike, Utc};
+
+use crate::schemas::v1::db::queries::freeze_window::FreezeWindow;
+
+/// Checks whether a freeze window is in effect at `now`.
+pub fn is_active(window: &FreezeWindow, now: DateTime<Utc>) -> bool {
+    if window.enabled == Some(false) {
+        return false;
+    }
+
+    match window.recurrence.as_str() {
+        "once" => match (window.starts_at, window.ends_at) {
+            (Some(starts_at), Some(ends_at)) => starts_at <= now && now < ends_at,
+            _ => false,
+        },
+        "weekly" => match (window.start_day, window.start_time, window.end_day, window.end_time) {
+            (Some(start_day), Some(start_time), Some(end_day), Some(end_time)) => {
+                let start = minute_of_week(start_day, start_time);
+                let end = minute_of_week(end_day, end_time);
+                let current = minute_of_week(now.weekday().num_days_from_monday() as i8, now.time());
+                if start <= end {
+                    start <= current && current < end
+                } else {
+                    // Wraps around the end of the week
+                    current >= start || current < end
+                }
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+/// Returns the first of `windows` in effect at `now`.
+pub fn active_window(windows: &[FreezeWindow], now: DateTime<Utc>) -> Option<&FreezeWindow> {
+    windows.iter().find(|window| is_active(window, now))
+}
+
+fn minute_of_week(day: i8, time: NaiveTime) -> i64 {
+    i64::from(day) * 24 * 60 + i64::from(time.hour() * 60 + time.minute())
+}
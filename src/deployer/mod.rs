@@ -0,0 +1,394 @@
+//! Execution of deployments.
+//!
+//! A deployment replaces the instances of an application with instances
+//! running a new release. The leader picks up the oldest pending deployment
+//! of each application and moves it through the steps of its strategy:
+//! rolling deployments replace instances a batch at a time, blue-green
+//! deployments start a complete new set before stopping the old one,
+//! canary deployments replace a share of the instances and wait to be
+//! promoted, and recreate deployments stop everything before starting the
+//! new release.
+//!
+//! The executor only decides which instances should exist. It creates
+//! instances tagged with the deployment and marks old ones 'stopping'; the
+//! [reconciler](crate::reconciler) launches and terminates their containers
+//! as usual. A new instance that crashes or does not start in time fails
+//! the deployment. Failed and aborted deployments stop their new instances,
+//! and the reconciler brings the application back to its size with the
+//! previous release.
+//!
+//! Progress is kept in the deployment's `phase`, `staged_instances` and
+//! `total_instances` columns, and every step is written to its timeline in
+//! `deployment_logs`.
+//!
+//! Deployments that need approval stay pending until enough approvers
+//! agreed, and block the later deployments of their application meanwhile.
+//! [Freeze windows](freeze) are enforced when a deployment is created.
+//! Builds queued by a repository push can be
+//! [deployed automatically](auto_deploy) once they succeed.
+//!
+//! Completed deployments are watched during a bake window. If one of their
+//! instances crashes or fails its health check, or the application's error
+//! rate exceeds the configured limit, the deployment is
+//! [rolled back](rollback) by deploying the release of the previous
+//! deployment again.
+
+pub mod auto_deploy;
+pub mod freeze;
+pub mod rollback;
+pub mod strategy;
+
+use anyhow::Context;
+use chrono::{Duration, Utc};
+use libomni::types::db::v1 as types;
+use serde_json::json;
+use sqlx::{MySql, Pool};
+use types::deployment::Deployment;
+
+use crate::config::DeploymentsConfig;
+use crate::schemas::v1::db::queries as db;
+use crate::DatabaseManager;
+use db::deployment::DeploymentInstance;
+use db::deployment_log::NewDeploymentEvent;
+use strategy::{Snapshot, Step, Strategy};
+
+/// What one pass of the executor did to pending and in-progress
+/// deployments, logged when anything changed.
+#[derive(Debug, Default, Clone)]
+pub struct ExecutionSummary {
+    pub started: usize,
+    pub instances_created: usize,
+    pub instances_stopped: usize,
+    pub completed: usize,
+    pub failed: usize,
+    pub canceled: usize,
+    pub rolled_back: usize,
+    pub failures: usize,
+}
+
+impl ExecutionSummary {
+    fn changed(&self) -> bool {
+        self.started + self.instances_created + self.instances_stopped + self.completed + self.failed + self.canceled + self.rolled_back + self.failures > 0
+    }
+}
+
+/// Advances the deployments of every platform once.
+pub async fn execute_all_platforms(db_manager: &DatabaseManager, config: &DeploymentsConfig) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for deployments: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for deployments: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        match execute_platform(&pool, config).await {
+            Ok(Some(summary)) if summary.changed() => {
+                log::info!("Advanced deployments of platform {}: {:?}", platform_id, summary);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to advance deployments of platform {}: {:#}", platform_id, e),
+        }
+    }
+}
+
+/// Advances every deployment of a platform by one step. Returns `None` if
+/// another node is executing the platform's deployments.
+pub async fn execute_platform(pool: &Pool<MySql>, config: &DeploymentsConfig) -> anyhow::Result<Option<ExecutionSummary>> {
+    // Named locks are server wide, so the lock name includes the database
+    let mut lock_conn = pool.acquire().await?;
+    let locked = sqlx::query_scalar::<_, Option<i64>>("SELECT GET_LOCK(CONCAT('omni_deployer:', DATABASE()), 0)")
+        .fetch_one(&mut *lock_conn)
+        .await
+        .context("Failed to acquire deployment executor lock")?;
+    if locked != Some(1) {
+        return Ok(None);
+    }
+
+    let mut summary = ExecutionSummary::default();
+    let result = match db::deployment::list_deployments_to_execute(pool).await {
+        Ok(deployments) => {
+            for deployment in &deployments {
+                if let Err(e) = advance(pool, deployment, config, &mut summary).await {
+                    summary.failures += 1;
+                    log::error!("Failed to advance deployment {}: {:#}", deployment.id, e);
+                }
+            }
+            match rollback::check_baking_deployments(pool, config, &mut summary).await {
+                Ok(()) => Ok(Some(summary)),
+                Err(e) => Err(e),
+            }
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = sqlx::query("SELECT RELEASE_LOCK(CONCAT('omni_deployer:', DATABASE()))")
+        .execute(&mut *lock_conn)
+        .await
+    {
+        log::warn!("Failed to release deployment executor lock: {}", e);
+    }
+
+    result
+}
+
+/// Takes the next step of a deployment while holding its application's
+/// control lock, so it never races a start, stop or scale of the
+/// application. Applications that are busy are retried on the next pass.
+async fn advance(
+    pool: &Pool<MySql>,
+    deployment: &Deployment,
+    config: &DeploymentsConfig,
+    summary: &mut ExecutionSummary,
+) -> anyhow::Result<()> {
+    let lock = match db::app::lock_app_control(pool, deployment.app_id, 0).await? {
+        Some(lock) => lock,
+        None => return Ok(()),
+    };
+
+    let result = Executor { pool, deployment, config, summary }.step().await;
+
+    db::app::unlock_app_control(lock, deployment.app_id).await;
+    result
+}
+
+struct Executor<'a> {
+    pool: &'a Pool<MySql>,
+    deployment: &'a Deployment,
+    config: &'a DeploymentsConfig,
+    summary: &'a mut ExecutionSummary,
+}
+
+impl Executor<'_> {
+    async fn step(&mut self) -> anyhow::Result<()> {
+        let id = self.deployment.id;
+        let app = db::app::get_app_by_id(self.pool, self.deployment.app_id).await?;
+        let running = app.deleted_at.is_none()
+            && matches!(app.status.as_deref(), Some("started" | "starting" | "crashed"));
+        let desired = if running { app.instances.unwrap_or(1).max(0) } else { 0 };
+
+        let strategy_name = self.deployment.deployment_strategy.as_deref().unwrap_or("rolling");
+        let strategy = Strategy::parse(strategy_name);
+
+        let mut progress = match db::deployment::get_deployment_progress(self.pool, id).await? {
+            Some(progress) => progress,
+            None => return Ok(()),
+        };
+
+        if progress.status.as_deref() == Some("pending") {
+            let current = db::deployment::get_current_deployment(self.pool, self.deployment.app_id).await?;
+            let previous = current.map(|current| current.id);
+            if !db::deployment::begin_deployment(self.pool, id, desired, previous).await? {
+                return Ok(());
+            }
+            self.summary.started += 1;
+            self.event("deployment_started", "info", format!(
+                "Started {} deployment of version {} to {} instance(s)",
+                strategy_name,
+                self.deployment.version.as_deref().unwrap_or("unknown"),
+                desired
+            ), None).await;
+
+            // A stopped application has nothing to replace; it runs the
+            // release the next time it is started
+            if !running {
+                return self.finish("deployed", None, "The application is not running; it runs this release when started").await;
+            }
+            progress = match db::deployment::get_deployment_progress(self.pool, id).await? {
+                Some(progress) => progress,
+                None => return Ok(()),
+            };
+        }
+
+        let instances = db::deployment::list_deployment_instances(self.pool, self.deployment.app_id).await?;
+        let (new, old): (Vec<&DeploymentInstance>, Vec<&DeploymentInstance>) =
+            instances.iter().partition(|instance| instance.deployment_id == Some(id));
+
+        if progress.abort_requested_at.is_some() {
+            return self.revert(&new, "canceled", "Deployment was aborted").await;
+        }
+        if !running {
+            return self.revert(&new, "canceled", "The application was stopped during the deployment").await;
+        }
+        let strategy = match strategy {
+            Some(strategy) => strategy,
+            None => {
+                let reason = format!("Unknown deployment strategy '{}'", strategy_name);
+                return self.revert(&new, "failed", &reason).await;
+            }
+        };
+        if progress.paused_at.is_some() {
+            return Ok(());
+        }
+
+        if let Some(failure) = self.failed_instance(&new) {
+            self.event(failure.event_type, "error", failure.reason.clone(),
+                Some(json!({ "instance_id": failure.instance_id }))).await;
+            return self.revert(&new, "failed", &failure.reason).await;
+        }
+
+        let is_status = |instance: &&&DeploymentInstance, status: &str| instance.status.as_deref() == Some(status);
+        let snapshot = Snapshot {
+            desired,
+            new_ready: new.iter().filter(|instance| is_status(instance, "running")).count() as i64,
+            new_pending: new.iter().filter(|instance| !is_status(instance, "running")).count() as i64,
+            old_live: old.iter().filter(|instance| !is_status(instance, "stopping")).count() as i64,
+            old_stopping: old.iter().filter(|instance| is_status(instance, "stopping")).count() as i64,
+            batch: strategy::batch_size(desired, self.config.rolling_batch_percentage),
+            canary: strategy::canary_size(desired, self.deployment.canary_percentage.unwrap_or(20)),
+            promoted: progress.promoted_at.is_some(),
+        };
+
+        if snapshot.new_ready > progress.staged_instances.unwrap_or(0) {
+            self.event("instances_ready", "info",
+                format!("{} of {} instance(s) of the new release are running", snapshot.new_ready, desired),
+                Some(json!({ "running": snapshot.new_ready, "desired": desired }))).await;
+        }
+
+        let step = strategy::next_step(strategy, &snapshot);
+        let phase_changed = progress.phase.as_deref() != Some(step.phase());
+        if phase_changed && step != Step::Complete {
+            let message = match &step {
+                Step::Wait { reason, .. } => format!("Entered phase {}: {}", step.phase(), reason),
+                _ => format!("Entered phase {}", step.phase()),
+            };
+            self.event("phase_changed", "info", message,
+                Some(json!({ "from": progress.phase, "to": step.phase() }))).await;
+        }
+
+        match &step {
+            Step::Create { count, .. } => {
+                for _ in 0..*count {
+                    db::instance::create_instance(self.pool, self.deployment.app_id, db::instance::DEFAULT_INSTANCE_TYPE, Some(id)).await?;
+                    self.summary.instances_created += 1;
+                }
+                self.event("instances_started", "info", format!("Starting {} instance(s) of the new release", count),
+                    Some(json!({ "phase": step.phase(), "running": snapshot.new_ready, "desired": desired }))).await;
+            }
+            Step::Stop { count, .. } => {
+                let stopping: Vec<i64> = old
+                    .iter()
+                    .rev()
+                    .filter(|instance| !is_status(instance, "stopping"))
+                    .take(*count as usize)
+                    .map(|instance| instance.id)
+                    .collect();
+                db::deployment::mark_instances_stopping(self.pool, &stopping).await?;
+                self.summary.instances_stopped += stopping.len();
+                self.event("instances_stopped", "info", format!("Stopping {} instance(s) of the previous release", stopping.len()),
+                    Some(json!({ "phase": step.phase(), "instance_ids": stopping }))).await;
+            }
+            Step::Wait { .. } => {}
+            Step::AwaitPromotion if phase_changed => {
+                self.event("awaiting_promotion", "info", format!(
+                    "{} canary instance(s) are running; waiting for the deployment to be promoted",
+                    snapshot.new_ready
+                ), None).await;
+            }
+            Step::AwaitPromotion => {}
+            Step::Complete => {
+                let message = format!("Deployment completed; {} instance(s) run the new release", snapshot.new_ready);
+                return self.finish("deployed", None, &message).await;
+            }
+        }
+
+        db::deployment::update_deployment_progress(self.pool, id, step.phase(), snapshot.new_ready, desired).await?;
+        Ok(())
+    }
+
+    /// Explains why the deployment has failed, if one of its instances
+    /// crashed, failed its health check or did not start in time.
+    fn failed_instance(&self, new: &[&DeploymentInstance]) -> Option<InstanceFailure> {
+        let deadline = Utc::now() - Duration::seconds(self.config.instance_ready_timeout_seconds as i64);
+        new.iter().find_map(|instance| {
+            let failure = |event_type, reason| Some(InstanceFailure { event_type, instance_id: instance.id, reason });
+            if instance.status.as_deref() == Some("crashed") || instance.restart_count.unwrap_or(0) > 0 {
+                return failure("instance_crashed", format!(
+                    "Instance {} of the new release crashed: {}",
+                    instance.instance_index,
+                    instance.exit_reason.as_deref().unwrap_or("unknown reason")
+                ));
+            }
+            if instance.health_status.as_deref() == Some("critical") {
+                return failure("health_check_failed",
+                    format!("Instance {} of the new release failed its health check", instance.instance_index));
+            }
+            let started = instance.status.as_deref() == Some("running");
+            match instance.created_at {
+                Some(created_at) if !started && created_at < deadline => failure("instance_ready_timeout", format!(
+                    "Instance {} of the new release did not start within {} seconds",
+                    instance.instance_index, self.config.instance_ready_timeout_seconds
+                )),
+                _ => None,
+            }
+        })
+    }
+
+    /// Ends the deployment as failed or canceled, stopping its instances.
+    /// The reconciler then restores the application with its previous
+    /// release.
+    async fn revert(&mut self, new: &[&DeploymentInstance], status: &str, reason: &str) -> anyhow::Result<()> {
+        let stopping: Vec<i64> = new
+            .iter()
+            .filter(|instance| instance.status.as_deref() != Some("stopping"))
+            .map(|instance| instance.id)
+            .collect();
+        db::deployment::mark_instances_stopping(self.pool, &stopping).await?;
+        self.summary.instances_stopped += stopping.len();
+
+        let message = format!("{}; stopping {} instance(s) of the new release", reason, stopping.len());
+        self.finish(status, Some(reason), &message).await
+    }
+
+    async fn finish(&mut self, status: &str, error_message: Option<&str>, message: &str) -> anyhow::Result<()> {
+        if !db::deployment::finish_deployment(self.pool, self.deployment.id, status, error_message).await? {
+            return Ok(());
+        }
+
+        let (event_type, level) = match status {
+            "deployed" => {
+                self.summary.completed += 1;
+                ("deployment_completed", "info")
+            }
+            "failed" => {
+                self.summary.failed += 1;
+                ("deployment_failed", "error")
+            }
+            _ => {
+                self.summary.canceled += 1;
+                ("deployment_canceled", "info")
+            }
+        };
+        self.event(event_type, level, message.to_string(), Some(json!({ "status": status }))).await;
+        Ok(())
+    }
+
+    async fn event(&self, event_type: &'static str, level: &'static str, message: String, metadata: Option<serde_json::Value>) {
+        let event = NewDeploymentEvent { deployment_id: self.deployment.id, event_type, level, message, metadata };
+        if let Err(e) = db::deployment_log::insert_deployment_event(self.pool, &event).await {
+            log::warn!("Failed to record {} event of deployment {}: {:#}", event_type, self.deployment.id, e);
+        }
+    }
+}
+
+/// A new instance that fails the deployment.
+struct InstanceFailure {
+    event_type: &'static str,
+    instance_id: i64,
+    reason: String,
+}
+
@@ -0,0 +1,92 @@
+use chrono::Utc;
+use libomni::types::db::v1 as types;
+use sqlx::{MySql, Pool};
+use types::deployment::Deployment;
+
+use super::ExecutionSummary;
+use crate::config::DeploymentsConfig;
+use crate::schemas::v1::api::deployments::rollback::{roll_back_deployment, RollbackOrigin};
+use crate::schemas::v1::db::queries as db;
+
+/// Why a baking deployment has to be rolled back.
+struct Trigger {
+    condition: &'static str,
+    reason: String,
+}
+
+/// Rolls back the deployments in their bake window whose instances fail or
+/// whose error rate is too high.
+pub async fn check_baking_deployments(
+    pool: &Pool<MySql>,
+    config: &DeploymentsConfig,
+    summary: &mut ExecutionSummary,
+) -> anyhow::Result<()> {
+    if !config.automatic_rollback || config.bake_window_seconds == 0 {
+        return Ok(());
+    }
+
+    for deployment in db::rollback::list_baking_deployments(pool, config.bake_window_seconds).await? {
+        let trigger = match trigger(pool, &deployment, config).await {
+            Ok(Some(trigger)) => trigger,
+            Ok(None) => continue,
+            Err(e) => {
+                summary.failures += 1;
+                log::error!("Failed to check deployment {} for rollback: {:#}", deployment.id, e);
+                continue;
+            }
+        };
+
+        let origin = RollbackOrigin {
+            reason: &trigger.reason,
+            automatic: true,
+            trigger_condition: Some(trigger.condition),
+            created_by: None,
+        };
+        match roll_back_deployment(pool, deployment.id, origin, 0).await {
+            Ok(Some(rollback)) => {
+                summary.rolled_back += 1;
+                log::warn!(
+                    "Rolling back deployment {} of app {} (rollback {}): {}",
+                    deployment.id, deployment.app_id, rollback.id, trigger.reason
+                );
+            }
+            // Retried on the next pass
+            Ok(None) => {}
+            Err((_, body)) => {
+                summary.failures += 1;
+                log::error!(
+                    "Failed to roll back deployment {}: {}",
+                    deployment.id,
+                    body["message"].as_str().unwrap_or("unknown error")
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+async fn trigger(pool: &Pool<MySql>, deployment: &Deployment, config: &DeploymentsConfig) -> anyhow::Result<Option<Trigger>> {
+    let failing = db::rollback::count_failing_instances(pool, deployment.app_id).await?;
+    if failing > 0 {
+        return Ok(Some(Trigger {
+            condition: "health_check_failed",
+            reason: format!("{} instance(s) crashed or failed their health check during the bake window", failing),
+        }));
+    }
+
+    let completed_at = match deployment.completed_at {
+        Some(completed_at) if config.max_error_rate > 0.0 => completed_at,
+        _ => return Ok(None),
+    };
+    let error_rate =
+        db::autoscaling::average_app_metric(pool, deployment.app_id, &config.error_rate_metric, completed_at, Utc::now()).await?;
+
+    Ok(error_rate.filter(|rate| *rate > config.max_error_rate).map(|rate| Trigger {
+        condition: "error_rate",
+        reason: format!(
+            "{} averaged {:.2}% since the deployment completed, above the limit of {:.2}%",
+            config.error_rate_metric, rate, config.max_error_rate
+        ),
+    }))
+}
@@ -0,0 +1,176 @@
+/// Names of the strategies, as stored in `deployments.deployment_strategy`.
+pub const STRATEGIES: [&str; 4] = ["rolling", "blue-green", "canary", "recreate"];
+
+/// How a deployment replaces the instances of the previous release.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Replace instances a batch at a time, starting new ones before
+    /// stopping old ones
+    Rolling,
+    /// Start a complete new set of instances, then stop the old set at once
+    BlueGreen,
+    /// Replace a share of the instances, wait to be promoted, then replace
+    /// the rest like a rolling deployment
+    Canary,
+    /// Stop every old instance before starting the new ones
+    Recreate,
+}
+
+impl Strategy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "rolling" => Some(Strategy::Rolling),
+            "blue-green" => Some(Strategy::BlueGreen),
+            "canary" => Some(Strategy::Canary),
+            "recreate" => Some(Strategy::Recreate),
+            _ => None,
+        }
+    }
+}
+
+/// The instances of an application as a deployment sees them.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Snapshot {
+    /// Number of instances the application should end up with
+    pub desired: i64,
+    /// Instances of the deployment that are running
+    pub new_ready: i64,
+    /// Instances of the deployment that have not started yet
+    pub new_pending: i64,
+    /// Instances of earlier releases that are still serving
+    pub old_live: i64,
+    /// Instances of earlier releases that are being stopped
+    pub old_stopping: i64,
+    /// Instances replaced per step of a rolling deployment
+    pub batch: i64,
+    /// Instances replaced before a canary deployment waits for promotion
+    pub canary: i64,
+    /// Whether a canary deployment was promoted
+    pub promoted: bool,
+}
+
+/// The next step of a deployment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Step {
+    /// Nothing can be done until instances start or stop
+    Wait { phase: &'static str, reason: String },
+    /// Create instances of the new release
+    Create { phase: &'static str, count: i64 },
+    /// Stop instances of earlier releases, highest index first
+    Stop { phase: &'static str, count: i64 },
+    /// The canary instances run; the deployment waits to be promoted
+    AwaitPromotion,
+    /// Every instance runs the new release
+    Complete,
+}
+
+impl Step {
+    /// The phase recorded on the deployment while it takes this step.
+    pub fn phase(&self) -> &'static str {
+        match self {
+            Step::Wait { phase, .. } | Step::Create { phase, .. } | Step::Stop { phase, .. } => phase,
+            Step::AwaitPromotion => "awaiting_promotion",
+            Step::Complete => "completed",
+        }
+    }
+}
+
+/// Number of instances replaced per step of a rolling deployment of
+/// `desired` instances, given as a percentage; at least one.
+pub fn batch_size(desired: i64, percentage: u32) -> i64 {
+    ((desired * percentage as i64 + 99) / 100).max(1)
+}
+
+/// Number of instances a canary deployment of `desired` instances replaces
+/// before waiting for promotion; at least one unless there are none.
+pub fn canary_size(desired: i64, percentage: i64) -> i64 {
+    ((desired * percentage.clamp(0, 100) + 99) / 100).clamp(desired.min(1), desired.max(0))
+}
+
+/// Decides the next step of a deployment.
+///
+/// New instances are created and given time to start before anything else
+/// happens, so a step never stops old instances while their replacements
+/// are still starting.
+pub fn next_step(strategy: Strategy, snapshot: &Snapshot) -> Step {
+    let s = snapshot;
+    if s.new_pending > 0 {
+        return Step::Wait {
+            phase: phase_while_starting(strategy, s),
+            reason: format!("waiting for {} new instance(s) to start", s.new_pending),
+        };
+    }
+
+    match strategy {
+        Strategy::Rolling => rolling(s, "rolling"),
+        Strategy::Canary if !s.promoted => {
+            if s.new_ready < s.canary {
+                Step::Create { phase: "canary", count: s.canary - s.new_ready }
+            } else if let Some(count) = surplus_old(s) {
+                Step::Stop { phase: "canary", count }
+            } else {
+                Step::AwaitPromotion
+            }
+        }
+        Strategy::Canary => rolling(s, "promoting"),
+        Strategy::BlueGreen => {
+            if s.new_ready < s.desired {
+                Step::Create { phase: "starting_green", count: s.desired - s.new_ready }
+            } else if s.old_live > 0 {
+                Step::Stop { phase: "switching", count: s.old_live }
+            } else {
+                finish(s, "switching")
+            }
+        }
+        Strategy::Recreate => {
+            if s.old_live > 0 {
+                Step::Stop { phase: "stopping_old", count: s.old_live }
+            } else if s.old_stopping > 0 {
+                Step::Wait {
+                    phase: "stopping_old",
+                    reason: format!("waiting for {} old instance(s) to stop", s.old_stopping),
+                }
+            } else if s.new_ready < s.desired {
+                Step::Create { phase: "starting_new", count: s.desired - s.new_ready }
+            } else {
+                Step::Complete
+            }
+        }
+    }
+}
+
+/// Replaces old instances a batch at a time: once the new instances are up,
+/// as many old ones are stopped, and the next batch is started.
+fn rolling(s: &Snapshot, phase: &'static str) -> Step {
+    if let Some(count) = surplus_old(s) {
+        Step::Stop { phase, count }
+    } else if s.new_ready < s.desired {
+        Step::Create { phase, count: s.batch.max(1).min(s.desired - s.new_ready) }
+    } else {
+        finish(s, phase)
+    }
+}
+
+/// Number of old instances to stop because running new ones replace them.
+fn surplus_old(s: &Snapshot) -> Option<i64> {
+    let surplus = (s.old_live + s.new_ready - s.desired).min(s.old_live);
+    (surplus > 0).then_some(surplus)
+}
+
+fn finish(s: &Snapshot, phase: &'static str) -> Step {
+    if s.old_stopping > 0 {
+        Step::Wait { phase, reason: format!("waiting for {} old instance(s) to stop", s.old_stopping) }
+    } else {
+        Step::Complete
+    }
+}
+
+fn phase_while_starting(strategy: Strategy, s: &Snapshot) -> &'static str {
+    match strategy {
+        Strategy::Rolling => "rolling",
+        Strategy::Canary if s.promoted => "promoting",
+        Strategy::Canary => "canary",
+        Strategy::BlueGreen => "starting_green",
+        Strategy::Recreate => "starting_new",
+    }
+}
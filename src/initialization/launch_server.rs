@@ -2,6 +2,8 @@ use crate::server::build_rocket;
 // use crate::{CLUSTER_MANAGER}; // removed unused import
 use crate::db_manager::DatabaseManager;
 use crate::state::SharedState;
+use crate::runtime::RuntimeDriver;
+use crate::artifacts::ArtifactStore;
 // use libomni::types::db::auth::AuthConfig; // removed unused import
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -16,6 +18,8 @@ use colored::Colorize;
 /// * `cluster_manager` - Shared cluster manager instance.
 /// * `clickhouse_client` - ClickHouse client instance.
 /// * `shared_state_for_server` - Shared state for the server.
+/// * `runtime_driver` - Container runtime driver instances run on.
+/// * `artifact_store` - Store release artifacts are kept in.
 ///
 /// # Errors
 /// Returns an error if the Rocket server fails to launch.
@@ -26,6 +30,8 @@ pub async fn launch_server(
     cluster_manager: Arc<RwLock<crate::cluster::ClusterManager>>,
     clickhouse_client: clickhouse::Client,
     shared_state_for_server: Arc<RwLock<SharedState>>,
+    runtime_driver: Arc<dyn RuntimeDriver>,
+    artifact_store: Arc<dyn ArtifactStore>,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let auth_config = super::create_auth_config();
     let rocket_with_routes = build_rocket(
@@ -36,6 +42,8 @@ pub async fn launch_server(
         clickhouse_client,
         shared_state_for_server,
         auth_config,
+        runtime_driver,
+        artifact_store,
     );
     log::info!("{}", "🚀 LAUNCHING SERVER...".bright_cyan().bold());
     rocket_with_routes.launch().await?;
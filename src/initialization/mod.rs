@@ -10,6 +10,17 @@
 //! - `setup_clickhouse`: Establishes a connection to ClickHouse and validates connectivity.
 //! - `setup_schema`: Loads and initializes the ClickHouse schema from SQL files.
 //! - `create_auth_config`: Constructs the authentication config from environment variables.
+//! - `setup_runtime`: Builds the configured container runtime driver and checks that the runtime is reachable.
+//! - `start_reconciler`: Periodically converges instances towards the desired state of their applications on the leader.
+//! - `start_worker_monitor`: Marks workers that stopped sending heartbeats unreachable and powers off drained decommissioning workers on the leader.
+//! - `start_autoscaler`: Periodically evaluates the autoscaling rules of running applications and scales them on the leader.
+//! - `start_deployer`: Periodically advances pending and in-progress deployments through the steps of their strategies on the leader.
+//! - `setup_artifact_store`: Builds the configured release artifact store.
+//! - `start_artifact_retention`: Periodically deletes release artifacts past their application's retention policy on the leader.
+//! - `start_audit_archiver`: Periodically moves audit log entries past their platform's retention period to the artifact store on the leader.
+//! - `start_build_queue`: Periodically requeues builds whose builder stopped sending heartbeats on the leader.
+//! - `start_pipeline_runner`: Periodically advances running pipeline runs through their stages on the leader.
+//! - `start_task_runner`: Periodically fires due task schedules and launches, follows and times out tasks on the leader.
 
 pub mod launch_server;
 pub mod setup_logging;
@@ -20,6 +31,17 @@ pub mod create_auth_config;
 pub mod start_peer_discovery;
 pub mod setup_cluster_management;
 pub mod start_leader_election;
+pub mod start_audit_archiver;
+pub mod setup_runtime;
+pub mod start_reconciler;
+pub mod start_worker_monitor;
+pub mod start_autoscaler;
+pub mod start_deployer;
+pub mod setup_artifact_store;
+pub mod start_artifact_retention;
+pub mod start_build_queue;
+pub mod start_pipeline_runner;
+pub mod start_task_runner;
 
 pub use launch_server::launch_server;
 pub use setup_logging::setup_logging;
@@ -29,4 +51,15 @@ pub use setup_schema::setup_schema;
 pub use create_auth_config::create_auth_config;
 pub use start_peer_discovery::start_peer_discovery;
 pub use setup_cluster_management::setup_cluster_management;
-pub use start_leader_election::start_leader_election;
\ No newline at end of file
+pub use start_leader_election::start_leader_election;
+pub use start_audit_archiver::start_audit_archiver;
+pub use setup_runtime::setup_runtime;
+pub use start_reconciler::start_reconciler;
+pub use start_worker_monitor::start_worker_monitor;
+pub use start_autoscaler::start_autoscaler;
+pub use start_deployer::start_deployer;
+pub use setup_artifact_store::setup_artifact_store;
+pub use start_artifact_retention::start_artifact_retention;
+pub use start_build_queue::start_build_queue;
+pub use start_pipeline_runner::start_pipeline_runner;
+pub use start_task_runner::start_task_runner;
\ No newline at end of file
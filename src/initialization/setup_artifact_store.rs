@@ -0,0 +1,11 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::SERVER_CONFIG;
+use crate::artifacts::{store_from_config, ArtifactStore};
+
+/// Builds the configured release artifact store.
+pub fn setup_artifact_store() -> anyhow::Result<Arc<dyn ArtifactStore>> {
+    let store = store_from_config(&SERVER_CONFIG.artifacts)?;
+    log::info!("{}", format!("✓ Storing release artifacts in {}", store.describe()).green());
+    Ok(store)
+}
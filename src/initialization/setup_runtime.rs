@@ -0,0 +1,18 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::SERVER_CONFIG;
+use crate::runtime::{driver_from_config, RuntimeDriver};
+
+/// Builds the configured container runtime driver and checks that the
+/// runtime is reachable. An unreachable runtime is only reported, as it may
+/// come up after the orchestrator.
+pub async fn setup_runtime() -> anyhow::Result<Arc<dyn RuntimeDriver>> {
+    let driver = driver_from_config(&SERVER_CONFIG.runtime)?;
+
+    match driver.ping().await {
+        Ok(description) => log::info!("{}", format!("✓ Connected to {}", description).green()),
+        Err(e) => log::warn!("{}", format!("Container runtime not reachable yet: {:#}", e).yellow()),
+    }
+
+    Ok(driver)
+}
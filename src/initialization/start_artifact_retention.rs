@@ -0,0 +1,31 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::artifacts::ArtifactStore;
+use crate::artifacts::retention::enforce_all_platforms;
+
+pub fn start_artifact_retention(
+    db_manager: Arc<DatabaseManager>,
+    shared_state: Arc<RwLock<SharedState>>,
+    store: Arc<dyn ArtifactStore>,
+) {
+    let config = SERVER_CONFIG.artifacts.clone();
+    if !config.retention_enabled {
+        log::info!("{}", "Artifact retention disabled in configuration".yellow());
+        return;
+    }
+
+    log::info!("{}", format!("Starting artifact retention; policies are applied every {}s", config.retention_interval_seconds).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.retention_interval_seconds.max(1));
+        loop {
+            tokio::time::sleep(period).await;
+
+            // Only the leader deletes artifacts
+            if !shared_state.read().await.is_leader {
+                continue;
+            }
+            enforce_all_platforms(&db_manager, &store).await;
+        }
+    });
+}
@@ -0,0 +1,26 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::artifacts::ArtifactStore;
+use crate::schemas::v1::api::audit_log::archive::archive_all_platforms;
+
+pub fn start_audit_archiver(
+    db_manager: Arc<DatabaseManager>,
+    shared_state: Arc<RwLock<SharedState>>,
+    store: Arc<dyn ArtifactStore>,
+) {
+    log::info!("{}", "Starting audit log archiver background task".yellow());
+    tokio::task::spawn({
+        let config = SERVER_CONFIG.audit.clone();
+        async move {
+            let period = tokio::time::Duration::from_secs(config.archive_interval_seconds.max(60));
+            loop {
+                // Only the leader archives audit logs
+                if shared_state.read().await.is_leader {
+                    archive_all_platforms(&db_manager, store.as_ref(), &config).await;
+                }
+                tokio::time::sleep(period).await;
+            }
+        }
+    });
+}
@@ -0,0 +1,26 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::autoscaler::autoscale_all_platforms;
+
+pub fn start_autoscaler(db_manager: Arc<DatabaseManager>, shared_state: Arc<RwLock<SharedState>>) {
+    let config = SERVER_CONFIG.autoscaler.clone();
+    if !config.enabled {
+        log::info!("{}", "Autoscaler disabled in configuration".yellow());
+        return;
+    }
+
+    log::info!("{}", format!("Starting autoscaler; rules are checked every {}s", config.interval_seconds).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.interval_seconds.max(1));
+        loop {
+            tokio::time::sleep(period).await;
+
+            // Only the leader scales applications
+            if !shared_state.read().await.is_leader {
+                continue;
+            }
+            autoscale_all_platforms(&db_manager, &config).await;
+        }
+    });
+}
@@ -0,0 +1,26 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::build_queue::expire_leases_all_platforms;
+
+pub fn start_build_queue(db_manager: Arc<DatabaseManager>, shared_state: Arc<RwLock<SharedState>>) {
+    let config = SERVER_CONFIG.builds.clone();
+    if !config.enabled {
+        log::info!("{}", "Build lease expiry disabled in configuration".yellow());
+        return;
+    }
+
+    log::info!("{}", format!("Starting build queue; leases last {}s without a heartbeat", config.lease_seconds).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.interval_seconds.max(1));
+        loop {
+            tokio::time::sleep(period).await;
+
+            // Only the leader requeues builds
+            if !shared_state.read().await.is_leader {
+                continue;
+            }
+            expire_leases_all_platforms(&db_manager, &config).await;
+        }
+    });
+}
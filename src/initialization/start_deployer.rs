@@ -0,0 +1,26 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::deployer::execute_all_platforms;
+
+pub fn start_deployer(db_manager: Arc<DatabaseManager>, shared_state: Arc<RwLock<SharedState>>) {
+    let config = SERVER_CONFIG.deployments.clone();
+    if !config.enabled {
+        log::info!("{}", "Deployment executor disabled in configuration".yellow());
+        return;
+    }
+
+    log::info!("{}", format!("Starting deployment executor; deployments advance every {}s", config.interval_seconds).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.interval_seconds.max(1));
+        loop {
+            tokio::time::sleep(period).await;
+
+            // Only the leader executes deployments
+            if !shared_state.read().await.is_leader {
+                continue;
+            }
+            execute_all_platforms(&db_manager, &config).await;
+        }
+    });
+}
@@ -7,6 +7,9 @@ use crate::SharedState;
 pub fn start_leader_election(shared_state: Arc<RwLock<SharedState>>, node_id: Arc<str>) {
     // Initialize and start leader election
     log::info!("{}", "Initializing leader election process".green());
-    let _leader_election = LeaderElection::new(node_id, shared_state.clone());
-    log::info!("{}", "✓ Leader election initialized".green());
+    let leader_election = LeaderElection::new(node_id, shared_state.clone());
+    tokio::task::spawn(async move {
+        leader_election.start().await;
+    });
+    log::info!("{}", "✓ Leader election started".green());
 }
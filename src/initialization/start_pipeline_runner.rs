@@ -0,0 +1,26 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::pipelines::execute_all_platforms;
+
+pub fn start_pipeline_runner(db_manager: Arc<DatabaseManager>, shared_state: Arc<RwLock<SharedState>>) {
+    let config = SERVER_CONFIG.pipelines.clone();
+    if !config.enabled {
+        log::info!("{}", "Pipeline runner disabled in configuration".yellow());
+        return;
+    }
+
+    log::info!("{}", format!("Starting pipeline runner; runs advance every {}s", config.interval_seconds).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.interval_seconds.max(1));
+        loop {
+            tokio::time::sleep(period).await;
+
+            // Only the leader executes pipeline runs
+            if !shared_state.read().await.is_leader {
+                continue;
+            }
+            execute_all_platforms(&db_manager).await;
+        }
+    });
+}
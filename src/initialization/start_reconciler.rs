@@ -0,0 +1,36 @@
+use colored::Colorize;
+use std::collections::HashMap;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::reconciler::reconcile_all_platforms;
+use crate::runtime::RuntimeDriver;
+
+pub fn start_reconciler(
+    db_manager: Arc<DatabaseManager>,
+    shared_state: Arc<RwLock<SharedState>>,
+    driver: Arc<dyn RuntimeDriver>,
+) {
+    let config = SERVER_CONFIG.reconciler.clone();
+    if !config.enabled {
+        log::info!("{}", "Reconciler disabled in configuration".yellow());
+        return;
+    }
+
+    log::info!("{}", format!("Starting reconciler with the {} runtime driver", driver.name()).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.interval_seconds.max(1));
+        let mut event_cursors = HashMap::new();
+        loop {
+            tokio::time::sleep(period).await;
+
+            // Only the leader reconciles; followers keep checking in case
+            // they are elected. A new leader starts reading runtime events
+            // afresh, as it may have missed some while following.
+            if !shared_state.read().await.is_leader {
+                event_cursors.clear();
+                continue;
+            }
+            reconcile_all_platforms(&db_manager, driver.as_ref(), &config, &mut event_cursors).await;
+        }
+    });
+}
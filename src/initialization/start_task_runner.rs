@@ -0,0 +1,31 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::runtime::RuntimeDriver;
+use crate::tasks::execute_all_platforms;
+
+pub fn start_task_runner(
+    db_manager: Arc<DatabaseManager>,
+    shared_state: Arc<RwLock<SharedState>>,
+    driver: Arc<dyn RuntimeDriver>,
+) {
+    let config = SERVER_CONFIG.tasks.clone();
+    if !config.enabled {
+        log::info!("{}", "Task runner disabled in configuration".yellow());
+        return;
+    }
+
+    log::info!("{}", format!("Starting task runner with the {} runtime driver; tasks are checked every {}s", driver.name(), config.interval_seconds).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.interval_seconds.max(1));
+        loop {
+            tokio::time::sleep(period).await;
+
+            // Only the leader launches tasks and fires their schedules
+            if !shared_state.read().await.is_leader {
+                continue;
+            }
+            execute_all_platforms(&db_manager, driver.as_ref(), &config).await;
+        }
+    });
+}
@@ -0,0 +1,65 @@
+use colored::Colorize;
+use std::sync::Arc;
+use crate::{DatabaseManager, RwLock, SharedState, SERVER_CONFIG};
+use crate::schemas::v1::db::queries as db;
+
+pub fn start_worker_monitor(db_manager: Arc<DatabaseManager>, shared_state: Arc<RwLock<SharedState>>) {
+    let config = SERVER_CONFIG.workers.clone();
+
+    log::info!("{}", format!(
+        "Starting worker monitor; workers are unreachable after {}s without a heartbeat",
+        config.unreachable_after_seconds
+    ).yellow());
+    tokio::task::spawn(async move {
+        let period = tokio::time::Duration::from_secs(config.monitor_interval_seconds.max(1));
+        loop {
+            tokio::time::sleep(period).await;
+            if !shared_state.read().await.is_leader {
+                continue;
+            }
+
+            let platforms = match db_manager.get_all_platforms().await {
+                Ok(platforms) => platforms,
+                Err(e) => {
+                    log::error!("Failed to list platforms for worker monitoring: {:?}", e);
+                    continue;
+                }
+            };
+
+            for platform in platforms {
+                let platform_id = match platform.id {
+                    Some(id) => id,
+                    None => continue,
+                };
+                let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+                    Ok(pool) => pool,
+                    Err(e) => {
+                        log::error!("Failed to connect to platform {} for worker monitoring: {:?}", platform_id, e);
+                        continue;
+                    }
+                };
+
+                match db::worker::mark_unreachable_workers(&pool, config.unreachable_after_seconds).await {
+                    Ok(workers) => {
+                        for worker in workers {
+                            log::warn!(
+                                "Worker {} ({}) of platform {} missed its heartbeats since {:?} and is now unreachable",
+                                worker.name, worker.id, platform_id, worker.last_heartbeat
+                            );
+                        }
+                    }
+                    Err(e) => log::error!("Failed to check worker heartbeats of platform {}: {:#}", platform_id, e),
+                }
+
+                match db::worker::finish_decommissioned_workers(&pool).await {
+                    Ok(workers) => {
+                        for worker_id in workers {
+                            log::info!("Worker {} of platform {} is drained and powered off", worker_id, platform_id);
+                        }
+                    }
+                    Err(e) => log::error!("Failed to finish decommissioned workers of platform {}: {:#}", platform_id, e),
+                }
+            }
+        }
+    });
+}
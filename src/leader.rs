@@ -109,9 +109,9 @@ impl LeaderElection {
         let nodes = cluster_manager.get_nodes_and_self().await;
         
         // Log participating nodes for debugging
-        log::info!("Nodes participating in election:");
+        log::debug!("Nodes participating in election:");
         for node in &nodes {
-            log::info!("  - {}", node.id);
+            log::debug!("  - {}", node.id);
         }
 
         // Acquire write lock on shared state to update leadership information
@@ -121,28 +121,35 @@ impl LeaderElection {
         // This ensures all nodes will independently choose the same leader
         let mut sorted_nodes = nodes.clone();
         sorted_nodes.sort_by(|a, b| a.id.cmp(&b.id));
-        log::info!("Sorted nodes: {:?}", sorted_nodes);
+        log::debug!("Sorted nodes: {:?}", sorted_nodes);
 
         // Handle the case where this is the only node (or no nodes, which shouldn't happen)
         if sorted_nodes.is_empty() {
             state.is_leader = true;
             state.leader_id = Some(self.node_id.clone());
-            log::info!("Single node {} becoming leader", self.node_id);
+            log::debug!("Single node {} becoming leader", self.node_id);
             return;
         }
 
         // First node in sorted list becomes leader
         let leader = &sorted_nodes[0];
         let is_self_leader = leader.id == self.node_id;
-        log::info!("Leader logic: {} == {}", leader.id, self.node_id);
+        log::debug!("Leader logic: {} == {}", leader.id, self.node_id);
 
         // Update state with leader information
+        if state.is_leader != is_self_leader {
+            log::info!(
+                "This node ({}) {} leadership",
+                self.node_id,
+                if is_self_leader { "took over" } else { "gave up" }
+            );
+        }
         state.is_leader = is_self_leader;
         state.leader_id = Some(leader.id.clone());
 
         // Log election results
-        log::info!("Leader elected: {})", leader.id);
-        log::info!(
+        log::debug!("Leader elected: {})", leader.id);
+        log::debug!(
             "This node ({}) is {}",
             self.node_id,
             if is_self_leader { "leader" } else { "follower" }
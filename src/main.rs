@@ -23,8 +23,17 @@ mod leader;
 mod config;
 mod cluster;
 mod network;
+mod runtime;
 mod schemas;
 mod logging;
+mod reconciler;
+mod scheduler;
+mod autoscaler;
+mod deployer;
+mod artifacts;
+mod build_queue;
+mod pipelines;
+mod tasks;
 mod endpoints;
 mod db_manager;
 mod api_models;
@@ -111,6 +120,15 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     // Clone shared_state for later use
     let shared_state_for_leader = shared_state.clone();
+    let shared_state_for_reconciler = shared_state.clone();
+    let shared_state_for_worker_monitor = shared_state.clone();
+    let shared_state_for_autoscaler = shared_state.clone();
+    let shared_state_for_deployer = shared_state.clone();
+    let shared_state_for_artifact_retention = shared_state.clone();
+    let shared_state_for_audit_archiver = shared_state.clone();
+    let shared_state_for_build_queue = shared_state.clone();
+    let shared_state_for_pipelines = shared_state.clone();
+    let shared_state_for_tasks = shared_state.clone();
     let shared_state_for_server = shared_state.clone();
 
     // ====================== Start Peer Discovery ======================
@@ -118,19 +136,61 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     logging::print_banner("START PEER DISCOVERY", |s| s.bright_magenta());
     initialization::start_peer_discovery(port);
 
-    // ====================== AUTOSCALER SETUP ======================
-    logging::print_banner("AUTOSCALER SETUP", |s| s.bright_yellow());
-
-    // TODO: Implement autoscaler setup via Lighthouse integration
-    // This will require establishing communication with the Lighthouse service
-    // for automatic scaling decisions based on cluster metrics and load patterns.
-    // Reference: https://github.com/OmniCloudOrg/Lighthouse
-
     // ====================== LEADER ELECTION ======================
     logging::print_banner("LEADER ELECTION", |s| s.bright_green());
 
     initialization::start_leader_election(shared_state_for_leader, node_id);
 
+    // ====================== RUNTIME SETUP ======================
+    logging::print_banner("RUNTIME SETUP", |s| s.bright_yellow());
+    let runtime_driver = initialization::setup_runtime().await?;
+
+    // ====================== RECONCILER ======================
+    logging::print_banner("RECONCILER", |s| s.bright_yellow());
+
+    initialization::start_reconciler(db_manager.clone(), shared_state_for_reconciler, runtime_driver.clone());
+
+    // ====================== WORKER MONITOR ======================
+    logging::print_banner("WORKER MONITOR", |s| s.bright_yellow());
+
+    initialization::start_worker_monitor(db_manager.clone(), shared_state_for_worker_monitor);
+
+    // ====================== AUTOSCALER ======================
+    logging::print_banner("AUTOSCALER", |s| s.bright_yellow());
+
+    initialization::start_autoscaler(db_manager.clone(), shared_state_for_autoscaler);
+
+    // ====================== DEPLOYMENT EXECUTOR ======================
+    logging::print_banner("DEPLOYMENT EXECUTOR", |s| s.bright_yellow());
+
+    initialization::start_deployer(db_manager.clone(), shared_state_for_deployer);
+
+    // ====================== ARTIFACT STORE ======================
+    logging::print_banner("ARTIFACT STORE", |s| s.bright_yellow());
+    let artifact_store = initialization::setup_artifact_store()?;
+
+    initialization::start_artifact_retention(db_manager.clone(), shared_state_for_artifact_retention, artifact_store.clone());
+
+    // ====================== AUDIT LOG ARCHIVER ======================
+    logging::print_banner("AUDIT LOG ARCHIVER", |s| s.bright_yellow());
+
+    initialization::start_audit_archiver(db_manager.clone(), shared_state_for_audit_archiver, artifact_store.clone());
+
+    // ====================== BUILD QUEUE ======================
+    logging::print_banner("BUILD QUEUE", |s| s.bright_yellow());
+
+    initialization::start_build_queue(db_manager.clone(), shared_state_for_build_queue);
+
+    // ====================== PIPELINES ======================
+    logging::print_banner("PIPELINES", |s| s.bright_yellow());
+
+    initialization::start_pipeline_runner(db_manager.clone(), shared_state_for_pipelines);
+
+    // ====================== TASKS ======================
+    logging::print_banner("TASKS", |s| s.bright_yellow());
+
+    initialization::start_task_runner(db_manager.clone(), shared_state_for_tasks, runtime_driver.clone());
+
     // ====================== SERVER STARTUP ======================
     logging::print_banner("SERVER STARTUP", |s| s.bright_cyan());
 
@@ -141,6 +201,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         CLUSTER_MANAGER.clone(),
         clickhouse_client,
         shared_state_for_server,
+        runtime_driver,
+        artifact_store,
     ).await?;
 
     Ok(())
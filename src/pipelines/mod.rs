@@ -0,0 +1,439 @@
+//! Execution of pipeline runs.
+//!
+//! A pipeline takes a build of an application through a sequence of
+//! [stages](stages): building it, running test tasks against it, waiting
+//! for approvals, deploying it and promoting it to the application of the
+//! next environment. Promoting copies the build record, so the same
+//! artifact moves from development through staging to production.
+//!
+//! A run keeps a snapshot of the stages it was started with and a row per
+//! stage in `pipeline_run_stages`. The leader advances every running run
+//! by one step per pass: it starts the first pending stage, or checks
+//! whether the build, task, deployment or approvals a running stage waits
+//! for are done. A failed stage is attempted again after its retry delay
+//! until it runs out of attempts, which fails the run and skips the stages
+//! after it. Failed and canceled runs can be retried from the stage that
+//! stopped them.
+//!
+//! Deploy stages never override [freeze windows](crate::deployer::freeze);
+//! they wait until the freeze ends. Approval policies of the target
+//! application apply to the deployments they create. Everything a run does
+//! is written to its log in `pipeline_run_logs`.
+
+pub mod stages;
+
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::{MySql, Pool};
+
+use crate::deployer::auto_deploy::{deploy_build, BuildDeployment};
+use crate::schemas::v1::db::queries as db;
+use crate::DatabaseManager;
+use db::pipeline_run::{PipelineRun, PipelineRunStage, StageFailure};
+use db::task::NewTask;
+use stages::{StageDefinition, StageKind};
+
+/// Seconds a deploy stage waits before checking a freeze window again.
+const FREEZE_RECHECK_SECONDS: u64 = 60;
+
+/// Stages and runs moved forward by one pass of the pipeline runner.
+#[derive(Debug, Default, Clone)]
+pub struct RunSummary {
+    pub stages_started: usize,
+    pub stages_succeeded: usize,
+    pub stages_retried: usize,
+    pub runs_succeeded: usize,
+    pub runs_failed: usize,
+    pub failures: usize,
+}
+
+impl RunSummary {
+    fn changed(&self) -> bool {
+        self.stages_started + self.stages_succeeded + self.stages_retried + self.runs_succeeded + self.runs_failed + self.failures > 0
+    }
+}
+
+/// Advances the pipeline runs of every platform once.
+pub async fn execute_all_platforms(db_manager: &DatabaseManager) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for pipelines: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for pipelines: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        match execute_platform(&pool, platform_id).await {
+            Ok(Some(summary)) if summary.changed() => {
+                log::info!("Advanced pipeline runs of platform {}: {:?}", platform_id, summary);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to advance pipeline runs of platform {}: {:#}", platform_id, e),
+        }
+    }
+}
+
+/// Advances every running pipeline run of a platform by one step. Returns
+/// `None` if another node is executing the platform's runs.
+pub async fn execute_platform(pool: &Pool<MySql>, platform_id: i64) -> anyhow::Result<Option<RunSummary>> {
+    // Named locks are server wide, so the lock name includes the database
+    let mut lock_conn = pool.acquire().await?;
+    let locked = sqlx::query_scalar::<_, Option<i64>>("SELECT GET_LOCK(CONCAT('omni_pipelines:', DATABASE()), 0)")
+        .fetch_one(&mut *lock_conn)
+        .await
+        .context("Failed to acquire pipeline runner lock")?;
+    if locked != Some(1) {
+        return Ok(None);
+    }
+
+    let mut summary = RunSummary::default();
+    let result = match db::pipeline_run::list_runs_to_execute(pool).await {
+        Ok(runs) => {
+            for run in &runs {
+                if let Err(e) = advance(pool, platform_id, run, &mut summary).await {
+                    summary.failures += 1;
+                    log::error!("Failed to advance pipeline run {}: {:#}", run.id, e);
+                }
+            }
+            Ok(Some(summary))
+        }
+        Err(e) => Err(e),
+    };
+
+    if let Err(e) = sqlx::query("SELECT RELEASE_LOCK(CONCAT('omni_pipelines:', DATABASE()))")
+        .execute(&mut *lock_conn)
+        .await
+    {
+        log::warn!("Failed to release pipeline runner lock: {}", e);
+    }
+
+    result
+}
+
+/// Starts or checks on the first stage of a run that has not succeeded, or
+/// completes the run if there is none.
+async fn advance(pool: &Pool<MySql>, platform_id: i64, run: &PipelineRun, summary: &mut RunSummary) -> anyhow::Result<()> {
+    let definitions: Vec<StageDefinition> =
+        serde_json::from_value(run.stages.clone()).context("Failed to read stages of pipeline run")?;
+    let stages = db::pipeline_run::list_run_stages(pool, run.id).await?;
+
+    let stage = stages
+        .iter()
+        .find(|stage| !matches!(stage.status.as_deref(), Some("succeeded") | Some("skipped")));
+    let stage = match stage {
+        Some(stage) => stage,
+        None => {
+            if db::pipeline_run::complete_run(pool, run.id).await? {
+                summary.runs_succeeded += 1;
+                note(pool, run.id, None, "info", "Run succeeded").await;
+            }
+            return Ok(());
+        }
+    };
+    let definition = definitions
+        .get(stage.stage_index as usize)
+        .with_context(|| format!("Pipeline run {} has no definition of stage {}", run.id, stage.stage_index))?;
+
+    match stage.status.as_deref() {
+        Some("pending") => {
+            if stage.next_attempt_at.is_some_and(|at| at > Utc::now()) {
+                return Ok(());
+            }
+            start(pool, platform_id, run, stage, definition, summary).await
+        }
+        Some("running") => check(pool, run, stage, definition, summary).await,
+        _ => Ok(()),
+    }
+}
+
+/// Starts a pending stage.
+async fn start(
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    run: &PipelineRun,
+    stage: &PipelineRunStage,
+    definition: &StageDefinition,
+    summary: &mut RunSummary,
+) -> anyhow::Result<()> {
+    let attempt = format!("attempt {} of {}", stage.attempts + 1, stage.max_attempts);
+
+    match &definition.kind {
+        StageKind::Build => {
+            // A run started with a build waits for it, unless it failed
+            let existing = match run.build_id {
+                Some(build_id) => Some(db::build::get_build_by_id(pool, build_id).await?),
+                None => None,
+            };
+            let existing = existing.filter(|build| !matches!(build.status.as_deref(), Some("failed") | Some("canceled")));
+            let (build, queued) = match existing {
+                Some(build) => (build, false),
+                None => {
+                    let trigger = format!("Queued by stage '{}' of pipeline run {}", stage.name, run.id);
+                    let build = db::build::create_pipeline_build(pool, run.app_id, run.commit_sha.as_deref(), &trigger).await?;
+                    (build, true)
+                }
+            };
+
+            if !db::pipeline_run::start_stage(pool, stage.id, Some(build.id), None, None).await? {
+                if queued {
+                    db::build_queue::cancel_build(pool, platform_id, build.id).await?;
+                }
+                return Ok(());
+            }
+            summary.stages_started += 1;
+            let message = if queued {
+                format!("Queued build {} ({})", build.id, attempt)
+            } else {
+                format!("Waiting for build {} ({})", build.id, attempt)
+            };
+            note(pool, run.id, Some(stage.id), "info", &message).await;
+        }
+        StageKind::Test { command, app_id, timeout_seconds } => {
+            let task = NewTask {
+                command: command.clone(),
+                name: Some(format!("Pipeline run {}: {}", run.id, stage.name)),
+                memory_in_mb: None,
+                disk_in_mb: None,
+                cpu: None,
+                timeout_seconds: *timeout_seconds,
+                schedule_id: None,
+            };
+            let task = db::task::create_task(pool, app_id.unwrap_or(run.app_id), &task, run.created_by).await?;
+
+            if !db::pipeline_run::start_stage(pool, stage.id, None, Some(task.id), None).await? {
+                db::task::cancel_task(pool, task.id, "The pipeline run is no longer running").await?;
+                return Ok(());
+            }
+            summary.stages_started += 1;
+            let message = format!("Started task {} of app {}: {} ({})", task.id, task.app_id, task.command, attempt);
+            note(pool, run.id, Some(stage.id), "info", &message).await;
+        }
+        StageKind::Deploy { app_id, deployment_strategy } => {
+            let build = match run.build_id {
+                Some(build_id) => db::build::get_build_by_id(pool, build_id).await?,
+                None => {
+                    return fail(pool, run, stage, definition, "The run has no build to deploy", summary).await;
+                }
+            };
+            if app_id.is_some_and(|app_id| app_id != build.app_id) {
+                let message = format!("Build {} belongs to app {}, not app {}", build.id, build.app_id, app_id.unwrap_or_default());
+                return fail(pool, run, stage, definition, &message, summary).await;
+            }
+            let strategy = deployment_strategy.as_deref().unwrap_or("rolling");
+
+            match deploy_build(pool, &build, strategy, run.created_by).await? {
+                BuildDeployment::Frozen(window) => {
+                    let reason = format!("Deployments of app {} are frozen by '{}'", build.app_id, window);
+                    if db::pipeline_run::defer_stage(pool, stage.id, FREEZE_RECHECK_SECONDS, &reason).await? {
+                        note(pool, run.id, Some(stage.id), "warn", &format!("{}; waiting for the freeze to end", reason)).await;
+                    }
+                }
+                BuildDeployment::Created { deployment, required_approvals } => {
+                    if !db::pipeline_run::start_stage(pool, stage.id, None, None, Some(deployment.id)).await? {
+                        db::deployment::cancel_pending_deployment(pool, deployment.id, "The pipeline run is no longer running").await?;
+                        return Ok(());
+                    }
+                    summary.stages_started += 1;
+                    let mut message = format!(
+                        "Created {} deployment {} of build {} to app {} ({})",
+                        strategy, deployment.id, build.id, build.app_id, attempt
+                    );
+                    if required_approvals > 0 {
+                        message.push_str(&format!("; it starts once it has {} approval(s)", required_approvals));
+                    }
+                    note(pool, run.id, Some(stage.id), "info", &message).await;
+                }
+            }
+        }
+        StageKind::Approval { required_approvals } => {
+            if !db::pipeline_run::start_stage(pool, stage.id, None, None, None).await? {
+                return Ok(());
+            }
+            summary.stages_started += 1;
+            let message = format!("Waiting for {} approval(s)", required_approvals.unwrap_or(1));
+            note(pool, run.id, Some(stage.id), "info", &message).await;
+        }
+        StageKind::Promote { .. } => {
+            if !db::pipeline_run::start_stage(pool, stage.id, None, None, None).await? {
+                return Ok(());
+            }
+            summary.stages_started += 1;
+            return check(pool, run, stage, definition, summary).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks whether a running stage is done.
+async fn check(
+    pool: &Pool<MySql>,
+    run: &PipelineRun,
+    stage: &PipelineRunStage,
+    definition: &StageDefinition,
+    summary: &mut RunSummary,
+) -> anyhow::Result<()> {
+    match &definition.kind {
+        StageKind::Build => {
+            let build_id = stage.build_id.context("Build stage has no build")?;
+            let build = db::build::get_build_by_id(pool, build_id).await?;
+            match build.status.as_deref() {
+                Some("succeeded") => {
+                    let message = format!("Build {} succeeded", build.id);
+                    succeed(pool, run, stage, Some(build.id), &message, summary).await?;
+                }
+                Some("failed") | Some("canceled") => {
+                    let message = match build.error_message {
+                        Some(error) => format!("Build {} {}: {}", build.id, build.status.unwrap_or_default(), error),
+                        None => format!("Build {} {}", build.id, build.status.unwrap_or_default()),
+                    };
+                    fail(pool, run, stage, definition, &message, summary).await?;
+                }
+                _ => {}
+            }
+        }
+        StageKind::Test { .. } => {
+            let task_id = stage.task_id.context("Test stage has no task")?;
+            let task = match db::task::get_task(pool, task_id).await? {
+                Some(task) => task,
+                None => return fail(pool, run, stage, definition, &format!("Task {} was deleted", task_id), summary).await,
+            };
+            match task.status.as_deref() {
+                Some("succeeded") => {
+                    let message = format!("Task {} succeeded", task.id);
+                    succeed(pool, run, stage, None, &message, summary).await?;
+                }
+                Some("failed") | Some("canceled") => {
+                    let mut message = format!("Task {} {}", task.id, task.status.as_deref().unwrap_or_default());
+                    if let Some(exit_code) = task.exit_code {
+                        message.push_str(&format!(" with exit code {}", exit_code));
+                    }
+                    if let Some(error) = &task.error_message {
+                        message.push_str(&format!(": {}", error));
+                    }
+                    fail(pool, run, stage, definition, &message, summary).await?;
+                }
+                _ => {}
+            }
+        }
+        StageKind::Deploy { .. } => {
+            let deployment_id = stage.deployment_id.context("Deploy stage has no deployment")?;
+            let deployment = db::deployment::get_deployment_by_id(pool, deployment_id).await?;
+            match deployment.status.as_deref() {
+                Some("deployed") => {
+                    let message = format!("Deployment {} completed", deployment.id);
+                    succeed(pool, run, stage, None, &message, summary).await?;
+                }
+                Some("failed") | Some("canceled") => {
+                    let message = match deployment.error_message {
+                        Some(error) => format!("Deployment {} {}: {}", deployment.id, deployment.status.unwrap_or_default(), error),
+                        None => format!("Deployment {} {}", deployment.id, deployment.status.unwrap_or_default()),
+                    };
+                    fail(pool, run, stage, definition, &message, summary).await?;
+                }
+                _ => {}
+            }
+        }
+        StageKind::Approval { required_approvals } => {
+            let decisions = db::pipeline_run::list_stage_approvals(pool, stage.id).await?;
+            if let Some(rejection) = decisions.iter().find(|decision| decision.decision == "rejected") {
+                let message = match &rejection.comment {
+                    Some(comment) => format!("Rejected by user {}: {}", rejection.user_id, comment),
+                    None => format!("Rejected by user {}", rejection.user_id),
+                };
+                return fail(pool, run, stage, definition, &message, summary).await;
+            }
+            let approvals = decisions.len() as i64;
+            let required = required_approvals.unwrap_or(1);
+            if approvals >= required {
+                let message = format!("Approved by {} user(s)", approvals);
+                succeed(pool, run, stage, None, &message, summary).await?;
+            }
+        }
+        StageKind::Promote { app_id } => {
+            let build_id = match run.build_id {
+                Some(build_id) => build_id,
+                None => return fail(pool, run, stage, definition, "The run has no build to promote", summary).await,
+            };
+            let trigger = format!("Promoted from build {} by stage '{}' of pipeline run {}", build_id, stage.name, run.id);
+            match db::build::create_promoted_build(pool, build_id, *app_id, &trigger).await {
+                Ok(build) => {
+                    let message = format!("Promoted build {} to app {} as build {}", build_id, app_id, build.id);
+                    succeed(pool, run, stage, Some(build.id), &message, summary).await?;
+                }
+                Err(e) => fail(pool, run, stage, definition, &format!("{:#}", e), summary).await?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Marks a stage as succeeded, carrying `build_id` on to the next stages
+/// if set.
+async fn succeed(
+    pool: &Pool<MySql>,
+    run: &PipelineRun,
+    stage: &PipelineRunStage,
+    build_id: Option<i64>,
+    message: &str,
+    summary: &mut RunSummary,
+) -> anyhow::Result<()> {
+    if db::pipeline_run::succeed_stage(pool, run.id, stage.id, build_id).await? {
+        summary.stages_succeeded += 1;
+        note(pool, run.id, Some(stage.id), "info", message).await;
+    }
+    Ok(())
+}
+
+/// Fails an attempt of a stage, retrying it if it has attempts left.
+async fn fail(
+    pool: &Pool<MySql>,
+    run: &PipelineRun,
+    stage: &PipelineRunStage,
+    definition: &StageDefinition,
+    message: &str,
+    summary: &mut RunSummary,
+) -> anyhow::Result<()> {
+    // Stages failing before they start still use up an attempt
+    if stage.status.as_deref() == Some("pending")
+        && !db::pipeline_run::start_stage(pool, stage.id, None, None, None).await?
+    {
+        return Ok(());
+    }
+
+    match db::pipeline_run::fail_stage(pool, run.id, stage.id, message, definition.retry_delay_seconds).await? {
+        StageFailure::Retrying => {
+            summary.stages_retried += 1;
+            let message = format!("{}; retrying in {}s", message, definition.retry_delay_seconds);
+            note(pool, run.id, Some(stage.id), "warn", &message).await;
+        }
+        StageFailure::Failed => {
+            summary.runs_failed += 1;
+            note(pool, run.id, Some(stage.id), "error", message).await;
+            note(pool, run.id, None, "error", &format!("Run failed at stage '{}'", stage.name)).await;
+        }
+        StageFailure::Finished => {}
+    }
+    Ok(())
+}
+
+async fn note(pool: &Pool<MySql>, run_id: i64, stage_id: Option<i64>, level: &str, message: &str) {
+    if let Err(e) = db::pipeline_run::record_run_log(pool, run_id, stage_id, level, message).await {
+        log::warn!("Failed to record log line of pipeline run {}: {:#}", run_id, e);
+    }
+}
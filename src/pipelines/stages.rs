@@ -0,0 +1,201 @@
+//! Stage definitions of pipelines and their validation.
+
+use serde::{Deserialize, Serialize};
+
+use crate::deployer::strategy::STRATEGIES;
+
+/// Most stages a pipeline may have.
+pub const MAX_STAGES: usize = 20;
+
+/// Most times a stage may be retried after failing.
+pub const MAX_RETRIES: u32 = 10;
+
+/// Most approvals an approval stage may require.
+pub const MAX_REQUIRED_APPROVALS: i64 = 10;
+
+/// A stage of a pipeline, as stored in the `stages` column.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StageDefinition {
+    pub name: String,
+    #[serde(flatten)]
+    pub kind: StageKind,
+    /// Times the stage is attempted again after failing
+    #[serde(default)]
+    pub retries: u32,
+    /// Seconds to wait before attempting a failed stage again
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay_seconds: u64,
+}
+
+/// What a stage does.
+///
+/// A run carries one build through its stages. Deploy stages deploy it to
+/// the application it belongs to, and promote stages copy it to another
+/// application, so that the same artifact moves from one environment to
+/// the next.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StageKind {
+    /// Queue a build of the pipeline's application and wait for it to
+    /// succeed. A run started with an existing build waits for that one.
+    Build,
+    /// Run a task and wait for it to exit successfully.
+    Test {
+        command: String,
+        /// Application the task runs against; the pipeline's if unset
+        app_id: Option<i64>,
+        timeout_seconds: Option<i64>,
+    },
+    /// Deploy the run's build and wait for the deployment to complete.
+    Deploy {
+        /// Must be the application the build belongs to at this stage;
+        /// that application if unset
+        app_id: Option<i64>,
+        deployment_strategy: Option<String>,
+    },
+    /// Wait for users holding `deployments:approve` to approve the run.
+    Approval {
+        required_approvals: Option<i64>,
+    },
+    /// Copy the run's build to another application.
+    Promote {
+        app_id: i64,
+    },
+}
+
+fn default_retry_delay() -> u64 {
+    30
+}
+
+impl StageKind {
+    /// The value of the stage's `stage_type` column.
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            StageKind::Build => "build",
+            StageKind::Test { .. } => "test",
+            StageKind::Deploy { .. } => "deploy",
+            StageKind::Approval { .. } => "approval",
+            StageKind::Promote { .. } => "promote",
+        }
+    }
+}
+
+impl StageDefinition {
+    /// Times the stage is attempted at most.
+    pub fn max_attempts(&self) -> i32 {
+        match self.kind {
+            // A rejection is final
+            StageKind::Approval { .. } => 1,
+            _ => self.retries as i32 + 1,
+        }
+    }
+}
+
+/// Whether one of `stages` builds the pipeline's application.
+pub fn has_build_stage(stages: &[StageDefinition]) -> bool {
+    stages.iter().any(|stage| stage.kind == StageKind::Build)
+}
+
+/// Applications other than the pipeline's that `stages` act on.
+pub fn referenced_apps(stages: &[StageDefinition], pipeline_app_id: i64) -> Vec<i64> {
+    let mut app_ids: Vec<i64> = stages
+        .iter()
+        .filter_map(|stage| match &stage.kind {
+            StageKind::Test { app_id, .. } | StageKind::Deploy { app_id, .. } => *app_id,
+            StageKind::Promote { app_id } => Some(*app_id),
+            _ => None,
+        })
+        .filter(|app_id| *app_id != pipeline_app_id)
+        .collect();
+    app_ids.sort_unstable();
+    app_ids.dedup();
+    app_ids
+}
+
+/// Checks that `stages` form a pipeline that can run, following the
+/// application the run's build belongs to from stage to stage.
+pub fn validate(stages: &[StageDefinition], pipeline_app_id: i64) -> Result<(), String> {
+    if stages.is_empty() || stages.len() > MAX_STAGES {
+        return Err(format!("A pipeline must have between 1 and {} stages", MAX_STAGES));
+    }
+
+    let mut names = std::collections::HashSet::new();
+    let mut build_app_id = pipeline_app_id;
+    let mut promoted = false;
+    let mut built = false;
+
+    for (index, stage) in stages.iter().enumerate() {
+        let position = index + 1;
+        let name = stage.name.trim();
+        if name.is_empty() || name.len() > 255 {
+            return Err(format!("Stage {} must have a name of at most 255 characters", position));
+        }
+        if !names.insert(name) {
+            return Err(format!("Stage name '{}' is used more than once", name));
+        }
+        if stage.retries > MAX_RETRIES {
+            return Err(format!("Stage '{}' may be retried at most {} times", name, MAX_RETRIES));
+        }
+        if stage.retry_delay_seconds > 3600 {
+            return Err(format!("Stage '{}' may wait at most 3600 seconds between attempts", name));
+        }
+
+        match &stage.kind {
+            StageKind::Build => {
+                if built {
+                    return Err("A pipeline may have only one build stage".to_string());
+                }
+                if promoted {
+                    return Err(format!("Build stage '{}' must come before the promote stages", name));
+                }
+                built = true;
+            }
+            StageKind::Test { command, timeout_seconds, .. } => {
+                if command.trim().is_empty() {
+                    return Err(format!("Test stage '{}' must have a command", name));
+                }
+                if timeout_seconds.is_some_and(|timeout| !(1..=86400).contains(&timeout)) {
+                    return Err(format!("Test stage '{}' must time out after between 1 and 86400 seconds", name));
+                }
+            }
+            StageKind::Deploy { app_id, deployment_strategy } => {
+                if let Some(app_id) = app_id {
+                    if *app_id != build_app_id {
+                        return Err(format!(
+                            "Deploy stage '{}' deploys to app {}, but the build belongs to app {} at that point; \
+                             add a promote stage to app {} before it",
+                            name, app_id, build_app_id, app_id
+                        ));
+                    }
+                }
+                if let Some(strategy) = deployment_strategy {
+                    if !STRATEGIES.contains(&strategy.as_str()) {
+                        return Err(format!(
+                            "Deploy stage '{}' has unknown deployment_strategy '{}'; use one of {}",
+                            name,
+                            strategy,
+                            STRATEGIES.join(", ")
+                        ));
+                    }
+                }
+            }
+            StageKind::Approval { required_approvals } => {
+                if required_approvals.is_some_and(|required| !(1..=MAX_REQUIRED_APPROVALS).contains(&required)) {
+                    return Err(format!(
+                        "Approval stage '{}' must require between 1 and {} approvals",
+                        name, MAX_REQUIRED_APPROVALS
+                    ));
+                }
+            }
+            StageKind::Promote { app_id } => {
+                if *app_id == build_app_id {
+                    return Err(format!("Promote stage '{}' promotes the build to app {}, which it already belongs to", name, app_id));
+                }
+                build_app_id = *app_id;
+                promoted = true;
+            }
+        }
+    }
+
+    Ok(())
+}
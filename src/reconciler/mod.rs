@@ -0,0 +1,610 @@
+//! Desired-state reconciliation of applications and instances.
+//!
+//! On every pass the reconciler first applies the events the runtime
+//! reported since the previous pass, so instances become 'running' or
+//! 'crashed' as their containers start and exit. It then compares what each
+//! application should be running (`apps.instances`, `apps.status` and its
+//! current deployment) with the `instances` rows of the application and the
+//! containers the runtime driver reports for them. It creates, launches,
+//! restarts and terminates instances until both agree, and records every
+//! change it makes in `app_events`. Instances are placed on a worker by the
+//! [scheduler](crate::scheduler) before they are first launched, and the
+//! capacity of instances that have finished is returned to their workers.
+//!
+//! Only the cluster leader reconciles, and a database lock keeps two nodes
+//! that both believe they lead from reconciling the same platform at once.
+//! While a deployment of an application is in progress, the
+//! [deployment executor](crate::deployer) decides which of its instances
+//! exist; the reconciler still launches, restarts and stops them, each with
+//! the release of the deployment it belongs to.
+
+pub mod plan;
+
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use libomni::types::db::v1 as types;
+use serde_json::json;
+use sqlx::{MySql, Pool};
+use types::app::App;
+use types::deployment::Deployment;
+use types::instance::Instance;
+
+use crate::config::ReconcilerConfig;
+use crate::runtime::{InstanceSpec, RuntimeContainer, RuntimeDriver, RuntimeEventKind};
+use crate::scheduler::{self, Placement};
+use crate::schemas::v1::db::queries as db;
+use crate::{DatabaseManager, SERVER_CONFIG};
+use db::app_event::NewAppEvent;
+use plan::{Action, DesiredState};
+
+/// Counts of the changes made by a reconciliation pass.
+#[derive(Debug, Default, Clone)]
+pub struct ReconcileSummary {
+    pub runtime_events: usize,
+    pub apps: usize,
+    pub created: usize,
+    pub launched: usize,
+    pub running: usize,
+    pub crashed: usize,
+    pub unhealthy: usize,
+    pub restarted: usize,
+    pub terminated: usize,
+    pub orphans_removed: usize,
+    pub unschedulable: usize,
+    pub reservations_released: usize,
+    pub failures: usize,
+}
+
+impl ReconcileSummary {
+    fn changed(&self) -> bool {
+        self.created + self.launched + self.running + self.crashed + self.unhealthy + self.restarted + self.terminated + self.orphans_removed + self.reservations_released + self.failures > 0
+    }
+}
+
+/// Outcome of a reconciliation pass over one platform.
+#[derive(Debug)]
+pub enum ReconcileRun {
+    /// Another node is reconciling this platform
+    Busy,
+    /// The pass ran to completion
+    Completed(ReconcileSummary),
+}
+
+/// How far back runtime events are read on the first pass over a platform,
+/// in seconds.
+const INITIAL_EVENT_LOOKBACK_SECS: i64 = 300;
+
+/// Reconciles every platform once.
+///
+/// `event_cursors` holds, per platform, the time up to which runtime events
+/// have been applied, and is advanced by every successful pass.
+pub async fn reconcile_all_platforms(
+    db_manager: &DatabaseManager,
+    driver: &dyn RuntimeDriver,
+    config: &ReconcilerConfig,
+    event_cursors: &mut HashMap<i64, DateTime<Utc>>,
+) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for reconciliation: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for reconciliation: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        let cursor = event_cursors
+            .entry(platform_id)
+            .or_insert_with(|| Utc::now() - chrono::Duration::seconds(INITIAL_EVENT_LOOKBACK_SECS));
+        match reconcile_platform(&pool, platform_id, driver, config, cursor).await {
+            Ok(ReconcileRun::Completed(summary)) if summary.changed() => {
+                log::info!("Reconciled platform {}: {:?}", platform_id, summary);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to reconcile platform {}: {:#}", platform_id, e),
+        }
+    }
+}
+
+/// Runs one reconciliation pass over a platform.
+///
+/// Runtime events from `event_cursor` up to now are applied first, and the
+/// cursor is advanced once they have been. Events that cannot be fetched are
+/// retried on the next pass; in the meantime the runtime's list of
+/// containers still reveals what changed. Failures to act on a single
+/// instance are recorded as events and counted, but do not stop the pass.
+pub async fn reconcile_platform(
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    driver: &dyn RuntimeDriver,
+    config: &ReconcilerConfig,
+    event_cursor: &mut DateTime<Utc>,
+) -> anyhow::Result<ReconcileRun> {
+    // Named locks are server wide, so the lock name includes the database
+    let mut lock_conn = pool.acquire().await?;
+    let locked = sqlx::query_scalar::<_, Option<i64>>("SELECT GET_LOCK(CONCAT('omni_reconciler:', DATABASE()), 0)")
+        .fetch_one(&mut *lock_conn)
+        .await
+        .context("Failed to acquire reconciler lock")?;
+    if locked != Some(1) {
+        return Ok(ReconcileRun::Busy);
+    }
+
+    let reconciler = Reconciler {
+        pool,
+        platform_id,
+        driver,
+        config,
+        stop_timeout: Duration::from_secs(SERVER_CONFIG.runtime.stop_timeout_seconds),
+        summary: ReconcileSummary::default(),
+    };
+    let result = reconciler.run(event_cursor).await;
+
+    if let Err(e) = sqlx::query("SELECT RELEASE_LOCK(CONCAT('omni_reconciler:', DATABASE()))")
+        .execute(&mut *lock_conn)
+        .await
+    {
+        log::warn!("Failed to release reconciler lock of platform {}: {}", platform_id, e);
+    }
+
+    result.map(ReconcileRun::Completed)
+}
+
+struct Reconciler<'a> {
+    pool: &'a Pool<MySql>,
+    platform_id: i64,
+    driver: &'a dyn RuntimeDriver,
+    config: &'a ReconcilerConfig,
+    stop_timeout: Duration,
+    summary: ReconcileSummary,
+}
+
+impl Reconciler<'_> {
+    async fn run(mut self, event_cursor: &mut DateTime<Utc>) -> anyhow::Result<ReconcileSummary> {
+        // Docker's event API has a resolution of one second, so the window
+        // overlaps the previous one by a second; applying events is idempotent
+        let until = Utc::now();
+        match self.apply_runtime_events(*event_cursor - chrono::Duration::seconds(1), until).await {
+            Ok(()) => *event_cursor = until,
+            Err(e) => log::warn!("Failed to apply runtime events of platform {}: {:#}", self.platform_id, e),
+        }
+
+        let apps = db::app::list_apps_to_reconcile(self.pool).await?;
+        let instances = db::instance::list_unfinished_instances(self.pool).await?;
+        let containers = self
+            .driver
+            .list(self.platform_id)
+            .await
+            .with_context(|| format!("Failed to list containers of the {} runtime", self.driver.name()))?;
+
+        let by_id: HashMap<&str, &RuntimeContainer> = containers
+            .iter()
+            .map(|container| (container.container_id.as_str(), container))
+            .collect();
+
+        for app in &apps {
+            let mut desired = DesiredState::of(app);
+            if db::deployment::has_deployment_in_progress(self.pool, app.id).await? {
+                desired = desired.during_deployment();
+            }
+
+            let app_instances: Vec<&Instance> = instances.iter().filter(|instance| instance.app_id == app.id).collect();
+            let actions = plan::plan_app(desired, &app_instances, &by_id, self.config, Utc::now());
+            if actions.is_empty() {
+                continue;
+            }
+
+            self.summary.apps += 1;
+            let releases = Releases::load(self.pool, app.id).await?;
+            for action in actions {
+                self.apply(app, &releases, &app_instances, action).await;
+            }
+        }
+
+        for container in plan::orphaned_containers(&instances, &containers) {
+            self.remove_orphan(container).await;
+        }
+
+        match scheduler::release_finished_reservations(self.pool).await {
+            Ok(released) => self.summary.reservations_released += released,
+            Err(e) => {
+                self.summary.failures += 1;
+                log::error!("Failed to release reservations of platform {}: {:#}", self.platform_id, e);
+            }
+        }
+
+        Ok(self.summary)
+    }
+
+    /// Moves instances along as their containers start, exit or disappear.
+    async fn apply_runtime_events(&mut self, since: DateTime<Utc>, until: DateTime<Utc>) -> anyhow::Result<()> {
+        let events = self.driver.events(self.platform_id, since, until).await?;
+        let mut oom_killed = HashSet::new();
+
+        for event in events {
+            self.summary.runtime_events += 1;
+            let instance = match db::instance::get_instance_by_guid(self.pool, &event.instance_guid).await? {
+                Some(instance) => instance,
+                None => continue,
+            };
+
+            match event.kind {
+                RuntimeEventKind::Started => {
+                    let ip_address = match self.driver.inspect(&event.container_id).await {
+                        Ok(Some(container)) => container.ip_address,
+                        _ => None,
+                    };
+                    self.mark_running(event.app_id, &instance, &event.container_id, ip_address.as_deref()).await;
+                }
+                RuntimeEventKind::OomKilled => {
+                    oom_killed.insert(event.container_id);
+                }
+                RuntimeEventKind::Died { exit_code } => {
+                    let reason = if oom_killed.contains(&event.container_id) {
+                        "container was killed for running out of memory".to_string()
+                    } else {
+                        match exit_code {
+                            Some(code) => format!("container exited with code {}", code),
+                            None => "container exited".to_string(),
+                        }
+                    };
+                    self.mark_crashed(event.app_id, &instance, &event.container_id, exit_code, &reason).await;
+                }
+                RuntimeEventKind::Destroyed => {
+                    self.mark_crashed(event.app_id, &instance, &event.container_id, None, "container was removed").await;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn apply(&mut self, app: &App, releases: &Releases, instances: &[&Instance], action: Action) {
+        let find = |id: i64| instances.iter().copied().find(|instance| instance.id == id);
+
+        match action {
+            Action::Create { count } => {
+                for _ in 0..count {
+                    match db::instance::create_instance(self.pool, app.id, db::instance::DEFAULT_INSTANCE_TYPE, None).await {
+                        Ok(instance) => {
+                            self.summary.created += 1;
+                            self.record(app.id, Some(instance.id), "instance_created", "info",
+                                format!("Created instance {} to reach {} instances", instance.instance_index, app.instances.unwrap_or(1)),
+                                None).await;
+                            self.launch(app, releases.current.as_ref(), &instance, None).await;
+                        }
+                        Err(e) => {
+                            self.summary.failures += 1;
+                            self.record(app.id, None, "instance_create_failed", "error",
+                                format!("Failed to create instance: {:#}", e), None).await;
+                        }
+                    }
+                }
+            }
+            Action::Launch { instance_id } => {
+                if let Some(instance) = find(instance_id) {
+                    self.launch(app, releases.of(instance_id), instance, None).await;
+                }
+            }
+            Action::MarkRunning { instance_id, container_ip } => {
+                if let Some(instance) = find(instance_id) {
+                    if let Some(container_id) = instance.container_id.as_deref() {
+                        self.mark_running(app.id, instance, container_id, container_ip.as_deref()).await;
+                    }
+                }
+            }
+            Action::RecordHealth { instance_id, health_status } => {
+                if let Some(instance) = find(instance_id) {
+                    if let Some(container_id) = instance.container_id.as_deref() {
+                        self.record_health(app.id, instance, container_id, health_status).await;
+                    }
+                }
+            }
+            Action::MarkCrashed { instance_id, exit_code, reason } => {
+                if let Some(instance) = find(instance_id) {
+                    if let Some(container_id) = instance.container_id.as_deref() {
+                        self.mark_crashed(app.id, instance, container_id, exit_code, &reason).await;
+                    }
+                }
+            }
+            Action::Restart { instance_id, reason } => {
+                if let Some(instance) = find(instance_id) {
+                    if let Some(container_id) = instance.container_id.as_deref() {
+                        if let Err(e) = self.driver.remove(container_id).await {
+                            self.summary.failures += 1;
+                            self.record(app.id, Some(instance_id), "container_remove_failed", "error",
+                                format!("Failed to remove crashed container {}: {:#}", container_id, e), None).await;
+                            return;
+                        }
+                    }
+                    self.launch(app, releases.of(instance_id), instance, Some(&reason)).await;
+                }
+            }
+            Action::Terminate { instance_id, reason } => {
+                if let Some(instance) = find(instance_id) {
+                    self.terminate(app, instance, reason).await;
+                }
+            }
+        }
+    }
+
+    /// Launches the container of an instance. `restart_reason` is set when
+    /// the container replaces one that crashed.
+    async fn launch(&mut self, app: &App, deployment: Option<&Deployment>, instance: &Instance, restart_reason: Option<&str>) {
+        let node_id = match instance.node_id {
+            Some(node_id) => Some(node_id),
+            None => match self.schedule(app, instance).await {
+                Some(placement) => placement,
+                None => return,
+            },
+        };
+
+        let spec = InstanceSpec {
+            platform_id: self.platform_id,
+            app_id: app.id,
+            app_name: app.name.clone(),
+            instance_id: instance.id,
+            instance_guid: instance.guid.clone(),
+            instance_index: instance.instance_index,
+            image: app.container_image_url.clone(),
+            deployment_id: deployment.map(|deployment| deployment.id),
+            node_id,
+            environment: environment_of(deployment),
+        };
+
+        let container = match self.driver.launch(&spec).await {
+            Ok(container) => container,
+            Err(e) => {
+                self.summary.failures += 1;
+                self.record(app.id, Some(instance.id), "instance_launch_failed", "error",
+                    format!("Failed to launch instance {}: {:#}", instance.instance_index, e), None).await;
+                return;
+            }
+        };
+
+        if let Err(e) = db::instance::record_instance_launch(
+            self.pool,
+            instance.id,
+            &container.container_id,
+            container.ip_address.as_deref(),
+            restart_reason,
+        )
+        .await
+        {
+            // Without the row pointing at it the container is an orphan
+            // and the next pass removes it
+            self.summary.failures += 1;
+            log::error!("Failed to record launch of instance {}: {:#}", instance.id, e);
+            return;
+        }
+
+        let metadata = Some(json!({
+            "container_id": container.container_id,
+            "driver": self.driver.name(),
+            "deployment_id": spec.deployment_id,
+            "worker_id": spec.node_id
+        }));
+        match restart_reason {
+            Some(reason) => {
+                self.summary.restarted += 1;
+                self.record(app.id, Some(instance.id), "instance_restarted", "info",
+                    format!("Restarted instance {} after {}", instance.instance_index, reason), metadata).await;
+            }
+            None => {
+                self.summary.launched += 1;
+                self.record(app.id, Some(instance.id), "instance_launched", "info",
+                    format!("Launched instance {}", instance.instance_index), metadata).await;
+            }
+        }
+    }
+
+    /// Places an instance on a worker. Returns the worker, `Some(None)` when
+    /// the platform has no workers to place it on, or `None` if the instance
+    /// cannot be launched yet.
+    async fn schedule(&mut self, app: &App, instance: &Instance) -> Option<Option<i64>> {
+        match scheduler::schedule_instance(self.pool, app, instance, &SERVER_CONFIG.scheduler).await {
+            Ok(Placement::Placed { worker_id }) => Some(Some(worker_id)),
+            Ok(Placement::Unplaced) => Some(None),
+            Ok(Placement::Unschedulable { reason }) => {
+                self.summary.unschedulable += 1;
+                // The instance is retried on every pass; only report it when
+                // the reason changes
+                let previous = instance
+                    .scheduler_metadata
+                    .as_ref()
+                    .filter(|metadata| metadata["decision"] == "unschedulable")
+                    .and_then(|metadata| metadata["reason"].as_str());
+                if previous != Some(reason.as_str()) {
+                    self.record(app.id, Some(instance.id), "instance_unschedulable", "warning",
+                        format!("Instance {} cannot be scheduled: {}", instance.instance_index, reason), None).await;
+                }
+                None
+            }
+            Err(e) => {
+                self.summary.failures += 1;
+                self.record(app.id, Some(instance.id), "instance_schedule_failed", "error",
+                    format!("Failed to schedule instance {}: {:#}", instance.instance_index, e), None).await;
+                None
+            }
+        }
+    }
+
+    async fn mark_running(&mut self, app_id: i64, instance: &Instance, container_id: &str, container_ip: Option<&str>) {
+        match db::instance::record_instance_running(self.pool, instance.id, container_id, container_ip).await {
+            Ok(true) => {
+                self.summary.running += 1;
+                self.record(app_id, Some(instance.id), "instance_running", "info",
+                    format!("Instance {} is running", instance.instance_index),
+                    Some(json!({ "container_id": container_id }))).await;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                self.summary.failures += 1;
+                log::error!("Failed to record instance {} running: {:#}", instance.id, e);
+            }
+        }
+    }
+
+    async fn record_health(&mut self, app_id: i64, instance: &Instance, container_id: &str, health_status: &str) {
+        match db::instance::record_instance_health(self.pool, instance.id, container_id, health_status).await {
+            Ok(true) if health_status == "critical" => {
+                self.summary.unhealthy += 1;
+                self.record(app_id, Some(instance.id), "instance_unhealthy", "warning",
+                    format!("Instance {} failed its health check", instance.instance_index),
+                    Some(json!({ "container_id": container_id }))).await;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                self.summary.failures += 1;
+                log::error!("Failed to record health of instance {}: {:#}", instance.id, e);
+            }
+        }
+    }
+
+    async fn mark_crashed(&mut self, app_id: i64, instance: &Instance, container_id: &str, exit_code: Option<i64>, reason: &str) {
+        match db::instance::record_instance_exit(self.pool, instance.id, container_id, exit_code, reason).await {
+            Ok(true) => {
+                self.summary.crashed += 1;
+                self.record(app_id, Some(instance.id), "instance_crashed", "warning",
+                    format!("Instance {} crashed: {}", instance.instance_index, reason),
+                    Some(json!({ "container_id": container_id, "exit_code": exit_code }))).await;
+            }
+            Ok(false) => {}
+            Err(e) => {
+                self.summary.failures += 1;
+                log::error!("Failed to record crash of instance {}: {:#}", instance.id, e);
+            }
+        }
+    }
+
+    async fn terminate(&mut self, app: &App, instance: &Instance, reason: &str) {
+        if let Some(container_id) = instance.container_id.as_deref() {
+            let stopped = match self.driver.stop(container_id, self.stop_timeout).await {
+                Ok(()) => self.driver.remove(container_id).await,
+                Err(e) => Err(e),
+            };
+            if let Err(e) = stopped {
+                self.summary.failures += 1;
+                self.record(app.id, Some(instance.id), "container_remove_failed", "error",
+                    format!("Failed to remove container {}: {:#}", container_id, e), None).await;
+                return;
+            }
+        }
+
+        match db::instance::update_instance_status(self.pool, instance.id, "terminated", None, None).await {
+            Ok(_) => {
+                self.summary.terminated += 1;
+                self.record(app.id, Some(instance.id), "instance_terminated", "info",
+                    format!("Terminated instance {}: {}", instance.instance_index, reason), None).await;
+            }
+            Err(e) => {
+                self.summary.failures += 1;
+                log::error!("Failed to terminate instance {}: {:#}", instance.id, e);
+            }
+        }
+    }
+
+    async fn remove_orphan(&mut self, container: &RuntimeContainer) {
+        if let Err(e) = self.driver.remove(&container.container_id).await {
+            self.summary.failures += 1;
+            log::error!("Failed to remove orphaned container {}: {:#}", container.container_id, e);
+            return;
+        }
+
+        self.summary.orphans_removed += 1;
+        self.record(container.app_id, None, "container_removed", "info",
+            format!("Removed container {} of instance {}, which should not be running", container.container_id, container.instance_guid),
+            None).await;
+    }
+
+    async fn record(
+        &self,
+        app_id: i64,
+        instance_id: Option<i64>,
+        event_type: &'static str,
+        severity: &'static str,
+        message: String,
+        metadata: Option<serde_json::Value>,
+    ) {
+        let event = NewAppEvent { app_id, instance_id, event_type, severity, message, source: "reconciler", metadata };
+        if let Err(e) = db::app_event::insert_app_event(self.pool, &event).await {
+            log::warn!("Failed to record {} event of app {}: {:#}", event_type, app_id, e);
+        }
+    }
+}
+
+/// The deployments whose releases the instances of an application run.
+struct Releases {
+    /// The application's current deployment, run by instances that do not
+    /// belong to a particular deployment
+    current: Option<Deployment>,
+    /// Deployments by ID
+    deployments: HashMap<i64, Deployment>,
+    /// Deployment of each instance that belongs to one
+    instances: HashMap<i64, i64>,
+}
+
+impl Releases {
+    async fn load(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Self> {
+        let current = db::deployment::get_current_deployment(pool, app_id).await?;
+        let mut deployments = HashMap::new();
+        let mut instances = HashMap::new();
+
+        for instance in db::deployment::list_deployment_instances(pool, app_id).await? {
+            let deployment_id = match instance.deployment_id {
+                Some(deployment_id) => deployment_id,
+                None => continue,
+            };
+            if !deployments.contains_key(&deployment_id) {
+                let deployment = db::deployment::get_deployment_by_id(pool, deployment_id).await?;
+                deployments.insert(deployment_id, deployment);
+            }
+            instances.insert(instance.id, deployment_id);
+        }
+
+        Ok(Self { current, deployments, instances })
+    }
+
+    /// The deployment whose release an instance runs.
+    fn of(&self, instance_id: i64) -> Option<&Deployment> {
+        match self.instances.get(&instance_id) {
+            Some(deployment_id) => self.deployments.get(deployment_id),
+            None => self.current.as_ref(),
+        }
+    }
+}
+
+/// Environment variables of a deployment, which are stored as a JSON object.
+pub(crate) fn environment_of(deployment: Option<&Deployment>) -> HashMap<String, String> {
+    let variables = match deployment.and_then(|deployment| deployment.environment_variables.as_ref()) {
+        Some(serde_json::Value::Object(variables)) => variables,
+        _ => return HashMap::new(),
+    };
+
+    variables
+        .iter()
+        .map(|(key, value)| {
+            let value = match value {
+                serde_json::Value::String(value) => value.clone(),
+                other => other.to_string(),
+            };
+            (key.clone(), value)
+        })
+        .collect()
+}
@@ -0,0 +1,408 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use libomni::types::db::v1 as types;
+use types::app::App;
+use types::instance::Instance;
+
+use crate::config::ReconcilerConfig;
+use crate::runtime::{ContainerState, RuntimeContainer};
+
+/// What to do with an instance whose container exited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestartPolicy {
+    /// Always replace the container
+    Always,
+    /// Replace the container unless it exited with code 0
+    OnFailure,
+    /// Leave the instance crashed
+    Never,
+}
+
+impl RestartPolicy {
+    /// Parses `apps.restart_policy`, which defaults to `always`.
+    pub fn parse(value: Option<&str>) -> Self {
+        match value {
+            Some("on-failure") => RestartPolicy::OnFailure,
+            Some("no") => RestartPolicy::Never,
+            _ => RestartPolicy::Always,
+        }
+    }
+
+    fn restarts(self, exit_code: Option<i64>) -> bool {
+        match self {
+            RestartPolicy::Always => true,
+            RestartPolicy::OnFailure => exit_code != Some(0),
+            RestartPolicy::Never => false,
+        }
+    }
+}
+
+/// What an application should be running.
+#[derive(Debug, Clone, Copy)]
+pub struct DesiredState {
+    /// Number of instances that should exist, or `None` while a deployment
+    /// decides which instances exist
+    pub instances: Option<i64>,
+    /// How crashed instances are handled
+    pub restart_policy: RestartPolicy,
+}
+
+impl DesiredState {
+    /// Derives the desired state of an application. Applications that are
+    /// stopped or deleted should have no instances at all.
+    pub fn of(app: &App) -> Self {
+        let running = app.deleted_at.is_none()
+            && matches!(app.status.as_deref(), Some("started" | "starting" | "crashed"));
+
+        Self {
+            instances: Some(if running { app.instances.unwrap_or(1).max(0) } else { 0 }),
+            restart_policy: RestartPolicy::parse(app.restart_policy.as_deref()),
+        }
+    }
+
+    /// The desired state while a deployment is rolled out: the deployment
+    /// creates and stops instances, so none are created or terminated to
+    /// match the instance count.
+    pub fn during_deployment(self) -> Self {
+        Self { instances: None, ..self }
+    }
+}
+
+/// A step that moves the actual state towards the desired state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Action {
+    /// Create new instances and launch their containers
+    Create { count: i64 },
+    /// Launch the container of an instance that has none yet
+    Launch { instance_id: i64 },
+    /// Record that the container of a starting instance is running
+    MarkRunning { instance_id: i64, container_ip: Option<String> },
+    /// Record the result of the health check of an instance's container
+    RecordHealth { instance_id: i64, health_status: &'static str },
+    /// Record that the container of an instance exited or vanished
+    MarkCrashed { instance_id: i64, exit_code: Option<i64>, reason: String },
+    /// Replace the container of a crashed instance
+    Restart { instance_id: i64, reason: String },
+    /// Remove the container of an instance and terminate it
+    Terminate { instance_id: i64, reason: &'static str },
+}
+
+/// Compares the desired state of an application with its instances and the
+/// containers the runtime reports for them, and returns what has to change.
+///
+/// `instances` are the application's instances that have not been stopped
+/// or terminated, ordered by index; `containers` are keyed by container ID.
+/// Runtime events have normally been applied to the instances already, so
+/// comparing with the containers only catches up on missed events.
+///
+/// Surplus instances are terminated highest index first. A crashed
+/// container is only replaced once the instance has been recorded as
+/// crashed and its restart backoff has passed.
+pub fn plan_app(
+    desired: DesiredState,
+    instances: &[&Instance],
+    containers: &HashMap<&str, &RuntimeContainer>,
+    config: &ReconcilerConfig,
+    now: DateTime<Utc>,
+) -> Vec<Action> {
+    let mut actions = Vec::new();
+
+    let (stopping, mut kept): (Vec<&Instance>, Vec<&Instance>) = instances
+        .iter()
+        .copied()
+        .partition(|instance| instance.status.as_deref() == Some("stopping"));
+
+    for instance in stopping {
+        actions.push(Action::Terminate { instance_id: instance.id, reason: "stop requested" });
+    }
+
+    if let Some(desired_count) = desired.instances {
+        let desired_count = desired_count.max(0) as usize;
+        if kept.len() > desired_count {
+            let reason = if desired_count == 0 { "application stopped" } else { "scaled down" };
+            for instance in kept.drain(desired_count..).rev() {
+                actions.push(Action::Terminate { instance_id: instance.id, reason });
+            }
+        } else if kept.len() < desired_count {
+            actions.push(Action::Create { count: (desired_count - kept.len()) as i64 });
+        }
+    }
+
+    for instance in kept {
+        if instance.status.as_deref() == Some("crashed") {
+            if desired.restart_policy.restarts(instance.exit_code) && backoff_passed(instance, config, now) {
+                let reason = match instance.exit_code {
+                    Some(code) => format!("container exited with code {}", code),
+                    None => "container disappeared".to_string(),
+                };
+                actions.push(Action::Restart { instance_id: instance.id, reason });
+            }
+            continue;
+        }
+
+        let container = match instance.container_id.as_deref() {
+            Some(container_id) => containers.get(container_id).copied(),
+            None => {
+                actions.push(Action::Launch { instance_id: instance.id });
+                continue;
+            }
+        };
+
+        match container.map(|container| &container.state) {
+            None => actions.push(Action::MarkCrashed {
+                instance_id: instance.id,
+                exit_code: None,
+                reason: "container disappeared".to_string(),
+            }),
+            Some(ContainerState::Exited { exit_code }) => actions.push(Action::MarkCrashed {
+                instance_id: instance.id,
+                exit_code: Some(*exit_code),
+                reason: format!("container exited with code {}", exit_code),
+            }),
+            // Normally the runtime's start event does this; this catches
+            // events that were missed, such as while leadership changed
+            Some(ContainerState::Running) if instance.status.as_deref() == Some("starting") => {
+                actions.push(Action::MarkRunning {
+                    instance_id: instance.id,
+                    container_ip: container.and_then(|container| container.ip_address.clone()),
+                })
+            }
+            Some(ContainerState::Created | ContainerState::Running) => {}
+        }
+
+        if let Some(health) = container.and_then(|container| container.health) {
+            if matches!(container.map(|container| &container.state), Some(ContainerState::Running))
+                && instance.health_status.as_deref() != Some(health.instance_status())
+            {
+                actions.push(Action::RecordHealth { instance_id: instance.id, health_status: health.instance_status() });
+            }
+        }
+    }
+
+    actions
+}
+
+/// Returns the containers no instance that should exist is using, such as
+/// those of instances terminated through the API or containers left behind
+/// when an instance was restarted. Containers of tasks belong to the task
+/// runner and are never orphaned.
+pub fn orphaned_containers<'a>(
+    instances: &[Instance],
+    containers: &'a [RuntimeContainer],
+) -> Vec<&'a RuntimeContainer> {
+    let in_use: HashSet<&str> = instances
+        .iter()
+        .filter_map(|instance| instance.container_id.as_deref())
+        .collect();
+    containers
+        .iter()
+        .filter(|container| container.task_id.is_none() && !in_use.contains(container.container_id.as_str()))
+        .collect()
+}
+
+/// Whether enough time has passed since an instance crashed to restart it.
+/// The delay doubles with every restart, up to the configured maximum.
+fn backoff_passed(instance: &Instance, config: &ReconcilerConfig, now: DateTime<Utc>) -> bool {
+    let stopped_at = match instance.stop_time {
+        Some(stopped_at) => stopped_at,
+        None => return true,
+    };
+
+    let restarts = instance.restart_count.unwrap_or(0).clamp(0, 16) as u32;
+    let delay = config
+        .restart_backoff_seconds
+        .saturating_mul(1u64 << restarts)
+        .min(config.max_restart_backoff_seconds);
+
+    now - stopped_at >= Duration::seconds(delay as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use chrono::{Duration, Utc};
+    use serde_json::json;
+
+    use super::{plan_app, Action, DesiredState, Instance, RestartPolicy};
+    use crate::config::ReconcilerConfig;
+    use crate::runtime::{ContainerHealth, ContainerState, RuntimeContainer};
+
+    /// An instance with the given status whose container is `container-<id>`.
+    fn instance(id: i64, status: &str) -> Instance {
+        serde_json::from_value(json!({
+            "id": id,
+            "app_id": 1,
+            "instance_type": "standard",
+            "guid": format!("guid-{}", id),
+            "status": status,
+            "container_id": format!("container-{}", id),
+            "instance_index": id,
+            "health_status": "unknown",
+            "uptime": 0,
+            "restart_count": 0,
+        }))
+        .expect("instance fixture should deserialize")
+    }
+
+    fn container(id: i64, state: ContainerState) -> RuntimeContainer {
+        RuntimeContainer {
+            container_id: format!("container-{}", id),
+            platform_id: 1,
+            app_id: 1,
+            instance_guid: format!("guid-{}", id),
+            task_id: None,
+            ip_address: Some(format!("10.0.0.{}", id)),
+            state,
+            health: None,
+        }
+    }
+
+    fn desired(instances: Option<i64>, restart_policy: RestartPolicy) -> DesiredState {
+        DesiredState { instances, restart_policy }
+    }
+
+    fn config() -> ReconcilerConfig {
+        ReconcilerConfig { restart_backoff_seconds: 5, max_restart_backoff_seconds: 60, ..Default::default() }
+    }
+
+    /// Plans with every instance's container running.
+    fn plan_running(desired: DesiredState, instances: &[Instance]) -> Vec<Action> {
+        let containers: Vec<RuntimeContainer> =
+            instances.iter().map(|instance| container(instance.id, ContainerState::Running)).collect();
+        plan(desired, instances, &containers)
+    }
+
+    fn plan(desired: DesiredState, instances: &[Instance], containers: &[RuntimeContainer]) -> Vec<Action> {
+        let instances: Vec<&Instance> = instances.iter().collect();
+        let containers: HashMap<&str, &RuntimeContainer> =
+            containers.iter().map(|container| (container.container_id.as_str(), container)).collect();
+        plan_app(desired, &instances, &containers, &config(), Utc::now())
+    }
+
+    #[test]
+    fn creates_missing_instances_when_scaled_up() {
+        let instances = [instance(1, "running")];
+        let actions = plan_running(desired(Some(3), RestartPolicy::Always), &instances);
+        assert_eq!(actions, vec![Action::Create { count: 2 }]);
+    }
+
+    #[test]
+    fn terminates_highest_index_first_when_scaled_down() {
+        let instances = [instance(1, "running"), instance(2, "running"), instance(3, "running")];
+        let actions = plan_running(desired(Some(1), RestartPolicy::Always), &instances);
+        assert_eq!(actions, vec![
+            Action::Terminate { instance_id: 3, reason: "scaled down" },
+            Action::Terminate { instance_id: 2, reason: "scaled down" },
+        ]);
+    }
+
+    #[test]
+    fn terminates_every_instance_of_a_stopped_app() {
+        let instances = [instance(1, "running"), instance(2, "running")];
+        let actions = plan_running(desired(Some(0), RestartPolicy::Always), &instances);
+        assert_eq!(actions, vec![
+            Action::Terminate { instance_id: 2, reason: "application stopped" },
+            Action::Terminate { instance_id: 1, reason: "application stopped" },
+        ]);
+    }
+
+    #[test]
+    fn stopping_instances_do_not_count_towards_the_desired_count() {
+        let instances = [instance(1, "stopping"), instance(2, "running")];
+        let actions = plan_running(desired(Some(2), RestartPolicy::Always), &instances);
+        assert_eq!(actions, vec![
+            Action::Terminate { instance_id: 1, reason: "stop requested" },
+            Action::Create { count: 1 },
+        ]);
+    }
+
+    #[test]
+    fn leaves_the_instance_count_alone_during_deployments() {
+        let instances = [instance(1, "running"), instance(2, "running")];
+        let during_deployment = desired(Some(5), RestartPolicy::Always).during_deployment();
+        assert_eq!(plan_running(during_deployment, &instances), vec![]);
+    }
+
+    #[test]
+    fn restarts_crashed_instances_by_restart_policy() {
+        let mut failed = instance(1, "crashed");
+        failed.exit_code = Some(1);
+        let mut succeeded = instance(2, "crashed");
+        succeeded.exit_code = Some(0);
+        let instances = [failed, succeeded];
+
+        let actions = plan(desired(Some(2), RestartPolicy::Always), &instances, &[]);
+        assert_eq!(actions, vec![
+            Action::Restart { instance_id: 1, reason: "container exited with code 1".to_string() },
+            Action::Restart { instance_id: 2, reason: "container exited with code 0".to_string() },
+        ]);
+
+        let actions = plan(desired(Some(2), RestartPolicy::OnFailure), &instances, &[]);
+        assert_eq!(actions, vec![
+            Action::Restart { instance_id: 1, reason: "container exited with code 1".to_string() },
+        ]);
+
+        assert_eq!(plan(desired(Some(2), RestartPolicy::Never), &instances, &[]), vec![]);
+    }
+
+    #[test]
+    fn waits_for_the_restart_backoff() {
+        let crashed = |restart_count: i64, seconds_ago: i64| {
+            let mut crashed = instance(1, "crashed");
+            crashed.exit_code = Some(1);
+            crashed.restart_count = Some(restart_count);
+            crashed.stop_time = Some(Utc::now() - Duration::seconds(seconds_ago));
+            [crashed]
+        };
+        let restarts = |instances: [Instance; 1]| {
+            plan(desired(Some(1), RestartPolicy::Always), &instances, &[])
+                .iter()
+                .any(|action| matches!(action, Action::Restart { .. }))
+        };
+
+        // The delay doubles with every restart: 5s, then 10s, then 20s
+        assert!(!restarts(crashed(0, 3)));
+        assert!(restarts(crashed(0, 6)));
+        assert!(!restarts(crashed(2, 15)));
+        assert!(restarts(crashed(2, 25)));
+        // and is capped at the configured maximum
+        assert!(!restarts(crashed(10, 50)));
+        assert!(restarts(crashed(10, 65)));
+    }
+
+    #[test]
+    fn launches_instances_without_a_container() {
+        let mut unlaunched = instance(1, "starting");
+        unlaunched.container_id = None;
+        let actions = plan(desired(Some(1), RestartPolicy::Always), &[unlaunched], &[]);
+        assert_eq!(actions, vec![Action::Launch { instance_id: 1 }]);
+    }
+
+    #[test]
+    fn catches_up_on_missed_runtime_events() {
+        let mut healthy = container(4, ContainerState::Running);
+        healthy.health = Some(ContainerHealth::Healthy);
+        // Instance 1 started, instance 2's container vanished, instance 3's
+        // exited and instance 4 passed its health check
+        let instances = [instance(1, "starting"), instance(2, "running"), instance(3, "running"), instance(4, "running")];
+        let containers = [
+            container(1, ContainerState::Running),
+            container(3, ContainerState::Exited { exit_code: 137 }),
+            healthy,
+        ];
+
+        let actions = plan(desired(Some(4), RestartPolicy::Always), &instances, &containers);
+        assert_eq!(actions, vec![
+            Action::MarkRunning { instance_id: 1, container_ip: Some("10.0.0.1".to_string()) },
+            Action::MarkCrashed { instance_id: 2, exit_code: None, reason: "container disappeared".to_string() },
+            Action::MarkCrashed {
+                instance_id: 3,
+                exit_code: Some(137),
+                reason: "container exited with code 137".to_string(),
+            },
+            Action::RecordHealth { instance_id: 4, health_status: "healthy" },
+        ]);
+    }
+}
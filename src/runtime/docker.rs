@@ -0,0 +1,546 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, TimeZone, Utc};
+use serde_json::{json, Value};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::UnixStream;
+
+use super::{
+    ContainerHealth, ContainerState, InstanceSpec, LogLine, LogStream, RuntimeContainer, RuntimeDriver, RuntimeEvent,
+    RuntimeEventKind, TaskSpec, APP_LABEL, INSTANCE_GUID_LABEL, PLATFORM_LABEL, TASK_LABEL,
+};
+
+/// Docker Engine API version requested, supported since Docker 20.10.
+const API_VERSION: &str = "v1.41";
+
+/// Time allowed for API calls that do not wait on containers.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Time allowed for pulling an image.
+const PULL_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// Runtime driver for the Docker Engine API, spoken over the engine's Unix
+/// socket.
+///
+/// Requests are plain HTTP/1.1 with one connection per request, so no state
+/// is kept between calls and the driver can be shared freely. Images are
+/// pulled when a container is created from one that is not present yet.
+#[derive(Debug, Clone)]
+pub struct DockerDriver {
+    socket_path: PathBuf,
+}
+
+/// A response from the Docker Engine API.
+struct Response {
+    status: u16,
+    body: Vec<u8>,
+}
+
+impl Response {
+    fn json(&self) -> anyhow::Result<Value> {
+        serde_json::from_slice(&self.body).context("Docker returned invalid JSON")
+    }
+
+    /// Fails with the error message Docker returned, unless the status is
+    /// one of `accepted`.
+    fn expect(self, accepted: &[u16], operation: &str) -> anyhow::Result<Self> {
+        if accepted.contains(&self.status) {
+            return Ok(self);
+        }
+
+        let message = serde_json::from_slice::<Value>(&self.body)
+            .ok()
+            .and_then(|body| body["message"].as_str().map(str::to_string))
+            .unwrap_or_else(|| String::from_utf8_lossy(&self.body).trim().to_string());
+        anyhow::bail!("Failed to {}: Docker answered {}: {}", operation, self.status, message)
+    }
+}
+
+impl DockerDriver {
+    pub fn new(socket_path: &str) -> Self {
+        Self {
+            socket_path: PathBuf::from(socket_path),
+        }
+    }
+
+    async fn request(&self, method: &str, path: &str, body: Option<&Value>, timeout: Duration) -> anyhow::Result<Response> {
+        let body = match body {
+            Some(body) => serde_json::to_vec(body)?,
+            None => Vec::new(),
+        };
+
+        let exchange = async {
+            let mut stream = UnixStream::connect(&self.socket_path)
+                .await
+                .with_context(|| format!("Failed to connect to Docker at {}", self.socket_path.display()))?;
+
+            let mut head = format!("{} /{}{} HTTP/1.1\r\nHost: docker\r\nConnection: close\r\n", method, API_VERSION, path);
+            if method != "GET" {
+                head.push_str(&format!("Content-Type: application/json\r\nContent-Length: {}\r\n", body.len()));
+            }
+            head.push_str("\r\n");
+
+            stream.write_all(head.as_bytes()).await?;
+            stream.write_all(&body).await?;
+
+            let mut raw = Vec::new();
+            stream.read_to_end(&mut raw).await?;
+            anyhow::Ok(raw)
+        };
+
+        let raw = tokio::time::timeout(timeout, exchange)
+            .await
+            .with_context(|| format!("Docker did not answer {} {} in time", method, path))??;
+        parse_response(&raw)
+    }
+
+    /// Creates a container named `name`, pulling its image first if it is
+    /// not present.
+    async fn create_container(&self, image: &str, name: &str, body: &Value) -> anyhow::Result<String> {
+        let path = format!("/containers/create?name={}", encode(name));
+
+        let mut response = self.request("POST", &path, Some(body), REQUEST_TIMEOUT).await?;
+        if response.status == 404 {
+            self.pull(image).await?;
+            response = self.request("POST", &path, Some(body), REQUEST_TIMEOUT).await?;
+        }
+
+        let created = response.expect(&[201], "create container")?.json()?;
+        created["Id"]
+            .as_str()
+            .map(str::to_string)
+            .context("Docker did not return the ID of the created container")
+    }
+
+    async fn pull(&self, image: &str) -> anyhow::Result<()> {
+        let (name, tag) = split_image(image);
+        let path = format!("/images/create?fromImage={}&tag={}", encode(name), encode(tag));
+        let response = self
+            .request("POST", &path, None, PULL_TIMEOUT)
+            .await?
+            .expect(&[200], &format!("pull image {}", image))?;
+
+        // Progress is streamed as JSON objects; failures are reported in
+        // one of them rather than through the status code
+        for message in serde_json::Deserializer::from_slice(&response.body).into_iter::<Value>() {
+            if let Some(error) = message.ok().and_then(|message| message["error"].as_str().map(str::to_string)) {
+                anyhow::bail!("Failed to pull image {}: {}", image, error);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[rocket::async_trait]
+impl RuntimeDriver for DockerDriver {
+    fn name(&self) -> &'static str {
+        "docker"
+    }
+
+    async fn ping(&self) -> anyhow::Result<String> {
+        let version = self
+            .request("GET", "/version", None, REQUEST_TIMEOUT)
+            .await?
+            .expect(&[200], "query the Docker version")?
+            .json()?;
+        Ok(format!(
+            "Docker {} (API {})",
+            version["Version"].as_str().unwrap_or("unknown"),
+            version["ApiVersion"].as_str().unwrap_or("unknown")
+        ))
+    }
+
+    async fn create(&self, spec: &InstanceSpec) -> anyhow::Result<String> {
+        let image = spec
+            .image
+            .as_deref()
+            .with_context(|| format!("App {} has no container image to run", spec.app_id))?;
+
+        let mut environment: Vec<String> = spec.environment.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+        environment.sort();
+
+        let body = json!({
+            "Image": image,
+            "Env": environment,
+            "Labels": spec.labels(),
+            "HostConfig": {
+                // Restarts are the reconciler's business
+                "RestartPolicy": { "Name": "no" }
+            }
+        });
+        self.create_container(image, &container_name(spec), &body).await
+    }
+
+    async fn create_task(&self, spec: &TaskSpec) -> anyhow::Result<String> {
+        let mut environment: Vec<String> = spec.environment.iter().map(|(key, value)| format!("{}={}", key, value)).collect();
+        environment.sort();
+
+        let mut host_config = json!({ "RestartPolicy": { "Name": "no" } });
+        if let Some(memory_in_mb) = spec.memory_in_mb {
+            host_config["Memory"] = json!(memory_in_mb * 1024 * 1024);
+        }
+        if let Some(cpu) = spec.cpu {
+            host_config["NanoCpus"] = json!((cpu * 1_000_000_000.0) as i64);
+        }
+
+        let body = json!({
+            "Image": spec.image,
+            "Entrypoint": ["/bin/sh", "-c"],
+            "Cmd": [spec.command],
+            "Env": environment,
+            "Labels": spec.labels(),
+            "HostConfig": host_config
+        });
+        self.create_container(&spec.image, &task_container_name(spec), &body).await
+    }
+
+    async fn start(&self, container_id: &str) -> anyhow::Result<()> {
+        self.request("POST", &format!("/containers/{}/start", encode(container_id)), None, REQUEST_TIMEOUT)
+            .await?
+            .expect(&[204, 304], "start container")?;
+        Ok(())
+    }
+
+    async fn stop(&self, container_id: &str, timeout: Duration) -> anyhow::Result<()> {
+        let path = format!("/containers/{}/stop?t={}", encode(container_id), timeout.as_secs());
+        self.request("POST", &path, None, REQUEST_TIMEOUT + timeout)
+            .await?
+            .expect(&[204, 304, 404], "stop container")?;
+        Ok(())
+    }
+
+    async fn remove(&self, container_id: &str) -> anyhow::Result<()> {
+        let path = format!("/containers/{}?force=true&v=true", encode(container_id));
+        self.request("DELETE", &path, None, REQUEST_TIMEOUT)
+            .await?
+            .expect(&[204, 404], "remove container")?;
+        Ok(())
+    }
+
+    async fn inspect(&self, container_id: &str) -> anyhow::Result<Option<RuntimeContainer>> {
+        let response = self
+            .request("GET", &format!("/containers/{}/json", encode(container_id)), None, REQUEST_TIMEOUT)
+            .await?;
+        if response.status == 404 {
+            return Ok(None);
+        }
+
+        let details = response.expect(&[200], "inspect container")?.json()?;
+        let state = match details["State"]["Status"].as_str() {
+            Some("running" | "paused" | "restarting") => ContainerState::Running,
+            Some("created") => ContainerState::Created,
+            _ => ContainerState::Exited {
+                exit_code: details["State"]["ExitCode"].as_i64().unwrap_or(-1),
+            },
+        };
+
+        Ok(container_from(
+            details["Id"].as_str().unwrap_or(container_id),
+            &details["Config"]["Labels"],
+            &details["NetworkSettings"],
+            state,
+            details["State"]["Health"]["Status"].as_str().and_then(ContainerHealth::parse),
+        ))
+    }
+
+    async fn logs(&self, container_id: &str, tail: Option<u64>) -> anyhow::Result<Vec<LogLine>> {
+        let tail = tail.map(|tail| tail.to_string()).unwrap_or_else(|| "all".to_string());
+        let path = format!(
+            "/containers/{}/logs?stdout=true&stderr=true&timestamps=true&tail={}",
+            encode(container_id),
+            tail
+        );
+        let response = self
+            .request("GET", &path, None, REQUEST_TIMEOUT)
+            .await?
+            .expect(&[200], "fetch container logs")?;
+        Ok(demultiplex_logs(&response.body))
+    }
+
+    async fn list(&self, platform_id: i64) -> anyhow::Result<Vec<RuntimeContainer>> {
+        let filters = json!({ "label": [format!("{}={}", PLATFORM_LABEL, platform_id)] });
+        let path = format!("/containers/json?all=true&filters={}", encode(&filters.to_string()));
+        let listed = self
+            .request("GET", &path, None, REQUEST_TIMEOUT)
+            .await?
+            .expect(&[200], "list containers")?
+            .json()?;
+
+        let containers = listed
+            .as_array()
+            .map(|containers| {
+                containers
+                    .iter()
+                    .filter_map(|container| {
+                        let state = match container["State"].as_str() {
+                            Some("running" | "paused" | "restarting") => ContainerState::Running,
+                            Some("created") => ContainerState::Created,
+                            _ => ContainerState::Exited {
+                                exit_code: exit_code_of_status(container["Status"].as_str().unwrap_or("")).unwrap_or(-1),
+                            },
+                        };
+                        container_from(
+                            container["Id"].as_str()?,
+                            &container["Labels"],
+                            &container["NetworkSettings"],
+                            state,
+                            health_of_status(container["Status"].as_str().unwrap_or("")),
+                        )
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok(containers)
+    }
+
+    async fn events(
+        &self,
+        platform_id: i64,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<RuntimeEvent>> {
+        let filters = json!({
+            "type": ["container"],
+            "event": ["start", "die", "oom", "destroy"],
+            "label": [format!("{}={}", PLATFORM_LABEL, platform_id)]
+        });
+        let path = format!(
+            "/events?since={}&until={}&filters={}",
+            since.timestamp(),
+            until.timestamp(),
+            encode(&filters.to_string())
+        );
+        let response = self
+            .request("GET", &path, None, REQUEST_TIMEOUT)
+            .await?
+            .expect(&[200], "fetch container events")?;
+
+        let mut events = Vec::new();
+        for message in serde_json::Deserializer::from_slice(&response.body).into_iter::<Value>() {
+            let message = message.context("Docker returned an invalid event")?;
+            let attributes = &message["Actor"]["Attributes"];
+            let kind = match message["Action"].as_str() {
+                Some("start") => RuntimeEventKind::Started,
+                Some("die") => RuntimeEventKind::Died {
+                    exit_code: attributes["exitCode"].as_str().and_then(|code| code.parse().ok()),
+                },
+                Some("oom") => RuntimeEventKind::OomKilled,
+                Some("destroy") => RuntimeEventKind::Destroyed,
+                _ => continue,
+            };
+
+            let (container_id, app_id, instance_guid) = match (
+                message["Actor"]["ID"].as_str(),
+                attributes[APP_LABEL].as_str().and_then(|id| id.parse().ok()),
+                attributes[INSTANCE_GUID_LABEL].as_str(),
+            ) {
+                (Some(container_id), Some(app_id), Some(guid)) => (container_id, app_id, guid),
+                _ => continue,
+            };
+
+            let time = message["timeNano"]
+                .as_i64()
+                .map(|nanos| Utc.timestamp_nanos(nanos))
+                .or_else(|| message["time"].as_i64().and_then(|secs| Utc.timestamp_opt(secs, 0).single()))
+                .unwrap_or(until);
+
+            events.push(RuntimeEvent {
+                container_id: container_id.to_string(),
+                app_id,
+                instance_guid: instance_guid.to_string(),
+                kind,
+                time,
+            });
+        }
+        Ok(events)
+    }
+}
+
+/// Builds a container from its labels, or `None` if it was not created by
+/// the orchestrator.
+fn container_from(
+    container_id: &str,
+    labels: &Value,
+    network: &Value,
+    state: ContainerState,
+    health: Option<ContainerHealth>,
+) -> Option<RuntimeContainer> {
+    let ip_address = network["IPAddress"]
+        .as_str()
+        .filter(|ip| !ip.is_empty())
+        .or_else(|| {
+            network["Networks"]
+                .as_object()?
+                .values()
+                .filter_map(|network| network["IPAddress"].as_str())
+                .find(|ip| !ip.is_empty())
+        })
+        .map(str::to_string);
+
+    // Containers of tasks are not tied to an instance
+    let task_id = labels[TASK_LABEL].as_str().and_then(|id| id.parse().ok());
+    let instance_guid = match labels[INSTANCE_GUID_LABEL].as_str() {
+        Some(guid) => guid.to_string(),
+        None if task_id.is_some() => String::new(),
+        None => return None,
+    };
+
+    Some(RuntimeContainer {
+        container_id: container_id.to_string(),
+        platform_id: labels[PLATFORM_LABEL].as_str()?.parse().ok()?,
+        app_id: labels[APP_LABEL].as_str()?.parse().ok()?,
+        instance_guid,
+        task_id,
+        ip_address,
+        state,
+        health,
+    })
+}
+
+/// Name of the container of an instance: `omni-<app>-<index>-<guid prefix>`,
+/// restricted to the characters Docker accepts.
+fn container_name(spec: &InstanceSpec) -> String {
+    let app: String = spec
+        .app_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .take(64)
+        .collect();
+    let guid: String = spec.instance_guid.chars().filter(|c| c.is_ascii_alphanumeric()).take(8).collect();
+    format!("omni-{}-{}-{}", app, spec.instance_index, guid)
+}
+
+/// Name of the container of a task: `omni-<app>-task-<sequence>-<task id>`,
+/// restricted to the characters Docker accepts.
+fn task_container_name(spec: &TaskSpec) -> String {
+    let app: String = spec
+        .app_name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.' { c } else { '-' })
+        .take(64)
+        .collect();
+    format!("omni-{}-task-{}-{}", app, spec.sequence_id, spec.task_id)
+}
+
+/// Splits an image reference into name and tag, defaulting to `latest`.
+/// Digests are passed through as the tag.
+fn split_image(image: &str) -> (&str, &str) {
+    if let Some((name, digest)) = image.split_once('@') {
+        return (name, digest);
+    }
+    match image.rfind(':') {
+        // A colon before the last slash belongs to a registry port
+        Some(colon) if !image[colon..].contains('/') => (&image[..colon], &image[colon + 1..]),
+        _ => (image, "latest"),
+    }
+}
+
+/// Extracts the exit code from a container status such as `Exited (137) 5 minutes ago`.
+fn exit_code_of_status(status: &str) -> Option<i64> {
+    let start = status.find('(')? + 1;
+    let end = start + status[start..].find(')')?;
+    status[start..end].parse().ok()
+}
+
+/// Extracts the health from a container status such as `Up 5 minutes (healthy)`.
+fn health_of_status(status: &str) -> Option<ContainerHealth> {
+    if status.ends_with("(health: starting)") {
+        Some(ContainerHealth::Starting)
+    } else if status.ends_with("(unhealthy)") {
+        Some(ContainerHealth::Unhealthy)
+    } else if status.ends_with("(healthy)") {
+        Some(ContainerHealth::Healthy)
+    } else {
+        None
+    }
+}
+
+/// Percent-encodes everything but unreserved characters.
+fn encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+/// Parses a raw HTTP/1.1 response, undoing chunked transfer encoding.
+fn parse_response(raw: &[u8]) -> anyhow::Result<Response> {
+    let split = raw
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .context("Docker sent an incomplete response")?;
+    let head = std::str::from_utf8(&raw[..split]).context("Docker sent invalid response headers")?;
+    let body = &raw[split + 4..];
+
+    let mut lines = head.split("\r\n");
+    let status = lines
+        .next()
+        .and_then(|line| line.split_whitespace().nth(1))
+        .and_then(|status| status.parse::<u16>().ok())
+        .context("Docker sent an invalid status line")?;
+
+    let headers: HashMap<String, String> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_ascii_lowercase(), value.trim().to_string()))
+        .collect();
+
+    let chunked = headers
+        .get("transfer-encoding")
+        .map(|encoding| encoding.eq_ignore_ascii_case("chunked"))
+        .unwrap_or(false);
+    let body = if chunked { dechunk(body)? } else { body.to_vec() };
+
+    Ok(Response { status, body })
+}
+
+fn dechunk(mut body: &[u8]) -> anyhow::Result<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(body.len());
+    loop {
+        let line_end = body
+            .windows(2)
+            .position(|window| window == b"\r\n")
+            .context("Docker sent a malformed chunk")?;
+        let size_field = std::str::from_utf8(&body[..line_end])?;
+        let size = usize::from_str_radix(size_field.split(';').next().unwrap_or("").trim(), 16)
+            .context("Docker sent an invalid chunk size")?;
+        body = &body[line_end + 2..];
+        if size == 0 {
+            return Ok(decoded);
+        }
+        anyhow::ensure!(body.len() >= size, "Docker sent a truncated chunk");
+        decoded.extend_from_slice(&body[..size]);
+        body = body.get(size + 2..).unwrap_or_default();
+    }
+}
+
+/// Splits the multiplexed log stream of a container without a TTY into
+/// lines. Each frame starts with an 8 byte header holding the stream and the
+/// length of the payload.
+fn demultiplex_logs(mut raw: &[u8]) -> Vec<LogLine> {
+    let mut lines = Vec::new();
+    while raw.len() >= 8 {
+        let stream = if raw[0] == 2 { LogStream::Stderr } else { LogStream::Stdout };
+        let size = u32::from_be_bytes([raw[4], raw[5], raw[6], raw[7]]) as usize;
+        let payload = &raw[8..(8 + size).min(raw.len())];
+        raw = &raw[(8 + size).min(raw.len())..];
+
+        for line in String::from_utf8_lossy(payload).lines() {
+            // Lines are prefixed with an RFC 3339 timestamp as requested
+            let (timestamp, message) = match line.split_once(' ') {
+                Some((time, message)) => match DateTime::parse_from_rfc3339(time) {
+                    Ok(time) => (Some(time.with_timezone(&Utc)), message),
+                    Err(_) => (None, line),
+                },
+                None => (None, line),
+            };
+            lines.push(LogLine { stream, timestamp, message: message.to_string() });
+        }
+    }
+    lines
+}
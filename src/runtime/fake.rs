@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use parking_lot::Mutex;
+
+use super::{
+    ContainerHealth, ContainerState, InstanceSpec, LogLine, LogStream, RuntimeContainer, RuntimeDriver, RuntimeEvent,
+    RuntimeEventKind, TaskSpec,
+};
+
+/// Number of events the fake driver remembers.
+const MAX_EVENTS: usize = 10_000;
+
+/// In-memory runtime driver.
+///
+/// Containers only exist in a map and start instantly; the containers of
+/// tasks report their command and exit with code 0 as soon as they start,
+/// unless [`FakeDriver::hold_tasks`] keeps them running. Failures can be
+/// injected with [`FakeDriver::exit`], [`FakeDriver::forget`],
+/// [`FakeDriver::set_health`] and [`FakeDriver::fail_launches`], which makes the driver suitable for
+/// exercising the reconciler without a real runtime. Every change is
+/// recorded as a runtime event, just like a real runtime would report it.
+#[derive(Debug, Default)]
+pub struct FakeDriver {
+    state: Mutex<FakeState>,
+}
+
+#[derive(Debug, Default)]
+struct FakeState {
+    containers: HashMap<String, FakeContainer>,
+    events: Vec<(i64, RuntimeEvent)>,
+    next_id: u64,
+    fail_launches: bool,
+    hold_tasks: bool,
+}
+
+#[derive(Debug)]
+struct FakeContainer {
+    container: RuntimeContainer,
+    logs: Vec<LogLine>,
+    /// Command of a task container
+    command: Option<String>,
+}
+
+impl FakeState {
+    fn record(&mut self, container: &RuntimeContainer, kind: RuntimeEventKind) {
+        // Like Docker's, the events reported are those of instances only
+        if container.task_id.is_some() {
+            return;
+        }
+        let event = RuntimeEvent {
+            container_id: container.container_id.clone(),
+            app_id: container.app_id,
+            instance_guid: container.instance_guid.clone(),
+            kind,
+            time: Utc::now(),
+        };
+        if self.events.len() >= MAX_EVENTS {
+            self.events.drain(..MAX_EVENTS / 2);
+        }
+        self.events.push((container.platform_id, event));
+    }
+
+    fn get(&mut self, container_id: &str) -> anyhow::Result<&mut FakeContainer> {
+        self.containers
+            .get_mut(container_id)
+            .ok_or_else(|| anyhow::anyhow!("No such container: {}", container_id))
+    }
+}
+
+impl FakeDriver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes a container's process exit with the given code.
+    pub fn exit(&self, container_id: &str, exit_code: i64) -> bool {
+        let mut state = self.state.lock();
+        let container = match state.containers.get_mut(container_id) {
+            Some(fake) => {
+                fake.container.state = ContainerState::Exited { exit_code };
+                fake.container.clone()
+            }
+            None => return false,
+        };
+        state.record(&container, RuntimeEventKind::Died { exit_code: Some(exit_code) });
+        true
+    }
+
+    /// Sets the result of a container's health check.
+    pub fn set_health(&self, container_id: &str, health: Option<ContainerHealth>) -> bool {
+        match self.state.lock().containers.get_mut(container_id) {
+            Some(fake) => {
+                fake.container.health = health;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Makes a container disappear without going through the orchestrator
+    /// and without any event being reported.
+    pub fn forget(&self, container_id: &str) -> bool {
+        self.state.lock().containers.remove(container_id).is_some()
+    }
+
+    /// Makes every following launch fail, or succeed again.
+    pub fn fail_launches(&self, fail: bool) {
+        self.state.lock().fail_launches = fail;
+    }
+
+    /// Makes the containers of tasks keep running once started, until they
+    /// are stopped or made to [`exit`](FakeDriver::exit), or exit right away
+    /// again.
+    pub fn hold_tasks(&self, hold: bool) {
+        self.state.lock().hold_tasks = hold;
+    }
+
+    /// Appends a line to the output of a container.
+    pub fn write_log(&self, container_id: &str, stream: LogStream, message: &str) -> bool {
+        match self.state.lock().containers.get_mut(container_id) {
+            Some(fake) => {
+                fake.logs.push(LogLine { stream, timestamp: Some(Utc::now()), message: message.to_string() });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns a snapshot of all containers.
+    pub fn containers(&self) -> Vec<RuntimeContainer> {
+        self.state.lock().containers.values().map(|fake| fake.container.clone()).collect()
+    }
+}
+
+#[rocket::async_trait]
+impl RuntimeDriver for FakeDriver {
+    fn name(&self) -> &'static str {
+        "fake"
+    }
+
+    async fn ping(&self) -> anyhow::Result<String> {
+        Ok("in-memory fake runtime".to_string())
+    }
+
+    async fn create(&self, spec: &InstanceSpec) -> anyhow::Result<String> {
+        let mut state = self.state.lock();
+        if state.fail_launches {
+            anyhow::bail!("Creation of a container for instance {} refused by fake driver", spec.instance_guid);
+        }
+
+        state.next_id += 1;
+        let container = RuntimeContainer {
+            container_id: format!("fake-{:08}", state.next_id),
+            platform_id: spec.platform_id,
+            app_id: spec.app_id,
+            instance_guid: spec.instance_guid.clone(),
+            task_id: None,
+            ip_address: None,
+            state: ContainerState::Created,
+            health: None,
+        };
+        let container_id = container.container_id.clone();
+        state.containers.insert(container_id.clone(), FakeContainer { container, logs: Vec::new(), command: None });
+        Ok(container_id)
+    }
+
+    async fn create_task(&self, spec: &TaskSpec) -> anyhow::Result<String> {
+        let mut state = self.state.lock();
+        if state.fail_launches {
+            anyhow::bail!("Creation of a container for task {} refused by fake driver", spec.task_id);
+        }
+
+        state.next_id += 1;
+        let container = RuntimeContainer {
+            container_id: format!("fake-{:08}", state.next_id),
+            platform_id: spec.platform_id,
+            app_id: spec.app_id,
+            instance_guid: String::new(),
+            task_id: Some(spec.task_id),
+            ip_address: None,
+            state: ContainerState::Created,
+            health: None,
+        };
+        let container_id = container.container_id.clone();
+        let fake = FakeContainer { container, logs: Vec::new(), command: Some(spec.command.clone()) };
+        state.containers.insert(container_id.clone(), fake);
+        Ok(container_id)
+    }
+
+    async fn start(&self, container_id: &str) -> anyhow::Result<()> {
+        let mut state = self.state.lock();
+        let next_id = state.next_id;
+        let hold_tasks = state.hold_tasks;
+        let fake = state.get(container_id)?;
+        if fake.container.state == ContainerState::Running {
+            return Ok(());
+        }
+
+        fake.container.state = ContainerState::Running;
+        fake.container.ip_address = Some(format!("10.0.{}.{}", (next_id / 250) % 250, next_id % 250 + 2));
+        let message = match &fake.command {
+            Some(command) => format!("fake task ran: {}", command),
+            None => "fake container started".to_string(),
+        };
+        fake.logs.push(LogLine { stream: LogStream::Stdout, timestamp: Some(Utc::now()), message });
+        if fake.command.is_some() && !hold_tasks {
+            fake.container.state = ContainerState::Exited { exit_code: 0 };
+        }
+        let container = fake.container.clone();
+        state.record(&container, RuntimeEventKind::Started);
+        Ok(())
+    }
+
+    async fn stop(&self, container_id: &str, _timeout: Duration) -> anyhow::Result<()> {
+        let mut state = self.state.lock();
+        let container = match state.containers.get_mut(container_id) {
+            Some(fake) if fake.container.state == ContainerState::Running => {
+                fake.container.state = ContainerState::Exited { exit_code: 0 };
+                fake.container.clone()
+            }
+            _ => return Ok(()),
+        };
+        state.record(&container, RuntimeEventKind::Died { exit_code: Some(0) });
+        Ok(())
+    }
+
+    async fn remove(&self, container_id: &str) -> anyhow::Result<()> {
+        let mut state = self.state.lock();
+        if let Some(fake) = state.containers.remove(container_id) {
+            if fake.container.state == ContainerState::Running {
+                state.record(&fake.container, RuntimeEventKind::Died { exit_code: Some(137) });
+            }
+            state.record(&fake.container, RuntimeEventKind::Destroyed);
+        }
+        Ok(())
+    }
+
+    async fn inspect(&self, container_id: &str) -> anyhow::Result<Option<RuntimeContainer>> {
+        Ok(self.state.lock().containers.get(container_id).map(|fake| fake.container.clone()))
+    }
+
+    async fn logs(&self, container_id: &str, tail: Option<u64>) -> anyhow::Result<Vec<LogLine>> {
+        let mut state = self.state.lock();
+        let logs = &state.get(container_id)?.logs;
+        let skip = match tail {
+            Some(tail) => logs.len().saturating_sub(tail as usize),
+            None => 0,
+        };
+        Ok(logs[skip..].to_vec())
+    }
+
+    async fn list(&self, platform_id: i64) -> anyhow::Result<Vec<RuntimeContainer>> {
+        Ok(self
+            .state
+            .lock()
+            .containers
+            .values()
+            .filter(|fake| fake.container.platform_id == platform_id)
+            .map(|fake| fake.container.clone())
+            .collect())
+    }
+
+    async fn events(
+        &self,
+        platform_id: i64,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<RuntimeEvent>> {
+        Ok(self
+            .state
+            .lock()
+            .events
+            .iter()
+            .filter(|(platform, event)| *platform == platform_id && event.time >= since && event.time <= until)
+            .map(|(_, event)| event.clone())
+            .collect())
+    }
+}
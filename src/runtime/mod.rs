@@ -0,0 +1,333 @@
+//! Container runtimes that instances are executed on.
+//!
+//! The orchestrator talks to runtimes through the [`RuntimeDriver`] trait so
+//! the reconciler does not depend on any one runtime. Every container it
+//! creates is labelled with the platform, application and instance it
+//! belongs to, which is how containers and runtime events are matched back
+//! to `instances` rows. Containers of one-off tasks carry the task instead
+//! of an instance and are left to the task runner.
+//!
+//! # Drivers
+//! - `docker`: Docker Engine API over a Unix socket
+//! - `fake`: in-memory driver that never runs anything, for tests and development
+
+pub mod docker;
+pub mod fake;
+
+pub use docker::DockerDriver;
+pub use fake::FakeDriver;
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::config::RuntimeConfig;
+
+/// Label holding the platform a container was created for.
+pub const PLATFORM_LABEL: &str = "io.omnicloud.platform_id";
+/// Label holding the application a container was created for.
+pub const APP_LABEL: &str = "io.omnicloud.app_id";
+/// Label holding the instance row a container was created for.
+pub const INSTANCE_LABEL: &str = "io.omnicloud.instance_id";
+/// Label holding the GUID of the instance a container was created for.
+pub const INSTANCE_GUID_LABEL: &str = "io.omnicloud.instance_guid";
+/// Label holding the deployment whose release a container runs.
+pub const DEPLOYMENT_LABEL: &str = "io.omnicloud.deployment_id";
+/// Label holding the worker the scheduler placed a container's instance on.
+pub const WORKER_LABEL: &str = "io.omnicloud.worker_id";
+/// Label holding the task row a one-off container was created for.
+pub const TASK_LABEL: &str = "io.omnicloud.task_id";
+
+/// Everything a driver needs to create the container of one instance.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceSpec {
+    /// Platform the instance belongs to
+    pub platform_id: i64,
+    /// Application the instance runs
+    pub app_id: i64,
+    /// Name of the application, used to name the container
+    pub app_name: String,
+    /// ID of the `instances` row
+    pub instance_id: i64,
+    /// GUID of the instance, the container's identity in the runtime
+    pub instance_guid: String,
+    /// Index of the instance within its application
+    pub instance_index: i64,
+    /// Image to run, if the application has one
+    pub image: Option<String>,
+    /// Deployment whose release the container runs
+    pub deployment_id: Option<i64>,
+    /// Worker the scheduler placed the instance on, if the platform has workers
+    pub node_id: Option<i64>,
+    /// Environment variables of the container
+    pub environment: HashMap<String, String>,
+}
+
+impl InstanceSpec {
+    /// Labels identifying the container of this instance.
+    pub fn labels(&self) -> HashMap<String, String> {
+        let mut labels = HashMap::from([
+            (PLATFORM_LABEL.to_string(), self.platform_id.to_string()),
+            (APP_LABEL.to_string(), self.app_id.to_string()),
+            (INSTANCE_LABEL.to_string(), self.instance_id.to_string()),
+            (INSTANCE_GUID_LABEL.to_string(), self.instance_guid.clone()),
+        ]);
+        if let Some(deployment_id) = self.deployment_id {
+            labels.insert(DEPLOYMENT_LABEL.to_string(), deployment_id.to_string());
+        }
+        if let Some(node_id) = self.node_id {
+            labels.insert(WORKER_LABEL.to_string(), node_id.to_string());
+        }
+        labels
+    }
+}
+
+/// Everything a driver needs to create the container of a one-off task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskSpec {
+    /// Platform the task belongs to
+    pub platform_id: i64,
+    /// Application whose release the task runs
+    pub app_id: i64,
+    /// Name of the application, used to name the container
+    pub app_name: String,
+    /// ID of the `tasks` row
+    pub task_id: i64,
+    /// Sequence number of the task within its application
+    pub sequence_id: i64,
+    /// Image to run
+    pub image: String,
+    /// Shell command the container runs instead of the image's command
+    pub command: String,
+    /// Deployment whose release the container runs
+    pub deployment_id: Option<i64>,
+    /// Memory limit of the container
+    pub memory_in_mb: Option<i64>,
+    /// CPU limit of the container, in cores
+    pub cpu: Option<f64>,
+    /// Environment variables of the container
+    pub environment: HashMap<String, String>,
+}
+
+impl TaskSpec {
+    /// Labels identifying the container of this task.
+    pub fn labels(&self) -> HashMap<String, String> {
+        let mut labels = HashMap::from([
+            (PLATFORM_LABEL.to_string(), self.platform_id.to_string()),
+            (APP_LABEL.to_string(), self.app_id.to_string()),
+            (TASK_LABEL.to_string(), self.task_id.to_string()),
+        ]);
+        if let Some(deployment_id) = self.deployment_id {
+            labels.insert(DEPLOYMENT_LABEL.to_string(), deployment_id.to_string());
+        }
+        labels
+    }
+}
+
+/// State of a container as reported by the runtime.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ContainerState {
+    /// The container has been created but is not running yet
+    Created,
+    /// The container is running
+    Running,
+    /// The container's process exited
+    Exited { exit_code: i64 },
+}
+
+/// A container known to the runtime.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeContainer {
+    /// Runtime-specific identifier of the container
+    pub container_id: String,
+    /// Platform the container was created for
+    pub platform_id: i64,
+    /// Application the container was created for
+    pub app_id: i64,
+    /// GUID of the instance the container was created for; empty for the
+    /// containers of tasks
+    pub instance_guid: String,
+    /// Task the container was created for, if it runs one
+    #[serde(default)]
+    pub task_id: Option<i64>,
+    /// IP address of the container, once it has one
+    pub ip_address: Option<String>,
+    /// Current state of the container
+    pub state: ContainerState,
+    /// Result of the container's health check, if its image defines one
+    #[serde(default)]
+    pub health: Option<ContainerHealth>,
+}
+
+/// Health of a container as reported by its health check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContainerHealth {
+    /// The health check has not passed yet
+    Starting,
+    Healthy,
+    Unhealthy,
+}
+
+impl ContainerHealth {
+    /// Parses a health status as Docker reports it.
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "starting" => Some(ContainerHealth::Starting),
+            "healthy" => Some(ContainerHealth::Healthy),
+            "unhealthy" => Some(ContainerHealth::Unhealthy),
+            _ => None,
+        }
+    }
+
+    /// The matching `instances.health_status`.
+    pub fn instance_status(self) -> &'static str {
+        match self {
+            ContainerHealth::Starting => "unknown",
+            ContainerHealth::Healthy => "healthy",
+            ContainerHealth::Unhealthy => "critical",
+        }
+    }
+}
+
+/// Something that happened to a container.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum RuntimeEventKind {
+    /// The container started running
+    Started,
+    /// The container's process exited
+    Died { exit_code: Option<i64> },
+    /// The container's process was killed for running out of memory
+    OomKilled,
+    /// The container was removed
+    Destroyed,
+}
+
+/// A runtime event concerning a container of the orchestrator.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RuntimeEvent {
+    /// Container the event concerns
+    pub container_id: String,
+    /// Application the container was created for
+    pub app_id: i64,
+    /// GUID of the instance the container was created for
+    pub instance_guid: String,
+    /// What happened
+    #[serde(flatten)]
+    pub kind: RuntimeEventKind,
+    /// When it happened
+    pub time: DateTime<Utc>,
+}
+
+/// Output stream a log line was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LogStream {
+    Stdout,
+    Stderr,
+}
+
+/// A line written by a container's process.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogLine {
+    pub stream: LogStream,
+    pub timestamp: Option<DateTime<Utc>>,
+    pub message: String,
+}
+
+/// Operations the orchestrator needs from a container runtime.
+///
+/// Implementations must be safe to call concurrently. Stopping or removing
+/// a container that no longer exists counts as success, as the reconciler
+/// may retry an operation whose outcome it never saw.
+#[rocket::async_trait]
+pub trait RuntimeDriver: Send + Sync {
+    /// Short name of the driver, as used in the configuration
+    fn name(&self) -> &'static str;
+
+    /// Checks that the runtime is reachable and describes it.
+    async fn ping(&self) -> anyhow::Result<String>;
+
+    /// Creates the container of an instance without starting it, returning
+    /// its identifier.
+    async fn create(&self, spec: &InstanceSpec) -> anyhow::Result<String>;
+
+    /// Creates the container of a one-off task without starting it,
+    /// returning its identifier. The container runs the task's command once
+    /// and is never restarted.
+    async fn create_task(&self, spec: &TaskSpec) -> anyhow::Result<String>;
+
+    /// Starts a created or exited container.
+    async fn start(&self, container_id: &str) -> anyhow::Result<()>;
+
+    /// Stops a container, killing its process if it has not exited within
+    /// `timeout`.
+    async fn stop(&self, container_id: &str, timeout: Duration) -> anyhow::Result<()>;
+
+    /// Removes a container, killing it first if it is still running.
+    async fn remove(&self, container_id: &str) -> anyhow::Result<()>;
+
+    /// Looks up a container; `None` if the runtime does not know it.
+    async fn inspect(&self, container_id: &str) -> anyhow::Result<Option<RuntimeContainer>>;
+
+    /// Returns the last `tail` lines a container wrote, or all of them.
+    async fn logs(&self, container_id: &str, tail: Option<u64>) -> anyhow::Result<Vec<LogLine>>;
+
+    /// Lists every container created for a platform, running or not.
+    async fn list(&self, platform_id: i64) -> anyhow::Result<Vec<RuntimeContainer>>;
+
+    /// Returns the events of a platform's containers between `since` and
+    /// `until`, oldest first. `until` must not lie in the future.
+    async fn events(
+        &self,
+        platform_id: i64,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<RuntimeEvent>>;
+
+    /// Creates and starts the container of an instance. A container that
+    /// fails to start is removed again.
+    async fn launch(&self, spec: &InstanceSpec) -> anyhow::Result<RuntimeContainer> {
+        let container_id = self.create(spec).await?;
+
+        if let Err(e) = self.start(&container_id).await {
+            if let Err(remove_error) = self.remove(&container_id).await {
+                log::warn!("Failed to remove container {} that did not start: {:#}", container_id, remove_error);
+            }
+            return Err(e);
+        }
+
+        match self.inspect(&container_id).await? {
+            Some(container) => Ok(container),
+            None => anyhow::bail!("Container {} disappeared right after it was started", container_id),
+        }
+    }
+
+    /// Creates and starts the container of a task, returning its
+    /// identifier. A container that fails to start is removed again.
+    async fn launch_task(&self, spec: &TaskSpec) -> anyhow::Result<String> {
+        let container_id = self.create_task(spec).await?;
+
+        if let Err(e) = self.start(&container_id).await {
+            if let Err(remove_error) = self.remove(&container_id).await {
+                log::warn!("Failed to remove task container {} that did not start: {:#}", container_id, remove_error);
+            }
+            return Err(e);
+        }
+        Ok(container_id)
+    }
+}
+
+/// Builds the runtime driver selected in the configuration.
+pub fn driver_from_config(config: &RuntimeConfig) -> anyhow::Result<Arc<dyn RuntimeDriver>> {
+    match config.driver.as_str() {
+        "docker" => Ok(Arc::new(DockerDriver::new(&config.docker_socket))),
+        "fake" => Ok(Arc::new(FakeDriver::new())),
+        other => anyhow::bail!("Unknown runtime driver '{}'; use 'docker' or 'fake'", other),
+    }
+}
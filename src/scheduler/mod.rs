@@ -0,0 +1,212 @@
+//! Placement of instances on workers.
+//!
+//! Before an instance is launched the scheduler picks the worker it runs on.
+//! Workers are first filtered: they must be active, in the instance's
+//! region, have enough CPU, memory and disk left, carry every label of the
+//! application's node selector and have no `NoSchedule` or `NoExecute`
+//! taint the application does not tolerate. The remaining workers are
+//! scored by the application's strategy, bin-packing or spreading, with
+//! penalties for workers already running the same application and for
+//! untolerated `PreferNoSchedule` taints.
+//!
+//! The chosen worker's capacity is reserved in the same transaction that
+//! assigns the instance to it, guarded so two schedulers can never
+//! overcommit a worker. The decision, including why every other worker was
+//! passed over, is stored in `instances.scheduler_metadata`. Capacity is
+//! returned to the worker once the instance is stopped or terminated.
+
+pub mod placement;
+
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::Utc;
+use libomni::types::db::v1 as types;
+use serde_json::{json, Value};
+use sqlx::{MySql, Pool};
+use types::app::App;
+use types::instance::Instance;
+
+use crate::config::SchedulerConfig;
+use crate::schemas::v1::db::queries as db;
+use db::scheduling::{AppSchedulingPolicy, Reservation};
+use placement::{AntiAffinity, Evaluation, Requirements, Strategy, Toleration};
+
+/// Number of worker evaluations kept in an instance's scheduler metadata.
+const MAX_EXPLAINED_CANDIDATES: usize = 50;
+
+/// Outcome of scheduling an instance.
+#[derive(Debug, Clone)]
+pub enum Placement {
+    /// The instance was placed on a worker and its capacity reserved
+    Placed { worker_id: i64 },
+    /// The platform has no workers, so the instance runs wherever the
+    /// runtime driver runs containers
+    Unplaced,
+    /// No worker can take the instance right now
+    Unschedulable { reason: String },
+}
+
+/// Picks a worker for an instance that has none and reserves its capacity.
+pub async fn schedule_instance(
+    pool: &Pool<MySql>,
+    app: &App,
+    instance: &Instance,
+    config: &SchedulerConfig,
+) -> anyhow::Result<Placement> {
+    let policy = db::scheduling::get_app_scheduling_policy(pool, app.id)
+        .await?
+        .unwrap_or_else(|| AppSchedulingPolicy::default_for(app.id));
+    let requirements = requirements_of(pool, app, instance, &policy, config).await?;
+    let reservation = Reservation {
+        cpu: requirements.cpu,
+        memory_mb: requirements.memory_mb,
+        disk_mb: requirements.disk_mb,
+    };
+
+    let workers = db::scheduling::list_all_workers(pool).await?;
+    if workers.is_empty() {
+        let metadata = decision("unplaced", &requirements, &[]);
+        db::scheduling::record_scheduling_decision(pool, instance.id, &with_reason(metadata, "no workers are registered")).await?;
+        return Ok(Placement::Unplaced);
+    }
+
+    let same_app: HashMap<i64, i64> = db::scheduling::count_app_instances_by_worker(pool, app.id)
+        .await?
+        .into_iter()
+        .collect();
+    let mut evaluations = placement::evaluate(&workers, &requirements, &same_app);
+
+    // Candidates are tried best first; one whose capacity was taken since
+    // it was read is skipped and explained as such
+    for index in 0..evaluations.len() {
+        if !evaluations[index].eligible {
+            break;
+        }
+
+        let worker_id = evaluations[index].worker_id;
+        let mut metadata = decision("placed", &requirements, &evaluations);
+        metadata["worker_id"] = json!(worker_id);
+        metadata["worker_name"] = json!(evaluations[index].worker_name);
+        metadata["score"] = json!(evaluations[index].score);
+        metadata["reservation"] = json!({
+            "cpu": reservation.cpu,
+            "memory_mb": reservation.memory_mb,
+            "disk_mb": reservation.disk_mb,
+            "released": false,
+        });
+
+        if db::scheduling::place_instance(pool, instance.id, worker_id, &reservation, &metadata).await? {
+            return Ok(Placement::Placed { worker_id });
+        }
+
+        let lost = &mut evaluations[index];
+        lost.eligible = false;
+        lost.reasons.push("capacity was taken by another placement".to_string());
+    }
+
+    let reason = unschedulable_reason(&evaluations);
+    let metadata = with_reason(decision("unschedulable", &requirements, &evaluations), &reason);
+    db::scheduling::record_scheduling_decision(pool, instance.id, &metadata).await?;
+    Ok(Placement::Unschedulable { reason })
+}
+
+/// Returns the capacity of stopped and terminated instances to their
+/// workers, returning how many reservations were released.
+pub async fn release_finished_reservations(pool: &Pool<MySql>) -> anyhow::Result<usize> {
+    let mut released = 0;
+    for instance in db::scheduling::list_unreleased_reservations(pool).await? {
+        if db::scheduling::release_reservation(pool, instance.id).await? {
+            released += 1;
+        }
+    }
+    Ok(released)
+}
+
+/// Works out what an instance needs. Resources come from the instance's
+/// allocation, else the application's default allocation, else the
+/// configured defaults.
+async fn requirements_of(
+    pool: &Pool<MySql>,
+    app: &App,
+    instance: &Instance,
+    policy: &AppSchedulingPolicy,
+    config: &SchedulerConfig,
+) -> anyhow::Result<Requirements> {
+    let allocation = match instance.allocation_id.or(app.default_allocation_id) {
+        Some(allocation_id) => db::scheduling::get_allocation(pool, allocation_id).await?,
+        None => None,
+    };
+    let (cpu, memory_mb, disk_mb) = match &allocation {
+        Some(allocation) => (allocation.cpu, allocation.memory, allocation.disk),
+        None => (config.default_cpu, config.default_memory_mb, config.default_disk_mb),
+    };
+
+    let strategy = policy
+        .strategy
+        .as_deref()
+        .and_then(Strategy::parse)
+        .or_else(|| Strategy::parse(&config.strategy))
+        .unwrap_or(Strategy::Binpack);
+    let node_selector: BTreeMap<String, String> = policy
+        .node_selector
+        .clone()
+        .and_then(|selector| serde_json::from_value(selector).ok())
+        .unwrap_or_default();
+    let tolerations: Vec<Toleration> = policy
+        .tolerations
+        .clone()
+        .and_then(|tolerations| serde_json::from_value(tolerations).ok())
+        .unwrap_or_default();
+
+    Ok(Requirements {
+        cpu,
+        memory_mb,
+        disk_mb,
+        region_id: instance.region_id.or(app.region_id),
+        node_selector,
+        tolerations,
+        anti_affinity: AntiAffinity::parse(&policy.anti_affinity).unwrap_or(AntiAffinity::Preferred),
+        strategy,
+    })
+}
+
+/// Builds the scheduler metadata describing a decision.
+fn decision(outcome: &str, requirements: &Requirements, evaluations: &[Evaluation]) -> Value {
+    json!({
+        "decision": outcome,
+        "scheduled_at": Utc::now().to_rfc3339(),
+        "strategy": requirements.strategy.as_str(),
+        "anti_affinity": requirements.anti_affinity.as_str(),
+        "requirements": {
+            "cpu": requirements.cpu,
+            "memory_mb": requirements.memory_mb,
+            "disk_mb": requirements.disk_mb,
+            "region_id": requirements.region_id,
+            "node_selector": requirements.node_selector,
+            "tolerations": requirements.tolerations,
+        },
+        "candidates": evaluations.iter().take(MAX_EXPLAINED_CANDIDATES).collect::<Vec<_>>(),
+        "candidates_total": evaluations.len(),
+    })
+}
+
+fn with_reason(mut metadata: Value, reason: &str) -> Value {
+    metadata["reason"] = json!(reason);
+    metadata
+}
+
+/// Summarizes why no worker could take an instance, such as
+/// "3 workers rejected: insufficient memory (2), worker is maintenance (1)".
+fn unschedulable_reason(evaluations: &[Evaluation]) -> String {
+    let mut causes: BTreeMap<String, usize> = BTreeMap::new();
+    for evaluation in evaluations {
+        for reason in &evaluation.reasons {
+            // Group by the kind of rejection rather than the exact numbers
+            let cause = reason.split(':').next().unwrap_or(reason).to_string();
+            *causes.entry(cause).or_default() += 1;
+        }
+    }
+
+    let causes: Vec<String> = causes.into_iter().map(|(cause, count)| format!("{} ({})", cause, count)).collect();
+    format!("{} worker(s) rejected: {}", evaluations.len(), causes.join(", "))
+}
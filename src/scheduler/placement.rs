@@ -0,0 +1,303 @@
+use std::collections::{BTreeMap, HashMap};
+
+use libomni::types::db::v1 as types;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use types::worker::Worker;
+
+/// Score subtracted for every instance of the same application a worker
+/// already runs when anti-affinity is preferred. It outweighs any
+/// difference in utilization, so replicas spread before they stack.
+const ANTI_AFFINITY_PENALTY: f64 = 1.0;
+
+/// Score subtracted for every `PreferNoSchedule` taint that is not tolerated.
+const PREFER_NO_SCHEDULE_PENALTY: f64 = 0.25;
+
+/// How eligible workers are ranked.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Prefer the workers that are the fullest after placement
+    Binpack,
+    /// Prefer the workers that are the emptiest after placement
+    Spread,
+}
+
+impl Strategy {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "binpack" => Some(Strategy::Binpack),
+            "spread" => Some(Strategy::Spread),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Strategy::Binpack => "binpack",
+            Strategy::Spread => "spread",
+        }
+    }
+}
+
+/// How instances of the same application are kept apart.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AntiAffinity {
+    /// Instances may share workers freely
+    None,
+    /// Workers without instances of the application are preferred
+    Preferred,
+    /// Instances never share a worker
+    Required,
+}
+
+impl AntiAffinity {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "none" => Some(AntiAffinity::None),
+            "preferred" => Some(AntiAffinity::Preferred),
+            "required" => Some(AntiAffinity::Required),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AntiAffinity::None => "none",
+            AntiAffinity::Preferred => "preferred",
+            AntiAffinity::Required => "required",
+        }
+    }
+}
+
+/// Effects a worker taint can have.
+pub const TAINT_EFFECTS: [&str; 3] = ["NoSchedule", "PreferNoSchedule", "NoExecute"];
+
+/// A taint of a worker, which keeps instances off it unless they tolerate it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Taint {
+    pub key: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    pub effect: String,
+}
+
+impl Taint {
+    /// Parses the taints of a worker, given either as objects or in the
+    /// `key=value:Effect` shorthand. Unparseable entries are skipped.
+    pub fn parse_all(taints: Option<&Value>) -> Vec<Taint> {
+        let entries = match taints.and_then(Value::as_array) {
+            Some(entries) => entries,
+            None => return Vec::new(),
+        };
+
+        entries
+            .iter()
+            .filter_map(|entry| match entry {
+                Value::String(shorthand) => {
+                    let (pair, effect) = shorthand.rsplit_once(':')?;
+                    let (key, value) = match pair.split_once('=') {
+                        Some((key, value)) => (key, Some(value.to_string())),
+                        None => (pair, None),
+                    };
+                    Some(Taint { key: key.to_string(), value, effect: effect.to_string() })
+                }
+                other => serde_json::from_value(other.clone()).ok(),
+            })
+            .collect()
+    }
+
+    fn describe(&self) -> String {
+        match &self.value {
+            Some(value) => format!("{}={}:{}", self.key, value, self.effect),
+            None => format!("{}:{}", self.key, self.effect),
+        }
+    }
+}
+
+/// A taint an application tolerates. A toleration without a value matches
+/// any value of the key; one without an effect matches every effect.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Toleration {
+    pub key: String,
+    #[serde(default)]
+    pub value: Option<String>,
+    #[serde(default)]
+    pub effect: Option<String>,
+}
+
+impl Toleration {
+    fn tolerates(&self, taint: &Taint) -> bool {
+        self.key == taint.key
+            && (self.value.is_none() || self.value == taint.value)
+            && self.effect.as_ref().map_or(true, |effect| *effect == taint.effect)
+    }
+}
+
+/// What an instance needs from the worker it is placed on.
+#[derive(Debug, Clone)]
+pub struct Requirements {
+    pub cpu: f64,
+    pub memory_mb: f64,
+    pub disk_mb: f64,
+    /// Region the instance must run in, if any
+    pub region_id: Option<i64>,
+    /// Labels the worker must have
+    pub node_selector: BTreeMap<String, String>,
+    pub tolerations: Vec<Toleration>,
+    pub anti_affinity: AntiAffinity,
+    pub strategy: Strategy,
+}
+
+/// The scheduler's verdict on one worker.
+#[derive(Debug, Clone, Serialize)]
+pub struct Evaluation {
+    pub worker_id: i64,
+    pub worker_name: String,
+    pub eligible: bool,
+    /// Why the worker was rejected, or what counted against it
+    pub reasons: Vec<String>,
+    /// Rank of an eligible worker; higher is better
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+    #[serde(skip_serializing_if = "Value::is_null")]
+    pub score_details: Value,
+}
+
+/// Filters and scores the workers for an instance.
+///
+/// `same_app` holds how many instances of the instance's application each
+/// worker already runs. Eligible workers come first, best score first and
+/// lowest worker ID on ties; rejected workers follow in ID order.
+pub fn evaluate(workers: &[Worker], requirements: &Requirements, same_app: &HashMap<i64, i64>) -> Vec<Evaluation> {
+    let mut evaluations: Vec<Evaluation> = workers
+        .iter()
+        .map(|worker| evaluate_worker(worker, requirements, same_app.get(&worker.id).copied().unwrap_or(0)))
+        .collect();
+
+    evaluations.sort_by(|a, b| {
+        b.eligible
+            .cmp(&a.eligible)
+            .then_with(|| {
+                let (a, b) = (a.score.unwrap_or(f64::MIN), b.score.unwrap_or(f64::MIN));
+                b.partial_cmp(&a).unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .then_with(|| a.worker_id.cmp(&b.worker_id))
+    });
+    evaluations
+}
+
+fn evaluate_worker(worker: &Worker, requirements: &Requirements, same_app: i64) -> Evaluation {
+    let mut rejections = Vec::new();
+
+    let status = worker.status.as_deref().unwrap_or("active");
+    if status != "active" {
+        rejections.push(format!("worker is {}", status));
+    }
+
+    if let Some(region_id) = requirements.region_id {
+        if worker.region_id != region_id {
+            rejections.push(format!("worker is in region {}, instance requires region {}", worker.region_id, region_id));
+        }
+    }
+
+    for (resource, needed, available) in [
+        ("cpu", requirements.cpu, worker.cpu_available),
+        ("memory", requirements.memory_mb, worker.memory_available),
+        ("disk", requirements.disk_mb, worker.disk_available),
+    ] {
+        if available < needed {
+            rejections.push(format!("insufficient {}: {} available, {} required", resource, available, needed));
+        }
+    }
+
+    let labels = worker.labels.as_ref().and_then(Value::as_object);
+    for (key, expected) in &requirements.node_selector {
+        let actual = labels.and_then(|labels| labels.get(key)).map(|value| match value {
+            Value::String(value) => value.clone(),
+            other => other.to_string(),
+        });
+        if actual.as_deref() != Some(expected.as_str()) {
+            rejections.push(match actual {
+                Some(actual) => format!("label {} is '{}', node selector requires '{}'", key, actual, expected),
+                None => format!("label {} is missing, node selector requires '{}'", key, expected),
+            });
+        }
+    }
+
+    let mut untolerated_preferences = Vec::new();
+    for taint in Taint::parse_all(worker.taints.as_ref()) {
+        if requirements.tolerations.iter().any(|toleration| toleration.tolerates(&taint)) {
+            continue;
+        }
+        if taint.effect == "PreferNoSchedule" {
+            untolerated_preferences.push(taint.describe());
+        } else {
+            rejections.push(format!("taint {} is not tolerated", taint.describe()));
+        }
+    }
+
+    if requirements.anti_affinity == AntiAffinity::Required && same_app > 0 {
+        rejections.push(format!("worker already runs {} instance(s) of this app and anti-affinity is required", same_app));
+    }
+
+    if !rejections.is_empty() {
+        return Evaluation {
+            worker_id: worker.id,
+            worker_name: worker.name.clone(),
+            eligible: false,
+            reasons: rejections,
+            score: None,
+            score_details: Value::Null,
+        };
+    }
+
+    let cpu_utilization = utilization_after(worker.cpu_total, worker.cpu_available, requirements.cpu);
+    let memory_utilization = utilization_after(worker.memory_total, worker.memory_available, requirements.memory_mb);
+    let utilization = (cpu_utilization + memory_utilization) / 2.0;
+    let base = match requirements.strategy {
+        Strategy::Binpack => utilization,
+        Strategy::Spread => 1.0 - utilization,
+    };
+
+    let mut reasons = Vec::new();
+    let anti_affinity_penalty = if requirements.anti_affinity == AntiAffinity::Preferred && same_app > 0 {
+        reasons.push(format!("already runs {} instance(s) of this app", same_app));
+        ANTI_AFFINITY_PENALTY * same_app as f64
+    } else {
+        0.0
+    };
+    let taint_penalty = PREFER_NO_SCHEDULE_PENALTY * untolerated_preferences.len() as f64;
+    for taint in &untolerated_preferences {
+        reasons.push(format!("taint {} is not tolerated", taint));
+    }
+
+    let score = base - anti_affinity_penalty - taint_penalty;
+    Evaluation {
+        worker_id: worker.id,
+        worker_name: worker.name.clone(),
+        eligible: true,
+        reasons,
+        score: Some(round(score)),
+        score_details: json!({
+            "cpu_utilization_after": round(cpu_utilization),
+            "memory_utilization_after": round(memory_utilization),
+            "strategy_score": round(base),
+            "anti_affinity_penalty": round(anti_affinity_penalty),
+            "taint_penalty": round(taint_penalty),
+        }),
+    }
+}
+
+/// Share of a resource in use once `needed` more is taken, between 0 and 1.
+fn utilization_after(total: f64, available: f64, needed: f64) -> f64 {
+    if total <= 0.0 {
+        return 1.0;
+    }
+    ((total - available + needed) / total).clamp(0.0, 1.0)
+}
+
+fn round(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
@@ -9,10 +9,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::user::User;
+use super::super::rbac::{Require, AlertsWrite};
 
 /// Acknowledge an alert
 #[post("/platform/<platform_id>/alerts/<id>/acknowledge", format = "json", data = "<ack_data>")]
 pub async fn acknowledge_alert(
+    _auth: Require<AlertsWrite>,
     platform_id: i64,
     id: i64,
     ack_data: Json<AcknowledgeAlertRequest>,
@@ -86,6 +88,7 @@ pub async fn acknowledge_alert(
 /// Resolve an alert
 #[post("/platform/<platform_id>/alerts/<id>/resolve", format = "json", data = "<resolve_data>")]
 pub async fn resolve_alert(
+    _auth: Require<AlertsWrite>,
     platform_id: i64,
     id: i64,
     resolve_data: Option<Json<HashMap<String, String>>>,
@@ -160,6 +163,7 @@ pub async fn resolve_alert(
 /// Create an escalation for an alert
 #[post("/platform/<platform_id>/alerts/<id>/escalate", format = "json", data = "<escalation_data>")]
 pub async fn escalate_alert(
+    _auth: Require<AlertsWrite>,
     platform_id: i64,
     id: i64,
     escalation_data: Json<CreateEscalationRequest>,
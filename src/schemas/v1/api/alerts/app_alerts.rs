@@ -4,10 +4,12 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{Require, AlertsRead};
 
 /// Get alerts for a specific application
 #[get("/platform/<platform_id>/apps/<app_id>/alerts?<limit>&<include_resolved>")]
 pub async fn get_app_alerts(
+    _auth: Require<AlertsRead>,
     platform_id: i64,
     app_id: i64,
     limit: Option<i64>,
@@ -4,10 +4,12 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{post, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{Require, AlertsWrite};
 
 /// Auto-resolve old alerts
 #[post("/platform/<platform_id>/alerts/auto-resolve?<days_threshold>&<severity_level>")]
 pub async fn auto_resolve_old_alerts(
+    _auth: Require<AlertsWrite>,
     platform_id: i64,
     days_threshold: Option<i64>,
     severity_level: Option<Vec<String>>, // Can provide multiple severity levels
@@ -8,10 +8,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::user::User;
+use super::super::rbac::{Require, AlertsWrite};
 
 /// Bulk update alert status
 #[put("/platform/<platform_id>/alerts/bulk-status", format = "json", data = "<update_data>")]
 pub async fn bulk_update_alert_status(
+    _auth: Require<AlertsWrite>,
     platform_id: i64,
     update_data: Json<BulkUpdateStatusRequest>,
     user: User, // Extract user from request guard
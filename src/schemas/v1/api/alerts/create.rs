@@ -5,10 +5,12 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{post, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{Require, AlertsWrite};
 
 /// Create a new alert
 #[post("/platform/<platform_id>/alerts", format = "json", data = "<alert_data>")]
 pub async fn create_alert(
+    _auth: Require<AlertsWrite>,
     platform_id: i64,
     alert_data: Json<CreateAlertRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
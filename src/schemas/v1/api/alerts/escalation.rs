@@ -4,15 +4,23 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{require_in_scope, AlertsRead, Caller, RequestScope};
 
 /// Get alerts needing escalation
 #[get("/platform/<platform_id>/alerts/needing-escalation?<org_id>&<hours_threshold>")]
 pub async fn get_alerts_needing_escalation(
+    caller: Caller,
     platform_id: i64,
     org_id: Option<i64>,
     hours_threshold: Option<i64>,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
+    require_in_scope::<AlertsRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id,
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
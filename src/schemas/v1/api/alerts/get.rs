@@ -4,10 +4,12 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{Require, AlertsRead};
 
 /// Get details of a specific alert including related data
 #[get("/platform/<platform_id>/alerts/<id>")]
 pub async fn get_alert(
+    _auth: Require<AlertsRead>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
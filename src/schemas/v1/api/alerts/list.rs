@@ -4,10 +4,12 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{require_in_scope, AlertsRead, Caller, RequestScope};
 
 /// Get a paginated list of alerts with filtering options
 #[get("/platform/<platform_id>/alerts?<page>&<per_page>&<status>&<severity>&<org_id>&<app_id>&<service>&<from_date>&<to_date>")]
 pub async fn list_alerts(
+    caller: Caller,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -20,6 +22,13 @@ pub async fn list_alerts(
     to_date: Option<String>,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
+    require_in_scope::<AlertsRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id,
+        app_id,
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
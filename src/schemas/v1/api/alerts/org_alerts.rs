@@ -4,10 +4,12 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{Require, AlertsRead};
 
 /// Get active alerts for an organization
 #[get("/platform/<platform_id>/orgs/<org_id>/active-alerts?<limit>")]
 pub async fn get_org_active_alerts(
+    _auth: Require<AlertsRead>,
     platform_id: i64,
     org_id: i64,
     limit: Option<i64>,
@@ -67,6 +69,7 @@ pub async fn get_org_active_alerts(
 /// Get alert statistics for an organization
 #[get("/platform/<platform_id>/orgs/<org_id>/alert-stats?<days>")]
 pub async fn get_org_alert_stats(
+    _auth: Require<AlertsRead>,
     platform_id: i64,
     org_id: i64,
     days: Option<i64>,
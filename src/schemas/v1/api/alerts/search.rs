@@ -4,10 +4,12 @@ use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
+use super::super::rbac::{require_in_scope, AlertsRead, Caller, RequestScope};
 
 /// Search for alerts
 #[get("/platform/<platform_id>/alerts/search?<query>&<org_id>&<page>&<per_page>")]
 pub async fn search_alerts(
+    caller: Caller,
     platform_id: i64,
     query: String,
     org_id: Option<i64>,
@@ -15,6 +17,12 @@ pub async fn search_alerts(
     per_page: Option<i64>,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
+    require_in_scope::<AlertsRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id,
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -8,10 +8,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::user::User;
+use super::super::rbac::{Require, AlertsWrite};
 
 /// Update an alert's status
 #[put("/platform/<platform_id>/alerts/<id>/status", format = "json", data = "<status_data>")]
 pub async fn update_alert_status(
+    _auth: Require<AlertsWrite>,
     platform_id: i64,
     id: i64,
     status_data: Json<UpdateAlertStatusRequest>,
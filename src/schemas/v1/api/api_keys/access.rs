@@ -0,0 +1,81 @@
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use sqlx::{MySql, Pool};
+
+use super::super::super::db::queries as db;
+use db::api_key::ApiKey;
+
+/// Membership of the calling user in the organization owning a key.
+pub struct OrgAccess {
+    pub role: String,
+}
+
+impl OrgAccess {
+    /// Owners and admins manage every key in their organization; everybody
+    /// else only manages the keys they created.
+    pub fn can_manage_all_keys(&self) -> bool {
+        self.role == "owner" || self.role == "admin"
+    }
+}
+
+/// Loads the caller's membership in an organization, failing with
+/// `403 Forbidden` if they are not an accepted member.
+pub async fn require_org_member(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    user_id: i64,
+) -> Result<OrgAccess, (Status, Json<Value>)> {
+    match db::org::get_org_member_role(pool, org_id, user_id).await {
+        Ok(Some(role)) => Ok(OrgAccess { role }),
+        Ok(None) => Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": format!("You are not a member of organization {}", org_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to check organization membership"
+            }))
+        )),
+    }
+}
+
+/// Loads a key and checks that the caller may manage it.
+///
+/// Fails with `404 Not Found` for unknown keys and `403 Forbidden` when the
+/// caller is neither the key's creator nor an owner/admin of its organization.
+pub async fn load_manageable_key(
+    pool: &Pool<MySql>,
+    key_id: i64,
+    user_id: i64,
+) -> Result<ApiKey, (Status, Json<Value>)> {
+    let key = match db::api_key::get_api_key_by_id(pool, key_id).await {
+        Ok(key) => key,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "API key not found",
+                    "message": format!("API key with ID {} does not exist", key_id)
+                }))
+            ));
+        }
+    };
+
+    let access = require_org_member(pool, key.org_id, user_id).await?;
+    if key.user_id != user_id && !access.can_manage_all_keys() {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": "Only the key's creator or an organization owner/admin can manage this key"
+            }))
+        ));
+    }
+
+    Ok(key)
+}
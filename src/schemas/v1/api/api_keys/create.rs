@@ -0,0 +1,128 @@
+use std::sync::Arc;
+use chrono::Utc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::require_org_member;
+use super::super::rbac::{require_in_scope, ApiKeysWrite, Caller, RequestScope};
+use super::token::generate_key;
+use super::types::{ApiKeySecretResponse, CreateApiKeyRequest};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+
+/// Create a new API key.
+///
+/// The full key (`omni_<prefix>_<secret>`) is only part of this response;
+/// it cannot be retrieved again later, only rotated.
+#[post("/platform/<platform_id>/api_keys", format = "json", data = "<request>")]
+pub async fn create_api_key(
+    platform_id: i64,
+    caller: Caller,
+    request: Json<CreateApiKeyRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ApiKeySecretResponse>, (Status, Json<Value>)> {
+    if request.name.trim().is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "API key name must not be empty"
+            }))
+        ));
+    }
+
+    if request.scopes.is_empty() || request.scopes.iter().any(|s| s.trim().is_empty()) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "API keys must be granted at least one non-empty scope"
+            }))
+        ));
+    }
+
+    if let Some(expires_at) = request.expires_at {
+        if expires_at <= Utc::now() {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": "expires_at must be in the future"
+                }))
+            ));
+        }
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    require_in_scope::<ApiKeysWrite>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id: Some(request.org_id),
+        ..Default::default()
+    }).await?;
+    require_org_member(&pool, request.org_id, caller.user.id).await?;
+
+    let generated = match generate_key() {
+        Ok(generated) => generated,
+        Err(e) => {
+            log::error!("Error generating API key: {}", e);
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Key generation failed",
+                    "message": "Failed to generate a random API key"
+                }))
+            ));
+        }
+    };
+
+    match db::api_key::create_api_key(
+        &pool,
+        request.org_id,
+        caller.user.id,
+        request.name.trim(),
+        request.description.as_deref(),
+        &generated.hash,
+        &generated.prefix,
+        &request.scopes,
+        request.expires_at,
+    ).await {
+        Ok(key) => Ok(Json(ApiKeySecretResponse {
+            api_key: key.into(),
+            key: generated.token,
+        })),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to create API key",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,13 @@
+use super::guard::ApiKeyAuth;
+use super::types::ApiKeyResponse;
+use rocket::get;
+use rocket::serde::json::Json;
+
+/// Describe the API key used to authenticate this request.
+///
+/// Lets CI pipelines verify their credentials (and the scopes they carry)
+/// without needing a user session.
+#[get("/platform/<_platform_id>/api_keys/current")]
+pub async fn get_current_api_key(_platform_id: i64, auth: ApiKeyAuth) -> Json<ApiKeyResponse> {
+    Json(auth.key.into())
+}
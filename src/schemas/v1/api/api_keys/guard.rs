@@ -0,0 +1,174 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::State;
+
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::helpers::request::platform_id_from_request;
+use super::token::{hash_secret, hashes_match, parse_token};
+use db::api_key::ApiKey;
+
+/// Reasons an API key can be rejected.
+#[derive(Debug, Clone)]
+pub enum ApiKeyError {
+    /// The route is not platform scoped, so the key cannot be looked up.
+    NoPlatform,
+    /// No key with the presented prefix exists, or the secret is wrong.
+    Invalid,
+    /// The key has been revoked.
+    Revoked,
+    /// The key's `expires_at` has passed.
+    Expired,
+    /// The key is valid but does not grant the required scope.
+    MissingScope(&'static str),
+    /// The platform database could not be reached.
+    Database,
+}
+
+/// An authenticated API key.
+///
+/// Succeeds for requests carrying `Authorization: Bearer omni_<prefix>_<secret>`
+/// on a `/platform/<platform_id>/...` route where the key exists in that
+/// platform's database, the secret matches, and the key is neither revoked nor
+/// expired. Requests without an API key (including JWT bearer tokens) are
+/// forwarded so that other guards get a chance to authenticate them.
+///
+/// A successful authentication updates the key's `last_used_at`.
+#[derive(Debug, Clone)]
+pub struct ApiKeyAuth {
+    pub key: ApiKey,
+    pub platform_id: i64,
+}
+
+impl ApiKeyAuth {
+    /// Checks whether the authenticated key grants the given scope.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.key.has_scope(scope)
+    }
+}
+
+type CachedOutcome = Option<Result<ApiKeyAuth, (Status, ApiKeyError)>>;
+
+async fn authenticate(req: &Request<'_>) -> CachedOutcome {
+    let header = req.headers().get_one("Authorization")?;
+    let token = header.strip_prefix("Bearer ")?.trim();
+    let (prefix, secret) = parse_token(token)?;
+
+    let platform_id = match platform_id_from_request(req) {
+        Some(id) => id,
+        None => return Some(Err((Status::BadRequest, ApiKeyError::NoPlatform))),
+    };
+
+    let db_manager = match req.guard::<&State<Arc<DatabaseManager>>>().await {
+        Outcome::Success(db_manager) => db_manager,
+        _ => return Some(Err((Status::InternalServerError, ApiKeyError::Database))),
+    };
+
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => return Some(Err((Status::Unauthorized, ApiKeyError::Invalid))),
+    };
+
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => return Some(Err((Status::InternalServerError, ApiKeyError::Database))),
+    };
+
+    let key = match db::api_key::get_api_key_by_prefix(&pool, prefix).await {
+        Ok(Some(key)) => key,
+        Ok(None) => return Some(Err((Status::Unauthorized, ApiKeyError::Invalid))),
+        Err(e) => {
+            log::error!("Error looking up API key: {}", e);
+            return Some(Err((Status::InternalServerError, ApiKeyError::Database)));
+        }
+    };
+
+    if !hashes_match(&key.key_hash, &hash_secret(secret)) {
+        return Some(Err((Status::Unauthorized, ApiKeyError::Invalid)));
+    }
+
+    if key.revoked {
+        return Some(Err((Status::Unauthorized, ApiKeyError::Revoked)));
+    }
+
+    if !key.is_usable() {
+        return Some(Err((Status::Unauthorized, ApiKeyError::Expired)));
+    }
+
+    if let Err(e) = db::api_key::touch_api_key(&pool, key.id).await {
+        // Failing to record usage must not lock a CI pipeline out.
+        log::warn!("Failed to update last_used_at for API key {}: {}", key.id, e);
+    }
+
+    Some(Ok(ApiKeyAuth { key, platform_id }))
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for ApiKeyAuth {
+    type Error = ApiKeyError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        // Cache the result so that stacking several key based guards on one
+        // route only hits the database once.
+        let cached: &CachedOutcome = req.local_cache_async(authenticate(req)).await;
+
+        match cached {
+            None => Outcome::Forward(Status::Unauthorized),
+            Some(Ok(auth)) => Outcome::Success(auth.clone()),
+            Some(Err((status, error))) => Outcome::Error((*status, error.clone())),
+        }
+    }
+}
+
+/// A scope an API key can be required to hold.
+///
+/// Declare scopes with [`api_key_scope!`](crate::api_key_scope) and use them as
+/// the parameter of [`ScopedApiKey`].
+pub trait ApiKeyScope: Send + Sync + 'static {
+    const SCOPE: &'static str;
+}
+
+/// Declares a marker type for an API key scope.
+///
+/// ```ignore
+/// api_key_scope!(DeploymentsWrite, "deployments:write");
+/// ```
+#[macro_export]
+macro_rules! api_key_scope {
+    ($name:ident, $scope:expr) => {
+        pub struct $name;
+
+        impl $crate::schemas::v1::api::api_keys::ApiKeyScope for $name {
+            const SCOPE: &'static str = $scope;
+        }
+    };
+}
+
+/// An authenticated API key that holds the scope `S`.
+///
+/// Behaves like [`ApiKeyAuth`] and additionally fails with `403 Forbidden`
+/// when the key does not grant `S::SCOPE`.
+pub struct ScopedApiKey<S: ApiKeyScope> {
+    pub auth: ApiKeyAuth,
+    _scope: PhantomData<S>,
+}
+
+#[rocket::async_trait]
+impl<'r, S: ApiKeyScope> FromRequest<'r> for ScopedApiKey<S> {
+    type Error = ApiKeyError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match ApiKeyAuth::from_request(req).await {
+            Outcome::Success(auth) if auth.has_scope(S::SCOPE) => Outcome::Success(ScopedApiKey {
+                auth,
+                _scope: PhantomData,
+            }),
+            Outcome::Success(_) => Outcome::Error((Status::Forbidden, ApiKeyError::MissingScope(S::SCOPE))),
+            Outcome::Error(e) => Outcome::Error(e),
+            Outcome::Forward(status) => Outcome::Forward(status),
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::require_org_member;
+use super::types::ApiKeyResponse;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use super::super::rbac::{Require, ApiKeysRead};
+
+/// List the API keys of an organization with pagination support.
+///
+/// Organization owners and admins see every key; other members only see
+/// the keys they created. Revoked keys are hidden unless `include_revoked`
+/// is set.
+#[get("/platform/<platform_id>/orgs/<org_id>/api_keys?<page>&<per_page>&<include_revoked>")]
+pub async fn list_api_keys(
+    auth: Require<ApiKeysRead>,
+    platform_id: i64,
+    org_id: i64,
+    page: Option<i64>,
+    per_page: Option<i64>,
+    include_revoked: Option<bool>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let access = require_org_member(&pool, org_id, auth.user_id()).await?;
+    let owner_filter = if access.can_manage_all_keys() { None } else { Some(auth.user_id()) };
+    let include_revoked = include_revoked.unwrap_or(false);
+    let p = page.unwrap_or(0);
+    let pp = per_page.unwrap_or(10);
+
+    let keys = match db::api_key::list_api_keys(&pool, org_id, owner_filter, include_revoked, p, pp).await {
+        Ok(keys) => keys,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retrieve API keys"
+                }))
+            ));
+        }
+    };
+
+    let total_count = match db::api_key::count_api_keys(&pool, org_id, owner_filter, include_revoked).await {
+        Ok(count) => count,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to count API keys"
+                }))
+            ));
+        }
+    };
+
+    let total_pages = if pp > 0 { (total_count + pp - 1) / pp } else { 1 };
+    let api_keys: Vec<ApiKeyResponse> = keys.into_iter().map(ApiKeyResponse::from).collect();
+
+    Ok(Json(json!({
+        "api_keys": api_keys,
+        "pagination": {
+            "page": p,
+            "per_page": pp,
+            "total_count": total_count,
+            "total_pages": total_pages
+        }
+    })))
+}
@@ -0,0 +1,31 @@
+//! API key management module for non-interactive credentials.
+//!
+//! This module provides a REST API for managing scoped API keys, including:
+//! - Creating keys (the secret is only ever returned once)
+//! - Listing keys for an organization
+//! - Rotating a key's secret
+//! - Revoking keys
+//! - Inspecting the key used to authenticate a request
+//!
+//! It also provides the `ApiKeyAuth` and `ScopedApiKey` request guards which
+//! authenticate `Authorization: Bearer omni_<prefix>_<secret>` headers.
+
+// Import and re-export all modules
+pub mod types;
+pub mod token;
+pub mod guard;
+pub mod access;
+pub mod list;
+pub mod create;
+pub mod rotate;
+pub mod revoke;
+pub mod current;
+
+// Re-export all route functions
+pub use types::*;
+pub use guard::{ApiKeyAuth, ApiKeyError, ApiKeyScope, ScopedApiKey};
+pub use list::list_api_keys;
+pub use create::create_api_key;
+pub use rotate::rotate_api_key;
+pub use revoke::revoke_api_key;
+pub use current::get_current_api_key;
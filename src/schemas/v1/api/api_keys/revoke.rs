@@ -0,0 +1,67 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::load_manageable_key;
+use super::super::rbac::{require_in_scope, ApiKeysWrite, Caller, RequestScope};
+use super::types::ApiKeyResponse;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, State};
+
+/// Revoke an API key.
+///
+/// Revocation is permanent; the row is kept for auditing. Revoking an
+/// already revoked key is a no-op.
+#[delete("/platform/<platform_id>/api_keys/<key_id>")]
+pub async fn revoke_api_key(
+    platform_id: i64,
+    key_id: i64,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ApiKeyResponse>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let key = load_manageable_key(&pool, key_id, caller.user.id).await?;
+    require_in_scope::<ApiKeysWrite>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id: Some(key.org_id),
+        ..Default::default()
+    }).await?;
+
+    match db::api_key::revoke_api_key(&pool, key.id, caller.user.id).await {
+        Ok(key) => Ok(Json(key.into())),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to revoke API key",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,95 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::load_manageable_key;
+use super::super::rbac::{require_in_scope, ApiKeysWrite, Caller, RequestScope};
+use super::token::generate_key;
+use super::types::ApiKeySecretResponse;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+
+/// Rotate an API key.
+///
+/// Issues a new prefix and secret for the key, keeping its name, scopes and
+/// expiry. The previous token stops working immediately and the new one is
+/// only returned in this response.
+#[post("/platform/<platform_id>/api_keys/<key_id>/rotate")]
+pub async fn rotate_api_key(
+    platform_id: i64,
+    key_id: i64,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ApiKeySecretResponse>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let key = load_manageable_key(&pool, key_id, caller.user.id).await?;
+    require_in_scope::<ApiKeysWrite>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id: Some(key.org_id),
+        ..Default::default()
+    }).await?;
+    if key.revoked {
+        return Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "API key revoked",
+                "message": "Revoked API keys cannot be rotated"
+            }))
+        ));
+    }
+
+    let generated = match generate_key() {
+        Ok(generated) => generated,
+        Err(e) => {
+            log::error!("Error generating API key: {}", e);
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Key generation failed",
+                    "message": "Failed to generate a random API key"
+                }))
+            ));
+        }
+    };
+
+    match db::api_key::rotate_api_key(&pool, key.id, &generated.hash, &generated.prefix).await {
+        Ok(key) => Ok(Json(ApiKeySecretResponse {
+            api_key: key.into(),
+            key: generated.token,
+        })),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to rotate API key",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
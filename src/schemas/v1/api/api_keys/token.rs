@@ -0,0 +1,65 @@
+use rand::rngs::OsRng;
+use rand::TryRngCore;
+use sha2::{Digest, Sha256};
+
+/// Prefix every API key token starts with.
+pub const TOKEN_PREFIX: &str = "omni_";
+
+/// A freshly generated API key.
+///
+/// Only `hash` and `prefix` are stored; `token` is handed to the caller
+/// once and then discarded.
+pub struct GeneratedKey {
+    pub prefix: String,
+    pub hash: String,
+    pub token: String,
+}
+
+/// Generates a new random API key.
+///
+/// The prefix is 8 hex characters (fits the `VARCHAR(10)` column and never
+/// contains an underscore), and the secret is 32 random bytes hex encoded.
+pub fn generate_key() -> anyhow::Result<GeneratedKey> {
+    let mut rng = OsRng;
+
+    let mut prefix_bytes = [0u8; 4];
+    rng.try_fill_bytes(&mut prefix_bytes)?;
+    let mut secret_bytes = [0u8; 32];
+    rng.try_fill_bytes(&mut secret_bytes)?;
+
+    let prefix = hex::encode(prefix_bytes);
+    let secret = hex::encode(secret_bytes);
+
+    Ok(GeneratedKey {
+        hash: hash_secret(&secret),
+        token: format!("{}{}_{}", TOKEN_PREFIX, prefix, secret),
+        prefix,
+    })
+}
+
+/// Hashes a key secret the way it is stored in `api_keys.key_hash`.
+pub fn hash_secret(secret: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(secret.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Splits a presented token of the form `omni_<prefix>_<secret>` into its
+/// prefix and secret. Returns `None` for anything that is not an API key.
+pub fn parse_token(token: &str) -> Option<(&str, &str)> {
+    let rest = token.strip_prefix(TOKEN_PREFIX)?;
+    let (prefix, secret) = rest.split_once('_')?;
+    if prefix.is_empty() || secret.is_empty() {
+        return None;
+    }
+    Some((prefix, secret))
+}
+
+/// Compares two hashes in constant time with respect to their contents.
+pub fn hashes_match(expected: &str, presented: &str) -> bool {
+    let (a, b) = (expected.as_bytes(), presented.as_bytes());
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
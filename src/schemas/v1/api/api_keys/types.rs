@@ -0,0 +1,67 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::super::super::db::queries::api_key::ApiKey;
+
+/// Request body for creating an API key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub org_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Public view of an API key.
+///
+/// This mirrors the `api_keys` row without the secret hash, and is what
+/// every endpoint returns when describing a key.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeyResponse {
+    pub id: i64,
+    pub org_id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked: bool,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<ApiKey> for ApiKeyResponse {
+    fn from(key: ApiKey) -> Self {
+        let scopes = key.scope_list();
+        Self {
+            id: key.id,
+            org_id: key.org_id,
+            user_id: key.user_id,
+            name: key.name,
+            description: key.description,
+            prefix: key.prefix,
+            scopes,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            revoked: key.revoked,
+            revoked_at: key.revoked_at,
+            revoked_by: key.revoked_by,
+            created_at: key.created_at,
+            updated_at: key.updated_at,
+        }
+    }
+}
+
+/// Response returned when a key is created or rotated.
+///
+/// `key` holds the full bearer token and is never shown again.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApiKeySecretResponse {
+    pub api_key: ApiKeyResponse,
+    pub key: String,
+}
@@ -0,0 +1,550 @@
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::control::MAX_INSTANCES;
+use super::scheduling::ensure_app_exists;
+use super::types::{AutoscalingRuleRequest, SetAutoscalingRequest};
+use db::autoscaling::{AutoscalingRule, AutoscalingRuleInput};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, put, State};
+use std::sync::Arc;
+
+use crate::DatabaseManager;
+use crate::autoscaler::rule::{Comparison, METRIC_TYPES};
+use super::super::rbac::{Require, AppsControl, AppsRead, AppsWrite};
+
+/// Page size used when the request does not specify one.
+const DEFAULT_DECISIONS_LIMIT: i64 = 50;
+
+/// Largest page size a request may ask for.
+const MAX_DECISIONS_LIMIT: i64 = 500;
+
+/// List the autoscaling rules of an application.
+#[get("/platform/<platform_id>/apps/<app_id>/autoscaling/rules")]
+pub async fn list_autoscaling_rules(
+    _auth: Require<AppsRead>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    let app = match db::app::get_app_by_id(&pool, app_id).await {
+        Ok(app) => app,
+        Err(_) => return Err(app_not_found(app_id)),
+    };
+
+    match db::autoscaling::list_autoscaling_rules(&pool, app_id).await {
+        Ok(rules) => Ok(Json(json!({
+            "auto_scaling_enabled": app.auto_scaling_enabled.unwrap_or(false),
+            "rules": rules
+        }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch autoscaling rules"
+            }))
+        )),
+    }
+}
+
+/// Get an autoscaling rule of an application.
+#[get("/platform/<platform_id>/apps/<app_id>/autoscaling/rules/<rule_id>")]
+pub async fn get_autoscaling_rule(
+    _auth: Require<AppsRead>,
+    platform_id: i64,
+    app_id: i64,
+    rule_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<AutoscalingRule>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    find_rule(&pool, app_id, rule_id).await.map(Json)
+}
+
+/// Create an autoscaling rule for an application.
+///
+/// Rules only take effect while autoscaling of the application is turned
+/// on with `PUT /platform/<platform_id>/apps/<app_id>/autoscaling`.
+#[post("/platform/<platform_id>/apps/<app_id>/autoscaling/rules", format = "json", data = "<request>")]
+pub async fn create_autoscaling_rule(
+    auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<AutoscalingRuleRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<AutoscalingRule>, (Status, Json<Value>)> {
+    let input = validate_rule(request.into_inner())?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    ensure_app_exists(&pool, app_id).await?;
+
+    match db::autoscaling::create_autoscaling_rule(&pool, app_id, &input, Some(auth.user_id())).await {
+        Ok(rule) => {
+            trail.resource("autoscaling_rule", rule.id);
+            trail.after(&rule);
+            Ok(Json(rule))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to create autoscaling rule"
+            }))
+        )),
+    }
+}
+
+/// Replace the settings of an autoscaling rule.
+#[put("/platform/<platform_id>/apps/<app_id>/autoscaling/rules/<rule_id>", format = "json", data = "<request>")]
+pub async fn update_autoscaling_rule(
+    _auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    rule_id: i64,
+    request: Json<AutoscalingRuleRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<AutoscalingRule>, (Status, Json<Value>)> {
+    let input = validate_rule(request.into_inner())?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    let existing = find_rule(&pool, app_id, rule_id).await?;
+    trail.resource("autoscaling_rule", rule_id);
+    trail.before(&existing);
+
+    match db::autoscaling::update_autoscaling_rule(&pool, app_id, rule_id, &input).await {
+        Ok(Some(rule)) => {
+            trail.after(&rule);
+            Ok(Json(rule))
+        }
+        Ok(None) => Err(rule_not_found(rule_id)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to update autoscaling rule"
+            }))
+        )),
+    }
+}
+
+/// Delete an autoscaling rule. Decisions it made are kept.
+#[delete("/platform/<platform_id>/apps/<app_id>/autoscaling/rules/<rule_id>")]
+pub async fn delete_autoscaling_rule(
+    _auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    rule_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    let existing = find_rule(&pool, app_id, rule_id).await?;
+    trail.resource("autoscaling_rule", rule_id);
+    trail.before(&existing);
+
+    match db::autoscaling::delete_autoscaling_rule(&pool, app_id, rule_id).await {
+        Ok(true) => Ok(Json(json!({ "status": "deleted" }))),
+        Ok(false) => Err(rule_not_found(rule_id)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to delete autoscaling rule"
+            }))
+        )),
+    }
+}
+
+/// Turn autoscaling of an application on or off.
+///
+/// While it is on, the leader evaluates the application's enabled rules
+/// and changes its instance count when they fire. Scaling the application
+/// manually leaves autoscaling on.
+#[put("/platform/<platform_id>/apps/<app_id>/autoscaling", format = "json", data = "<request>")]
+pub async fn set_app_autoscaling(
+    _auth: Require<AppsControl>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<SetAutoscalingRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    let app = match db::app::get_app_by_id(&pool, app_id).await {
+        Ok(app) => app,
+        Err(_) => return Err(app_not_found(app_id)),
+    };
+
+    let enabled = request.enabled;
+    trail.resource("app", app_id);
+    trail.before(&json!({ "auto_scaling_enabled": app.auto_scaling_enabled.unwrap_or(false) }));
+
+    match db::autoscaling::set_app_autoscaling(&pool, app_id, enabled).await {
+        Ok(()) => {
+            let state = json!({ "auto_scaling_enabled": enabled });
+            trail.after(&state);
+            Ok(Json(state))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to update app autoscaling"
+            }))
+        )),
+    }
+}
+
+/// List the autoscaling decisions of an application, newest first.
+///
+/// Every evaluation of a rule is recorded with the metric averages it
+/// observed and why it did or did not scale the application. Pass the
+/// `next_before` value of a page as `before` to fetch the next one.
+#[get("/platform/<platform_id>/apps/<app_id>/autoscaling/decisions?<rule_id>&<before>&<limit>")]
+pub async fn list_autoscaling_decisions(
+    _auth: Require<AppsRead>,
+    platform_id: i64,
+    app_id: i64,
+    rule_id: Option<i64>,
+    before: Option<i64>,
+    limit: Option<i64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    let limit = limit.unwrap_or(DEFAULT_DECISIONS_LIMIT);
+    if !(1..=MAX_DECISIONS_LIMIT).contains(&limit) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("limit must be between 1 and {}", MAX_DECISIONS_LIMIT)
+            }))
+        ));
+    }
+
+    let mut decisions = match db::autoscaling::list_autoscaling_decisions(&pool, app_id, rule_id, before, limit + 1).await {
+        Ok(decisions) => decisions,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retrieve autoscaling decisions"
+                }))
+            ));
+        }
+    };
+
+    let has_more = decisions.len() as i64 > limit;
+    decisions.truncate(limit as usize);
+    let next_before = if has_more { decisions.last().map(|decision| decision.id) } else { None };
+
+    Ok(Json(json!({
+        "decisions": decisions,
+        "pagination": {
+            "limit": limit,
+            "has_more": has_more,
+            "next_before": next_before
+        }
+    })))
+}
+
+fn validate_rule(request: AutoscalingRuleRequest) -> Result<AutoscalingRuleInput, (Status, Json<Value>)> {
+    let invalid = |message: String| {
+        Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": message
+            }))
+        ))
+    };
+
+    if request.min_instances < 0 || request.max_instances < request.min_instances {
+        return invalid("min_instances must be at least 0 and no more than max_instances".to_string());
+    }
+    if request.max_instances > MAX_INSTANCES {
+        return invalid(format!("max_instances cannot exceed {}", MAX_INSTANCES));
+    }
+    if let Some(target) = request.target_instances {
+        if target < request.min_instances || target > request.max_instances {
+            return invalid("target_instances must be between min_instances and max_instances".to_string());
+        }
+    }
+    if !METRIC_TYPES.contains(&request.metric_type.as_str()) {
+        return invalid(format!("Unknown metric_type '{}'; use one of {}", request.metric_type, METRIC_TYPES.join(", ")));
+    }
+    let custom_metric_name = request.custom_metric_name.filter(|name| !name.trim().is_empty());
+    if request.metric_type == "custom" && custom_metric_name.is_none() {
+        return invalid("custom rules need a custom_metric_name".to_string());
+    }
+    if !request.threshold_value.is_finite() {
+        return invalid("threshold_value must be a finite number".to_string());
+    }
+    let comparison_operator = request
+        .comparison_operator
+        .unwrap_or_else(|| "GreaterThanOrEqualToThreshold".to_string());
+    if Comparison::parse(&comparison_operator).is_none() {
+        return invalid(format!(
+            "Unknown comparison_operator '{}'; use one of {}",
+            comparison_operator,
+            Comparison::NAMES.join(", ")
+        ));
+    }
+
+    let evaluation_periods = request.evaluation_periods.unwrap_or(1);
+    if !(1..=60).contains(&evaluation_periods) {
+        return invalid("evaluation_periods must be between 1 and 60".to_string());
+    }
+    let period_seconds = request.period_seconds.unwrap_or(60);
+    if !(10..=3600).contains(&period_seconds) {
+        return invalid("period_seconds must be between 10 and 3600".to_string());
+    }
+    let scaling_adjustment = request.scaling_adjustment.unwrap_or(1);
+    if scaling_adjustment == 0 && request.target_instances.is_none() {
+        return invalid("scaling_adjustment cannot be 0 unless target_instances is set".to_string());
+    }
+    let cooldown_period_seconds = request.cooldown_period_seconds.unwrap_or(300);
+    if cooldown_period_seconds < 0 {
+        return invalid("cooldown_period_seconds cannot be negative".to_string());
+    }
+
+    Ok(AutoscalingRuleInput {
+        name: request.name,
+        min_instances: request.min_instances,
+        max_instances: request.max_instances,
+        target_instances: request.target_instances,
+        metric_type: request.metric_type,
+        custom_metric_name,
+        threshold_value: request.threshold_value,
+        threshold_unit: request.threshold_unit,
+        comparison_operator,
+        evaluation_periods,
+        period_seconds,
+        scaling_adjustment,
+        cooldown_period_seconds,
+        enabled: request.enabled.unwrap_or(true),
+    })
+}
+
+async fn find_rule(
+    pool: &sqlx::Pool<sqlx::MySql>,
+    app_id: i64,
+    rule_id: i64,
+) -> Result<AutoscalingRule, (Status, Json<Value>)> {
+    match db::autoscaling::get_autoscaling_rule(pool, app_id, rule_id).await {
+        Ok(Some(rule)) => Ok(rule),
+        Ok(None) => Err(rule_not_found(rule_id)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch autoscaling rule"
+            }))
+        )),
+    }
+}
+
+fn app_not_found(app_id: i64) -> (Status, Json<Value>) {
+    (
+        Status::NotFound,
+        Json(json!({
+            "error": "App not found",
+            "message": format!("App with ID {} does not exist", app_id)
+        }))
+    )
+}
+
+fn rule_not_found(rule_id: i64) -> (Status, Json<Value>) {
+    (
+        Status::NotFound,
+        Json(json!({
+            "error": "Autoscaling rule not found",
+            "message": format!("Autoscaling rule with ID {} does not exist for this app", rule_id)
+        }))
+    )
+}
@@ -1,13 +1,263 @@
-use super::types::{Application, ScaleRequest};
+use super::super::super::db::queries as db;
+use super::types::ScaleRequest;
 use rocket::http::Status;
-use rocket::serde::json::{Json, Value};
+use rocket::serde::json::{json, Json, Value};
 use rocket::{put, State};
+use sqlx::{MySql, Pool};
 use std::sync::Arc;
 
 use crate::DatabaseManager;
 
+use libomni::types::db::v1 as types;
+use types::app::App;
+use super::super::rbac::{Require, AppsControl};
+use super::super::audit_log::AuditTrail;
+use super::super::spaces::access::ensure_not_archived;
+
+/// Largest number of instances an application can be scaled to.
+pub const MAX_INSTANCES: i64 = 100;
+
+/// Seconds to wait for a concurrent start, stop or scale of the same application.
+const CONTROL_LOCK_TIMEOUT_SECS: i64 = 10;
+
+/// A change to the run state of an application.
+#[derive(Debug, Clone, Copy)]
+enum ControlAction {
+    Start,
+    Stop,
+    Scale(i64),
+}
+
+impl ControlAction {
+    fn verb(self) -> &'static str {
+        match self {
+            ControlAction::Start => "start",
+            ControlAction::Stop => "stop",
+            ControlAction::Scale(_) => "scale",
+        }
+    }
+}
+
+fn database_error(message: String) -> (Status, Json<Value>) {
+    (
+        Status::InternalServerError,
+        Json(json!({
+            "error": "Database error",
+            "message": message
+        }))
+    )
+}
+
+/// Rejects starting or scaling applications that are frozen, either by
+/// maintenance mode or by living in an archived space.
+async fn ensure_controllable(pool: &Pool<MySql>, app: &App) -> Result<(), (Status, Json<Value>)> {
+    if let Some(space_id) = app.space_id {
+        if let Ok(space) = db::space::get_space_by_id(pool, space_id).await {
+            ensure_not_archived(&space)?;
+        }
+    }
+
+    if app.maintenance_mode.unwrap_or(false) {
+        return Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Maintenance mode",
+                "message": format!("Application '{}' is in maintenance mode", app.name)
+            }))
+        ));
+    }
+
+    Ok(())
+}
+
+/// Creates or terminates instances until the application has `desired`
+/// active instances. Surplus instances are terminated highest index first.
+///
+/// Returns the number of instances created and terminated.
+async fn converge_instances(pool: &Pool<MySql>, app_id: i64, desired: i64) -> anyhow::Result<(i64, i64)> {
+    let active = db::instance::get_active_instances(pool, app_id).await?;
+    let current = active.len() as i64;
+
+    if current < desired {
+        for _ in current..desired {
+            db::instance::create_instance(pool, app_id, db::instance::DEFAULT_INSTANCE_TYPE, None).await?;
+        }
+        return Ok((desired - current, 0));
+    }
+
+    for instance in active.iter().rev().take((current - desired) as usize) {
+        db::instance::update_instance_status(pool, instance.id, "terminated", None, None).await?;
+    }
+    Ok((0, current - desired))
+}
+
+/// Converges the instances of an application like [`converge_instances`],
+/// unless a deployment is being rolled out; the deployment then works
+/// towards the new number of instances itself.
+async fn converge_unless_deploying(pool: &Pool<MySql>, app_id: i64, desired: i64) -> anyhow::Result<(i64, i64)> {
+    if db::deployment::has_deployment_in_progress(pool, app_id).await? {
+        return Ok((0, 0));
+    }
+    converge_instances(pool, app_id, desired).await
+}
+
+/// Applies a control action to an application while holding its control
+/// lock, returning the updated application and the number of instances
+/// created and terminated.
+async fn apply(
+    pool: &Pool<MySql>,
+    app: &App,
+    action: ControlAction,
+) -> Result<(App, i64, i64), (Status, Json<Value>)> {
+    let failed = |e: anyhow::Error| {
+        log::error!("Failed to {} app {}: {:#}", action.verb(), app.id, e);
+        database_error(format!("Failed to {} application", action.verb()))
+    };
+
+    let (updated, created, terminated) = match action {
+        ControlAction::Start => {
+            ensure_controllable(pool, app).await?;
+            let desired = app.instances.unwrap_or(1).max(0);
+            let updated = db::app::set_app_run_state(pool, app.id, Some("started"), None).await.map_err(failed)?;
+            let (created, terminated) = converge_unless_deploying(pool, app.id, desired).await.map_err(failed)?;
+            (updated, created, terminated)
+        }
+        ControlAction::Stop => {
+            let active = db::instance::get_active_instances(pool, app.id).await.map_err(failed)?;
+            db::instance::terminate_all_instances(pool, app.id).await.map_err(failed)?;
+            let updated = db::app::set_app_run_state(pool, app.id, Some("stopped"), None).await.map_err(failed)?;
+            (updated, 0, active.len() as i64)
+        }
+        ControlAction::Scale(instances) => {
+            ensure_controllable(pool, app).await?;
+            let updated = db::app::set_app_run_state(pool, app.id, None, Some(instances)).await.map_err(failed)?;
+            // Stopped applications only record the new size for their next start
+            let running = matches!(updated.status.as_deref(), Some("started" | "starting" | "crashed"));
+            let (created, terminated) = if running {
+                converge_unless_deploying(pool, app.id, instances).await.map_err(failed)?
+            } else {
+                (0, 0)
+            };
+            (updated, created, terminated)
+        }
+    };
+
+    Ok((updated, created, terminated))
+}
+
+/// Loads the application, takes its control lock and applies the action.
+async fn control_app(
+    db_manager: &DatabaseManager,
+    platform_id: i64,
+    app_id: i64,
+    action: ControlAction,
+    trail: &AuditTrail<'_>,
+) -> Result<Json<App>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let lock = match db::app::lock_app_control(&pool, app_id, CONTROL_LOCK_TIMEOUT_SECS).await {
+        Ok(Some(lock)) => lock,
+        Ok(None) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Operation in progress",
+                    "message": format!("Another operation on application {} is in progress; try again", app_id)
+                }))
+            ));
+        }
+        Err(_) => return Err(database_error("Failed to lock application".to_string())),
+    };
+
+    let result = match db::app::get_app_by_id(&pool, app_id).await {
+        Ok(app) => {
+            trail.resource("app", app_id);
+            trail.before(&app);
+            apply(&pool, &app, action).await.map(|(updated, created, terminated)| {
+                trail.detail("instances_created", created);
+                trail.detail("instances_terminated", terminated);
+                updated
+            })
+        }
+        Err(_) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        )),
+    };
+
+    db::app::unlock_app_control(lock, app_id).await;
+
+    let app = result?;
+    trail.after(&app);
+    Ok(Json(app))
+}
+
+/// Scales an application on behalf of the orchestrator itself, such as the
+/// autoscaler, with the same lock and checks as `PUT .../scale`.
+///
+/// Returns `None` without waiting if another operation on the application
+/// is in progress.
+pub async fn scale_app_to(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    instances: i64,
+) -> Result<Option<App>, (Status, Json<Value>)> {
+    let lock = match db::app::lock_app_control(pool, app_id, 0).await {
+        Ok(Some(lock)) => lock,
+        Ok(None) => return Ok(None),
+        Err(_) => return Err(database_error("Failed to lock application".to_string())),
+    };
+
+    let result = match db::app::get_app_by_id(pool, app_id).await {
+        Ok(app) => apply(pool, &app, ControlAction::Scale(instances.clamp(0, MAX_INSTANCES))).await,
+        Err(_) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        )),
+    };
+
+    db::app::unlock_app_control(lock, app_id).await;
+    result.map(|(updated, _, _)| Some(updated))
+}
+
 /// Start a specific application.
 ///
+/// The application is marked `started` and instances are created until it
+/// runs its configured number of instances. Applications in maintenance
+/// mode or in an archived space cannot be started.
+///
 /// # Arguments
 ///
 /// * `platform_id` - Platform identifier
@@ -16,20 +266,23 @@ use crate::DatabaseManager;
 ///
 /// # Returns
 ///
-/// The updated application if found, or None if not found
+/// The updated application
 #[put("/platform/<platform_id>/apps/<app_id>/start")]
 pub async fn start_app(
+    _auth: Require<AppsControl>,
+    trail: AuditTrail<'_>,
     platform_id: i64,
-    app_id: String,
+    app_id: i64,
     db_manager: &State<Arc<DatabaseManager>>
-) -> Result<Json<Application>, (Status, Json<Value>)> {
-    // This function is already marked as todo!, but we need to add platform-specific
-    // handling for future implementation
-    todo!()
+) -> Result<Json<App>, (Status, Json<Value>)> {
+    control_app(db_manager, platform_id, app_id, ControlAction::Start, &trail).await
 }
 
 /// Stop a specific application.
 ///
+/// The application is marked `stopped` and all of its instances are
+/// terminated. Its configured number of instances is kept for the next start.
+///
 /// # Arguments
 ///
 /// * `platform_id` - Platform identifier
@@ -38,20 +291,26 @@ pub async fn start_app(
 ///
 /// # Returns
 ///
-/// The updated application if found, or None if not found
+/// The updated application
 #[put("/platform/<platform_id>/apps/<app_id>/stop")]
 pub async fn stop_app(
+    _auth: Require<AppsControl>,
+    trail: AuditTrail<'_>,
     platform_id: i64,
-    app_id: String,
+    app_id: i64,
     db_manager: &State<Arc<DatabaseManager>>
-) -> Result<Json<Application>, (Status, Json<Value>)> {
-    // This function is already marked as todo!, but we need to add platform-specific
-    // handling for future implementation
-    todo!()
+) -> Result<Json<App>, (Status, Json<Value>)> {
+    control_app(db_manager, platform_id, app_id, ControlAction::Stop, &trail).await
 }
 
 /// Scale a specific application.
 ///
+/// Sets the number of instances the application runs. A running application
+/// is scaled immediately, terminating its highest-indexed instances first
+/// when scaling down; a stopped one starts with the new number of instances.
+/// Memory comes from the application's allocation, so a `memory` in the
+/// request is ignored.
+///
 /// # Arguments
 ///
 /// * `platform_id` - Platform identifier
@@ -61,15 +320,26 @@ pub async fn stop_app(
 ///
 /// # Returns
 ///
-/// The updated application if found, or None if not found
+/// The updated application
 #[put("/platform/<platform_id>/apps/<app_id>/scale", format = "json", data = "<scale>")]
 pub async fn scale_app(
+    _auth: Require<AppsControl>,
+    trail: AuditTrail<'_>,
     platform_id: i64,
-    app_id: String, 
+    app_id: i64,
     scale: Json<ScaleRequest>,
     db_manager: &State<Arc<DatabaseManager>>
-) -> Result<Json<Application>, (Status, Json<Value>)> {
-    // This function is already marked as todo!, but we need to add platform-specific
-    // handling for future implementation
-    todo!()
-}
\ No newline at end of file
+) -> Result<Json<App>, (Status, Json<Value>)> {
+    let instances = i64::from(scale.instances);
+    if !(0..=MAX_INSTANCES).contains(&instances) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("instances must be between 0 and {}", MAX_INSTANCES)
+            }))
+        ));
+    }
+
+    control_app(db_manager, platform_id, app_id, ControlAction::Scale(instances), &trail).await
+}
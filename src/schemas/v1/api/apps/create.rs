@@ -1,5 +1,6 @@
 use super::super::super::db::queries as db;
 use super::types::CreateAppRequest;
+use super::super::rbac::{require_in_scope, AppsWrite, Caller, RequestScope};
 use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::{post, State};
@@ -16,6 +17,7 @@ use types::app::App;
 ///
 /// * `platform_id` - Platform identifier
 /// * `app_request` - JSON data containing application details
+/// * `caller` - Authenticated caller, who must hold `apps:write` in the target organization or space
 /// * `db_manager` - Database manager for accessing platform-specific pools
 ///
 /// # Returns
@@ -25,8 +27,16 @@ use types::app::App;
 pub async fn create_app(
     platform_id: i64,
     app_request: Json<CreateAppRequest>,
+    caller: Caller,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<App>, (Status, Json<Value>)> {
+    require_in_scope::<AppsWrite>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id: Some(app_request.org_id),
+        space_id: app_request.space_id,
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -55,10 +65,44 @@ pub async fn create_app(
         }
     };
 
+    if let Some(space_id) = app_request.space_id {
+        match db::space::get_space_by_id(&pool, space_id).await {
+            Ok(space) if space.org_id != app_request.org_id => {
+                return Err((
+                    Status::BadRequest,
+                    Json(json!({
+                        "error": "Invalid space",
+                        "message": format!("Space {} does not belong to organization {}", space_id, app_request.org_id)
+                    }))
+                ));
+            }
+            Ok(space) if space.is_archived() => {
+                return Err((
+                    Status::Conflict,
+                    Json(json!({
+                        "error": "Space archived",
+                        "message": format!("Space '{}' is archived and does not accept new applications", space.name)
+                    }))
+                ));
+            }
+            Ok(_) => {}
+            Err(_) => {
+                return Err((
+                    Status::NotFound,
+                    Json(json!({
+                        "error": "Space not found",
+                        "message": format!("Space with ID {} does not exist", space_id)
+                    }))
+                ));
+            }
+        }
+    }
+
     match db::app::create_app(
         &pool,
         &app_request.name,
         app_request.org_id,
+        app_request.space_id,
         None,
         None,
         None,
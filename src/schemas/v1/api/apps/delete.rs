@@ -5,6 +5,8 @@ use rocket::{delete, State};
 use std::sync::Arc;
 
 use crate::DatabaseManager;
+use super::super::rbac::{Require, AppsDelete};
+use super::super::audit_log::AuditTrail;
 
 /// Delete a specific application.
 ///
@@ -19,6 +21,8 @@ use crate::DatabaseManager;
 /// A JSON response indicating success or an error message
 #[delete("/platform/<platform_id>/apps/<app_id>")]
 pub async fn delete_app(
+    _auth: Require<AppsDelete>,
+    trail: AuditTrail<'_>,
     platform_id: i64,
     app_id: String,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -53,6 +57,11 @@ pub async fn delete_app(
 
     match app_id.parse::<i64>() {
         Ok(id) => {
+            trail.resource("app", id);
+            if let Ok(existing) = db::app::get_app_by_id(&pool, id).await {
+                trail.before(&existing);
+            }
+
             match db::app::delete_app(&pool, id).await {
                 Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
                 Err(_) => {
@@ -0,0 +1,93 @@
+use super::super::super::db::queries as db;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use std::sync::Arc;
+
+use crate::DatabaseManager;
+use super::super::rbac::{Require, AppsRead};
+
+/// Page size used when the request does not specify one.
+const DEFAULT_EVENTS_LIMIT: i64 = 50;
+
+/// Largest page size a request may ask for.
+const MAX_EVENTS_LIMIT: i64 = 500;
+
+/// List the events of an application, newest first.
+///
+/// Events record what the orchestrator did to the application on its own,
+/// such as the reconciler creating, restarting or terminating instances.
+/// Pass the `next_before` value of a page as `before` to fetch the next one.
+#[get("/platform/<platform_id>/apps/<app_id>/events?<before>&<limit>")]
+pub async fn list_app_events(
+    _auth: Require<AppsRead>,
+    platform_id: i64,
+    app_id: i64,
+    before: Option<i64>,
+    limit: Option<i64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    let limit = limit.unwrap_or(DEFAULT_EVENTS_LIMIT);
+    if !(1..=MAX_EVENTS_LIMIT).contains(&limit) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("limit must be between 1 and {}", MAX_EVENTS_LIMIT)
+            }))
+        ));
+    }
+
+    let mut events = match db::app_event::list_app_events(&pool, app_id, before, limit + 1).await {
+        Ok(events) => events,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retrieve app events"
+                }))
+            ));
+        }
+    };
+
+    let has_more = events.len() as i64 > limit;
+    events.truncate(limit as usize);
+    let next_before = if has_more { events.last().map(|event| event.id) } else { None };
+
+    Ok(Json(json!({
+        "events": events,
+        "pagination": {
+            "limit": limit,
+            "has_more": has_more,
+            "next_before": next_before
+        }
+    })))
+}
@@ -9,14 +9,22 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::app::{App, AppWithInstances};
+use super::super::rbac::{require_in_scope, Caller, Require, AppsRead, RequestScope};
 
 /// Get app with instances
 #[get("/platform/<platform_id>/app_with_instances/<app_id>")]
 pub async fn get_app_with_instances(
+    caller: Caller,
     db_manager: &State<Arc<DatabaseManager>>, 
     platform_id: i64,
     app_id: i64
 ) -> Result<Json<AppWithInstances>, (Status, Json<Value>)> {
+    require_in_scope::<AppsRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id: Some(app_id),
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -72,6 +80,7 @@ pub async fn get_app_with_instances(
 /// The application if found, or None if not found
 #[get("/platform/<platform_id>/apps/<app_id>")]
 pub async fn get_app(
+    _auth: Require<AppsRead>,
     platform_id: i64,
     app_id: i64, 
     db_manager: &State<Arc<DatabaseManager>>
@@ -131,6 +140,7 @@ pub async fn get_app(
 /// Statistics for the application
 #[get("/platform/<platform_id>/apps/<app_id>/stats")]
 pub async fn get_app_stats(
+    _auth: Require<AppsRead>,
     platform_id: i64,
     app_id: String, 
     db_manager: &State<Arc<DatabaseManager>>
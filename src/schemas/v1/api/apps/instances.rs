@@ -5,10 +5,12 @@ use rocket::{get, State};
 use std::sync::Arc;
 
 use crate::DatabaseManager;
+use super::super::rbac::{Require, InstancesRead};
 
 // List all instances for an application with pagination
 #[get("/platform/<platform_id>/apps/<app_id>/instances?<page>&<per_page>")]
 pub async fn list_instances(
+    _auth: Require<InstancesRead>,
     platform_id: i64,
     app_id: i64,
     page: Option<i64>,
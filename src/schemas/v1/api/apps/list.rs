@@ -5,6 +5,7 @@ use rocket::{get, State};
 use std::sync::Arc;
 
 use crate::DatabaseManager;
+use super::super::rbac::{Require, AppsRead};
 
 /// List all applications with pagination support.
 ///
@@ -20,6 +21,7 @@ use crate::DatabaseManager;
 /// A JSON array of applications or an error if pagination parameters are missing
 #[get("/platform/<platform_id>/apps?<page>&<per_page>")]
 pub async fn list_apps(
+    _auth: Require<AppsRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -108,6 +110,7 @@ pub async fn list_apps(
 /// Count the total number of applications.
 #[get("/platform/<platform_id>/app-count")]
 pub async fn count_apps(
+    _auth: Require<AppsRead>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>
 ) -> Result<Json<i64>, (Status, Json<Value>)> {
@@ -9,6 +9,10 @@
 //! - Scaling applications
 //! - Deleting applications
 //! - Releasing new versions of applications
+//! - Moving applications between spaces
+//! - Listing events recorded by the orchestrator
+//! - Configuring how instances are placed on workers
+//! - Managing autoscaling rules and reviewing autoscaling decisions
 
 // Import and re-export all route modules
 pub mod types;
@@ -20,6 +24,10 @@ pub mod control;
 pub mod delete;
 pub mod release;
 pub mod instances;
+pub mod space;
+pub mod events;
+pub mod scheduling;
+pub mod autoscaling;
 
 // Re-export types for easier access
 pub use types::*;
@@ -33,4 +41,11 @@ pub use control::{start_app, stop_app, scale_app};
 pub use delete::delete_app;
 pub use release::create_release;
 pub use instances::list_instances;
+pub use space::move_app_to_space;
+pub use events::list_app_events;
+pub use scheduling::{get_app_scheduling, update_app_scheduling};
+pub use autoscaling::{
+    list_autoscaling_rules, get_autoscaling_rule, create_autoscaling_rule, update_autoscaling_rule,
+    delete_autoscaling_rule, set_app_autoscaling, list_autoscaling_decisions,
+};
 
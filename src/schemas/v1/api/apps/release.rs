@@ -1,14 +1,19 @@
 use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
 use rocket::http::{ContentType, Status};
+use rocket::serde::json::{json, Json, Value};
 use rocket::{post, Data, State};
 use std::sync::Arc;
 
+use libomni::types::db::v1 as types;
+use types::build::Build;
+
+use crate::artifacts::ArtifactStore;
 use crate::DatabaseManager;
+use super::super::rbac::{Require, BuildsWrite};
 
 /// Releases a new version of the target application by uploading an artifact.
-/// TODO: @tristanpoland Review if we actually need this or should drop in favor
-///       of using the deploy route.
-/// 
+///
 /// # Arguments
 ///
 /// * `platform_id` - Platform identifier
@@ -17,17 +22,20 @@ use crate::DatabaseManager;
 /// * `content_type` - The content type of the data being uploaded
 /// * `data` - The data stream of the artifact being uploaded
 /// * `db_manager` - Database manager for accessing platform-specific pools
+/// * `artifact_store` - Store the artifact is kept in
 ///
 /// # Returns
 ///
-/// * `Status::Ok` - If the artifact is successfully uploaded and added to the build jobs list
-/// * `Status::BadRequest` - If there is an error in the upload process
+/// * The pending build recording the artifact, its checksum and its size
+/// * `Status::BadRequest` - If the upload is malformed or does not match its `sha256` field
+/// * `Status::NotFound` - If the platform or application does not exist
 ///
 /// # Details
 ///
-/// This route handles the release of a new version of an application by:
-/// 1. Uploading the provided artifact to the build artifacts list.
-/// 2. Adding the artifact to the list of build jobs for the Forge instances to pick up and process.
+/// The artifact is uploaded in a `media`, `file` or `upload` form field, with
+/// an optional `sha256` field holding its expected hex-encoded SHA-256. It is
+/// stored in the configured artifact store and recorded as a pending build
+/// of the application, which builders lease from the build queue.
 ///
 /// The actual implementation of the release process is delegated to the `helpers::release::release`
 /// function, as it is quite extensive.
@@ -37,25 +45,66 @@ use crate::DatabaseManager;
     data = "<data>"
 )]
 pub async fn create_release(
+    _auth: Require<BuildsWrite>,
+    trail: AuditTrail<'_>,
     platform_id: i64,
-    app_id: String,
+    app_id: i64,
     release_version: String,
     content_type: &ContentType,
     data: Data<'_>,
     db_manager: &State<Arc<DatabaseManager>>,
-) -> Result<Status, Status> {
-    // We need to modify the helper function to work with platform-specific DBs
-    // For now, we'll just pass the helper what it needs, but ideally the helper should be updated to use platform pools
+    artifact_store: &State<Arc<dyn ArtifactStore>>,
+) -> Result<Json<Build>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
 
-    // Get platform info and pass to helper
-    match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
-        Ok(_) => {
-            // We found the platform, proceed with release
-            super::super::helpers::release::release(app_id, release_version, content_type, data).await
-        },
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
         Err(_) => {
-            // Platform not found
-            Err(Status::NotFound)
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
         }
+    };
+
+    if db::app::get_app_by_id(&pool, app_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        ));
     }
-}
\ No newline at end of file
+
+    let build = super::super::helpers::release::release(
+        &pool,
+        artifact_store.inner().as_ref(),
+        platform_id,
+        app_id,
+        release_version,
+        content_type,
+        data,
+    )
+    .await?;
+
+    trail.resource("build", build.id);
+    trail.after(&build);
+    Ok(Json(build))
+}
@@ -0,0 +1,189 @@
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::types::UpdateSchedulingPolicyRequest;
+use db::scheduling::AppSchedulingPolicy;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, put, State};
+use std::sync::Arc;
+
+use crate::DatabaseManager;
+use crate::scheduler::placement::{AntiAffinity, Strategy, TAINT_EFFECTS};
+use super::super::rbac::{Require, AppsRead, AppsWrite};
+
+/// Get the scheduling policy of an application.
+///
+/// Applications that never configured one get the default policy: the
+/// platform's strategy, preferred anti-affinity, no node selector and no
+/// tolerations.
+#[get("/platform/<platform_id>/apps/<app_id>/scheduling")]
+pub async fn get_app_scheduling(
+    _auth: Require<AppsRead>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<AppSchedulingPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    ensure_app_exists(&pool, app_id).await?;
+
+    match db::scheduling::get_app_scheduling_policy(&pool, app_id).await {
+        Ok(policy) => Ok(Json(policy.unwrap_or_else(|| AppSchedulingPolicy::default_for(app_id)))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch app scheduling policy"
+            }))
+        )),
+    }
+}
+
+/// Replace the scheduling policy of an application.
+///
+/// The policy applies to instances placed from now on; instances that are
+/// already running stay on their workers.
+#[put("/platform/<platform_id>/apps/<app_id>/scheduling", format = "json", data = "<request>")]
+pub async fn update_app_scheduling(
+    auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<UpdateSchedulingPolicyRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<AppSchedulingPolicy>, (Status, Json<Value>)> {
+    let request = request.into_inner();
+    validate_policy(&request)?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+    ensure_app_exists(&pool, app_id).await?;
+
+    trail.resource("app_scheduling_policy", app_id);
+    if let Ok(Some(existing)) = db::scheduling::get_app_scheduling_policy(&pool, app_id).await {
+        trail.before(&existing);
+    }
+
+    let node_selector = request.node_selector.as_ref().map(|selector| json!(selector));
+    let tolerations = request.tolerations.as_ref().map(|tolerations| json!(tolerations));
+    match db::scheduling::set_app_scheduling_policy(
+        &pool,
+        app_id,
+        request.strategy.as_deref(),
+        request.anti_affinity.as_deref().unwrap_or("preferred"),
+        node_selector.as_ref(),
+        tolerations.as_ref(),
+        Some(auth.user_id()),
+    )
+    .await
+    {
+        Ok(policy) => {
+            trail.after(&policy);
+            Ok(Json(policy))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to update app scheduling policy"
+            }))
+        )),
+    }
+}
+
+fn validate_policy(request: &UpdateSchedulingPolicyRequest) -> Result<(), (Status, Json<Value>)> {
+    let invalid = |message: String| {
+        Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": message
+            }))
+        ))
+    };
+
+    if let Some(strategy) = request.strategy.as_deref() {
+        if Strategy::parse(strategy).is_none() {
+            return invalid(format!("Unknown strategy '{}'; use 'binpack' or 'spread'", strategy));
+        }
+    }
+    if let Some(anti_affinity) = request.anti_affinity.as_deref() {
+        if AntiAffinity::parse(anti_affinity).is_none() {
+            return invalid(format!("Unknown anti_affinity '{}'; use 'none', 'preferred' or 'required'", anti_affinity));
+        }
+    }
+    for toleration in request.tolerations.iter().flatten() {
+        if toleration.key.is_empty() {
+            return invalid("Every toleration needs a key".to_string());
+        }
+        if let Some(effect) = toleration.effect.as_deref() {
+            if !TAINT_EFFECTS.contains(&effect) {
+                return invalid(format!("Unknown taint effect '{}'; use one of {}", effect, TAINT_EFFECTS.join(", ")));
+            }
+        }
+    }
+    Ok(())
+}
+
+pub(super) async fn ensure_app_exists(pool: &sqlx::Pool<sqlx::MySql>, app_id: i64) -> Result<(), (Status, Json<Value>)> {
+    match db::app::get_app_by_id(pool, app_id).await {
+        Ok(_) => Ok(()),
+        Err(_) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        )),
+    }
+}
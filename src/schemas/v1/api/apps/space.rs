@@ -0,0 +1,129 @@
+use super::super::super::db::queries as db;
+use super::types::MoveAppRequest;
+use super::super::rbac::{require_in_scope, AppsWrite, Require, RequestScope};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{put, State};
+use std::sync::Arc;
+
+use crate::DatabaseManager;
+
+use libomni::types::db::v1 as types;
+use types::app::App;
+
+/// Move an application to another space of its organization.
+///
+/// # Arguments
+///
+/// * `platform_id` - Platform identifier
+/// * `app_id` - The ID of the application to move
+/// * `request` - Target space, or `null` to take the application out of its space
+/// * `db_manager` - Database manager for accessing platform-specific pools
+///
+/// # Returns
+///
+/// The moved application. The caller needs `apps:write` on the application
+/// and in the target space; applications cannot be moved into or out of
+/// archived spaces.
+#[put("/platform/<platform_id>/apps/<app_id>/space", format = "json", data = "<request>")]
+pub async fn move_app_to_space(
+    auth: Require<AppsWrite>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<MoveAppRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<App>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let (org_id, current_space_id) = match db::app::get_app_scope(&pool, app_id).await {
+        Ok(Some(scope)) => scope,
+        _ => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "App not found",
+                    "message": format!("App with ID {} does not exist", app_id)
+                }))
+            ));
+        }
+    };
+
+    for space_id in [current_space_id, request.space_id].into_iter().flatten() {
+        let space = match db::space::get_space_by_id(&pool, space_id).await {
+            Ok(space) => space,
+            Err(_) => {
+                return Err((
+                    Status::NotFound,
+                    Json(json!({
+                        "error": "Space not found",
+                        "message": format!("Space with ID {} does not exist", space_id)
+                    }))
+                ));
+            }
+        };
+
+        if space.org_id != org_id {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid space",
+                    "message": "Applications can only be moved between spaces of their own organization"
+                }))
+            ));
+        }
+
+        if space.is_archived() {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Space archived",
+                    "message": format!("Space '{}' is archived; restore it first", space.name)
+                }))
+            ));
+        }
+    }
+
+    require_in_scope::<AppsWrite>(db_manager, auth.caller(), RequestScope {
+        platform_id: Some(platform_id),
+        org_id: Some(org_id),
+        space_id: request.space_id,
+        ..Default::default()
+    }).await?;
+
+    match db::space::move_app_to_space(&pool, app_id, request.space_id).await {
+        Ok(app) => Ok(Json(app)),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to move application",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
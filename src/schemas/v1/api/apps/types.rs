@@ -1,5 +1,9 @@
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
+use crate::scheduler::placement::Toleration;
+
 // TODO: @tristanpoland Review if we actually need this or should drop in favor of using a central struct. Regardless we will need to move these to the modals module and eventually to LibOmni.
 
 /// Represents an application in the system.
@@ -28,8 +32,10 @@ pub struct Application {
 pub struct ScaleRequest {
     /// Number of instances to scale to
     pub instances: i32,
-    /// Memory allocation in MB to scale to
-    pub memory: i32,
+    /// Memory allocation in MB to scale to. Accepted for compatibility but
+    /// ignored; memory comes from the application's allocation.
+    #[serde(default)]
+    pub memory: Option<i32>,
 }
 
 /// Statistics for an application's resource usage and performance.
@@ -58,6 +64,15 @@ pub struct CreateAppRequest {
     pub instances: i64,
     /// Organization ID that owns the application
     pub org_id: i64,
+    /// Optional space within the organization to create the application in
+    pub space_id: Option<i64>,
+}
+
+/// Request data for moving an application between spaces.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MoveAppRequest {
+    /// Space to move the application to, or `null` to remove it from its space
+    pub space_id: Option<i64>,
 }
 
 /// Request data for updating an existing application.
@@ -71,4 +86,66 @@ pub struct UpdateAppRequest {
     pub instances: i64,
     /// Organization ID that owns the application
     pub org_id: i64,
-}
\ No newline at end of file
+}
+
+/// Request data for replacing the scheduling policy of an application.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateSchedulingPolicyRequest {
+    /// `binpack` or `spread`; omit to use the platform default
+    #[serde(default)]
+    pub strategy: Option<String>,
+    /// `none`, `preferred` or `required`; defaults to `preferred`
+    #[serde(default)]
+    pub anti_affinity: Option<String>,
+    /// Labels a worker must have to run the application's instances
+    #[serde(default)]
+    pub node_selector: Option<BTreeMap<String, String>>,
+    /// Worker taints the application's instances tolerate
+    #[serde(default)]
+    pub tolerations: Option<Vec<Toleration>>,
+}
+
+/// Request data for creating or replacing an autoscaling rule.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AutoscalingRuleRequest {
+    #[serde(default)]
+    pub name: Option<String>,
+    pub min_instances: i64,
+    pub max_instances: i64,
+    /// Instance count to move to when the rule fires; overrides
+    /// `scaling_adjustment`
+    #[serde(default)]
+    pub target_instances: Option<i64>,
+    /// `cpu`, `memory`, `http_throughput`, `http_latency`, `queue_depth` or `custom`
+    pub metric_type: String,
+    /// Name of the metric watched by a `custom` rule
+    #[serde(default)]
+    pub custom_metric_name: Option<String>,
+    pub threshold_value: f64,
+    #[serde(default)]
+    pub threshold_unit: Option<String>,
+    /// Defaults to `GreaterThanOrEqualToThreshold`
+    #[serde(default)]
+    pub comparison_operator: Option<String>,
+    /// Consecutive periods the comparison must hold in; defaults to 1
+    #[serde(default)]
+    pub evaluation_periods: Option<i64>,
+    /// Length of a period in seconds; defaults to 60
+    #[serde(default)]
+    pub period_seconds: Option<i64>,
+    /// Instances added, or removed if negative, when the rule fires; defaults to 1
+    #[serde(default)]
+    pub scaling_adjustment: Option<i64>,
+    /// Seconds after an autoscaling of the app during which the rule does
+    /// not scale it again; defaults to 300
+    #[serde(default)]
+    pub cooldown_period_seconds: Option<i64>,
+    #[serde(default)]
+    pub enabled: Option<bool>,
+}
+
+/// Request data for turning autoscaling of an application on or off.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetAutoscalingRequest {
+    pub enabled: bool,
+}
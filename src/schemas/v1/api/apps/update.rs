@@ -9,6 +9,8 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::app::App;
+use super::super::rbac::{Require, AppsWrite};
+use super::super::audit_log::AuditTrail;
 
 /// Update an existing application.
 ///
@@ -24,6 +26,8 @@ use types::app::App;
 /// The updated application
 #[post("/platform/<platform_id>/apps/<app_id>", format = "json", data = "<app_request>")]
 pub async fn update_app(
+    _auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
     platform_id: i64,
     app_request: Json<UpdateAppRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -57,6 +61,11 @@ pub async fn update_app(
         }
     };
 
+    trail.resource("app", app_id);
+    if let Ok(existing) = db::app::get_app_by_id(&pool, app_id).await {
+        trail.before(&existing);
+    }
+
     match db::app::update_app(
         &pool,
         app_id,
@@ -67,7 +76,10 @@ pub async fn update_app(
         None,
         None,
     ).await {
-        Ok(app) => Ok(Json(app)),
+        Ok(app) => {
+            trail.after(&app);
+            Ok(Json(app))
+        }
         Err(_) => {
             Err((
                 Status::InternalServerError,
@@ -5,16 +5,27 @@ use rocket::get;
 use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::State;
+use super::super::rbac::{require_in_scope, AuditLogsRead, Caller, RequestScope};
 
 /// List all audit log entries for a given app_id with pagination support.
-#[get("/platform/<platform_id>/audit_logs/<app_id>?<page>&<per_page>")]
+///
+/// Ranked after the static `audit_logs/...` routes, which it would otherwise
+/// collide with.
+#[get("/platform/<platform_id>/audit_logs/<app_id>?<page>&<per_page>", rank = 2)]
 pub async fn list_audit_logs_for_app(
+    caller: Caller,
     platform_id: i64,
     app_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
+    require_in_scope::<AuditLogsRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id: Some(app_id),
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -0,0 +1,237 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+use chrono::{Duration, Utc};
+use serde_json::json;
+use sqlx::{MySql, Pool};
+use tokio::io::AsyncWriteExt;
+
+use crate::artifacts::ArtifactStore;
+use crate::config::AuditConfig;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::signing::{self, ExportFormat, SIGNATURE_ALGORITHM};
+use db::audit_log::{compute_entry_hash, AuditLogArchive, NewAuditLogArchive, GENESIS_HASH};
+
+/// Maximum number of entries written to one archive file.
+const ARCHIVE_BATCH_SIZE: i64 = 10_000;
+
+/// Outcome of an archiving run for one platform.
+#[derive(Debug)]
+pub enum ArchiveRun {
+    /// The platform keeps its audit log forever
+    Disabled,
+    /// Another node or request is archiving this platform
+    Busy,
+    /// Archives written in this run, possibly none
+    Completed(Vec<AuditLogArchive>),
+}
+
+/// Moves the entries of a platform's audit log that are older than its
+/// retention period into archive files.
+///
+/// Entries are archived oldest first in contiguous stretches of the hash
+/// chain. Each stretch is written as a JSON lines file with a signed
+/// manifest next to it, staged under `<archive_dir>/platform-<id>/` and
+/// stored in the artifact store, and only removed from the database once
+/// the store holds both files. The last hash of each archive is
+/// kept in `audit_log_archives` to anchor verification of the entries that
+/// remain. Stretches that do not verify are never archived, so tampering
+/// cannot be hidden by moving the evidence out of the database.
+///
+/// A database lock ensures only one node archives a platform at a time.
+pub async fn archive_platform(
+    pool: &Pool<MySql>,
+    store: &dyn ArtifactStore,
+    platform_id: i64,
+    config: &AuditConfig,
+) -> anyhow::Result<ArchiveRun> {
+    let retention_days = match db::audit_log::get_audit_retention_policy(pool).await?.retention_days {
+        Some(days) if days > 0 => days,
+        _ => return Ok(ArchiveRun::Disabled),
+    };
+
+    // Named locks are server wide, so the lock name includes the database
+    let mut lock_conn = pool.acquire().await?;
+    let locked = sqlx::query_scalar::<_, Option<i64>>("SELECT GET_LOCK(CONCAT('omni_audit_archive:', DATABASE()), 0)")
+        .fetch_one(&mut *lock_conn)
+        .await
+        .context("Failed to acquire audit archive lock")?;
+    if locked != Some(1) {
+        return Ok(ArchiveRun::Busy);
+    }
+
+    let cutoff = Utc::now() - Duration::days(retention_days.into());
+    let result = archive_before(pool, store, platform_id, config, cutoff).await;
+
+    if let Err(e) = sqlx::query("SELECT RELEASE_LOCK(CONCAT('omni_audit_archive:', DATABASE()))")
+        .execute(&mut *lock_conn)
+        .await
+    {
+        log::warn!("Failed to release audit archive lock of platform {}: {}", platform_id, e);
+    }
+
+    result.map(ArchiveRun::Completed)
+}
+
+async fn archive_before(
+    pool: &Pool<MySql>,
+    store: &dyn ArtifactStore,
+    platform_id: i64,
+    config: &AuditConfig,
+    cutoff: chrono::DateTime<Utc>,
+) -> anyhow::Result<Vec<AuditLogArchive>> {
+    let dir = PathBuf::from(&config.archive_dir).join(format!("platform-{}", platform_id));
+    let mut archives = Vec::new();
+
+    loop {
+        let (anchor_id, anchor_hash) = match db::audit_log::get_latest_audit_log_archive(pool).await? {
+            Some(archive) => (archive.last_id, archive.last_hash),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+
+        let up_to = match db::audit_log::last_audit_log_id_before(pool, anchor_id, cutoff).await? {
+            Some(id) => id,
+            None => break,
+        };
+
+        let entries = db::audit_log::list_chained_audit_logs(pool, anchor_id, up_to, ARCHIVE_BATCH_SIZE).await?;
+        let (first, last) = match (entries.first(), entries.last()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => break,
+        };
+
+        let mut expected = anchor_hash.as_str();
+        for entry in &entries {
+            let intact = entry.prev_hash == expected
+                && entry.entry_hash.as_deref() == Some(compute_entry_hash(entry).as_str());
+            if !intact {
+                anyhow::bail!("Audit log hash chain is broken at entry {}; not archiving", entry.id);
+            }
+            expected = entry.entry_hash.as_deref().unwrap_or_default();
+        }
+
+        let mut content = ExportFormat::Jsonl.header();
+        ExportFormat::Jsonl.write_entries(&entries, &mut content)?;
+        let content_sha256 = signing::content_sha256(content.as_bytes());
+        let signature = signing::sign(content.as_bytes());
+
+        let file_name = format!("audit-{:012}-{:012}.jsonl", first.id, last.id);
+        let path = dir.join(&file_name);
+        let manifest_name = format!("{}.manifest.json", file_name);
+        let manifest_path = dir.join(&manifest_name);
+        let mut new_archive = NewAuditLogArchive {
+            first_id: first.id,
+            last_id: last.id,
+            entry_count: entries.len() as i64,
+            first_prev_hash: first.prev_hash.clone(),
+            last_hash: last.entry_hash.clone().unwrap_or_default(),
+            oldest_at: first.created_at,
+            newest_at: last.created_at,
+            path: String::new(),
+            content_sha256,
+            signature,
+        };
+
+        let manifest = json!({
+            "platform_id": platform_id,
+            "file": file_name,
+            "format": "jsonl",
+            "first_id": new_archive.first_id,
+            "last_id": new_archive.last_id,
+            "entry_count": new_archive.entry_count,
+            "first_prev_hash": new_archive.first_prev_hash,
+            "last_hash": new_archive.last_hash,
+            "oldest_at": new_archive.oldest_at,
+            "newest_at": new_archive.newest_at,
+            "content_sha256": new_archive.content_sha256,
+            "signature": new_archive.signature,
+            "signature_algorithm": new_archive.signature.as_ref().map(|_| SIGNATURE_ALGORITHM),
+            "created_at": Utc::now(),
+        });
+
+        tokio::fs::create_dir_all(&dir)
+            .await
+            .with_context(|| format!("Failed to create archive directory {}", dir.display()))?;
+        let manifest = serde_json::to_string_pretty(&manifest)?;
+        write_file(&path, content.as_bytes()).await?;
+        write_file(&manifest_path, manifest.as_bytes()).await?;
+
+        // The entries are only deleted once the store holds the archive
+        let stored = async {
+            let url = store
+                .put(&archive_key(platform_id, &file_name), &path, &new_archive.content_sha256, content.len() as u64)
+                .await?;
+            store
+                .put(
+                    &archive_key(platform_id, &manifest_name),
+                    &manifest_path,
+                    &signing::content_sha256(manifest.as_bytes()),
+                    manifest.len() as u64,
+                )
+                .await?;
+            anyhow::Ok(url)
+        }
+        .await;
+        for staged in [&path, &manifest_path] {
+            if let Err(e) = tokio::fs::remove_file(staged).await {
+                log::warn!("Failed to remove staged archive file {}: {}", staged.display(), e);
+            }
+        }
+        new_archive.path = stored.context("Failed to store audit log archive")?;
+
+        let archive = db::audit_log::record_audit_log_archive(pool, &new_archive).await?;
+        log::info!(
+            "Archived audit log entries {}-{} of platform {} to {}",
+            archive.first_id, archive.last_id, platform_id, archive.path
+        );
+        archives.push(archive);
+    }
+
+    Ok(archives)
+}
+
+/// Key an audit log archive file of a platform is stored under.
+fn archive_key(platform_id: i64, file_name: &str) -> String {
+    format!("platforms/{}/audit/{}", platform_id, file_name)
+}
+
+/// Writes a file and flushes it to disk before returning.
+async fn write_file(path: &std::path::Path, content: &[u8]) -> anyhow::Result<()> {
+    let mut file = tokio::fs::File::create(path)
+        .await
+        .with_context(|| format!("Failed to create {}", path.display()))?;
+    file.write_all(content).await?;
+    file.sync_all().await?;
+    Ok(())
+}
+
+/// Runs the archiver for every platform, logging failures.
+pub async fn archive_all_platforms(db_manager: &DatabaseManager, store: &dyn ArtifactStore, config: &AuditConfig) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for audit archiving: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for audit archiving: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        if let Err(e) = archive_platform(&pool, store, platform_id, config).await {
+            log::error!("Failed to archive audit log of platform {}: {:#}", platform_id, e);
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{MySql, Pool};
+
+use super::super::super::db::queries as db;
+use db::audit_log::{compute_entry_hash, GENESIS_HASH};
+
+/// Number of entries read from the database at a time while verifying.
+const VERIFY_BATCH_SIZE: i64 = 1000;
+
+/// Maximum number of breaks listed in a verification report.
+const MAX_REPORTED_BREAKS: usize = 100;
+
+/// A point at which the hash chain does not verify.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainBreak {
+    /// ID of the entry at which the chain breaks
+    pub id: i64,
+    /// `prev_hash_mismatch` if entries before this one were removed,
+    /// inserted or reordered, `hash_mismatch` if the entry was modified,
+    /// `missing_hash` if it was never hashed, or `head_mismatch` if entries
+    /// were removed from the end of the log
+    pub kind: &'static str,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+}
+
+/// Result of walking a platform's audit log hash chain.
+#[derive(Debug, Clone, Serialize)]
+pub struct ChainVerification {
+    pub valid: bool,
+    pub entries_checked: i64,
+    pub first_id: Option<i64>,
+    pub last_id: Option<i64>,
+    /// Entry the walk started after: the last archived entry, or 0
+    pub anchor_id: i64,
+    pub anchor_hash: String,
+    pub head_id: i64,
+    pub head_hash: String,
+    pub break_count: usize,
+    /// The first breaks found, at most 100
+    pub breaks: Vec<ChainBreak>,
+    pub verified_at: DateTime<Utc>,
+}
+
+/// Walks the hash chain from the last archive (or the beginning) to the
+/// current head, recomputing every entry's hash.
+///
+/// The walk continues from each entry's stored hash, so a single modified
+/// entry is reported once rather than breaking every entry after it.
+pub async fn verify_chain(pool: &Pool<MySql>) -> anyhow::Result<ChainVerification> {
+    // Entries appended while the walk runs are not covered by this snapshot
+    let head = db::audit_log::get_audit_chain_head(pool).await?;
+    let (anchor_id, anchor_hash) = match db::audit_log::get_latest_audit_log_archive(pool).await? {
+        Some(archive) => (archive.last_id, archive.last_hash),
+        None => (0, GENESIS_HASH.to_string()),
+    };
+
+    let mut breaks = Vec::new();
+    let mut break_count = 0;
+    let mut report = |entry_break: ChainBreak| {
+        break_count += 1;
+        if breaks.len() < MAX_REPORTED_BREAKS {
+            breaks.push(entry_break);
+        }
+    };
+
+    let mut expected = anchor_hash.clone();
+    let mut last_id = anchor_id;
+    let mut first_id = None;
+    let mut entries_checked = 0;
+
+    loop {
+        let entries = db::audit_log::list_chained_audit_logs(pool, last_id, head.last_id, VERIFY_BATCH_SIZE).await?;
+        if entries.is_empty() {
+            break;
+        }
+
+        for entry in &entries {
+            entries_checked += 1;
+            first_id.get_or_insert(entry.id);
+
+            if entry.prev_hash != expected {
+                report(ChainBreak {
+                    id: entry.id,
+                    kind: "prev_hash_mismatch",
+                    expected: Some(expected.clone()),
+                    found: Some(entry.prev_hash.clone()),
+                });
+            }
+
+            let computed = compute_entry_hash(entry);
+            match entry.entry_hash.as_deref() {
+                Some(stored) if stored == computed => {}
+                Some(stored) => report(ChainBreak {
+                    id: entry.id,
+                    kind: "hash_mismatch",
+                    expected: Some(computed.clone()),
+                    found: Some(stored.to_string()),
+                }),
+                None => report(ChainBreak {
+                    id: entry.id,
+                    kind: "missing_hash",
+                    expected: Some(computed.clone()),
+                    found: None,
+                }),
+            }
+
+            expected = entry.entry_hash.clone().unwrap_or(computed);
+            last_id = entry.id;
+        }
+    }
+
+    if head.last_id > anchor_id && (last_id != head.last_id || expected != head.last_hash) {
+        report(ChainBreak {
+            id: head.last_id,
+            kind: "head_mismatch",
+            expected: Some(head.last_hash.clone()),
+            found: Some(expected.clone()),
+        });
+    }
+
+    Ok(ChainVerification {
+        valid: break_count == 0,
+        entries_checked,
+        first_id,
+        last_id: (last_id > anchor_id).then_some(last_id),
+        anchor_id,
+        anchor_hash,
+        head_id: head.last_id,
+        head_hash: head.last_hash,
+        break_count,
+        breaks,
+        verified_at: Utc::now(),
+    })
+}
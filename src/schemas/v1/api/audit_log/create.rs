@@ -1,6 +1,9 @@
 use std::sync::Arc;
 use crate::DatabaseManager;
 use super::super::super::db::queries as db;
+use super::trail::AuditTrail;
+use super::types::CreateAuditLogRequest;
+use db::audit_log::AuditEntry;
 use rocket::post;
 use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
@@ -8,14 +11,43 @@ use rocket::State;
 
 use libomni::types::db::v1 as types;
 use types::audit_log::AuditLog;
+use super::super::rbac::{require_in_scope, Require, AuditLogsWrite, RequestScope};
 
 /// Creates a new audit log entry in the system.
+///
+/// The entry is attributed to the caller and marked with `"source": "manual"`
+/// in its details, so it can always be told apart from the records written
+/// automatically for API requests.
 #[post("/platform/<platform_id>/audit_log", format = "json", data = "<audit_log>")]
 pub async fn create_audit_log(
+    auth: Require<AuditLogsWrite>,
+    trail: AuditTrail<'_>,
     platform_id: i64,
-    audit_log: Json<AuditLog>,
+    audit_log: Json<CreateAuditLogRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<AuditLog>, (Status, Json<Value>)> {
+    if audit_log.action.trim().is_empty() || audit_log.resource_type.trim().is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "action and resource_type must not be empty"
+            }))
+        ));
+    }
+
+    // Entries may only be attached to organizations and apps the caller can write audit logs for
+    let scope = if audit_log.org_id.is_some() || audit_log.app_id.is_some() {
+        require_in_scope::<AuditLogsWrite>(db_manager, auth.caller(), RequestScope {
+            platform_id: Some(platform_id),
+            org_id: audit_log.org_id,
+            app_id: audit_log.app_id,
+            ..Default::default()
+        }).await?.scope
+    } else {
+        RequestScope::default()
+    };
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -44,14 +76,44 @@ pub async fn create_audit_log(
         }
     };
 
-    match db::audit_log::create_audit_log(
-        &pool,
-        audit_log.user_id,
-        audit_log.org_id,
-        &audit_log.action,
-        &audit_log.resource_type,
-        audit_log.resource_id.clone(),
-    ).await {
+    let mut details = match audit_log.details.clone() {
+        Some(Value::Object(details)) => details,
+        Some(other) => {
+            let mut details = serde_json::Map::new();
+            details.insert("data".to_string(), other);
+            details
+        }
+        None => serde_json::Map::new(),
+    };
+    details.insert("source".to_string(), json!("manual"));
+
+    let entry = AuditEntry {
+        user_id: Some(auth.user_id()),
+        org_id: scope.org_id,
+        space_id: scope.space_id,
+        app_id: scope.app_id,
+        action: audit_log.action.clone(),
+        resource_type: audit_log.resource_type.clone(),
+        resource_id: audit_log.resource_id.clone(),
+        before_state: None,
+        after_state: None,
+        details: Some(Value::Object(details)),
+        ip_address: trail.ip_address().map(str::to_string),
+        user_agent: trail.user_agent().map(str::to_string),
+        request_id: Some(trail.request_id().to_string()),
+        status: "success".to_string(),
+    };
+
+    let result = match db::audit_log::insert_audit_entry(&pool, &entry).await {
+        Ok(id) => {
+            // The entry is the record of this request
+            trail.skip();
+            db::audit_log::get_audit_log_by_id(&pool, id).await
+        }
+        Err(e) => Err(e),
+    };
+
+    match result {
         Ok(result) => Ok(Json(result)),
         Err(_) => Err((
             Status::InternalServerError,
@@ -61,4 +123,4 @@ pub async fn create_audit_log(
             }))
         )),
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,226 @@
+use std::io::Cursor;
+use std::sync::Arc;
+
+use rocket::fairing::{Fairing, Info, Kind};
+use rocket::http::{Header, Method};
+use rocket::request::{FromRequest, Outcome};
+use rocket::{Data, Request, Response};
+use serde_json::{json, Map, Value};
+use uuid::Uuid;
+
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::rbac::{Caller, RequestScope};
+use super::trail::AuditContext;
+use db::audit_log::AuditEntry;
+
+/// Header carrying the correlation ID of a request.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Fairing that writes an audit record for every mutating API request.
+///
+/// Each request is given a correlation ID, taken from a well-formed
+/// `X-Request-Id` request header or generated, and echoed back in the
+/// response. Once a `POST`, `PUT`, `PATCH` or `DELETE` request on a
+/// platform has been handled, successfully or not, a record is written to
+/// that platform's `audit_logs` with the caller, the resource, the client IP
+/// and user agent, the correlation ID and, when the handler recorded them
+/// through `AuditTrail`, the resource state before and after the change.
+///
+/// Records are written in the background so auditing never delays or fails
+/// the request itself.
+pub struct AuditFairing;
+
+fn is_mutating(method: Method) -> bool {
+    matches!(method, Method::Post | Method::Put | Method::Patch | Method::Delete)
+}
+
+/// Accepts client supplied correlation IDs only if they are short and
+/// consist of characters that are safe to log and echo back.
+fn sanitize_request_id(value: &str) -> Option<String> {
+    let valid = !value.is_empty()
+        && value.len() <= 64
+        && value.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    valid.then(|| value.to_string())
+}
+
+/// Derives the resource type and ID from the matched route.
+///
+/// The resource is the last static path segment that is followed by a
+/// parameter (`/apps/<app_id>/start` names app `<app_id>`), or the last
+/// static segment for collection routes such as `POST /apps`.
+fn route_resource(req: &Request<'_>) -> (String, Option<String>) {
+    let route = match req.route() {
+        Some(route) => route,
+        None => return ("unknown".to_string(), None),
+    };
+
+    let template: Vec<&str> = route.uri.unmounted_origin.path().segments().collect();
+    let actual: Vec<&str> = req.routed_segments(0..).collect();
+
+    let mut resource = None;
+    let mut last_static = None;
+    for (i, segment) in template.iter().enumerate() {
+        if segment.starts_with('<') {
+            if let (Some(name), Some(value)) = (i.checked_sub(1).and_then(|j| template.get(j)), actual.get(i)) {
+                if !name.starts_with('<') && *name != "platform" {
+                    resource = Some((name.to_string(), Some(value.to_string())));
+                }
+            }
+        } else {
+            last_static = Some(segment.to_string());
+        }
+    }
+
+    match resource {
+        Some(resource) => resource,
+        None => (last_static.unwrap_or_else(|| "unknown".to_string()), None),
+    }
+}
+
+/// Computes the top-level fields that differ between two states.
+fn diff(before: &Value, after: &Value) -> Option<Value> {
+    let (before, after) = match (before.as_object(), after.as_object()) {
+        (Some(before), Some(after)) => (before, after),
+        _ => return None,
+    };
+
+    let mut changes = Map::new();
+    for key in before.keys().chain(after.keys()) {
+        let (from, to) = (before.get(key), after.get(key));
+        if from != to && !changes.contains_key(key) {
+            changes.insert(key.clone(), json!({ "from": from, "to": to }));
+        }
+    }
+
+    Some(Value::Object(changes))
+}
+
+#[rocket::async_trait]
+impl Fairing for AuditFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Audit logging of mutating requests",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _data: &mut Data<'_>) {
+        let request_id = req
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .and_then(sanitize_request_id)
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let ip_address = req.client_ip().map(|ip| ip.to_string());
+        let user_agent = req.headers().get_one("User-Agent").map(str::to_string);
+
+        req.local_cache(|| AuditContext {
+            request_id,
+            ip_address,
+            user_agent,
+            ..Default::default()
+        });
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let context = AuditContext::of(req);
+        res.set_header(Header::new(REQUEST_ID_HEADER, context.request_id.clone()));
+
+        if !is_mutating(req.method()) || req.route().is_none() {
+            return;
+        }
+
+        // Query parameters are chosen by the client; only the path names the
+        // resources a request acted on
+        let scope = RequestScope::from_path(req);
+        let platform_id = match scope.platform_id {
+            Some(platform_id) => platform_id,
+            // Audit logs live in the platform databases
+            None => return,
+        };
+
+        let draft = match context.draft.lock() {
+            Ok(mut draft) if !draft.skip => std::mem::take(&mut *draft),
+            _ => return,
+        };
+
+        let user_id = match Caller::from_request(req).await {
+            Outcome::Success(caller) => Some(caller.user.id),
+            _ => None,
+        };
+
+        let status = res.status();
+        let mut details = draft.details;
+        details.insert("method".to_string(), json!(req.method().as_str()));
+        details.insert("path".to_string(), json!(req.uri().path().as_str()));
+        details.insert("status_code".to_string(), json!(status.code));
+
+        // Keep the error message of failed requests; the body is put back untouched.
+        if status.code >= 400 {
+            if let Ok(body) = res.body_mut().to_string().await {
+                if let Some(error) = serde_json::from_str::<Value>(&body).ok().and_then(|v| v.get("error").cloned()) {
+                    details.insert("error".to_string(), error);
+                } else if !body.is_empty() && body.len() <= 512 {
+                    details.insert("error".to_string(), json!(body));
+                }
+                res.set_sized_body(body.len(), Cursor::new(body));
+            }
+        }
+
+        if let (Some(before), Some(after)) = (&draft.before_state, &draft.after_state) {
+            if let Some(changes) = diff(before, after) {
+                details.insert("changes".to_string(), changes);
+            }
+        }
+
+        let (route_type, route_id) = route_resource(req);
+        let action = draft
+            .action
+            .or_else(|| req.route().and_then(|route| route.name.as_ref().map(|name| name.to_string())))
+            .unwrap_or_else(|| format!("{} {}", req.method(), route_type));
+
+        let entry = AuditEntry {
+            user_id,
+            org_id: scope.org_id,
+            space_id: scope.space_id,
+            app_id: scope.app_id,
+            action,
+            resource_type: draft.resource_type.unwrap_or(route_type),
+            resource_id: draft.resource_id.or(route_id),
+            before_state: draft.before_state,
+            after_state: draft.after_state,
+            details: Some(Value::Object(details)),
+            ip_address: context.ip_address.clone(),
+            user_agent: context.user_agent.clone(),
+            request_id: Some(context.request_id.clone()),
+            status: if status.code < 400 { "success" } else { "failure" }.to_string(),
+        };
+
+        let db_manager = match req.rocket().state::<Arc<DatabaseManager>>() {
+            Some(db_manager) => db_manager.clone(),
+            None => return,
+        };
+
+        tokio::spawn(async move {
+            if let Err(e) = write_entry(&db_manager, platform_id, &entry).await {
+                log::error!("Failed to write audit record for request {}: {:#}", entry.request_id.as_deref().unwrap_or(""), e);
+            }
+        });
+    }
+}
+
+async fn write_entry(db_manager: &DatabaseManager, platform_id: i64, entry: &AuditEntry) -> anyhow::Result<()> {
+    // Requests against platforms that do not exist have nowhere to be recorded
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => return Ok(()),
+    };
+
+    let pool = db_manager
+        .get_platform_pool(&platform.name, platform_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+
+    db::audit_log::insert_audit_entry(&pool, entry).await?;
+    Ok(())
+}
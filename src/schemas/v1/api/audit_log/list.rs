@@ -5,10 +5,12 @@ use rocket::get;
 use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::State;
+use super::super::rbac::{Require, AuditLogsRead};
 
 /// List audit log entries with pagination support.
 #[get("/platform/<platform_id>/audit_logs?<page>&<per_page>")]
 pub async fn list_audit_logs(
+    _auth: Require<AuditLogsRead>,
     platform_id: i64,
     page: Option<u32>,
     per_page: Option<u32>,
@@ -1,7 +1,35 @@
+//! Audit logging for the V1 API.
+//!
+//! This module provides:
+//! - `AuditFairing`, which records every mutating request automatically
+//! - The `AuditTrail` guard, through which handlers add the affected
+//!   resource and its state before and after the change
+//! - Hash chaining of entries, with verification of the chain
+//! - Signed JSON lines and CSV exports
+//! - Retention policies that move old entries into archive files
+//! - Routes for listing and searching audit logs and recording entries by hand
+
 pub mod app_logs;
+pub mod archive;
+pub mod chain;
 pub mod create;
+pub mod export;
+pub mod fairing;
 pub mod list;
+pub mod retention;
+pub mod search;
+pub mod signing;
+pub mod trail;
+pub mod types;
+pub mod verify;
 
 pub use app_logs::list_audit_logs_for_app;
 pub use create::create_audit_log;
-pub use list::list_audit_logs;
\ No newline at end of file
+pub use export::export_audit_logs;
+pub use fairing::AuditFairing;
+pub use list::list_audit_logs;
+pub use retention::{archive_audit_logs, get_audit_retention, list_audit_log_archives, update_audit_retention};
+pub use search::{count_audit_logs, search_audit_logs};
+pub use trail::AuditTrail;
+pub use types::*;
+pub use verify::verify_audit_log;
@@ -0,0 +1,248 @@
+use std::sync::Arc;
+use crate::artifacts::ArtifactStore;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::archive::{self, ArchiveRun};
+use super::trail::AuditTrail;
+use super::types::UpdateAuditRetentionRequest;
+use db::audit_log::{AuditLogArchive, AuditRetentionPolicy};
+use rocket::{get, post, put};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+use super::super::rbac::{Require, AuditLogsManage, AuditLogsRead};
+
+/// Longest retention period that can be configured, in days.
+const MAX_RETENTION_DAYS: i32 = 36_500;
+
+/// Get the platform's audit log retention policy.
+#[get("/platform/<platform_id>/audit_logs/retention")]
+pub async fn get_audit_retention(
+    _auth: Require<AuditLogsRead>,
+    platform_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<AuditRetentionPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::audit_log::get_audit_retention_policy(&pool).await {
+        Ok(policy) => Ok(Json(policy)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch audit retention policy"
+            }))
+        )),
+    }
+}
+
+/// Set the platform's audit log retention policy.
+///
+/// Entries older than `retention_days` are moved into archive files by the
+/// archiver; they are never deleted outright.
+#[put("/platform/<platform_id>/audit_logs/retention", format = "json", data = "<request>")]
+pub async fn update_audit_retention(
+    auth: Require<AuditLogsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    request: Json<UpdateAuditRetentionRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<AuditRetentionPolicy>, (Status, Json<Value>)> {
+    if let Some(days) = request.retention_days {
+        if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": format!("retention_days must be between 1 and {}, or null", MAX_RETENTION_DAYS)
+                }))
+            ));
+        }
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    trail.resource("audit_retention_policy", platform_id);
+    if let Ok(existing) = db::audit_log::get_audit_retention_policy(&pool).await {
+        trail.before(&existing);
+    }
+
+    match db::audit_log::set_audit_retention_policy(&pool, request.retention_days, auth.user_id()).await {
+        Ok(policy) => {
+            trail.after(&policy);
+            Ok(Json(policy))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to update audit retention policy"
+            }))
+        )),
+    }
+}
+
+/// Archive the entries that are past the retention period now, instead of
+/// waiting for the next scheduled run.
+#[post("/platform/<platform_id>/audit_logs/archive")]
+pub async fn archive_audit_logs(
+    _auth: Require<AuditLogsManage>,
+    platform_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+    artifact_store: &State<Arc<dyn ArtifactStore>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match archive::archive_platform(&pool, artifact_store.inner().as_ref(), platform_id, &crate::config::SERVER_CONFIG.audit).await {
+        Ok(ArchiveRun::Completed(archives)) => Ok(Json(json!({ "archives": archives }))),
+        Ok(ArchiveRun::Disabled) => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "No retention policy",
+                "message": "This platform keeps its audit log forever; set a retention policy first"
+            }))
+        )),
+        Ok(ArchiveRun::Busy) => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Archive in progress",
+                "message": "The audit log of this platform is already being archived"
+            }))
+        )),
+        Err(e) => {
+            log::error!("Failed to archive audit log of platform {}: {:#}", platform_id, e);
+            Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Archive failed",
+                    "message": format!("{:#}", e)
+                }))
+            ))
+        }
+    }
+}
+
+/// List the archive files the platform's audit log has been moved into.
+#[get("/platform/<platform_id>/audit_logs/archives")]
+pub async fn list_audit_log_archives(
+    _auth: Require<AuditLogsRead>,
+    platform_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<AuditLogArchive>>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::audit_log::list_audit_log_archives(&pool).await {
+        Ok(archives) => Ok(Json(archives)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to list audit log archives"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,268 @@
+use std::sync::Arc;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::export::parse_time;
+use super::types::AuditLogSearchQuery;
+use db::audit_log::{AuditLogFilter, AuditLogGrouping};
+use rocket::get;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+use super::super::rbac::{require_in_scope, AuditLogsRead, Authorization, Caller, RequestScope};
+
+/// Page size used when the request does not specify one.
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+/// Largest page size a search may request.
+const MAX_SEARCH_LIMIT: i64 = 500;
+
+fn bad_request(message: String) -> (Status, Json<Value>) {
+    (
+        Status::BadRequest,
+        Json(json!({
+            "error": "Invalid request",
+            "message": message
+        }))
+    )
+}
+
+fn database_error(message: &str) -> (Status, Json<Value>) {
+    (
+        Status::InternalServerError,
+        Json(json!({
+            "error": "Database error",
+            "message": message
+        }))
+    )
+}
+
+/// The scope a search asks to read, named by its `org_id`, `space_id` and
+/// `app_id` filters.
+fn search_scope(platform_id: i64, query: &AuditLogSearchQuery) -> RequestScope {
+    RequestScope {
+        platform_id: Some(platform_id),
+        org_id: query.org_id,
+        space_id: query.space_id,
+        app_id: query.app_id,
+    }
+}
+
+/// Validates the search parameters and turns them into a filter confined to
+/// the scope the caller was authorized in.
+///
+/// Only system administrators may search across organizations; everyone
+/// else has to name an organization, space or application, whose resolved
+/// scope, not the query parameters themselves, selects the entries.
+fn build_filter(query: &AuditLogSearchQuery, authorization: &Authorization) -> Result<AuditLogFilter, (Status, Json<Value>)> {
+    let scope = &authorization.scope;
+    if scope.org_id.is_none() && !authorization.system_admin {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": "Searching audit logs requires an org_id, space_id or app_id"
+            }))
+        ));
+    }
+
+    let time = |name: &str, value: &Option<String>| match value.as_deref() {
+        Some(value) => parse_time(value)
+            .map(Some)
+            .ok_or_else(|| bad_request(format!("Invalid '{}' time '{}'", name, value))),
+        None => Ok(None),
+    };
+    let text = |value: &Option<String>| value.as_deref().map(str::trim).filter(|v| !v.is_empty()).map(str::to_string);
+
+    let status = text(&query.status);
+    if let Some(status) = status.as_deref() {
+        if !["success", "failure", "warning"].contains(&status) {
+            return Err(bad_request(format!(
+                "Invalid status '{}'; use 'success', 'failure' or 'warning'",
+                status
+            )));
+        }
+    }
+
+    let filter = AuditLogFilter {
+        user_id: query.user_id,
+        org_id: scope.org_id,
+        space_id: scope.space_id,
+        app_id: scope.app_id,
+        action: text(&query.action),
+        resource_type: text(&query.resource_type),
+        resource_id: text(&query.resource_id),
+        status,
+        request_id: text(&query.request_id),
+        ip_address: text(&query.ip_address),
+        from: time("from", &query.from)?,
+        to: time("to", &query.to)?,
+    };
+
+    if let (Some(from), Some(to)) = (filter.from, filter.to) {
+        if from >= to {
+            return Err(bad_request("'from' must be before 'to'".to_string()));
+        }
+    }
+
+    Ok(filter)
+}
+
+/// Cursors are opaque to clients; they encode the ID of the last entry of
+/// the previous page.
+fn encode_cursor(id: i64) -> String {
+    URL_SAFE_NO_PAD.encode(format!("id:{}", id))
+}
+
+fn decode_cursor(cursor: &str) -> Option<i64> {
+    let decoded = URL_SAFE_NO_PAD.decode(cursor).ok()?;
+    String::from_utf8(decoded).ok()?.strip_prefix("id:")?.parse().ok()
+}
+
+/// Search audit log entries.
+///
+/// Filters by user, organization, space, application, action, resource type and
+/// ID, status, correlation ID, client IP and a time window, returning the
+/// newest entries first. Pages are continued by passing `next_cursor` back
+/// as `cursor`; `total_count` is the number of entries matching the filters
+/// across all pages.
+#[get("/platform/<platform_id>/audit_logs/search?<query..>", rank = 1)]
+pub async fn search_audit_logs(
+    caller: Caller,
+    platform_id: i64,
+    query: AuditLogSearchQuery,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let authorization = require_in_scope::<AuditLogsRead>(db_manager, &caller, search_scope(platform_id, &query)).await?;
+    let filter = build_filter(&query, &authorization)?;
+    let before_id = match query.cursor.as_deref() {
+        Some(cursor) => Some(decode_cursor(cursor).ok_or_else(|| bad_request("Invalid cursor".to_string()))?),
+        None => None,
+    };
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    // One extra entry tells whether there is another page
+    let mut audit_logs = db::audit_log::search_audit_logs(&pool, &filter, before_id, limit + 1)
+        .await
+        .map_err(|_| database_error("Failed to search audit logs"))?;
+    let has_more = audit_logs.len() as i64 > limit;
+    audit_logs.truncate(limit as usize);
+
+    let total_count = db::audit_log::count_audit_logs_with_filter(&pool, &filter)
+        .await
+        .map_err(|_| database_error("Failed to count audit logs"))?;
+
+    let next_cursor = if has_more {
+        audit_logs.last().map(|log| encode_cursor(log.id))
+    } else {
+        None
+    };
+
+    Ok(Json(json!({
+        "audit_logs": audit_logs,
+        "pagination": {
+            "limit": limit,
+            "has_more": has_more,
+            "next_cursor": next_cursor,
+            "total_count": total_count
+        }
+    })))
+}
+
+/// Count audit log entries matching the search filters.
+///
+/// With `group_by` (`action`, `status`, `resource_type`, `user_id`,
+/// `org_id` or `app_id`) the count is also broken down per value of that
+/// field, most frequent first.
+#[get("/platform/<platform_id>/audit_logs/count?<group_by>&<query..>", rank = 1)]
+pub async fn count_audit_logs(
+    caller: Caller,
+    platform_id: i64,
+    group_by: Option<&str>,
+    query: AuditLogSearchQuery,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let authorization = require_in_scope::<AuditLogsRead>(db_manager, &caller, search_scope(platform_id, &query)).await?;
+    let filter = build_filter(&query, &authorization)?;
+    let grouping = match group_by {
+        Some(group_by) => Some(AuditLogGrouping::parse(group_by).ok_or_else(|| {
+            bad_request(format!(
+                "Cannot group by '{}'; use action, status, resource_type, user_id, org_id or app_id",
+                group_by
+            ))
+        })?),
+        None => None,
+    };
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let total_count = db::audit_log::count_audit_logs_with_filter(&pool, &filter)
+        .await
+        .map_err(|_| database_error("Failed to count audit logs"))?;
+
+    let mut response = json!({ "total_count": total_count });
+    if let Some(grouping) = grouping {
+        let groups = db::audit_log::count_audit_logs_grouped(&pool, &filter, grouping)
+            .await
+            .map_err(|_| database_error("Failed to count audit logs"))?;
+        response["group_by"] = json!(group_by);
+        response["groups"] = json!(groups);
+    }
+
+    Ok(Json(response))
+}
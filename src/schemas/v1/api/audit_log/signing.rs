@@ -0,0 +1,128 @@
+use hmac::{Hmac, Mac};
+use rocket::http::ContentType;
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+
+use super::super::super::db::queries as db;
+use db::audit_log::ChainedAuditLog;
+
+/// Name of the signature algorithm, as reported alongside signatures.
+pub const SIGNATURE_ALGORITHM: &str = "HMAC-SHA256";
+
+/// Columns of CSV exports, in order.
+const CSV_COLUMNS: &[&str] = &[
+    "id", "created_at", "user_id", "org_id", "app_id", "action", "resource_type", "resource_id",
+    "status", "ip_address", "user_agent", "request_id", "before_state", "after_state", "details",
+    "prev_hash", "entry_hash",
+];
+
+/// File format of audit log exports and archives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// Comma separated values with a header row; JSON columns hold compact JSON
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.to_ascii_lowercase().as_str() {
+            "jsonl" | "ndjson" => Some(ExportFormat::Jsonl),
+            "csv" => Some(ExportFormat::Csv),
+            _ => None,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Jsonl => "jsonl",
+            ExportFormat::Csv => "csv",
+        }
+    }
+
+    pub fn content_type(self) -> ContentType {
+        match self {
+            ExportFormat::Jsonl => ContentType::new("application", "x-ndjson"),
+            ExportFormat::Csv => ContentType::CSV,
+        }
+    }
+
+    /// Text that starts a file in this format.
+    pub fn header(self) -> String {
+        match self {
+            ExportFormat::Jsonl => String::new(),
+            ExportFormat::Csv => format!("{}\r\n", CSV_COLUMNS.join(",")),
+        }
+    }
+
+    /// Appends entries to a file in this format.
+    pub fn write_entries(self, entries: &[ChainedAuditLog], out: &mut String) -> anyhow::Result<()> {
+        for entry in entries {
+            match self {
+                ExportFormat::Jsonl => {
+                    out.push_str(&serde_json::to_string(entry)?);
+                    out.push('\n');
+                }
+                ExportFormat::Csv => {
+                    let json = |value: &Option<Value>| value.as_ref().map(Value::to_string).unwrap_or_default();
+                    let number = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+                    let fields = [
+                        entry.id.to_string(),
+                        entry
+                            .created_at
+                            .map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string())
+                            .unwrap_or_default(),
+                        number(entry.user_id),
+                        number(entry.org_id),
+                        number(entry.app_id),
+                        entry.action.clone(),
+                        entry.resource_type.clone(),
+                        entry.resource_id.clone().unwrap_or_default(),
+                        entry.status.clone().unwrap_or_default(),
+                        entry.ip_address.clone().unwrap_or_default(),
+                        entry.user_agent.clone().unwrap_or_default(),
+                        entry.request_id.clone().unwrap_or_default(),
+                        json(&entry.before_state),
+                        json(&entry.after_state),
+                        json(&entry.details),
+                        entry.prev_hash.clone(),
+                        entry.entry_hash.clone().unwrap_or_default(),
+                    ];
+                    let row: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+                    out.push_str(&row.join(","));
+                    out.push_str("\r\n");
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Hex encoded SHA-256 of a file's content.
+pub fn content_sha256(content: &[u8]) -> String {
+    hex::encode(Sha256::digest(content))
+}
+
+/// Signs a file's content with the configured audit signing key.
+///
+/// # Returns
+///
+/// The hex encoded HMAC-SHA256 of the content, or `None` if no signing key
+/// is configured.
+pub fn sign(content: &[u8]) -> Option<String> {
+    let key = crate::config::SERVER_CONFIG.audit.signing_key.as_deref()?;
+    let mut mac = Hmac::<Sha256>::new_from_slice(key.as_bytes()).ok()?;
+    mac.update(content);
+    Some(hex::encode(mac.finalize().into_bytes()))
+}
@@ -0,0 +1,127 @@
+use std::convert::Infallible;
+use std::sync::Mutex;
+
+use rocket::request::{FromRequest, Outcome, Request};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/// What a handler has told the audit fairing about the current request.
+#[derive(Debug, Default)]
+pub(crate) struct AuditDraft {
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub before_state: Option<Value>,
+    pub after_state: Option<Value>,
+    pub details: Map<String, Value>,
+    pub skip: bool,
+}
+
+/// Per-request audit state, kept in the request's local cache.
+///
+/// The correlation ID and client details are captured by the fairing when
+/// the request arrives; the draft is filled in by handlers through
+/// [`AuditTrail`].
+#[derive(Debug, Default)]
+pub(crate) struct AuditContext {
+    pub request_id: String,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub draft: Mutex<AuditDraft>,
+}
+
+impl AuditContext {
+    pub(crate) fn of<'r>(req: &'r Request<'_>) -> &'r AuditContext {
+        req.local_cache(AuditContext::default)
+    }
+}
+
+/// Request guard for enriching the audit record of a mutating request.
+///
+/// Every mutating request is audited automatically by `AuditFairing`, which
+/// derives the action and resource from the matched route. Handlers can use
+/// this guard to name the resource precisely and to record the state of the
+/// resource before and after the change, from which the fairing computes a
+/// diff.
+///
+/// ```ignore
+/// trail.resource("app", app_id);
+/// trail.before(&existing);
+/// let updated = db::app::update_app(...).await?;
+/// trail.after(&updated);
+/// ```
+pub struct AuditTrail<'r> {
+    context: &'r AuditContext,
+}
+
+impl<'r> AuditTrail<'r> {
+    fn update(&self, f: impl FnOnce(&mut AuditDraft)) {
+        if let Ok(mut draft) = self.context.draft.lock() {
+            f(&mut draft);
+        }
+    }
+
+    /// Correlation ID of the request, also returned in the `X-Request-Id`
+    /// response header.
+    pub fn request_id(&self) -> &str {
+        &self.context.request_id
+    }
+
+    /// Client IP address of the request, if known.
+    pub fn ip_address(&self) -> Option<&str> {
+        self.context.ip_address.as_deref()
+    }
+
+    /// User agent of the request, if sent.
+    pub fn user_agent(&self) -> Option<&str> {
+        self.context.user_agent.as_deref()
+    }
+
+    /// Overrides the action recorded for the request (by default the name of
+    /// the route handler, such as `update_app`).
+    pub fn action(&self, action: &str) {
+        self.update(|draft| draft.action = Some(action.to_string()));
+    }
+
+    /// Names the resource the request acts on.
+    pub fn resource(&self, resource_type: &str, resource_id: impl ToString) {
+        self.update(|draft| {
+            draft.resource_type = Some(resource_type.to_string());
+            draft.resource_id = Some(resource_id.to_string());
+        });
+    }
+
+    /// Records the state of the resource before the change.
+    pub fn before<T: Serialize>(&self, state: &T) {
+        let state = serde_json::to_value(state).ok();
+        self.update(|draft| draft.before_state = state);
+    }
+
+    /// Records the state of the resource after the change.
+    pub fn after<T: Serialize>(&self, state: &T) {
+        let state = serde_json::to_value(state).ok();
+        self.update(|draft| draft.after_state = state);
+    }
+
+    /// Adds a free-form detail to the record.
+    pub fn detail<T: Serialize>(&self, key: &str, value: T) {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        self.update(|draft| {
+            draft.details.insert(key.to_string(), value);
+        });
+    }
+
+    /// Suppresses the automatic record, for handlers that write their own.
+    pub fn skip(&self) {
+        self.update(|draft| draft.skip = true);
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AuditTrail<'r> {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(AuditTrail { context: AuditContext::of(req) })
+    }
+}
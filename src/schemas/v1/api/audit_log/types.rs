@@ -0,0 +1,51 @@
+use rocket::FromForm;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request body for recording an audit log entry by hand.
+///
+/// Only the description of the event can be supplied; the actor, client
+/// IP, user agent, correlation ID and status are always taken from the
+/// request itself so that entries cannot be attributed to someone else.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateAuditLogRequest {
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub org_id: Option<i64>,
+    pub app_id: Option<i64>,
+    pub details: Option<Value>,
+}
+
+/// Request body for changing the audit log retention policy.
+///
+/// `retention_days` is the age after which entries are moved from the
+/// database into archive files; `null` keeps them in the database forever.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateAuditRetentionRequest {
+    pub retention_days: Option<i32>,
+}
+
+/// Query parameters for searching audit logs.
+///
+/// `from` and `to` accept an RFC 3339 timestamp or a `YYYY-MM-DD` date;
+/// `from` is inclusive and `to` exclusive. `cursor` is the `next_cursor`
+/// of the previous page. `org_id`, `space_id` and `app_id` select the scope
+/// the caller is authorized in, which the results are confined to.
+#[derive(FromForm, Default, Debug)]
+pub struct AuditLogSearchQuery {
+    pub user_id: Option<i64>,
+    pub org_id: Option<i64>,
+    pub space_id: Option<i64>,
+    pub app_id: Option<i64>,
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub status: Option<String>,
+    pub request_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
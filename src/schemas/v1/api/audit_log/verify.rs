@@ -0,0 +1,70 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::chain::{self, ChainVerification};
+use rocket::get;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+use super::super::rbac::{Require, AuditLogsExport};
+
+/// Verifies the integrity of the platform's audit log.
+///
+/// Walks the hash chain from the last archive to the current head and
+/// reports every point at which it is broken.
+#[get("/platform/<platform_id>/audit_logs/verify")]
+pub async fn verify_audit_log(
+    _auth: Require<AuditLogsExport>,
+    platform_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ChainVerification>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match chain::verify_chain(&pool).await {
+        Ok(verification) => {
+            if !verification.valid {
+                log::warn!(
+                    "Audit log of platform {} failed verification with {} break(s)",
+                    platform_id, verification.break_count
+                );
+            }
+            Ok(Json(verification))
+        }
+        Err(e) => {
+            log::error!("Failed to verify audit log of platform {}: {:#}", platform_id, e);
+            Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to verify audit log"
+                }))
+            ))
+        }
+    }
+}
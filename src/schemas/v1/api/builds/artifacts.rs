@@ -0,0 +1,331 @@
+use std::sync::Arc;
+use crate::artifacts::{ArtifactReader, ArtifactStore};
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use db::artifact::{ArtifactRetentionInput, ArtifactRetentionPolicy};
+use rocket::http::{ContentType, Status};
+use rocket::response::{self, Responder, Response};
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, put, Request, State};
+use super::super::rbac::{BuildsRead, BuildsWrite, Caller, Require};
+use super::get::authorize_build;
+use super::types::ArtifactRetentionRequest;
+
+/// The contents of a stored release artifact, streamed from the artifact
+/// store.
+pub struct ArtifactDownload {
+    reader: ArtifactReader,
+    file_name: String,
+    checksum: Option<String>,
+}
+
+impl<'r> Responder<'r, 'static> for ArtifactDownload {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let mut response = Response::build();
+        response
+            .header(ContentType::GZIP)
+            .raw_header("Content-Disposition", format!("attachment; filename=\"{}\"", self.file_name));
+        if let Some(checksum) = self.checksum {
+            response.raw_header("X-Artifact-Sha256", checksum);
+        }
+        response.streamed_body(self.reader).ok()
+    }
+}
+
+/// Download the release artifact of a build.
+///
+/// The artifact's SHA-256 is returned in the `X-Artifact-Sha256` header so
+/// that clients can verify it.
+#[get("/platform/<platform_id>/builds/<build_id>/artifact")]
+pub async fn download_build_artifact(
+    caller: Caller,
+    platform_id: i64,
+    build_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+    artifact_store: &State<Arc<dyn ArtifactStore>>,
+) -> Result<ArtifactDownload, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_build::<BuildsRead>(db_manager, &caller, &pool, platform_id, build_id).await?;
+
+    let build = match db::build::get_build_by_id(&pool, build_id).await {
+        Ok(build) => build,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Build not found",
+                    "message": format!("Build with ID {} could not be found", build_id)
+                }))
+            ));
+        }
+    };
+
+    let artifact_url = match build.artifact_url {
+        Some(url) => url,
+        None => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Artifact not found",
+                    "message": format!("Build {} has no stored artifact", build_id)
+                }))
+            ));
+        }
+    };
+
+    match artifact_store.get(&artifact_url).await {
+        Ok(reader) => Ok(ArtifactDownload {
+            reader,
+            file_name: format!("build-{}.tar.gz", build_id),
+            checksum: build.artifact_checksum,
+        }),
+        Err(e) => {
+            log::error!("Failed to fetch artifact of build {}: {:#}", build_id, e);
+            Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Storage error",
+                    "message": "Failed to fetch build artifact"
+                }))
+            ))
+        }
+    }
+}
+
+/// Get the artifact retention policy of an application.
+#[get("/platform/<platform_id>/apps/<app_id>/artifact-retention")]
+pub async fn get_artifact_retention(
+    _auth: Require<BuildsRead>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ArtifactRetentionPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_policy(&pool, app_id).await.map(Json)
+}
+
+/// Set the artifact retention policy of an application. Artifacts past it
+/// are deleted by the leader on its next retention pass.
+#[put("/platform/<platform_id>/apps/<app_id>/artifact-retention", format = "json", data = "<request>")]
+pub async fn set_artifact_retention(
+    auth: Require<BuildsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<ArtifactRetentionRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ArtifactRetentionPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let request = request.into_inner();
+    if request.keep_last < 1 {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "keep_last must be at least 1"
+            }))
+        ));
+    }
+    if request.max_age_days.is_some_and(|days| days < 1) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "max_age_days must be at least 1"
+            }))
+        ));
+    }
+
+    if db::app::get_app_by_id(&pool, app_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        ));
+    }
+
+    if let Ok(Some(existing)) = db::artifact::get_retention_policy(&pool, app_id).await {
+        trail.before(&existing);
+    }
+
+    let input = ArtifactRetentionInput {
+        keep_last: request.keep_last,
+        max_age_days: request.max_age_days,
+        enabled: request.enabled.unwrap_or(true),
+    };
+    match db::artifact::set_retention_policy(&pool, app_id, &input, Some(auth.user_id())).await {
+        Ok(policy) => {
+            trail.resource("artifact_retention_policy", policy.id);
+            trail.after(&policy);
+            Ok(Json(policy))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to save artifact retention policy"
+            }))
+        )),
+    }
+}
+
+/// Remove the artifact retention policy of an application, keeping all of
+/// its artifacts.
+#[delete("/platform/<platform_id>/apps/<app_id>/artifact-retention")]
+pub async fn delete_artifact_retention(
+    _auth: Require<BuildsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let policy = find_policy(&pool, app_id).await?;
+    trail.resource("artifact_retention_policy", policy.id);
+    trail.before(&policy);
+
+    match db::artifact::delete_retention_policy(&pool, app_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to delete artifact retention policy"
+            }))
+        )),
+    }
+}
+
+async fn find_policy(
+    pool: &sqlx::Pool<sqlx::MySql>,
+    app_id: i64,
+) -> Result<ArtifactRetentionPolicy, (Status, Json<Value>)> {
+    match db::artifact::get_retention_policy(pool, app_id).await {
+        Ok(Some(policy)) => Ok(policy),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Retention policy not found",
+                "message": format!("App {} has no artifact retention policy", app_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch artifact retention policy"
+            }))
+        )),
+    }
+}
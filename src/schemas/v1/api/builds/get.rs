@@ -6,10 +6,13 @@ use rocket::{get, http::Status, State};
 
 use libomni::types::db::v1 as types;
 use types::build::Build;
+use sqlx::{MySql, Pool};
+use super::super::rbac::{require_in_scope, Authorization, Caller, BuildsRead, Permission, RequestScope};
 
 /// Get a specific build by ID.
 #[get("/platform/<platform_id>/builds/<build_id>")]
 pub async fn get_build(
+    caller: Caller,
     platform_id: i64,
     build_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -42,6 +45,8 @@ pub async fn get_build(
         }
     };
 
+    authorize_build::<BuildsRead>(db_manager, &caller, &pool, platform_id, build_id).await?;
+
     match db::build::get_build_by_id(&pool, build_id).await {
         Ok(build) => Ok(Json(build)),
         Err(_) => Err((
@@ -52,4 +57,33 @@ pub async fn get_build(
             }))
         )),
     }
+}
+
+/// Checks `P` against the application a build was made for, or against the
+/// platform if there is no such build.
+pub(crate) async fn authorize_build<P: Permission>(
+    db_manager: &Arc<DatabaseManager>,
+    caller: &Caller,
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    build_id: i64,
+) -> Result<Authorization, (Status, Json<Value>)> {
+    let app_id = match db::build::get_build_app_id(pool, build_id).await {
+        Ok(app_id) => app_id,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch build"
+                }))
+            ));
+        }
+    };
+
+    require_in_scope::<P>(db_manager, caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id,
+        ..Default::default()
+    }).await
 }
\ No newline at end of file
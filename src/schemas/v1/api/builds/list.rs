@@ -6,10 +6,12 @@ use rocket::{get, http::Status, State};
 
 use libomni::types::db::v1 as types;
 use types::build::Build;
+use super::super::rbac::{Require, BuildsRead};
 
 /// List all builds with pagination support.
 #[get("/platform/<platform_id>/builds?<page>&<per_page>")]
 pub async fn list_builds(
+    _auth: Require<BuildsRead>,
     platform_id: i64,
     page: Option<u32>,
     per_page: Option<u32>,
@@ -91,6 +93,7 @@ pub async fn list_builds(
 /// List builds for a specific application with pagination support.
 #[get("/platform/<platform_id>/apps/<app_id>/builds?<page>&<per_page>")]
 pub async fn list_builds_for_app(
+    _auth: Require<BuildsRead>,
     platform_id: i64,
     app_id: i64,
     page: Option<u32>,
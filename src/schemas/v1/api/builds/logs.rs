@@ -0,0 +1,267 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::super::helpers::tail::{self, LastEventId, Tail, TailStatus};
+use super::queue::lease_error;
+use super::types::AppendBuildLogsRequest;
+use db::build_log::{AppendOutcome, BuildLogLine, NewBuildLogLine};
+use rocket::http::Status;
+use rocket::response::stream::EventStream;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, post, Either, Shutdown, State};
+use sqlx::{MySql, Pool};
+use super::super::rbac::{BuildsExecute, BuildsRead, Caller, Require};
+use super::get::authorize_build;
+
+/// Most lines a builder may send at once.
+const MAX_LINES_PER_REQUEST: usize = 1000;
+/// Longest line kept, in bytes; longer lines are truncated.
+const MAX_LINE_BYTES: usize = 16 * 1024;
+/// Most lines returned by a single read.
+const MAX_READ_LIMIT: i64 = 5000;
+
+/// Send lines of output of a build being built.
+///
+/// Builders send their output in batches while the build runs, with the
+/// lease token they received when leasing it.
+#[post("/platform/<platform_id>/builds/<build_id>/logs", format = "json", data = "<request>")]
+pub async fn append_build_logs(
+    _auth: Require<BuildsExecute>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    build_id: i64,
+    request: Json<AppendBuildLogsRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Build output is far too frequent to audit
+    trail.skip();
+
+    let request = request.into_inner();
+    if request.lines.len() > MAX_LINES_PER_REQUEST {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("At most {} lines can be sent at once", MAX_LINES_PER_REQUEST)
+            }))
+        ));
+    }
+
+    let mut lines = Vec::with_capacity(request.lines.len());
+    for line in request.lines {
+        let stream = line.stream.unwrap_or_else(|| "stdout".to_string());
+        if !matches!(stream.as_str(), "stdout" | "stderr") {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": "stream must be 'stdout' or 'stderr'"
+                }))
+            ));
+        }
+        let level = line.level.unwrap_or_else(|| "info".to_string());
+        if !matches!(level.as_str(), "debug" | "info" | "warn" | "error" | "fatal") {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": "level must be one of: debug, info, warn, error, fatal"
+                }))
+            ));
+        }
+        lines.push(NewBuildLogLine {
+            stream,
+            level,
+            message: truncate_line(line.message),
+            timestamp: line.timestamp,
+        });
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::build_log::append_build_log_lines(&pool, build_id, &request.lease_token, &lines).await {
+        Ok(AppendOutcome::Appended(count)) => Ok(Json(json!({ "appended": count }))),
+        Ok(AppendOutcome::Rejected(outcome)) => Err(lease_error(build_id, outcome)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to record build logs"
+            }))
+        )),
+    }
+}
+
+/// Read the output of a build, oldest first.
+///
+/// Lines after the line with ID `after` are returned, at most `limit` of
+/// them; `next_after` continues from the last one. With `follow=true` the
+/// output is streamed as server-sent events instead: every line is sent as
+/// a `log` event whose ID is the line's ID, starting after `after` or the
+/// `Last-Event-ID` of a reconnecting client, and an `end` event with the
+/// build's final status closes the stream once the build has finished.
+#[get("/platform/<platform_id>/builds/<build_id>/logs?<after>&<limit>&<follow>")]
+pub async fn get_build_logs(
+    caller: Caller,
+    platform_id: i64,
+    build_id: i64,
+    after: Option<i64>,
+    limit: Option<i64>,
+    follow: Option<bool>,
+    last_event_id: LastEventId,
+    db_manager: &State<Arc<DatabaseManager>>,
+    shutdown: Shutdown,
+) -> Result<Either<Json<Value>, EventStream![]>, (Status, Json<Value>)> {
+    let limit = limit.unwrap_or(500);
+    if !(1..=MAX_READ_LIMIT).contains(&limit) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("limit must be between 1 and {}", MAX_READ_LIMIT)
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_build::<BuildsRead>(db_manager, &caller, &pool, platform_id, build_id).await?;
+
+    let build = match db::build::get_build_by_id(&pool, build_id).await {
+        Ok(build) => build,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Build not found",
+                    "message": format!("Build with ID {} could not be found", build_id)
+                }))
+            ));
+        }
+    };
+
+    if !follow.unwrap_or(false) {
+        let after = after.unwrap_or(0);
+        return match db::build_log::list_build_log_lines_after(&pool, build_id, after, limit).await {
+            Ok(lines) => {
+                let next_after = lines.last().map(|line| line.id).unwrap_or(after);
+                Ok(Either::Left(Json(json!({
+                    "build_id": build_id,
+                    "status": build.status,
+                    "lines": lines,
+                    "next_after": next_after
+                }))))
+            }
+            Err(_) => Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch build logs"
+                }))
+            )),
+        };
+    }
+
+    let after = last_event_id.0.or(after).unwrap_or(0);
+    Ok(Either::Right(tail::follow(BuildTail { pool, build_id }, after, shutdown)))
+}
+
+/// The output of a build, followed until the build has finished.
+struct BuildTail {
+    pool: Pool<MySql>,
+    build_id: i64,
+}
+
+#[rocket::async_trait]
+impl Tail for BuildTail {
+    type Entry = BuildLogLine;
+
+    const EVENT: &'static str = "log";
+    const RESOURCE: &'static str = "build";
+    const ENTRIES: &'static str = "logs";
+
+    fn id(&self) -> i64 {
+        self.build_id
+    }
+
+    async fn status(&self) -> anyhow::Result<Option<TailStatus>> {
+        let status = db::build::get_build_by_id(&self.pool, self.build_id).await?.status;
+        Ok(Some(TailStatus {
+            finished: !matches!(status.as_deref(), Some("pending" | "building")),
+            end: json!({ "build_id": self.build_id, "status": status }),
+        }))
+    }
+
+    async fn entries_after(&self, after: i64, limit: i64) -> anyhow::Result<Vec<BuildLogLine>> {
+        db::build_log::list_build_log_lines_after(&self.pool, self.build_id, after, limit).await
+    }
+
+    fn entry_id(line: &BuildLogLine) -> i64 {
+        line.id
+    }
+}
+
+/// Cuts a line down to [`MAX_LINE_BYTES`] on a character boundary.
+fn truncate_line(mut message: String) -> String {
+    if message.len() > MAX_LINE_BYTES {
+        let mut end = MAX_LINE_BYTES;
+        while !message.is_char_boundary(end) {
+            end -= 1;
+        }
+        message.truncate(end);
+    }
+    message
+}
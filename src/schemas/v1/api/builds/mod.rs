@@ -4,11 +4,25 @@
 //! - Listing builds with pagination
 //! - Getting build details
 //! - Listing builds for specific applications
+//! - Downloading build artifacts
+//! - Managing the artifact retention policies of applications
+//! - Leasing queued builds to builders and recording their results
+//! - Canceling builds
+//! - Receiving and streaming build logs
 
 // Import and re-export all route modules
 pub mod list;
 pub mod get;
+pub mod artifacts;
+pub mod queue;
+pub mod logs;
+pub mod types;
 
 // Re-export all route functions
 pub use list::{list_builds, list_builds_for_app};
-pub use get::get_build;
\ No newline at end of file
+pub use get::get_build;
+pub use artifacts::{
+    delete_artifact_retention, download_build_artifact, get_artifact_retention, set_artifact_retention,
+};
+pub use logs::{append_build_logs, get_build_logs};
+pub use queue::{cancel_build, complete_build, fail_build, heartbeat_build, lease_build};
\ No newline at end of file
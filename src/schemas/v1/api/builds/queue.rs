@@ -0,0 +1,414 @@
+use std::sync::Arc;
+use crate::{DatabaseManager, SERVER_CONFIG};
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::types::{BuildHeartbeatRequest, CompleteBuildRequest, FailBuildRequest, LeaseBuildRequest};
+use db::build_queue::{BuildLease, BuildResult, CancelOutcome, LeaseOutcome};
+use crate::deployer::auto_deploy::{self, AutoDeployOutcome};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, Either, State};
+
+use libomni::types::db::v1 as types;
+use types::build::Build;
+use super::super::rbac::{BuildsExecute, BuildsWrite, Caller, Require};
+use super::get::authorize_build;
+
+/// Lease the next queued build.
+///
+/// Returns the oldest pending build whose application and organization are
+/// below their concurrent build limits, together with the lease token the
+/// builder sends with its heartbeats and result, or `204 No Content` if no
+/// build can start right now.
+#[post("/platform/<platform_id>/builds/lease", format = "json", data = "<request>")]
+pub async fn lease_build(
+    _auth: Require<BuildsExecute>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    request: Json<LeaseBuildRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Either<Json<BuildLease>, Status>, (Status, Json<Value>)> {
+    let request = request.into_inner();
+    let builder_id = request.builder_id.trim();
+    if builder_id.is_empty() || builder_id.len() > 255 {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "builder_id must be between 1 and 255 characters"
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::build_queue::lease_next_build(&pool, builder_id, SERVER_CONFIG.builds.lease_seconds).await {
+        Ok(Some(lease)) => {
+            trail.resource("build", lease.build.id);
+            trail.detail("builder_id", builder_id);
+            trail.after(&lease.build);
+            Ok(Either::Left(Json(lease)))
+        }
+        Ok(None) => {
+            // Builders poll for work; empty polls are not worth auditing
+            trail.skip();
+            Ok(Either::Right(Status::NoContent))
+        }
+        Err(e) => {
+            log::error!("Failed to lease build on platform {}: {:#}", platform_id, e);
+            Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to lease build"
+                }))
+            ))
+        }
+    }
+}
+
+/// Extend the lease of a build being built.
+///
+/// The response tells the builder whether the build was canceled, in which
+/// case it should stop working on it.
+#[post("/platform/<platform_id>/builds/<build_id>/heartbeat", format = "json", data = "<request>")]
+pub async fn heartbeat_build(
+    _auth: Require<BuildsExecute>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    build_id: i64,
+    request: Json<BuildHeartbeatRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Heartbeats are far too frequent to audit
+    trail.skip();
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let lease_seconds = SERVER_CONFIG.builds.lease_seconds;
+    match db::build_queue::heartbeat_build(&pool, build_id, &request.lease_token, lease_seconds).await {
+        Ok((LeaseOutcome::Updated(build), lease_expires_at)) => Ok(Json(json!({
+            "build_id": build.id,
+            "status": build.status,
+            "lease_expires_at": lease_expires_at,
+            "canceled": false
+        }))),
+        Ok((LeaseOutcome::Canceled(build), _)) => Ok(Json(json!({
+            "build_id": build.id,
+            "status": build.status,
+            "lease_expires_at": null,
+            "canceled": true
+        }))),
+        Ok((outcome, _)) => Err(lease_error(build_id, outcome)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to extend build lease"
+            }))
+        )),
+    }
+}
+
+/// Report that a leased build succeeded, with the image and artifact it
+/// produced.
+///
+/// A build queued by a repository push with auto deploy enabled is then
+/// deployed, unless a freeze window is in effect.
+#[post("/platform/<platform_id>/builds/<build_id>/complete", format = "json", data = "<request>")]
+pub async fn complete_build(
+    _auth: Require<BuildsExecute>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    build_id: i64,
+    request: Json<CompleteBuildRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Build>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let request = request.into_inner();
+    let result = BuildResult {
+        build_image: request.build_image,
+        build_pack_used: request.build_pack_used,
+        build_pack_version: request.build_pack_version,
+        artifact_url: request.artifact_url,
+        artifact_checksum: request.artifact_checksum,
+        artifact_size: request.artifact_size,
+        log_url: request.log_url,
+    };
+
+    trail.resource("build", build_id);
+    match db::build_queue::complete_build(&pool, platform_id, build_id, &request.lease_token, &result).await {
+        Ok(LeaseOutcome::Updated(build)) => {
+            trail.after(&build);
+            match auto_deploy::deploy_succeeded_build(&pool, &build).await {
+                Ok(AutoDeployOutcome::Created(deployment)) => trail.detail("auto_deployment_id", deployment.id),
+                Ok(AutoDeployOutcome::Frozen(window)) => trail.detail("auto_deploy_frozen_by", window),
+                Ok(AutoDeployOutcome::NotRequested) => {}
+                Err(e) => log::error!("Failed to deploy build {} automatically: {:#}", build_id, e),
+            }
+            Ok(Json(build))
+        }
+        Ok(outcome) => Err(lease_error(build_id, outcome)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to complete build"
+            }))
+        )),
+    }
+}
+
+/// Report that a leased build failed.
+///
+/// A failure marked `retryable` puts the build back in the queue until it
+/// has been attempted the configured number of times.
+#[post("/platform/<platform_id>/builds/<build_id>/fail", format = "json", data = "<request>")]
+pub async fn fail_build(
+    _auth: Require<BuildsExecute>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    build_id: i64,
+    request: Json<FailBuildRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Build>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let request = request.into_inner();
+    let retryable = request.retryable.unwrap_or(false);
+
+    trail.resource("build", build_id);
+    trail.detail("error_message", &request.error_message);
+    trail.detail("retryable", retryable);
+    match db::build_queue::fail_build(
+        &pool,
+        platform_id,
+        build_id,
+        &request.lease_token,
+        &request.error_message,
+        retryable,
+        SERVER_CONFIG.builds.max_attempts,
+    )
+    .await
+    {
+        Ok(LeaseOutcome::Updated(build)) => {
+            trail.after(&build);
+            Ok(Json(build))
+        }
+        Ok(outcome) => Err(lease_error(build_id, outcome)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to record build failure"
+            }))
+        )),
+    }
+}
+
+/// Cancel a pending or running build. A builder running it is told to stop
+/// by its next heartbeat.
+#[post("/platform/<platform_id>/builds/<build_id>/cancel")]
+pub async fn cancel_build(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    build_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Build>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_build::<BuildsWrite>(db_manager, &caller, &pool, platform_id, build_id).await?;
+
+    trail.resource("build", build_id);
+    match db::build_queue::cancel_build(&pool, platform_id, build_id).await {
+        Ok(CancelOutcome::Canceled(build)) => {
+            trail.after(&build);
+            Ok(Json(build))
+        }
+        Ok(CancelOutcome::AlreadyFinished(build)) => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Build finished",
+                "message": format!(
+                    "Build {} is {} and can no longer be canceled",
+                    build_id,
+                    build.status.as_deref().unwrap_or("unknown")
+                )
+            }))
+        )),
+        Ok(CancelOutcome::NotFound) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Build not found",
+                "message": format!("Build with ID {} could not be found", build_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to cancel build"
+            }))
+        )),
+    }
+}
+
+/// Error response for a builder acting on a build it does not hold the
+/// lease of.
+pub(super) fn lease_error(build_id: i64, outcome: LeaseOutcome) -> (Status, Json<Value>) {
+    match outcome {
+        LeaseOutcome::NotFound => (
+            Status::NotFound,
+            Json(json!({
+                "error": "Build not found",
+                "message": format!("Build with ID {} could not be found", build_id)
+            }))
+        ),
+        LeaseOutcome::Canceled(_) => (
+            Status::Conflict,
+            Json(json!({
+                "error": "Build canceled",
+                "message": format!("Build {} was canceled", build_id)
+            }))
+        ),
+        LeaseOutcome::LeaseLost | LeaseOutcome::Updated(_) => (
+            Status::Conflict,
+            Json(json!({
+                "error": "Lease lost",
+                "message": format!("The lease on build {} expired or is held by another builder", build_id)
+            }))
+        ),
+    }
+}
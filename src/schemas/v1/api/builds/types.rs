@@ -0,0 +1,67 @@
+use serde::{Deserialize, Serialize};
+
+/// Request body for setting the artifact retention policy of an application.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArtifactRetentionRequest {
+    /// Number of newest artifacts that are always kept
+    pub keep_last: i64,
+    /// Age in days after which older artifacts are deleted; unset deletes
+    /// them as soon as they fall out of the newest `keep_last`
+    pub max_age_days: Option<i64>,
+    pub enabled: Option<bool>,
+}
+
+/// Request body for leasing a queued build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeaseBuildRequest {
+    /// Name identifying the builder, recorded on the build
+    pub builder_id: String,
+}
+
+/// Request body for extending the lease of a build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildHeartbeatRequest {
+    pub lease_token: String,
+}
+
+/// Request body for reporting a successful build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CompleteBuildRequest {
+    pub lease_token: String,
+    pub build_image: Option<String>,
+    pub build_pack_used: Option<String>,
+    pub build_pack_version: Option<String>,
+    pub artifact_url: Option<String>,
+    pub artifact_checksum: Option<String>,
+    pub artifact_size: Option<i64>,
+    pub log_url: Option<String>,
+}
+
+/// Request body for reporting a failed build.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FailBuildRequest {
+    pub lease_token: String,
+    pub error_message: String,
+    /// Whether the failure is transient and the build should be attempted
+    /// again by another lease
+    pub retryable: Option<bool>,
+}
+
+/// Request body for sending lines of a build's output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppendBuildLogsRequest {
+    pub lease_token: String,
+    pub lines: Vec<BuildLogLineRequest>,
+}
+
+/// A line of a build's output.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BuildLogLineRequest {
+    pub message: String,
+    /// 'stdout' (the default) or 'stderr'
+    pub stream: Option<String>,
+    /// One of 'debug', 'info' (the default), 'warn', 'error' or 'fatal'
+    pub level: Option<String>,
+    /// When the line was produced; the time it is received if unset
+    pub timestamp: Option<chrono::DateTime<chrono::Utc>>,
+}
@@ -8,10 +8,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::cost::CostAllocationTag;
+use super::super::rbac::{Require, CostRead, CostWrite};
 
 /// Get cost allocation tags for a specific resource.
 #[get("/platform/<platform_id>/cost_allocation_tags/<resource_id>/<resource_type>")]
 pub async fn get_cost_allocation_tags(
+    _auth: Require<CostRead>,
     platform_id: i64,
     resource_id: i64,
     resource_type: String,
@@ -60,6 +62,7 @@ pub async fn get_cost_allocation_tags(
 /// Create a new cost allocation tag.
 #[post("/platform/<platform_id>/cost_allocation_tags", format = "json", data = "<request>")]
 pub async fn create_cost_allocation_tag(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     request: Json<CreateCostAllocationTagRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -113,6 +116,7 @@ pub async fn create_cost_allocation_tag(
 /// Delete a cost allocation tag.
 #[delete("/platform/<platform_id>/cost_allocation_tags/<id>")]
 pub async fn delete_cost_allocation_tag(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
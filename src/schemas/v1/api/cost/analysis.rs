@@ -6,10 +6,12 @@ use rocket::{post, State};
 use std::sync::Arc;
 use crate::DatabaseManager;
 use chrono::{DateTime, Utc};
+use super::super::rbac::{Require, CostRead};
 
 /// Get cost analysis by dimension (app, provider, resource_type, etc.)
 #[post("/platform/<platform_id>/cost_analysis/by_dimension", format = "json", data = "<request>")]
 pub async fn analyze_costs_by_dimension(
+    _auth: Require<CostRead>,
     platform_id: i64,
     request: Json<CostAnalysisByDimensionRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -63,6 +65,7 @@ pub async fn analyze_costs_by_dimension(
 /// Get application cost over time
 #[post("/platform/<platform_id>/cost_analysis/over_time", format = "json", data = "<request>")]
 pub async fn analyze_cost_over_time(
+    _auth: Require<CostRead>,
     platform_id: i64,
     request: Json<CostOverTimeRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
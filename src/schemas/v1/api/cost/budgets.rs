@@ -9,10 +9,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::cost::CostBudget;
+use super::super::rbac::{Require, CostRead, CostWrite};
 
 /// List all cost budgets with pagination support.
 #[get("/platform/<platform_id>/cost_budgets?<page>&<per_page>")]
 pub async fn list_cost_budgets(
+    _auth: Require<CostRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -101,6 +103,7 @@ pub async fn list_cost_budgets(
 /// Get a specific cost budget by ID.
 #[get("/platform/<platform_id>/cost_budgets/<id>")]
 pub async fn get_cost_budget(
+    _auth: Require<CostRead>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -148,6 +151,7 @@ pub async fn get_cost_budget(
 /// Create a new cost budget.
 #[post("/platform/<platform_id>/cost_budgets", format = "json", data = "<request>")]
 pub async fn create_cost_budget(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     request: Json<CreateCostBudgetRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -213,6 +217,7 @@ pub async fn create_cost_budget(
 /// Update an existing cost budget.
 #[put("/platform/<platform_id>/cost_budgets/<id>", format = "json", data = "<request>")]
 pub async fn update_cost_budget(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i64,
     request: Json<UpdateCostBudgetRequest>,
@@ -269,6 +274,7 @@ pub async fn update_cost_budget(
 /// Delete a cost budget.
 #[delete("/platform/<platform_id>/cost_budgets/<id>")]
 pub async fn delete_cost_budget(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -9,10 +9,12 @@ use chrono::{DateTime, Utc};
 
 use libomni::types::db::v1 as types;
 use types::cost::{CostMetric, CostMetricWithType};
+use super::super::rbac::{require_in_scope, Require, CostRead, CostWrite, Caller, RequestScope};
 
 /// List cost metrics with pagination and filtering support.
 #[get("/platform/<platform_id>/cost_metrics?<page>&<per_page>&<resource_type_id>&<provider_id>&<app_id>&<start_date>&<end_date>&<billing_period>")]
 pub async fn list_cost_metrics(
+    caller: Caller,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -24,6 +26,12 @@ pub async fn list_cost_metrics(
     billing_period: Option<String>,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
+    require_in_scope::<CostRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id,
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -129,6 +137,7 @@ pub async fn list_cost_metrics(
 /// Get a specific cost metric by ID.
 #[get("/platform/<platform_id>/cost_metrics/<id>")]
 pub async fn get_cost_metric(
+    _auth: Require<CostRead>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -176,6 +185,7 @@ pub async fn get_cost_metric(
 /// Create a new cost metric.
 #[post("/platform/<platform_id>/cost_metrics", format = "json", data = "<request>")]
 pub async fn create_cost_metric(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     request: Json<CreateCostMetricRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -240,6 +250,7 @@ pub async fn create_cost_metric(
 /// Delete a cost metric.
 #[delete("/platform/<platform_id>/cost_metrics/<id>")]
 pub async fn delete_cost_metric(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -8,10 +8,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::cost::ResourcePricing;
+use super::super::rbac::{Require, CostRead, CostWrite};
 
 /// List resource pricing with pagination and filtering support.
 #[get("/platform/<platform_id>/resource_pricing?<page>&<per_page>&<resource_type_id>&<provider_id>&<region_id>&<pricing_model>&<tier_name>")]
 pub async fn list_resource_pricing(
+    _auth: Require<CostRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -90,6 +92,7 @@ pub async fn list_resource_pricing(
 /// Get a specific resource pricing entry by ID.
 #[get("/platform/<platform_id>/resource_pricing/<id>")]
 pub async fn get_resource_pricing(
+    _auth: Require<CostRead>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -137,6 +140,7 @@ pub async fn get_resource_pricing(
 /// Create a new resource pricing entry.
 #[post("/platform/<platform_id>/resource_pricing", format = "json", data = "<request>")]
 pub async fn create_resource_pricing(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     request: Json<CreateResourcePricingRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -197,6 +201,7 @@ pub async fn create_resource_pricing(
 /// Update an existing resource pricing entry.
 #[put("/platform/<platform_id>/resource_pricing/<id>", format = "json", data = "<request>")]
 pub async fn update_resource_pricing(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i64,
     request: Json<UpdateResourcePricingRequest>,
@@ -251,6 +256,7 @@ pub async fn update_resource_pricing(
 /// Delete a resource pricing entry.
 #[delete("/platform/<platform_id>/resource_pricing/<id>")]
 pub async fn delete_resource_pricing(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -8,10 +8,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::cost::CostProjection;
+use super::super::rbac::{Require, CostRead, CostWrite};
 
 /// List all cost projections with pagination support.
 #[get("/platform/<platform_id>/cost_projections?<page>&<per_page>")]
 pub async fn list_cost_projections(
+    _auth: Require<CostRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -83,6 +85,7 @@ pub async fn list_cost_projections(
 /// Get a specific cost projection by ID.
 #[get("/platform/<platform_id>/cost_projections/<id>")]
 pub async fn get_cost_projection(
+    _auth: Require<CostRead>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -130,6 +133,7 @@ pub async fn get_cost_projection(
 /// Create a new cost projection.
 #[post("/platform/<platform_id>/cost_projections", format = "json", data = "<request>")]
 pub async fn create_cost_projection(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     request: Json<CreateCostProjectionRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -189,6 +193,7 @@ pub async fn create_cost_projection(
 /// Delete a cost projection.
 #[delete("/platform/<platform_id>/cost_projections/<id>")]
 pub async fn delete_cost_projection(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
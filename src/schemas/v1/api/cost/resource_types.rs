@@ -8,10 +8,12 @@ use crate::DatabaseManager;
 
 use libomni::types::db::v1 as types;
 use types::util_tables::ResourceType;
+use super::super::rbac::{Require, CostRead, CostWrite};
 
 /// List all resource types with pagination support.
 #[get("/platform/<platform_id>/resource_types?<page>&<per_page>")]
 pub async fn list_resource_types(
+    _auth: Require<CostRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -100,6 +102,7 @@ pub async fn list_resource_types(
 /// Count the total number of resource types.
 #[get("/platform/<platform_id>/count/resource_types")]
 pub async fn count_resource_types(
+    _auth: Require<CostRead>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<i64>, (Status, Json<Value>)> {
@@ -146,6 +149,7 @@ pub async fn count_resource_types(
 /// Get a specific resource type by ID.
 #[get("/platform/<platform_id>/resource_types/<id>")]
 pub async fn get_resource_type(
+    _auth: Require<CostRead>,
     platform_id: i64,
     id: i32,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -193,6 +197,7 @@ pub async fn get_resource_type(
 /// Create a new resource type.
 #[post("/platform/<platform_id>/resource_types", format = "json", data = "<request>")]
 pub async fn create_resource_type(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     request: Json<CreateResourceTypeRequest>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -246,6 +251,7 @@ pub async fn create_resource_type(
 /// Update an existing resource type.
 #[put("/platform/<platform_id>/resource_types/<id>", format = "json", data = "<request>")]
 pub async fn update_resource_type(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i32,
     request: Json<UpdateResourceTypeRequest>,
@@ -301,6 +307,7 @@ pub async fn update_resource_type(
 /// Delete a resource type.
 #[delete("/platform/<platform_id>/resource_types/<id>")]
 pub async fn delete_resource_type(
+    _auth: Require<CostWrite>,
     platform_id: i64,
     id: i32,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -2,6 +2,8 @@ use rocket::get;
 use rocket::http::Status;
 use serde::{Deserialize, Serialize};
 
+use super::rbac::Caller;
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct DeployPermissions {
     max_file_count: u64,
@@ -15,6 +17,6 @@ impl Default for DeployPermissions {
 }
 //TODO: replace with proxy
 #[get("/deploy/permissions")]
-pub fn deploy_permissions() -> Result<rocket::serde::json::Json<DeployPermissions>, Status> {
+pub fn deploy_permissions(_caller: Caller) -> Result<rocket::serde::json::Json<DeployPermissions>, Status> {
     Ok(rocket::serde::json::Json(DeployPermissions::default()))
 }
@@ -0,0 +1,669 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::types::{ApprovalPolicyRequest, DeploymentDecisionRequest};
+use db::deployment_approval::{ApprovalOutcome, ApprovalPolicy, ApprovalPolicyInput, PolicyTarget};
+use db::deployment_log::NewDeploymentEvent;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, put, State};
+use sqlx::{MySql, Pool};
+use super::super::rbac::{
+    require_in_scope, Caller, DeploymentsApprove, DeploymentsManage, DeploymentsRead, RequestScope, Require,
+};
+use super::get::authorize_deployment;
+
+/// Most approvals a policy may require.
+const MAX_REQUIRED_APPROVALS: i64 = 10;
+
+/// List the approvals and rejections of a deployment, with its approval
+/// requirement.
+#[get("/platform/<platform_id>/deployments/<deployment_id>/approvals")]
+pub async fn list_deployment_approvals(
+    caller: Caller,
+    platform_id: i64,
+    deployment_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsRead>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    approvals_response(&pool, deployment_id).await
+}
+
+/// Approve a deployment that awaits approval.
+///
+/// The approver must hold `deployments:approve` for the deployment's
+/// application and, if the policy names one, its approver role. Users
+/// cannot approve their own deployments. Once the deployment has the
+/// required approvals it is rolled out like any other.
+#[post("/platform/<platform_id>/deployments/<deployment_id>/approve", format = "json", data = "<request>")]
+pub async fn approve_deployment(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    request: Option<Json<DeploymentDecisionRequest>>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let comment = request.and_then(|request| request.into_inner().comment);
+    decide_deployment(db_manager, &caller, &trail, platform_id, deployment_id, true, comment).await
+}
+
+/// Reject a deployment that awaits approval. A single rejection cancels
+/// the deployment.
+#[post("/platform/<platform_id>/deployments/<deployment_id>/reject", format = "json", data = "<request>")]
+pub async fn reject_deployment(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    request: Option<Json<DeploymentDecisionRequest>>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let comment = request.and_then(|request| request.into_inner().comment);
+    decide_deployment(db_manager, &caller, &trail, platform_id, deployment_id, false, comment).await
+}
+
+/// Get the approval policy of an application.
+#[get("/platform/<platform_id>/apps/<app_id>/approval-policy")]
+pub async fn get_app_approval_policy(
+    _auth: Require<DeploymentsRead>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ApprovalPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_policy(&pool, PolicyTarget::App(app_id)).await.map(Json)
+}
+
+/// Set the approval policy of an application. It takes precedence over the
+/// policy of the application's space, also when disabled.
+#[put("/platform/<platform_id>/apps/<app_id>/approval-policy", format = "json", data = "<request>")]
+pub async fn set_app_approval_policy(
+    auth: Require<DeploymentsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<ApprovalPolicyRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ApprovalPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::app::get_app_by_id(&pool, app_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        ));
+    }
+
+    save_policy(&pool, &trail, auth.user_id(), PolicyTarget::App(app_id), request.into_inner()).await
+}
+
+/// Remove the approval policy of an application.
+#[delete("/platform/<platform_id>/apps/<app_id>/approval-policy")]
+pub async fn delete_app_approval_policy(
+    _auth: Require<DeploymentsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    remove_policy(&pool, &trail, PolicyTarget::App(app_id)).await
+}
+
+/// Get the approval policy of a space.
+#[get("/platform/<platform_id>/spaces/<space_id>/approval-policy")]
+pub async fn get_space_approval_policy(
+    _auth: Require<DeploymentsRead>,
+    platform_id: i64,
+    space_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ApprovalPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_policy(&pool, PolicyTarget::Space(space_id)).await.map(Json)
+}
+
+/// Set the approval policy of a space. It applies to the applications of
+/// the space that have no policy of their own.
+#[put("/platform/<platform_id>/spaces/<space_id>/approval-policy", format = "json", data = "<request>")]
+pub async fn set_space_approval_policy(
+    auth: Require<DeploymentsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    space_id: i64,
+    request: Json<ApprovalPolicyRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ApprovalPolicy>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::space::get_space_by_id(&pool, space_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Space not found",
+                "message": format!("Space with ID {} does not exist", space_id)
+            }))
+        ));
+    }
+
+    save_policy(&pool, &trail, auth.user_id(), PolicyTarget::Space(space_id), request.into_inner()).await
+}
+
+/// Remove the approval policy of a space.
+#[delete("/platform/<platform_id>/spaces/<space_id>/approval-policy")]
+pub async fn delete_space_approval_policy(
+    _auth: Require<DeploymentsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    space_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    remove_policy(&pool, &trail, PolicyTarget::Space(space_id)).await
+}
+
+/// Records an approval or rejection, in the audit trail and the
+/// deployment's timeline.
+async fn decide_deployment(
+    db_manager: &State<Arc<DatabaseManager>>,
+    caller: &Caller,
+    trail: &AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    approve: bool,
+    comment: Option<String>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let state = match db::deployment_approval::get_deployment_approval_state(&pool, deployment_id).await {
+        Ok(state) => state,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch deployment"
+                }))
+            ));
+        }
+    };
+
+    // Checked before revealing whether the deployment exists
+    require_in_scope::<DeploymentsApprove>(db_manager, caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id: state.as_ref().map(|state| state.app_id),
+        ..Default::default()
+    }).await?;
+
+    let state = match state {
+        Some(state) => state,
+        None => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Deployment not found",
+                    "message": format!("Deployment with ID {} could not be found", deployment_id)
+                }))
+            ));
+        }
+    };
+
+    let user_id = caller.user.id;
+    if state.created_by == Some(user_id) {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": "Deployments cannot be approved or rejected by the user who created them"
+            }))
+        ));
+    }
+    if let Some(role_id) = state.approver_role_id {
+        match db::deployment_approval::user_holds_role_for_app(&pool, user_id, role_id, state.app_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err((
+                    Status::Forbidden,
+                    Json(json!({
+                        "error": "Forbidden",
+                        "message": format!("Approvers of this deployment must hold role {}", role_id)
+                    }))
+                ));
+            }
+            Err(_) => {
+                return Err((
+                    Status::InternalServerError,
+                    Json(json!({
+                        "error": "Database error",
+                        "message": "Failed to check approver role"
+                    }))
+                ));
+            }
+        }
+    }
+
+    trail.resource("deployment", deployment_id);
+    trail.before(&state);
+    trail.detail("decision", if approve { "approved" } else { "rejected" });
+    if let Some(comment) = &comment {
+        trail.detail("comment", comment);
+    }
+
+    let comment = comment.as_deref().map(str::trim).filter(|comment| !comment.is_empty());
+    let (approval_status, approvals) =
+        match db::deployment_approval::decide_deployment(&pool, deployment_id, user_id, approve, comment).await {
+            Ok(ApprovalOutcome::Recorded { approval_status, approvals }) => (approval_status, approvals),
+            Ok(ApprovalOutcome::AlreadyDecided) => {
+                return Err((
+                    Status::Conflict,
+                    Json(json!({
+                        "error": "Already decided",
+                        "message": format!("User {} has already approved or rejected deployment {}", user_id, deployment_id)
+                    }))
+                ));
+            }
+            Ok(ApprovalOutcome::NotAwaitingApproval) => {
+                return Err((
+                    Status::Conflict,
+                    Json(json!({
+                        "error": "Invalid deployment state",
+                        "message": format!(
+                            "Deployment {} is {} and does not await approval",
+                            deployment_id,
+                            state.status.as_deref().unwrap_or("unknown")
+                        )
+                    }))
+                ));
+            }
+            Err(_) => {
+                return Err((
+                    Status::InternalServerError,
+                    Json(json!({
+                        "error": "Database error",
+                        "message": format!("Failed to record decision on deployment {}", deployment_id)
+                    }))
+                ));
+            }
+        };
+
+    let required = state.required_approvals.unwrap_or(0);
+    let (event_type, level, message) = match approval_status.as_str() {
+        "rejected" => ("rejected", "warn", format!("Deployment rejected by user {}", user_id)),
+        "approved" => (
+            "approved",
+            "info",
+            format!("Deployment approved by user {} ({} of {} approvals)", user_id, approvals, required),
+        ),
+        _ => (
+            "approval_granted",
+            "info",
+            format!("Approval by user {} ({} of {} approvals)", user_id, approvals, required),
+        ),
+    };
+    let event = NewDeploymentEvent {
+        deployment_id,
+        event_type,
+        level,
+        message,
+        metadata: Some(json!({ "user_id": user_id, "comment": comment, "approvals": approvals, "required_approvals": required })),
+    };
+    if let Err(e) = db::deployment_log::insert_deployment_event(&pool, &event).await {
+        log::warn!("Failed to record {} event of deployment {}: {:#}", event.event_type, deployment_id, e);
+    }
+
+    let response = approvals_response(&pool, deployment_id).await?;
+    trail.after(&response.0);
+    Ok(response)
+}
+
+async fn approvals_response(pool: &Pool<MySql>, deployment_id: i64) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let database_error = || {
+        (
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch deployment approvals"
+            }))
+        )
+    };
+
+    let state = match db::deployment_approval::get_deployment_approval_state(pool, deployment_id).await {
+        Ok(Some(state)) => state,
+        Ok(None) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Deployment not found",
+                    "message": format!("Deployment with ID {} could not be found", deployment_id)
+                }))
+            ));
+        }
+        Err(_) => return Err(database_error()),
+    };
+    let approvals = db::deployment_approval::list_deployment_approvals(pool, deployment_id)
+        .await
+        .map_err(|_| database_error())?;
+
+    Ok(Json(json!({
+        "deployment_id": deployment_id,
+        "status": state.status,
+        "approval_status": state.approval_status,
+        "required_approvals": state.required_approvals,
+        "approver_role_id": state.approver_role_id,
+        "approvals": approvals
+    })))
+}
+
+async fn find_policy(pool: &Pool<MySql>, target: PolicyTarget) -> Result<ApprovalPolicy, (Status, Json<Value>)> {
+    match db::deployment_approval::get_approval_policy(pool, target).await {
+        Ok(Some(policy)) => Ok(policy),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Approval policy not found",
+                "message": "No approval policy is set"
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch approval policy"
+            }))
+        )),
+    }
+}
+
+async fn save_policy(
+    pool: &Pool<MySql>,
+    trail: &AuditTrail<'_>,
+    user_id: i64,
+    target: PolicyTarget,
+    request: ApprovalPolicyRequest,
+) -> Result<Json<ApprovalPolicy>, (Status, Json<Value>)> {
+    if !(1..=MAX_REQUIRED_APPROVALS).contains(&request.required_approvals) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("required_approvals must be between 1 and {}", MAX_REQUIRED_APPROVALS)
+            }))
+        ));
+    }
+    if let Some(role_id) = request.approver_role_id {
+        if db::role::get_role(pool, role_id).await.is_err() {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Role not found",
+                    "message": format!("Role with ID {} does not exist", role_id)
+                }))
+            ));
+        }
+    }
+
+    let existing = db::deployment_approval::get_approval_policy(pool, target).await.ok().flatten();
+    if let Some(existing) = &existing {
+        trail.before(existing);
+    }
+
+    let input = ApprovalPolicyInput {
+        required_approvals: request.required_approvals,
+        approver_role_id: request.approver_role_id,
+        enabled: request.enabled.unwrap_or(true),
+    };
+    match db::deployment_approval::set_approval_policy(pool, target, &input, Some(user_id)).await {
+        Ok(policy) => {
+            trail.resource("deployment_approval_policy", policy.id);
+            trail.after(&policy);
+            Ok(Json(policy))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to save approval policy"
+            }))
+        )),
+    }
+}
+
+async fn remove_policy(
+    pool: &Pool<MySql>,
+    trail: &AuditTrail<'_>,
+    target: PolicyTarget,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let policy = find_policy(pool, target).await?;
+    trail.resource("deployment_approval_policy", policy.id);
+    trail.before(&policy);
+
+    match db::deployment_approval::delete_approval_policy(pool, target).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to delete approval policy"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,345 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use db::deployment::DeploymentProgress;
+use db::deployment_log::NewDeploymentEvent;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, post, State};
+use sqlx::{MySql, Pool};
+use super::super::rbac::{Caller, DeploymentsRead, DeploymentsWrite};
+use super::get::authorize_deployment;
+
+/// A change requested to a deployment that is being rolled out.
+#[derive(Debug, Clone, Copy)]
+enum DeploymentAction {
+    Pause,
+    Resume,
+    Abort,
+    Promote,
+}
+
+impl DeploymentAction {
+    fn verb(self) -> &'static str {
+        match self {
+            DeploymentAction::Pause => "paused",
+            DeploymentAction::Resume => "resumed",
+            DeploymentAction::Abort => "aborted",
+            DeploymentAction::Promote => "promoted",
+        }
+    }
+}
+
+/// Get how far the rollout of a deployment has come.
+///
+/// `phase` names the step of the strategy the deployment is in, and
+/// `staged_instances` of `total_instances` run the new release.
+#[get("/platform/<platform_id>/deployments/<deployment_id>/progress")]
+pub async fn get_deployment_progress(
+    caller: Caller,
+    platform_id: i64,
+    deployment_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<DeploymentProgress>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsRead>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    find_progress(&pool, deployment_id).await.map(Json)
+}
+
+/// Pause a deployment in progress. It takes no further steps until resumed;
+/// instances already started or stopping carry on.
+#[post("/platform/<platform_id>/deployments/<deployment_id>/pause")]
+pub async fn pause_deployment(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<DeploymentProgress>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsWrite>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    control_deployment(&pool, &trail, caller.user.id, deployment_id, DeploymentAction::Pause).await
+}
+
+/// Resume a paused deployment.
+#[post("/platform/<platform_id>/deployments/<deployment_id>/resume")]
+pub async fn resume_deployment(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<DeploymentProgress>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsWrite>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    control_deployment(&pool, &trail, caller.user.id, deployment_id, DeploymentAction::Resume).await
+}
+
+/// Abort a deployment.
+///
+/// Pending deployments are canceled at once. A deployment in progress is
+/// canceled on the executor's next step: its new instances are stopped and
+/// the application returns to its previous release.
+#[post("/platform/<platform_id>/deployments/<deployment_id>/abort")]
+pub async fn abort_deployment(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<DeploymentProgress>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsWrite>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    control_deployment(&pool, &trail, caller.user.id, deployment_id, DeploymentAction::Abort).await
+}
+
+/// Promote a canary deployment that waits for promotion. The rest of the
+/// instances are then replaced like in a rolling deployment.
+#[post("/platform/<platform_id>/deployments/<deployment_id>/promote")]
+pub async fn promote_deployment(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<DeploymentProgress>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsWrite>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    control_deployment(&pool, &trail, caller.user.id, deployment_id, DeploymentAction::Promote).await
+}
+
+/// Applies an action to a deployment, recording it in the audit trail and
+/// the deployment's timeline. Fails with `409 Conflict` if the deployment
+/// is not in a state the action applies to.
+async fn control_deployment(
+    pool: &Pool<MySql>,
+    trail: &AuditTrail<'_>,
+    user_id: i64,
+    deployment_id: i64,
+    action: DeploymentAction,
+) -> Result<Json<DeploymentProgress>, (Status, Json<Value>)> {
+    let progress = find_progress(pool, deployment_id).await?;
+    trail.resource("deployment", deployment_id);
+    trail.before(&progress);
+
+    let pending = progress.status.as_deref() == Some("pending");
+    let applied = match action {
+        DeploymentAction::Pause => db::deployment::set_deployment_paused(pool, deployment_id, true).await,
+        DeploymentAction::Resume => db::deployment::set_deployment_paused(pool, deployment_id, false).await,
+        DeploymentAction::Abort if pending => {
+            db::deployment::cancel_pending_deployment(pool, deployment_id, "Deployment was aborted before it started").await
+        }
+        DeploymentAction::Abort => db::deployment::request_deployment_abort(pool, deployment_id).await,
+        DeploymentAction::Promote => db::deployment::promote_deployment(pool, deployment_id).await,
+    };
+
+    match applied {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Invalid deployment state",
+                    "message": format!(
+                        "Deployment {} is {}{} and cannot be {}",
+                        deployment_id,
+                        progress.status.as_deref().unwrap_or("unknown"),
+                        progress.phase.as_deref().map(|phase| format!(" ({})", phase)).unwrap_or_default(),
+                        action.verb()
+                    )
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": format!("Failed to update deployment {}", deployment_id)
+                }))
+            ));
+        }
+    }
+
+    let event = NewDeploymentEvent {
+        deployment_id,
+        event_type: match action {
+            DeploymentAction::Pause => "paused",
+            DeploymentAction::Resume => "resumed",
+            DeploymentAction::Abort if pending => "deployment_canceled",
+            DeploymentAction::Abort => "abort_requested",
+            DeploymentAction::Promote => "promoted",
+        },
+        level: "info",
+        message: format!("Deployment {} by user {}", action.verb(), user_id),
+        metadata: Some(json!({ "user_id": user_id })),
+    };
+    if let Err(e) = db::deployment_log::insert_deployment_event(pool, &event).await {
+        log::warn!("Failed to record {} event of deployment {}: {:#}", event.event_type, deployment_id, e);
+    }
+
+    let updated = find_progress(pool, deployment_id).await?;
+    trail.after(&updated);
+    Ok(Json(updated))
+}
+
+async fn find_progress(pool: &Pool<MySql>, deployment_id: i64) -> Result<DeploymentProgress, (Status, Json<Value>)> {
+    match db::deployment::get_deployment_progress(pool, deployment_id).await {
+        Ok(Some(progress)) => Ok(progress),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Deployment not found",
+                "message": format!("Deployment with ID {} could not be found", deployment_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch deployment progress"
+            }))
+        )),
+    }
+}
@@ -2,20 +2,45 @@ use std::sync::Arc;
 use crate::DatabaseManager;
 use super::super::super::db::queries as db;
 use super::types::CreateDeploymentRequest;
+use super::super::audit_log::AuditTrail;
+use super::super::rbac::{require_in_scope, Caller, DeploymentsOverride, DeploymentsWrite, RequestScope};
+use chrono::Utc;
+use db::deployment::DeploymentGates;
 use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::{post, State};
 
 use libomni::types::db::v1 as types;
 use types::deployment::Deployment;
+use crate::deployer::freeze;
+use crate::deployer::strategy::STRATEGIES;
 
 /// Create a new deployment.
+///
+/// The deployment starts out `pending`. The leader rolls out the oldest
+/// pending deployment of each application once no other deployment of the
+/// application is in progress, following its `deployment_strategy`.
+///
+/// If the application or its space has an approval policy, the deployment
+/// waits for the required approvals first. While a freeze window of the
+/// application's organization is in effect the request fails with
+/// `409 Conflict`, unless it carries an `emergency_override` with a reason
+/// and the caller holds `deployments:override`.
 #[post("/platform/<platform_id>/deployments", format = "json", data = "<deployment_request>")]
 pub async fn create_deployment(
     platform_id: i64,
     deployment_request: Json<CreateDeploymentRequest>,
+    caller: Caller,
+    trail: AuditTrail<'_>,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Deployment>, (Status, Json<Value>)> {
+    let scope = RequestScope {
+        platform_id: Some(platform_id),
+        app_id: Some(deployment_request.app_id),
+        ..Default::default()
+    };
+    require_in_scope::<DeploymentsWrite>(db_manager, &caller, scope.clone()).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -44,20 +69,148 @@ pub async fn create_deployment(
         }
     };
 
+    if !STRATEGIES.contains(&deployment_request.deployment_strategy.as_str()) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!(
+                    "Unknown deployment_strategy '{}'; use one of {}",
+                    deployment_request.deployment_strategy,
+                    STRATEGIES.join(", ")
+                )
+            }))
+        ));
+    }
+    if let Some(percentage) = deployment_request.canary_percentage {
+        if !(1..=100).contains(&percentage) {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": "canary_percentage must be between 1 and 100"
+                }))
+            ));
+        }
+    }
+
+    match db::build::get_build_by_id(&pool, deployment_request.build_id).await {
+        Ok(build) if build.app_id == deployment_request.app_id => {}
+        _ => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Build not found",
+                    "message": format!(
+                        "Build with ID {} does not exist for app {}",
+                        deployment_request.build_id, deployment_request.app_id
+                    )
+                }))
+            ));
+        }
+    }
+
+    let windows = match db::freeze_window::list_app_freeze_windows(&pool, deployment_request.app_id).await {
+        Ok(windows) => windows,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to check deploy freeze windows"
+                }))
+            ));
+        }
+    };
+    let mut freeze_override_reason = None;
+    if let Some(window) = freeze::active_window(&windows, Utc::now()) {
+        let reason = deployment_request
+            .emergency_override
+            .as_ref()
+            .map(|emergency| emergency.reason.trim())
+            .filter(|reason| !reason.is_empty());
+        let reason = match reason {
+            Some(reason) => reason,
+            None => {
+                return Err((
+                    Status::Conflict,
+                    Json(json!({
+                        "error": "Deploy freeze",
+                        "message": format!(
+                            "Deployments are frozen by '{}'{}; an emergency_override with a reason is required",
+                            window.name,
+                            window.reason.as_deref().map(|reason| format!(" ({})", reason)).unwrap_or_default()
+                        ),
+                        "freeze_window": window
+                    }))
+                ));
+            }
+        };
+        require_in_scope::<DeploymentsOverride>(db_manager, &caller, scope).await?;
+
+        trail.action("override_deploy_freeze");
+        trail.detail("freeze_window_id", window.id);
+        trail.detail("freeze_window", &window.name);
+        trail.detail("override_reason", reason);
+        freeze_override_reason = Some(reason.to_string());
+    }
+
+    let (required_approvals, approver_role_id) =
+        match db::deployment_approval::resolve_approval_policy(&pool, deployment_request.app_id).await {
+            Ok(Some(policy)) if policy.enabled != Some(false) => (policy.required_approvals, policy.approver_role_id),
+            Ok(_) => (0, None),
+            Err(_) => {
+                return Err((
+                    Status::InternalServerError,
+                    Json(json!({
+                        "error": "Database error",
+                        "message": "Failed to fetch approval policy"
+                    }))
+                ));
+            }
+        };
+    let gates = DeploymentGates {
+        required_approvals,
+        approver_role_id,
+        freeze_override_reason,
+    };
+
+    // The deployment replaces whatever the application currently runs
+    let previous = match db::deployment::get_current_deployment(&pool, deployment_request.app_id).await {
+        Ok(previous) => previous,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch current deployment"
+                }))
+            ));
+        }
+    };
+
     match db::deployment::create_deployment(
         &pool,
         deployment_request.app_id,
         deployment_request.build_id,
         &deployment_request.version,
         &deployment_request.deployment_strategy,
-        deployment_request.previous_deployment_id,
+        previous.map(|previous| previous.id),
         deployment_request.canary_percentage,
         deployment_request.environment_variables.clone(),
         deployment_request.annotations.clone(),
         deployment_request.labels.clone(),
-        None, // created_by would typically come from auth middleware
+        &gates,
+        Some(caller.user.id),
     ).await {
-        Ok(deployment) => Ok(Json(deployment)),
+        Ok(deployment) => {
+            trail.resource("deployment", deployment.id);
+            trail.after(&deployment);
+            if gates.required_approvals > 0 {
+                trail.detail("required_approvals", gates.required_approvals);
+            }
+            Ok(Json(deployment))
+        }
         Err(e) => Err((
             Status::InternalServerError,
             Json(json!({
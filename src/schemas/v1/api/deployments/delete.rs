@@ -4,10 +4,13 @@ use super::super::super::db::queries as db;
 use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::{delete, State};
+use super::super::rbac::{Caller, DeploymentsWrite};
+use super::get::authorize_deployment;
 
 /// Delete a specific deployment.
 #[delete("/platform/<platform_id>/deployments/<deployment_id>")]
 pub async fn delete_deployment(
+    caller: Caller,
     platform_id: i64,
     deployment_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -40,6 +43,8 @@ pub async fn delete_deployment(
         }
     };
 
+    authorize_deployment::<DeploymentsWrite>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
     match db::deployment::delete_deployment(&pool, deployment_id).await {
         Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
         Err(e) => Err((
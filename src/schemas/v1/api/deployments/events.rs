@@ -0,0 +1,216 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::helpers::tail::{self, LastEventId, Tail, TailStatus};
+use db::deployment_log::DeploymentEvent;
+use rocket::http::Status;
+use rocket::response::stream::EventStream;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, Shutdown, State};
+use sqlx::{MySql, Pool};
+use super::super::rbac::{Caller, DeploymentsRead};
+use super::get::authorize_deployment;
+
+/// List the timeline of a deployment with pagination, oldest first.
+///
+/// Events cover phase transitions, instances being started, becoming ready
+/// and being stopped, failed health checks, pauses, promotions, rollbacks
+/// and the outcome of the deployment; `event_type` tells them apart.
+#[get("/platform/<platform_id>/deployments/<deployment_id>/events?<page>&<per_page>")]
+pub async fn list_deployment_events(
+    caller: Caller,
+    platform_id: i64,
+    deployment_id: i64,
+    page: Option<i64>,
+    per_page: Option<i64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsRead>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    ensure_deployment_exists(&pool, deployment_id).await?;
+
+    match (page, per_page) {
+        (Some(p), Some(pp)) => {
+            let events = match db::deployment_log::list_deployment_events(&pool, deployment_id, p, pp).await {
+                Ok(events) => events,
+                Err(_) => {
+                    return Err((
+                        Status::InternalServerError,
+                        Json(json!({
+                            "error": "Database error",
+                            "message": "Failed to retrieve deployment events"
+                        }))
+                    ));
+                }
+            };
+
+            let total_count = match db::deployment_log::count_deployment_events(&pool, deployment_id).await {
+                Ok(count) => count,
+                Err(_) => {
+                    return Err((
+                        Status::InternalServerError,
+                        Json(json!({
+                            "error": "Database error",
+                            "message": "Failed to count deployment events"
+                        }))
+                    ));
+                }
+            };
+
+            let total_pages = (total_count as f64 / pp as f64).ceil() as i64;
+
+            Ok(Json(json!({
+                "events": events,
+                "pagination": {
+                    "page": p,
+                    "per_page": pp,
+                    "total_count": total_count,
+                    "total_pages": total_pages
+                }
+            })))
+        }
+        _ => Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Missing pagination parameters",
+                "message": "Please provide both 'page' and 'per_page' parameters"
+            }))
+        ))
+    }
+}
+
+/// Stream the timeline of a deployment as server-sent events.
+///
+/// Every event is sent as a `deployment_event` whose ID is the event's ID;
+/// events after `after`, or after the `Last-Event-ID` of a reconnecting
+/// client, are sent first. Once the deployment has finished and all of its
+/// events were sent, an `end` event with its final status closes the
+/// stream.
+#[get("/platform/<platform_id>/deployments/<deployment_id>/events/stream?<after>")]
+pub async fn stream_deployment_events(
+    caller: Caller,
+    platform_id: i64,
+    deployment_id: i64,
+    after: Option<i64>,
+    last_event_id: LastEventId,
+    db_manager: &State<Arc<DatabaseManager>>,
+    shutdown: Shutdown,
+) -> Result<EventStream![], (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsRead>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    ensure_deployment_exists(&pool, deployment_id).await?;
+    let after = last_event_id.0.or(after).unwrap_or(0);
+    Ok(tail::follow(DeploymentTail { pool, deployment_id }, after, shutdown))
+}
+
+/// The timeline of a deployment, followed until the deployment has
+/// finished.
+struct DeploymentTail {
+    pool: Pool<MySql>,
+    deployment_id: i64,
+}
+
+#[rocket::async_trait]
+impl Tail for DeploymentTail {
+    type Entry = DeploymentEvent;
+
+    const EVENT: &'static str = "deployment_event";
+    const RESOURCE: &'static str = "deployment";
+    const ENTRIES: &'static str = "events";
+
+    fn id(&self) -> i64 {
+        self.deployment_id
+    }
+
+    async fn status(&self) -> anyhow::Result<Option<TailStatus>> {
+        let progress = db::deployment::get_deployment_progress(&self.pool, self.deployment_id).await?;
+        Ok(progress.map(|progress| TailStatus {
+            finished: !matches!(progress.status.as_deref(), Some("pending" | "in_progress")),
+            end: json!({ "deployment_id": self.deployment_id, "status": progress.status }),
+        }))
+    }
+
+    async fn entries_after(&self, after: i64, limit: i64) -> anyhow::Result<Vec<DeploymentEvent>> {
+        db::deployment_log::list_deployment_events_after(&self.pool, self.deployment_id, after, limit).await
+    }
+
+    fn entry_id(event: &DeploymentEvent) -> i64 {
+        event.id
+    }
+}
+
+async fn ensure_deployment_exists(pool: &Pool<MySql>, deployment_id: i64) -> Result<(), (Status, Json<Value>)> {
+    match db::deployment::get_deployment_progress(pool, deployment_id).await {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Deployment not found",
+                "message": format!("Deployment with ID {} could not be found", deployment_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch deployment"
+            }))
+        )),
+    }
+}
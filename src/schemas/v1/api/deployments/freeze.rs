@@ -0,0 +1,356 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::types::FreezeWindowRequest;
+use chrono::{NaiveTime, Utc};
+use db::freeze_window::{FreezeWindow, FreezeWindowInput};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, put, State};
+use sqlx::{MySql, Pool};
+use super::super::rbac::{Require, DeploymentsManage, DeploymentsRead};
+use crate::deployer::freeze;
+
+/// List the deploy freeze windows of an organization, each with whether it
+/// is in effect now.
+#[get("/platform/<platform_id>/orgs/<org_id>/freeze-windows")]
+pub async fn list_freeze_windows(
+    _auth: Require<DeploymentsRead>,
+    platform_id: i64,
+    org_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let windows = match db::freeze_window::list_freeze_windows(&pool, org_id).await {
+        Ok(windows) => windows,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch freeze windows"
+                }))
+            ));
+        }
+    };
+
+    let now = Utc::now();
+    let windows: Vec<Value> = windows
+        .iter()
+        .map(|window| {
+            let mut value = json!(window);
+            value["active"] = json!(freeze::is_active(window, now));
+            value
+        })
+        .collect();
+
+    Ok(Json(json!({ "freeze_windows": windows })))
+}
+
+/// Create a deploy freeze window for an organization. While it is in
+/// effect, new deployments of the organization's applications need an
+/// emergency override.
+#[post("/platform/<platform_id>/orgs/<org_id>/freeze-windows", format = "json", data = "<request>")]
+pub async fn create_freeze_window(
+    auth: Require<DeploymentsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    org_id: i64,
+    request: Json<FreezeWindowRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<FreezeWindow>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::org::get_org_by_id(&pool, org_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Organization not found",
+                "message": format!("Organization with ID {} does not exist", org_id)
+            }))
+        ));
+    }
+    let input = validate_window(request.into_inner())?;
+
+    match db::freeze_window::create_freeze_window(&pool, org_id, &input, Some(auth.user_id())).await {
+        Ok(window) => {
+            trail.resource("freeze_window", window.id);
+            trail.after(&window);
+            Ok(Json(window))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to create freeze window"
+            }))
+        )),
+    }
+}
+
+/// Replace the settings of a deploy freeze window.
+#[put("/platform/<platform_id>/orgs/<org_id>/freeze-windows/<window_id>", format = "json", data = "<request>")]
+pub async fn update_freeze_window(
+    _auth: Require<DeploymentsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    org_id: i64,
+    window_id: i64,
+    request: Json<FreezeWindowRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<FreezeWindow>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let existing = find_window(&pool, org_id, window_id).await?;
+    let input = validate_window(request.into_inner())?;
+    trail.resource("freeze_window", window_id);
+    trail.before(&existing);
+
+    match db::freeze_window::update_freeze_window(&pool, org_id, window_id, &input).await {
+        Ok(Some(window)) => {
+            trail.after(&window);
+            Ok(Json(window))
+        }
+        Ok(None) => Err(window_not_found(window_id)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to update freeze window"
+            }))
+        )),
+    }
+}
+
+/// Delete a deploy freeze window.
+#[delete("/platform/<platform_id>/orgs/<org_id>/freeze-windows/<window_id>")]
+pub async fn delete_freeze_window(
+    _auth: Require<DeploymentsManage>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    org_id: i64,
+    window_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let existing = find_window(&pool, org_id, window_id).await?;
+    trail.resource("freeze_window", window_id);
+    trail.before(&existing);
+
+    match db::freeze_window::delete_freeze_window(&pool, org_id, window_id).await {
+        Ok(true) => Ok(Json(json!({ "status": "deleted" }))),
+        Ok(false) => Err(window_not_found(window_id)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to delete freeze window"
+            }))
+        )),
+    }
+}
+
+fn validate_window(request: FreezeWindowRequest) -> Result<FreezeWindowInput, (Status, Json<Value>)> {
+    let invalid = |message: String| {
+        (
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": message
+            }))
+        )
+    };
+
+    let name = request.name.trim().to_string();
+    if name.is_empty() {
+        return Err(invalid("name cannot be empty".to_string()));
+    }
+    let recurrence = request.recurrence.unwrap_or_else(|| "once".to_string());
+    let mut input = FreezeWindowInput {
+        name,
+        reason: request.reason.filter(|reason| !reason.trim().is_empty()),
+        recurrence,
+        starts_at: None,
+        ends_at: None,
+        start_day: None,
+        start_time: None,
+        end_day: None,
+        end_time: None,
+        enabled: request.enabled.unwrap_or(true),
+    };
+
+    match input.recurrence.as_str() {
+        "once" => match (request.starts_at, request.ends_at) {
+            (Some(starts_at), Some(ends_at)) if starts_at < ends_at => {
+                input.starts_at = Some(starts_at);
+                input.ends_at = Some(ends_at);
+            }
+            (Some(_), Some(_)) => return Err(invalid("ends_at must be after starts_at".to_string())),
+            _ => return Err(invalid("One-time freeze windows need starts_at and ends_at".to_string())),
+        },
+        "weekly" => {
+            let (start_day, end_day) = match (request.start_day, request.end_day) {
+                (Some(start_day), Some(end_day)) if (0..=6).contains(&start_day) && (0..=6).contains(&end_day) => {
+                    (start_day, end_day)
+                }
+                _ => {
+                    return Err(invalid(
+                        "Weekly freeze windows need a start_day and end_day between 0 (Monday) and 6 (Sunday)".to_string(),
+                    ));
+                }
+            };
+            let start_time = parse_time(request.start_time.as_deref())
+                .ok_or_else(|| invalid("start_time must be a time of day as HH:MM".to_string()))?;
+            let end_time = parse_time(request.end_time.as_deref())
+                .ok_or_else(|| invalid("end_time must be a time of day as HH:MM".to_string()))?;
+            if start_day == end_day && start_time == end_time {
+                return Err(invalid("Weekly freeze windows cannot start and end at the same time".to_string()));
+            }
+            input.start_day = Some(start_day);
+            input.start_time = Some(start_time);
+            input.end_day = Some(end_day);
+            input.end_time = Some(end_time);
+        }
+        other => return Err(invalid(format!("Unknown recurrence '{}'; use once or weekly", other))),
+    }
+
+    Ok(input)
+}
+
+fn parse_time(time: Option<&str>) -> Option<NaiveTime> {
+    let time = time?.trim();
+    NaiveTime::parse_from_str(time, "%H:%M")
+        .or_else(|_| NaiveTime::parse_from_str(time, "%H:%M:%S"))
+        .ok()
+}
+
+async fn find_window(pool: &Pool<MySql>, org_id: i64, window_id: i64) -> Result<FreezeWindow, (Status, Json<Value>)> {
+    match db::freeze_window::get_freeze_window(pool, org_id, window_id).await {
+        Ok(Some(window)) => Ok(window),
+        Ok(None) => Err(window_not_found(window_id)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch freeze window"
+            }))
+        )),
+    }
+}
+
+fn window_not_found(window_id: i64) -> (Status, Json<Value>) {
+    (
+        Status::NotFound,
+        Json(json!({
+            "error": "Freeze window not found",
+            "message": format!("Freeze window with ID {} does not exist", window_id)
+        }))
+    )
+}
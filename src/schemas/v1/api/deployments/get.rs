@@ -7,10 +7,13 @@ use rocket::{get, State};
 
 use libomni::types::db::v1 as types;
 use types::deployment::Deployment;
+use sqlx::{MySql, Pool};
+use super::super::rbac::{require_in_scope, Authorization, Caller, DeploymentsRead, Permission, RequestScope};
 
 /// Get a specific deployment by ID.
 #[get("/platform/<platform_id>/deployments/<deployment_id>")]
 pub async fn get_deployment(
+    caller: Caller,
     platform_id: i64,
     deployment_id: i64,
     db_manager: &State<Arc<DatabaseManager>>
@@ -43,6 +46,8 @@ pub async fn get_deployment(
         }
     };
 
+    authorize_deployment::<DeploymentsRead>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
     match db::deployment::get_deployment_by_id(&pool, deployment_id).await {
         Ok(deployment) => Ok(Json(deployment)),
         Err(_) => Err((
@@ -53,4 +58,34 @@ pub async fn get_deployment(
             }))
         )),
     }
+}
+
+/// Checks that the caller holds `P` for the application a deployment belongs
+/// to. A deployment that does not exist is checked at platform level, so
+/// callers without access cannot tell whether it exists.
+pub(crate) async fn authorize_deployment<P: Permission>(
+    db_manager: &Arc<DatabaseManager>,
+    caller: &Caller,
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    deployment_id: i64,
+) -> Result<Authorization, (Status, Json<Value>)> {
+    let app_id = match db::deployment::get_deployment_app_id(pool, deployment_id).await {
+        Ok(app_id) => app_id,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch deployment"
+                }))
+            ));
+        }
+    };
+
+    require_in_scope::<P>(db_manager, caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id,
+        ..Default::default()
+    }).await
 }
\ No newline at end of file
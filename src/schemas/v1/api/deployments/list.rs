@@ -4,10 +4,12 @@ use super::super::super::db::queries as db;
 use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
+use super::super::rbac::{Require, DeploymentsRead};
 
 /// List all deployments with pagination support.
 #[get("/platform/<platform_id>/deployments?<page>&<per_page>")]
 pub async fn list_deployments(
+    _auth: Require<DeploymentsRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -96,6 +98,7 @@ pub async fn list_deployments(
 /// Count the total number of deployments.
 #[get("/platform/<platform_id>/count/deployments")]
 pub async fn count_deployments(
+    _auth: Require<DeploymentsRead>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>
 ) -> Result<Json<i64>, (Status, Json<Value>)> {
@@ -142,6 +145,7 @@ pub async fn count_deployments(
 /// List all deployments for a specific application with pagination.
 #[get("/platform/<platform_id>/apps/<app_id>/deployments?<page>&<per_page>")]
 pub async fn list_app_deployments(
+    _auth: Require<DeploymentsRead>,
     platform_id: i64,
     app_id: i64,
     page: Option<i64>,
@@ -5,6 +5,11 @@
 //! - Getting deployment details
 //! - Creating and managing deployments
 //! - Updating deployment status
+//! - Following, pausing, resuming, aborting and promoting rollouts
+//! - Rolling back to the previous deployment
+//! - Listing and streaming the event timeline of a deployment
+//! - Approval policies, approving and rejecting deployments
+//! - Deploy freeze windows of organizations
 //! - Deleting deployments
 
 // Import and re-export all modules
@@ -14,10 +19,24 @@ pub mod get;
 pub mod create;
 pub mod update;
 pub mod delete;
+pub mod control;
+pub mod rollback;
+pub mod events;
+pub mod approvals;
+pub mod freeze;
 
 // Re-export all route functions
 pub use list::{list_deployments, count_deployments, list_app_deployments};
 pub use get::get_deployment;
 pub use create::create_deployment;
 pub use update::update_deployment_status;
-pub use delete::delete_deployment;
\ No newline at end of file
+pub use delete::delete_deployment;
+pub use control::{get_deployment_progress, pause_deployment, resume_deployment, abort_deployment, promote_deployment};
+pub use rollback::{rollback_deployment, list_app_rollbacks, get_rollback};
+pub use events::{list_deployment_events, stream_deployment_events};
+pub use approvals::{
+    list_deployment_approvals, approve_deployment, reject_deployment,
+    get_app_approval_policy, set_app_approval_policy, delete_app_approval_policy,
+    get_space_approval_policy, set_space_approval_policy, delete_space_approval_policy,
+};
+pub use freeze::{list_freeze_windows, create_freeze_window, update_freeze_window, delete_freeze_window};
@@ -0,0 +1,440 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::types::RollbackDeploymentRequest;
+use db::deployment_log::NewDeploymentEvent;
+use db::rollback::{NewRollback, Rollback};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, post, State};
+use sqlx::{MySql, Pool};
+use super::super::rbac::{require_in_scope, Caller, DeploymentsRead, DeploymentsWrite, RequestScope, Require};
+use super::get::authorize_deployment;
+
+/// Seconds a rollback requested through the API waits for another
+/// operation on the application to finish.
+const ROLLBACK_LOCK_TIMEOUT_SECS: i64 = 10;
+
+/// Who asked for a rollback and why.
+#[derive(Debug, Clone, Copy)]
+pub struct RollbackOrigin<'a> {
+    pub reason: &'a str,
+    /// Whether the orchestrator rolled back on its own
+    pub automatic: bool,
+    /// What made the orchestrator roll back, such as `health_check_failed`
+    pub trigger_condition: Option<&'a str>,
+    pub created_by: Option<i64>,
+}
+
+/// Roll back a deployment.
+///
+/// Only the deployment an application currently runs can be rolled back,
+/// and only while no other deployment of the application is pending or in
+/// progress. A new deployment of the release of `previous_deployment_id` is
+/// created and rolled out like any other; the rollback completes or fails
+/// with it.
+#[post("/platform/<platform_id>/deployments/<deployment_id>/rollback", format = "json", data = "<request>")]
+pub async fn rollback_deployment(
+    caller: Caller,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    deployment_id: i64,
+    request: Option<Json<RollbackDeploymentRequest>>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Rollback>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_deployment::<DeploymentsWrite>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
+    let reason = request
+        .and_then(|request| request.into_inner().reason)
+        .filter(|reason| !reason.trim().is_empty())
+        .unwrap_or_else(|| format!("Rolled back by user {}", caller.user.id));
+    let origin = RollbackOrigin {
+        reason: &reason,
+        automatic: false,
+        trigger_condition: Some("manual"),
+        created_by: Some(caller.user.id),
+    };
+
+    trail.resource("deployment", deployment_id);
+    match roll_back_deployment(&pool, deployment_id, origin, ROLLBACK_LOCK_TIMEOUT_SECS).await? {
+        Some(rollback) => {
+            trail.after(&rollback);
+            Ok(Json(rollback))
+        }
+        None => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Application busy",
+                "message": "Another operation on the application is in progress"
+            }))
+        )),
+    }
+}
+
+/// List the rollbacks of an application with pagination, newest first.
+#[get("/platform/<platform_id>/apps/<app_id>/rollbacks?<page>&<per_page>")]
+pub async fn list_app_rollbacks(
+    _auth: Require<DeploymentsRead>,
+    platform_id: i64,
+    app_id: i64,
+    page: Option<i64>,
+    per_page: Option<i64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match (page, per_page) {
+        (Some(p), Some(pp)) => {
+            let rollbacks = match db::rollback::list_rollbacks_by_app(&pool, app_id, p, pp).await {
+                Ok(rollbacks) => rollbacks,
+                Err(_) => {
+                    return Err((
+                        Status::InternalServerError,
+                        Json(json!({
+                            "error": "Database error",
+                            "message": "Failed to retrieve rollbacks"
+                        }))
+                    ));
+                }
+            };
+
+            let total_count = match db::rollback::count_rollbacks_by_app(&pool, app_id).await {
+                Ok(count) => count,
+                Err(_) => {
+                    return Err((
+                        Status::InternalServerError,
+                        Json(json!({
+                            "error": "Database error",
+                            "message": "Failed to count rollbacks"
+                        }))
+                    ));
+                }
+            };
+
+            let total_pages = (total_count as f64 / pp as f64).ceil() as i64;
+
+            Ok(Json(json!({
+                "rollbacks": rollbacks,
+                "pagination": {
+                    "page": p,
+                    "per_page": pp,
+                    "total_count": total_count,
+                    "total_pages": total_pages
+                }
+            })))
+        }
+        _ => Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Missing pagination parameters",
+                "message": "Please provide both 'page' and 'per_page' parameters"
+            }))
+        ))
+    }
+}
+
+/// Get a specific rollback by ID.
+#[get("/platform/<platform_id>/rollbacks/<rollback_id>")]
+pub async fn get_rollback(
+    caller: Caller,
+    platform_id: i64,
+    rollback_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Rollback>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let rollback = match db::rollback::get_rollback_by_id(&pool, rollback_id).await {
+        Ok(rollback) => rollback,
+        Err(_) => return Err(database_error("Failed to fetch rollback".to_string())),
+    };
+
+    // Checked before revealing whether the rollback exists
+    require_in_scope::<DeploymentsRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id: rollback.as_ref().map(|rollback| rollback.app_id),
+        ..Default::default()
+    }).await?;
+
+    match rollback {
+        Some(rollback) => Ok(Json(rollback)),
+        None => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Rollback not found",
+                "message": format!("Rollback with ID {} could not be found", rollback_id)
+            }))
+        )),
+    }
+}
+
+/// Rolls a deployment back to the release of its previous deployment, on
+/// behalf of a user or of the orchestrator itself, and raises an alert.
+///
+/// Returns `None` if another operation on the application held its control
+/// lock for longer than `lock_timeout_secs`.
+pub async fn roll_back_deployment(
+    pool: &Pool<MySql>,
+    deployment_id: i64,
+    origin: RollbackOrigin<'_>,
+    lock_timeout_secs: i64,
+) -> Result<Option<Rollback>, (Status, Json<Value>)> {
+    let from = match db::deployment::get_deployment_progress(pool, deployment_id).await {
+        Ok(Some(deployment)) => deployment,
+        Ok(None) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Deployment not found",
+                    "message": format!("Deployment with ID {} could not be found", deployment_id)
+                }))
+            ));
+        }
+        Err(_) => return Err(database_error("Failed to fetch deployment".to_string())),
+    };
+
+    let lock = match db::app::lock_app_control(pool, from.app_id, lock_timeout_secs).await {
+        Ok(Some(lock)) => lock,
+        Ok(None) => return Ok(None),
+        Err(_) => return Err(database_error("Failed to lock application".to_string())),
+    };
+
+    let result = create(pool, deployment_id, from.app_id, origin).await;
+
+    db::app::unlock_app_control(lock, from.app_id).await;
+    result.map(Some)
+}
+
+async fn create(
+    pool: &Pool<MySql>,
+    deployment_id: i64,
+    app_id: i64,
+    origin: RollbackOrigin<'_>,
+) -> Result<Rollback, (Status, Json<Value>)> {
+    let from = db::deployment::get_deployment_by_id(pool, deployment_id)
+        .await
+        .map_err(|_| database_error("Failed to fetch deployment".to_string()))?;
+
+    let current = db::deployment::get_current_deployment(pool, app_id)
+        .await
+        .map_err(|_| database_error("Failed to fetch current deployment".to_string()))?;
+    if current.map(|current| current.id) != Some(deployment_id) {
+        return Err(conflict(format!(
+            "Deployment {} is {} and not the release its application runs; only the current deployment can be rolled back",
+            deployment_id,
+            from.status.as_deref().unwrap_or("unknown")
+        )));
+    }
+
+    match db::deployment::has_unfinished_deployment(pool, app_id).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return Err(conflict(
+                "Another deployment of the application is pending or in progress; abort it instead".to_string(),
+            ));
+        }
+        Err(_) => return Err(database_error("Failed to check for unfinished deployments".to_string())),
+    }
+    match db::rollback::has_rollback_from(pool, deployment_id).await {
+        Ok(false) => {}
+        Ok(true) => return Err(conflict(format!("Deployment {} has already been rolled back", deployment_id))),
+        Err(_) => return Err(database_error("Failed to check for rollbacks".to_string())),
+    }
+
+    let to_deployment_id = from.previous_deployment_id.ok_or_else(|| {
+        conflict(format!("Deployment {} has no previous deployment to roll back to", deployment_id))
+    })?;
+    let to = db::deployment::get_deployment_by_id(pool, to_deployment_id)
+        .await
+        .map_err(|_| database_error("Failed to fetch previous deployment".to_string()))?;
+    if to.app_id != app_id {
+        return Err((
+            Status::UnprocessableEntity,
+            Json(json!({
+                "error": "Cannot roll back",
+                "message": format!(
+                    "Previous deployment {} belongs to another application and cannot be rolled back to",
+                    to_deployment_id
+                )
+            }))
+        ));
+    }
+    if to.status.as_deref() != Some("deployed") {
+        return Err(conflict(format!(
+            "Previous deployment {} is {} and cannot be rolled back to",
+            to_deployment_id,
+            to.status.as_deref().unwrap_or("unknown")
+        )));
+    }
+
+    // A rollback should not wait for promotion
+    let strategy = match from.deployment_strategy.as_deref() {
+        Some("canary") | None => "rolling",
+        Some(strategy) => strategy,
+    };
+    let rollback = db::rollback::create_rollback(pool, &NewRollback {
+        app_id,
+        from_deployment_id: deployment_id,
+        to_deployment_id,
+        deployment_strategy: strategy,
+        reason: origin.reason,
+        automatic: origin.automatic,
+        trigger_condition: origin.trigger_condition,
+        created_by: origin.created_by,
+    })
+    .await
+    .map_err(|_| database_error("Failed to create rollback".to_string()))?;
+
+    let event = NewDeploymentEvent {
+        deployment_id,
+        event_type: "rollback_started",
+        level: "warn",
+        message: format!(
+            "Rolling back to deployment {} (version {}): {}",
+            to_deployment_id,
+            to.version.as_deref().unwrap_or("unknown"),
+            origin.reason
+        ),
+        metadata: Some(json!({
+            "rollback_id": rollback.id,
+            "deployment_id": rollback.deployment_id,
+            "automatic": origin.automatic,
+            "trigger_condition": origin.trigger_condition
+        })),
+    };
+    if let Err(e) = db::deployment_log::insert_deployment_event(pool, &event).await {
+        log::warn!("Failed to record rollback_started event of deployment {}: {:#}", deployment_id, e);
+    }
+
+    let org_id = db::app::get_app_by_id(pool, app_id).await.ok().map(|app| app.org_id);
+    let alert = db::alert::create_alert(
+        pool,
+        "deployment_rollback",
+        if origin.automatic { "critical" } else { "warning" },
+        "deployments",
+        &format!(
+            "{} of deployment {} to deployment {}: {}",
+            if origin.automatic { "Automatic rollback" } else { "Rollback" },
+            deployment_id,
+            to_deployment_id,
+            origin.reason
+        ),
+        Some(json!({
+            "rollback_id": rollback.id,
+            "from_deployment_id": deployment_id,
+            "to_deployment_id": to_deployment_id,
+            "trigger_condition": origin.trigger_condition
+        })),
+        org_id,
+        Some(app_id),
+        None,
+        None,
+        None,
+    )
+    .await;
+    if let Err(e) = alert {
+        log::warn!("Failed to raise alert for rollback {}: {:#}", rollback.id, e);
+    }
+
+    Ok(rollback)
+}
+
+fn conflict(message: String) -> (Status, Json<Value>) {
+    (
+        Status::Conflict,
+        Json(json!({
+            "error": "Cannot roll back",
+            "message": message
+        }))
+    )
+}
+
+fn database_error(message: String) -> (Status, Json<Value>) {
+    (
+        Status::InternalServerError,
+        Json(json!({
+            "error": "Database error",
+            "message": message
+        }))
+    )
+}
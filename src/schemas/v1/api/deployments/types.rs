@@ -7,11 +7,19 @@ pub struct CreateDeploymentRequest {
     pub build_id: i64,
     pub version: String,
     pub deployment_strategy: String,
-    pub previous_deployment_id: Option<i64>,
     pub canary_percentage: Option<i64>,
     pub environment_variables: Option<serde_json::Value>,
     pub annotations: Option<serde_json::Value>,
     pub labels: Option<serde_json::Value>,
+    /// Lets the deployment through an active deploy freeze
+    pub emergency_override: Option<EmergencyOverride>,
+}
+
+/// An override of the deploy freeze windows of an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmergencyOverride {
+    /// Why the deployment cannot wait for the freeze to end
+    pub reason: String,
 }
 
 /// Request body for updating a deployment's status.
@@ -19,4 +27,46 @@ pub struct CreateDeploymentRequest {
 pub struct UpdateDeploymentStatusRequest {
     pub status: String,
     pub error_message: Option<String>,
-}
\ No newline at end of file
+}
+/// Request body for rolling back a deployment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollbackDeploymentRequest {
+    /// Why the deployment is rolled back
+    pub reason: Option<String>,
+}
+
+/// Request body for approving or rejecting a deployment.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeploymentDecisionRequest {
+    pub comment: Option<String>,
+}
+
+/// Request body for setting the approval policy of an application or space.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalPolicyRequest {
+    /// Approvals a deployment needs before it starts
+    pub required_approvals: i64,
+    /// Role approvers must hold; any holder of `deployments:approve` if unset
+    pub approver_role_id: Option<i64>,
+    pub enabled: Option<bool>,
+}
+
+/// Request body for creating or replacing a deploy freeze window.
+///
+/// One-time windows set `starts_at` and `ends_at`. Weekly windows set
+/// `start_day`, `start_time`, `end_day` and `end_time`, with days counted
+/// from 0 = Monday and times as UTC `HH:MM`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FreezeWindowRequest {
+    pub name: String,
+    pub reason: Option<String>,
+    /// 'once' (default) or 'weekly'
+    pub recurrence: Option<String>,
+    pub starts_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub ends_at: Option<chrono::DateTime<chrono::Utc>>,
+    pub start_day: Option<i8>,
+    pub start_time: Option<String>,
+    pub end_day: Option<i8>,
+    pub end_time: Option<String>,
+    pub enabled: Option<bool>,
+}
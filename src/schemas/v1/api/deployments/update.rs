@@ -8,10 +8,13 @@ use rocket::{put, State};
 
 use libomni::types::db::v1 as types;
 use types::deployment::Deployment;
+use super::super::rbac::{Caller, DeploymentsWrite};
+use super::get::authorize_deployment;
 
 /// Update a deployment's status.
 #[put("/platform/<platform_id>/deployments/<deployment_id>/status", format = "json", data = "<status_request>")]
 pub async fn update_deployment_status(
+    caller: Caller,
     platform_id: i64,
     deployment_id: i64,
     status_request: Json<UpdateDeploymentStatusRequest>,
@@ -45,6 +48,8 @@ pub async fn update_deployment_status(
         }
     };
 
+    authorize_deployment::<DeploymentsWrite>(db_manager, &caller, &pool, platform_id, deployment_id).await?;
+
     match db::deployment::update_deployment_status(
         &pool,
         deployment_id,
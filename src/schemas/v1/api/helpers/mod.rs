@@ -1 +1,3 @@
 pub mod release;
+pub mod request;
+pub mod tail;
\ No newline at end of file
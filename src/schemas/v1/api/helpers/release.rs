@@ -1,118 +1,142 @@
 use rocket::data::Data;
 use rocket::http::ContentType;
 use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
 use rocket_multipart_form_data::{
     MultipartFormData, MultipartFormDataField, MultipartFormDataOptions,
 };
-use std::fs;
+use sqlx::{MySql, Pool};
 
+use libomni::types::db::v1 as types;
+use types::build::Build;
+
+use crate::artifacts::{hash_file, release_key, ArtifactStore};
+use super::super::super::db::queries as db;
+
+/// Largest release artifact accepted, in bytes.
+const MAX_RELEASE_SIZE: u64 = 5 * 1024 * 1024 * 1024;
+
+/// Form fields a release artifact may be uploaded in, for compatibility with
+/// the CLI and third-party tools.
+const FILE_FIELDS: [&str; 3] = ["media", "file", "upload"];
+
+/// Stores an uploaded release artifact and records it as a pending build of
+/// the application.
+///
+/// The upload is hashed before it is stored and rejected if the form carries
+/// a `sha256` field that does not match it. The store verifies what it wrote
+/// against the same checksum.
 pub async fn release<'a>(
-    app_id: String,
+    pool: &Pool<MySql>,
+    store: &dyn ArtifactStore,
+    platform_id: i64,
+    app_id: i64,
     release_version: String,
     content_type: &ContentType,
     data: Data<'a>,
-) -> Result<Status, Status> {
-    println!("Starting deploy handler");
-    println!("Content-Type: {:?}", content_type);
-
-    println!("Build started for app: {:#?}", app_id);
-
+) -> Result<Build, (Status, Json<Value>)> {
     let mut options = MultipartFormDataOptions::new();
+    for field in FILE_FIELDS {
+        options
+            .allowed_fields
+            .push(MultipartFormDataField::file(field).size_limit(MAX_RELEASE_SIZE));
+    }
+    options.allowed_fields.push(MultipartFormDataField::text("sha256"));
 
-    // Add multiple possible field names to help debug
-    options
-        .allowed_fields
-        .push(MultipartFormDataField::file("media").size_limit(5 * 1024 * 1024 * 1024));
-    options
-        .allowed_fields
-        .push(MultipartFormDataField::file("file").size_limit(5 * 1024 * 1024 * 1024));
-    options
-        .allowed_fields
-        .push(MultipartFormDataField::file("upload").size_limit(5 * 1024 * 1024 * 1024));
-
-    // Parse form data with detailed error handling
     let form_data = match MultipartFormData::parse(content_type, data, options).await {
-        Ok(form) => {
-            println!("Successfully parsed form data");
-            form
-        }
+        Ok(form) => form,
         Err(e) => {
-            println!("Error parsing form data: {:?}", e);
-            return Err(Status::new(400));
+            log::warn!("Failed to parse release upload for app {}: {:?}", app_id, e);
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid upload",
+                    "message": format!("Failed to parse multipart form: {:?}", e)
+                }))
+            ));
         }
     };
 
-    // Print ALL available fields for debugging
-    println!("Available fields in form_data:");
-    println!("Raw fields: {:#?}", form_data.raw);
-    println!("Text fields: {:#?}", form_data.texts);
-    println!("Files: {:#?}", form_data.files);
-
-    // Check each possible file field for compatibility with CLI and third-party tools
-    for field_name in ["media", "file", "upload"] {
-        // if the field is found to have data accept the file and save it
-        if let Some(files) = form_data.files.get(field_name) {
-            println!("Found files in field '{}': {:?}", field_name, files);
-
-            // Check if the file is valid, if so, process it
-            if let Some(file) = files.first() {
-                println!("Processing file:");
-                println!("  Path: {:?}", file.path);
-                println!("  Filename: {:?}", file.file_name);
-                println!("  Content-Type: {:?}", file.content_type);
-
-                let mut release_version = release_version.clone();
+    let file = match FILE_FIELDS
+        .iter()
+        .find_map(|field| form_data.files.get(*field).and_then(|files| files.first()))
+    {
+        Some(file) => file,
+        None => {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid upload",
+                    "message": "No artifact found in the 'media', 'file' or 'upload' field"
+                }))
+            ));
+        }
+    };
+    let expected_checksum = form_data
+        .texts
+        .get("sha256")
+        .and_then(|texts| texts.first())
+        .map(|text| text.text.trim().to_ascii_lowercase());
 
-                // Create App directory
-                if release_version.is_empty() {
-                    release_version = uuid::Uuid::new_v4().to_string();
-                }
+    let (checksum, size) = match hash_file(&file.path).await {
+        Ok(digest) => digest,
+        Err(e) => {
+            log::error!("Failed to hash release upload for app {}: {:#}", app_id, e);
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Storage error",
+                    "message": "Failed to read uploaded artifact"
+                }))
+            ));
+        }
+    };
 
-                // Handle the case where the directory does not exist
-                match fs::create_dir_all(format!("./Apps/{}/{}", app_id, release_version)) {
-                    Ok(_) => {
-                        let dir = std::path::PathBuf::from(format!(
-                            "./Apps/{}/{}",
-                            app_id, release_version
-                        ));
-                        let canon_dir = dir.canonicalize().unwrap();
-                        log::info!("Created Directory at {}", canon_dir.display())
-                    }
-                    Err(_) => {
-                        return Err::<Status, Status>(Status::new(500));
-                    }
-                }
+    if let Some(expected) = expected_checksum {
+        if expected != checksum {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Checksum mismatch",
+                    "message": format!("Uploaded artifact has SHA-256 {}, expected {}", checksum, expected)
+                }))
+            ));
+        }
+    }
 
-                // Copy file with size verification
-                let source_size = fs::metadata(&file.path)
-                    .map_err(|_| Err::<Status, Status>(Status::new(500)))
-                    .unwrap()
-                    .len();
+    let release_version = if release_version.is_empty() {
+        uuid::Uuid::new_v4().to_string()
+    } else {
+        release_version
+    };
 
-                println!("Source file size: {} bytes", source_size);
+    let key = release_key(platform_id, app_id, &release_version, &checksum);
+    let artifact_url = match store.put(&key, &file.path, &checksum, size).await {
+        Ok(url) => url,
+        Err(e) => {
+            log::error!("Failed to store release artifact for app {}: {:#}", app_id, e);
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Storage error",
+                    "message": "Failed to store release artifact"
+                }))
+            ));
+        }
+    };
+    log::info!("Stored release {} of app {} ({} bytes) at {}", release_version, app_id, size, artifact_url);
 
-                match fs::copy(
-                    &file.path,
-                    format!("./Apps/{}/{}/release.tar.gz", app_id, release_version),
-                ) {
-                    Ok(bytes_written) => {
-                        println!("Successfully wrote {} bytes", bytes_written);
-                        if bytes_written == source_size {
-                            return Ok(Status::new(200));
-                        } else {
-                            return Err(Status::new(500));
-                        }
-                    }
-                    Err(e) => {
-                        println!("Error copying file: {:?}", e);
-                        return Err(Status::new(500));
-                    }
-                }
-            } else {
-                println!("No valid file found in request");
-                return Err(Status::new(500));
-            }
+    match db::build::create_release_build(pool, app_id, &release_version, &artifact_url, &checksum, size as i64).await {
+        Ok(build) => Ok(build),
+        Err(e) => {
+            log::error!("Failed to record release build for app {}: {:#}", app_id, e);
+            Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to record release build"
+                }))
+            ))
         }
     }
-    Ok::<Status, Status>(Status::new(200))
 }
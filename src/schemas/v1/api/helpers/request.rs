@@ -0,0 +1,18 @@
+use rocket::request::Request;
+
+/// Extracts the platform ID from the routed path of a request.
+///
+/// Platform scoped routes all follow the `/platform/<platform_id>/...`
+/// layout, so request guards that need the platform database (and are
+/// therefore unable to take `platform_id` as a handler argument) look for
+/// the segment following `platform`.
+pub fn platform_id_from_request(req: &Request<'_>) -> Option<i64> {
+    let mut segments = req.routed_segments(0..);
+    while let Some(segment) = segments.next() {
+        if segment == "platform" {
+            return segments.next().and_then(|id| id.parse::<i64>().ok());
+        }
+    }
+    None
+}
+
@@ -0,0 +1,123 @@
+use std::convert::Infallible;
+use std::time::Duration;
+
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::response::stream::{Event, EventStream};
+use rocket::serde::json::{json, Value};
+use rocket::tokio::select;
+use rocket::tokio::time::sleep;
+use rocket::Shutdown;
+use serde::Serialize;
+
+/// How often a followed stream checks for new entries.
+pub const TAIL_POLL_INTERVAL: Duration = Duration::from_secs(1);
+/// Most entries a followed stream reads at once.
+pub const TAIL_BATCH_SIZE: i64 = 500;
+/// How often an idle stream sends a comment to keep proxies from closing it.
+pub const TAIL_HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// The ID of the last event a reconnecting `EventSource` received.
+pub struct LastEventId(pub Option<i64>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let id = req.headers().get_one("Last-Event-ID").and_then(|id| id.trim().parse().ok());
+        Outcome::Success(LastEventId(id))
+    }
+}
+
+/// Where a tailed resource stands.
+pub struct TailStatus {
+    /// Whether the resource will record no further entries
+    pub finished: bool,
+    /// Payload of the `end` event sent once everything was streamed
+    pub end: Value,
+}
+
+/// A resource whose entries (log lines, events) can be followed.
+#[rocket::async_trait]
+pub trait Tail: Send + Sync + 'static {
+    type Entry: Serialize + Send;
+
+    /// Name the entries are sent as, such as `log`.
+    const EVENT: &'static str;
+    /// What the resource is called in error messages, such as `build`.
+    const RESOURCE: &'static str;
+    /// What its entries are called in error messages, such as `logs`.
+    const ENTRIES: &'static str;
+
+    /// ID of the resource, for log messages.
+    fn id(&self) -> i64;
+
+    /// Fetches the status of the resource, or `None` if it was deleted.
+    async fn status(&self) -> anyhow::Result<Option<TailStatus>>;
+
+    /// Fetches at most `limit` entries with an ID above `after`, oldest
+    /// first.
+    async fn entries_after(&self, after: i64, limit: i64) -> anyhow::Result<Vec<Self::Entry>>;
+
+    /// ID of an entry, sent as the event ID.
+    fn entry_id(entry: &Self::Entry) -> i64;
+}
+
+/// Streams the entries of a resource after the entry with ID `after` as
+/// server-sent events until it has finished or the server shuts down.
+///
+/// Every entry is sent as a `T::EVENT` event whose ID is the entry's ID.
+/// Once the resource has finished and all of its entries were sent, an
+/// `end` event closes the stream; problems reading the resource end it with
+/// an `error` event instead.
+pub fn follow<T: Tail>(source: T, after: i64, mut shutdown: Shutdown) -> EventStream![] {
+    let mut last_id = after;
+    EventStream! {
+        // Final entries are recorded around the time a resource's status
+        // changes, so the stream reads once more after seeing it finish
+        let mut finishing = false;
+        loop {
+            let status = match source.status().await {
+                Ok(Some(status)) => status,
+                Ok(None) => {
+                    yield Event::json(&json!({ "message": format!("The {} was deleted", T::RESOURCE) })).event("error");
+                    break;
+                }
+                Err(e) => {
+                    log::warn!("Failed to fetch status of {} {} for streaming: {:#}", T::RESOURCE, source.id(), e);
+                    yield Event::json(&json!({ "message": format!("Failed to fetch {}", T::RESOURCE) })).event("error");
+                    break;
+                }
+            };
+
+            let entries = match source.entries_after(last_id, TAIL_BATCH_SIZE).await {
+                Ok(entries) => entries,
+                Err(e) => {
+                    log::warn!("Failed to fetch {} of {} {} for streaming: {:#}", T::ENTRIES, T::RESOURCE, source.id(), e);
+                    yield Event::json(&json!({ "message": format!("Failed to fetch {} {}", T::RESOURCE, T::ENTRIES) })).event("error");
+                    break;
+                }
+            };
+            let caught_up = (entries.len() as i64) < TAIL_BATCH_SIZE;
+            for entry in entries {
+                last_id = T::entry_id(&entry);
+                yield Event::json(&entry).id(last_id.to_string()).event(T::EVENT);
+            }
+            if !caught_up {
+                continue;
+            }
+
+            if finishing {
+                yield Event::json(&status.end).event("end");
+                break;
+            }
+            finishing = status.finished;
+
+            select! {
+                _ = sleep(TAIL_POLL_INTERVAL) => {}
+                _ = &mut shutdown => break,
+            }
+        }
+    }
+    .heartbeat(TAIL_HEARTBEAT_INTERVAL)
+}
@@ -5,10 +5,12 @@ use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::State;
 use crate::schemas::v1::db::queries::{self as db};
+use super::super::rbac::{Require, InstancesRead};
 
 /// Count all instances across all applications
 #[get("/platform/<platform_id>/instance-count")]
 pub async fn count_instances(
+    _auth: Require<InstancesRead>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
@@ -8,10 +8,13 @@ use crate::schemas::v1::db::queries::{self as db};
 
 use libomni::types::db::v1 as types;
 use types::instance::Instance;
+use sqlx::{MySql, Pool};
+use super::super::rbac::{require_in_scope, Authorization, Caller, InstancesRead, Permission, RequestScope};
 
 /// Get an instance by ID
 #[get("/platform/<platform_id>/instances/<instance_id>")]
 pub async fn get_instance(
+    caller: Caller,
     platform_id: i64,
     instance_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -44,6 +47,8 @@ pub async fn get_instance(
         }
     };
 
+    authorize_instance::<InstancesRead>(db_manager, &caller, &pool, platform_id, instance_id).await?;
+
     match db::instance::get_instance_by_id(&pool, instance_id).await {
         Ok(instance) => Ok(Json(instance)),
         Err(_) => {
@@ -56,4 +61,33 @@ pub async fn get_instance(
             ))
         }
     }
+}
+
+/// Checks `P` against the application running an instance, or against the
+/// platform if the instance is unknown.
+pub(crate) async fn authorize_instance<P: Permission>(
+    db_manager: &Arc<DatabaseManager>,
+    caller: &Caller,
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    instance_id: i64,
+) -> Result<Authorization, (Status, Json<Value>)> {
+    let app_id = match db::instance::get_instance_app_id(pool, instance_id).await {
+        Ok(app_id) => app_id,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch instance"
+                }))
+            ));
+        }
+    };
+
+    require_in_scope::<P>(db_manager, caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id,
+        ..Default::default()
+    }).await
 }
\ No newline at end of file
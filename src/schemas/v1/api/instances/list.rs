@@ -8,10 +8,12 @@ use crate::schemas::v1::db::queries::{self as db};
 
 use libomni::types::db::v1 as types;
 use types::instance::Instance;
+use super::super::rbac::{Require, InstancesRead};
 
 /// List all instances by `region_id` and `app_id`
 #[get("/platform/<platform_id>/apps/<app_id>/instances/region/<region_id>?<page>&<per_page>")]
 pub async fn list_instances_by_region(
+    _auth: Require<InstancesRead>,
     platform_id: i64,
     app_id: i64,
     region_id: i64,
@@ -0,0 +1,117 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use crate::runtime::RuntimeDriver;
+use rocket::get;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+use crate::schemas::v1::db::queries::{self as db};
+
+use super::super::rbac::{Caller, InstancesRead};
+use super::get::authorize_instance;
+
+/// Number of lines returned when the request does not specify `tail`.
+const DEFAULT_TAIL: u64 = 100;
+
+/// Largest number of lines a request may ask for.
+const MAX_TAIL: u64 = 10_000;
+
+/// Get the most recent output of an instance's container.
+///
+/// Lines are read from the container runtime, oldest first. Containers that
+/// have been removed no longer have any output to show.
+#[get("/platform/<platform_id>/instances/<instance_id>/logs?<tail>")]
+pub async fn get_instance_logs(
+    caller: Caller,
+    platform_id: i64,
+    instance_id: i64,
+    tail: Option<u64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+    driver: &State<Arc<dyn RuntimeDriver>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    authorize_instance::<InstancesRead>(db_manager, &caller, &pool, platform_id, instance_id).await?;
+    let tail = tail.unwrap_or(DEFAULT_TAIL);
+    if !(1..=MAX_TAIL).contains(&tail) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("tail must be between 1 and {}", MAX_TAIL)
+            }))
+        ));
+    }
+
+    let instance = match db::instance::get_instance_by_id(&pool, instance_id).await {
+        Ok(instance) => instance,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Instance not found",
+                    "message": format!("Instance with ID {} does not exist", instance_id)
+                }))
+            ));
+        }
+    };
+
+    let container_id = match instance.container_id.as_deref() {
+        Some(container_id) => container_id,
+        None => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "No container",
+                    "message": format!("Instance {} has not been launched", instance_id)
+                }))
+            ));
+        }
+    };
+
+    let lines = match driver.logs(container_id, Some(tail)).await {
+        Ok(lines) => lines,
+        Err(e) => {
+            log::warn!("Failed to fetch logs of container {}: {:#}", container_id, e);
+            return Err((
+                Status::BadGateway,
+                Json(json!({
+                    "error": "Runtime error",
+                    "message": format!("Failed to fetch the logs of instance {} from the {} runtime", instance_id, driver.name())
+                }))
+            ));
+        }
+    };
+
+    Ok(Json(json!({
+        "instance_id": instance_id,
+        "container_id": container_id,
+        "lines": lines
+    })))
+}
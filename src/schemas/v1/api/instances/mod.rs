@@ -4,13 +4,16 @@
 //! - Listing instances by region with pagination
 //! - Getting instance details by ID
 //! - Counting total instances
+//! - Reading the output of an instance's container
 
 // Import and re-export all modules
 pub mod list;
 pub mod get;
 pub mod count;
+pub mod logs;
 
 // Re-export all route functions
 pub use list::list_instances_by_region;
 pub use get::get_instance;
-pub use count::count_instances;
\ No newline at end of file
+pub use count::count_instances;
+pub use logs::get_instance_logs;
\ No newline at end of file
@@ -6,6 +6,9 @@ use chrono::{DateTime, Utc};
 use serde::{Serialize, Deserialize};
 use clickhouse::Client;
 use uuid::Uuid;
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::rbac::{require_in_scope, Caller, Require, LogsRead, LogsWrite, RequestScope};
 
 // Enum for log levels matching ClickHouse schema
 #[derive(Debug, Serialize, Deserialize)]
@@ -209,6 +212,7 @@ async fn fetch_logs_paginated(
 // Main logs endpoint with filtering and pagination
 #[get("/logs?<page>&<per_page>&<platform_id>&<org_id>&<app_id>&<instance_id>&<level>&<start_time>&<end_time>&<search>")]
 pub async fn list_logs(
+    caller: Caller,
     page: Option<i64>,
     per_page: Option<i64>,
     platform_id: Option<String>,
@@ -220,7 +224,18 @@ pub async fn list_logs(
     end_time: Option<String>,
     search: Option<String>,
     clickhouse: &State<Client>,
+    db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Permissions are checked in the platform, organization and application
+    // the logs are filtered by
+    let id = |value: &Option<String>| value.as_deref().and_then(|value| value.parse::<i64>().ok());
+    require_in_scope::<LogsRead>(db_manager, &caller, RequestScope {
+        platform_id: id(&platform_id),
+        org_id: id(&org_id),
+        app_id: id(&app_id),
+        ..Default::default()
+    }).await?;
+
     // Default pagination values
     let page = page.unwrap_or(1);
     let per_page = per_page.unwrap_or(50);
@@ -336,6 +351,7 @@ pub async fn list_logs(
 // Platform routes - reuse the main list_logs with prefilled platform_id
 #[get("/platforms/<platform_id>/logs?<page>&<per_page>&<level>&<start_time>&<end_time>&<search>")]
 pub async fn list_platform_logs(
+    caller: Caller,
     platform_id: String,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -344,8 +360,10 @@ pub async fn list_platform_logs(
     end_time: Option<String>,
     search: Option<String>,
     clickhouse: &State<Client>,
+    db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
     list_logs(
+        caller,
         page,
         per_page,
         Some(platform_id),
@@ -357,12 +375,14 @@ pub async fn list_platform_logs(
         end_time,
         search,
         clickhouse,
+        db_manager,
     ).await
 }
 
 // Organization routes
 #[get("/orgs/<org_id>/logs?<page>&<per_page>&<platform_id>&<level>&<start_time>&<end_time>&<search>")]
 pub async fn list_org_logs(
+    caller: Caller,
     org_id: String,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -372,8 +392,10 @@ pub async fn list_org_logs(
     end_time: Option<String>,
     search: Option<String>,
     clickhouse: &State<Client>,
+    db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
     list_logs(
+        caller,
         page,
         per_page,
         platform_id,
@@ -385,12 +407,14 @@ pub async fn list_org_logs(
         end_time,
         search,
         clickhouse,
+        db_manager,
     ).await
 }
 
 // App routes
 #[get("/apps/<app_id>/logs?<page>&<per_page>&<platform_id>&<org_id>&<level>&<start_time>&<end_time>&<search>")]
 pub async fn list_app_logs(
+    caller: Caller,
     app_id: String,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -401,8 +425,10 @@ pub async fn list_app_logs(
     end_time: Option<String>,
     search: Option<String>,
     clickhouse: &State<Client>,
+    db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
     list_logs(
+        caller,
         page,
         per_page,
         platform_id,
@@ -414,12 +440,14 @@ pub async fn list_app_logs(
         end_time,
         search,
         clickhouse,
+        db_manager,
     ).await
 }
 
 // Instance routes
 #[get("/instances/<instance_id>/logs?<page>&<per_page>&<platform_id>&<org_id>&<app_id>&<level>&<start_time>&<end_time>&<search>")]
 pub async fn list_instance_logs(
+    caller: Caller,
     instance_id: String,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -431,8 +459,10 @@ pub async fn list_instance_logs(
     end_time: Option<String>,
     search: Option<String>,
     clickhouse: &State<Client>,
+    db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
     list_logs(
+        caller,
         page,
         per_page,
         platform_id,
@@ -444,12 +474,14 @@ pub async fn list_instance_logs(
         end_time,
         search,
         clickhouse,
+        db_manager,
     ).await
 }
 
 // Efficient bulk log insertion - using multiple rows approach instead of tuples
 #[post("/logs", format = "json", data = "<log_batch>")]
 pub async fn insert_logs(
+    _auth: Require<LogsWrite>,
     log_batch: Json<BulkLogInsert>,
     clickhouse: &State<Client>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
@@ -1,9 +1,11 @@
 use crate::schemas::v1::db::queries::{self as db};
 use rocket::{get, post, State};
 use sqlx::MySql;
+use super::rbac::{Require, MetadataRead, MetadataWrite};
 
 #[get("/meta/<key>")]
 pub async fn get_meta_value(
+    _auth: Require<MetadataRead>,
     pool: &State<sqlx::Pool<MySql>>,
     key: String,
 ) -> (rocket::http::Status, String) {
@@ -16,6 +18,7 @@ pub async fn get_meta_value(
 
 #[post("/meta/<key>", format = "json", data = "<value>")]
 pub async fn set_meta_value(
+    _auth: Require<MetadataWrite>,
     pool: &State<sqlx::Pool<MySql>>,
     key: String,
     value: String,
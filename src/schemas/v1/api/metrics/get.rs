@@ -5,9 +5,12 @@ use rocket::{get, http::Status, serde::json::{json, Json, Value}, State};
 
 use libomni::types::db::v1 as types;
 use types::metrics::Metric;
+use super::super::rbac::{require_in_scope, Caller, MetricsRead, RequestScope, Require};
+use super::super::instances::get::authorize_instance;
 
 #[get("/platform/<platform_id>/metrics/<instance_id>")]
 pub async fn get_metrics_by_app_id(
+    caller: Caller,
     platform_id: i64,
     instance_id: Option<i64>,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -40,6 +43,18 @@ pub async fn get_metrics_by_app_id(
         }
     };
 
+    match instance_id {
+        Some(instance_id) => {
+            authorize_instance::<MetricsRead>(db_manager, &caller, &pool, platform_id, instance_id).await?;
+        }
+        None => {
+            require_in_scope::<MetricsRead>(db_manager, &caller, RequestScope {
+                platform_id: Some(platform_id),
+                ..Default::default()
+            }).await?;
+        }
+    }
+
     let instance_id = instance_id.or(Some(0)); // Set to 0 (or null equivalent) if blank
     
     match db::metrics::get_metrics_by_app_id(&pool, instance_id).await {
@@ -58,6 +73,7 @@ pub async fn get_metrics_by_app_id(
 
 #[get("/platform/<platform_id>/metrics")]
 pub async fn get_metrics(
+    _auth: Require<MetricsRead>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
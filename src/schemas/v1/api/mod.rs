@@ -7,7 +7,7 @@
 // It includes all the necessary routes for various functionalities such as
 // apps, alerts, notifications, instances, users, permissions, metadata,
 // audit logs, builds, regions, providers, workers, metrics, storage,
-// cost, deployments, and logging.
+// cost, deployments, pipelines, and logging.
 //
 // Each module corresponds to a specific functionality and contains the
 // implementation of the routes related to that functionality.
@@ -32,6 +32,7 @@
 use rocket::routes;
 
 pub mod alerts;
+pub mod api_keys;
 pub mod apps;
 pub mod audit_log;
 pub mod builds;
@@ -43,14 +44,22 @@ pub mod instances;
 pub mod metadata;
 pub mod metrics;
 pub mod notifications;
+pub mod orgs;
 pub mod permissions;
+pub mod pipelines;
 pub mod deployments;
 pub mod index;
 pub mod logging;
 pub mod providers;
+pub mod rbac;
 pub mod regions;
+pub mod roles;
+pub mod spaces;
+pub mod sso;
 pub mod storage;
+pub mod tasks;
 pub mod users;
+pub mod webhooks;
 pub mod workers;
 pub mod platforms;
 
@@ -60,7 +69,11 @@ pub fn routes() -> Vec<rocket::Route> {
         apps::update_app,     apps::get_app,    apps::stop_app,      apps::list_apps,
         apps::start_app,      apps::scale_app,  apps::count_apps,    apps::create_app,
         apps::create_release, apps::delete_app, apps::get_app_stats, apps::list_instances,
-        apps::get_app_with_instances,
+        apps::get_app_with_instances, apps::move_app_to_space, apps::list_app_events,
+        apps::get_app_scheduling, apps::update_app_scheduling,
+        apps::list_autoscaling_rules, apps::get_autoscaling_rule, apps::create_autoscaling_rule,
+        apps::update_autoscaling_rule, apps::delete_autoscaling_rule, apps::set_app_autoscaling,
+        apps::list_autoscaling_decisions,
 
         // alerts
         alerts::list_alerts,         alerts::get_alert,                     alerts::create_alert,
@@ -78,7 +91,7 @@ pub fn routes() -> Vec<rocket::Route> {
         notifications::acknowledge_notification,       notifications::get_all_user_notifications_with_count,
 
         // Instances
-        instances::list_instances_by_region, instances::count_instances, instances::get_instance,
+        instances::list_instances_by_region, instances::count_instances, instances::get_instance, instances::get_instance_logs,
         // deploy
         deploy::deploy_permissions,
 
@@ -86,25 +99,110 @@ pub fn routes() -> Vec<rocket::Route> {
         users::handle_register,         users::handle_login, users::update_profile,   users::get_current_user,
         users::change_password,         users::logout,       users::get_user_profile, users::list_user_sessions,
         users::invalidate_user_session, users::list_users,
+        rbac::get_my_permissions,
+
+        // Single sign-on
+        sso::oidc_login, sso::oidc_callback,
 
         // permissions
         permissions::list_permission,   permissions::get_permission_by_id,
         permissions::create_permission, permissions::delete_permission,
-        // update_permission,
+        permissions::update_permission,
+
+        // Organizations
+        orgs::list_orgs,              orgs::get_org,
+        orgs::create_org,             orgs::update_org,
+        orgs::delete_org,             orgs::list_org_members,
+        orgs::update_org_member,      orgs::remove_org_member,
+        orgs::list_org_invitations,   orgs::create_org_invitation,
+        orgs::revoke_org_invitation,  orgs::accept_org_invitation,
+        orgs::transfer_org_ownership,
+
+        // Spaces
+        spaces::list_spaces,          spaces::get_space,
+        spaces::create_space,         spaces::update_space,
+        spaces::delete_space,         spaces::archive_space,
+        spaces::restore_space,        spaces::list_space_apps,
+        spaces::list_space_role_bindings,
+
+        // Roles
+        roles::list_roles,            roles::get_role,
+        roles::create_role,           roles::update_role,
+        roles::delete_role,           roles::list_role_permissions,
+        roles::add_role_permission,   roles::remove_role_permission,
+        roles::list_role_bindings,    roles::create_role_binding,
+        roles::delete_role_binding,
 
         // Metadata
         metadata::get_meta_value,
         metadata::set_meta_value,
 
+        // API keys
+        api_keys::create_api_key, api_keys::list_api_keys,
+        api_keys::rotate_api_key, api_keys::revoke_api_key,
+        api_keys::get_current_api_key,
+
         // Audit log
         audit_log::create_audit_log,
         audit_log::list_audit_logs,
         audit_log::list_audit_logs_for_app,
+        audit_log::search_audit_logs,
+        audit_log::count_audit_logs,
+        audit_log::verify_audit_log,
+        audit_log::export_audit_logs,
+        audit_log::get_audit_retention,
+        audit_log::update_audit_retention,
+        audit_log::archive_audit_logs,
+        audit_log::list_audit_log_archives,
 
         //Builds
         builds::list_builds,
         builds::list_builds_for_app,
         builds::get_build,
+        builds::download_build_artifact,
+        builds::get_artifact_retention,
+        builds::set_artifact_retention,
+        builds::delete_artifact_retention,
+        builds::lease_build,
+        builds::heartbeat_build,
+        builds::complete_build,
+        builds::fail_build,
+        builds::cancel_build,
+        builds::append_build_logs,
+        builds::get_build_logs,
+
+        // Webhooks
+        webhooks::get_app_webhook,
+        webhooks::set_app_webhook,
+        webhooks::rotate_app_webhook_secret,
+        webhooks::delete_app_webhook,
+        webhooks::receive_app_webhook,
+
+        // Pipelines
+        pipelines::list_pipelines,
+        pipelines::get_pipeline,
+        pipelines::create_pipeline,
+        pipelines::update_pipeline,
+        pipelines::delete_pipeline,
+        pipelines::start_pipeline_run,
+        pipelines::list_pipeline_runs,
+        pipelines::get_pipeline_run,
+        pipelines::cancel_pipeline_run,
+        pipelines::retry_pipeline_run,
+        pipelines::approve_pipeline_stage,
+        pipelines::reject_pipeline_stage,
+        pipelines::get_pipeline_run_logs,
+        // Tasks
+        tasks::run_task,
+        tasks::list_tasks,
+        tasks::get_task,
+        tasks::cancel_task,
+        tasks::get_task_logs,
+        tasks::list_task_schedules,
+        tasks::get_task_schedule,
+        tasks::create_task_schedule,
+        tasks::update_task_schedule,
+        tasks::delete_task_schedule,
 
         // Regions
         regions::list_regions,
@@ -129,6 +227,9 @@ pub fn routes() -> Vec<rocket::Route> {
 
         // Workers
         workers::list_workers, workers::get_worker_by_id,
+        workers::create_bootstrap_token, workers::list_bootstrap_tokens, workers::revoke_bootstrap_token,
+        workers::register_worker, workers::worker_heartbeat,
+        workers::cordon_worker, workers::uncordon_worker, workers::drain_worker, workers::decommission_worker,
 
         // Metrics
         metrics::get_metrics, metrics::get_metrics_by_app_id,
@@ -158,7 +259,15 @@ pub fn routes() -> Vec<rocket::Route> {
 
         deployments::list_deployments,     deployments::count_deployments, deployments::get_deployment,
         deployments::list_app_deployments, deployments::create_deployment, deployments::update_deployment_status,
-        deployments::delete_deployment,
+        deployments::delete_deployment,    deployments::get_deployment_progress, deployments::pause_deployment,
+        deployments::resume_deployment,    deployments::abort_deployment, deployments::promote_deployment,
+        deployments::rollback_deployment,  deployments::list_app_rollbacks, deployments::get_rollback,
+        deployments::list_deployment_events, deployments::stream_deployment_events,
+        deployments::list_deployment_approvals, deployments::approve_deployment, deployments::reject_deployment,
+        deployments::get_app_approval_policy, deployments::set_app_approval_policy, deployments::delete_app_approval_policy,
+        deployments::get_space_approval_policy, deployments::set_space_approval_policy, deployments::delete_space_approval_policy,
+        deployments::list_freeze_windows, deployments::create_freeze_window,
+        deployments::update_freeze_window, deployments::delete_freeze_window,
 
         // Logging
         logging::list_logs,     logging::list_platform_logs, logging::list_org_logs,
@@ -8,10 +8,12 @@ use rocket::{post, State};
 
 use libomni::types::db::v1 as types;
 use types::user::User;
+use super::super::rbac::{Require, NotificationsWrite};
 
 /// Acknowledge a notification
 #[post("/platform/<platform_id>/notifications/acknowledge", format = "json", data = "<ack_data>")]
 pub async fn acknowledge_notification(
+    _auth: Require<NotificationsWrite>,
     platform_id: i64,
     ack_data: Json<AcknowledgeNotificationRequest>,
     user: User, // For authentication
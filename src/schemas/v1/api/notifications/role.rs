@@ -8,10 +8,12 @@ use rocket::{get, post, State};
 
 use libomni::types::db::v1 as types;
 use types::user::User;
+use super::super::rbac::{Require, NotificationsRead, NotificationsWrite};
 
 /// Get a paginated list of role notifications
 #[get("/platform/<platform_id>/notifications/role/<role_id>?<page>&<per_page>")]
 pub async fn list_role_notifications(
+    _auth: Require<NotificationsRead>,
     platform_id: i64,
     role_id: i64,
     page: Option<i64>,
@@ -94,6 +96,7 @@ pub async fn list_role_notifications(
 /// Create a new notification for a role
 #[post("/platform/<platform_id>/notifications/role", format = "json", data = "<notification_data>")]
 pub async fn create_role_notification(
+    _auth: Require<NotificationsWrite>,
     platform_id: i64,
     notification_data: Json<CreateRoleNotificationRequest>,
     user: User, // For authentication
@@ -8,10 +8,12 @@ use rocket::{delete, get, post, put, State};
 
 use libomni::types::db::v1 as types;
 use types::user::User;
+use super::super::rbac::{Require, NotificationsRead, NotificationsWrite};
 
 /// Get a paginated list of notifications for a user
 #[get("/platform/<platform_id>/notifications/user/<user_id>?<page>&<per_page>&<include_read>")]
 pub async fn list_user_notifications(
+    _auth: Require<NotificationsRead>,
     platform_id: i64,
     user_id: i64,
     page: Option<i64>,
@@ -95,6 +97,7 @@ pub async fn list_user_notifications(
 /// Count unread notifications for a user (for badges)
 #[get("/platform/<platform_id>/notifications/user/count/<user_id>")]
 pub async fn count_unread_user_notifications(
+    _auth: Require<NotificationsRead>,
     platform_id: i64,
     user_id: i64,
     user: User, // For authentication
@@ -161,6 +164,7 @@ pub async fn count_unread_user_notifications(
 /// Get a specific notification by ID
 #[get("/platform/<platform_id>/notifications/<id>")]
 pub async fn get_user_notification_by_id(
+    _auth: Require<NotificationsRead>,
     platform_id: i64,
     id: i64,
     user: User, // For authentication
@@ -239,6 +243,7 @@ pub async fn get_user_notification_by_id(
 /// Create a new notification for a user
 #[post("/platform/<platform_id>/notifications/user", format = "json", data = "<notification_data>")]
 pub async fn create_user_notification(
+    _auth: Require<NotificationsWrite>,
     platform_id: i64,
     notification_data: Json<CreateUserNotificationRequest>,
     user: User, // For authentication
@@ -310,6 +315,7 @@ pub async fn create_user_notification(
 /// Mark a notification as read
 #[put("/platform/<platform_id>/notifications/<id>/read")]
 pub async fn mark_user_notification_as_read(
+    _auth: Require<NotificationsWrite>,
     platform_id: i64,
     id: i64,
     user: User, // For authentication
@@ -407,6 +413,7 @@ pub async fn mark_user_notification_as_read(
 /// Mark all notifications for a user as read
 #[put("/platform/<platform_id>/notifications/user/<user_id>/read-all")]
 pub async fn mark_all_user_notifications_as_read(
+    _auth: Require<NotificationsWrite>,
     platform_id: i64,
     user_id: i64,
     user: User, // For authentication
@@ -475,6 +482,7 @@ pub async fn mark_all_user_notifications_as_read(
 /// Delete a notification
 #[delete("/platform/<platform_id>/notifications/<id>")]
 pub async fn delete_user_notification(
+    _auth: Require<NotificationsWrite>,
     platform_id: i64,
     id: i64,
     user: User, // For authentication
@@ -571,6 +579,7 @@ pub async fn delete_user_notification(
 /// Delete all read notifications for a user
 #[delete("/platform/<platform_id>/notifications/user/<user_id>/read")]
 pub async fn delete_read_user_notifications(
+    _auth: Require<NotificationsWrite>,
     platform_id: i64,
     user_id: i64,
     user: User, // For authentication
@@ -640,6 +649,7 @@ pub async fn delete_read_user_notifications(
 /// Get all notifications for a user including role notifications
 #[get("/platform/<platform_id>/notifications/user/<user_id>/all?<page>&<per_page>")]
 pub async fn get_all_user_notifications_with_count(
+    _auth: Require<NotificationsRead>,
     platform_id: i64,
     user_id: i64,
     page: Option<i64>,
@@ -0,0 +1,70 @@
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use sqlx::{MySql, Pool};
+
+use super::super::super::db::queries as db;
+use super::super::rbac::Authorization;
+use super::super::roles::access::ensure_can_grant;
+use db::org::{MembershipError, MEMBER_ROLES};
+
+/// Rejects unknown membership roles with `400 Bad Request`.
+pub fn validate_member_role(role: &str) -> Result<(), (Status, Json<Value>)> {
+    if !MEMBER_ROLES.contains(&role) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid role",
+                "message": format!("role must be one of: {}", MEMBER_ROLES.join(", "))
+            }))
+        ));
+    }
+    Ok(())
+}
+
+/// Checks that the caller holds every permission a membership role grants,
+/// so that members can only be given (or stripped of) roles up to the
+/// caller's own level.
+pub async fn ensure_can_assign_member_role(
+    pool: &Pool<MySql>,
+    authorization: &Authorization,
+    role: &str,
+) -> Result<(), (Status, Json<Value>)> {
+    let system_role = db::org::system_role_for_member_role(role);
+    let permissions = match db::role::get_system_role_by_name(pool, system_role).await {
+        Ok(role) => db::permission::get_role_permissions(pool, role.id).await,
+        Err(e) => Err(e),
+    };
+
+    match permissions {
+        Ok(permissions) => {
+            let names: Vec<String> = permissions.into_iter().map(|p| p.name).collect();
+            ensure_can_grant(authorization, &names)
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve role permissions"
+            }))
+        )),
+    }
+}
+
+/// Converts a failed membership change into a response, mapping the
+/// business rule violations in `MembershipError` to client errors.
+pub fn membership_error(e: anyhow::Error, error: &str) -> (Status, Json<Value>) {
+    let status = match e.downcast_ref::<MembershipError>() {
+        Some(MembershipError::LastOwner) | Some(MembershipError::AlreadyMember) => Status::Conflict,
+        Some(MembershipError::NotMember) => Status::NotFound,
+        Some(MembershipError::InvitationUnavailable) => Status::Gone,
+        None => Status::InternalServerError,
+    };
+
+    (
+        status,
+        Json(json!({
+            "error": error,
+            "message": e.to_string()
+        }))
+    )
+}
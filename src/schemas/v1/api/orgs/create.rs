@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::types::CreateOrgRequest;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+use super::super::rbac::{Require, OrgsWrite};
+
+use libomni::types::db::v1 as types;
+use types::org::Org;
+
+/// Create a new organization.
+///
+/// The caller (or the user named by `owner_id`) becomes the organization's
+/// first owner.
+#[post("/platform/<platform_id>/orgs", format = "json", data = "<request>")]
+pub async fn create_org(
+    auth: Require<OrgsWrite>,
+    platform_id: i64,
+    request: Json<CreateOrgRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Org>, (Status, Json<Value>)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "Organization name must not be empty"
+            }))
+        ));
+    }
+
+    let owner_id = request.owner_id.unwrap_or(auth.user_id());
+    if owner_id != auth.user_id() {
+        if db::user::get_user_by_id(db_manager.get_main_pool(), owner_id).await.is_err() {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "User not found",
+                    "message": format!("User with ID {} does not exist", owner_id)
+                }))
+            ));
+        }
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::org::create_org_with_owner(
+        &pool,
+        name,
+        request.display_name.as_deref(),
+        request.description.as_deref(),
+        owner_id,
+    ).await {
+        Ok(org) => Ok(Json(org)),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to create organization",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,90 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, State};
+use super::super::rbac::{Require, OrgsWrite};
+
+/// Delete an organization.
+///
+/// Organizations that still own applications cannot be deleted; the
+/// applications must be deleted first.
+#[delete("/platform/<platform_id>/orgs/<org_id>")]
+pub async fn delete_org(
+    _auth: Require<OrgsWrite>,
+    platform_id: i64,
+    org_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::org::get_org_by_id(&pool, org_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Organization not found",
+                "message": format!("Organization with ID {} does not exist", org_id)
+            }))
+        ));
+    }
+
+    match db::app::get_apps_by_org(&pool, org_id).await {
+        Ok(apps) if apps.is_empty() => {}
+        Ok(apps) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Organization not empty",
+                    "message": format!("Organization still owns {} application(s)", apps.len())
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retrieve organization applications"
+                }))
+            ));
+        }
+    }
+
+    match db::org::delete_org(&pool, org_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to delete organization",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
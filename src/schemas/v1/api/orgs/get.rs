@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use super::super::rbac::{Require, OrgsRead};
+
+use libomni::types::db::v1 as types;
+use types::org::Org;
+
+/// Get an organization by ID.
+#[get("/platform/<platform_id>/orgs/<org_id>")]
+pub async fn get_org(
+    _auth: Require<OrgsRead>,
+    platform_id: i64,
+    org_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Org>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::org::get_org_by_id(&pool, org_id).await {
+        Ok(org) => Ok(Json(org)),
+        Err(_) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Organization not found",
+                "message": format!("Organization with ID {} does not exist", org_id)
+            }))
+        )),
+    }
+}
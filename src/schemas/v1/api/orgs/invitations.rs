@@ -0,0 +1,365 @@
+use std::sync::Arc;
+use chrono::{Duration, Utc};
+use rand::rngs::OsRng;
+use rand::TryRngCore;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::api_keys::token::hash_secret;
+use super::access::{ensure_can_assign_member_role, membership_error, validate_member_role};
+use super::types::{AcceptInvitationRequest, CreateInvitationRequest, InvitationSecretResponse};
+use db::org::{OrgInvitation, OrgMember};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, State};
+use super::super::rbac::{Caller, MembersRead, MembersWrite, Require};
+
+/// Default lifetime of an invitation.
+const DEFAULT_EXPIRY_HOURS: i64 = 7 * 24;
+
+/// Longest lifetime an invitation can be given.
+const MAX_EXPIRY_HOURS: i64 = 30 * 24;
+
+/// Generates a random invitation token, returning it with its hash.
+fn generate_invitation_token() -> anyhow::Result<(String, String)> {
+    let mut bytes = [0u8; 32];
+    OsRng.try_fill_bytes(&mut bytes)?;
+    let token = hex::encode(bytes);
+    let hash = hash_secret(&token);
+    Ok((token, hash))
+}
+
+/// List the invitations of an organization.
+///
+/// Only pending invitations are listed unless `include_closed` is set.
+#[get("/platform/<platform_id>/orgs/<org_id>/invitations?<include_closed>")]
+pub async fn list_org_invitations(
+    _auth: Require<MembersRead>,
+    platform_id: i64,
+    org_id: i64,
+    include_closed: Option<bool>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::org::list_org_invitations(&pool, org_id, include_closed.unwrap_or(false)).await {
+        Ok(invitations) => Ok(Json(json!({ "invitations": invitations }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve invitations"
+            }))
+        )),
+    }
+}
+
+/// Invite someone to an organization by email.
+///
+/// The invitation token is only part of this response and has to be sent
+/// to the invitee, who accepts it while signed in with the invited email
+/// address. The caller must hold every permission of the offered role.
+#[post("/platform/<platform_id>/orgs/<org_id>/invitations", format = "json", data = "<request>")]
+pub async fn create_org_invitation(
+    auth: Require<MembersWrite>,
+    platform_id: i64,
+    org_id: i64,
+    request: Json<CreateInvitationRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<InvitationSecretResponse>, (Status, Json<Value>)> {
+    let email = request.email.trim().to_lowercase();
+    if email.is_empty() || !email.contains('@') {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "A valid email address is required"
+            }))
+        ));
+    }
+
+    let role = request.role.as_deref().unwrap_or("member");
+    validate_member_role(role)?;
+
+    let expires_in_hours = request.expires_in_hours.unwrap_or(DEFAULT_EXPIRY_HOURS);
+    if expires_in_hours <= 0 || expires_in_hours > MAX_EXPIRY_HOURS {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("expires_in_hours must be between 1 and {}", MAX_EXPIRY_HOURS)
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::org::get_org_by_id(&pool, org_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Organization not found",
+                "message": format!("Organization with ID {} does not exist", org_id)
+            }))
+        ));
+    }
+
+    ensure_can_assign_member_role(&pool, &auth.authorization, role).await?;
+
+    if let Ok(user) = db::user::get_user_by_email(db_manager.get_main_pool(), &email).await {
+        if let Ok(Some(_)) = db::org::get_org_member_role(&pool, org_id, user.id).await {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Already a member",
+                    "message": format!("{} is already a member of this organization", email)
+                }))
+            ));
+        }
+    }
+
+    match db::org::has_pending_org_invitation(&pool, org_id, &email).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Invitation exists",
+                    "message": format!("{} already has a pending invitation", email)
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to check for pending invitations"
+                }))
+            ));
+        }
+    }
+
+    let (token, token_hash) = match generate_invitation_token() {
+        Ok(generated) => generated,
+        Err(e) => {
+            log::error!("Error generating invitation token: {}", e);
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Token generation failed",
+                    "message": "Failed to generate an invitation token"
+                }))
+            ));
+        }
+    };
+
+    let expires_at = Utc::now() + Duration::hours(expires_in_hours);
+    match db::org::create_org_invitation(&pool, org_id, &email, role, &token_hash, auth.user_id(), expires_at).await {
+        Ok(invitation) => Ok(Json(InvitationSecretResponse { invitation, token })),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to create invitation",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
+
+/// Revoke a pending invitation.
+#[delete("/platform/<platform_id>/orgs/<org_id>/invitations/<invitation_id>")]
+pub async fn revoke_org_invitation(
+    _auth: Require<MembersWrite>,
+    platform_id: i64,
+    org_id: i64,
+    invitation_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let invitation: OrgInvitation = match db::org::get_org_invitation_by_id(&pool, invitation_id).await {
+        Ok(invitation) if invitation.org_id == org_id => invitation,
+        _ => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Invitation not found",
+                    "message": format!("Invitation with ID {} does not exist", invitation_id)
+                }))
+            ));
+        }
+    };
+
+    match db::org::revoke_org_invitation(&pool, invitation.id).await {
+        Ok(true) => Ok(Json(json!({ "status": "revoked" }))),
+        Ok(false) => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Invitation closed",
+                "message": format!("Invitation has already been {}", invitation.status)
+            }))
+        )),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to revoke invitation",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
+
+/// Accept an invitation, joining the organization it was sent for.
+///
+/// Invitations can only be accepted by a signed-in user whose email address
+/// matches the invited one; API keys cannot accept invitations.
+#[post("/platform/<platform_id>/invitations/accept", format = "json", data = "<request>")]
+pub async fn accept_org_invitation(
+    caller: Caller,
+    platform_id: i64,
+    request: Json<AcceptInvitationRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<OrgMember>, (Status, Json<Value>)> {
+    if caller.api_key.is_some() {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": "Invitations cannot be accepted with an API key"
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let invitation = match db::org::get_org_invitation_by_token_hash(&pool, &hash_secret(request.token.trim())).await {
+        Ok(Some(invitation)) if invitation.email.eq_ignore_ascii_case(&caller.user.email) => invitation,
+        Ok(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Invitation not found",
+                    "message": "No invitation matches this token for your account"
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retrieve invitation"
+                }))
+            ));
+        }
+    };
+
+    match db::org::accept_org_invitation(&pool, invitation.id, caller.user.id).await {
+        Ok(member) => Ok(Json(member)),
+        Err(e) => Err(membership_error(e, "Failed to accept invitation")),
+    }
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::rbac::scope::resolve;
+use super::super::rbac::{Caller, OrgsRead, Permission, RequestScope};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+
+/// List organizations.
+///
+/// Callers holding `orgs:read` platform-wide see every organization on the
+/// platform; everybody else sees the organizations they are a member of.
+#[get("/platform/<platform_id>/orgs")]
+pub async fn list_orgs(
+    platform_id: i64,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let scope = RequestScope { platform_id: Some(platform_id), ..Default::default() };
+    let see_all = match resolve(db_manager, caller.clone(), scope).await {
+        Ok(authorization) => authorization.allows(OrgsRead::NAME),
+        Err(status) => {
+            return Err((
+                status,
+                Json(json!({
+                    "error": "Authorization error",
+                    "message": "Failed to resolve permissions"
+                }))
+            ));
+        }
+    };
+
+    let orgs = if see_all {
+        db::org::list_orgs(&pool).await
+    } else {
+        db::org::list_orgs_for_user(&pool, caller.user.id).await
+    };
+
+    match orgs {
+        Ok(orgs) => Ok(Json(json!({ "orgs": orgs }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve organizations"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,195 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::{ensure_can_assign_member_role, membership_error, validate_member_role};
+use super::types::UpdateMemberRequest;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, put, State};
+use super::super::rbac::{require_in_scope, Caller, MembersRead, MembersWrite, Require, RequestScope};
+
+/// List the members of an organization.
+#[get("/platform/<platform_id>/orgs/<org_id>/members")]
+pub async fn list_org_members(
+    _auth: Require<MembersRead>,
+    platform_id: i64,
+    org_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::org::list_org_members(&pool, org_id).await {
+        Ok(members) => Ok(Json(json!({ "members": members }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve organization members"
+            }))
+        )),
+    }
+}
+
+/// Change the role of a member.
+///
+/// The caller must hold every permission of both the member's current and
+/// new role, so admins cannot promote anybody to owner or demote owners.
+/// The last owner of an organization cannot be demoted.
+#[put("/platform/<platform_id>/orgs/<org_id>/members/<user_id>", format = "json", data = "<request>")]
+pub async fn update_org_member(
+    auth: Require<MembersWrite>,
+    platform_id: i64,
+    org_id: i64,
+    user_id: i64,
+    request: Json<UpdateMemberRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    validate_member_role(&request.role)?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let current_role = match db::org::get_org_member_role(&pool, org_id, user_id).await {
+        Ok(Some(role)) => role,
+        Ok(None) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Member not found",
+                    "message": format!("User {} is not a member of organization {}", user_id, org_id)
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to check organization membership"
+                }))
+            ));
+        }
+    };
+
+    ensure_can_assign_member_role(&pool, &auth.authorization, &current_role).await?;
+    ensure_can_assign_member_role(&pool, &auth.authorization, &request.role).await?;
+
+    match db::org::update_org_member_role(&pool, org_id, user_id, &request.role).await {
+        Ok(_) => Ok(Json(json!({
+            "org_id": org_id,
+            "user_id": user_id,
+            "role": request.role
+        }))),
+        Err(e) => Err(membership_error(e, "Failed to update member")),
+    }
+}
+
+/// Remove a member from an organization.
+///
+/// Members may always remove themselves. Removing somebody else requires
+/// `members:write` and every permission of the member's role. The last
+/// owner of an organization cannot be removed.
+#[delete("/platform/<platform_id>/orgs/<org_id>/members/<user_id>")]
+pub async fn remove_org_member(
+    caller: Caller,
+    platform_id: i64,
+    org_id: i64,
+    user_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if user_id != caller.user.id {
+        let authorization = require_in_scope::<MembersWrite>(db_manager, &caller, RequestScope {
+            platform_id: Some(platform_id),
+            org_id: Some(org_id),
+            ..Default::default()
+        }).await?;
+
+        if let Ok(Some(role)) = db::org::get_org_member_role(&pool, org_id, user_id).await {
+            ensure_can_assign_member_role(&pool, &authorization, &role).await?;
+        }
+    }
+
+    match db::org::remove_org_member(&pool, org_id, user_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(e) => Err(membership_error(e, "Failed to remove member")),
+    }
+}
@@ -0,0 +1,35 @@
+//! Organization management module for handling organizations and their members.
+//!
+//! This module provides a REST API for managing organizations, including:
+//! - Listing, creating, updating and deleting organizations
+//! - Listing members and changing or removing their roles
+//! - Email invitations with an accept flow and expiry
+//! - Transferring ownership
+//!
+//! Membership roles (`owner`, `admin`, `billing`, `member`, `guest`) are
+//! backed by the matching system role binding in the organization's scope,
+//! so changing a member's role changes what they may do. An organization
+//! always keeps at least one owner.
+
+// Import and re-export all modules
+pub mod types;
+pub mod access;
+pub mod list;
+pub mod get;
+pub mod create;
+pub mod update;
+pub mod delete;
+pub mod members;
+pub mod invitations;
+pub mod transfer;
+
+// Re-export all route functions
+pub use types::*;
+pub use list::list_orgs;
+pub use get::get_org;
+pub use create::create_org;
+pub use update::update_org;
+pub use delete::delete_org;
+pub use members::{list_org_members, remove_org_member, update_org_member};
+pub use invitations::{accept_org_invitation, create_org_invitation, list_org_invitations, revoke_org_invitation};
+pub use transfer::transfer_org_ownership;
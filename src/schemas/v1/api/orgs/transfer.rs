@@ -0,0 +1,81 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::membership_error;
+use super::types::TransferOwnershipRequest;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+use super::super::rbac::{Require, OrgsWrite};
+
+/// Transfer ownership of an organization to another member.
+///
+/// Only an owner can transfer ownership. The new owner must already be a
+/// member; the caller is demoted to `admin`.
+#[post("/platform/<platform_id>/orgs/<org_id>/transfer_ownership", format = "json", data = "<request>")]
+pub async fn transfer_org_ownership(
+    auth: Require<OrgsWrite>,
+    platform_id: i64,
+    org_id: i64,
+    request: Json<TransferOwnershipRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::org::get_org_member_role(&pool, org_id, auth.user_id()).await {
+        Ok(Some(role)) if role == "owner" => {}
+        Ok(_) => {
+            return Err((
+                Status::Forbidden,
+                Json(json!({
+                    "error": "Forbidden",
+                    "message": "Only an owner can transfer ownership of an organization"
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to check organization membership"
+                }))
+            ));
+        }
+    }
+
+    match db::org::transfer_org_ownership(&pool, org_id, auth.user_id(), request.user_id).await {
+        Ok(_) => Ok(Json(json!({
+            "org_id": org_id,
+            "previous_owner_id": auth.user_id(),
+            "owner_id": request.user_id
+        }))),
+        Err(e) => Err(membership_error(e, "Failed to transfer ownership")),
+    }
+}
@@ -0,0 +1,60 @@
+use serde::{Deserialize, Serialize};
+
+use super::super::super::db::queries as db;
+use db::org::OrgInvitation;
+
+/// Request body for creating an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateOrgRequest {
+    pub name: String,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    /// User that becomes the first owner; defaults to the caller
+    pub owner_id: Option<i64>,
+}
+
+/// Request body for updating an organization. Omitted fields are left unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateOrgRequest {
+    pub name: Option<String>,
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Request body for changing a member's role.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateMemberRequest {
+    pub role: String,
+}
+
+/// Request body for inviting someone to an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    /// Membership role granted on acceptance; defaults to `member`
+    pub role: Option<String>,
+    /// Hours until the invitation expires; defaults to one week
+    pub expires_in_hours: Option<i64>,
+}
+
+/// Request body for accepting an invitation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+}
+
+/// Request body for transferring ownership of an organization.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferOwnershipRequest {
+    pub user_id: i64,
+}
+
+/// Response returned when an invitation is created.
+///
+/// `token` must be delivered to the invitee (for example by email); it is
+/// not stored and cannot be retrieved again.
+#[derive(Debug, Clone, Serialize)]
+pub struct InvitationSecretResponse {
+    pub invitation: OrgInvitation,
+    pub token: String,
+}
@@ -0,0 +1,96 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::types::UpdateOrgRequest;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{put, State};
+use super::super::rbac::{Require, OrgsWrite};
+use super::super::audit_log::AuditTrail;
+
+use libomni::types::db::v1 as types;
+use types::org::Org;
+
+/// Update an organization's name, display name or description.
+#[put("/platform/<platform_id>/orgs/<org_id>", format = "json", data = "<request>")]
+pub async fn update_org(
+    _auth: Require<OrgsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    org_id: i64,
+    request: Json<UpdateOrgRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Org>, (Status, Json<Value>)> {
+    let name = request.name.as_deref().map(str::trim);
+    if name.map_or(false, str::is_empty) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "Organization name must not be empty"
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    trail.resource("org", org_id);
+    match db::org::get_org_by_id(&pool, org_id).await {
+        Ok(existing) => trail.before(&existing),
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Organization not found",
+                    "message": format!("Organization with ID {} does not exist", org_id)
+                }))
+            ));
+        }
+    }
+
+    match db::org::update_org(
+        &pool,
+        org_id,
+        name,
+        request.display_name.as_deref(),
+        request.description.as_deref(),
+    ).await {
+        Ok(org) => {
+            trail.after(&org);
+            Ok(Json(org))
+        }
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to update organization",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -4,9 +4,11 @@ use sqlx::MySql;
 
 use libomni::types::db::v1 as types;
 use types::permission::Permission;
+use super::super::rbac::{Require, PermissionsWrite};
 
 #[post("/permissions", format = "json", data = "<permission>")]
 pub async fn create_permission(
+    _auth: Require<PermissionsWrite>,
     pool: &State<sqlx::Pool<MySql>>,
     permission: Json<Permission>,
 ) -> Json<Permission> {
@@ -1,9 +1,11 @@
 use crate::schemas::v1::db::queries::{self as db};
 use rocket::{delete, State};
 use sqlx::MySql;
+use super::super::rbac::{Require, PermissionsWrite};
 
 #[delete("/permissions/<id>")]
 pub async fn delete_permission(
+    _auth: Require<PermissionsWrite>,
     pool: &State<sqlx::Pool<MySql>>,
     id: i64,
 ) -> (rocket::http::Status, String) {
@@ -4,9 +4,11 @@ use sqlx::MySql;
 
 use libomni::types::db::v1 as types;
 use types::permission::Permission;
+use super::super::rbac::{Require, PermissionsRead};
 
 #[get("/permissions/<id>")]
 pub async fn get_permission_by_id(
+    _auth: Require<PermissionsRead>,
     pool: &State<sqlx::Pool<MySql>>,
     id: i64,
 ) -> Json<Permission> {
@@ -4,9 +4,10 @@ use sqlx::MySql;
 
 use libomni::types::db::v1 as types;
 use types::permission::Permission;
+use super::super::rbac::{Require, PermissionsRead};
 
 #[get("/permissions")]
-pub async fn list_permission(pool: &State<sqlx::Pool<MySql>>) -> Json<Vec<Permission>> {
+pub async fn list_permission(_auth: Require<PermissionsRead>, pool: &State<sqlx::Pool<MySql>>) -> Json<Vec<Permission>> {
     let permissions = db::permission::list_permissions(pool).await.unwrap();
 
     Json(permissions)
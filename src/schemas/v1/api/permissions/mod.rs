@@ -4,16 +4,19 @@
 //! - Listing all permissions
 //! - Getting permission details by ID
 //! - Creating new permissions
+//! - Updating permissions
 //! - Deleting permissions
 
 // Import and re-export all modules
 pub mod list;
 pub mod get;
 pub mod create;
+pub mod update;
 pub mod delete;
 
 // Re-export all route functions
 pub use list::list_permission;
 pub use get::get_permission_by_id;
 pub use create::create_permission;
+pub use update::update_permission;
 pub use delete::delete_permission;
\ No newline at end of file
@@ -0,0 +1,43 @@
+use crate::schemas::v1::db::queries::{self as db};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{put, State};
+use serde::{Deserialize, Serialize};
+use sqlx::MySql;
+
+use libomni::types::db::v1 as types;
+use types::permission::Permission;
+use super::super::rbac::{Require, PermissionsWrite};
+
+/// Request body for updating a permission. Omitted fields are left unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdatePermissionRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub resource_type: Option<String>,
+}
+
+#[put("/permissions/<id>", format = "json", data = "<permission>")]
+pub async fn update_permission(
+    _auth: Require<PermissionsWrite>,
+    pool: &State<sqlx::Pool<MySql>>,
+    id: i64,
+    permission: Json<UpdatePermissionRequest>,
+) -> Result<Json<Permission>, (Status, Json<Value>)> {
+    match db::permission::update_permission(
+        pool,
+        id,
+        permission.name.as_deref(),
+        permission.description.as_deref(),
+        permission.resource_type.as_deref(),
+    ).await {
+        Ok(permission) => Ok(Json(permission)),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to update permission",
+                "message": format!("{e:#}")
+            }))
+        )),
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::helpers::tail::{self, LastEventId, Tail, TailStatus};
+use super::super::rbac::{PipelinesRead, Require};
+use super::manage::find_pipeline;
+use super::runs::find_run;
+use db::pipeline_run::PipelineRunLog;
+use rocket::http::Status;
+use rocket::response::stream::EventStream;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, Either, Shutdown, State};
+use sqlx::{MySql, Pool};
+
+/// Most lines returned by a single read.
+const MAX_READ_LIMIT: i64 = 5000;
+
+/// Read the log of a pipeline run, oldest first, optionally only the lines
+/// of the stage with ID `stage_id`.
+///
+/// Lines after the line with ID `after` are returned, at most `limit` of
+/// them; `next_after` continues from the last one. With `follow=true` the
+/// log is streamed as server-sent events instead: every line is sent as a
+/// `log` event whose ID is the line's ID, and an `end` event with the run's
+/// final status closes the stream once the run has finished.
+#[get("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs/<run_id>/logs?<stage_id>&<after>&<limit>&<follow>")]
+pub async fn get_pipeline_run_logs(
+    _auth: Require<PipelinesRead>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    run_id: i64,
+    stage_id: Option<i64>,
+    after: Option<i64>,
+    limit: Option<i64>,
+    follow: Option<bool>,
+    last_event_id: LastEventId,
+    db_manager: &State<Arc<DatabaseManager>>,
+    shutdown: Shutdown,
+) -> Result<Either<Json<Value>, EventStream![]>, (Status, Json<Value>)> {
+    let limit = limit.unwrap_or(500);
+    if !(1..=MAX_READ_LIMIT).contains(&limit) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("limit must be between 1 and {}", MAX_READ_LIMIT)
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_pipeline(&pool, app_id, pipeline_id).await?;
+    let run = find_run(&pool, pipeline_id, run_id).await?;
+
+    if !follow.unwrap_or(false) {
+        let after = after.unwrap_or(0);
+        return match db::pipeline_run::list_run_logs_after(&pool, run_id, stage_id, after, limit).await {
+            Ok(lines) => {
+                let next_after = lines.last().map(|line| line.id).unwrap_or(after);
+                Ok(Either::Left(Json(json!({
+                    "run_id": run_id,
+                    "status": run.status,
+                    "lines": lines,
+                    "next_after": next_after
+                }))))
+            }
+            Err(_) => Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch pipeline run logs"
+                }))
+            )),
+        };
+    }
+
+    let after = last_event_id.0.or(after).unwrap_or(0);
+    Ok(Either::Right(tail::follow(RunTail { pool, pipeline_id, run_id, stage_id }, after, shutdown)))
+}
+
+/// The log of a pipeline run, or of one of its stages, followed until the
+/// run has finished.
+struct RunTail {
+    pool: Pool<MySql>,
+    pipeline_id: i64,
+    run_id: i64,
+    stage_id: Option<i64>,
+}
+
+#[rocket::async_trait]
+impl Tail for RunTail {
+    type Entry = PipelineRunLog;
+
+    const EVENT: &'static str = "log";
+    const RESOURCE: &'static str = "pipeline run";
+    const ENTRIES: &'static str = "logs";
+
+    fn id(&self) -> i64 {
+        self.run_id
+    }
+
+    async fn status(&self) -> anyhow::Result<Option<TailStatus>> {
+        let run = db::pipeline_run::get_run(&self.pool, self.pipeline_id, self.run_id).await?;
+        Ok(run.map(|run| TailStatus {
+            finished: run.status.as_deref() != Some("running"),
+            end: json!({ "run_id": self.run_id, "status": run.status }),
+        }))
+    }
+
+    async fn entries_after(&self, after: i64, limit: i64) -> anyhow::Result<Vec<PipelineRunLog>> {
+        db::pipeline_run::list_run_logs_after(&self.pool, self.run_id, self.stage_id, after, limit).await
+    }
+
+    fn entry_id(line: &PipelineRunLog) -> i64 {
+        line.id
+    }
+}
@@ -0,0 +1,388 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use crate::pipelines::stages;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::super::rbac::{require_in_scope, Caller, DeploymentsWrite, PipelinesRead, PipelinesWrite, RequestScope, Require};
+use super::types::PipelineRequest;
+use db::pipeline::{Pipeline, PipelineInput};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, put, State};
+use sqlx::{MySql, Pool};
+
+/// Statuses a pipeline can be given.
+const PIPELINE_STATUSES: [&str; 4] = ["draft", "active", "paused", "archived"];
+
+/// List the pipelines of an application.
+#[get("/platform/<platform_id>/apps/<app_id>/pipelines")]
+pub async fn list_pipelines(
+    _auth: Require<PipelinesRead>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<Pipeline>>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::pipeline::list_app_pipelines(&pool, app_id).await {
+        Ok(pipelines) => Ok(Json(pipelines)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch pipelines"
+            }))
+        )),
+    }
+}
+
+/// Get a pipeline of an application.
+#[get("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>")]
+pub async fn get_pipeline(
+    _auth: Require<PipelinesRead>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Pipeline>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_pipeline(&pool, app_id, pipeline_id).await.map(Json)
+}
+
+/// Create a pipeline of an application.
+///
+/// Stages that test, deploy or promote to other applications require
+/// `deployments:write` on those applications.
+#[post("/platform/<platform_id>/apps/<app_id>/pipelines", format = "json", data = "<request>")]
+pub async fn create_pipeline(
+    auth: Require<PipelinesWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<PipelineRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Pipeline>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::app::get_app_by_id(&pool, app_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        ));
+    }
+
+    let input = pipeline_input(db_manager, &pool, auth.caller(), platform_id, app_id, request.into_inner()).await?;
+    match db::pipeline::create_pipeline(&pool, app_id, &input, Some(auth.user_id())).await {
+        Ok(pipeline) => {
+            trail.resource("pipeline", pipeline.id);
+            trail.after(&pipeline);
+            Ok(Json(pipeline))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to create pipeline"
+            }))
+        )),
+    }
+}
+
+/// Replace a pipeline of an application. Runs in progress keep the stages
+/// they were started with.
+#[put("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>", format = "json", data = "<request>")]
+pub async fn update_pipeline(
+    auth: Require<PipelinesWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    request: Json<PipelineRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Pipeline>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let existing = find_pipeline(&pool, app_id, pipeline_id).await?;
+    trail.resource("pipeline", pipeline_id);
+    trail.before(&existing);
+
+    let input = pipeline_input(db_manager, &pool, auth.caller(), platform_id, app_id, request.into_inner()).await?;
+    match db::pipeline::update_pipeline(&pool, app_id, pipeline_id, &input, Some(auth.user_id())).await {
+        Ok(Some(pipeline)) => {
+            trail.after(&pipeline);
+            Ok(Json(pipeline))
+        }
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Pipeline not found",
+                "message": format!("App {} has no pipeline with ID {}", app_id, pipeline_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to update pipeline"
+            }))
+        )),
+    }
+}
+
+/// Delete a pipeline of an application together with its runs. Pipelines
+/// with a run in progress cannot be deleted.
+#[delete("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>")]
+pub async fn delete_pipeline(
+    _auth: Require<PipelinesWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let pipeline = find_pipeline(&pool, app_id, pipeline_id).await?;
+    trail.resource("pipeline", pipeline_id);
+    trail.before(&pipeline);
+
+    match db::pipeline::delete_pipeline(&pool, app_id, pipeline_id).await {
+        Ok(true) => Ok(Json(json!({ "status": "deleted" }))),
+        Ok(false) => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Pipeline is running",
+                "message": format!("Pipeline {} has a run in progress; cancel it first", pipeline_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to delete pipeline"
+            }))
+        )),
+    }
+}
+
+/// Fetches a pipeline of an application, failing with `404` if there is
+/// none.
+pub(super) async fn find_pipeline(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    pipeline_id: i64,
+) -> Result<Pipeline, (Status, Json<Value>)> {
+    match db::pipeline::get_pipeline(pool, app_id, pipeline_id).await {
+        Ok(Some(pipeline)) => Ok(pipeline),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Pipeline not found",
+                "message": format!("App {} has no pipeline with ID {}", app_id, pipeline_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch pipeline"
+            }))
+        )),
+    }
+}
+
+/// Validates a pipeline request, checking that the applications its stages
+/// act on exist and that the caller may deploy to them.
+async fn pipeline_input(
+    db_manager: &State<Arc<DatabaseManager>>,
+    pool: &Pool<MySql>,
+    caller: &Caller,
+    platform_id: i64,
+    app_id: i64,
+    request: PipelineRequest,
+) -> Result<PipelineInput, (Status, Json<Value>)> {
+    let invalid = |message: String| {
+        (
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": message
+            }))
+        )
+    };
+
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.len() > 255 {
+        return Err(invalid("name must be between 1 and 255 characters".to_string()));
+    }
+    let status = request.status.unwrap_or_else(|| "draft".to_string());
+    if !PIPELINE_STATUSES.contains(&status.as_str()) {
+        return Err(invalid(format!("status must be one of: {}", PIPELINE_STATUSES.join(", "))));
+    }
+    stages::validate(&request.stages, app_id).map_err(invalid)?;
+
+    for other_app_id in stages::referenced_apps(&request.stages, app_id) {
+        if db::app::get_app_by_id(pool, other_app_id).await.is_err() {
+            return Err(invalid(format!("App with ID {} does not exist", other_app_id)));
+        }
+        require_in_scope::<DeploymentsWrite>(db_manager, caller, RequestScope {
+            platform_id: Some(platform_id),
+            app_id: Some(other_app_id),
+            ..Default::default()
+        }).await?;
+    }
+
+    let stages = match serde_json::to_value(&request.stages) {
+        Ok(stages) => stages,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Internal error",
+                    "message": "Failed to encode pipeline stages"
+                }))
+            ));
+        }
+    };
+
+    Ok(PipelineInput {
+        name,
+        description: request.description,
+        status,
+        stages,
+    })
+}
@@ -0,0 +1,21 @@
+//! Pipeline module for promoting builds through environments.
+//!
+//! This module provides a REST API for:
+//! - Creating, updating and deleting the pipelines of an application
+//! - Starting, canceling and retrying pipeline runs
+//! - Approving and rejecting the approval stages of runs
+//! - Reading and following the logs of runs
+
+// Import and re-export all route modules
+pub mod manage;
+pub mod runs;
+pub mod logs;
+pub mod types;
+
+// Re-export all route functions
+pub use manage::{create_pipeline, delete_pipeline, get_pipeline, list_pipelines, update_pipeline};
+pub use runs::{
+    approve_pipeline_stage, cancel_pipeline_run, get_pipeline_run, list_pipeline_runs, reject_pipeline_stage,
+    retry_pipeline_run, start_pipeline_run,
+};
+pub use logs::get_pipeline_run_logs;
@@ -0,0 +1,737 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use crate::pipelines::stages::{self, StageDefinition};
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::super::rbac::{DeploymentsApprove, PipelinesRead, PipelinesRun, Require};
+use super::manage::find_pipeline;
+use super::types::{StageDecisionRequest, StartPipelineRunRequest};
+use db::pipeline_run::{
+    NewRunStage, PipelineRun, RetryRunOutcome, StageDecisionOutcome, StartRunOutcome,
+};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, post, State};
+use sqlx::{MySql, Pool};
+
+/// Start a run of a pipeline.
+///
+/// Only active pipelines can be run, one run at a time. Pipelines without
+/// a build stage take an existing succeeded `build_id` of the application
+/// through their stages; pipelines with one queue a build of `commit_sha`,
+/// or of the head of the tracked branch, unless given a `build_id`.
+#[post("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs", format = "json", data = "<request>")]
+pub async fn start_pipeline_run(
+    auth: Require<PipelinesRun>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    request: Option<Json<StartPipelineRunRequest>>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let request = request.map(|request| request.into_inner()).unwrap_or_default();
+    let invalid = |message: String| {
+        (
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": message
+            }))
+        )
+    };
+
+    let pipeline = find_pipeline(&pool, app_id, pipeline_id).await?;
+    if pipeline.status.as_deref() != Some("active") {
+        return Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Pipeline not active",
+                "message": format!(
+                    "Pipeline {} is {}; only active pipelines can be run",
+                    pipeline_id,
+                    pipeline.status.as_deref().unwrap_or("unknown")
+                )
+            }))
+        ));
+    }
+    let stages_value = pipeline.stages.clone().unwrap_or_else(|| json!([]));
+    let definitions: Vec<StageDefinition> = match serde_json::from_value(stages_value.clone()) {
+        Ok(definitions) => definitions,
+        Err(_) => {
+            return Err((
+                Status::UnprocessableEntity,
+                Json(json!({
+                    "error": "Invalid pipeline",
+                    "message": format!("The stages of pipeline {} cannot be read; update the pipeline", pipeline_id)
+                }))
+            ));
+        }
+    };
+    stages::validate(&definitions, app_id).map_err(invalid)?;
+
+    let builds = stages::has_build_stage(&definitions);
+    if let Some(commit_sha) = &request.commit_sha {
+        if !builds {
+            return Err(invalid("commit_sha can only be given to pipelines with a build stage".to_string()));
+        }
+        if commit_sha.trim().is_empty() || commit_sha.len() > 255 {
+            return Err(invalid("commit_sha must be between 1 and 255 characters".to_string()));
+        }
+    }
+    match request.build_id {
+        Some(build_id) => {
+            let build = match db::build::get_build_by_id(&pool, build_id).await {
+                Ok(build) => build,
+                Err(_) => return Err(invalid(format!("Build with ID {} does not exist", build_id))),
+            };
+            if build.app_id != app_id {
+                return Err(invalid(format!("Build {} belongs to app {}, not app {}", build_id, build.app_id, app_id)));
+            }
+            if !builds && build.status.as_deref() != Some("succeeded") {
+                return Err(invalid(format!("Build {} has not succeeded", build_id)));
+            }
+        }
+        None if !builds => {
+            return Err(invalid("Pipelines without a build stage need a build_id to run".to_string()));
+        }
+        None => {}
+    }
+
+    let run_stages: Vec<NewRunStage> = definitions
+        .iter()
+        .map(|definition| NewRunStage {
+            name: definition.name.trim().to_string(),
+            stage_type: definition.kind.type_name(),
+            max_attempts: definition.max_attempts(),
+        })
+        .collect();
+
+    let outcome = db::pipeline_run::create_run(
+        &pool,
+        pipeline_id,
+        app_id,
+        &stages_value,
+        &run_stages,
+        request.build_id,
+        request.commit_sha.as_deref().map(str::trim),
+        Some(auth.user_id()),
+    )
+    .await;
+    match outcome {
+        Ok(StartRunOutcome::Started(run)) => {
+            trail.resource("pipeline_run", run.id);
+            trail.detail("pipeline_id", pipeline_id);
+            trail.after(&run);
+            run_response(&pool, run).await
+        }
+        Ok(StartRunOutcome::AlreadyRunning(run_id)) => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Pipeline is running",
+                "message": format!("Run {} of pipeline {} is still in progress", run_id, pipeline_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to start pipeline run"
+            }))
+        )),
+    }
+}
+
+/// List the runs of a pipeline with pagination, newest first.
+#[get("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs?<page>&<per_page>")]
+pub async fn list_pipeline_runs(
+    _auth: Require<PipelinesRead>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_pipeline(&pool, app_id, pipeline_id).await?;
+
+    let page: i64 = page.unwrap_or(0).into();
+    let per_page: i64 = per_page.unwrap_or(10).clamp(1, 100).into();
+
+    let runs = match db::pipeline_run::list_pipeline_runs(&pool, pipeline_id, page, per_page).await {
+        Ok(runs) => runs,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to list pipeline runs"
+                }))
+            ));
+        }
+    };
+
+    let total_count = match db::pipeline_run::count_pipeline_runs(&pool, pipeline_id).await {
+        Ok(count) => count,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to count pipeline runs"
+                }))
+            ));
+        }
+    };
+
+    let total_pages = (total_count as f64 / per_page as f64).ceil() as i64;
+
+    Ok(Json(json!({
+        "runs": runs,
+        "pagination": {
+            "page": page,
+            "per_page": per_page,
+            "total_count": total_count,
+            "total_pages": total_pages
+        }
+    })))
+}
+
+/// Get a run of a pipeline with the progress of its stages and the
+/// decisions on its approval stages.
+#[get("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs/<run_id>")]
+pub async fn get_pipeline_run(
+    _auth: Require<PipelinesRead>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    run_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_pipeline(&pool, app_id, pipeline_id).await?;
+    let run = find_run(&pool, pipeline_id, run_id).await?;
+    run_response(&pool, run).await
+}
+
+/// Cancel a run in progress.
+///
+/// Builds the run queued and tasks it started are canceled, as are its
+/// deployments that have not started rolling out yet. Deployments already
+/// rolling out are left to finish; abort them separately if needed.
+#[post("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs/<run_id>/cancel")]
+pub async fn cancel_pipeline_run(
+    auth: Require<PipelinesRun>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    run_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_pipeline(&pool, app_id, pipeline_id).await?;
+    let run = find_run(&pool, pipeline_id, run_id).await?;
+    trail.resource("pipeline_run", run_id);
+    trail.before(&run);
+
+    let reason = format!("Run canceled by user {}", auth.user_id());
+    let stopped = match db::pipeline_run::cancel_run(&pool, run_id, &reason).await {
+        Ok(Some(stopped)) => stopped,
+        Ok(None) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Invalid run state",
+                    "message": format!(
+                        "Run {} is {} and cannot be canceled",
+                        run_id,
+                        run.status.as_deref().unwrap_or("unknown")
+                    )
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to cancel pipeline run"
+                }))
+            ));
+        }
+    };
+
+    // Stop what the interrupted stages were waiting for. A build the run
+    // was started with belongs to whoever queued it and keeps going.
+    for stage in &stopped {
+        let result = match (stage.build_id, stage.task_id, stage.deployment_id) {
+            (Some(build_id), _, _) if run.build_id != Some(build_id) => {
+                db::build_queue::cancel_build(&pool, platform_id, build_id).await.map(|_| ())
+            }
+            (_, Some(task_id), _) => db::task::cancel_task(&pool, task_id, &reason).await.map(|_| ()),
+            (_, _, Some(deployment_id)) => {
+                db::deployment::cancel_pending_deployment(&pool, deployment_id, &reason).await.map(|_| ())
+            }
+            _ => Ok(()),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to stop stage {} of canceled pipeline run {}: {:#}", stage.id, run_id, e);
+        }
+    }
+
+    let run = find_run(&pool, pipeline_id, run_id).await?;
+    trail.after(&run);
+    run_response(&pool, run).await
+}
+
+/// Retry a failed or canceled run from the stage that stopped it.
+///
+/// Stages that succeeded are not repeated; the others get all of their
+/// attempts again. The pipeline must be active and have no other run in
+/// progress.
+#[post("/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs/<run_id>/retry")]
+pub async fn retry_pipeline_run(
+    auth: Require<PipelinesRun>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    run_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let pipeline = find_pipeline(&pool, app_id, pipeline_id).await?;
+    if pipeline.status.as_deref() != Some("active") {
+        return Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Pipeline not active",
+                "message": format!(
+                    "Pipeline {} is {}; only runs of active pipelines can be retried",
+                    pipeline_id,
+                    pipeline.status.as_deref().unwrap_or("unknown")
+                )
+            }))
+        ));
+    }
+    let run = find_run(&pool, pipeline_id, run_id).await?;
+    trail.resource("pipeline_run", run_id);
+    trail.before(&run);
+
+    match db::pipeline_run::retry_run(&pool, pipeline_id, run_id, auth.user_id()).await {
+        Ok(RetryRunOutcome::Retried) => {}
+        Ok(RetryRunOutcome::NotFinished) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Invalid run state",
+                    "message": format!(
+                        "Run {} is {}; only failed and canceled runs can be retried",
+                        run_id,
+                        run.status.as_deref().unwrap_or("unknown")
+                    )
+                }))
+            ));
+        }
+        Ok(RetryRunOutcome::OtherRunActive(other)) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Pipeline is running",
+                    "message": format!("Run {} of pipeline {} is still in progress", other, pipeline_id)
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retry pipeline run"
+                }))
+            ));
+        }
+    }
+
+    let run = find_run(&pool, pipeline_id, run_id).await?;
+    trail.after(&run);
+    run_response(&pool, run).await
+}
+
+/// Approve the approval stage a run is waiting at.
+///
+/// Approvers need `deployments:approve` for the pipeline's application and
+/// cannot approve runs they started. The run continues once the stage has
+/// the approvals it requires.
+#[post(
+    "/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs/<run_id>/stages/<stage_id>/approve",
+    format = "json",
+    data = "<request>"
+)]
+pub async fn approve_pipeline_stage(
+    auth: Require<DeploymentsApprove>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    run_id: i64,
+    stage_id: i64,
+    request: Option<Json<StageDecisionRequest>>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let comment = request.and_then(|request| request.into_inner().comment);
+    decide_stage(db_manager, &auth, &trail, platform_id, app_id, pipeline_id, run_id, stage_id, true, comment).await
+}
+
+/// Reject the approval stage a run is waiting at. A single rejection fails
+/// the run.
+#[post(
+    "/platform/<platform_id>/apps/<app_id>/pipelines/<pipeline_id>/runs/<run_id>/stages/<stage_id>/reject",
+    format = "json",
+    data = "<request>"
+)]
+pub async fn reject_pipeline_stage(
+    auth: Require<DeploymentsApprove>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    run_id: i64,
+    stage_id: i64,
+    request: Option<Json<StageDecisionRequest>>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let comment = request.and_then(|request| request.into_inner().comment);
+    decide_stage(db_manager, &auth, &trail, platform_id, app_id, pipeline_id, run_id, stage_id, false, comment).await
+}
+
+/// Records an approval or rejection in the run's log and the audit trail.
+async fn decide_stage(
+    db_manager: &State<Arc<DatabaseManager>>,
+    auth: &Require<DeploymentsApprove>,
+    trail: &AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    pipeline_id: i64,
+    run_id: i64,
+    stage_id: i64,
+    approve: bool,
+    comment: Option<String>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_pipeline(&pool, app_id, pipeline_id).await?;
+    let run = find_run(&pool, pipeline_id, run_id).await?;
+    let stages = match db::pipeline_run::list_run_stages(&pool, run_id).await {
+        Ok(stages) => stages,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch pipeline run stages"
+                }))
+            ));
+        }
+    };
+    let stage = match stages.into_iter().find(|stage| stage.id == stage_id) {
+        Some(stage) => stage,
+        None => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Stage not found",
+                    "message": format!("Run {} has no stage with ID {}", run_id, stage_id)
+                }))
+            ));
+        }
+    };
+
+    let user_id = auth.user_id();
+    if run.created_by == Some(user_id) {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": "Pipeline runs cannot be approved or rejected by the user who started them"
+            }))
+        ));
+    }
+
+    trail.resource("pipeline_run", run_id);
+    trail.detail("stage_id", stage_id);
+    trail.detail("decision", if approve { "approved" } else { "rejected" });
+    if let Some(comment) = &comment {
+        trail.detail("comment", comment);
+    }
+
+    let comment = comment.as_deref().map(str::trim).filter(|comment| !comment.is_empty());
+    match db::pipeline_run::decide_stage(&pool, stage_id, user_id, approve, comment).await {
+        Ok(StageDecisionOutcome::Recorded) => {}
+        Ok(StageDecisionOutcome::AlreadyDecided) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Already decided",
+                    "message": format!("User {} has already approved or rejected stage '{}'", user_id, stage.name)
+                }))
+            ));
+        }
+        Ok(StageDecisionOutcome::NotAwaitingApproval) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Invalid stage state",
+                    "message": format!(
+                        "Stage '{}' is a {} stage that is {} and does not await approval",
+                        stage.name,
+                        stage.stage_type,
+                        stage.status.as_deref().unwrap_or("unknown")
+                    )
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": format!("Failed to record decision on stage '{}'", stage.name)
+                }))
+            ));
+        }
+    }
+
+    let message = match (approve, comment) {
+        (true, Some(comment)) => format!("Approved by user {}: {}", user_id, comment),
+        (true, None) => format!("Approved by user {}", user_id),
+        (false, Some(comment)) => format!("Rejected by user {}: {}", user_id, comment),
+        (false, None) => format!("Rejected by user {}", user_id),
+    };
+    let level = if approve { "info" } else { "warn" };
+    if let Err(e) = db::pipeline_run::record_run_log(&pool, run_id, Some(stage_id), level, &message).await {
+        log::warn!("Failed to record decision on stage {} of pipeline run {}: {:#}", stage_id, run_id, e);
+    }
+
+    run_response(&pool, run).await
+}
+
+/// Fetches a run of a pipeline, failing with `404` if there is none.
+pub(super) async fn find_run(
+    pool: &Pool<MySql>,
+    pipeline_id: i64,
+    run_id: i64,
+) -> Result<PipelineRun, (Status, Json<Value>)> {
+    match db::pipeline_run::get_run(pool, pipeline_id, run_id).await {
+        Ok(Some(run)) => Ok(run),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Run not found",
+                "message": format!("Pipeline {} has no run with ID {}", pipeline_id, run_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch pipeline run"
+            }))
+        )),
+    }
+}
+
+/// Describes a run with the progress of its stages, and the decisions on
+/// approval stages. `stages` stays the definitions the run started with.
+async fn run_response(pool: &Pool<MySql>, run: PipelineRun) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let database_error = || {
+        (
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch pipeline run stages"
+            }))
+        )
+    };
+
+    let stages = db::pipeline_run::list_run_stages(pool, run.id).await.map_err(|_| database_error())?;
+    let mut described = Vec::with_capacity(stages.len());
+    for stage in stages {
+        let mut value = json!(stage);
+        if stage.stage_type == "approval" {
+            let approvals = db::pipeline_run::list_stage_approvals(pool, stage.id)
+                .await
+                .map_err(|_| database_error())?;
+            value["approvals"] = json!(approvals);
+        }
+        described.push(value);
+    }
+
+    let mut response = json!(run);
+    response["stage_progress"] = json!(described);
+    Ok(Json(response))
+}
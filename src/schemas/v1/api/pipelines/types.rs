@@ -0,0 +1,31 @@
+use serde::{Deserialize, Serialize};
+
+use crate::pipelines::stages::StageDefinition;
+
+/// Request body for creating or replacing a pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PipelineRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// 'draft', 'active', 'paused' or 'archived'; 'draft' if unset
+    pub status: Option<String>,
+    pub stages: Vec<StageDefinition>,
+}
+
+/// Request body for starting a run of a pipeline.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StartPipelineRunRequest {
+    /// Build to take through the stages. Required by pipelines without a
+    /// build stage; a pipeline with one waits for this build instead of
+    /// queuing a new one.
+    pub build_id: Option<i64>,
+    /// Commit the build stage builds; the head of the tracked branch if
+    /// unset
+    pub commit_sha: Option<String>,
+}
+
+/// Request body for approving or rejecting an approval stage.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StageDecisionRequest {
+    pub comment: Option<String>,
+}
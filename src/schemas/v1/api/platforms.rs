@@ -9,9 +9,10 @@ use log::{info, error}; // Add logging
 // Import the types we need
 use libomni::types::db::v1 as types;
 use types::platform::Platform;
+use super::rbac::{Require, PlatformsRead, PlatformsWrite};
 
 #[get("/platforms")]
-pub async fn list_platforms(db_manager: &State<Arc<DatabaseManager>>) -> Json<Vec<Platform>> {
+pub async fn list_platforms(_auth: Require<PlatformsRead>, db_manager: &State<Arc<DatabaseManager>>) -> Json<Vec<Platform>> {
     info!("Listing all platforms");
     let platforms = db_manager.get_all_platforms().await.unwrap_or_default();
     Json(platforms)
@@ -19,6 +20,7 @@ pub async fn list_platforms(db_manager: &State<Arc<DatabaseManager>>) -> Json<Ve
 
 #[post("/platforms", data = "<platform_data>")]
 pub async fn add_platform(
+    _auth: Require<PlatformsWrite>,
     platform_data: Json<Platform>,
     db_manager: &State<Arc<DatabaseManager>>
 ) -> Status {
@@ -56,6 +58,7 @@ pub async fn add_platform(
 
 #[delete("/platforms/<platform_id>")]
 pub async fn remove_platform(
+    _auth: Require<PlatformsWrite>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>
 ) -> Status {
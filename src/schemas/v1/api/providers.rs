@@ -15,6 +15,7 @@ use crate::DatabaseManager;
 use libomni::types::db::v1 as types;
 use types::provider::{ProviderAuditLog, Provider};
 use types::instance::Instance;
+use super::rbac::{Require, ProvidersRead};
 
 /// List all providers in the system with pagination support.
 /// 
@@ -28,6 +29,7 @@ use types::instance::Instance;
 /// A JSON response containing the list of providers and pagination information.
 #[get("/platform/<platform_id>/providers?<page>&<per_page>")]
 pub async fn list_providers(
+    _auth: Require<ProvidersRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -111,6 +113,7 @@ pub async fn list_providers(
 /// Retrieves a paginated list of audit logs for a specific provider.
 #[get("/platform/<platform_id>/providers/<provider_id>/audit_logs?<page>&<per_page>")]
 pub async fn get_provider_audit_logs_paginated(
+    _auth: Require<ProvidersRead>,
     platform_id: i64,
     provider_id: i64,
     page: Option<i64>,
@@ -194,6 +197,7 @@ pub async fn get_provider_audit_logs_paginated(
 /// Fetch all instances for a given provider.
 #[get("/platform/<platform_id>/providers/<provider_id>/instances?<page>&<per_page>")]
 pub async fn get_provider_instances(
+    _auth: Require<ProvidersRead>,
     platform_id: i64,
     provider_id: i64,
     page: Option<i64>,
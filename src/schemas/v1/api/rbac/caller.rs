@@ -0,0 +1,87 @@
+use std::sync::Arc;
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::State;
+
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::api_keys::{ApiKeyAuth, ApiKeyError};
+use db::api_key::ApiKey;
+
+use libomni::types::db::v1 as types;
+use types::user::User;
+
+/// Reasons a caller could not be identified.
+#[derive(Debug, Clone)]
+pub enum CallerError {
+    /// Neither a session token nor an API key was presented.
+    Unauthenticated,
+    /// An API key was presented but rejected.
+    ApiKey(ApiKeyError),
+    /// The user owning an API key no longer exists or is inactive.
+    InactiveUser,
+}
+
+/// The authenticated principal behind a request.
+///
+/// Requests are authenticated either with a user session token or with an
+/// API key (`Bearer omni_<prefix>_<secret>`). In both cases the request acts
+/// on behalf of a user; for API keys this is the user that created the key,
+/// and `api_key` is set so that permission checks can additionally be
+/// limited to the key's scopes.
+#[derive(Clone)]
+pub struct Caller {
+    pub user: User,
+    pub api_key: Option<ApiKey>,
+}
+
+impl Caller {
+    /// Whether the caller is one of the system administrators listed in the
+    /// server configuration.
+    pub fn is_system_admin(&self) -> bool {
+        crate::config::SERVER_CONFIG
+            .admin_emails
+            .iter()
+            .any(|email| email.eq_ignore_ascii_case(&self.user.email))
+    }
+}
+
+async fn identify(req: &Request<'_>) -> Result<Caller, (Status, CallerError)> {
+    match ApiKeyAuth::from_request(req).await {
+        Outcome::Success(auth) => {
+            let db_manager = match req.guard::<&State<Arc<DatabaseManager>>>().await {
+                Outcome::Success(db_manager) => db_manager,
+                _ => return Err((Status::InternalServerError, CallerError::InactiveUser)),
+            };
+
+            return match db::user::get_user_by_id(db_manager.get_main_pool(), auth.key.user_id).await {
+                Ok(user) if user.active => Ok(Caller { user, api_key: Some(auth.key) }),
+                _ => Err((Status::Unauthorized, CallerError::InactiveUser)),
+            };
+        }
+        Outcome::Error((status, error)) => return Err((status, CallerError::ApiKey(error))),
+        Outcome::Forward(_) => {}
+    }
+
+    match User::from_request(req).await {
+        Outcome::Success(user) => Ok(Caller { user, api_key: None }),
+        _ => Err((Status::Unauthorized, CallerError::Unauthenticated)),
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Caller {
+    type Error = CallerError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let cached = req
+            .local_cache_async(async { identify(req).await })
+            .await;
+
+        match cached {
+            Ok(caller) => Outcome::Success(caller.clone()),
+            Err((status, error)) => Outcome::Error((*status, error.clone())),
+        }
+    }
+}
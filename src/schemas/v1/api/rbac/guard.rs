@@ -0,0 +1,141 @@
+use std::marker::PhantomData;
+use std::sync::Arc;
+
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::{json, Json, Value};
+use rocket::State;
+
+use crate::DatabaseManager;
+use super::caller::{Caller, CallerError};
+use super::permissions::Permission;
+use super::scope::{resolve, Authorization, RequestScope};
+
+/// Reasons a permission check can fail.
+#[derive(Debug, Clone)]
+pub enum AuthzError {
+    /// The caller could not be authenticated.
+    Caller(CallerError),
+    /// The caller is authenticated but lacks the named permission.
+    Forbidden(&'static str),
+    /// The request names an organization or space that the resource in its
+    /// path does not belong to.
+    ScopeMismatch,
+    /// Permissions could not be resolved.
+    Internal,
+}
+
+type CachedAuthorization = Result<Authorization, (Status, AuthzError)>;
+
+async fn authorize(req: &Request<'_>) -> CachedAuthorization {
+    let caller = match Caller::from_request(req).await {
+        Outcome::Success(caller) => caller,
+        Outcome::Error((status, error)) => return Err((status, AuthzError::Caller(error))),
+        Outcome::Forward(status) => return Err((status, AuthzError::Caller(CallerError::Unauthenticated))),
+    };
+
+    let db_manager = match req.guard::<&State<Arc<DatabaseManager>>>().await {
+        Outcome::Success(db_manager) => db_manager,
+        _ => return Err((Status::InternalServerError, AuthzError::Internal)),
+    };
+
+    resolve(db_manager, caller, RequestScope::from_path(req))
+        .await
+        .map_err(|status| match status {
+            Status::Forbidden => (status, AuthzError::ScopeMismatch),
+            _ => (status, AuthzError::Internal),
+        })
+}
+
+/// Resolves (once per request) the caller's permissions in the request scope.
+pub async fn request_authorization<'r>(req: &'r Request<'_>) -> &'r CachedAuthorization {
+    req.local_cache_async(authorize(req)).await
+}
+
+/// Request guard that requires the caller to hold permission `P`.
+///
+/// Declaring `_auth: Require<AppsWrite>` on a route authenticates the
+/// caller (session token or API key) and checks that one of their roles,
+/// within the platform/organization/space/application named in the
+/// request path, grants `apps:write`. Anything not explicitly granted is denied:
+/// unauthenticated requests fail with `401 Unauthorized` and authenticated
+/// callers lacking the permission with `403 Forbidden`.
+pub struct Require<P: Permission> {
+    pub authorization: Authorization,
+    _permission: PhantomData<P>,
+}
+
+impl<P: Permission> Require<P> {
+    /// The authenticated caller.
+    pub fn caller(&self) -> &Caller {
+        &self.authorization.caller
+    }
+
+    /// Identifier of the user the request acts on behalf of.
+    pub fn user_id(&self) -> i64 {
+        self.authorization.caller.user.id
+    }
+}
+
+#[rocket::async_trait]
+impl<'r, P: Permission> FromRequest<'r> for Require<P> {
+    type Error = AuthzError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        match request_authorization(req).await {
+            Ok(authorization) if authorization.allows(P::NAME) => Outcome::Success(Require {
+                authorization: authorization.clone(),
+                _permission: PhantomData,
+            }),
+            Ok(_) => Outcome::Error((Status::Forbidden, AuthzError::Forbidden(P::NAME))),
+            Err((status, error)) => Outcome::Error((*status, error.clone())),
+        }
+    }
+}
+
+/// Checks a permission for a scope that is only known inside the handler.
+///
+/// Some routes only learn which organization or application they act on
+/// from the request body, from a record they load or from the filters in
+/// their query (for example creating an app in the organization named in
+/// the body, or reading a deployment by its ID). Those routes take a
+/// `Caller` guard and call this once the scope is known.
+pub async fn require_in_scope<P: Permission>(
+    db_manager: &Arc<DatabaseManager>,
+    caller: &Caller,
+    scope: RequestScope,
+) -> Result<Authorization, (Status, Json<Value>)> {
+    let authorization = match resolve(db_manager, caller.clone(), scope).await {
+        Ok(authorization) => authorization,
+        Err(Status::Forbidden) => {
+            return Err((
+                Status::Forbidden,
+                Json(json!({
+                    "error": "Forbidden",
+                    "message": "The requested scope does not match the resource"
+                }))
+            ));
+        }
+        Err(status) => {
+            return Err((
+                status,
+                Json(json!({
+                    "error": "Authorization error",
+                    "message": "Failed to resolve permissions"
+                }))
+            ));
+        }
+    };
+
+    if !authorization.allows(P::NAME) {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": format!("Missing required permission '{}'", P::NAME)
+            }))
+        ));
+    }
+
+    Ok(authorization)
+}
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::caller::Caller;
+use super::scope::{resolve, RequestScope};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+
+/// Describe the permissions the caller holds.
+///
+/// Without parameters this only reports whether the caller is a system
+/// administrator; pass `platform_id` (and optionally `org_id`, `space_id`
+/// or `app_id`) to see the permissions granted in that scope.
+#[get("/auth/me/permissions?<platform_id>&<org_id>&<space_id>&<app_id>")]
+pub async fn get_my_permissions(
+    platform_id: Option<i64>,
+    org_id: Option<i64>,
+    space_id: Option<i64>,
+    app_id: Option<i64>,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let scope = RequestScope { platform_id, org_id, space_id, app_id };
+
+    let authorization = match resolve(db_manager, caller, scope).await {
+        Ok(authorization) => authorization,
+        Err(status) => {
+            return Err((
+                status,
+                Json(json!({
+                    "error": "Authorization error",
+                    "message": "Failed to resolve permissions"
+                }))
+            ));
+        }
+    };
+
+    Ok(Json(json!({
+        "user_id": authorization.caller.user.id,
+        "api_key_id": authorization.caller.api_key.as_ref().map(|key| key.id),
+        "system_admin": authorization.system_admin,
+        "scope": authorization.scope,
+        "permissions": authorization.effective_permissions()
+    })))
+}
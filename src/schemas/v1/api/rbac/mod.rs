@@ -0,0 +1,23 @@
+//! Role based access control for the V1 API.
+//!
+//! This module provides:
+//! - The catalog of permissions routes can require (`permissions`)
+//! - The `Caller` guard, authenticating session tokens and API keys
+//! - Resolution of a caller's permissions within the platform, organization,
+//!   space or application a request is scoped to
+//! - The `Require<P>` guard enforcing a permission on a route
+//! - The `/auth/me/permissions` introspection endpoint
+
+// Import and re-export all modules
+pub mod permissions;
+pub mod caller;
+pub mod scope;
+pub mod guard;
+pub mod me;
+
+// Re-export the guards and route functions
+pub use permissions::*;
+pub use caller::{Caller, CallerError};
+pub use scope::{Authorization, RequestScope};
+pub use guard::{require_in_scope, AuthzError, Require};
+pub use me::get_my_permissions;
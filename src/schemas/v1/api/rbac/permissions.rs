@@ -0,0 +1,101 @@
+use super::super::api_keys::ApiKeyScope;
+
+/// A permission that can be required by a route.
+///
+/// Permissions are named `<resource>:<action>` and are declared with the
+/// [`permissions!`] macro below, which produces one marker type per
+/// permission so that routes can declare `Require<AppsWrite>` as a guard.
+pub trait Permission: Send + Sync + 'static {
+    const NAME: &'static str;
+}
+
+/// Checks whether a granted permission name satisfies a required one.
+///
+/// `*` grants everything and `<resource>:*` grants every action on the
+/// resource. Anything else must match exactly.
+pub fn grants(granted: &str, required: &str) -> bool {
+    if granted == "*" || granted == required {
+        return true;
+    }
+    match (granted.strip_suffix(":*"), required.split_once(':')) {
+        (Some(granted_resource), Some((required_resource, _))) => granted_resource == required_resource,
+        _ => false,
+    }
+}
+
+macro_rules! permissions {
+    ($($name:ident => $value:literal, $description:literal;)*) => {
+        $(
+            #[doc = $description]
+            pub struct $name;
+
+            impl Permission for $name {
+                const NAME: &'static str = $value;
+            }
+
+            impl ApiKeyScope for $name {
+                const SCOPE: &'static str = $value;
+            }
+        )*
+
+        /// Every permission known to the orchestrator as `(name, description)`.
+        ///
+        /// This must be kept in sync with the `permissions` rows seeded in
+        /// `sql/v1/platform_up.sql`.
+        pub const ALL_PERMISSIONS: &[(&str, &str)] = &[$(($value, $description)),*];
+    };
+}
+
+permissions! {
+    PlatformsRead      => "platforms:read",      "View platforms";
+    PlatformsWrite     => "platforms:write",     "Create and remove platforms";
+    OrgsRead           => "orgs:read",           "View organizations";
+    OrgsWrite          => "orgs:write",          "Create, update and delete organizations";
+    AppsRead           => "apps:read",           "View applications";
+    AppsWrite          => "apps:write",          "Create and update applications";
+    AppsDelete         => "apps:delete",         "Delete applications";
+    AppsControl        => "apps:control",        "Start, stop and scale applications";
+    InstancesRead      => "instances:read",      "View application instances";
+    BuildsRead         => "builds:read",         "View builds";
+    BuildsWrite        => "builds:write",        "Create builds and upload releases";
+    BuildsExecute      => "builds:execute",      "Lease and run queued builds";
+    DeploymentsRead    => "deployments:read",    "View deployments";
+    DeploymentsWrite   => "deployments:write",   "Create, update and delete deployments";
+    DeploymentsApprove => "deployments:approve", "Approve and reject deployments";
+    DeploymentsManage  => "deployments:manage",  "Manage approval policies and freezes";
+    DeploymentsOverride => "deployments:override", "Deploy during freeze windows";
+    PipelinesRead      => "pipelines:read",      "View pipelines and their runs";
+    PipelinesWrite     => "pipelines:write",     "Create, update and delete pipelines";
+    PipelinesRun       => "pipelines:run",       "Start, cancel and retry pipeline runs";
+    TasksRead          => "tasks:read",          "View tasks, their output and schedules";
+    TasksWrite         => "tasks:write",         "Run and cancel tasks, manage schedules";
+    AlertsRead         => "alerts:read",         "View alerts";
+    AlertsWrite        => "alerts:write",        "Create, acknowledge and resolve alerts";
+    NotificationsRead  => "notifications:read",  "View notifications";
+    NotificationsWrite => "notifications:write", "Create and manage notifications";
+    AuditLogsRead      => "audit_logs:read",     "View audit logs";
+    AuditLogsWrite     => "audit_logs:write",    "Write audit log entries";
+    AuditLogsExport    => "audit_logs:export",   "Export and verify the audit log";
+    AuditLogsManage    => "audit_logs:manage",   "Manage audit log retention and archives";
+    CostRead           => "cost:read",           "View cost data, budgets and pricing";
+    CostWrite          => "cost:write",          "Manage cost data, budgets and pricing";
+    MetricsRead        => "metrics:read",        "View metrics";
+    LogsRead           => "logs:read",           "View logs";
+    LogsWrite          => "logs:write",          "Ingest logs";
+    StorageRead        => "storage:read",        "View storage classes and volumes";
+    ProvidersRead      => "providers:read",      "View providers";
+    RegionsRead        => "regions:read",        "View regions";
+    WorkersRead        => "workers:read",        "View workers";
+    WorkersWrite       => "workers:write",       "Manage, drain and decommission workers";
+    MetadataRead       => "metadata:read",       "View system metadata";
+    MetadataWrite      => "metadata:write",      "Modify system metadata";
+    PermissionsRead    => "permissions:read",    "View roles and permissions";
+    PermissionsWrite   => "permissions:write",   "Manage roles and permissions";
+    UsersRead          => "users:read",          "View users";
+    ApiKeysRead        => "api_keys:read",       "View API keys";
+    ApiKeysWrite       => "api_keys:write",      "Create, rotate and revoke API keys";
+    MembersRead        => "members:read",        "View organization members and invites";
+    MembersWrite       => "members:write",       "Invite, update and remove members";
+    SpacesRead         => "spaces:read",         "View spaces";
+    SpacesWrite        => "spaces:write",        "Manage, archive and delete spaces";
+}
@@ -0,0 +1,204 @@
+use std::sync::Arc;
+
+use rocket::http::Status;
+use rocket::request::Request;
+use serde::{Deserialize, Serialize};
+
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::caller::Caller;
+use super::permissions::grants;
+
+/// The resources a request is scoped to.
+///
+/// Request guards take the scope from the routed path only (`/platform/<id>`,
+/// `/orgs/<id>`, `/spaces/<id>`, `/apps/<id>`); query parameters are chosen
+/// by the client and would let it widen its own scope. Routes that act on a
+/// resource addressed by its own ID, or that filter by a resource named in
+/// the query, build the scope themselves and check it with
+/// [`require_in_scope`](super::guard::require_in_scope).
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RequestScope {
+    pub platform_id: Option<i64>,
+    pub org_id: Option<i64>,
+    pub space_id: Option<i64>,
+    pub app_id: Option<i64>,
+}
+
+impl RequestScope {
+    /// Extracts the resources named in the routed path of a request,
+    /// ignoring its query string.
+    pub fn from_path(req: &Request<'_>) -> Self {
+        let mut scope = RequestScope::default();
+
+        let segments: Vec<&str> = req.routed_segments(0..).collect();
+        for pair in segments.windows(2) {
+            let id = match pair[1].parse::<i64>() {
+                Ok(id) => id,
+                Err(_) => continue,
+            };
+            match pair[0] {
+                "platform" | "platforms" => scope.platform_id = scope.platform_id.or(Some(id)),
+                "orgs" => scope.org_id = scope.org_id.or(Some(id)),
+                "spaces" => scope.space_id = scope.space_id.or(Some(id)),
+                "apps" => scope.app_id = scope.app_id.or(Some(id)),
+                _ => {}
+            }
+        }
+
+        scope
+    }
+}
+
+/// The outcome of resolving a caller's permissions within a scope.
+#[derive(Clone)]
+pub struct Authorization {
+    pub caller: Caller,
+    pub scope: RequestScope,
+    pub system_admin: bool,
+    /// Permission names granted by the caller's role bindings in `scope`.
+    pub permissions: Vec<String>,
+}
+
+impl Authorization {
+    /// Checks whether the caller holds a permission in the resolved scope.
+    ///
+    /// Requests made with an API key are additionally limited to the key's
+    /// scopes, so a key can never do more than the user that created it.
+    pub fn allows(&self, permission: &str) -> bool {
+        if let Some(key) = &self.caller.api_key {
+            if !key.has_scope(permission) {
+                return false;
+            }
+        }
+        self.system_admin || self.permissions.iter().any(|granted| grants(granted, permission))
+    }
+
+    /// Returns the effective permissions, taking API key scopes into account.
+    pub fn effective_permissions(&self) -> Vec<String> {
+        if self.system_admin && self.caller.api_key.is_none() {
+            return vec!["*".to_string()];
+        }
+        if self.system_admin {
+            return self.caller.api_key.as_ref().map(|key| key.scope_list()).unwrap_or_default();
+        }
+        self.permissions
+            .iter()
+            .filter(|granted| {
+                self.caller
+                    .api_key
+                    .as_ref()
+                    .map_or(true, |key| key.has_scope(granted))
+            })
+            .cloned()
+            .collect()
+    }
+}
+
+/// Resolves the permissions a caller holds within a scope.
+///
+/// Without a platform in scope only system administrators hold any
+/// permission. Within a platform, the caller's role bindings from that
+/// platform's database are used: global bindings always apply, and
+/// organization/space/application bindings apply when the request is
+/// scoped to that resource. A named application's organization and space,
+/// and a named space's organization, are always looked up and replace
+/// whatever the request claimed; a request naming an organization or space
+/// the resource does not belong to is refused with `403 Forbidden`.
+///
+/// API keys are confined to the organization they were created in; a key
+/// used against another organization resolves to no permissions at all.
+pub async fn resolve(
+    db_manager: &Arc<DatabaseManager>,
+    caller: Caller,
+    mut scope: RequestScope,
+) -> Result<Authorization, Status> {
+    let system_admin = caller.is_system_admin();
+
+    let platform_id = match scope.platform_id {
+        Some(platform_id) => platform_id,
+        None => {
+            return Ok(Authorization { caller, scope, system_admin, permissions: Vec::new() });
+        }
+    };
+
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Ok(Authorization { caller, scope, system_admin, permissions: Vec::new() });
+        }
+    };
+
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => return Err(Status::InternalServerError),
+    };
+
+    // The organization and space of a named application or space are always
+    // taken from the resource itself; a conflicting value from elsewhere in
+    // the request is refused rather than used to widen the scope.
+    if let Some(app_id) = scope.app_id {
+        match db::app::get_app_scope(&pool, app_id).await {
+            Ok(Some((org_id, space_id))) => {
+                let org_conflicts = scope.org_id.map_or(false, |id| id != org_id);
+                let space_conflicts = scope.space_id.map_or(false, |id| Some(id) != space_id);
+                if org_conflicts || space_conflicts {
+                    return Err(Status::Forbidden);
+                }
+                scope.org_id = Some(org_id);
+                scope.space_id = space_id;
+            }
+            // Bindings of an unknown application's claimed organization or
+            // space must not apply to it
+            Ok(None) => {
+                scope.org_id = None;
+                scope.space_id = None;
+            }
+            Err(e) => {
+                log::error!("Error resolving scope of app {}: {}", app_id, e);
+                return Err(Status::InternalServerError);
+            }
+        }
+    }
+
+    if let Some(space_id) = scope.space_id {
+        match db::space::get_space_org_id(&pool, space_id).await {
+            Ok(Some(org_id)) => {
+                if scope.org_id.map_or(false, |id| id != org_id) {
+                    return Err(Status::Forbidden);
+                }
+                scope.org_id = Some(org_id);
+            }
+            Ok(None) => {
+                scope.org_id = None;
+                scope.space_id = None;
+            }
+            Err(e) => {
+                log::error!("Error resolving scope of space {}: {}", space_id, e);
+                return Err(Status::InternalServerError);
+            }
+        }
+    }
+
+    if let Some(key) = &caller.api_key {
+        if scope.org_id.map_or(false, |org_id| org_id != key.org_id) {
+            return Ok(Authorization { caller, scope, system_admin: false, permissions: Vec::new() });
+        }
+    }
+
+    let permissions = match db::permission::get_user_permission_names_in_scope(
+        &pool,
+        caller.user.id,
+        scope.org_id,
+        scope.space_id,
+        scope.app_id,
+    ).await {
+        Ok(permissions) => permissions,
+        Err(e) => {
+            log::error!("Error resolving permissions for user {}: {}", caller.user.id, e);
+            return Err(Status::InternalServerError);
+        }
+    };
+
+    Ok(Authorization { caller, scope, system_admin, permissions })
+}
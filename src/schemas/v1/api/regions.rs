@@ -12,6 +12,7 @@ use tokio::sync::RwLock;
 use libomni::types::db::v1 as types;
 use types::region::Region;
 use types::provider::ProviderRegion;
+use super::rbac::{Require, RegionsRead};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateRegionRequest {
@@ -46,6 +47,7 @@ pub struct UpdateRegionRequest {
 // List all regions paginated
 #[get("/platform/<platform_id>/regions?<page>&<per_page>")]
 pub async fn list_regions(
+    _auth: Require<RegionsRead>,
     platform_id: i64,
     page: Option<i64>,
     per_page: Option<i64>,
@@ -101,6 +103,7 @@ pub async fn list_regions(
 
 #[get("/platform/<platform_id>/provider_regions")]
 pub async fn list_provider_regions(
+    _auth: Require<RegionsRead>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Vec<ProviderRegion>>, (Status, Json<Value>)> {
@@ -0,0 +1,121 @@
+use std::sync::Arc;
+
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use sqlx::{MySql, Pool};
+
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::rbac::{require_in_scope, Authorization, Caller, Permission, RequestScope};
+use super::types::SCOPE_TYPES;
+use db::role::ScopedRole;
+
+/// Loads a role, failing with `404 Not Found` if it does not exist.
+pub async fn load_role(pool: &Pool<MySql>, role_id: i64) -> Result<ScopedRole, (Status, Json<Value>)> {
+    match db::role::get_role(pool, role_id).await {
+        Ok(role) => Ok(role),
+        Err(_) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Role not found",
+                "message": format!("Role with ID {} does not exist", role_id)
+            }))
+        )),
+    }
+}
+
+/// Checks that the caller holds `P` where the role lives: in the owning
+/// organization for custom organization roles, platform-wide otherwise.
+pub async fn require_for_role<P: Permission>(
+    db_manager: &Arc<DatabaseManager>,
+    caller: &Caller,
+    platform_id: i64,
+    role: &ScopedRole,
+) -> Result<Authorization, (Status, Json<Value>)> {
+    require_in_scope::<P>(db_manager, caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id: role.org_id,
+        ..Default::default()
+    }).await
+}
+
+/// Rejects changes to system roles with `403 Forbidden`.
+pub fn ensure_not_system_role(role: &ScopedRole) -> Result<(), (Status, Json<Value>)> {
+    if role.is_system_role {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "System role",
+                "message": format!("'{}' is a system role and cannot be modified or deleted", role.name)
+            }))
+        ));
+    }
+    Ok(())
+}
+
+/// Validates a binding scope and converts it into the scope a permission
+/// check should be made in, returning the normalized `scope_id` alongside.
+pub fn binding_scope(
+    platform_id: i64,
+    scope_type: &str,
+    scope_id: Option<i64>,
+) -> Result<(RequestScope, i64), (Status, Json<Value>)> {
+    if !SCOPE_TYPES.contains(&scope_type) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid scope type",
+                "message": format!("scope_type must be one of: {}", SCOPE_TYPES.join(", "))
+            }))
+        ));
+    }
+
+    let mut scope = RequestScope { platform_id: Some(platform_id), ..Default::default() };
+    if scope_type == "global" {
+        return Ok((scope, 0));
+    }
+
+    let id = match scope_id {
+        Some(id) => id,
+        None => {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Missing scope ID",
+                    "message": format!("scope_id is required for '{}' bindings", scope_type)
+                }))
+            ));
+        }
+    };
+
+    match scope_type {
+        "organization" => scope.org_id = Some(id),
+        "space" => scope.space_id = Some(id),
+        _ => scope.app_id = Some(id),
+    }
+    Ok((scope, id))
+}
+
+/// Prevents privilege escalation: callers may only hand out permissions
+/// they hold themselves in the scope they are granting them in.
+pub fn ensure_can_grant(
+    authorization: &Authorization,
+    permission_names: &[String],
+) -> Result<(), (Status, Json<Value>)> {
+    let missing: Vec<&String> = permission_names
+        .iter()
+        .filter(|name| !authorization.allows(name))
+        .collect();
+
+    if !missing.is_empty() {
+        return Err((
+            Status::Forbidden,
+            Json(json!({
+                "error": "Forbidden",
+                "message": "Cannot grant permissions you do not hold",
+                "missing_permissions": missing
+            }))
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,287 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::{binding_scope, ensure_can_grant, load_role};
+use super::super::rbac::{require_in_scope, Caller, PermissionsRead, PermissionsWrite, RequestScope};
+use super::types::RoleBindingRequest;
+use db::role::RoleBinding;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, State};
+use sqlx::{MySql, Pool};
+
+/// Loads the permission names bound to a role.
+async fn role_permission_names(pool: &Pool<MySql>, role_id: i64) -> Result<Vec<String>, (Status, Json<Value>)> {
+    match db::permission::get_role_permissions(pool, role_id).await {
+        Ok(permissions) => Ok(permissions.into_iter().map(|p| p.name).collect()),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve role permissions"
+            }))
+        )),
+    }
+}
+
+/// List role bindings, optionally filtered by user and scope.
+///
+/// Without a `scope_type` this lists bindings across the whole platform and
+/// requires `permissions:read` platform-wide; otherwise the permission is
+/// checked within the named scope.
+#[get("/platform/<platform_id>/role_bindings?<user_id>&<scope_type>&<scope_id>")]
+pub async fn list_role_bindings(
+    platform_id: i64,
+    user_id: Option<i64>,
+    scope_type: Option<String>,
+    scope_id: Option<i64>,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let (scope, scope_id) = match scope_type.as_deref() {
+        Some(scope_type) => {
+            let (scope, id) = binding_scope(platform_id, scope_type, scope_id)?;
+            (scope, Some(id))
+        }
+        None => (RequestScope { platform_id: Some(platform_id), ..Default::default() }, None),
+    };
+    require_in_scope::<PermissionsRead>(db_manager, &caller, scope).await?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::role::list_role_bindings(&pool, user_id, scope_type.as_deref(), scope_id).await {
+        Ok(bindings) => Ok(Json(json!({ "role_bindings": bindings }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve role bindings"
+            }))
+        )),
+    }
+}
+
+/// Bind a role to a user within a scope.
+///
+/// The caller needs `permissions:write` in the target scope and must hold
+/// every permission of the role there, so roles can never be used to hand
+/// out more access than the caller has. Roles owned by an organization can
+/// only be bound to that organization or to its spaces and applications.
+#[post("/platform/<platform_id>/role_bindings", format = "json", data = "<request>")]
+pub async fn create_role_binding(
+    platform_id: i64,
+    caller: Caller,
+    request: Json<RoleBindingRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<RoleBinding>, (Status, Json<Value>)> {
+    let (scope, scope_id) = binding_scope(platform_id, &request.scope_type, request.scope_id)?;
+    let authorization = require_in_scope::<PermissionsWrite>(db_manager, &caller, scope).await?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let role = load_role(&pool, request.role_id).await?;
+
+    if request.scope_type != "global" {
+        let scope_org = match db::role::get_binding_scope_org(&pool, &request.scope_type, scope_id).await {
+            Ok(Some(org_id)) => org_id,
+            Ok(None) => {
+                return Err((
+                    Status::NotFound,
+                    Json(json!({
+                        "error": "Scope not found",
+                        "message": format!("No {} with ID {} exists", request.scope_type, scope_id)
+                    }))
+                ));
+            }
+            Err(_) => {
+                return Err((
+                    Status::InternalServerError,
+                    Json(json!({
+                        "error": "Database error",
+                        "message": "Failed to look up binding scope"
+                    }))
+                ));
+            }
+        };
+
+        if role.org_id.map_or(false, |org_id| org_id != scope_org) {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid scope",
+                    "message": format!("Role '{}' belongs to another organization", role.name)
+                }))
+            ));
+        }
+    } else if role.org_id.is_some() {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid scope",
+                "message": format!("Role '{}' belongs to an organization and cannot be bound globally", role.name)
+            }))
+        ));
+    }
+
+    if db::user::get_user_by_id(db_manager.get_main_pool(), request.user_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "User not found",
+                "message": format!("User with ID {} does not exist", request.user_id)
+            }))
+        ));
+    }
+
+    let permission_names = role_permission_names(&pool, role.id).await?;
+    ensure_can_grant(&authorization, &permission_names)?;
+
+    if let Err(e) = db::role::bind_role(&pool, request.user_id, role.id, &request.scope_type, scope_id).await {
+        return Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to bind role",
+                "message": e.to_string()
+            }))
+        ));
+    }
+
+    match db::role::list_role_bindings(&pool, Some(request.user_id), Some(&request.scope_type), Some(scope_id)).await {
+        Ok(bindings) => match bindings.into_iter().find(|b| b.role_id == role.id) {
+            Some(binding) => Ok(Json(binding)),
+            None => Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retrieve created role binding"
+                }))
+            )),
+        },
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve created role binding"
+            }))
+        )),
+    }
+}
+
+/// Remove a role binding.
+///
+/// As with creating bindings, the caller must hold every permission of the
+/// role in the scope, which keeps admins from demoting owners.
+#[delete("/platform/<platform_id>/role_bindings?<user_id>&<role_id>&<scope_type>&<scope_id>")]
+pub async fn delete_role_binding(
+    platform_id: i64,
+    user_id: i64,
+    role_id: i64,
+    scope_type: String,
+    scope_id: Option<i64>,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let (scope, scope_id) = binding_scope(platform_id, &scope_type, scope_id)?;
+    let authorization = require_in_scope::<PermissionsWrite>(db_manager, &caller, scope).await?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let permission_names = role_permission_names(&pool, role_id).await?;
+    ensure_can_grant(&authorization, &permission_names)?;
+
+    match db::role::unbind_role(&pool, user_id, role_id, &scope_type, scope_id).await {
+        Ok(true) => Ok(Json(json!({ "status": "deleted" }))),
+        Ok(false) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Role binding not found",
+                "message": "The user does not have this role in the given scope"
+            }))
+        )),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to remove role binding",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
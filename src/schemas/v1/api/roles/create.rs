@@ -0,0 +1,111 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::rbac::{require_in_scope, Caller, PermissionsWrite, RequestScope};
+use super::types::{CreateRoleRequest, SCOPE_TYPES};
+use db::role::ScopedRole;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+
+/// Create a new custom role.
+///
+/// Roles created with an `org_id` belong to that organization and require
+/// `permissions:write` within it; platform-wide roles require the
+/// permission platform-wide. New roles start out without any permissions.
+#[post("/platform/<platform_id>/roles", format = "json", data = "<request>")]
+pub async fn create_role(
+    platform_id: i64,
+    caller: Caller,
+    request: Json<CreateRoleRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ScopedRole>, (Status, Json<Value>)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "Role name must not be empty"
+            }))
+        ));
+    }
+
+    let scope = request.scope.as_deref().unwrap_or("organization");
+    if !SCOPE_TYPES.contains(&scope) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("scope must be one of: {}", SCOPE_TYPES.join(", "))
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    require_in_scope::<PermissionsWrite>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id: request.org_id,
+        ..Default::default()
+    }).await?;
+
+    match db::role::role_name_exists(&pool, request.org_id, name).await {
+        Ok(false) => {}
+        Ok(true) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Role already exists",
+                    "message": format!("A role named '{}' already exists", name)
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to check for existing roles"
+                }))
+            ));
+        }
+    }
+
+    match db::role::create_role(&pool, request.org_id, name, request.description.as_deref(), scope).await {
+        Ok(role) => Ok(Json(role)),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to create role",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,63 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::{ensure_not_system_role, load_role, require_for_role};
+use super::super::rbac::{Caller, PermissionsWrite};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, State};
+
+/// Delete a custom role.
+///
+/// Every permission and user binding of the role is removed with it.
+/// System roles cannot be deleted.
+#[delete("/platform/<platform_id>/roles/<role_id>")]
+pub async fn delete_role(
+    platform_id: i64,
+    role_id: i64,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let role = load_role(&pool, role_id).await?;
+    require_for_role::<PermissionsWrite>(db_manager, &caller, platform_id, &role).await?;
+    ensure_not_system_role(&role)?;
+
+    match db::role::delete_role(&pool, role_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to delete role",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
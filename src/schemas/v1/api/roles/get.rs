@@ -0,0 +1,62 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::{load_role, require_for_role};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use super::super::rbac::{Caller, PermissionsRead};
+
+/// Get a role together with the permissions bound to it.
+#[get("/platform/<platform_id>/roles/<role_id>")]
+pub async fn get_role(
+    caller: Caller,
+    platform_id: i64,
+    role_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let role = load_role(&pool, role_id).await?;
+    require_for_role::<PermissionsRead>(db_manager, &caller, platform_id, &role).await?;
+
+    match db::permission::get_role_permissions(&pool, role_id).await {
+        Ok(permissions) => Ok(Json(json!({
+            "role": role,
+            "permissions": permissions
+        }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve role permissions"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,65 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use super::super::rbac::{require_in_scope, PermissionsRead, Caller, RequestScope};
+
+/// List the roles available on a platform.
+///
+/// Platform-wide roles, including the built-in system roles, are always
+/// listed. When `org_id` is given the custom roles of that organization are
+/// included as well.
+#[get("/platform/<platform_id>/roles?<org_id>")]
+pub async fn list_roles(
+    caller: Caller,
+    platform_id: i64,
+    org_id: Option<i64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    require_in_scope::<PermissionsRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        org_id,
+        ..Default::default()
+    }).await?;
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::role::list_roles(&pool, org_id).await {
+        Ok(roles) => Ok(Json(json!({ "roles": roles }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve roles"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,32 @@
+//! Role management module for handling roles and their bindings.
+//!
+//! This module provides a REST API for managing roles, including:
+//! - Listing the platform-wide and organization-specific roles
+//! - Creating, updating and deleting custom roles
+//! - Binding permissions to roles
+//! - Binding roles to users within a global, organization, space or
+//!   application scope
+//!
+//! The built-in system roles (`owner`, `admin`, `developer` and `viewer`)
+//! are seeded with the platform schema and cannot be modified or deleted.
+
+// Import and re-export all modules
+pub mod types;
+pub mod access;
+pub mod list;
+pub mod get;
+pub mod create;
+pub mod update;
+pub mod delete;
+pub mod permissions;
+pub mod bindings;
+
+// Re-export all route functions
+pub use types::*;
+pub use list::list_roles;
+pub use get::get_role;
+pub use create::create_role;
+pub use update::update_role;
+pub use delete::delete_role;
+pub use permissions::{add_role_permission, list_role_permissions, remove_role_permission};
+pub use bindings::{create_role_binding, delete_role_binding, list_role_bindings};
@@ -0,0 +1,185 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::{ensure_can_grant, ensure_not_system_role, load_role, require_for_role};
+use super::super::rbac::{Caller, PermissionsRead, PermissionsWrite};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, put, State};
+
+/// List the permissions bound to a role.
+#[get("/platform/<platform_id>/roles/<role_id>/permissions")]
+pub async fn list_role_permissions(
+    platform_id: i64,
+    role_id: i64,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let role = load_role(&pool, role_id).await?;
+    require_for_role::<PermissionsRead>(db_manager, &caller, platform_id, &role).await?;
+
+    match db::permission::get_role_permissions(&pool, role_id).await {
+        Ok(permissions) => Ok(Json(json!({ "permissions": permissions }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve role permissions"
+            }))
+        )),
+    }
+}
+
+/// Bind a permission to a custom role.
+///
+/// The caller must hold the permission themselves; binding a permission
+/// that is already part of the role is a no-op.
+#[put("/platform/<platform_id>/roles/<role_id>/permissions/<permission_id>")]
+pub async fn add_role_permission(
+    platform_id: i64,
+    role_id: i64,
+    permission_id: i64,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let role = load_role(&pool, role_id).await?;
+    let authorization = require_for_role::<PermissionsWrite>(db_manager, &caller, platform_id, &role).await?;
+    ensure_not_system_role(&role)?;
+
+    let permission = match db::permission::get_permission_by_id(&pool, permission_id).await {
+        Ok(permission) => permission,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Permission not found",
+                    "message": format!("Permission with ID {} does not exist", permission_id)
+                }))
+            ));
+        }
+    };
+    ensure_can_grant(&authorization, &[permission.name.clone()])?;
+
+    match db::permission::assign_permission_to_role(&pool, permission_id, role_id).await {
+        Ok(_) => Ok(Json(json!({
+            "role_id": role_id,
+            "permission": permission
+        }))),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to add permission to role",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
+
+/// Remove a permission from a custom role.
+#[delete("/platform/<platform_id>/roles/<role_id>/permissions/<permission_id>")]
+pub async fn remove_role_permission(
+    platform_id: i64,
+    role_id: i64,
+    permission_id: i64,
+    caller: Caller,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let role = load_role(&pool, role_id).await?;
+    require_for_role::<PermissionsWrite>(db_manager, &caller, platform_id, &role).await?;
+    ensure_not_system_role(&role)?;
+
+    match db::permission::remove_permission_from_role(&pool, permission_id, role_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to remove permission from role",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
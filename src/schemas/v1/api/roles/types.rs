@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Request body for creating a custom role.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateRoleRequest {
+    /// Owning organization; omit to create a platform-wide role
+    pub org_id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    /// One of `global`, `organization`, `space` or `application`
+    pub scope: Option<String>,
+}
+
+/// Request body for updating a custom role. Omitted fields are left unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateRoleRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Request body for binding a role to a user.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoleBindingRequest {
+    pub user_id: i64,
+    pub role_id: i64,
+    /// One of `global`, `organization`, `space` or `application`
+    pub scope_type: String,
+    /// Identifier of the scoped resource; ignored for `global` bindings
+    pub scope_id: Option<i64>,
+}
+
+/// Scope types a role can be bound at, matching `role_user.scope_type`.
+pub const SCOPE_TYPES: &[&str] = &["global", "organization", "space", "application"];
@@ -0,0 +1,102 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::{ensure_not_system_role, load_role, require_for_role};
+use super::super::rbac::{Caller, PermissionsWrite};
+use super::types::UpdateRoleRequest;
+use db::role::ScopedRole;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{put, State};
+
+/// Update the name or description of a custom role.
+///
+/// System roles cannot be modified.
+#[put("/platform/<platform_id>/roles/<role_id>", format = "json", data = "<request>")]
+pub async fn update_role(
+    platform_id: i64,
+    role_id: i64,
+    caller: Caller,
+    request: Json<UpdateRoleRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<ScopedRole>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let role = load_role(&pool, role_id).await?;
+    require_for_role::<PermissionsWrite>(db_manager, &caller, platform_id, &role).await?;
+    ensure_not_system_role(&role)?;
+
+    let name = request.name.as_deref().map(str::trim);
+    if let Some(name) = name {
+        if name.is_empty() {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": "Role name must not be empty"
+                }))
+            ));
+        }
+
+        if name != role.name {
+            match db::role::role_name_exists(&pool, role.org_id, name).await {
+                Ok(false) => {}
+                Ok(true) => {
+                    return Err((
+                        Status::Conflict,
+                        Json(json!({
+                            "error": "Role already exists",
+                            "message": format!("A role named '{}' already exists", name)
+                        }))
+                    ));
+                }
+                Err(_) => {
+                    return Err((
+                        Status::InternalServerError,
+                        Json(json!({
+                            "error": "Database error",
+                            "message": "Failed to check for existing roles"
+                        }))
+                    ));
+                }
+            }
+        }
+    }
+
+    match db::role::update_role(&pool, role_id, name, request.description.as_deref()).await {
+        Ok(role) => Ok(Json(role)),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to update role",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
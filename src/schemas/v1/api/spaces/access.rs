@@ -0,0 +1,59 @@
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use sqlx::{MySql, Pool};
+
+use super::super::super::db::queries as db;
+use db::space::Space;
+
+/// Loads a space, failing with `404 Not Found` if it does not exist.
+pub async fn load_space(pool: &Pool<MySql>, space_id: i64) -> Result<Space, (Status, Json<Value>)> {
+    match db::space::get_space_by_id(pool, space_id).await {
+        Ok(space) => Ok(space),
+        Err(_) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Space not found",
+                "message": format!("Space with ID {} does not exist", space_id)
+            }))
+        )),
+    }
+}
+
+/// Rejects changes to archived spaces with `409 Conflict`.
+pub fn ensure_not_archived(space: &Space) -> Result<(), (Status, Json<Value>)> {
+    if space.is_archived() {
+        return Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Space archived",
+                "message": format!("Space '{}' is archived; restore it first", space.name)
+            }))
+        ));
+    }
+    Ok(())
+}
+
+/// Checks whether a space name is free within an organization.
+pub async fn ensure_name_available(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    name: &str,
+) -> Result<(), (Status, Json<Value>)> {
+    match db::space::list_spaces(pool, org_id, true).await {
+        Ok(spaces) if spaces.iter().any(|s| s.name == name) => Err((
+            Status::Conflict,
+            Json(json!({
+                "error": "Space already exists",
+                "message": format!("A space named '{}' already exists in this organization", name)
+            }))
+        )),
+        Ok(_) => Ok(()),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to check for existing spaces"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::load_space;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use super::super::rbac::{Require, AppsRead};
+
+/// List the applications in a space with pagination support.
+#[get("/platform/<platform_id>/spaces/<space_id>/apps?<page>&<per_page>")]
+pub async fn list_space_apps(
+    _auth: Require<AppsRead>,
+    platform_id: i64,
+    space_id: i64,
+    page: Option<i64>,
+    per_page: Option<i64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    load_space(&pool, space_id).await?;
+    let p = page.unwrap_or(0);
+    let pp = per_page.unwrap_or(10);
+
+    let apps = match db::space::list_space_apps(&pool, space_id, p, pp).await {
+        Ok(apps) => apps,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to retrieve applications"
+                }))
+            ));
+        }
+    };
+
+    let total_count = match db::space::count_space_apps(&pool, space_id).await {
+        Ok(count) => count,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to count applications"
+                }))
+            ));
+        }
+    };
+
+    let total_pages = if pp > 0 { (total_count + pp - 1) / pp } else { 1 };
+
+    Ok(Json(json!({
+        "apps": apps,
+        "pagination": {
+            "page": p,
+            "per_page": pp,
+            "total_count": total_count,
+            "total_pages": total_pages
+        }
+    })))
+}
@@ -0,0 +1,136 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::load_space;
+use db::space::Space;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+use super::super::rbac::{Require, SpacesWrite};
+use super::super::audit_log::AuditTrail;
+
+/// Archive a space.
+///
+/// Every running application in the space is stopped, and all of its
+/// applications are frozen in maintenance mode until the space is restored.
+#[post("/platform/<platform_id>/spaces/<space_id>/archive")]
+pub async fn archive_space(
+    _auth: Require<SpacesWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    space_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let space = load_space(&pool, space_id).await?;
+    trail.resource("space", space_id);
+    trail.before(&space);
+    if space.is_archived() {
+        return Ok(Json(json!({ "space": space, "stopped_apps": 0 })));
+    }
+
+    match db::space::archive_space(&pool, space_id).await {
+        Ok((space, stopped)) => {
+            trail.after(&space);
+            trail.detail("stopped_apps", stopped);
+            log::info!("Archived space {} and stopped {} application(s)", space_id, stopped);
+            Ok(Json(json!({ "space": space, "stopped_apps": stopped })))
+        }
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to archive space",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
+
+/// Restore an archived space.
+///
+/// Applications leave maintenance mode but stay stopped until they are
+/// started again.
+#[post("/platform/<platform_id>/spaces/<space_id>/restore")]
+pub async fn restore_space(
+    _auth: Require<SpacesWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    space_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Space>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let space = load_space(&pool, space_id).await?;
+    trail.resource("space", space_id);
+    trail.before(&space);
+    if !space.is_archived() {
+        return Ok(Json(space));
+    }
+
+    match db::space::restore_space(&pool, space_id).await {
+        Ok(space) => {
+            trail.after(&space);
+            Ok(Json(space))
+        }
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to restore space",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use super::super::rbac::{Require, PermissionsRead};
+
+/// List the role bindings scoped to a space.
+///
+/// Bindings are created and removed through the role binding routes with
+/// `scope_type` set to `space`.
+#[get("/platform/<platform_id>/spaces/<space_id>/role_bindings")]
+pub async fn list_space_role_bindings(
+    _auth: Require<PermissionsRead>,
+    platform_id: i64,
+    space_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::role::list_role_bindings(&pool, None, Some("space"), Some(space_id)).await {
+        Ok(bindings) => Ok(Json(json!({ "role_bindings": bindings }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve role bindings"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,89 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::ensure_name_available;
+use super::types::CreateSpaceRequest;
+use db::space::Space;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+use super::super::rbac::{Require, SpacesWrite};
+
+/// Create a new space in an organization.
+#[post("/platform/<platform_id>/orgs/<org_id>/spaces", format = "json", data = "<request>")]
+pub async fn create_space(
+    _auth: Require<SpacesWrite>,
+    platform_id: i64,
+    org_id: i64,
+    request: Json<CreateSpaceRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Space>, (Status, Json<Value>)> {
+    let name = request.name.trim();
+    if name.is_empty() {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "Space name must not be empty"
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::org::get_org_by_id(&pool, org_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Organization not found",
+                "message": format!("Organization with ID {} does not exist", org_id)
+            }))
+        ));
+    }
+
+    ensure_name_available(&pool, org_id, name).await?;
+
+    match db::space::create_space(
+        &pool,
+        org_id,
+        name,
+        request.description.as_deref(),
+        request.isolation_segment.as_deref(),
+        request.network_isolation.unwrap_or(false),
+    ).await {
+        Ok(space) => Ok(Json(space)),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to create space",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::load_space;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, State};
+use super::super::rbac::{Require, SpacesWrite};
+use super::super::audit_log::AuditTrail;
+
+/// Delete a space.
+///
+/// Only empty spaces can be deleted; move or delete their applications first.
+#[delete("/platform/<platform_id>/spaces/<space_id>")]
+pub async fn delete_space(
+    _auth: Require<SpacesWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    space_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let space = load_space(&pool, space_id).await?;
+    trail.resource("space", space_id);
+    trail.before(&space);
+
+    match db::space::count_space_apps(&pool, space_id).await {
+        Ok(0) => {}
+        Ok(count) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Space not empty",
+                    "message": format!("Space still contains {} application(s)", count)
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to count space applications"
+                }))
+            ));
+        }
+    }
+
+    match db::space::delete_space(&pool, space_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to delete space",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,58 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use super::super::rbac::{Require, SpacesRead};
+
+/// List the spaces of an organization.
+///
+/// Archived spaces are only listed when `include_archived` is set.
+#[get("/platform/<platform_id>/orgs/<org_id>/spaces?<include_archived>")]
+pub async fn list_spaces(
+    _auth: Require<SpacesRead>,
+    platform_id: i64,
+    org_id: i64,
+    include_archived: Option<bool>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::space::list_spaces(&pool, org_id, include_archived.unwrap_or(false)).await {
+        Ok(spaces) => Ok(Json(json!({ "spaces": spaces }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to retrieve spaces"
+            }))
+        )),
+    }
+}
@@ -0,0 +1,36 @@
+//! Space management module for handling spaces within organizations.
+//!
+//! Spaces partition an organization's applications, typically into
+//! environments such as dev, staging and prod. This module provides a REST
+//! API for:
+//! - Listing, creating, updating and deleting spaces
+//! - Archiving spaces, which stops and freezes their applications, and
+//!   restoring them again
+//! - Listing the applications in a space
+//! - Listing the role bindings scoped to a space
+//!
+//! Role bindings with `scope_type` `space` (see the roles module) grant
+//! permissions on a space and every application in it.
+
+// Import and re-export all modules
+pub mod types;
+pub mod access;
+pub mod list;
+pub mod get;
+pub mod create;
+pub mod update;
+pub mod delete;
+pub mod archive;
+pub mod apps;
+pub mod bindings;
+
+// Re-export all route functions
+pub use types::*;
+pub use list::list_spaces;
+pub use get::get_space;
+pub use create::create_space;
+pub use update::update_space;
+pub use delete::delete_space;
+pub use archive::{archive_space, restore_space};
+pub use apps::list_space_apps;
+pub use bindings::list_space_role_bindings;
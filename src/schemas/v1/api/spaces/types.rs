@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// Request body for creating a space.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateSpaceRequest {
+    pub name: String,
+    pub description: Option<String>,
+    /// Isolation segment the space's applications are placed in
+    pub isolation_segment: Option<String>,
+    /// Whether the space's applications are network isolated from other spaces
+    pub network_isolation: Option<bool>,
+}
+
+/// Request body for updating a space. Omitted fields are left unchanged.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSpaceRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub isolation_segment: Option<String>,
+    pub network_isolation: Option<bool>,
+}
@@ -0,0 +1,92 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::access::{ensure_name_available, ensure_not_archived, load_space};
+use super::types::UpdateSpaceRequest;
+use db::space::Space;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{put, State};
+use super::super::rbac::{Require, SpacesWrite};
+use super::super::audit_log::AuditTrail;
+
+/// Update a space. Archived spaces must be restored before they can be changed.
+#[put("/platform/<platform_id>/spaces/<space_id>", format = "json", data = "<request>")]
+pub async fn update_space(
+    _auth: Require<SpacesWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    space_id: i64,
+    request: Json<UpdateSpaceRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Space>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let space = load_space(&pool, space_id).await?;
+    trail.resource("space", space_id);
+    trail.before(&space);
+    ensure_not_archived(&space)?;
+
+    let name = request.name.as_deref().map(str::trim);
+    if let Some(name) = name {
+        if name.is_empty() {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": "Space name must not be empty"
+                }))
+            ));
+        }
+        if name != space.name {
+            ensure_name_available(&pool, space.org_id, name).await?;
+        }
+    }
+
+    match db::space::update_space(
+        &pool,
+        space_id,
+        name,
+        request.description.as_deref(),
+        request.isolation_segment.as_deref(),
+        request.network_isolation,
+    ).await {
+        Ok(space) => {
+            trail.after(&space);
+            Ok(Json(space))
+        }
+        Err(e) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Failed to update space",
+                "message": e.to_string()
+            }))
+        )),
+    }
+}
@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use rocket::http::{Cookie, CookieJar, SameSite, Status};
+use rocket::response::Redirect;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, Responder, State};
+use sqlx::mysql::MySqlPool as Pool;
+
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::users::create_auth_token_and_session;
+use super::client::OidcClient;
+use super::provisioning::{resolve_user, sync_group_memberships};
+
+use libomni::types::db::auth::AuthConfig;
+
+/// Response of a completed single sign-on login.
+#[derive(Responder)]
+pub enum SsoLoginResponse {
+    /// The session token and user, as returned by the password login.
+    Token(Json<Value>),
+    /// Redirect to the `redirect_to` path of the login, with the session
+    /// token in the URL fragment.
+    Redirect(Redirect),
+}
+
+fn login_failed(status: Status, error: &str, message: &str) -> (Status, Json<Value>) {
+    (status, Json(json!({ "error": error, "message": message })))
+}
+
+/// Complete a single sign-on login.
+///
+/// The identity provider redirects here after the user signed in. The
+/// authorization code is exchanged using the login's PKCE verifier, the ID
+/// token is validated, the user is resolved or provisioned and their
+/// organization memberships are synced from their groups.
+#[get("/auth/oidc/callback?<code>&<state>&<error>&<error_description>")]
+pub async fn oidc_callback(
+    code: Option<String>,
+    state: Option<String>,
+    error: Option<String>,
+    error_description: Option<String>,
+    oidc: &State<OidcClient>,
+    pool: &State<Pool>,
+    db_manager: &State<Arc<DatabaseManager>>,
+    auth_config: &State<AuthConfig>,
+    cookies: &CookieJar<'_>,
+) -> Result<SsoLoginResponse, (Status, Json<Value>)> {
+    let config = match oidc.config() {
+        Some(config) => config,
+        None => return Err(login_failed(Status::NotFound, "SSO not configured", "Single sign-on is not enabled on this server")),
+    };
+
+    let state = match state {
+        Some(state) => state,
+        None => return Err(login_failed(Status::BadRequest, "Invalid callback", "Missing state parameter")),
+    };
+
+    // The state is consumed even if the provider reports an error, so it can never be reused
+    let login = match db::sso::take_oidc_login_state(pool, &state).await {
+        Ok(Some(login)) => login,
+        Ok(None) => return Err(login_failed(Status::BadRequest, "Invalid callback", "Unknown or expired login; please sign in again")),
+        Err(e) => {
+            log::error!("Failed to load OIDC login state: {}", e);
+            return Err(login_failed(Status::InternalServerError, "Database error", "Failed to complete login"));
+        }
+    };
+
+    if let Some(error) = error {
+        log::warn!("Identity provider rejected login: {} {}", error, error_description.as_deref().unwrap_or(""));
+        return Err((
+            Status::Unauthorized,
+            Json(json!({
+                "error": "Login rejected",
+                "message": error_description.unwrap_or(error)
+            }))
+        ));
+    }
+
+    let code = match code {
+        Some(code) => code,
+        None => return Err(login_failed(Status::BadRequest, "Invalid callback", "Missing authorization code")),
+    };
+
+    let tokens = match oidc.exchange_code(&code, &login.code_verifier).await {
+        Ok(tokens) => tokens,
+        Err(e) => {
+            log::error!("OIDC code exchange failed: {:#}", e);
+            return Err(login_failed(Status::BadGateway, "Identity provider error", "Failed to exchange the authorization code"));
+        }
+    };
+
+    let id_token = match tokens.id_token {
+        Some(id_token) => id_token,
+        None => return Err(login_failed(Status::BadGateway, "Identity provider error", "The identity provider did not return an ID token")),
+    };
+
+    let claims = match oidc.validate_id_token(&id_token, &login.nonce).await {
+        Ok(claims) => claims,
+        Err(e) => {
+            log::warn!("Rejected OIDC ID token: {:#}", e);
+            return Err(login_failed(Status::Unauthorized, "Invalid ID token", "The identity provider's ID token could not be validated"));
+        }
+    };
+
+    let user = resolve_user(pool, config, &claims)
+        .await
+        .map_err(|e| e.into_response())?;
+
+    sync_group_memberships(db_manager, config, user.id, &claims.groups).await;
+
+    let (token, session_id) = create_auth_token_and_session(pool, &user, auth_config)
+        .await
+        .map_err(|e| login_failed(e.0, "Login failed", &e.1))?;
+
+    let mut cookie = Cookie::new("session_id", session_id.to_string());
+    cookie.set_path("/");
+    cookie.set_http_only(true);
+    cookie.set_same_site(SameSite::Lax);
+    cookies.add(cookie);
+
+    log::info!("User {} signed in through SSO", user.id);
+
+    if let Some(target) = login.redirect_to {
+        return Ok(SsoLoginResponse::Redirect(Redirect::to(format!("{}#token={}", target, token))));
+    }
+
+    Ok(SsoLoginResponse::Token(Json(json!({
+        "token": token,
+        "user": {
+            "id": user.id,
+            "email": user.email,
+            "created_at": user.created_at,
+            "active": user.active
+        }
+    }))))
+}
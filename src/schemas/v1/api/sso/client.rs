@@ -0,0 +1,322 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Context;
+use jsonwebtoken::jwk::Jwk;
+use jsonwebtoken::{decode, decode_header, Algorithm, DecodingKey, Validation};
+use serde::Deserialize;
+use serde_json::Value;
+use tokio::sync::RwLock;
+
+use crate::config::OidcConfig;
+
+/// How long discovered provider metadata and signing keys are cached.
+const PROVIDER_CACHE_TTL: Duration = Duration::from_secs(3600);
+
+/// The parts of the provider's discovery document the relying party uses.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ProviderMetadata {
+    pub issuer: String,
+    pub authorization_endpoint: String,
+    pub token_endpoint: String,
+    pub jwks_uri: String,
+    #[serde(default)]
+    pub code_challenge_methods_supported: Vec<String>,
+    #[serde(default)]
+    pub token_endpoint_auth_methods_supported: Vec<String>,
+}
+
+/// Response of the provider's token endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub id_token: Option<String>,
+}
+
+/// The identity asserted by a validated ID token.
+#[derive(Debug, Clone)]
+pub struct IdTokenClaims {
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub email_verified: bool,
+    pub name: Option<String>,
+    pub given_name: Option<String>,
+    pub family_name: Option<String>,
+    pub groups: Vec<String>,
+}
+
+struct ProviderCache {
+    metadata: ProviderMetadata,
+    keys: Vec<Jwk>,
+    fetched_at: Instant,
+}
+
+/// OpenID Connect relying party.
+///
+/// Managed as Rocket state. When single sign-on is not configured the client
+/// holds no configuration and the SSO routes answer `404 Not Found`.
+pub struct OidcClient {
+    config: Option<OidcConfig>,
+    http: reqwest::Client,
+    provider: RwLock<Option<ProviderCache>>,
+}
+
+impl OidcClient {
+    pub fn new(config: Option<OidcConfig>) -> Self {
+        Self {
+            config,
+            http: reqwest::Client::new(),
+            provider: RwLock::new(None),
+        }
+    }
+
+    /// The SSO configuration, if single sign-on is enabled.
+    pub fn config(&self) -> Option<&OidcConfig> {
+        self.config.as_ref()
+    }
+
+    fn require_config(&self) -> anyhow::Result<&OidcConfig> {
+        self.config.as_ref().context("Single sign-on is not configured")
+    }
+
+    /// Returns the provider metadata, running discovery if the cached copy is
+    /// missing or stale.
+    pub async fn metadata(&self) -> anyhow::Result<ProviderMetadata> {
+        if let Some(cache) = self.provider.read().await.as_ref() {
+            if cache.fetched_at.elapsed() < PROVIDER_CACHE_TTL {
+                return Ok(cache.metadata.clone());
+            }
+        }
+
+        Ok(self.refresh().await?.0)
+    }
+
+    /// Runs discovery and fetches the provider's signing keys.
+    async fn refresh(&self) -> anyhow::Result<(ProviderMetadata, Vec<Jwk>)> {
+        let config = self.require_config()?;
+        let discovery_url = format!(
+            "{}/.well-known/openid-configuration",
+            config.issuer.trim_end_matches('/')
+        );
+
+        let metadata: ProviderMetadata = self
+            .http
+            .get(&discovery_url)
+            .send()
+            .await
+            .context("Failed to reach the identity provider")?
+            .error_for_status()
+            .context("Discovery request was rejected")?
+            .json()
+            .await
+            .context("Invalid discovery document")?;
+
+        if metadata.issuer.trim_end_matches('/') != config.issuer.trim_end_matches('/') {
+            anyhow::bail!(
+                "Discovery document issuer '{}' does not match the configured issuer '{}'",
+                metadata.issuer,
+                config.issuer
+            );
+        }
+
+        let jwks: Value = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .context("Failed to fetch the provider's signing keys")?
+            .error_for_status()
+            .context("Key set request was rejected")?
+            .json()
+            .await
+            .context("Invalid key set")?;
+
+        // Keys of types this client cannot use (such as encryption keys) are
+        // skipped instead of failing the whole set.
+        let keys: Vec<Jwk> = jwks
+            .get("keys")
+            .and_then(Value::as_array)
+            .map(|keys| {
+                keys.iter()
+                    .filter_map(|key| serde_json::from_value(key.clone()).ok())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        *self.provider.write().await = Some(ProviderCache {
+            metadata: metadata.clone(),
+            keys: keys.clone(),
+            fetched_at: Instant::now(),
+        });
+
+        Ok((metadata, keys))
+    }
+
+    /// Looks up a signing key by key ID, refreshing the key set once if the
+    /// key is unknown so that key rotation at the provider is picked up.
+    async fn signing_key(&self, kid: Option<&str>) -> anyhow::Result<Jwk> {
+        let find = |keys: &[Jwk]| match kid {
+            Some(kid) => keys.iter().find(|k| k.common.key_id.as_deref() == Some(kid)).cloned(),
+            None if keys.len() == 1 => keys.first().cloned(),
+            None => None,
+        };
+
+        let cached = match self.provider.read().await.as_ref() {
+            Some(cache) if cache.fetched_at.elapsed() < PROVIDER_CACHE_TTL => find(&cache.keys),
+            _ => None,
+        };
+        if let Some(key) = cached {
+            return Ok(key);
+        }
+
+        let (_, keys) = self.refresh().await?;
+        find(&keys).context("ID token is signed with an unknown key")
+    }
+
+    /// Builds the URL the user is redirected to in order to sign in.
+    pub fn authorization_url(
+        &self,
+        metadata: &ProviderMetadata,
+        state: &str,
+        nonce: &str,
+        code_challenge: &str,
+    ) -> anyhow::Result<String> {
+        let config = self.require_config()?;
+        let mut url = reqwest::Url::parse(&metadata.authorization_endpoint)
+            .context("Invalid authorization endpoint")?;
+
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", &config.redirect_uri)
+            .append_pair("scope", &config.scopes.join(" "))
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", code_challenge)
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.into())
+    }
+
+    /// Exchanges an authorization code for tokens.
+    pub async fn exchange_code(&self, code: &str, code_verifier: &str) -> anyhow::Result<TokenResponse> {
+        let config = self.require_config()?;
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", config.redirect_uri.as_str()),
+            ("code_verifier", code_verifier),
+        ];
+
+        let mut request = self.http.post(&metadata.token_endpoint);
+        match config.client_secret.as_deref() {
+            // client_secret_basic is the default authentication method;
+            // fall back to client_secret_post only if the provider says so.
+            Some(secret)
+                if metadata.token_endpoint_auth_methods_supported.is_empty()
+                    || metadata
+                        .token_endpoint_auth_methods_supported
+                        .iter()
+                        .any(|m| m == "client_secret_basic") =>
+            {
+                request = request.basic_auth(&config.client_id, Some(secret));
+            }
+            Some(secret) => {
+                form.push(("client_id", config.client_id.as_str()));
+                form.push(("client_secret", secret));
+            }
+            None => form.push(("client_id", config.client_id.as_str())),
+        }
+
+        let response = request
+            .form(&form)
+            .send()
+            .await
+            .context("Failed to reach the token endpoint")?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let body = response.text().await.unwrap_or_default();
+            anyhow::bail!("Token endpoint returned {}: {}", status, body);
+        }
+
+        response.json().await.context("Invalid token response")
+    }
+
+    /// Validates an ID token and extracts the identity it asserts.
+    ///
+    /// The signature is checked against the provider's published keys (or
+    /// the client secret for HMAC-signed tokens), together with the issuer,
+    /// audience, authorized party, expiry and the nonce of the login.
+    pub async fn validate_id_token(&self, id_token: &str, expected_nonce: &str) -> anyhow::Result<IdTokenClaims> {
+        let config = self.require_config()?;
+        let header = decode_header(id_token).context("Malformed ID token")?;
+
+        let key = match header.alg {
+            Algorithm::HS256 | Algorithm::HS384 | Algorithm::HS512 => {
+                let secret = config
+                    .client_secret
+                    .as_deref()
+                    .context("HMAC-signed ID tokens require a client secret")?;
+                DecodingKey::from_secret(secret.as_bytes())
+            }
+            _ => {
+                let jwk = self.signing_key(header.kid.as_deref()).await?;
+                DecodingKey::from_jwk(&jwk).context("Unusable signing key")?
+            }
+        };
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[config.issuer.as_str(), config.issuer.trim_end_matches('/')]);
+        validation.set_audience(&[config.client_id.as_str()]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+        validation.leeway = config.leeway_seconds;
+
+        let claims = decode::<Value>(id_token, &key, &validation)
+            .context("ID token validation failed")?
+            .claims;
+
+        let claim_str = |name: &str| claims.get(name).and_then(Value::as_str).map(str::to_string);
+
+        if claim_str("nonce").as_deref() != Some(expected_nonce) {
+            anyhow::bail!("ID token nonce does not match the login");
+        }
+
+        // With several audiences the token must name this client as the
+        // authorized party.
+        let multiple_audiences = claims
+            .get("aud")
+            .and_then(Value::as_array)
+            .map_or(false, |aud| aud.len() > 1);
+        if multiple_audiences || claims.get("azp").is_some() {
+            if claim_str("azp").as_deref() != Some(config.client_id.as_str()) {
+                anyhow::bail!("ID token was not issued to this client");
+            }
+        }
+
+        let groups = match claims.get(&config.groups_claim) {
+            Some(Value::Array(groups)) => groups
+                .iter()
+                .filter_map(|g| g.as_str().map(str::to_string))
+                .collect(),
+            Some(Value::String(group)) => vec![group.clone()],
+            _ => Vec::new(),
+        };
+
+        Ok(IdTokenClaims {
+            issuer: claim_str("iss").unwrap_or_default(),
+            subject: claim_str("sub").context("ID token has no subject")?,
+            email: claim_str("email"),
+            email_verified: match claims.get("email_verified") {
+                Some(Value::Bool(verified)) => *verified,
+                Some(Value::String(verified)) => verified == "true",
+                _ => false,
+            },
+            name: claim_str("name"),
+            given_name: claim_str("given_name"),
+            family_name: claim_str("family_name"),
+            groups,
+        })
+    }
+}
@@ -0,0 +1,129 @@
+use chrono::{Duration, Utc};
+use rocket::http::Status;
+use rocket::response::Redirect;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use sqlx::mysql::MySqlPool as Pool;
+
+use super::super::super::db::queries as db;
+use super::client::OidcClient;
+use super::pkce::{code_challenge, random_token};
+
+/// How long a user has to complete the login at the identity provider.
+const LOGIN_TIMEOUT_MINUTES: i64 = 10;
+
+/// Start a single sign-on login.
+///
+/// Redirects to the identity provider's authorization endpoint. After a
+/// successful login the user is sent to `redirect_to`, if given, which must be
+/// a path on this server.
+#[get("/auth/oidc/login?<redirect_to>")]
+pub async fn oidc_login(
+    redirect_to: Option<String>,
+    oidc: &State<OidcClient>,
+    pool: &State<Pool>,
+) -> Result<Redirect, (Status, Json<Value>)> {
+    if oidc.config().is_none() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "SSO not configured",
+                "message": "Single sign-on is not enabled on this server"
+            }))
+        ));
+    }
+
+    // Only local paths are accepted so the login cannot be abused as an open redirect
+    if let Some(target) = redirect_to.as_deref() {
+        if !target.starts_with('/') || target.starts_with("//") || target.contains('\\') {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid redirect",
+                    "message": "redirect_to must be a path on this server"
+                }))
+            ));
+        }
+    }
+
+    let metadata = match oidc.metadata().await {
+        Ok(metadata) => metadata,
+        Err(e) => {
+            log::error!("OIDC discovery failed: {:#}", e);
+            return Err((
+                Status::BadGateway,
+                Json(json!({
+                    "error": "Identity provider unavailable",
+                    "message": "Failed to discover the identity provider"
+                }))
+            ));
+        }
+    };
+
+    if !metadata.code_challenge_methods_supported.is_empty()
+        && !metadata.code_challenge_methods_supported.iter().any(|m| m == "S256")
+    {
+        log::error!("Identity provider does not support S256 PKCE challenges");
+        return Err((
+            Status::BadGateway,
+            Json(json!({
+                "error": "Identity provider unsupported",
+                "message": "The identity provider does not support PKCE with S256"
+            }))
+        ));
+    }
+
+    let generated = (|| -> anyhow::Result<_> {
+        Ok((random_token(32)?, random_token(32)?, random_token(32)?))
+    })();
+    let (state, nonce, code_verifier) = match generated {
+        Ok(values) => values,
+        Err(e) => {
+            log::error!("Failed to generate OIDC login parameters: {}", e);
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Internal error",
+                    "message": "Failed to start login"
+                }))
+            ));
+        }
+    };
+
+    if let Err(e) = db::sso::purge_expired_oidc_login_states(pool).await {
+        log::warn!("Failed to purge expired OIDC login states: {}", e);
+    }
+
+    let expires_at = Utc::now() + Duration::minutes(LOGIN_TIMEOUT_MINUTES);
+    if let Err(e) = db::sso::create_oidc_login_state(
+        pool,
+        &state,
+        &nonce,
+        &code_verifier,
+        redirect_to.as_deref(),
+        expires_at,
+    ).await {
+        log::error!("Failed to store OIDC login state: {}", e);
+        return Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to start login"
+            }))
+        ));
+    }
+
+    match oidc.authorization_url(&metadata, &state, &nonce, &code_challenge(&code_verifier)) {
+        Ok(url) => Ok(Redirect::to(url)),
+        Err(e) => {
+            log::error!("Failed to build OIDC authorization URL: {}", e);
+            Err((
+                Status::BadGateway,
+                Json(json!({
+                    "error": "Identity provider unsupported",
+                    "message": "The identity provider's authorization endpoint is invalid"
+                }))
+            ))
+        }
+    }
+}
@@ -0,0 +1,21 @@
+//! Single sign-on through an OpenID Connect identity provider.
+//!
+//! The server acts as an OIDC relying party using the authorization code
+//! flow with PKCE:
+//! - `GET /auth/oidc/login` redirects the browser to the identity provider
+//! - `GET /auth/oidc/callback` exchanges the returned code, validates the ID
+//!   token and signs the user in with a regular session token
+//!
+//! Users signing in for the first time are provisioned just in time, and the
+//! groups reported by the provider are mapped to organization memberships
+//! as configured under `oidc` in `config.json`.
+
+pub mod client;
+pub mod pkce;
+pub mod provisioning;
+pub mod login;
+pub mod callback;
+
+pub use client::OidcClient;
+pub use login::oidc_login;
+pub use callback::oidc_callback;
@@ -0,0 +1,247 @@
+use std::collections::BTreeMap;
+
+use rand::rngs::OsRng;
+use rand::TryRngCore;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{MySql, Pool};
+
+use crate::config::OidcConfig;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::client::IdTokenClaims;
+
+use libomni::types::db::v1 as types;
+use types::user::User;
+
+/// Reasons an identity could not be signed in.
+#[derive(Debug)]
+pub enum ProvisionError {
+    /// The ID token carries no email address for a new user.
+    MissingEmail,
+    /// A password account with the same email exists, and the provider does
+    /// not vouch for the address, so it cannot be linked safely.
+    EmailNotVerified,
+    /// The identity is unknown and automatic provisioning is disabled.
+    ProvisioningDisabled,
+    /// The linked user is deactivated.
+    Inactive,
+    Database(anyhow::Error),
+}
+
+impl ProvisionError {
+    pub fn into_response(self) -> (Status, Json<Value>) {
+        let (status, error, message) = match self {
+            ProvisionError::MissingEmail => (
+                Status::BadRequest,
+                "Missing email",
+                "The identity provider did not supply an email address".to_string(),
+            ),
+            ProvisionError::EmailNotVerified => (
+                Status::Conflict,
+                "Account exists",
+                "An account with this email already exists; sign in with your password, or verify the email address at your identity provider".to_string(),
+            ),
+            ProvisionError::ProvisioningDisabled => (
+                Status::Forbidden,
+                "Unknown user",
+                "No account is linked to this identity and automatic provisioning is disabled".to_string(),
+            ),
+            ProvisionError::Inactive => (
+                Status::Forbidden,
+                "Account is inactive",
+                "This account has been deactivated".to_string(),
+            ),
+            ProvisionError::Database(e) => {
+                log::error!("SSO provisioning failed: {:#}", e);
+                (
+                    Status::InternalServerError,
+                    "Database error",
+                    "Failed to provision user".to_string(),
+                )
+            }
+        };
+
+        (status, Json(json!({ "error": error, "message": message })))
+    }
+}
+
+/// Resolves the user an identity signs in as, creating it if necessary.
+///
+/// Known identities sign in as the user they are linked to. An unknown
+/// identity is linked to the password account with the same email only if
+/// the provider marks the email as verified; otherwise a new user is created
+/// (just in time) when `auto_provision` is enabled.
+pub async fn resolve_user(
+    pool: &Pool<MySql>,
+    config: &OidcConfig,
+    claims: &IdTokenClaims,
+) -> Result<User, ProvisionError> {
+    let identity = db::sso::get_user_identity(pool, &claims.issuer, &claims.subject)
+        .await
+        .map_err(ProvisionError::Database)?;
+
+    let user = match identity {
+        Some(identity) => {
+            let user = db::user::get_user_by_id(pool, identity.user_id)
+                .await
+                .map_err(ProvisionError::Database)?;
+            link_and_record_login(pool, &user, claims).await?
+        }
+        None => {
+            let email = claims.email.as_deref().ok_or(ProvisionError::MissingEmail)?;
+
+            match db::user::get_user_by_email(pool, email).await {
+                Ok(user) => {
+                    if !claims.email_verified {
+                        return Err(ProvisionError::EmailNotVerified);
+                    }
+                    link_and_record_login(pool, &user, claims).await?
+                }
+                Err(_) if config.auto_provision => {
+                    let (password, salt) = unusable_password().map_err(ProvisionError::Database)?;
+                    let user = db::sso::create_sso_user(
+                        pool,
+                        email,
+                        claims.email_verified,
+                        &password,
+                        &salt,
+                        claims.given_name.as_deref(),
+                        claims.family_name.as_deref(),
+                        claims.name.as_deref(),
+                        &claims.issuer,
+                        &claims.subject,
+                        &claims.groups,
+                    )
+                    .await
+                    .map_err(ProvisionError::Database)?;
+
+                    log::info!("Provisioned user {} for SSO identity {}", user.id, claims.subject);
+                    user
+                }
+                Err(_) => return Err(ProvisionError::ProvisioningDisabled),
+            }
+        }
+    };
+
+    if !user.active {
+        return Err(ProvisionError::Inactive);
+    }
+
+    Ok(user)
+}
+
+async fn link_and_record_login(
+    pool: &Pool<MySql>,
+    user: &User,
+    claims: &IdTokenClaims,
+) -> Result<User, ProvisionError> {
+    if !user.active {
+        return Err(ProvisionError::Inactive);
+    }
+
+    db::sso::link_user_identity(
+        pool,
+        user.id,
+        &claims.issuer,
+        &claims.subject,
+        claims.email.as_deref(),
+        &claims.groups,
+    )
+    .await
+    .map_err(ProvisionError::Database)?;
+
+    db::user::record_login_attempt(pool, user.id, true)
+        .await
+        .map_err(ProvisionError::Database)
+}
+
+/// Generates a random password hash that nobody knows the password for.
+fn unusable_password() -> anyhow::Result<(String, String)> {
+    let mut secret = [0u8; 32];
+    let mut salt = [0u8; 16];
+    OsRng.try_fill_bytes(&mut secret)?;
+    OsRng.try_fill_bytes(&mut salt)?;
+
+    let salt_hex = hex::encode(salt);
+    let mut hasher = Sha256::new();
+    hasher.update(format!("{}{}", hex::encode(secret), salt_hex).as_bytes());
+    Ok((hex::encode(hasher.finalize()), salt_hex))
+}
+
+/// Applies the configured group mappings to a user's organization
+/// memberships.
+///
+/// For every organization named in a mapping the user receives the most
+/// privileged role of the groups they are in. Memberships of mapped
+/// organizations are removed when the user is in none of their groups and
+/// `remove_unmapped_members` is set. Failures are logged and do not prevent
+/// the login.
+pub async fn sync_group_memberships(
+    db_manager: &DatabaseManager,
+    config: &OidcConfig,
+    user_id: i64,
+    groups: &[String],
+) {
+    // (platform_id, org_id) -> most privileged role granted by the user's groups
+    let mut desired: BTreeMap<(i64, i64), Option<&str>> = BTreeMap::new();
+    for mapping in &config.group_mappings {
+        let entry = desired.entry((mapping.platform_id, mapping.org_id)).or_insert(None);
+        if !groups.iter().any(|g| g == &mapping.group) {
+            continue;
+        }
+        if !db::org::MEMBER_ROLES.contains(&mapping.role.as_str()) {
+            log::warn!("Ignoring SSO group mapping for '{}' with unknown role '{}'", mapping.group, mapping.role);
+            continue;
+        }
+        if entry.map_or(true, |current| role_rank(&mapping.role) < role_rank(current)) {
+            *entry = Some(mapping.role.as_str());
+        }
+    }
+
+    for ((platform_id, org_id), role) in desired {
+        if let Err(e) = sync_membership(db_manager, config, platform_id, org_id, user_id, role).await {
+            log::warn!(
+                "Failed to sync SSO membership of user {} in org {} on platform {}: {:#}",
+                user_id, org_id, platform_id, e
+            );
+        }
+    }
+}
+
+async fn sync_membership(
+    db_manager: &DatabaseManager,
+    config: &OidcConfig,
+    platform_id: i64,
+    org_id: i64,
+    user_id: i64,
+    role: Option<&str>,
+) -> anyhow::Result<()> {
+    let platform = db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await?;
+    let pool = db_manager
+        .get_platform_pool(&platform.name, platform_id)
+        .await
+        .map_err(|e| anyhow::anyhow!("{:?}", e))?;
+
+    let current = db::org::get_org_member_role(&pool, org_id, user_id).await?;
+
+    match (current.as_deref(), role) {
+        (None, Some(role)) => db::org::add_org_member(&pool, org_id, user_id, role).await,
+        (Some(current), Some(role)) if current != role => {
+            db::org::update_org_member_role(&pool, org_id, user_id, role).await
+        }
+        (Some(_), None) if config.remove_unmapped_members => {
+            db::org::remove_org_member(&pool, org_id, user_id).await
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Position of a membership role in `MEMBER_ROLES`; lower is more privileged.
+fn role_rank(role: &str) -> usize {
+    db::org::MEMBER_ROLES
+        .iter()
+        .position(|r| *r == role)
+        .unwrap_or(usize::MAX)
+}
@@ -5,6 +5,7 @@ use rocket::http::Status;
 use rocket::serde::json::{json, Json, Value};
 use rocket::{get, State};
 use crate::schemas::v1::db::queries::{self as db};
+use super::rbac::{require_in_scope, Caller, Require, RequestScope, StorageRead};
 
 /// Query parameters for storage class listing
 #[derive(FromForm, Default, Debug)]
@@ -30,6 +31,7 @@ pub struct StorageVolumeQuery {
 /// List all storage classes with optional filtering
 #[get("/platform/<platform_id>/storage/classes?<query..>")]
 pub async fn list_storage_classes(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     query: StorageClassQuery,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -85,6 +87,7 @@ pub async fn list_storage_classes(
 /// Get a specific storage class by ID
 #[get("/platform/<platform_id>/storage/classes/<id>")]
 pub async fn get_storage_class(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
@@ -141,10 +144,17 @@ pub async fn get_storage_class(
 /// List storage volumes with comprehensive filtering
 #[get("/platform/<platform_id>/storage/volumes?<query..>")]
 pub async fn list_storage_volumes(
+    caller: Caller,
     platform_id: i64,
     query: StorageVolumeQuery,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
+    require_in_scope::<StorageRead>(db_manager, &caller, RequestScope {
+        platform_id: Some(platform_id),
+        app_id: query.app_id,
+        ..Default::default()
+    }).await?;
+
     // Get platform information
     let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
         Ok(platform) => platform,
@@ -227,6 +237,7 @@ pub async fn list_storage_volumes(
 /// Get volumes by storage class
 #[get("/platform/<platform_id>/storage/classes/<id>/volumes?<page>&<per_page>")]
 pub async fn get_volumes_by_storage_class(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     id: i64,
     page: Option<i64>,
@@ -334,6 +345,7 @@ pub async fn get_volumes_by_storage_class(
 /// Get QoS policies
 #[get("/platform/<platform_id>/storage/qos-policies")]
 pub async fn list_qos_policies(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     db_manager: &State<Arc<DatabaseManager>>,
 ) -> Result<Json<Value>, (Status, Json<Value>)> {
@@ -382,6 +394,7 @@ pub async fn list_qos_policies(
 /// List volumes by write concern level
 #[get("/platform/<platform_id>/storage/write-concerns/<write_concern>/volumes?<page>&<per_page>")]
 pub async fn list_volumes_by_write_concern(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     write_concern: String,
     page: Option<i64>,
@@ -466,6 +479,7 @@ pub async fn list_volumes_by_write_concern(
 /// List volumes by persistence level
 #[get("/platform/<platform_id>/storage/persistence-levels/<persistence_level>/volumes?<page>&<per_page>")]
 pub async fn list_volumes_by_persistence_level(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     persistence_level: String,
     page: Option<i64>,
@@ -550,6 +564,7 @@ pub async fn list_volumes_by_persistence_level(
 /// Get storage volumes for a specific region, grouped by region, with pagination
 #[get("/platform/<platform_id>/storage/regions/<region_id>/volumes?<page>&<per_page>")]
 pub async fn get_volumes_for_region_route(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     region_id: i64,
     page: Option<i64>,
@@ -630,6 +645,7 @@ pub async fn get_volumes_for_region_route(
 /// Get storage volumes for a specific provider, with pagination
 #[get("/platform/<platform_id>/storage/providers/<provider_id>/volumes?<page>&<per_page>")]
 pub async fn get_storage_volumes_for_provider(
+    _auth: Require<StorageRead>,
     platform_id: i64,
     provider_id: i64,
     page: Option<i64>,
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::helpers::tail::{self, LastEventId, Tail, TailStatus};
+use super::super::rbac::{Require, TasksRead};
+use super::run::find_task;
+use db::task_log::TaskLogLine;
+use rocket::http::Status;
+use rocket::response::stream::EventStream;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, Either, Shutdown, State};
+use sqlx::{MySql, Pool};
+
+/// Most lines returned by a single read.
+const MAX_READ_LIMIT: i64 = 5000;
+
+/// Read the output of a task, oldest first.
+///
+/// Lines after the line with ID `after` are returned, at most `limit` of
+/// them; `next_after` continues from the last one. Output is copied from
+/// the task's container every few seconds while it runs. With
+/// `follow=true` the output is streamed as server-sent events instead:
+/// every line is sent as a `log` event whose ID is the line's ID, and an
+/// `end` event with the task's final status and exit code closes the
+/// stream once the task has finished.
+#[get("/platform/<platform_id>/apps/<app_id>/tasks/<task_id>/logs?<after>&<limit>&<follow>")]
+pub async fn get_task_logs(
+    _auth: Require<TasksRead>,
+    platform_id: i64,
+    app_id: i64,
+    task_id: i64,
+    after: Option<i64>,
+    limit: Option<i64>,
+    follow: Option<bool>,
+    last_event_id: LastEventId,
+    db_manager: &State<Arc<DatabaseManager>>,
+    shutdown: Shutdown,
+) -> Result<Either<Json<Value>, EventStream![]>, (Status, Json<Value>)> {
+    let limit = limit.unwrap_or(500);
+    if !(1..=MAX_READ_LIMIT).contains(&limit) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!("limit must be between 1 and {}", MAX_READ_LIMIT)
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let task = find_task(&pool, app_id, task_id).await?;
+
+    if !follow.unwrap_or(false) {
+        let after = after.unwrap_or(0);
+        return match db::task_log::list_task_log_lines_after(&pool, task_id, after, limit).await {
+            Ok(lines) => {
+                let next_after = lines.last().map(|line| line.id).unwrap_or(after);
+                Ok(Either::Left(Json(json!({
+                    "task_id": task_id,
+                    "status": task.status,
+                    "exit_code": task.exit_code,
+                    "lines": lines,
+                    "next_after": next_after
+                }))))
+            }
+            Err(_) => Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch task logs"
+                }))
+            )),
+        };
+    }
+
+    let after = last_event_id.0.or(after).unwrap_or(0);
+    Ok(Either::Right(tail::follow(TaskTail { pool, task_id }, after, shutdown)))
+}
+
+/// The output of a task, followed until the task has finished.
+struct TaskTail {
+    pool: Pool<MySql>,
+    task_id: i64,
+}
+
+#[rocket::async_trait]
+impl Tail for TaskTail {
+    type Entry = TaskLogLine;
+
+    const EVENT: &'static str = "log";
+    const RESOURCE: &'static str = "task";
+    const ENTRIES: &'static str = "logs";
+
+    fn id(&self) -> i64 {
+        self.task_id
+    }
+
+    async fn status(&self) -> anyhow::Result<Option<TailStatus>> {
+        let task = db::task::get_task(&self.pool, self.task_id).await?;
+        Ok(task.map(|task| TailStatus {
+            finished: db::task::is_finished(task.status.as_deref()),
+            end: json!({
+                "task_id": self.task_id,
+                "status": task.status,
+                "exit_code": task.exit_code
+            }),
+        }))
+    }
+
+    async fn entries_after(&self, after: i64, limit: i64) -> anyhow::Result<Vec<TaskLogLine>> {
+        db::task_log::list_task_log_lines_after(&self.pool, self.task_id, after, limit).await
+    }
+
+    fn entry_id(line: &TaskLogLine) -> i64 {
+        line.id
+    }
+}
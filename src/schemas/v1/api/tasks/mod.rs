@@ -0,0 +1,19 @@
+//! Task module for one-off and scheduled commands of applications.
+//!
+//! This module provides a REST API for:
+//! - Running one-off tasks against the current release of an application
+//! - Following the status and output of tasks and canceling them
+//! - Managing cron schedules that create tasks periodically
+
+// Import and re-export all route modules
+pub mod run;
+pub mod logs;
+pub mod schedules;
+pub mod types;
+
+// Re-export all route functions
+pub use run::{cancel_task, get_task, list_tasks, run_task};
+pub use logs::get_task_logs;
+pub use schedules::{
+    create_task_schedule, delete_task_schedule, get_task_schedule, list_task_schedules, update_task_schedule,
+};
@@ -0,0 +1,373 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::super::rbac::{Require, TasksRead, TasksWrite};
+use super::types::RunTaskRequest;
+use db::task::{NewTask, Task};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, post, State};
+use sqlx::{MySql, Pool};
+
+/// Statuses tasks can be filtered by.
+const TASK_STATUSES: [&str; 5] = ["pending", "running", "succeeded", "failed", "canceled"];
+/// Longest a task may be allowed to run, one day.
+const MAX_TIMEOUT_SECONDS: i64 = 86_400;
+/// Longest command accepted.
+const MAX_COMMAND_LENGTH: usize = 65_535;
+
+/// Run a one-off task against the current release of an application.
+///
+/// The command runs once in a new container of the application's image
+/// with the environment of its current deployment. The task is launched by
+/// the leader within a few seconds; follow it through its status and logs.
+#[post("/platform/<platform_id>/apps/<app_id>/tasks", format = "json", data = "<request>")]
+pub async fn run_task(
+    auth: Require<TasksWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<RunTaskRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Task>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let request = request.into_inner();
+    let task = NewTask {
+        command: request.command.trim().to_string(),
+        name: request.name.map(|name| name.trim().to_string()).filter(|name| !name.is_empty()),
+        memory_in_mb: request.memory_in_mb,
+        disk_in_mb: request.disk_in_mb,
+        cpu: request.cpu,
+        timeout_seconds: request.timeout_seconds,
+        schedule_id: None,
+    };
+    validate_task(&task).map_err(|message| {
+        (
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": message
+            }))
+        )
+    })?;
+
+    let app = match db::app::get_app_by_id(&pool, app_id).await {
+        Ok(app) => app,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "App not found",
+                    "message": format!("App with ID {} does not exist", app_id)
+                }))
+            ));
+        }
+    };
+    if app.container_image_url.is_none() {
+        return Err((
+            Status::UnprocessableEntity,
+            Json(json!({
+                "error": "No release",
+                "message": format!("App {} has no container image to run tasks with", app_id)
+            }))
+        ));
+    }
+
+    match db::task::create_task(&pool, app_id, &task, Some(auth.user_id())).await {
+        Ok(task) => {
+            trail.resource("task", task.id);
+            trail.after(&task);
+            Ok(Json(task))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to create task"
+            }))
+        )),
+    }
+}
+
+/// List the tasks of an application with pagination, newest first,
+/// optionally only those with the given `status`.
+#[get("/platform/<platform_id>/apps/<app_id>/tasks?<status>&<page>&<per_page>")]
+pub async fn list_tasks(
+    _auth: Require<TasksRead>,
+    platform_id: i64,
+    app_id: i64,
+    status: Option<String>,
+    page: Option<u32>,
+    per_page: Option<u32>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    if let Some(status) = &status {
+        if !TASK_STATUSES.contains(&status.as_str()) {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": format!("status must be one of: {}", TASK_STATUSES.join(", "))
+                }))
+            ));
+        }
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let page: i64 = page.unwrap_or(0).into();
+    let per_page: i64 = per_page.unwrap_or(10).clamp(1, 100).into();
+    let offset = page * per_page;
+
+    let tasks = match db::task::list_app_tasks_paginated(&pool, app_id, status.as_deref(), per_page, offset).await {
+        Ok(tasks) => tasks,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to list tasks"
+                }))
+            ));
+        }
+    };
+
+    let total_count = match db::task::count_app_tasks(&pool, app_id, status.as_deref()).await {
+        Ok(count) => count,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to count tasks"
+                }))
+            ));
+        }
+    };
+
+    let total_pages = (total_count as f64 / per_page as f64).ceil() as i64;
+
+    Ok(Json(json!({
+        "tasks": tasks,
+        "pagination": {
+            "page": page,
+            "per_page": per_page,
+            "total_count": total_count,
+            "total_pages": total_pages
+        }
+    })))
+}
+
+/// Get a task of an application, including its status and exit code.
+#[get("/platform/<platform_id>/apps/<app_id>/tasks/<task_id>")]
+pub async fn get_task(
+    _auth: Require<TasksRead>,
+    platform_id: i64,
+    app_id: i64,
+    task_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Task>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_task(&pool, app_id, task_id).await.map(Json)
+}
+
+/// Cancel a pending or running task. The container of a running task is
+/// stopped by the leader shortly after.
+#[post("/platform/<platform_id>/apps/<app_id>/tasks/<task_id>/cancel")]
+pub async fn cancel_task(
+    auth: Require<TasksWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    task_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Task>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let task = find_task(&pool, app_id, task_id).await?;
+    trail.resource("task", task_id);
+    trail.before(&task);
+
+    let reason = format!("Task canceled by user {}", auth.user_id());
+    match db::task::cancel_task(&pool, task_id, &reason).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err((
+                Status::Conflict,
+                Json(json!({
+                    "error": "Invalid task state",
+                    "message": format!(
+                        "Task {} is {} and cannot be canceled",
+                        task_id,
+                        task.status.as_deref().unwrap_or("unknown")
+                    )
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to cancel task"
+                }))
+            ));
+        }
+    }
+
+    let canceled = find_task(&pool, app_id, task_id).await?;
+    trail.after(&canceled);
+    Ok(Json(canceled))
+}
+
+/// Fetches a task of an application, failing with `404` if there is none.
+pub(super) async fn find_task(pool: &Pool<MySql>, app_id: i64, task_id: i64) -> Result<Task, (Status, Json<Value>)> {
+    match db::task::get_task(pool, task_id).await {
+        Ok(Some(task)) if task.app_id == app_id => Ok(task),
+        Ok(_) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Task not found",
+                "message": format!("App {} has no task with ID {}", app_id, task_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch task"
+            }))
+        )),
+    }
+}
+
+/// Checks the command and resource limits of a task, describing the first
+/// problem found.
+pub(super) fn validate_task(task: &NewTask) -> Result<(), String> {
+    if task.command.is_empty() || task.command.len() > MAX_COMMAND_LENGTH {
+        return Err(format!("command must be between 1 and {} characters", MAX_COMMAND_LENGTH));
+    }
+    if task.name.as_ref().is_some_and(|name| name.len() > 255) {
+        return Err("name must be at most 255 characters".to_string());
+    }
+    if task.memory_in_mb.is_some_and(|memory| memory < 1) {
+        return Err("memory_in_mb must be positive".to_string());
+    }
+    if task.disk_in_mb.is_some_and(|disk| disk < 1) {
+        return Err("disk_in_mb must be positive".to_string());
+    }
+    if task.cpu.is_some_and(|cpu| !cpu.is_finite() || cpu <= 0.0) {
+        return Err("cpu must be positive".to_string());
+    }
+    if task.timeout_seconds.is_some_and(|timeout| !(1..=MAX_TIMEOUT_SECONDS).contains(&timeout)) {
+        return Err(format!("timeout_seconds must be between 1 and {}", MAX_TIMEOUT_SECONDS));
+    }
+    Ok(())
+}
@@ -0,0 +1,367 @@
+use std::sync::Arc;
+use chrono::Utc;
+use crate::DatabaseManager;
+use crate::tasks::cron::CronSchedule;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::super::rbac::{Require, TasksRead, TasksWrite};
+use super::run::validate_task;
+use super::types::TaskScheduleRequest;
+use db::task::NewTask;
+use db::task_schedule::{TaskSchedule, TaskScheduleInput};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, put, State};
+use sqlx::{MySql, Pool};
+
+/// List the task schedules of an application.
+#[get("/platform/<platform_id>/apps/<app_id>/task-schedules")]
+pub async fn list_task_schedules(
+    _auth: Require<TasksRead>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<TaskSchedule>>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::task_schedule::list_app_schedules(&pool, app_id).await {
+        Ok(schedules) => Ok(Json(schedules)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch task schedules"
+            }))
+        )),
+    }
+}
+
+/// Get a task schedule of an application, including when it runs next.
+#[get("/platform/<platform_id>/apps/<app_id>/task-schedules/<schedule_id>")]
+pub async fn get_task_schedule(
+    _auth: Require<TasksRead>,
+    platform_id: i64,
+    app_id: i64,
+    schedule_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<TaskSchedule>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    find_schedule(&pool, app_id, schedule_id).await.map(Json)
+}
+
+/// Create a task schedule of an application. Each time the cron expression
+/// matches, in UTC, the leader creates a task running the schedule's
+/// command.
+#[post("/platform/<platform_id>/apps/<app_id>/task-schedules", format = "json", data = "<request>")]
+pub async fn create_task_schedule(
+    auth: Require<TasksWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<TaskScheduleRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<TaskSchedule>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    if db::app::get_app_by_id(&pool, app_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        ));
+    }
+
+    let input = schedule_input(request.into_inner())?;
+    match db::task_schedule::create_schedule(&pool, app_id, &input, Some(auth.user_id())).await {
+        Ok(schedule) => {
+            trail.resource("task_schedule", schedule.id);
+            trail.after(&schedule);
+            Ok(Json(schedule))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to create task schedule"
+            }))
+        )),
+    }
+}
+
+/// Replace a task schedule of an application. Its next run is computed
+/// afresh from the new cron expression; tasks it already created are not
+/// affected.
+#[put("/platform/<platform_id>/apps/<app_id>/task-schedules/<schedule_id>", format = "json", data = "<request>")]
+pub async fn update_task_schedule(
+    _auth: Require<TasksWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    schedule_id: i64,
+    request: Json<TaskScheduleRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<TaskSchedule>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let existing = find_schedule(&pool, app_id, schedule_id).await?;
+    trail.resource("task_schedule", schedule_id);
+    trail.before(&existing);
+
+    let input = schedule_input(request.into_inner())?;
+    match db::task_schedule::update_schedule(&pool, app_id, schedule_id, &input).await {
+        Ok(Some(schedule)) => {
+            trail.after(&schedule);
+            Ok(Json(schedule))
+        }
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Task schedule not found",
+                "message": format!("App {} has no task schedule with ID {}", app_id, schedule_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to update task schedule"
+            }))
+        )),
+    }
+}
+
+/// Delete a task schedule of an application. Tasks it created are kept.
+#[delete("/platform/<platform_id>/apps/<app_id>/task-schedules/<schedule_id>")]
+pub async fn delete_task_schedule(
+    _auth: Require<TasksWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    schedule_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let schedule = find_schedule(&pool, app_id, schedule_id).await?;
+    trail.resource("task_schedule", schedule_id);
+    trail.before(&schedule);
+
+    match db::task_schedule::delete_schedule(&pool, app_id, schedule_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to delete task schedule"
+            }))
+        )),
+    }
+}
+
+/// Fetches a task schedule of an application, failing with `404` if there
+/// is none.
+async fn find_schedule(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    schedule_id: i64,
+) -> Result<TaskSchedule, (Status, Json<Value>)> {
+    match db::task_schedule::get_schedule(pool, app_id, schedule_id).await {
+        Ok(Some(schedule)) => Ok(schedule),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Task schedule not found",
+                "message": format!("App {} has no task schedule with ID {}", app_id, schedule_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch task schedule"
+            }))
+        )),
+    }
+}
+
+/// Validates a schedule request and computes the schedule's first run.
+fn schedule_input(request: TaskScheduleRequest) -> Result<TaskScheduleInput, (Status, Json<Value>)> {
+    let invalid = |message: String| {
+        (
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": message
+            }))
+        )
+    };
+
+    let name = request.name.trim().to_string();
+    if name.is_empty() || name.len() > 255 {
+        return Err(invalid("name must be between 1 and 255 characters".to_string()));
+    }
+    let task = NewTask {
+        command: request.command.trim().to_string(),
+        name: Some(name),
+        memory_in_mb: request.memory_in_mb,
+        disk_in_mb: request.disk_in_mb,
+        cpu: request.cpu,
+        timeout_seconds: request.timeout_seconds,
+        schedule_id: None,
+    };
+    validate_task(&task).map_err(invalid)?;
+
+    let cron_expression = request.cron_expression.trim().to_string();
+    let cron = CronSchedule::parse(&cron_expression).map_err(invalid)?;
+    let next_run_at = match cron.next_after(Utc::now()) {
+        Some(next_run_at) => next_run_at,
+        None => return Err(invalid(format!("cron expression '{}' never matches", cron_expression))),
+    };
+    let enabled = request.enabled.unwrap_or(true);
+
+    Ok(TaskScheduleInput {
+        name: task.name.unwrap_or_default(),
+        command: task.command,
+        cron_expression,
+        memory_in_mb: task.memory_in_mb,
+        disk_in_mb: task.disk_in_mb,
+        cpu: task.cpu,
+        timeout_seconds: task.timeout_seconds,
+        enabled,
+        allow_overlap: request.allow_overlap.unwrap_or(false),
+        next_run_at: enabled.then_some(next_run_at),
+    })
+}
@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Request body for running a one-off task.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunTaskRequest {
+    /// Shell command to run, such as `bin/rails db:migrate`
+    pub command: String,
+    pub name: Option<String>,
+    pub memory_in_mb: Option<i64>,
+    pub disk_in_mb: Option<i64>,
+    pub cpu: Option<f64>,
+    /// Seconds the task may run before it is stopped; 3600 if unset
+    pub timeout_seconds: Option<i64>,
+}
+
+/// Request body for creating or replacing a task schedule.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskScheduleRequest {
+    pub name: String,
+    pub command: String,
+    /// Five-field cron expression or shortcut such as `@daily`, in UTC
+    pub cron_expression: String,
+    pub memory_in_mb: Option<i64>,
+    pub disk_in_mb: Option<i64>,
+    pub cpu: Option<f64>,
+    /// Seconds each task may run before it is stopped; 3600 if unset
+    pub timeout_seconds: Option<i64>,
+    /// true if unset
+    pub enabled: Option<bool>,
+    /// Whether a run starts a task while the previous one has not
+    /// finished; false if unset
+    pub allow_overlap: Option<bool>,
+}
@@ -23,6 +23,7 @@ use uuid::Uuid;
 use libomni::types::db::v1 as types;
 use libomni::types::db::auth::{AuthConfig, Claims};
 use types::user::User;
+use super::rbac::{Require, UsersRead};
 
 /// Register a new user
 #[post("/auth/register", data = "<data>")]
@@ -449,7 +450,7 @@ pub async fn logout(
 }
 
 /// Helper function to create a JWT token and session
-async fn create_auth_token_and_session(
+pub(crate) async fn create_auth_token_and_session(
     pool: &State<Pool>,
     user: &User,
     auth_config: &State<AuthConfig>,
@@ -688,6 +689,7 @@ pub async fn update_user_profile(
 /// List  all users
 #[get("/users?<page>&<per_page>")]
 pub async fn list_users(
+    _auth: Require<UsersRead>,
     page: Option<i64>,
     per_page: Option<i64>,
     pool: &State<Pool>,
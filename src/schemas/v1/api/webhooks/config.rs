@@ -0,0 +1,358 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::super::rbac::{AppsRead, AppsWrite, Require};
+use super::types::AppWebhookRequest;
+use db::webhook::{AppWebhook, AppWebhookInput};
+use rand::rngs::OsRng;
+use rand::TryRngCore;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, put, State};
+
+use crate::deployer::strategy::STRATEGIES;
+
+/// Shortest secret accepted when one is chosen by the caller.
+const MIN_SECRET_LENGTH: usize = 16;
+
+/// Path deliveries to the webhook of an application are sent to.
+pub fn webhook_url(platform_id: i64, app_id: i64) -> String {
+    format!("/api/v1/platform/{}/apps/{}/webhook", platform_id, app_id)
+}
+
+/// Generates a random webhook secret.
+fn generate_secret() -> anyhow::Result<String> {
+    let mut secret = [0u8; 32];
+    OsRng.try_fill_bytes(&mut secret)?;
+    Ok(hex::encode(secret))
+}
+
+/// Describes a webhook, with its secret only if it was just set.
+fn webhook_response(platform_id: i64, webhook: &AppWebhook, secret: Option<&str>) -> Value {
+    let mut response = json!(webhook);
+    response["url"] = json!(webhook_url(platform_id, webhook.app_id));
+    if let Some(secret) = secret {
+        response["secret"] = json!(secret);
+    }
+    response
+}
+
+/// Get the repository webhook of an application, without its secret.
+#[get("/platform/<platform_id>/apps/<app_id>/webhook")]
+pub async fn get_app_webhook(
+    _auth: Require<AppsRead>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let webhook = find_webhook(&pool, app_id).await?;
+    Ok(Json(webhook_response(platform_id, &webhook, None)))
+}
+
+/// Set up the repository webhook of an application.
+///
+/// Pushes to the application's `git_branch` delivered to the returned `url`
+/// queue a build of the pushed commit, deployed once it succeeds if
+/// `auto_deploy` is set. The secret is only returned when it is set: a new
+/// webhook gets a generated one unless the request carries one.
+#[put("/platform/<platform_id>/apps/<app_id>/webhook", format = "json", data = "<request>")]
+pub async fn set_app_webhook(
+    auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    request: Json<AppWebhookRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let request = request.into_inner();
+    let deployment_strategy = request.deployment_strategy.unwrap_or_else(|| "rolling".to_string());
+    if !STRATEGIES.contains(&deployment_strategy.as_str()) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": format!(
+                    "Unknown deployment_strategy '{}'; use one of {}",
+                    deployment_strategy,
+                    STRATEGIES.join(", ")
+                )
+            }))
+        ));
+    }
+    if let Some(secret) = &request.secret {
+        if secret.len() < MIN_SECRET_LENGTH || secret.len() > 255 {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": format!("secret must be between {} and 255 characters", MIN_SECRET_LENGTH)
+                }))
+            ));
+        }
+    }
+
+    if db::app::get_app_by_id(&pool, app_id).await.is_err() {
+        return Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "App not found",
+                "message": format!("App with ID {} does not exist", app_id)
+            }))
+        ));
+    }
+
+    let existing = match db::webhook::get_app_webhook(&pool, app_id).await {
+        Ok(existing) => existing,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch app webhook"
+                }))
+            ));
+        }
+    };
+    if let Some(existing) = &existing {
+        trail.before(existing);
+    }
+    let secret = match (request.secret, &existing) {
+        (Some(secret), _) => Some(secret),
+        (None, Some(_)) => None,
+        (None, None) => match generate_secret() {
+            Ok(secret) => Some(secret),
+            Err(_) => {
+                return Err((
+                    Status::InternalServerError,
+                    Json(json!({
+                        "error": "Internal error",
+                        "message": "Failed to generate webhook secret"
+                    }))
+                ));
+            }
+        },
+    };
+
+    let input = AppWebhookInput {
+        auto_deploy: request.auto_deploy.unwrap_or(false),
+        deployment_strategy,
+        enabled: request.enabled.unwrap_or(true),
+    };
+    match db::webhook::set_app_webhook(&pool, app_id, &input, secret.as_deref(), Some(auth.user_id())).await {
+        Ok(webhook) => {
+            trail.resource("app_webhook", webhook.id);
+            trail.after(&webhook);
+            if secret.is_some() {
+                trail.detail("secret_changed", true);
+            }
+            Ok(Json(webhook_response(platform_id, &webhook, secret.as_deref())))
+        }
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to save app webhook"
+            }))
+        )),
+    }
+}
+
+/// Replace the secret of an application's webhook with a generated one.
+///
+/// Deliveries signed with the previous secret are rejected from then on.
+#[post("/platform/<platform_id>/apps/<app_id>/webhook/rotate-secret")]
+pub async fn rotate_app_webhook_secret(
+    _auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let webhook = find_webhook(&pool, app_id).await?;
+    trail.resource("app_webhook", webhook.id);
+
+    let secret = match generate_secret() {
+        Ok(secret) => secret,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Internal error",
+                    "message": "Failed to generate webhook secret"
+                }))
+            ));
+        }
+    };
+    match db::webhook::rotate_app_webhook_secret(&pool, app_id, &secret).await {
+        Ok(_) => Ok(Json(webhook_response(platform_id, &webhook, Some(&secret)))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to rotate app webhook secret"
+            }))
+        )),
+    }
+}
+
+/// Remove the repository webhook of an application. Later deliveries are
+/// rejected.
+#[delete("/platform/<platform_id>/apps/<app_id>/webhook")]
+pub async fn delete_app_webhook(
+    _auth: Require<AppsWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let webhook = find_webhook(&pool, app_id).await?;
+    trail.resource("app_webhook", webhook.id);
+    trail.before(&webhook);
+
+    match db::webhook::delete_app_webhook(&pool, app_id).await {
+        Ok(_) => Ok(Json(json!({ "status": "deleted" }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to delete app webhook"
+            }))
+        )),
+    }
+}
+
+async fn find_webhook(
+    pool: &sqlx::Pool<sqlx::MySql>,
+    app_id: i64,
+) -> Result<AppWebhook, (Status, Json<Value>)> {
+    match db::webhook::get_app_webhook(pool, app_id).await {
+        Ok(Some(webhook)) => Ok(webhook),
+        Ok(None) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Webhook not found",
+                "message": format!("App {} has no webhook", app_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch app webhook"
+            }))
+        )),
+    }
+}
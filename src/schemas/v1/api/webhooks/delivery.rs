@@ -0,0 +1,165 @@
+use hmac::{Hmac, Mac};
+use rocket::request::{FromRequest, Outcome, Request};
+use sha2::{Digest, Sha256};
+use std::convert::Infallible;
+
+use super::super::super::db::queries::build::PushedCommit;
+use super::super::api_keys::token::hashes_match;
+use super::types::PushPayload;
+
+/// Git hosting services whose webhook deliveries are understood.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provider {
+    GitHub,
+    GitLab,
+    Gitea,
+}
+
+impl Provider {
+    pub fn name(self) -> &'static str {
+        match self {
+            Provider::GitHub => "github",
+            Provider::GitLab => "gitlab",
+            Provider::Gitea => "gitea",
+        }
+    }
+}
+
+/// The headers identifying a webhook delivery and its sender.
+///
+/// Gitea also sends GitHub's headers, so its own are checked first.
+pub struct DeliveryHeaders {
+    pub provider: Option<Provider>,
+    pub event: String,
+    /// GitHub and Gitea: hex-encoded HMAC-SHA256 of the body; GitLab: the
+    /// secret token itself
+    pub signature: Option<String>,
+    pub delivery_id: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for DeliveryHeaders {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let header = |name: &str| {
+            req.headers()
+                .get_one(name)
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+
+        let (provider, event, signature, delivery_id) = if let Some(event) = header("X-Gitea-Event") {
+            (Some(Provider::Gitea), event, header("X-Gitea-Signature"), header("X-Gitea-Delivery"))
+        } else if let Some(event) = header("X-Gitlab-Event") {
+            (Some(Provider::GitLab), event, header("X-Gitlab-Token"), header("X-Gitlab-Event-UUID"))
+        } else if let Some(event) = header("X-GitHub-Event") {
+            let signature = header("X-Hub-Signature-256")
+                .map(|signature| signature.trim_start_matches("sha256=").to_string());
+            (Some(Provider::GitHub), event, signature, header("X-GitHub-Delivery"))
+        } else {
+            (None, String::new(), None, None)
+        };
+
+        Outcome::Success(DeliveryHeaders { provider, event, signature, delivery_id })
+    }
+}
+
+impl DeliveryHeaders {
+    /// Checks that the delivery was sent by someone holding the webhook's
+    /// secret.
+    pub fn verify(&self, provider: Provider, secret: &str, body: &[u8]) -> bool {
+        let signature = match &self.signature {
+            Some(signature) if !secret.is_empty() => signature,
+            _ => return false,
+        };
+
+        match provider {
+            // Comparing digests keeps the time taken independent of the
+            // token's length as well as its contents
+            Provider::GitLab => hashes_match(
+                &hex::encode(Sha256::digest(secret.as_bytes())),
+                &hex::encode(Sha256::digest(signature.as_bytes())),
+            ),
+            Provider::GitHub | Provider::Gitea => {
+                let signature = match hex::decode(signature) {
+                    Ok(signature) => signature,
+                    Err(_) => return false,
+                };
+                let mut mac = match Hmac::<Sha256>::new_from_slice(secret.as_bytes()) {
+                    Ok(mac) => mac,
+                    Err(_) => return false,
+                };
+                mac.update(body);
+                mac.verify_slice(&signature).is_ok()
+            }
+        }
+    }
+
+    /// Whether the delivery reports a push.
+    pub fn is_push(&self, provider: Provider) -> bool {
+        match provider {
+            Provider::GitLab => self.event == "Push Hook",
+            Provider::GitHub | Provider::Gitea => self.event == "push",
+        }
+    }
+
+    /// Whether the delivery is GitHub's test of a newly added webhook.
+    pub fn is_ping(&self, provider: Provider) -> bool {
+        provider == Provider::GitHub && self.event == "ping"
+    }
+}
+
+/// What a push changed.
+#[derive(Debug, Clone)]
+pub enum Push {
+    /// A branch now points to `commit`.
+    Branch { branch: String, commit: PushedCommit },
+    /// A branch was deleted.
+    Deleted { branch: String },
+    /// A tag or other ref that is not a branch.
+    OtherRef(String),
+}
+
+/// Reads the pushed branch and its new head commit from a push payload.
+pub fn parse_push(payload: &PushPayload) -> Result<Push, String> {
+    let git_ref = payload.git_ref.as_deref().ok_or("The push payload has no ref")?;
+    let branch = match git_ref.strip_prefix("refs/heads/") {
+        Some(branch) => branch.to_string(),
+        None => return Ok(Push::OtherRef(git_ref.to_string())),
+    };
+
+    let sha = payload
+        .checkout_sha
+        .as_deref()
+        .or(payload.after.as_deref())
+        .filter(|sha| !sha.chars().all(|c| c == '0'));
+    let sha = match sha {
+        Some(sha) if payload.deleted != Some(true) => sha,
+        _ => return Ok(Push::Deleted { branch }),
+    };
+    if sha.len() < 7 || sha.len() > 64 || !sha.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(format!("'{}' is not a commit SHA", sha));
+    }
+
+    let head = payload
+        .head_commit
+        .as_ref()
+        .filter(|commit| commit.id.eq_ignore_ascii_case(sha))
+        .or_else(|| payload.commits.iter().find(|commit| commit.id.eq_ignore_ascii_case(sha)));
+    let author = head
+        .and_then(|commit| commit.author.as_ref())
+        .and_then(|author| author.display_name())
+        .or_else(|| payload.pusher.as_ref().and_then(|pusher| pusher.display_name()))
+        .or(payload.user_name.as_deref())
+        .map(str::to_string);
+
+    Ok(Push::Branch {
+        branch,
+        commit: PushedCommit {
+            sha: sha.to_ascii_lowercase(),
+            message: head.and_then(|commit| commit.message.clone()),
+            author,
+        },
+    })
+}
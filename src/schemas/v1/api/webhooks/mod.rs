@@ -0,0 +1,17 @@
+//! Repository webhook module for building applications from git pushes.
+//!
+//! This module provides a REST API for:
+//! - Setting up, rotating the secret of and removing the webhook of an
+//!   application
+//! - Receiving push deliveries from GitHub, GitLab and Gitea, which queue
+//!   builds of the pushed commits
+
+// Import and re-export all route modules
+pub mod config;
+pub mod delivery;
+pub mod receive;
+pub mod types;
+
+// Re-export all route functions
+pub use config::{delete_app_webhook, get_app_webhook, rotate_app_webhook_secret, set_app_webhook};
+pub use receive::receive_app_webhook;
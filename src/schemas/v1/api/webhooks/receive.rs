@@ -0,0 +1,276 @@
+use std::sync::Arc;
+use crate::DatabaseManager;
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::delivery::{parse_push, DeliveryHeaders, Push};
+use super::types::PushPayload;
+use rocket::data::{Data, ToByteUnit};
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+use sqlx::{MySql, Pool};
+
+/// Largest delivery accepted, in bytes. GitHub caps payloads at 25 MB but
+/// truncates the commit list long before a push gets that large.
+const MAX_DELIVERY_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Receive a delivery to the repository webhook of an application.
+///
+/// Accepts push events from GitHub, GitLab and Gitea, authenticated by the
+/// webhook's secret instead of a user: GitHub and Gitea sign the body with
+/// it, GitLab sends it as its token. A push to the application's
+/// `git_branch` ('main' if unset) queues a build of the pushed commit.
+/// Pushes to other branches, tags, branch deletions and other events are
+/// acknowledged and ignored. Redeliveries, recognized by the provider's
+/// delivery ID, are acknowledged without acting on them again.
+#[post("/platform/<platform_id>/apps/<app_id>/webhook", data = "<body>")]
+pub async fn receive_app_webhook(
+    headers: DeliveryHeaders,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    app_id: i64,
+    body: Data<'_>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let provider = match headers.provider {
+        Some(provider) => provider,
+        None => {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": "Not a GitHub, GitLab or Gitea webhook delivery"
+                }))
+            ));
+        }
+    };
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let body = match body.open(MAX_DELIVERY_SIZE.bytes()).into_bytes().await {
+        Ok(body) if body.is_complete() => body.into_inner(),
+        Ok(_) => {
+            return Err((
+                Status::PayloadTooLarge,
+                Json(json!({
+                    "error": "Payload too large",
+                    "message": format!("Deliveries may not exceed {} bytes", MAX_DELIVERY_SIZE)
+                }))
+            ));
+        }
+        Err(e) => {
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": format!("Failed to read delivery: {}", e)
+                }))
+            ));
+        }
+    };
+
+    let webhook = match db::webhook::get_app_webhook(&pool, app_id).await {
+        Ok(Some(webhook)) => webhook,
+        Ok(None) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Webhook not found",
+                    "message": format!("App {} has no webhook", app_id)
+                }))
+            ));
+        }
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch app webhook"
+                }))
+            ));
+        }
+    };
+
+    if !headers.verify(provider, &webhook.secret, &body) {
+        log::warn!("Rejected {} webhook delivery to app {} with an invalid signature", provider.name(), app_id);
+        return Err((
+            Status::Unauthorized,
+            Json(json!({
+                "error": "Unauthorized",
+                "message": "The delivery signature does not match the webhook secret"
+            }))
+        ));
+    }
+
+    trail.detail("provider", provider.name());
+    trail.detail("event", &headers.event);
+    if let Some(delivery_id) = &headers.delivery_id {
+        trail.detail("delivery_id", delivery_id);
+    }
+
+    if let Some(delivery_id) = &headers.delivery_id {
+        match db::webhook::claim_webhook_delivery(&pool, webhook.id, delivery_id).await {
+            Ok(true) => {}
+            Ok(false) => {
+                trail.skip();
+                return Ok(Json(json!({
+                    "status": "duplicate",
+                    "reason": format!("delivery {} was already received", delivery_id)
+                })));
+            }
+            Err(_) => {
+                return Err((
+                    Status::InternalServerError,
+                    Json(json!({
+                        "error": "Database error",
+                        "message": "Failed to record webhook delivery"
+                    }))
+                ));
+            }
+        }
+    }
+
+    if webhook.enabled == Some(false) {
+        return Ok(ignored(&pool, &trail, webhook.id, "the webhook is disabled".to_string()).await);
+    }
+    if headers.is_ping(provider) {
+        record(&pool, webhook.id, "pong").await;
+        trail.skip();
+        return Ok(Json(json!({ "status": "pong" })));
+    }
+    if !headers.is_push(provider) {
+        let reason = format!("{} events are not handled", headers.event);
+        return Ok(ignored(&pool, &trail, webhook.id, reason).await);
+    }
+
+    let push = match serde_json::from_slice::<PushPayload>(&body)
+        .map_err(|e| format!("Invalid push payload: {}", e))
+        .and_then(|payload| parse_push(&payload))
+    {
+        Ok(push) => push,
+        Err(message) => {
+            record(&pool, webhook.id, &format!("rejected: {}", message)).await;
+            return Err((
+                Status::BadRequest,
+                Json(json!({
+                    "error": "Invalid request",
+                    "message": message
+                }))
+            ));
+        }
+    };
+
+    let app = match db::app::get_app_by_id(&pool, app_id).await {
+        Ok(app) if app.deleted_at.is_none() => app,
+        _ => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "App not found",
+                    "message": format!("App with ID {} does not exist", app_id)
+                }))
+            ));
+        }
+    };
+    let tracked_branch = app.git_branch.as_deref().filter(|branch| !branch.is_empty()).unwrap_or("main");
+
+    let (branch, commit) = match push {
+        Push::Branch { branch, commit } if branch == tracked_branch => (branch, commit),
+        Push::Branch { branch, .. } => {
+            let reason = format!("push to {}, which is not the tracked branch {}", branch, tracked_branch);
+            return Ok(ignored(&pool, &trail, webhook.id, reason).await);
+        }
+        Push::Deleted { branch } => {
+            return Ok(ignored(&pool, &trail, webhook.id, format!("branch {} was deleted", branch)).await);
+        }
+        Push::OtherRef(git_ref) => {
+            return Ok(ignored(&pool, &trail, webhook.id, format!("{} is not a branch", git_ref)).await);
+        }
+    };
+
+    let auto_deploy_strategy = match webhook.auto_deploy {
+        Some(true) => Some(webhook.deployment_strategy.as_deref().unwrap_or("rolling")),
+        _ => None,
+    };
+    let trigger = format!(
+        "Queued by a push of {} to {}{} received from {}",
+        commit.sha,
+        branch,
+        commit.author.as_deref().map(|author| format!(" by {}", author)).unwrap_or_default(),
+        provider.name()
+    );
+
+    match db::build::create_commit_build(&pool, app_id, &commit, &trigger, auto_deploy_strategy).await {
+        Ok(build) => {
+            log::info!("Queued build {} of app {} for commit {} on {}", build.id, app_id, commit.sha, branch);
+            record(&pool, webhook.id, &format!("queued build {}", build.id)).await;
+            trail.action("webhook_push");
+            trail.resource("build", build.id);
+            trail.after(&build);
+            trail.detail("branch", &branch);
+            Ok(Json(json!({
+                "status": "queued",
+                "build": build,
+                "auto_deploy": auto_deploy_strategy.is_some()
+            })))
+        }
+        Err(_) => {
+            // Let the provider's retry of the delivery through
+            if let Some(delivery_id) = &headers.delivery_id {
+                if let Err(e) = db::webhook::release_webhook_delivery(&pool, webhook.id, delivery_id).await {
+                    log::warn!("Failed to release delivery {} to webhook {}: {:#}", delivery_id, webhook.id, e);
+                }
+            }
+            Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to queue build"
+                }))
+            ))
+        }
+    }
+}
+
+/// Acknowledges a delivery that does not queue a build. Such deliveries are
+/// only noted on the webhook, not in the audit log.
+async fn ignored(pool: &Pool<MySql>, trail: &AuditTrail<'_>, webhook_id: i64, reason: String) -> Json<Value> {
+    record(pool, webhook_id, &format!("ignored: {}", reason)).await;
+    trail.skip();
+    Json(json!({
+        "status": "ignored",
+        "reason": reason
+    }))
+}
+
+async fn record(pool: &Pool<MySql>, webhook_id: i64, result: &str) {
+    if let Err(e) = db::webhook::record_webhook_delivery(pool, webhook_id, result).await {
+        log::warn!("Failed to record delivery to webhook {}: {:#}", webhook_id, e);
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// Request body for setting up the repository webhook of an application.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppWebhookRequest {
+    /// Deploy builds triggered by the webhook once they succeed
+    pub auto_deploy: Option<bool>,
+    /// Strategy of automatic deployments; 'rolling' if unset
+    pub deployment_strategy: Option<String>,
+    pub enabled: Option<bool>,
+    /// Secret deliveries are signed with; generated for a new webhook if
+    /// unset, and kept for an existing one
+    pub secret: Option<String>,
+}
+
+/// The commits of a push delivered by GitHub, GitLab or Gitea.
+///
+/// Only the fields the providers share are read; everything else in the
+/// payload is ignored.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PushPayload {
+    /// Pushed ref, such as 'refs/heads/main'
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    /// Commit the ref points to after the push; all zeros if it was deleted
+    pub after: Option<String>,
+    /// GitLab: commit the ref points to after the push
+    pub checkout_sha: Option<String>,
+    /// GitHub and Gitea: whether the ref was deleted
+    pub deleted: Option<bool>,
+    /// GitHub and Gitea: the pushed head commit
+    pub head_commit: Option<PayloadCommit>,
+    #[serde(default)]
+    pub commits: Vec<PayloadCommit>,
+    /// GitHub and Gitea: who pushed
+    pub pusher: Option<PayloadUser>,
+    /// GitLab: who pushed
+    pub user_name: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayloadCommit {
+    pub id: String,
+    pub message: Option<String>,
+    pub author: Option<PayloadUser>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PayloadUser {
+    pub name: Option<String>,
+    pub username: Option<String>,
+    pub login: Option<String>,
+}
+
+impl PayloadUser {
+    /// The name to record the user as.
+    pub fn display_name(&self) -> Option<&str> {
+        [&self.name, &self.username, &self.login]
+            .into_iter()
+            .flatten()
+            .map(|name| name.trim())
+            .find(|name| !name.is_empty())
+    }
+}
@@ -0,0 +1,353 @@
+use super::super::super::db::queries as db;
+use super::super::api_keys::token::{hash_secret, hashes_match};
+use super::super::audit_log::AuditTrail;
+use super::bootstrap::{generate_token, AGENT_TOKEN_PREFIX};
+use super::types::{RegisterWorkerRequest, WorkerHeartbeatRequest};
+use chrono::Utc;
+use db::worker::NewWorker;
+use rocket::http::Status;
+use rocket::request::{FromRequest, Outcome, Request};
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, State};
+use std::collections::HashSet;
+use std::convert::Infallible;
+use std::sync::Arc;
+
+use crate::runtime::ContainerHealth;
+use crate::{DatabaseManager, SERVER_CONFIG};
+
+/// The bearer token a worker agent sent, if any.
+///
+/// Agents do not act on behalf of a user, so they authenticate with a
+/// bootstrap token when registering and with the credential they received
+/// at registration afterwards, rather than through `Require`.
+pub struct AgentToken(Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AgentToken {
+    type Error = Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let token = req
+            .headers()
+            .get_one("Authorization")
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .map(|token| token.trim().to_string())
+            .filter(|token| !token.is_empty());
+        Outcome::Success(AgentToken(token))
+    }
+}
+
+fn unauthorized(message: &str) -> (Status, Json<Value>) {
+    (
+        Status::Unauthorized,
+        Json(json!({
+            "error": "Unauthorized",
+            "message": message
+        }))
+    )
+}
+
+fn bad_request(message: String) -> (Status, Json<Value>) {
+    (
+        Status::BadRequest,
+        Json(json!({
+            "error": "Invalid request",
+            "message": message
+        }))
+    )
+}
+
+/// Register a worker with a bootstrap token.
+///
+/// The agent sends `Authorization: Bearer <bootstrap token>` and describes
+/// its worker. A worker of the same name in the same region is updated
+/// rather than duplicated, so an agent can re-register after losing its
+/// credential. The response carries the credential for heartbeats, which
+/// replaces any earlier one and is only returned once.
+#[post("/platform/<platform_id>/workers/register", format = "json", data = "<request>")]
+pub async fn register_worker(
+    token: AgentToken,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    request: Json<RegisterWorkerRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    let token = token.0.ok_or_else(|| unauthorized("A bootstrap token is required"))?;
+    let request = request.into_inner();
+
+    if request.name.trim().is_empty() {
+        return Err(bad_request("name must not be empty".to_string()));
+    }
+    for (resource, total) in [("cpu_total", request.cpu_total), ("memory_total", request.memory_total), ("disk_total", request.disk_total)] {
+        if !(total.is_finite() && total > 0.0) {
+            return Err(bad_request(format!("{} must be greater than 0", resource)));
+        }
+    }
+    if request.labels.as_ref().is_some_and(|labels| !labels.is_object()) {
+        return Err(bad_request("labels must be an object".to_string()));
+    }
+    if request.taints.as_ref().is_some_and(|taints| !taints.is_array()) {
+        return Err(bad_request("taints must be an array".to_string()));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let bootstrap = match db::worker::get_usable_bootstrap_token(&pool, &hash_secret(&token)).await {
+        Ok(Some(bootstrap)) => bootstrap,
+        Ok(None) => return Err(unauthorized("The bootstrap token is invalid, expired, revoked or used up")),
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to verify bootstrap token"
+                }))
+            ));
+        }
+    };
+
+    let region_id = match (bootstrap.region_id, request.region_id) {
+        (Some(allowed), Some(requested)) if allowed != requested => {
+            return Err((
+                Status::Forbidden,
+                Json(json!({
+                    "error": "Forbidden",
+                    "message": format!("The bootstrap token only registers workers in region {}", allowed)
+                }))
+            ));
+        }
+        (Some(region_id), _) | (None, Some(region_id)) => region_id,
+        (None, None) => return Err(bad_request("region_id is required".to_string())),
+    };
+    if db::region::get_region_by_id(&pool, region_id).await.is_err() {
+        return Err(bad_request(format!("Region {} does not exist", region_id)));
+    }
+
+    let (credential, credential_hash) = generate_token(AGENT_TOKEN_PREFIX).map_err(|_| {
+        (
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Internal error",
+                "message": "Failed to generate worker credential"
+            }))
+        )
+    })?;
+
+    let new_worker = NewWorker {
+        name: request.name.trim().to_string(),
+        region_id,
+        provider_id: request.provider_id,
+        instance_type: request.instance_type,
+        cpu_total: request.cpu_total,
+        memory_total: request.memory_total,
+        disk_total: request.disk_total,
+        docker_version: request.docker_version,
+        labels: request.labels,
+        taints: request.taints,
+        annotations: request.annotations,
+        agent_version: request.agent_version,
+    };
+
+    match db::worker::register_worker(&pool, bootstrap.id, &new_worker, &credential_hash).await {
+        Ok(Some(worker)) => {
+            trail.resource("worker", worker.id);
+            trail.after(&worker);
+            trail.detail("bootstrap_token_id", bootstrap.id);
+            log::info!("Worker {} ({}) registered on platform {}", worker.name, worker.id, platform_id);
+            Ok(Json(json!({
+                "worker": worker,
+                "token": credential,
+                "heartbeat_interval_seconds": SERVER_CONFIG.workers.heartbeat_interval_seconds
+            })))
+        }
+        Ok(None) => Err(unauthorized("The bootstrap token is invalid, expired, revoked or used up")),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to register worker"
+            }))
+        )),
+    }
+}
+
+/// Receive a heartbeat from a worker's agent.
+///
+/// The agent sends `Authorization: Bearer <credential>` with its current
+/// capacity and the containers it runs. The response tells the agent the
+/// status of its worker, the instances placed on it, the reported
+/// containers that belong to none of them and the instances whose
+/// container was not reported.
+#[post("/platform/<platform_id>/workers/<worker_id>/heartbeat", format = "json", data = "<request>")]
+pub async fn worker_heartbeat(
+    token: AgentToken,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    worker_id: i64,
+    request: Json<WorkerHeartbeatRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Heartbeats are far too frequent to audit
+    trail.skip();
+
+    let token = token.0.ok_or_else(|| unauthorized("A worker credential is required"))?;
+    let request = request.into_inner();
+    for (resource, total) in [("cpu_total", request.cpu_total), ("memory_total", request.memory_total), ("disk_total", request.disk_total)] {
+        if total.is_some_and(|total| !(total.is_finite() && total > 0.0)) {
+            return Err(bad_request(format!("{} must be greater than 0", resource)));
+        }
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::worker::get_worker_agent_token_hash(&pool, worker_id).await {
+        Ok(Some(expected)) if hashes_match(&expected, &hash_secret(&token)) => {}
+        Ok(_) => return Err(unauthorized("The worker credential is invalid")),
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to verify worker credential"
+                }))
+            ));
+        }
+    }
+
+    let report = json!({
+        "received_at": Utc::now().to_rfc3339(),
+        "cpu_used": request.cpu_used,
+        "memory_used": request.memory_used,
+        "disk_used": request.disk_used,
+        "containers": request.containers,
+    });
+    let worker = db::worker::record_worker_heartbeat(
+        &pool,
+        worker_id,
+        request.cpu_total,
+        request.memory_total,
+        request.disk_total,
+        request.agent_version.as_deref(),
+        &report,
+    )
+    .await
+    .map_err(|_| {
+        (
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to record heartbeat"
+            }))
+        )
+    })?;
+
+    let instances = db::worker::list_worker_instances(&pool, worker_id).await.map_err(|_| {
+        (
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch instances of worker"
+            }))
+        )
+    })?;
+
+    // Health checks run next to the containers, so the agent is the one
+    // that sees their results
+    for container in &request.containers {
+        let health = match container.health.as_deref().and_then(ContainerHealth::parse) {
+            Some(health) => health,
+            None => continue,
+        };
+        let instance = instances
+            .iter()
+            .find(|instance| instance.container_id.as_deref() == Some(container.container_id.as_str()));
+        if let Some(instance) = instance {
+            if let Err(e) = db::instance::record_instance_health(&pool, instance.id, &container.container_id, health.instance_status()).await {
+                log::warn!("Failed to record health of instance {}: {:#}", instance.id, e);
+            }
+        }
+    }
+
+    let reported: HashSet<&str> = request.containers.iter().map(|container| container.container_id.as_str()).collect();
+    let expected: HashSet<&str> = instances.iter().filter_map(|instance| instance.container_id.as_deref()).collect();
+    let unknown_containers: Vec<&str> = request
+        .containers
+        .iter()
+        .map(|container| container.container_id.as_str())
+        .filter(|container_id| !expected.contains(container_id))
+        .collect();
+    let missing_instances: Vec<i64> = instances
+        .iter()
+        .filter(|instance| matches!(instance.status.as_deref(), Some("running" | "starting")))
+        .filter(|instance| instance.container_id.as_deref().is_some_and(|container_id| !reported.contains(container_id)))
+        .map(|instance| instance.id)
+        .collect();
+
+    Ok(Json(json!({
+        "worker_id": worker.id,
+        "status": worker.status,
+        "heartbeat_interval_seconds": SERVER_CONFIG.workers.heartbeat_interval_seconds,
+        "instances": instances.iter().map(|instance| json!({
+            "id": instance.id,
+            "guid": instance.guid,
+            "app_id": instance.app_id,
+            "status": instance.status,
+            "container_id": instance.container_id
+        })).collect::<Vec<_>>(),
+        "unknown_containers": unknown_containers,
+        "missing_instances": missing_instances
+    })))
+}
@@ -0,0 +1,229 @@
+use super::super::super::db::queries as db;
+use super::super::api_keys::token::hash_secret;
+use super::types::CreateBootstrapTokenRequest;
+use chrono::Utc;
+use db::worker::WorkerBootstrapToken;
+use rand::rngs::OsRng;
+use rand::TryRngCore;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{delete, get, post, State};
+use std::sync::Arc;
+
+use crate::DatabaseManager;
+use super::super::rbac::{Require, WorkersRead, WorkersWrite};
+
+/// Prefix of bootstrap tokens.
+pub const BOOTSTRAP_TOKEN_PREFIX: &str = "owb_";
+
+/// Prefix of the credentials worker agents send heartbeats with.
+pub const AGENT_TOKEN_PREFIX: &str = "owk_";
+
+/// Generates a random token with the given prefix, returning the token and
+/// the hash it is stored as.
+pub(super) fn generate_token(prefix: &str) -> anyhow::Result<(String, String)> {
+    let mut secret = [0u8; 32];
+    OsRng.try_fill_bytes(&mut secret)?;
+    let token = format!("{}{}", prefix, hex::encode(secret));
+    let hash = hash_secret(&token);
+    Ok((token, hash))
+}
+
+/// Create a bootstrap token for registering workers.
+///
+/// The token is only returned by this request; afterwards only its hash is
+/// kept.
+#[post("/platform/<platform_id>/workers/bootstrap_tokens", format = "json", data = "<request>")]
+pub async fn create_bootstrap_token(
+    auth: Require<WorkersWrite>,
+    platform_id: i64,
+    request: Json<CreateBootstrapTokenRequest>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    if request.max_uses.is_some_and(|max_uses| max_uses < 1) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "max_uses must be at least 1, or omitted for unlimited use"
+            }))
+        ));
+    }
+    if request.expires_at.is_some_and(|expires_at| expires_at <= Utc::now()) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "expires_at must lie in the future"
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let (token, hash) = generate_token(BOOTSTRAP_TOKEN_PREFIX).map_err(|_| {
+        (
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Internal error",
+                "message": "Failed to generate bootstrap token"
+            }))
+        )
+    })?;
+
+    match db::worker::create_bootstrap_token(
+        &pool,
+        &hash,
+        request.description.as_deref(),
+        request.region_id,
+        request.max_uses,
+        request.expires_at,
+        Some(auth.user_id()),
+    )
+    .await
+    {
+        Ok(created) => Ok(Json(json!({
+            "bootstrap_token": created,
+            "token": token
+        }))),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to create bootstrap token"
+            }))
+        )),
+    }
+}
+
+/// List the bootstrap tokens of a platform. The tokens themselves are
+/// never returned.
+#[get("/platform/<platform_id>/workers/bootstrap_tokens", rank = 1)]
+pub async fn list_bootstrap_tokens(
+    _auth: Require<WorkersRead>,
+    platform_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<WorkerBootstrapToken>>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::worker::list_bootstrap_tokens(&pool).await {
+        Ok(tokens) => Ok(Json(tokens)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch bootstrap tokens"
+            }))
+        )),
+    }
+}
+
+/// Revoke a bootstrap token. Workers already registered with it keep
+/// working.
+#[delete("/platform/<platform_id>/workers/bootstrap_tokens/<token_id>")]
+pub async fn revoke_bootstrap_token(
+    _auth: Require<WorkersWrite>,
+    platform_id: i64,
+    token_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::worker::revoke_bootstrap_token(&pool, token_id).await {
+        Ok(true) => Ok(Json(json!({
+            "message": format!("Bootstrap token {} revoked", token_id)
+        }))),
+        Ok(false) => Err((
+            Status::NotFound,
+            Json(json!({
+                "error": "Bootstrap token not found",
+                "message": format!("No active bootstrap token with ID {} exists", token_id)
+            }))
+        )),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to revoke bootstrap token"
+            }))
+        )),
+    }
+}
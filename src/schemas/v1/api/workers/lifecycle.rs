@@ -0,0 +1,281 @@
+use super::super::super::db::queries as db;
+use super::super::audit_log::AuditTrail;
+use super::list::find_worker;
+use db::app_event::NewAppEvent;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{post, put, State};
+use sqlx::{MySql, Pool};
+use std::sync::Arc;
+
+use crate::DatabaseManager;
+use libomni::types::db::v1 as types;
+use types::worker::Worker;
+use super::super::rbac::{Require, WorkersWrite};
+
+/// Statuses a worker can be cordoned from.
+const CORDONABLE: &[&str] = &["active", "degraded", "unreachable", "provisioning"];
+
+/// Statuses a worker can be decommissioned from.
+const DECOMMISSIONABLE: &[&str] = &["active", "degraded", "unreachable", "provisioning", "maintenance"];
+
+fn conflict(worker: &Worker, action: &str) -> (Status, Json<Value>) {
+    (
+        Status::Conflict,
+        Json(json!({
+            "error": "Invalid worker status",
+            "message": format!(
+                "Worker {} is {} and cannot be {}",
+                worker.id,
+                worker.status.as_deref().unwrap_or("unknown"),
+                action
+            )
+        }))
+    )
+}
+
+fn database_error(message: &str) -> (Status, Json<Value>) {
+    (
+        Status::InternalServerError,
+        Json(json!({
+            "error": "Database error",
+            "message": message
+        }))
+    )
+}
+
+/// Moves a worker from one of `from` to `to`, recording the change in the
+/// audit trail. Fails with `409 Conflict` if the worker has another status.
+async fn transition(
+    pool: &Pool<MySql>,
+    trail: &AuditTrail<'_>,
+    worker_id: i64,
+    from: &[&str],
+    to: &str,
+    action: &str,
+) -> Result<Worker, (Status, Json<Value>)> {
+    let worker = find_worker(pool, worker_id).await?;
+    trail.resource("worker", worker_id);
+    trail.before(&worker);
+
+    if worker.status.as_deref() != Some(to) {
+        match db::worker::transition_worker_status(pool, worker_id, from, to).await {
+            Ok(true) => {}
+            Ok(false) => return Err(conflict(&worker, action)),
+            Err(_) => return Err(database_error("Failed to update worker status")),
+        }
+    }
+
+    let worker = find_worker(pool, worker_id).await?;
+    trail.after(&worker);
+    Ok(worker)
+}
+
+/// Marks every instance on a worker 'stopping' so the reconciler replaces
+/// them on other workers, and records why on each application.
+async fn drain(pool: &Pool<MySql>, worker: &Worker, reason: &'static str) -> Result<Vec<i64>, (Status, Json<Value>)> {
+    let instances = db::worker::drain_worker_instances(pool, worker.id)
+        .await
+        .map_err(|_| database_error("Failed to drain instances of worker"))?;
+
+    for instance in &instances {
+        let event = NewAppEvent {
+            app_id: instance.app_id,
+            instance_id: Some(instance.id),
+            event_type: "instance_drained",
+            severity: "info",
+            message: format!("Instance {} is being moved off worker {} ({})", instance.instance_index, worker.name, reason),
+            source: "workers",
+            metadata: Some(json!({ "worker_id": worker.id, "reason": reason })),
+        };
+        if let Err(e) = db::app_event::insert_app_event(pool, &event).await {
+            log::warn!("Failed to record drain of instance {}: {:#}", instance.id, e);
+        }
+    }
+
+    Ok(instances.iter().map(|instance| instance.id).collect())
+}
+
+/// Cordon a worker so no new instances are placed on it. Instances already
+/// running on it are left alone.
+#[put("/platform/<platform_id>/workers/<worker_id>/cordon")]
+pub async fn cordon_worker(
+    _auth: Require<WorkersWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    worker_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Worker>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    transition(&pool, &trail, worker_id, CORDONABLE, "maintenance", "cordoned").await.map(Json)
+}
+
+/// Uncordon a worker so instances can be placed on it again.
+#[put("/platform/<platform_id>/workers/<worker_id>/uncordon")]
+pub async fn uncordon_worker(
+    _auth: Require<WorkersWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    worker_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Worker>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    transition(&pool, &trail, worker_id, &["maintenance"], "active", "uncordoned").await.map(Json)
+}
+
+/// Drain a worker: cordon it and move its instances to other workers.
+///
+/// The instances are marked 'stopping'; the reconciler terminates them and
+/// creates replacements, which the scheduler places elsewhere.
+#[post("/platform/<platform_id>/workers/<worker_id>/drain")]
+pub async fn drain_worker(
+    _auth: Require<WorkersWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    worker_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    // A decommissioning worker is drained already but may be drained again
+    let current = find_worker(&pool, worker_id).await?;
+    let worker = if current.status.as_deref() == Some("decommissioning") {
+        current
+    } else {
+        transition(&pool, &trail, worker_id, CORDONABLE, "maintenance", "drained").await?
+    };
+
+    let drained = drain(&pool, &worker, "worker drained").await?;
+    trail.detail("drained_instances", &drained);
+    Ok(Json(json!({
+        "worker": worker,
+        "drained_instances": drained
+    })))
+}
+
+/// Decommission a worker: drain it and power it off once its last instance
+/// has been moved. The worker's agent credential is revoked at that point.
+#[post("/platform/<platform_id>/workers/<worker_id>/decommission")]
+pub async fn decommission_worker(
+    _auth: Require<WorkersWrite>,
+    trail: AuditTrail<'_>,
+    platform_id: i64,
+    worker_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let worker = transition(&pool, &trail, worker_id, DECOMMISSIONABLE, "decommissioning", "decommissioned").await?;
+    let drained = drain(&pool, &worker, "worker decommissioned").await?;
+    trail.detail("drained_instances", &drained);
+    Ok(Json(json!({
+        "worker": worker,
+        "drained_instances": drained
+    })))
+}
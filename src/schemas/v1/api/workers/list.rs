@@ -0,0 +1,144 @@
+use super::super::super::db::queries as db;
+use rocket::http::Status;
+use rocket::serde::json::{json, Json, Value};
+use rocket::{get, State};
+use std::sync::Arc;
+
+use crate::DatabaseManager;
+use libomni::types::db::v1 as types;
+use types::worker::Worker;
+use super::super::rbac::{Require, WorkersRead};
+
+/// List the workers of a platform with pagination support.
+#[get("/platform/<platform_id>/workers?<page>&<per_page>")]
+pub async fn list_workers(
+    _auth: Require<WorkersRead>,
+    platform_id: i64,
+    page: Option<u64>,
+    per_page: Option<u64>,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Vec<Worker>>, (Status, Json<Value>)> {
+    if page == Some(0) {
+        return Err((
+            Status::BadRequest,
+            Json(json!({
+                "error": "Invalid request",
+                "message": "page starts at 1"
+            }))
+        ));
+    }
+
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    match db::worker::list_workers(&pool, page, per_page).await {
+        Ok(workers) => Ok(Json(workers)),
+        Err(_) => Err((
+            Status::InternalServerError,
+            Json(json!({
+                "error": "Database error",
+                "message": "Failed to fetch workers"
+            }))
+        )),
+    }
+}
+
+/// Get a worker by its ID, together with the instances placed on it.
+///
+/// Ranked after `workers/bootstrap_tokens`, which it would otherwise collide
+/// with.
+#[get("/platform/<platform_id>/workers/<worker_id>", rank = 2)]
+pub async fn get_worker_by_id(
+    _auth: Require<WorkersRead>,
+    platform_id: i64,
+    worker_id: i64,
+    db_manager: &State<Arc<DatabaseManager>>,
+) -> Result<Json<Value>, (Status, Json<Value>)> {
+    // Get platform information
+    let platform = match db::platforms::get_platform_by_id(db_manager.get_main_pool(), platform_id).await {
+        Ok(platform) => platform,
+        Err(_) => {
+            return Err((
+                Status::NotFound,
+                Json(json!({
+                    "error": "Platform not found",
+                    "message": format!("Platform with ID {} does not exist", platform_id)
+                }))
+            ));
+        }
+    };
+
+    // Get platform-specific database pool
+    let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+        Ok(pool) => pool,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to connect to platform database"
+                }))
+            ));
+        }
+    };
+
+    let worker = find_worker(&pool, worker_id).await?;
+    let instances = match db::worker::list_worker_instances(&pool, worker_id).await {
+        Ok(instances) => instances,
+        Err(_) => {
+            return Err((
+                Status::InternalServerError,
+                Json(json!({
+                    "error": "Database error",
+                    "message": "Failed to fetch instances of worker"
+                }))
+            ));
+        }
+    };
+
+    Ok(Json(json!({
+        "worker": worker,
+        "instances": instances
+    })))
+}
+
+/// Looks up a worker, failing with `404 Not Found` if it does not exist.
+pub(super) async fn find_worker(
+    pool: &sqlx::Pool<sqlx::MySql>,
+    worker_id: i64,
+) -> Result<Worker, (Status, Json<Value>)> {
+    db::worker::get_worker_by_id(pool, worker_id).await.map_err(|_| {
+        (
+            Status::NotFound,
+            Json(json!({
+                "error": "Worker not found",
+                "message": format!("Worker with ID {} does not exist", worker_id)
+            }))
+        )
+    })
+}
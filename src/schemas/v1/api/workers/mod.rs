@@ -0,0 +1,22 @@
+//! Worker management module for the OmniOrchestrator API
+//! 
+//! This module provides a REST API for managing workers, including:
+//! - Listing workers and getting worker details
+//! - Issuing bootstrap tokens that worker agents register with
+//! - Registering workers and receiving their agents' heartbeats
+//! - Cordoning, draining and decommissioning workers
+
+pub mod types;
+pub mod list;
+pub mod bootstrap;
+pub mod agent;
+pub mod lifecycle;
+
+// Re-export types for easier access
+pub use types::*;
+
+// Re-export all route functions
+pub use list::{list_workers, get_worker_by_id};
+pub use bootstrap::{create_bootstrap_token, list_bootstrap_tokens, revoke_bootstrap_token};
+pub use agent::{register_worker, worker_heartbeat};
+pub use lifecycle::{cordon_worker, uncordon_worker, drain_worker, decommission_worker};
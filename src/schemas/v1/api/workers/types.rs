@@ -0,0 +1,93 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Request data for creating a bootstrap token.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateBootstrapTokenRequest {
+    /// What the token is for
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Region workers registering with the token must join
+    #[serde(default)]
+    pub region_id: Option<i64>,
+    /// How many workers may register with the token; unlimited if omitted
+    #[serde(default)]
+    pub max_uses: Option<i32>,
+    /// When the token stops working
+    #[serde(default)]
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Request data a worker agent registers its worker with.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RegisterWorkerRequest {
+    /// Name of the worker, unique within its region
+    pub name: String,
+    /// Region of the worker; may be omitted if the bootstrap token names one
+    #[serde(default)]
+    pub region_id: Option<i64>,
+    #[serde(default)]
+    pub provider_id: Option<String>,
+    #[serde(default)]
+    pub instance_type: Option<String>,
+    /// CPU cores of the worker
+    pub cpu_total: f64,
+    /// Memory of the worker in MB
+    pub memory_total: f64,
+    /// Disk space of the worker in MB
+    pub disk_total: f64,
+    #[serde(default)]
+    pub docker_version: Option<String>,
+    /// Labels application node selectors are matched against
+    #[serde(default)]
+    pub labels: Option<Value>,
+    /// Taints that keep applications off the worker unless tolerated
+    #[serde(default)]
+    pub taints: Option<Value>,
+    #[serde(default)]
+    pub annotations: Option<Value>,
+    /// Version of the agent software
+    #[serde(default)]
+    pub agent_version: Option<String>,
+}
+
+/// A container an agent reports running on its worker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportedContainer {
+    pub container_id: String,
+    /// GUID of the instance the container belongs to, if it has one
+    #[serde(default)]
+    pub instance_guid: Option<String>,
+    /// State of the container as seen by the agent, such as `running`
+    #[serde(default)]
+    pub state: Option<String>,
+    /// Result of the container's health check, if it has one: `starting`,
+    /// `healthy` or `unhealthy`
+    #[serde(default)]
+    pub health: Option<String>,
+}
+
+/// Request data of a worker heartbeat.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkerHeartbeatRequest {
+    /// Current totals, if they changed since registration
+    #[serde(default)]
+    pub cpu_total: Option<f64>,
+    #[serde(default)]
+    pub memory_total: Option<f64>,
+    #[serde(default)]
+    pub disk_total: Option<f64>,
+    /// Resources actually in use, for information
+    #[serde(default)]
+    pub cpu_used: Option<f64>,
+    #[serde(default)]
+    pub memory_used: Option<f64>,
+    #[serde(default)]
+    pub disk_used: Option<f64>,
+    /// Containers running on the worker
+    #[serde(default)]
+    pub containers: Vec<ReportedContainer>,
+    #[serde(default)]
+    pub agent_version: Option<String>,
+}
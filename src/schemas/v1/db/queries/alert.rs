@@ -291,7 +291,7 @@ pub async fn create_alert(
     let mut tx = pool.begin().await?;
 
     // Insert alert
-    let alert = sqlx::query_as::<_, Alert>(
+    let result = sqlx::query(
         r#"INSERT INTO alerts (
             alert_type, severity, service, message, timestamp, status,
             metadata, org_id, app_id, instance_id, region_id, node_id
@@ -307,9 +307,15 @@ pub async fn create_alert(
     .bind(instance_id)
     .bind(region_id)
     .bind(node_id)
-    .fetch_one(&mut *tx)
+    .execute(&mut *tx)
     .await
     .context("Failed to create alert")?;
+
+    let alert = sqlx::query_as::<_, Alert>("SELECT * FROM alerts WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created alert")?;
     
     // Add history record for alert creation
     sqlx::query(
@@ -0,0 +1,308 @@
+// db/queries/api_key.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// A row of the `api_keys` table.
+///
+/// The `key_hash` column holds the hex encoded SHA-256 digest of the key
+/// secret. The secret itself is never persisted, so callers must not
+/// serialize this struct back to API clients without stripping the hash
+/// first (see `api::api_keys::ApiKeyResponse`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApiKey {
+    pub id: i64,
+    pub org_id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub key_hash: String,
+    pub prefix: String,
+    pub scopes: Option<serde_json::Value>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked: bool,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub revoked_by: Option<i64>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl ApiKey {
+    /// Returns the scopes granted to this key as plain strings.
+    ///
+    /// Scopes are stored as a JSON array; anything that is not a string is
+    /// ignored rather than treated as an error so that a malformed row can
+    /// never widen a key's access.
+    pub fn scope_list(&self) -> Vec<String> {
+        match &self.scopes {
+            Some(serde_json::Value::Array(values)) => values
+                .iter()
+                .filter_map(|v| v.as_str().map(|s| s.to_string()))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Checks whether the key grants the given scope.
+    ///
+    /// A granted scope of `*` matches everything, and a granted scope of the
+    /// form `<resource>:*` matches every action on that resource.
+    pub fn has_scope(&self, scope: &str) -> bool {
+        let resource = scope.split(':').next().unwrap_or(scope);
+        self.scope_list().iter().any(|granted| {
+            granted == "*"
+                || granted == scope
+                || granted
+                    .strip_suffix(":*")
+                    .map_or(false, |granted_resource| granted_resource == resource)
+        })
+    }
+
+    /// Checks whether the key has been revoked or has passed its expiry time.
+    pub fn is_usable(&self) -> bool {
+        if self.revoked {
+            return false;
+        }
+        match self.expires_at {
+            Some(expires_at) => expires_at > Utc::now(),
+            None => true,
+        }
+    }
+}
+
+/// Retrieves a paginated list of API keys belonging to an organization.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Organization whose keys should be listed
+/// * `user_id` - When set, only keys owned by this user are listed
+/// * `include_revoked` - Whether revoked keys are included in the result
+/// * `page` - Zero-based page number
+/// * `per_page` - Number of records per page
+///
+/// # Returns
+///
+/// * `Ok(Vec<ApiKey>)` - The requested page of keys, newest first
+/// * `Err(anyhow::Error)` - Failed to fetch keys
+pub async fn list_api_keys(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    user_id: Option<i64>,
+    include_revoked: bool,
+    page: i64,
+    per_page: i64,
+) -> anyhow::Result<Vec<ApiKey>> {
+    let keys = sqlx::query_as::<_, ApiKey>(
+        r#"
+        SELECT * FROM api_keys
+        WHERE org_id = ? AND (? IS NULL OR user_id = ?) AND (? OR revoked = 0)
+        ORDER BY created_at DESC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(org_id)
+    .bind(user_id)
+    .bind(user_id)
+    .bind(include_revoked)
+    .bind(per_page)
+    .bind(page * per_page)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch API keys")?;
+
+    Ok(keys)
+}
+
+/// Counts the API keys belonging to an organization.
+pub async fn count_api_keys(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    user_id: Option<i64>,
+    include_revoked: bool,
+) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM api_keys WHERE org_id = ? AND (? IS NULL OR user_id = ?) AND (? OR revoked = 0)",
+    )
+    .bind(org_id)
+    .bind(user_id)
+    .bind(user_id)
+    .bind(include_revoked)
+    .fetch_one(pool)
+    .await
+    .context("Failed to count API keys")?;
+
+    Ok(count)
+}
+
+/// Retrieves a specific API key by its unique identifier.
+pub async fn get_api_key_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<ApiKey> {
+    let key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to fetch API key")?;
+
+    Ok(key)
+}
+
+/// Retrieves an API key by its public prefix.
+///
+/// The prefix is the non-secret part of a presented token and is unique
+/// across the platform, which makes it the lookup key used during
+/// authentication before the secret hash is compared.
+pub async fn get_api_key_by_prefix(pool: &Pool<MySql>, prefix: &str) -> anyhow::Result<Option<ApiKey>> {
+    let key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE prefix = ?")
+        .bind(prefix)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch API key by prefix")?;
+
+    Ok(key)
+}
+
+/// Creates a new API key record.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Organization the key belongs to
+/// * `user_id` - User that owns (and is acting through) the key
+/// * `name` - Human readable name of the key
+/// * `description` - Optional description
+/// * `key_hash` - Hex encoded SHA-256 digest of the secret
+/// * `prefix` - Public, unique prefix of the key
+/// * `scopes` - Scopes granted to the key
+/// * `expires_at` - Optional expiry time
+///
+/// # Returns
+///
+/// * `Ok(ApiKey)` - The newly created key record
+/// * `Err(anyhow::Error)` - Failed to create the key
+pub async fn create_api_key(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    user_id: i64,
+    name: &str,
+    description: Option<&str>,
+    key_hash: &str,
+    prefix: &str,
+    scopes: &[String],
+    expires_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<ApiKey> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO api_keys (org_id, user_id, name, description, key_hash, prefix, scopes, expires_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(org_id)
+    .bind(user_id)
+    .bind(name)
+    .bind(description)
+    .bind(key_hash)
+    .bind(prefix)
+    .bind(serde_json::json!(scopes))
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to insert API key")?;
+
+    let key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
+        .bind(result.last_insert_id())
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created API key")?;
+
+    tx.commit().await?;
+    Ok(key)
+}
+
+/// Replaces the secret of an API key.
+///
+/// Both the hash and the prefix are replaced so that the previous token
+/// stops resolving immediately. Revoked keys cannot be rotated.
+pub async fn rotate_api_key(
+    pool: &Pool<MySql>,
+    id: i64,
+    key_hash: &str,
+    prefix: &str,
+) -> anyhow::Result<ApiKey> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        "UPDATE api_keys SET key_hash = ?, prefix = ?, last_used_at = NULL WHERE id = ? AND revoked = 0",
+    )
+    .bind(key_hash)
+    .bind(prefix)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to rotate API key")?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!("API key {} does not exist or has been revoked", id);
+    }
+
+    let key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch rotated API key")?;
+
+    tx.commit().await?;
+    Ok(key)
+}
+
+/// Revokes an API key.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `id` - Unique identifier of the key to revoke
+/// * `revoked_by` - User performing the revocation
+///
+/// # Returns
+///
+/// * `Ok(ApiKey)` - The revoked key record
+/// * `Err(anyhow::Error)` - Failed to revoke the key
+pub async fn revoke_api_key(pool: &Pool<MySql>, id: i64, revoked_by: i64) -> anyhow::Result<ApiKey> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE api_keys
+        SET revoked = 1, revoked_at = COALESCE(revoked_at, CURRENT_TIMESTAMP), revoked_by = COALESCE(revoked_by, ?)
+        WHERE id = ?
+        "#,
+    )
+    .bind(revoked_by)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to revoke API key")?;
+
+    let key = sqlx::query_as::<_, ApiKey>("SELECT * FROM api_keys WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch revoked API key")?;
+
+    tx.commit().await?;
+    Ok(key)
+}
+
+/// Records that an API key has just been used to authenticate a request.
+pub async fn touch_api_key(pool: &Pool<MySql>, id: i64) -> anyhow::Result<()> {
+    sqlx::query("UPDATE api_keys SET last_used_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to update API key last_used_at")?;
+
+    Ok(())
+}
@@ -1,5 +1,6 @@
 use anyhow::Context;
 use serde::Serialize;
+use sqlx::pool::PoolConnection;
 use sqlx::{MySql, Pool};
 
 use libomni::types::db::v1 as types;
@@ -147,6 +148,49 @@ pub async fn get_app_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<App> {
     Ok(app)
 }
 
+/// Retrieves the organization and space an application belongs to.
+///
+/// This is a lightweight lookup used when resolving the scope of a request
+/// (for example by permission checks) where loading the full application
+/// record would be wasteful.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `id` - Unique identifier of the application
+///
+/// # Returns
+///
+/// * `Ok(Some((org_id, space_id)))` - The application's organization and optional space
+/// * `Ok(None)` - No application with the given ID exists
+/// * `Err(anyhow::Error)` - Failed to fetch the application
+pub async fn get_app_scope(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<(i64, Option<i64>)>> {
+    let scope = sqlx::query_as::<_, (i64, Option<i64>)>("SELECT org_id, space_id FROM apps WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch app scope")?;
+
+    Ok(scope)
+}
+
+/// Retrieves the applications the reconciler has to look at: those that
+/// should be running and those that still have instances which have not been
+/// stopped or terminated, including deleted applications.
+pub async fn list_apps_to_reconcile(pool: &Pool<MySql>) -> anyhow::Result<Vec<App>> {
+    let apps = sqlx::query_as::<_, App>(
+        r#"SELECT * FROM apps
+        WHERE (deleted_at IS NULL AND status IN ('started', 'starting', 'crashed'))
+            OR id IN (SELECT app_id FROM instances WHERE status NOT IN ('stopped', 'terminated'))
+        ORDER BY id ASC"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch apps to reconcile")?;
+
+    Ok(apps)
+}
+
 /// Retrieves all applications belonging to a specific organization.
 ///
 /// This function fetches all applications associated with the provided organization ID,
@@ -188,6 +232,7 @@ pub async fn get_apps_by_org(pool: &Pool<MySql>, org_id: i64) -> anyhow::Result<
 /// * `pool` - Database connection pool for executing the query
 /// * `name` - Name of the application
 /// * `org_id` - Organization ID that the application belongs to
+/// * `space_id` - Optional space within the organization
 /// * `git_repo` - Optional URL of the Git repository for the application
 /// * `git_branch` - Optional branch name in the Git repository
 /// * `container_image_url` - Optional URL for a container image
@@ -206,6 +251,7 @@ pub async fn create_app(
     pool: &Pool<MySql>,
     name: &str,
     org_id: i64,
+    space_id: Option<i64>,
     git_repo: Option<&str>,
     git_branch: Option<&str>,
     container_image_url: Option<&str>,
@@ -215,24 +261,32 @@ pub async fn create_app(
     let mut tx = pool.begin().await?;
 
     // Define query to insert app with default maintenance_mode set to false
-    let app = sqlx::query_as::<_, App>(
+    let result = sqlx::query(
         r#"INSERT INTO apps (
-            name, org_id, git_repo, git_branch, container_image_url, region_id, maintenance_mode
-        ) VALUES (?, ?, ?, ?, ?, ?, false)"#,
+            name, org_id, space_id, git_repo, git_branch, container_image_url, region_id, maintenance_mode
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, false)"#,
     )
     // Bind required parameters
     .bind(name)
     .bind(org_id)
     // Bind optional parameters
+    .bind(space_id)
     .bind(git_repo)
     .bind(git_branch)
     .bind(container_image_url)
     .bind(region_id)
     // Execute query and handle errors
-    .fetch_one(&mut *tx)
+    .execute(&mut *tx)
     .await
     .context("Failed to create app")?;
 
+    // Fetch the inserted row, including database-assigned fields
+    let app = sqlx::query_as::<_, App>("SELECT * FROM apps WHERE id = ?")
+        .bind(result.last_insert_id())
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created app")?;
+
     // Commit transaction
     tx.commit().await?;
 
@@ -483,3 +537,91 @@ pub async fn count_instances_by_app(pool: &Pool<MySql>, app_id: i64) -> anyhow::
 
     Ok(count)
 }
+
+/// Sets the desired run state of an application.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `id` - Unique identifier of the application to update
+/// * `status` - New status, if it changes ('started', 'stopped', ...)
+/// * `instances` - New desired number of instances, if it changes
+///
+/// # Returns
+///
+/// * `Ok(App)` - The updated application
+/// * `Err(anyhow::Error)` - Failed to update the application
+pub async fn set_app_run_state(
+    pool: &Pool<MySql>,
+    id: i64,
+    status: Option<&str>,
+    instances: Option<i64>,
+) -> anyhow::Result<App> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"UPDATE apps
+        SET status = COALESCE(?, status),
+            instances = COALESCE(?, instances),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?"#,
+    )
+    .bind(status)
+    .bind(instances)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update app run state")?;
+
+    let app = sqlx::query_as::<_, App>("SELECT * FROM apps WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch updated app")?;
+
+    tx.commit().await?;
+    Ok(app)
+}
+
+/// Takes the control lock of an application.
+///
+/// Start, stop and scale operations hold this lock so that concurrent
+/// requests, possibly on different nodes, cannot create or terminate
+/// instances based on the same stale count. The lock is a MySQL named lock
+/// bound to the returned connection, and must be given back with
+/// [`unlock_app_control`].
+///
+/// # Returns
+///
+/// * `Ok(Some(connection))` - The lock was acquired
+/// * `Ok(None)` - Another operation held the lock for longer than `timeout_secs`
+/// * `Err(anyhow::Error)` - Failed to request the lock
+pub async fn lock_app_control(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    timeout_secs: i64,
+) -> anyhow::Result<Option<PoolConnection<MySql>>> {
+    let mut conn = pool.acquire().await?;
+
+    let locked = sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT GET_LOCK(CONCAT('omni_app_control:', DATABASE(), ':', ?), ?)",
+    )
+    .bind(app_id)
+    .bind(timeout_secs)
+    .fetch_one(&mut *conn)
+    .await
+    .context("Failed to acquire app control lock")?;
+
+    Ok((locked == Some(1)).then_some(conn))
+}
+
+/// Releases a lock taken with [`lock_app_control`].
+pub async fn unlock_app_control(mut conn: PoolConnection<MySql>, app_id: i64) {
+    if let Err(e) = sqlx::query("SELECT RELEASE_LOCK(CONCAT('omni_app_control:', DATABASE(), ':', ?))")
+        .bind(app_id)
+        .execute(&mut *conn)
+        .await
+    {
+        log::warn!("Failed to release control lock of app {}: {}", app_id, e);
+    }
+}
@@ -0,0 +1,88 @@
+// db/queries/app_event.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, MySql, Pool};
+
+/// A change the orchestrator made to an application or one of its
+/// instances on its own, such as the reconciler replacing a crashed instance.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AppEvent {
+    pub id: i64,
+    pub app_id: i64,
+    pub instance_id: Option<i64>,
+    pub event_type: String,
+    pub severity: String,
+    pub message: String,
+    pub source: String,
+    pub metadata: Option<Value>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// An event to be recorded with [`insert_app_event`].
+#[derive(Debug, Clone)]
+pub struct NewAppEvent {
+    pub app_id: i64,
+    pub instance_id: Option<i64>,
+    pub event_type: &'static str,
+    pub severity: &'static str,
+    pub message: String,
+    pub source: &'static str,
+    pub metadata: Option<Value>,
+}
+
+/// Records an application event.
+pub async fn insert_app_event(pool: &Pool<MySql>, event: &NewAppEvent) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO app_events (app_id, instance_id, event_type, severity, message, source, metadata)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(event.app_id)
+    .bind(event.instance_id)
+    .bind(event.event_type)
+    .bind(event.severity)
+    .bind(&event.message)
+    .bind(event.source)
+    .bind(&event.metadata)
+    .execute(pool)
+    .await
+    .context("Failed to record app event")?;
+
+    Ok(())
+}
+
+/// Retrieves the events of an application, newest first.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `app_id` - Application whose events should be listed
+/// * `before_id` - Only return events older than this one, for paging
+/// * `limit` - Maximum number of events to return
+pub async fn list_app_events(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    before_id: Option<i64>,
+    limit: i64,
+) -> anyhow::Result<Vec<AppEvent>> {
+    let events = sqlx::query_as::<_, AppEvent>(
+        r#"
+        SELECT * FROM app_events
+        WHERE app_id = ? AND (? IS NULL OR id < ?)
+        ORDER BY id DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(app_id)
+    .bind(before_id)
+    .bind(before_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch app events")?;
+
+    Ok(events)
+}
@@ -0,0 +1,174 @@
+// db/queries/artifact.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+use libomni::types::db::v1 as types;
+use types::build::Build;
+
+/// How long the release artifacts of an application's builds are kept.
+///
+/// The newest `keep_last` artifacts are always kept. Older ones are
+/// deleted once they are `max_age_days` old, or right away if no age is
+/// set. Artifacts of builds that are being deployed, or that the current or
+/// previous deployment of the application runs, are never deleted.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ArtifactRetentionPolicy {
+    pub id: i64,
+    pub app_id: i64,
+    pub keep_last: i64,
+    pub max_age_days: Option<i64>,
+    pub enabled: Option<bool>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The settable fields of a retention policy.
+#[derive(Debug, Clone)]
+pub struct ArtifactRetentionInput {
+    pub keep_last: i64,
+    pub max_age_days: Option<i64>,
+    pub enabled: bool,
+}
+
+/// Retrieves the artifact retention policy of an application.
+pub async fn get_retention_policy(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Option<ArtifactRetentionPolicy>> {
+    let policy = sqlx::query_as::<_, ArtifactRetentionPolicy>(
+        "SELECT * FROM artifact_retention_policies WHERE app_id = ?",
+    )
+    .bind(app_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch artifact retention policy")?;
+
+    Ok(policy)
+}
+
+/// Retrieves the enabled artifact retention policies.
+pub async fn list_enabled_retention_policies(pool: &Pool<MySql>) -> anyhow::Result<Vec<ArtifactRetentionPolicy>> {
+    let policies = sqlx::query_as::<_, ArtifactRetentionPolicy>(
+        "SELECT * FROM artifact_retention_policies WHERE enabled = 1 ORDER BY app_id",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch artifact retention policies")?;
+
+    Ok(policies)
+}
+
+/// Creates or replaces the artifact retention policy of an application.
+pub async fn set_retention_policy(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    policy: &ArtifactRetentionInput,
+    created_by: Option<i64>,
+) -> anyhow::Result<ArtifactRetentionPolicy> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"INSERT INTO artifact_retention_policies (app_id, keep_last, max_age_days, enabled, created_by)
+        VALUES (?, ?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            keep_last = VALUES(keep_last),
+            max_age_days = VALUES(max_age_days),
+            enabled = VALUES(enabled)"#,
+    )
+    .bind(app_id)
+    .bind(policy.keep_last)
+    .bind(policy.max_age_days)
+    .bind(policy.enabled)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to save artifact retention policy")?;
+
+    let saved = sqlx::query_as::<_, ArtifactRetentionPolicy>(
+        "SELECT * FROM artifact_retention_policies WHERE app_id = ?",
+    )
+    .bind(app_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to fetch saved artifact retention policy")?;
+
+    tx.commit().await?;
+
+    Ok(saved)
+}
+
+/// Deletes the artifact retention policy of an application. Returns `false`
+/// if it had none.
+pub async fn delete_retention_policy(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM artifact_retention_policies WHERE app_id = ?")
+        .bind(app_id)
+        .execute(pool)
+        .await
+        .context("Failed to delete artifact retention policy")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Retrieves the builds of an application whose artifacts have outlived its
+/// retention policy, oldest first.
+pub async fn list_expired_artifacts(pool: &Pool<MySql>, policy: &ArtifactRetentionPolicy) -> anyhow::Result<Vec<Build>> {
+    let builds = sqlx::query_as::<_, Build>(
+        r#"SELECT b.* FROM builds b
+        WHERE b.app_id = ? AND b.artifact_url IS NOT NULL
+          AND b.id NOT IN (
+              SELECT id FROM (
+                  SELECT id FROM builds
+                  WHERE app_id = ? AND artifact_url IS NOT NULL
+                  ORDER BY created_at DESC, id DESC
+                  LIMIT ?
+              ) newest)
+          AND (? IS NULL OR b.created_at < NOW() - INTERVAL ? DAY)
+          AND b.id NOT IN (
+              SELECT build_id FROM deployments
+              WHERE app_id = ? AND status IN ('pending', 'in_progress'))
+          AND b.id NOT IN (
+              SELECT build_id FROM (
+                  SELECT build_id FROM deployments
+                  WHERE app_id = ? AND status = 'deployed'
+                  ORDER BY completed_at DESC, id DESC
+                  LIMIT 2
+              ) running)
+        ORDER BY b.created_at ASC, b.id ASC"#,
+    )
+    .bind(policy.app_id)
+    .bind(policy.app_id)
+    .bind(policy.keep_last)
+    .bind(policy.max_age_days)
+    .bind(policy.max_age_days)
+    .bind(policy.app_id)
+    .bind(policy.app_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch expired artifacts")?;
+
+    Ok(builds)
+}
+
+/// Removes the artifact of a build from its record, keeping its checksum
+/// and size. Returns whether any other build still refers to the same
+/// stored artifact, in which case it must not be deleted from the store.
+pub async fn clear_build_artifact(pool: &Pool<MySql>, build_id: i64, artifact_url: &str) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE builds SET artifact_url = NULL WHERE id = ? AND artifact_url = ?")
+        .bind(build_id)
+        .bind(artifact_url)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to clear build artifact")?;
+
+    let shared = sqlx::query_scalar::<_, i64>("SELECT EXISTS(SELECT 1 FROM builds WHERE artifact_url = ?)")
+        .bind(artifact_url)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to check for other builds of artifact")?;
+
+    tx.commit().await?;
+
+    Ok(shared != 0)
+}
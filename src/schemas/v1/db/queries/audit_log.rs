@@ -1,9 +1,277 @@
 use anyhow::Context;
-use sqlx::{MySql, Pool};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, MySql, Pool, QueryBuilder};
 
 use libomni::types::db::v1 as types;
 use types::audit_log::AuditLog;
 
+/// `prev_hash` of the first entry of a platform's audit log.
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// A complete audit record, as written by the audit fairing.
+///
+/// `org_id`, `space_id` and `app_id` name the resources the request was
+/// scoped to. Identifiers of resources that do not exist (for example
+/// because the request failed with `404 Not Found`) are dropped. The
+/// organization of a named application or space is always the one it
+/// belongs to; `org_id` is only used when neither is named.
+#[derive(Debug, Clone, Default)]
+pub struct AuditEntry {
+    pub user_id: Option<i64>,
+    pub org_id: Option<i64>,
+    pub space_id: Option<i64>,
+    pub app_id: Option<i64>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub before_state: Option<serde_json::Value>,
+    pub after_state: Option<serde_json::Value>,
+    pub details: Option<serde_json::Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    /// One of `success`, `failure` or `warning`
+    pub status: String,
+}
+
+/// An audit log entry together with its place in the hash chain.
+///
+/// Each entry stores the hash of the entry before it (`prev_hash`) and its
+/// own hash (`entry_hash`), computed by [`compute_entry_hash`] over its
+/// content and `prev_hash`. Changing, removing or reordering entries breaks
+/// the chain.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ChainedAuditLog {
+    pub id: i64,
+    pub user_id: Option<i64>,
+    pub org_id: Option<i64>,
+    pub app_id: Option<i64>,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub before_state: Option<Value>,
+    pub after_state: Option<Value>,
+    pub details: Option<Value>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    pub status: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub prev_hash: String,
+    pub entry_hash: Option<String>,
+}
+
+/// Head of a platform's hash chain: the last entry appended.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditChainHead {
+    pub last_id: i64,
+    pub last_hash: String,
+}
+
+/// A range of entries moved from `audit_logs` into an archive file.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditLogArchive {
+    pub id: i64,
+    pub first_id: i64,
+    pub last_id: i64,
+    pub entry_count: i64,
+    pub first_prev_hash: String,
+    pub last_hash: String,
+    pub oldest_at: Option<DateTime<Utc>>,
+    pub newest_at: Option<DateTime<Utc>>,
+    pub path: String,
+    pub content_sha256: String,
+    pub signature: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Details of an archive file that has been written, to be recorded.
+#[derive(Debug, Clone)]
+pub struct NewAuditLogArchive {
+    pub first_id: i64,
+    pub last_id: i64,
+    pub entry_count: i64,
+    pub first_prev_hash: String,
+    pub last_hash: String,
+    pub oldest_at: Option<DateTime<Utc>>,
+    pub newest_at: Option<DateTime<Utc>>,
+    pub path: String,
+    pub content_sha256: String,
+    pub signature: Option<String>,
+}
+
+/// Retention policy of a platform's audit log.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditRetentionPolicy {
+    /// Age in days after which entries are archived; `None` keeps them in
+    /// the database forever
+    pub retention_days: Option<i32>,
+    pub updated_by: Option<i64>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Computes the hash of an audit log entry.
+///
+/// The hash is the hex encoded SHA-256 of the canonical JSON form (keys
+/// sorted, no whitespace) of every column except `entry_hash`, including
+/// `prev_hash`, with `created_at` formatted as `YYYY-MM-DDTHH:MM:SSZ`.
+pub fn compute_entry_hash(entry: &ChainedAuditLog) -> String {
+    let content = json!({
+        "id": entry.id,
+        "user_id": entry.user_id,
+        "org_id": entry.org_id,
+        "app_id": entry.app_id,
+        "action": entry.action,
+        "resource_type": entry.resource_type,
+        "resource_id": entry.resource_id,
+        "before_state": entry.before_state,
+        "after_state": entry.after_state,
+        "details": entry.details,
+        "ip_address": entry.ip_address,
+        "user_agent": entry.user_agent,
+        "request_id": entry.request_id,
+        "status": entry.status,
+        "created_at": entry.created_at.map(|t| t.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+        "prev_hash": entry.prev_hash,
+    });
+
+    let mut canonical = String::new();
+    write_canonical_json(&content, &mut canonical);
+    hex::encode(Sha256::digest(canonical.as_bytes()))
+}
+
+/// Serializes a JSON value with object keys sorted at every level, so the
+/// result does not depend on the order the database returns keys in.
+fn write_canonical_json(value: &Value, out: &mut String) {
+    match value {
+        Value::Object(map) => {
+            let mut keys: Vec<&String> = map.keys().collect();
+            keys.sort();
+            out.push('{');
+            for (i, key) in keys.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                out.push_str(&Value::String(key.clone()).to_string());
+                out.push(':');
+                write_canonical_json(&map[key], out);
+            }
+            out.push('}');
+        }
+        Value::Array(items) => {
+            out.push('[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                write_canonical_json(item, out);
+            }
+            out.push(']');
+        }
+        scalar => out.push_str(&scalar.to_string()),
+    }
+}
+
+/// Appends an audit record to the platform's hash chain.
+///
+/// The chain head is locked for the duration of the append, so entries are
+/// chained in the order they are committed. The entry is read back after the
+/// insert and hashed in the form it is stored in, which is also the form
+/// verification and exports read it in.
+///
+/// # Returns
+///
+/// * `Ok(i64)` - ID of the new audit log entry
+/// * `Err(anyhow::Error)` - Failed to write the entry
+pub async fn insert_audit_entry(pool: &Pool<MySql>, entry: &AuditEntry) -> anyhow::Result<i64> {
+    let mut tx = pool.begin().await?;
+
+    let head = sqlx::query_as::<_, AuditChainHead>(
+        "SELECT last_id, last_hash FROM audit_log_chain WHERE id = 1 FOR UPDATE",
+    )
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to lock the audit log chain")?
+    .context("Audit log chain head is missing")?;
+
+    let id = sqlx::query(
+        r#"
+        INSERT INTO audit_logs (
+            user_id, org_id, app_id, action, resource_type, resource_id,
+            before_state, after_state, details, ip_address, user_agent, request_id, status,
+            prev_hash
+        ) VALUES (
+            ?,
+            (SELECT id FROM orgs WHERE id = COALESCE(
+                (SELECT org_id FROM apps WHERE id = ?),
+                (SELECT org_id FROM spaces WHERE id = ?),
+                ?
+            )),
+            (SELECT id FROM apps WHERE id = ?),
+            ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
+        )
+        "#,
+    )
+    .bind(entry.user_id)
+    .bind(entry.app_id)
+    .bind(entry.space_id)
+    .bind(entry.org_id)
+    .bind(entry.app_id)
+    .bind(&entry.action)
+    .bind(&entry.resource_type)
+    .bind(&entry.resource_id)
+    .bind(&entry.before_state)
+    .bind(&entry.after_state)
+    .bind(&entry.details)
+    .bind(&entry.ip_address)
+    .bind(&entry.user_agent)
+    .bind(&entry.request_id)
+    .bind(&entry.status)
+    .bind(&head.last_hash)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to write audit log entry")?
+    .last_insert_id() as i64;
+
+    let stored = sqlx::query_as::<_, ChainedAuditLog>("SELECT * FROM audit_logs WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to read back audit log entry")?;
+    let entry_hash = compute_entry_hash(&stored);
+
+    sqlx::query("UPDATE audit_logs SET entry_hash = ? WHERE id = ?")
+        .bind(&entry_hash)
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to store audit log entry hash")?;
+
+    sqlx::query("UPDATE audit_log_chain SET last_id = ?, last_hash = ? WHERE id = 1")
+        .bind(id)
+        .bind(&entry_hash)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to advance the audit log chain")?;
+
+    tx.commit().await?;
+    Ok(id)
+}
+
+/// Retrieves a specific audit log entry by its unique identifier.
+pub async fn get_audit_log_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<AuditLog> {
+    let audit_log = sqlx::query_as::<_, AuditLog>("SELECT * FROM audit_logs WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to fetch audit log entry")?;
+
+    Ok(audit_log)
+}
+
 /// Creates a new audit log entry in the system.
 ///
 /// This function records an action performed within the system, tracking who
@@ -57,23 +325,18 @@ pub async fn create_audit_log(
     resource_type: &str,
     resource_id: Option<String>,
 ) -> anyhow::Result<AuditLog> {
-    let audit_log = sqlx::query_as::<_, AuditLog>(
-        r#"
-            INSERT INTO audit_logs (
-            user_id, org_id, action, resource_type, resource_id
-            ) VALUES (?, ?, ?, ?, ?)
-        "#,
-    )
-    .bind(user_id)
-    .bind(org_id)
-    .bind(action)
-    .bind(resource_type)
-    .bind(resource_id)
-    .fetch_one(pool)
-    .await
-    .context("Failed to create audit log")?;
+    let entry = AuditEntry {
+        user_id,
+        org_id,
+        action: action.to_string(),
+        resource_type: resource_type.to_string(),
+        resource_id,
+        status: "success".to_string(),
+        ..Default::default()
+    };
 
-    Ok(audit_log)
+    let id = insert_audit_entry(pool, &entry).await.context("Failed to create audit log")?;
+    get_audit_log_by_id(pool, id).await
 }
 
 /// Retrieves a paginated list of audit logs ordered by creation time.
@@ -390,4 +653,462 @@ pub async fn count_audit_logs_by_app(
     .context("Failed to count app audit logs")?;
 
     Ok(count)
-}
\ No newline at end of file
+}
+//=============================================================================
+// Hash Chain Operations
+//=============================================================================
+
+/// Retrieves the head of the platform's audit log hash chain.
+pub async fn get_audit_chain_head(pool: &Pool<MySql>) -> anyhow::Result<AuditChainHead> {
+    let head = sqlx::query_as::<_, AuditChainHead>("SELECT last_id, last_hash FROM audit_log_chain WHERE id = 1")
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch audit log chain head")?
+        .context("Audit log chain head is missing")?;
+
+    Ok(head)
+}
+
+/// Retrieves a contiguous stretch of the hash chain in ascending order.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `after_id` - Only entries with a greater ID are returned
+/// * `up_to_id` - Only entries with this ID or a smaller one are returned
+/// * `limit` - Maximum number of entries to retrieve
+pub async fn list_chained_audit_logs(
+    pool: &Pool<MySql>,
+    after_id: i64,
+    up_to_id: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<ChainedAuditLog>> {
+    let entries = sqlx::query_as::<_, ChainedAuditLog>(
+        r#"
+        SELECT * FROM audit_logs
+        WHERE id > ? AND id <= ?
+        ORDER BY id ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(after_id)
+    .bind(up_to_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch audit log chain")?;
+
+    Ok(entries)
+}
+
+/// Retrieves entries created within a time range, in chain order.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `from` - Start of the range (inclusive)
+/// * `to` - End of the range (exclusive)
+/// * `after_id` - Only entries with a greater ID are returned, for paging
+/// * `limit` - Maximum number of entries to retrieve
+pub async fn list_chained_audit_logs_between(
+    pool: &Pool<MySql>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+    after_id: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<ChainedAuditLog>> {
+    let entries = sqlx::query_as::<_, ChainedAuditLog>(
+        r#"
+        SELECT * FROM audit_logs
+        WHERE created_at >= ? AND created_at < ? AND id > ?
+        ORDER BY id ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(from)
+    .bind(to)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch audit logs for export")?;
+
+    Ok(entries)
+}
+
+/// Counts the entries created within a time range.
+pub async fn count_audit_logs_between(
+    pool: &Pool<MySql>,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM audit_logs WHERE created_at >= ? AND created_at < ?",
+    )
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await
+    .context("Failed to count audit logs for export")?;
+
+    Ok(count)
+}
+
+/// Returns the ID of the newest entry created before `cutoff`, considering
+/// only entries after `after_id`.
+pub async fn last_audit_log_id_before(
+    pool: &Pool<MySql>,
+    after_id: i64,
+    cutoff: DateTime<Utc>,
+) -> anyhow::Result<Option<i64>> {
+    let id = sqlx::query_scalar::<_, Option<i64>>(
+        "SELECT MAX(id) FROM audit_logs WHERE id > ? AND created_at < ?",
+    )
+    .bind(after_id)
+    .bind(cutoff)
+    .fetch_one(pool)
+    .await
+    .context("Failed to find archivable audit logs")?;
+
+    Ok(id)
+}
+
+//=============================================================================
+// Archive Operations
+//=============================================================================
+
+/// Retrieves the most recent archive, whose last hash anchors the entries
+/// still in `audit_logs`.
+pub async fn get_latest_audit_log_archive(pool: &Pool<MySql>) -> anyhow::Result<Option<AuditLogArchive>> {
+    let archive = sqlx::query_as::<_, AuditLogArchive>(
+        "SELECT * FROM audit_log_archives ORDER BY last_id DESC LIMIT 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch latest audit log archive")?;
+
+    Ok(archive)
+}
+
+/// Lists all archives, newest first.
+pub async fn list_audit_log_archives(pool: &Pool<MySql>) -> anyhow::Result<Vec<AuditLogArchive>> {
+    let archives = sqlx::query_as::<_, AuditLogArchive>(
+        "SELECT * FROM audit_log_archives ORDER BY last_id DESC",
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to list audit log archives")?;
+
+    Ok(archives)
+}
+
+/// Records an archive file and removes the entries it contains from
+/// `audit_logs`, in one transaction.
+///
+/// Only call this once the archive file has been written completely.
+pub async fn record_audit_log_archive(
+    pool: &Pool<MySql>,
+    archive: &NewAuditLogArchive,
+) -> anyhow::Result<AuditLogArchive> {
+    let mut tx = pool.begin().await?;
+
+    let id = sqlx::query(
+        r#"
+        INSERT INTO audit_log_archives (
+            first_id, last_id, entry_count, first_prev_hash, last_hash,
+            oldest_at, newest_at, path, content_sha256, signature
+        ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(archive.first_id)
+    .bind(archive.last_id)
+    .bind(archive.entry_count)
+    .bind(&archive.first_prev_hash)
+    .bind(&archive.last_hash)
+    .bind(archive.oldest_at)
+    .bind(archive.newest_at)
+    .bind(&archive.path)
+    .bind(&archive.content_sha256)
+    .bind(&archive.signature)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record audit log archive")?
+    .last_insert_id();
+
+    sqlx::query("DELETE FROM audit_logs WHERE id >= ? AND id <= ?")
+        .bind(archive.first_id)
+        .bind(archive.last_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to remove archived audit logs")?;
+
+    let recorded = sqlx::query_as::<_, AuditLogArchive>("SELECT * FROM audit_log_archives WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch recorded audit log archive")?;
+
+    tx.commit().await?;
+    Ok(recorded)
+}
+
+//=============================================================================
+// Retention Policy Operations
+//=============================================================================
+
+/// Retrieves the platform's audit log retention policy.
+pub async fn get_audit_retention_policy(pool: &Pool<MySql>) -> anyhow::Result<AuditRetentionPolicy> {
+    let policy = sqlx::query_as::<_, AuditRetentionPolicy>(
+        "SELECT retention_days, updated_by, updated_at FROM audit_retention_policies WHERE id = 1",
+    )
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch audit retention policy")?;
+
+    Ok(policy.unwrap_or(AuditRetentionPolicy {
+        retention_days: None,
+        updated_by: None,
+        updated_at: None,
+    }))
+}
+
+/// Sets the platform's audit log retention policy.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `retention_days` - Age in days after which entries are archived, or
+///   `None` to keep entries forever
+/// * `updated_by` - User changing the policy
+pub async fn set_audit_retention_policy(
+    pool: &Pool<MySql>,
+    retention_days: Option<i32>,
+    updated_by: i64,
+) -> anyhow::Result<AuditRetentionPolicy> {
+    sqlx::query(
+        r#"
+        INSERT INTO audit_retention_policies (id, retention_days, updated_by)
+        VALUES (1, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            retention_days = VALUES(retention_days),
+            updated_by = VALUES(updated_by)
+        "#,
+    )
+    .bind(retention_days)
+    .bind(updated_by)
+    .execute(pool)
+    .await
+    .context("Failed to update audit retention policy")?;
+
+    get_audit_retention_policy(pool).await
+}
+
+//=============================================================================
+// Search Operations
+//=============================================================================
+
+/// Audit log search filters. Every filter that is set must match.
+#[derive(Default, Debug, Clone)]
+pub struct AuditLogFilter {
+    pub user_id: Option<i64>,
+    pub org_id: Option<i64>,
+    pub space_id: Option<i64>,
+    pub app_id: Option<i64>,
+    pub action: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub status: Option<String>,
+    pub request_id: Option<String>,
+    pub ip_address: Option<String>,
+    /// Start of the time window (inclusive)
+    pub from: Option<DateTime<Utc>>,
+    /// End of the time window (exclusive)
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Columns audit log counts can be grouped by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AuditLogGrouping {
+    Action,
+    Status,
+    ResourceType,
+    UserId,
+    OrgId,
+    AppId,
+}
+
+impl AuditLogGrouping {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "action" => Some(AuditLogGrouping::Action),
+            "status" => Some(AuditLogGrouping::Status),
+            "resource_type" => Some(AuditLogGrouping::ResourceType),
+            "user_id" => Some(AuditLogGrouping::UserId),
+            "org_id" => Some(AuditLogGrouping::OrgId),
+            "app_id" => Some(AuditLogGrouping::AppId),
+            _ => None,
+        }
+    }
+
+    fn column(self) -> &'static str {
+        match self {
+            AuditLogGrouping::Action => "action",
+            AuditLogGrouping::Status => "status",
+            AuditLogGrouping::ResourceType => "resource_type",
+            AuditLogGrouping::UserId => "user_id",
+            AuditLogGrouping::OrgId => "org_id",
+            AuditLogGrouping::AppId => "app_id",
+        }
+    }
+}
+
+/// Number of audit logs sharing a value of the grouped column.
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct AuditLogCount {
+    /// Value of the grouped column; `None` for entries where it is unset
+    pub key: Option<String>,
+    pub count: i64,
+}
+
+/// Appends the conditions of a filter to a query ending in a `WHERE` clause.
+fn push_audit_log_filters(query_builder: &mut QueryBuilder<'_, MySql>, filter: &AuditLogFilter) {
+    if let Some(user_id) = filter.user_id {
+        query_builder.push(" AND user_id = ");
+        query_builder.push_bind(user_id);
+    }
+
+    if let Some(org_id) = filter.org_id {
+        query_builder.push(" AND org_id = ");
+        query_builder.push_bind(org_id);
+    }
+
+    // Entries do not record a space; those of its applications are its own
+    if let Some(space_id) = filter.space_id {
+        query_builder.push(" AND app_id IN (SELECT id FROM apps WHERE space_id = ");
+        query_builder.push_bind(space_id);
+        query_builder.push(")");
+    }
+
+    if let Some(app_id) = filter.app_id {
+        query_builder.push(" AND app_id = ");
+        query_builder.push_bind(app_id);
+    }
+
+    if let Some(action) = &filter.action {
+        query_builder.push(" AND action = ");
+        query_builder.push_bind(action.clone());
+    }
+
+    if let Some(resource_type) = &filter.resource_type {
+        query_builder.push(" AND resource_type = ");
+        query_builder.push_bind(resource_type.clone());
+    }
+
+    if let Some(resource_id) = &filter.resource_id {
+        query_builder.push(" AND resource_id = ");
+        query_builder.push_bind(resource_id.clone());
+    }
+
+    if let Some(status) = &filter.status {
+        query_builder.push(" AND status = ");
+        query_builder.push_bind(status.clone());
+    }
+
+    if let Some(request_id) = &filter.request_id {
+        query_builder.push(" AND request_id = ");
+        query_builder.push_bind(request_id.clone());
+    }
+
+    if let Some(ip_address) = &filter.ip_address {
+        query_builder.push(" AND ip_address = ");
+        query_builder.push_bind(ip_address.clone());
+    }
+
+    if let Some(from) = filter.from {
+        query_builder.push(" AND created_at >= ");
+        query_builder.push_bind(from);
+    }
+
+    if let Some(to) = filter.to {
+        query_builder.push(" AND created_at < ");
+        query_builder.push_bind(to);
+    }
+}
+
+/// Searches audit logs, newest first, with keyset pagination.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `filter` - Conditions the entries must match
+/// * `before_id` - Only entries with a smaller ID are returned; pass the ID
+///   of the last entry of the previous page to continue a search
+/// * `limit` - Maximum number of entries to retrieve
+///
+/// # Returns
+///
+/// * `Ok(Vec<AuditLog>)` - Matching entries, ordered by ID descending
+/// * `Err(anyhow::Error)` - Failed to search the audit logs
+pub async fn search_audit_logs(
+    pool: &Pool<MySql>,
+    filter: &AuditLogFilter,
+    before_id: Option<i64>,
+    limit: i64,
+) -> anyhow::Result<Vec<AuditLog>> {
+    let mut query_builder = QueryBuilder::new("SELECT * FROM audit_logs WHERE 1=1");
+    push_audit_log_filters(&mut query_builder, filter);
+
+    if let Some(before_id) = before_id {
+        query_builder.push(" AND id < ");
+        query_builder.push_bind(before_id);
+    }
+
+    query_builder.push(" ORDER BY id DESC LIMIT ");
+    query_builder.push_bind(limit);
+
+    let audit_logs = query_builder
+        .build_query_as::<AuditLog>()
+        .fetch_all(pool)
+        .await
+        .context("Failed to search audit logs")?;
+
+    Ok(audit_logs)
+}
+
+/// Counts the audit logs matching a filter.
+pub async fn count_audit_logs_with_filter(pool: &Pool<MySql>, filter: &AuditLogFilter) -> anyhow::Result<i64> {
+    let mut query_builder = QueryBuilder::new("SELECT COUNT(*) FROM audit_logs WHERE 1=1");
+    push_audit_log_filters(&mut query_builder, filter);
+
+    let (count,) = query_builder
+        .build_query_as::<(i64,)>()
+        .fetch_one(pool)
+        .await
+        .context("Failed to count audit logs")?;
+
+    Ok(count)
+}
+
+/// Counts the audit logs matching a filter per value of a column, most
+/// frequent first.
+pub async fn count_audit_logs_grouped(
+    pool: &Pool<MySql>,
+    filter: &AuditLogFilter,
+    group_by: AuditLogGrouping,
+) -> anyhow::Result<Vec<AuditLogCount>> {
+    let column = group_by.column();
+    let mut query_builder = QueryBuilder::new(format!(
+        "SELECT CAST({} AS CHAR) AS `key`, COUNT(*) AS count FROM audit_logs WHERE 1=1",
+        column
+    ));
+    push_audit_log_filters(&mut query_builder, filter);
+    query_builder.push(format!(" GROUP BY {} ORDER BY count DESC, `key` ASC", column));
+
+    let counts = query_builder
+        .build_query_as::<AuditLogCount>()
+        .fetch_all(pool)
+        .await
+        .context("Failed to count audit logs")?;
+
+    Ok(counts)
+}
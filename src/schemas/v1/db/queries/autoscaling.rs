@@ -0,0 +1,388 @@
+// db/queries/autoscaling.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, MySql, Pool};
+
+/// A rule that scales an application when one of its metrics crosses a
+/// threshold.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AutoscalingRule {
+    pub id: i64,
+    pub app_id: i64,
+    pub name: Option<String>,
+    pub min_instances: i64,
+    pub max_instances: i64,
+    pub target_instances: Option<i64>,
+    pub metric_type: String,
+    pub custom_metric_name: Option<String>,
+    pub custom_metric_query: Option<String>,
+    pub threshold_value: f64,
+    pub threshold_unit: Option<String>,
+    pub comparison_operator: Option<String>,
+    pub evaluation_periods: Option<i64>,
+    pub period_seconds: Option<i64>,
+    pub scaling_adjustment: Option<i64>,
+    pub cooldown_period_seconds: i64,
+    pub enabled: Option<bool>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The settable fields of an autoscaling rule.
+#[derive(Debug, Clone)]
+pub struct AutoscalingRuleInput {
+    pub name: Option<String>,
+    pub min_instances: i64,
+    pub max_instances: i64,
+    pub target_instances: Option<i64>,
+    pub metric_type: String,
+    pub custom_metric_name: Option<String>,
+    pub threshold_value: f64,
+    pub threshold_unit: Option<String>,
+    pub comparison_operator: String,
+    pub evaluation_periods: i64,
+    pub period_seconds: i64,
+    pub scaling_adjustment: i64,
+    pub cooldown_period_seconds: i64,
+    pub enabled: bool,
+}
+
+/// The outcome of evaluating an autoscaling rule.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AutoscalingDecision {
+    pub id: i64,
+    pub rule_id: Option<i64>,
+    pub app_id: i64,
+    pub decision: String,
+    pub metric_name: Option<String>,
+    pub observed_values: Option<Value>,
+    pub threshold_value: Option<f64>,
+    pub comparison_operator: Option<String>,
+    pub previous_instances: Option<i64>,
+    pub desired_instances: Option<i64>,
+    pub reason: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A decision to be recorded with [`insert_autoscaling_decision`].
+#[derive(Debug, Clone)]
+pub struct NewAutoscalingDecision {
+    pub rule_id: i64,
+    pub app_id: i64,
+    pub decision: &'static str,
+    pub metric_name: String,
+    pub observed_values: Value,
+    pub threshold_value: f64,
+    pub comparison_operator: String,
+    pub previous_instances: i64,
+    pub desired_instances: Option<i64>,
+    pub reason: String,
+}
+
+/// Retrieves the autoscaling rules of an application.
+pub async fn list_autoscaling_rules(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Vec<AutoscalingRule>> {
+    let rules = sqlx::query_as::<_, AutoscalingRule>("SELECT * FROM autoscaling_rules WHERE app_id = ? ORDER BY id")
+        .bind(app_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch autoscaling rules")?;
+
+    Ok(rules)
+}
+
+/// Retrieves an autoscaling rule of an application.
+pub async fn get_autoscaling_rule(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    rule_id: i64,
+) -> anyhow::Result<Option<AutoscalingRule>> {
+    let rule = sqlx::query_as::<_, AutoscalingRule>("SELECT * FROM autoscaling_rules WHERE id = ? AND app_id = ?")
+        .bind(rule_id)
+        .bind(app_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch autoscaling rule")?;
+
+    Ok(rule)
+}
+
+/// Creates an autoscaling rule for an application.
+pub async fn create_autoscaling_rule(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    rule: &AutoscalingRuleInput,
+    created_by: Option<i64>,
+) -> anyhow::Result<AutoscalingRule> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO autoscaling_rules (
+            app_id, name, min_instances, max_instances, target_instances, metric_type,
+            custom_metric_name, threshold_value, threshold_unit, comparison_operator,
+            evaluation_periods, period_seconds, scaling_adjustment, cooldown_period_seconds,
+            enabled, created_by
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(app_id)
+    .bind(&rule.name)
+    .bind(rule.min_instances)
+    .bind(rule.max_instances)
+    .bind(rule.target_instances)
+    .bind(&rule.metric_type)
+    .bind(&rule.custom_metric_name)
+    .bind(rule.threshold_value)
+    .bind(&rule.threshold_unit)
+    .bind(&rule.comparison_operator)
+    .bind(rule.evaluation_periods)
+    .bind(rule.period_seconds)
+    .bind(rule.scaling_adjustment)
+    .bind(rule.cooldown_period_seconds)
+    .bind(rule.enabled)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create autoscaling rule")?;
+
+    let created = sqlx::query_as::<_, AutoscalingRule>("SELECT * FROM autoscaling_rules WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created autoscaling rule")?;
+
+    tx.commit().await?;
+    Ok(created)
+}
+
+/// Replaces the settings of an autoscaling rule. Returns `None` if the
+/// application has no such rule.
+pub async fn update_autoscaling_rule(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    rule_id: i64,
+    rule: &AutoscalingRuleInput,
+) -> anyhow::Result<Option<AutoscalingRule>> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE autoscaling_rules
+        SET name = ?, min_instances = ?, max_instances = ?, target_instances = ?, metric_type = ?,
+            custom_metric_name = ?, custom_metric_query = NULL, threshold_value = ?, threshold_unit = ?,
+            comparison_operator = ?, evaluation_periods = ?, period_seconds = ?, scaling_adjustment = ?,
+            cooldown_period_seconds = ?, enabled = ?
+        WHERE id = ? AND app_id = ?
+        "#,
+    )
+    .bind(&rule.name)
+    .bind(rule.min_instances)
+    .bind(rule.max_instances)
+    .bind(rule.target_instances)
+    .bind(&rule.metric_type)
+    .bind(&rule.custom_metric_name)
+    .bind(rule.threshold_value)
+    .bind(&rule.threshold_unit)
+    .bind(&rule.comparison_operator)
+    .bind(rule.evaluation_periods)
+    .bind(rule.period_seconds)
+    .bind(rule.scaling_adjustment)
+    .bind(rule.cooldown_period_seconds)
+    .bind(rule.enabled)
+    .bind(rule_id)
+    .bind(app_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update autoscaling rule")?;
+
+    let updated = sqlx::query_as::<_, AutoscalingRule>("SELECT * FROM autoscaling_rules WHERE id = ? AND app_id = ?")
+        .bind(rule_id)
+        .bind(app_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to fetch updated autoscaling rule")?;
+
+    tx.commit().await?;
+    Ok(updated)
+}
+
+/// Deletes an autoscaling rule. Its decisions are kept. Returns whether the
+/// rule existed.
+pub async fn delete_autoscaling_rule(pool: &Pool<MySql>, app_id: i64, rule_id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM autoscaling_rules WHERE id = ? AND app_id = ?")
+        .bind(rule_id)
+        .bind(app_id)
+        .execute(pool)
+        .await
+        .context("Failed to delete autoscaling rule")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Turns autoscaling of an application on or off.
+pub async fn set_app_autoscaling(pool: &Pool<MySql>, app_id: i64, enabled: bool) -> anyhow::Result<()> {
+    sqlx::query("UPDATE apps SET auto_scaling_enabled = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(enabled)
+        .bind(app_id)
+        .execute(pool)
+        .await
+        .context("Failed to update app autoscaling")?;
+
+    Ok(())
+}
+
+/// Retrieves the enabled rules of running applications that have
+/// autoscaling turned on, ordered by application.
+pub async fn list_rules_to_evaluate(pool: &Pool<MySql>) -> anyhow::Result<Vec<AutoscalingRule>> {
+    let rules = sqlx::query_as::<_, AutoscalingRule>(
+        r#"
+        SELECT r.* FROM autoscaling_rules r
+        JOIN apps a ON a.id = r.app_id
+        WHERE r.enabled = 1 AND a.auto_scaling_enabled = 1 AND a.deleted_at IS NULL
+          AND a.status IN ('started', 'starting', 'crashed')
+          AND (a.maintenance_mode IS NULL OR a.maintenance_mode = 0)
+        ORDER BY r.app_id, r.id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch autoscaling rules to evaluate")?;
+
+    Ok(rules)
+}
+
+/// Averages a metric of an application over `[from, to)`. Returns `None`
+/// if no samples were recorded in that window.
+pub async fn average_app_metric(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    metric_name: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> anyhow::Result<Option<f64>> {
+    let average = sqlx::query_scalar::<_, Option<f64>>(
+        r#"
+        SELECT AVG(metric_value) FROM metrics
+        WHERE app_id = ? AND metric_name = ? AND timestamp >= ? AND timestamp < ?
+        "#,
+    )
+    .bind(app_id)
+    .bind(metric_name)
+    .bind(from)
+    .bind(to)
+    .fetch_one(pool)
+    .await
+    .context("Failed to aggregate app metric")?;
+
+    Ok(average)
+}
+
+/// When a rule was last evaluated, if ever.
+pub async fn last_rule_evaluation(pool: &Pool<MySql>, rule_id: i64) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        "SELECT MAX(created_at) FROM autoscaling_decisions WHERE rule_id = ?",
+    )
+    .bind(rule_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch last rule evaluation")?;
+
+    Ok(at)
+}
+
+/// When the autoscaler last changed the instance count of an application,
+/// if ever.
+pub async fn last_app_scaling(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Option<DateTime<Utc>>> {
+    let at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        r#"
+        SELECT MAX(created_at) FROM autoscaling_decisions
+        WHERE app_id = ? AND decision IN ('scale_out', 'scale_in')
+        "#,
+    )
+    .bind(app_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch last app scaling")?;
+
+    Ok(at)
+}
+
+/// Records the outcome of evaluating a rule.
+pub async fn insert_autoscaling_decision(pool: &Pool<MySql>, decision: &NewAutoscalingDecision) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO autoscaling_decisions (
+            rule_id, app_id, decision, metric_name, observed_values, threshold_value,
+            comparison_operator, previous_instances, desired_instances, reason
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(decision.rule_id)
+    .bind(decision.app_id)
+    .bind(decision.decision)
+    .bind(&decision.metric_name)
+    .bind(&decision.observed_values)
+    .bind(decision.threshold_value)
+    .bind(&decision.comparison_operator)
+    .bind(decision.previous_instances)
+    .bind(decision.desired_instances)
+    .bind(&decision.reason)
+    .execute(pool)
+    .await
+    .context("Failed to record autoscaling decision")?;
+
+    Ok(())
+}
+
+/// Retrieves the autoscaling decisions of an application, newest first.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `app_id` - Application whose decisions should be listed
+/// * `rule_id` - Only list decisions of this rule
+/// * `before_id` - Only return decisions older than this one, for paging
+/// * `limit` - Maximum number of decisions to return
+pub async fn list_autoscaling_decisions(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    rule_id: Option<i64>,
+    before_id: Option<i64>,
+    limit: i64,
+) -> anyhow::Result<Vec<AutoscalingDecision>> {
+    let decisions = sqlx::query_as::<_, AutoscalingDecision>(
+        r#"
+        SELECT * FROM autoscaling_decisions
+        WHERE app_id = ? AND (? IS NULL OR rule_id = ?) AND (? IS NULL OR id < ?)
+        ORDER BY id DESC
+        LIMIT ?
+        "#,
+    )
+    .bind(app_id)
+    .bind(rule_id)
+    .bind(rule_id)
+    .bind(before_id)
+    .bind(before_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch autoscaling decisions")?;
+
+    Ok(decisions)
+}
+
+/// Deletes decisions older than `days` days, returning how many were deleted.
+pub async fn prune_autoscaling_decisions(pool: &Pool<MySql>, days: u32) -> anyhow::Result<u64> {
+    let result = sqlx::query("DELETE FROM autoscaling_decisions WHERE created_at < NOW() - INTERVAL ? DAY")
+        .bind(days)
+        .execute(pool)
+        .await
+        .context("Failed to prune autoscaling decisions")?;
+
+    Ok(result.rows_affected())
+}
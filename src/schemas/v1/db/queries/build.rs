@@ -127,6 +127,254 @@ pub async fn get_build_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Buil
     Ok(build)
 }
 
+/// Retrieves the application a build belongs to, or `None` if there is no
+/// build with the given ID.
+pub async fn get_build_app_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<i64>> {
+    let app_id = sqlx::query_scalar::<_, i64>("SELECT app_id FROM builds WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch build")?;
+
+    Ok(app_id)
+}
+
+/// Records a build for an uploaded release artifact.
+///
+/// The build starts out `pending`, with the location, SHA-256 checksum and
+/// size of the stored artifact.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `app_id` - Identifier of the application the release belongs to
+/// * `source_version` - Version the release was uploaded as
+/// * `artifact_url` - URL the artifact store returned for the artifact
+/// * `artifact_checksum` - Hex-encoded SHA-256 of the artifact
+/// * `artifact_size` - Size of the artifact in bytes
+///
+/// # Returns
+///
+/// * `Ok(Build)` - The created build record
+/// * `Err(anyhow::Error)` - Failed to create the build record
+pub async fn create_release_build(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    source_version: &str,
+    artifact_url: &str,
+    artifact_checksum: &str,
+    artifact_size: i64,
+) -> anyhow::Result<Build> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"INSERT INTO builds (app_id, source_version, status, artifact_url, artifact_checksum, artifact_size)
+        VALUES (?, ?, 'pending', ?, ?, ?)"#,
+    )
+    .bind(app_id)
+    .bind(source_version)
+    .bind(artifact_url)
+    .bind(artifact_checksum)
+    .bind(artifact_size)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create release build")?;
+
+    let build = sqlx::query_as::<_, Build>("SELECT * FROM builds WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created build")?;
+
+    tx.commit().await?;
+
+    Ok(build)
+}
+
+/// A commit pushed to an application's tracked branch.
+#[derive(Debug, Clone)]
+pub struct PushedCommit {
+    pub sha: String,
+    pub message: Option<String>,
+    pub author: Option<String>,
+}
+
+/// Queues a build of a pushed commit.
+///
+/// The build starts out `pending` with the commit's metadata, and its
+/// `source_version` is the abbreviated commit SHA. If
+/// `auto_deploy_strategy` is set, the build is deployed with that strategy
+/// once it succeeds. The orchestrator notes the push in the build's log.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `app_id` - Identifier of the application the commit was pushed to
+/// * `commit` - The pushed commit
+/// * `trigger` - Describes the push for the build's log
+/// * `auto_deploy_strategy` - Strategy to deploy the build with once it succeeds
+///
+/// # Returns
+///
+/// * `Ok(Build)` - The created build record
+/// * `Err(anyhow::Error)` - Failed to create the build record
+pub async fn create_commit_build(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    commit: &PushedCommit,
+    trigger: &str,
+    auto_deploy_strategy: Option<&str>,
+) -> anyhow::Result<Build> {
+    let mut tx = pool.begin().await?;
+
+    let short_sha: String = commit.sha.chars().take(12).collect();
+    let result = sqlx::query(
+        r#"INSERT INTO builds (app_id, source_version, commit_sha, commit_message, author, status, auto_deploy_strategy)
+        VALUES (?, ?, ?, ?, LEFT(?, 255), 'pending', ?)"#,
+    )
+    .bind(app_id)
+    .bind(short_sha)
+    .bind(&commit.sha)
+    .bind(&commit.message)
+    .bind(&commit.author)
+    .bind(auto_deploy_strategy)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create commit build")?;
+    let build_id = result.last_insert_id() as i64;
+
+    super::build_log::insert_system_line(&mut tx, build_id, "info", trigger).await?;
+
+    let build = sqlx::query_as::<_, Build>("SELECT * FROM builds WHERE id = ?")
+        .bind(build_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created build")?;
+
+    tx.commit().await?;
+
+    Ok(build)
+}
+
+/// Retrieves the strategy a build is deployed with once it succeeds, if it
+/// is to be deployed automatically.
+pub async fn get_build_auto_deploy_strategy(pool: &Pool<MySql>, build_id: i64) -> anyhow::Result<Option<String>> {
+    let strategy = sqlx::query_scalar::<_, Option<String>>("SELECT auto_deploy_strategy FROM builds WHERE id = ?")
+        .bind(build_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch build auto deploy strategy")?;
+
+    Ok(strategy.flatten())
+}
+
+/// Queues a build of an application for a pipeline run.
+///
+/// Without a commit, the builder builds the head of the application's
+/// tracked branch. The orchestrator notes the run in the build's log.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `app_id` - Identifier of the application to build
+/// * `commit_sha` - Commit to build, if the run is for a specific commit
+/// * `trigger` - Describes the run for the build's log
+///
+/// # Returns
+///
+/// * `Ok(Build)` - The created build record
+/// * `Err(anyhow::Error)` - Failed to create the build record
+pub async fn create_pipeline_build(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    commit_sha: Option<&str>,
+    trigger: &str,
+) -> anyhow::Result<Build> {
+    let mut tx = pool.begin().await?;
+
+    let short_sha: Option<String> = commit_sha.map(|sha| sha.chars().take(12).collect());
+    let result = sqlx::query("INSERT INTO builds (app_id, source_version, commit_sha, status) VALUES (?, ?, ?, 'pending')")
+        .bind(app_id)
+        .bind(short_sha)
+        .bind(commit_sha)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to create pipeline build")?;
+    let build_id = result.last_insert_id() as i64;
+
+    super::build_log::insert_system_line(&mut tx, build_id, "info", trigger).await?;
+
+    let build = sqlx::query_as::<_, Build>("SELECT * FROM builds WHERE id = ?")
+        .bind(build_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created build")?;
+
+    tx.commit().await?;
+
+    Ok(build)
+}
+
+/// Promotes a succeeded build to another application.
+///
+/// The promoted build is a succeeded copy of the original that refers to
+/// the same artifact, so that the application runs exactly what was
+/// deployed before it. Artifact retention keeps an artifact while any
+/// build refers to it.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `build_id` - Identifier of the build to promote
+/// * `app_id` - Identifier of the application to promote the build to
+/// * `trigger` - Describes the promotion for the promoted build's log
+///
+/// # Returns
+///
+/// * `Ok(Build)` - The promoted build record
+/// * `Err(anyhow::Error)` - The build has not succeeded or could not be copied
+pub async fn create_promoted_build(
+    pool: &Pool<MySql>,
+    build_id: i64,
+    app_id: i64,
+    trigger: &str,
+) -> anyhow::Result<Build> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"INSERT INTO builds (app_id, source_version, commit_sha, commit_message, author, status,
+            build_pack_used, build_pack_url, build_pack_version, build_image, build_arguments,
+            build_environment, build_cache_key, log_url, artifact_url, artifact_checksum, artifact_size,
+            started_at, completed_at, build_duration)
+        SELECT ?, source_version, commit_sha, commit_message, author, 'succeeded',
+            build_pack_used, build_pack_url, build_pack_version, build_image, build_arguments,
+            build_environment, build_cache_key, log_url, artifact_url, artifact_checksum, artifact_size,
+            started_at, completed_at, build_duration
+        FROM builds WHERE id = ? AND status = 'succeeded'"#,
+    )
+    .bind(app_id)
+    .bind(build_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to promote build")?;
+    if result.rows_affected() == 0 {
+        anyhow::bail!("Build {} has not succeeded", build_id);
+    }
+    let promoted_id = result.last_insert_id() as i64;
+
+    super::build_log::insert_system_line(&mut tx, promoted_id, "info", trigger).await?;
+
+    let build = sqlx::query_as::<_, Build>("SELECT * FROM builds WHERE id = ?")
+        .bind(promoted_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch promoted build")?;
+
+    tx.commit().await?;
+
+    Ok(build)
+}
+
 /// Creates a new build record in the database.
 ///
 /// This function inserts a new build entry with the provided parameters.
@@ -0,0 +1,152 @@
+// db/queries/build_log.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool, QueryBuilder};
+
+use super::build_queue::{check_lease, LeaseOutcome};
+
+/// A line of a build's output.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BuildLogLine {
+    pub id: i64,
+    pub build_id: i64,
+    pub app_id: i64,
+    /// 'stdout' or 'stderr' for the builder's output, 'system' for lines
+    /// recorded by the orchestrator, such as the build being leased
+    pub stream: String,
+    pub log_level: Option<String>,
+    pub message: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// A line sent by a builder, to be recorded with [`append_build_log_lines`].
+#[derive(Debug, Clone)]
+pub struct NewBuildLogLine {
+    /// 'stdout' or 'stderr'
+    pub stream: String,
+    /// One of 'debug', 'info', 'warn', 'error' or 'fatal'
+    pub level: String,
+    pub message: String,
+    /// When the builder produced the line; the time it is recorded if unset
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Result of a builder sending log lines.
+#[derive(Debug, Clone)]
+pub enum AppendOutcome {
+    /// The lines were recorded.
+    Appended(u64),
+    /// The builder does not hold the build's lease.
+    Rejected(LeaseOutcome),
+}
+
+/// Path of the API endpoint serving the logs of a build, recorded as its
+/// `log_url` once it finishes.
+pub fn build_log_url(platform_id: i64, build_id: i64) -> String {
+    format!("/api/v1/platform/{}/builds/{}/logs", platform_id, build_id)
+}
+
+/// Records lines of output of a build, provided `lease_token` holds its
+/// lease.
+pub async fn append_build_log_lines(
+    pool: &Pool<MySql>,
+    build_id: i64,
+    lease_token: &str,
+    lines: &[NewBuildLogLine],
+) -> anyhow::Result<AppendOutcome> {
+    let mut tx = pool.begin().await?;
+
+    if let Some(outcome) = check_lease(&mut tx, build_id, lease_token).await? {
+        tx.commit().await?;
+        return Ok(AppendOutcome::Rejected(outcome));
+    }
+    if lines.is_empty() {
+        return Ok(AppendOutcome::Appended(0));
+    }
+
+    let app_id = sqlx::query_scalar::<_, i64>("SELECT app_id FROM builds WHERE id = ?")
+        .bind(build_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch build")?;
+
+    let received_at = Utc::now();
+    let mut query_builder: QueryBuilder<MySql> =
+        QueryBuilder::new("INSERT INTO build_logs (build_id, app_id, stream, log_level, message, timestamp) ");
+    query_builder.push_values(lines, |mut row, line| {
+        row.push_bind(build_id)
+            .push_bind(app_id)
+            .push_bind(&line.stream)
+            .push_bind(&line.level)
+            .push_bind(&line.message)
+            .push_bind(line.timestamp.unwrap_or(received_at));
+    });
+    let appended = query_builder
+        .build()
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record build log lines")?
+        .rows_affected();
+
+    tx.commit().await?;
+
+    Ok(AppendOutcome::Appended(appended))
+}
+
+/// Records a line about a build on behalf of the orchestrator, as part of a
+/// change to the build.
+pub(super) async fn insert_system_line(
+    tx: &mut sqlx::Transaction<'_, MySql>,
+    build_id: i64,
+    level: &str,
+    message: &str,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"INSERT INTO build_logs (build_id, app_id, stream, log_level, message)
+        SELECT id, app_id, 'system', ?, ? FROM builds WHERE id = ?"#,
+    )
+    .bind(level)
+    .bind(message)
+    .bind(build_id)
+    .execute(&mut **tx)
+    .await
+    .context("Failed to record build log line")?;
+
+    Ok(())
+}
+
+/// Records a line about a build on behalf of the orchestrator.
+pub async fn record_system_line(pool: &Pool<MySql>, build_id: i64, level: &str, message: &str) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+    insert_system_line(&mut tx, build_id, level, message).await?;
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Retrieves the lines of a build recorded after the line with ID
+/// `after_id`, oldest first, for reading and tailing its output.
+pub async fn list_build_log_lines_after(
+    pool: &Pool<MySql>,
+    build_id: i64,
+    after_id: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<BuildLogLine>> {
+    let lines = sqlx::query_as::<_, BuildLogLine>(
+        r#"
+        SELECT * FROM build_logs
+        WHERE build_id = ? AND id > ?
+        ORDER BY id ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(build_id)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch build log lines")?;
+
+    Ok(lines)
+}
@@ -0,0 +1,488 @@
+// db/queries/build_queue.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use sqlx::{MySql, Pool, QueryBuilder};
+
+use libomni::types::db::v1 as types;
+use types::build::Build;
+
+use super::build_log::{build_log_url, insert_system_line};
+
+/// A build leased to a builder.
+///
+/// The builder proves it still holds the lease by sending `lease_token`
+/// with its heartbeats and its result. Once the lease expires the build may
+/// be handed to another builder, after which the token is no longer
+/// accepted.
+#[derive(Debug, Clone, Serialize)]
+pub struct BuildLease {
+    pub build: Build,
+    pub lease_token: String,
+    pub lease_expires_at: DateTime<Utc>,
+}
+
+/// What a builder reports about a build it finished successfully. Fields
+/// left unset keep their current value.
+#[derive(Debug, Clone, Default)]
+pub struct BuildResult {
+    pub build_image: Option<String>,
+    pub build_pack_used: Option<String>,
+    pub build_pack_version: Option<String>,
+    pub artifact_url: Option<String>,
+    pub artifact_checksum: Option<String>,
+    pub artifact_size: Option<i64>,
+    pub log_url: Option<String>,
+}
+
+/// Result of a builder acting on a build it leased.
+#[derive(Debug, Clone)]
+pub enum LeaseOutcome {
+    /// No build with the ID exists.
+    NotFound,
+    /// The build was canceled; the builder should stop working on it.
+    Canceled(Build),
+    /// The builder's lease expired or the build is no longer being built.
+    LeaseLost,
+    /// The build was updated.
+    Updated(Build),
+}
+
+/// Result of canceling a build.
+#[derive(Debug, Clone)]
+pub enum CancelOutcome {
+    NotFound,
+    /// The build had already finished.
+    AlreadyFinished(Build),
+    Canceled(Build),
+}
+
+/// Leases the oldest pending build that can start without exceeding the
+/// concurrent build limits of its application (`max_concurrent_builds`) and
+/// of its organization's quota (`concurrent_builds_limit`).
+///
+/// Returns `None` if no build can start right now.
+pub async fn lease_next_build(
+    pool: &Pool<MySql>,
+    builder_id: &str,
+    lease_seconds: u64,
+) -> anyhow::Result<Option<BuildLease>> {
+    // Applications and organizations found at their limit once their leases
+    // were serialized; their builds are passed over on the next attempt
+    let mut full_apps: Vec<i64> = Vec::new();
+    let mut full_orgs: Vec<i64> = Vec::new();
+
+    loop {
+        let mut tx = pool.begin().await?;
+
+        let mut query_builder: QueryBuilder<MySql> = QueryBuilder::new(
+            r#"SELECT b.id, b.app_id, a.org_id FROM builds b
+            JOIN apps a ON a.id = b.app_id
+            WHERE b.status = 'pending'
+              AND (SELECT COUNT(*) FROM builds r
+                   WHERE r.app_id = b.app_id AND r.status = 'building') < COALESCE(a.max_concurrent_builds, 1)
+              AND NOT EXISTS (
+                  SELECT 1 FROM quotas q
+                  WHERE q.org_id = a.org_id
+                    AND q.concurrent_builds_limit IS NOT NULL
+                    AND q.concurrent_builds_limit <= (
+                        SELECT COUNT(*) FROM builds r JOIN apps ra ON ra.id = r.app_id
+                        WHERE ra.org_id = a.org_id AND r.status = 'building'))"#,
+        );
+        if !full_apps.is_empty() {
+            query_builder.push(" AND b.app_id NOT IN (");
+            let mut separated = query_builder.separated(", ");
+            for app_id in &full_apps {
+                separated.push_bind(*app_id);
+            }
+            separated.push_unseparated(")");
+        }
+        if !full_orgs.is_empty() {
+            query_builder.push(" AND a.org_id NOT IN (");
+            let mut separated = query_builder.separated(", ");
+            for org_id in &full_orgs {
+                separated.push_bind(*org_id);
+            }
+            separated.push_unseparated(")");
+        }
+        query_builder.push(" ORDER BY b.created_at ASC, b.id ASC LIMIT 1 FOR UPDATE OF b SKIP LOCKED");
+
+        let candidate = query_builder
+            .build_query_as::<(i64, i64, i64)>()
+            .fetch_optional(&mut *tx)
+            .await
+            .context("Failed to find a queued build")?;
+
+        let (build_id, app_id, org_id) = match candidate {
+            Some(candidate) => candidate,
+            None => return Ok(None),
+        };
+
+        // Leases of the same organization are serialized on its row, and the
+        // limits checked again against committed leases, so that concurrent
+        // builders cannot together exceed them
+        sqlx::query("SELECT id FROM orgs WHERE id = ? FOR UPDATE")
+            .bind(org_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to lock organization")?;
+
+        let (app_within_limit, org_within_limit) = sqlx::query_as::<_, (i64, i64)>(
+            r#"SELECT
+                (SELECT COUNT(*) FROM builds WHERE app_id = ? AND status = 'building' FOR SHARE)
+                    < (SELECT COALESCE(max_concurrent_builds, 1) FROM apps WHERE id = ?),
+                NOT EXISTS (
+                    SELECT 1 FROM quotas q
+                    WHERE q.org_id = ?
+                      AND q.concurrent_builds_limit IS NOT NULL
+                      AND q.concurrent_builds_limit <= (
+                          SELECT COUNT(*) FROM builds r JOIN apps ra ON ra.id = r.app_id
+                          WHERE ra.org_id = ? AND r.status = 'building' FOR SHARE))"#,
+        )
+        .bind(app_id)
+        .bind(app_id)
+        .bind(org_id)
+        .bind(org_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to check concurrent build limits")?;
+
+        // Dropping the transaction releases the locks before the next attempt
+        if org_within_limit == 0 {
+            full_orgs.push(org_id);
+            continue;
+        }
+        if app_within_limit == 0 {
+            full_apps.push(app_id);
+            continue;
+        }
+
+        let lease_token = uuid::Uuid::new_v4().simple().to_string();
+        sqlx::query(
+            r#"UPDATE builds SET
+                status = 'building',
+                builder_id = ?,
+                lease_token = ?,
+                lease_expires_at = NOW() + INTERVAL ? SECOND,
+                attempts = attempts + 1,
+                error_message = NULL,
+                started_at = NOW(),
+                completed_at = NULL
+            WHERE id = ?"#,
+        )
+        .bind(builder_id)
+        .bind(&lease_token)
+        .bind(lease_seconds)
+        .bind(build_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to lease build")?;
+
+        let (build, lease_expires_at) = fetch_build_with_lease(&mut tx, build_id).await?;
+        let attempts = sqlx::query_scalar::<_, i32>("SELECT attempts FROM builds WHERE id = ?")
+            .bind(build_id)
+            .fetch_one(&mut *tx)
+            .await
+            .context("Failed to fetch build attempts")?;
+        insert_system_line(&mut tx, build_id, "info", &format!("Build leased by {} (attempt {})", builder_id, attempts)).await?;
+
+        tx.commit().await?;
+
+        return Ok(Some(BuildLease {
+            build,
+            lease_token,
+            lease_expires_at: lease_expires_at.context("Leased build has no lease expiry")?,
+        }));
+    }
+}
+
+/// Extends the lease of a build by `lease_seconds` from now.
+pub async fn heartbeat_build(
+    pool: &Pool<MySql>,
+    build_id: i64,
+    lease_token: &str,
+    lease_seconds: u64,
+) -> anyhow::Result<(LeaseOutcome, Option<DateTime<Utc>>)> {
+    let mut tx = pool.begin().await?;
+
+    let outcome = match check_lease(&mut tx, build_id, lease_token).await? {
+        Some(outcome) => outcome,
+        None => {
+            sqlx::query("UPDATE builds SET lease_expires_at = NOW() + INTERVAL ? SECOND WHERE id = ?")
+                .bind(lease_seconds)
+                .bind(build_id)
+                .execute(&mut *tx)
+                .await
+                .context("Failed to extend build lease")?;
+
+            let (build, lease_expires_at) = fetch_build_with_lease(&mut tx, build_id).await?;
+            tx.commit().await?;
+            return Ok((LeaseOutcome::Updated(build), lease_expires_at));
+        }
+    };
+
+    tx.commit().await?;
+    Ok((outcome, None))
+}
+
+/// Marks a leased build as succeeded with the builder's result.
+pub async fn complete_build(
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    build_id: i64,
+    lease_token: &str,
+    result: &BuildResult,
+) -> anyhow::Result<LeaseOutcome> {
+    let mut tx = pool.begin().await?;
+
+    if let Some(outcome) = check_lease(&mut tx, build_id, lease_token).await? {
+        tx.commit().await?;
+        return Ok(outcome);
+    }
+
+    sqlx::query(
+        r#"UPDATE builds SET
+            status = 'succeeded',
+            build_image = COALESCE(?, build_image),
+            build_pack_used = COALESCE(?, build_pack_used),
+            build_pack_version = COALESCE(?, build_pack_version),
+            artifact_url = COALESCE(?, artifact_url),
+            artifact_checksum = COALESCE(?, artifact_checksum),
+            artifact_size = COALESCE(?, artifact_size),
+            log_url = COALESCE(?, log_url, ?),
+            error_message = NULL,
+            lease_token = NULL,
+            lease_expires_at = NULL,
+            completed_at = NOW(),
+            build_duration = TIMESTAMPDIFF(SECOND, started_at, NOW())
+        WHERE id = ?"#,
+    )
+    .bind(&result.build_image)
+    .bind(&result.build_pack_used)
+    .bind(&result.build_pack_version)
+    .bind(&result.artifact_url)
+    .bind(&result.artifact_checksum)
+    .bind(result.artifact_size)
+    .bind(&result.log_url)
+    .bind(build_log_url(platform_id, build_id))
+    .bind(build_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to complete build")?;
+    insert_system_line(&mut tx, build_id, "info", "Build succeeded").await?;
+
+    let (build, _) = fetch_build_with_lease(&mut tx, build_id).await?;
+    tx.commit().await?;
+
+    Ok(LeaseOutcome::Updated(build))
+}
+
+/// Records that a leased build failed. A `retryable` failure puts the build
+/// back in the queue unless it has already been attempted `max_attempts`
+/// times; any other failure fails it.
+pub async fn fail_build(
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    build_id: i64,
+    lease_token: &str,
+    error_message: &str,
+    retryable: bool,
+    max_attempts: u32,
+) -> anyhow::Result<LeaseOutcome> {
+    let mut tx = pool.begin().await?;
+
+    if let Some(outcome) = check_lease(&mut tx, build_id, lease_token).await? {
+        tx.commit().await?;
+        return Ok(outcome);
+    }
+
+    sqlx::query(
+        r#"UPDATE builds SET
+            status = IF(? AND attempts < ?, 'pending', 'failed'),
+            completed_at = IF(? AND attempts < ?, NULL, NOW()),
+            build_duration = IF(? AND attempts < ?, NULL, TIMESTAMPDIFF(SECOND, started_at, NOW())),
+            log_url = IF(? AND attempts < ?, log_url, COALESCE(log_url, ?)),
+            error_message = ?,
+            builder_id = NULL,
+            lease_token = NULL,
+            lease_expires_at = NULL
+        WHERE id = ?"#,
+    )
+    .bind(retryable)
+    .bind(max_attempts)
+    .bind(retryable)
+    .bind(max_attempts)
+    .bind(retryable)
+    .bind(max_attempts)
+    .bind(retryable)
+    .bind(max_attempts)
+    .bind(build_log_url(platform_id, build_id))
+    .bind(error_message)
+    .bind(build_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to fail build")?;
+
+    let (build, _) = fetch_build_with_lease(&mut tx, build_id).await?;
+    let line = if build.status.as_deref() == Some("pending") {
+        format!("Build failed: {}; it will be retried", error_message)
+    } else {
+        format!("Build failed: {}", error_message)
+    };
+    insert_system_line(&mut tx, build_id, "error", &line).await?;
+    tx.commit().await?;
+
+    Ok(LeaseOutcome::Updated(build))
+}
+
+/// Cancels a pending or running build. The builder running it learns of the
+/// cancellation from its next heartbeat.
+pub async fn cancel_build(pool: &Pool<MySql>, platform_id: i64, build_id: i64) -> anyhow::Result<CancelOutcome> {
+    let mut tx = pool.begin().await?;
+
+    let build = match sqlx::query_as::<_, Build>("SELECT * FROM builds WHERE id = ? FOR UPDATE")
+        .bind(build_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to fetch build")?
+    {
+        Some(build) => build,
+        None => return Ok(CancelOutcome::NotFound),
+    };
+
+    if !matches!(build.status.as_deref(), Some("pending") | Some("building")) {
+        return Ok(CancelOutcome::AlreadyFinished(build));
+    }
+
+    // The lease token is kept so that the builder's next heartbeat is told
+    // the build was canceled rather than that it lost its lease
+    sqlx::query(
+        r#"UPDATE builds SET
+            status = 'canceled',
+            lease_expires_at = NULL,
+            log_url = COALESCE(log_url, ?),
+            completed_at = NOW(),
+            build_duration = TIMESTAMPDIFF(SECOND, started_at, NOW())
+        WHERE id = ?"#,
+    )
+    .bind(build_log_url(platform_id, build_id))
+    .bind(build_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to cancel build")?;
+    insert_system_line(&mut tx, build_id, "warn", "Build canceled").await?;
+
+    let (build, _) = fetch_build_with_lease(&mut tx, build_id).await?;
+    tx.commit().await?;
+
+    Ok(CancelOutcome::Canceled(build))
+}
+
+/// Returns the builds whose lease expired to the queue, or fails them if
+/// they have been attempted `max_attempts` times. Returns the number of
+/// builds requeued and failed.
+pub async fn expire_build_leases(pool: &Pool<MySql>, platform_id: i64, max_attempts: u32) -> anyhow::Result<(u64, u64)> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"INSERT INTO build_logs (build_id, app_id, stream, log_level, message)
+        SELECT id, app_id, 'system', 'warn',
+            CONCAT('Build lease of ', COALESCE(builder_id, 'unknown builder'), ' expired',
+                   IF(attempts >= ?, '', '; the build will be retried'))
+        FROM builds
+        WHERE status = 'building' AND lease_expires_at < NOW()"#,
+    )
+    .bind(max_attempts)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record expired build leases")?;
+
+    let failed = sqlx::query(
+        r#"UPDATE builds SET
+            status = 'failed',
+            error_message = CONCAT('Build lease of ', COALESCE(builder_id, 'unknown builder'), ' expired after ', attempts, ' attempts'),
+            lease_token = NULL,
+            lease_expires_at = NULL,
+            log_url = COALESCE(log_url, CONCAT('/api/v1/platform/', ?, '/builds/', id, '/logs')),
+            completed_at = NOW(),
+            build_duration = TIMESTAMPDIFF(SECOND, started_at, NOW())
+        WHERE status = 'building' AND lease_expires_at < NOW() AND attempts >= ?"#,
+    )
+    .bind(platform_id)
+    .bind(max_attempts)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to fail builds with expired leases")?
+    .rows_affected();
+
+    let requeued = sqlx::query(
+        r#"UPDATE builds SET
+            status = 'pending',
+            error_message = CONCAT('Build lease of ', COALESCE(builder_id, 'unknown builder'), ' expired'),
+            builder_id = NULL,
+            lease_token = NULL,
+            lease_expires_at = NULL
+        WHERE status = 'building' AND lease_expires_at < NOW()"#,
+    )
+    .execute(&mut *tx)
+    .await
+    .context("Failed to requeue builds with expired leases")?
+    .rows_affected();
+
+    tx.commit().await?;
+
+    Ok((requeued, failed))
+}
+
+/// Locks a build and checks that `lease_token` holds its lease. Returns
+/// `None` if it does, or the outcome to report otherwise.
+pub(super) async fn check_lease(
+    tx: &mut sqlx::Transaction<'_, MySql>,
+    build_id: i64,
+    lease_token: &str,
+) -> anyhow::Result<Option<LeaseOutcome>> {
+    let lease = sqlx::query_as::<_, (Option<String>, Option<String>)>(
+        "SELECT status, lease_token FROM builds WHERE id = ? FOR UPDATE",
+    )
+    .bind(build_id)
+    .fetch_optional(&mut **tx)
+    .await
+    .context("Failed to fetch build lease")?;
+
+    let (status, token) = match lease {
+        Some(lease) => lease,
+        None => return Ok(Some(LeaseOutcome::NotFound)),
+    };
+    if token.as_deref() != Some(lease_token) {
+        return Ok(Some(LeaseOutcome::LeaseLost));
+    }
+
+    match status.as_deref() {
+        Some("building") => Ok(None),
+        Some("canceled") => {
+            let (build, _) = fetch_build_with_lease(tx, build_id).await?;
+            Ok(Some(LeaseOutcome::Canceled(build)))
+        }
+        _ => Ok(Some(LeaseOutcome::LeaseLost)),
+    }
+}
+
+async fn fetch_build_with_lease(
+    tx: &mut sqlx::Transaction<'_, MySql>,
+    build_id: i64,
+) -> anyhow::Result<(Build, Option<DateTime<Utc>>)> {
+    let build = sqlx::query_as::<_, Build>("SELECT * FROM builds WHERE id = ?")
+        .bind(build_id)
+        .fetch_one(&mut **tx)
+        .await
+        .context("Failed to fetch build")?;
+    let lease_expires_at = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(
+        "SELECT lease_expires_at FROM builds WHERE id = ?",
+    )
+    .bind(build_id)
+    .fetch_one(&mut **tx)
+    .await
+    .context("Failed to fetch build lease expiry")?;
+
+    Ok((build, lease_expires_at))
+}
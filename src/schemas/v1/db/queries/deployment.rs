@@ -1,6 +1,8 @@
 // db/queries/deployment.rs
 use anyhow::Context;
-use sqlx::{MySql, Pool};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
 
 use libomni::types::db::v1 as types;
 use types::deployment::Deployment;
@@ -55,6 +57,62 @@ pub async fn get_deployment_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result
     Ok(deployment)
 }
 
+/// Retrieves the application a deployment belongs to, or `None` if there
+/// is no deployment with the given ID.
+pub async fn get_deployment_app_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<i64>> {
+    let app_id = sqlx::query_scalar::<_, i64>("SELECT app_id FROM deployments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch deployment")?;
+
+    Ok(app_id)
+}
+
+/// Retrieves the deployment an application currently runs: its most
+/// recently completed successful deployment, if any.
+pub async fn get_current_deployment(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Option<Deployment>> {
+    let deployment = sqlx::query_as::<_, Deployment>(
+        r#"SELECT * FROM deployments
+        WHERE app_id = ? AND status = 'deployed'
+        ORDER BY completed_at DESC, id DESC
+        LIMIT 1"#,
+    )
+    .bind(app_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch current deployment")?;
+
+    Ok(deployment)
+}
+
+/// Checks whether an application has a deployment that is pending or being
+/// rolled out.
+pub async fn has_unfinished_deployment(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<bool> {
+    let unfinished = sqlx::query_scalar::<_, i64>(
+        "SELECT EXISTS(SELECT 1 FROM deployments WHERE app_id = ? AND status IN ('pending', 'in_progress'))",
+    )
+    .bind(app_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to check for unfinished deployments")?;
+
+    Ok(unfinished != 0)
+}
+
+/// Checks whether a deployment of an application is being rolled out.
+pub async fn has_deployment_in_progress(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<bool> {
+    let in_progress = sqlx::query_scalar::<_, i64>(
+        "SELECT EXISTS(SELECT 1 FROM deployments WHERE app_id = ? AND status = 'in_progress')",
+    )
+    .bind(app_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to check for deployments in progress")?;
+
+    Ok(in_progress != 0)
+}
+
 /// Retrieves all deployments for a specific application with pagination.
 pub async fn list_deployments_by_app(
     pool: &Pool<MySql>, 
@@ -88,7 +146,22 @@ pub async fn count_deployments_by_app(pool: &Pool<MySql>, app_id: i64) -> anyhow
     Ok(count)
 }
 
+/// The approval requirement and freeze override a deployment is created
+/// with.
+#[derive(Debug, Clone, Default)]
+pub struct DeploymentGates {
+    /// Approvals needed before the deployment may start; none if 0
+    pub required_approvals: i64,
+    /// Role approvers must hold
+    pub approver_role_id: Option<i64>,
+    /// Why the deployment may proceed during a freeze window
+    pub freeze_override_reason: Option<String>,
+}
+
 /// Creates a new deployment in the database.
+///
+/// Deployments that require approvals start out with `approval_status`
+/// 'pending' and are not executed until approved.
 pub async fn create_deployment(
     pool: &Pool<MySql>,
     app_id: i64,
@@ -100,18 +173,20 @@ pub async fn create_deployment(
     environment_variables: Option<serde_json::Value>,
     annotations: Option<serde_json::Value>,
     labels: Option<serde_json::Value>,
+    gates: &DeploymentGates,
     created_by: Option<i64>,
 ) -> anyhow::Result<Deployment> {
     // Begin transaction
     let mut tx = pool.begin().await?;
 
     // Insert new deployment
-    let deployment = sqlx::query_as::<_, Deployment>(
+    let result = sqlx::query(
         r#"INSERT INTO deployments (
             app_id, build_id, version, status, deployment_strategy, 
             previous_deployment_id, canary_percentage, environment_variables,
-            annotations, labels, created_at, created_by
-        ) VALUES (?, ?, ?, 'pending', ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP, ?)"#,
+            annotations, labels, approval_status, required_approvals, approver_role_id,
+            freeze_override_reason, created_at, created_by
+        ) VALUES (?, ?, ?, 'pending', ?, ?, COALESCE(?, 20), ?, ?, ?, ?, ?, ?, ?, CURRENT_TIMESTAMP, ?)"#,
     )
     .bind(app_id)
     .bind(build_id)
@@ -122,11 +197,21 @@ pub async fn create_deployment(
     .bind(environment_variables)
     .bind(annotations)
     .bind(labels)
+    .bind(if gates.required_approvals > 0 { "pending" } else { "not_required" })
+    .bind(gates.required_approvals)
+    .bind(gates.approver_role_id)
+    .bind(&gates.freeze_override_reason)
     .bind(created_by)
-    .fetch_one(&mut *tx)
+    .execute(&mut *tx)
     .await
     .context("Failed to create deployment")?;
 
+    let deployment = sqlx::query_as::<_, Deployment>("SELECT * FROM deployments WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created deployment")?;
+
     // Commit transaction
     tx.commit().await?;
 
@@ -144,18 +229,18 @@ pub async fn update_deployment_status(
     let mut tx = pool.begin().await?;
 
     // Update fields based on the new status
-    let deployment = match status {
+    match status {
         "in_progress" => {
-            sqlx::query_as::<_, Deployment>(
+            sqlx::query(
                 "UPDATE deployments SET status = ?, started_at = CURRENT_TIMESTAMP WHERE id = ?"
             )
             .bind(status)
             .bind(id)
-            .fetch_one(&mut *tx)
+            .execute(&mut *tx)
             .await
         },
         "deployed" | "failed" | "canceled" => {
-            sqlx::query_as::<_, Deployment>(
+            sqlx::query(
                 "UPDATE deployments SET status = ?, completed_at = CURRENT_TIMESTAMP, 
                 deployment_duration = TIMESTAMPDIFF(SECOND, started_at, CURRENT_TIMESTAMP),
                 error_message = ? WHERE id = ?"
@@ -163,20 +248,26 @@ pub async fn update_deployment_status(
             .bind(status)
             .bind(error_message)
             .bind(id)
-            .fetch_one(&mut *tx)
+            .execute(&mut *tx)
             .await
         },
         _ => {
-            sqlx::query_as::<_, Deployment>(
+            sqlx::query(
                 "UPDATE deployments SET status = ? WHERE id = ?"
             )
             .bind(status)
             .bind(id)
-            .fetch_one(&mut *tx)
+            .execute(&mut *tx)
             .await
         }
     }.context("Failed to update deployment status")?;
 
+    let deployment = sqlx::query_as::<_, Deployment>("SELECT * FROM deployments WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch updated deployment")?;
+
     tx.commit().await?;
     Ok(deployment)
 }
@@ -193,4 +284,312 @@ pub async fn delete_deployment(pool: &Pool<MySql>, id: i64) -> anyhow::Result<()
 
     tx.commit().await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// How far the rollout of a deployment has come.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DeploymentProgress {
+    pub id: i64,
+    pub app_id: i64,
+    pub status: Option<String>,
+    pub deployment_strategy: Option<String>,
+    /// Step of the strategy the deployment is in
+    pub phase: Option<String>,
+    pub canary_percentage: Option<i64>,
+    /// Instances of the deployment that are running
+    pub staged_instances: Option<i64>,
+    /// Instances the application should end up with
+    pub total_instances: Option<i64>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub promoted_at: Option<DateTime<Utc>>,
+    pub abort_requested_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+    /// 'not_required', 'pending', 'approved' or 'rejected'
+    pub approval_status: Option<String>,
+}
+
+/// An unfinished instance of an application, with the deployment it runs.
+#[derive(Debug, Clone, FromRow)]
+pub struct DeploymentInstance {
+    pub id: i64,
+    pub instance_index: i64,
+    pub status: Option<String>,
+    pub deployment_id: Option<i64>,
+    pub restart_count: Option<i64>,
+    pub exit_reason: Option<String>,
+    pub health_status: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// Retrieves the progress of a deployment.
+pub async fn get_deployment_progress(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<DeploymentProgress>> {
+    let progress = sqlx::query_as::<_, DeploymentProgress>("SELECT * FROM deployments WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch deployment progress")?;
+
+    Ok(progress)
+}
+
+/// Retrieves the deployments the executor should work on: every deployment
+/// in progress, and the oldest pending deployment of each application that
+/// has none in progress, unless it still awaits approval. Later deployments
+/// of the application wait behind it.
+pub async fn list_deployments_to_execute(pool: &Pool<MySql>) -> anyhow::Result<Vec<Deployment>> {
+    let deployments = sqlx::query_as::<_, Deployment>(
+        r#"SELECT d.* FROM deployments d
+        WHERE d.status = 'in_progress'
+           OR (d.status = 'pending'
+               AND COALESCE(d.approval_status, 'not_required') IN ('not_required', 'approved')
+               AND d.id = (SELECT MIN(p.id) FROM deployments p WHERE p.app_id = d.app_id AND p.status = 'pending')
+               AND NOT EXISTS (SELECT 1 FROM deployments r WHERE r.app_id = d.app_id AND r.status = 'in_progress'))
+        ORDER BY d.id ASC"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch deployments to execute")?;
+
+    Ok(deployments)
+}
+
+/// Moves a pending deployment to 'in_progress', together with the rollback
+/// it carries out, if any. `previous_deployment_id` is replaced by the
+/// deployment the application runs as this one starts. Returns `false` if
+/// it was no longer pending.
+pub async fn begin_deployment(
+    pool: &Pool<MySql>,
+    id: i64,
+    total_instances: i64,
+    previous_deployment_id: Option<i64>,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"UPDATE deployments
+        SET status = 'in_progress', started_at = CURRENT_TIMESTAMP, phase = 'starting',
+            staged_instances = 0, total_instances = ?,
+            previous_deployment_id = ?
+        WHERE id = ? AND status = 'pending'"#,
+    )
+    .bind(total_instances)
+    .bind(previous_deployment_id)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to begin deployment")?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    sqlx::query(
+        r#"UPDATE rollbacks
+        SET status = 'in_progress', started_at = CURRENT_TIMESTAMP
+        WHERE deployment_id = ? AND status = 'pending'"#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to begin rollback")?;
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Records the phase and instance counts of a deployment in progress.
+pub async fn update_deployment_progress(
+    pool: &Pool<MySql>,
+    id: i64,
+    phase: &str,
+    staged_instances: i64,
+    total_instances: i64,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"UPDATE deployments SET phase = ?, staged_instances = ?, total_instances = ?
+        WHERE id = ? AND status = 'in_progress'"#,
+    )
+    .bind(phase)
+    .bind(staged_instances)
+    .bind(total_instances)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to update deployment progress")?;
+
+    Ok(())
+}
+
+/// Ends a deployment in progress as 'deployed', 'failed' or 'canceled'.
+/// Returns `false` if it was no longer in progress.
+pub async fn finish_deployment(
+    pool: &Pool<MySql>,
+    id: i64,
+    status: &str,
+    error_message: Option<&str>,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"UPDATE deployments
+        SET status = ?, phase = ?, completed_at = CURRENT_TIMESTAMP,
+            deployment_duration = TIMESTAMPDIFF(SECOND, started_at, CURRENT_TIMESTAMP),
+            error_message = ?, paused_at = NULL
+        WHERE id = ? AND status = 'in_progress'"#,
+    )
+    .bind(status)
+    .bind(if status == "deployed" { "completed" } else { status })
+    .bind(error_message)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to finish deployment")?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    finish_rollback(&mut tx, id, status, error_message).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Finishes the rollback a deployment carries out, if any, as the
+/// deployment ended.
+async fn finish_rollback(
+    tx: &mut sqlx::Transaction<'_, MySql>,
+    deployment_id: i64,
+    deployment_status: &str,
+    error_message: Option<&str>,
+) -> anyhow::Result<()> {
+    let (status, error_message) = match deployment_status {
+        "deployed" => ("completed", None),
+        "canceled" => ("failed", Some(error_message.unwrap_or("The rollback deployment was canceled"))),
+        _ => ("failed", error_message),
+    };
+
+    sqlx::query(
+        r#"UPDATE rollbacks
+        SET status = ?, error_message = ?, completed_at = CURRENT_TIMESTAMP,
+            rollback_duration = TIMESTAMPDIFF(SECOND, COALESCE(started_at, created_at), CURRENT_TIMESTAMP)
+        WHERE deployment_id = ? AND status IN ('pending', 'in_progress')"#,
+    )
+    .bind(status)
+    .bind(error_message)
+    .bind(deployment_id)
+    .execute(&mut **tx)
+    .await
+    .context("Failed to finish rollback")?;
+
+    Ok(())
+}
+
+/// Cancels a deployment that has not started. Returns `false` if it was no
+/// longer pending.
+pub async fn cancel_pending_deployment(pool: &Pool<MySql>, id: i64, reason: &str) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"UPDATE deployments
+        SET status = 'canceled', phase = 'canceled', completed_at = CURRENT_TIMESTAMP, error_message = ?
+        WHERE id = ? AND status = 'pending'"#,
+    )
+    .bind(reason)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to cancel deployment")?;
+
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    finish_rollback(&mut tx, id, "canceled", Some(reason)).await?;
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Pauses or resumes a deployment in progress. Returns `false` if it was not
+/// in progress or already in the requested state.
+pub async fn set_deployment_paused(pool: &Pool<MySql>, id: i64, paused: bool) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE deployments SET paused_at = IF(?, CURRENT_TIMESTAMP, NULL)
+        WHERE id = ? AND status = 'in_progress' AND abort_requested_at IS NULL
+          AND (paused_at IS NULL) = ?"#,
+    )
+    .bind(paused)
+    .bind(id)
+    .bind(paused)
+    .execute(pool)
+    .await
+    .context("Failed to pause or resume deployment")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Asks the executor to abort a deployment in progress. Returns `false` if
+/// it was not in progress or an abort was already requested.
+pub async fn request_deployment_abort(pool: &Pool<MySql>, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE deployments SET abort_requested_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND status = 'in_progress' AND abort_requested_at IS NULL"#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to request deployment abort")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Promotes a canary deployment that waits for promotion. Returns `false`
+/// if it was not waiting.
+pub async fn promote_deployment(pool: &Pool<MySql>, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE deployments SET promoted_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND status = 'in_progress' AND deployment_strategy = 'canary'
+          AND phase = 'awaiting_promotion' AND promoted_at IS NULL AND abort_requested_at IS NULL"#,
+    )
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to promote deployment")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Retrieves the instances of an application that have not been stopped or
+/// terminated, with the deployment each runs, ordered by instance index.
+pub async fn list_deployment_instances(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Vec<DeploymentInstance>> {
+    let instances = sqlx::query_as::<_, DeploymentInstance>(
+        r#"SELECT id, instance_index, status, deployment_id, restart_count, exit_reason, health_status, created_at
+        FROM instances
+        WHERE app_id = ? AND status NOT IN ('stopped', 'terminated')
+        ORDER BY instance_index ASC"#,
+    )
+    .bind(app_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch deployment instances")?;
+
+    Ok(instances)
+}
+
+/// Asks the reconciler to stop instances; they are terminated on its next
+/// pass.
+pub async fn mark_instances_stopping(pool: &Pool<MySql>, instance_ids: &[i64]) -> anyhow::Result<()> {
+    for id in instance_ids {
+        sqlx::query(
+            r#"UPDATE instances SET status = 'stopping', updated_at = CURRENT_TIMESTAMP
+            WHERE id = ? AND status NOT IN ('stopping', 'stopped', 'terminated')"#,
+        )
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to mark instance stopping")?;
+    }
+
+    Ok(())
+}
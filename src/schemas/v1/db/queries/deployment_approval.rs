@@ -0,0 +1,321 @@
+// db/queries/deployment_approval.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// A requirement for deployments of an application, or of every application
+/// in a space, to be approved before they start.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ApprovalPolicy {
+    pub id: i64,
+    pub app_id: Option<i64>,
+    pub space_id: Option<i64>,
+    pub required_approvals: i64,
+    /// Role approvers must hold; any holder of `deployments:approve` if unset
+    pub approver_role_id: Option<i64>,
+    pub enabled: Option<bool>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The settable fields of an approval policy.
+#[derive(Debug, Clone)]
+pub struct ApprovalPolicyInput {
+    pub required_approvals: i64,
+    pub approver_role_id: Option<i64>,
+    pub enabled: bool,
+}
+
+/// What an approval policy applies to.
+#[derive(Debug, Clone, Copy)]
+pub enum PolicyTarget {
+    App(i64),
+    Space(i64),
+}
+
+impl PolicyTarget {
+    fn column(self) -> &'static str {
+        match self {
+            PolicyTarget::App(_) => "app_id",
+            PolicyTarget::Space(_) => "space_id",
+        }
+    }
+
+    fn id(self) -> i64 {
+        match self {
+            PolicyTarget::App(id) | PolicyTarget::Space(id) => id,
+        }
+    }
+}
+
+/// A user's decision on a deployment awaiting approval.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DeploymentApproval {
+    pub id: i64,
+    pub deployment_id: i64,
+    pub user_id: i64,
+    /// 'approved' or 'rejected'
+    pub decision: String,
+    pub comment: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// The approval requirement of a deployment.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DeploymentApprovalState {
+    pub id: i64,
+    pub app_id: i64,
+    pub status: Option<String>,
+    /// 'not_required', 'pending', 'approved' or 'rejected'
+    pub approval_status: Option<String>,
+    pub required_approvals: Option<i64>,
+    pub approver_role_id: Option<i64>,
+    pub created_by: Option<i64>,
+}
+
+/// The outcome of [`decide_deployment`].
+#[derive(Debug, Clone)]
+pub enum ApprovalOutcome {
+    /// The deployment is not pending or does not await approval.
+    NotAwaitingApproval,
+    /// The user has already approved or rejected the deployment.
+    AlreadyDecided,
+    /// The decision was recorded. Carries the deployment's approval status
+    /// and the number of approvals it has.
+    Recorded { approval_status: String, approvals: i64 },
+}
+
+/// Retrieves the approval policy of an application or space.
+pub async fn get_approval_policy(pool: &Pool<MySql>, target: PolicyTarget) -> anyhow::Result<Option<ApprovalPolicy>> {
+    let policy = sqlx::query_as::<_, ApprovalPolicy>(&format!(
+        "SELECT * FROM deployment_approval_policies WHERE {} = ?",
+        target.column()
+    ))
+    .bind(target.id())
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch approval policy")?;
+
+    Ok(policy)
+}
+
+/// Retrieves the approval policy that applies to new deployments of an
+/// application: its own policy if it has one, enabled or not, and otherwise
+/// the policy of its space.
+pub async fn resolve_approval_policy(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Option<ApprovalPolicy>> {
+    let policy = sqlx::query_as::<_, ApprovalPolicy>(
+        r#"SELECT p.* FROM deployment_approval_policies p
+        JOIN apps a ON p.app_id = a.id OR (p.space_id IS NOT NULL AND p.space_id = a.space_id)
+        WHERE a.id = ?
+        ORDER BY p.app_id IS NULL, p.id
+        LIMIT 1"#,
+    )
+    .bind(app_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to resolve approval policy")?;
+
+    Ok(policy)
+}
+
+/// Creates or replaces the approval policy of an application or space.
+pub async fn set_approval_policy(
+    pool: &Pool<MySql>,
+    target: PolicyTarget,
+    policy: &ApprovalPolicyInput,
+    created_by: Option<i64>,
+) -> anyhow::Result<ApprovalPolicy> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(&format!(
+        r#"INSERT INTO deployment_approval_policies ({}, required_approvals, approver_role_id, enabled, created_by)
+        VALUES (?, ?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            required_approvals = VALUES(required_approvals),
+            approver_role_id = VALUES(approver_role_id),
+            enabled = VALUES(enabled)"#,
+        target.column()
+    ))
+    .bind(target.id())
+    .bind(policy.required_approvals)
+    .bind(policy.approver_role_id)
+    .bind(policy.enabled)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to save approval policy")?;
+
+    let saved = sqlx::query_as::<_, ApprovalPolicy>(&format!(
+        "SELECT * FROM deployment_approval_policies WHERE {} = ?",
+        target.column()
+    ))
+    .bind(target.id())
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to fetch saved approval policy")?;
+
+    tx.commit().await?;
+
+    Ok(saved)
+}
+
+/// Deletes the approval policy of an application or space. Returns `false`
+/// if there was none. Deployments already awaiting approval keep waiting.
+pub async fn delete_approval_policy(pool: &Pool<MySql>, target: PolicyTarget) -> anyhow::Result<bool> {
+    let result = sqlx::query(&format!(
+        "DELETE FROM deployment_approval_policies WHERE {} = ?",
+        target.column()
+    ))
+    .bind(target.id())
+    .execute(pool)
+    .await
+    .context("Failed to delete approval policy")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Retrieves the approval requirement of a deployment.
+pub async fn get_deployment_approval_state(
+    pool: &Pool<MySql>,
+    deployment_id: i64,
+) -> anyhow::Result<Option<DeploymentApprovalState>> {
+    let state = sqlx::query_as::<_, DeploymentApprovalState>("SELECT * FROM deployments WHERE id = ?")
+        .bind(deployment_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch deployment approval state")?;
+
+    Ok(state)
+}
+
+/// Retrieves the approvals and rejections of a deployment, oldest first.
+pub async fn list_deployment_approvals(pool: &Pool<MySql>, deployment_id: i64) -> anyhow::Result<Vec<DeploymentApproval>> {
+    let approvals = sqlx::query_as::<_, DeploymentApproval>(
+        "SELECT * FROM deployment_approvals WHERE deployment_id = ? ORDER BY id ASC",
+    )
+    .bind(deployment_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch deployment approvals")?;
+
+    Ok(approvals)
+}
+
+/// Records a user's decision on a pending deployment awaiting approval.
+///
+/// The deployment is approved once it has as many approvals as it requires,
+/// after which the executor picks it up. A single rejection rejects and
+/// cancels it.
+pub async fn decide_deployment(
+    pool: &Pool<MySql>,
+    deployment_id: i64,
+    user_id: i64,
+    approve: bool,
+    comment: Option<&str>,
+) -> anyhow::Result<ApprovalOutcome> {
+    let mut tx = pool.begin().await?;
+
+    let required = sqlx::query_scalar::<_, Option<i64>>(
+        r#"SELECT required_approvals FROM deployments
+        WHERE id = ? AND status = 'pending' AND approval_status = 'pending'
+        FOR UPDATE"#,
+    )
+    .bind(deployment_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to lock deployment")?;
+
+    let required = match required {
+        Some(required) => required.unwrap_or(0),
+        None => return Ok(ApprovalOutcome::NotAwaitingApproval),
+    };
+
+    let decided = sqlx::query_scalar::<_, i64>(
+        "SELECT EXISTS(SELECT 1 FROM deployment_approvals WHERE deployment_id = ? AND user_id = ?)",
+    )
+    .bind(deployment_id)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to check for earlier decision")?;
+
+    if decided != 0 {
+        return Ok(ApprovalOutcome::AlreadyDecided);
+    }
+
+    sqlx::query(
+        "INSERT INTO deployment_approvals (deployment_id, user_id, decision, comment) VALUES (?, ?, ?, ?)",
+    )
+    .bind(deployment_id)
+    .bind(user_id)
+    .bind(if approve { "approved" } else { "rejected" })
+    .bind(comment)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record deployment approval")?;
+
+    let approvals = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM deployment_approvals WHERE deployment_id = ? AND decision = 'approved'",
+    )
+    .bind(deployment_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to count deployment approvals")?;
+
+    let approval_status = if !approve {
+        sqlx::query(
+            r#"UPDATE deployments
+            SET approval_status = 'rejected', status = 'canceled', phase = 'canceled',
+                completed_at = CURRENT_TIMESTAMP, error_message = ?
+            WHERE id = ?"#,
+        )
+        .bind(format!("Deployment was rejected by user {}", user_id))
+        .bind(deployment_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to reject deployment")?;
+        "rejected"
+    } else if approvals >= required {
+        sqlx::query("UPDATE deployments SET approval_status = 'approved' WHERE id = ?")
+            .bind(deployment_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to approve deployment")?;
+        "approved"
+    } else {
+        "pending"
+    };
+
+    tx.commit().await?;
+
+    Ok(ApprovalOutcome::Recorded { approval_status: approval_status.to_string(), approvals })
+}
+
+/// Checks whether a user is bound to a role in the scope of an application:
+/// globally, or on the application, its space or its organization.
+pub async fn user_holds_role_for_app(
+    pool: &Pool<MySql>,
+    user_id: i64,
+    role_id: i64,
+    app_id: i64,
+) -> anyhow::Result<bool> {
+    let holds = sqlx::query_scalar::<_, i64>(
+        r#"SELECT EXISTS(
+            SELECT 1 FROM role_user ru JOIN apps a ON a.id = ?
+            WHERE ru.user_id = ? AND ru.role_id = ?
+              AND (ru.scope_type = 'global'
+                OR (ru.scope_type = 'organization' AND ru.scope_id = a.org_id)
+                OR (ru.scope_type = 'space' AND ru.scope_id = a.space_id)
+                OR (ru.scope_type = 'application' AND ru.scope_id = a.id)))"#,
+    )
+    .bind(app_id)
+    .bind(user_id)
+    .bind(role_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to check role binding")?;
+
+    Ok(holds != 0)
+}
@@ -0,0 +1,113 @@
+// db/queries/deployment_log.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, MySql, Pool};
+
+/// An entry of a deployment's timeline, such as a phase transition, the
+/// replacement of instances, a failed health check or an error.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DeploymentEvent {
+    pub id: i64,
+    pub deployment_id: i64,
+    pub event_type: String,
+    pub log_type: String,
+    pub log_level: Option<String>,
+    pub message: String,
+    pub metadata: Option<Value>,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// An event to be recorded with [`insert_deployment_event`].
+#[derive(Debug, Clone)]
+pub struct NewDeploymentEvent {
+    pub deployment_id: i64,
+    pub event_type: &'static str,
+    /// One of 'debug', 'info', 'warn', 'error' or 'fatal'
+    pub level: &'static str,
+    pub message: String,
+    pub metadata: Option<Value>,
+}
+
+/// Adds an event to the timeline of a deployment.
+pub async fn insert_deployment_event(pool: &Pool<MySql>, event: &NewDeploymentEvent) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO deployment_logs (deployment_id, log_type, event_type, log_level, message, metadata)
+        VALUES (?, 'deployment', ?, ?, ?, ?)
+        "#,
+    )
+    .bind(event.deployment_id)
+    .bind(event.event_type)
+    .bind(event.level)
+    .bind(&event.message)
+    .bind(&event.metadata)
+    .execute(pool)
+    .await
+    .context("Failed to record deployment event")?;
+
+    Ok(())
+}
+
+/// Retrieves a page of the timeline of a deployment, oldest first.
+pub async fn list_deployment_events(
+    pool: &Pool<MySql>,
+    deployment_id: i64,
+    page: i64,
+    per_page: i64,
+) -> anyhow::Result<Vec<DeploymentEvent>> {
+    let events = sqlx::query_as::<_, DeploymentEvent>(
+        r#"
+        SELECT * FROM deployment_logs
+        WHERE deployment_id = ?
+        ORDER BY id ASC
+        LIMIT ? OFFSET ?
+        "#,
+    )
+    .bind(deployment_id)
+    .bind(per_page)
+    .bind(page * per_page)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch deployment events")?;
+
+    Ok(events)
+}
+
+/// Counts the events of a deployment.
+pub async fn count_deployment_events(pool: &Pool<MySql>, deployment_id: i64) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM deployment_logs WHERE deployment_id = ?")
+        .bind(deployment_id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to count deployment events")?;
+
+    Ok(count)
+}
+
+/// Retrieves the events of a deployment recorded after the event with ID
+/// `after_id`, oldest first, for tailing its timeline.
+pub async fn list_deployment_events_after(
+    pool: &Pool<MySql>,
+    deployment_id: i64,
+    after_id: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<DeploymentEvent>> {
+    let events = sqlx::query_as::<_, DeploymentEvent>(
+        r#"
+        SELECT * FROM deployment_logs
+        WHERE deployment_id = ? AND id > ?
+        ORDER BY id ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(deployment_id)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch deployment events")?;
+
+    Ok(events)
+}
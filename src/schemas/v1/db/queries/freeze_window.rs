@@ -0,0 +1,190 @@
+// db/queries/freeze_window.rs
+use anyhow::Context;
+use chrono::{DateTime, NaiveTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// A period in which an organization's applications may not be deployed.
+///
+/// One-time windows run from `starts_at` to `ends_at`. Weekly windows recur
+/// every week from `start_day` at `start_time` to `end_day` at `end_time`
+/// (UTC, days counted from 0 = Monday), and may wrap around the end of the
+/// week.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FreezeWindow {
+    pub id: i64,
+    pub org_id: i64,
+    pub name: String,
+    pub reason: Option<String>,
+    /// 'once' or 'weekly'
+    pub recurrence: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub start_day: Option<i8>,
+    pub start_time: Option<NaiveTime>,
+    pub end_day: Option<i8>,
+    pub end_time: Option<NaiveTime>,
+    pub enabled: Option<bool>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The settable fields of a freeze window.
+#[derive(Debug, Clone)]
+pub struct FreezeWindowInput {
+    pub name: String,
+    pub reason: Option<String>,
+    pub recurrence: String,
+    pub starts_at: Option<DateTime<Utc>>,
+    pub ends_at: Option<DateTime<Utc>>,
+    pub start_day: Option<i8>,
+    pub start_time: Option<NaiveTime>,
+    pub end_day: Option<i8>,
+    pub end_time: Option<NaiveTime>,
+    pub enabled: bool,
+}
+
+/// Retrieves the freeze windows of an organization.
+pub async fn list_freeze_windows(pool: &Pool<MySql>, org_id: i64) -> anyhow::Result<Vec<FreezeWindow>> {
+    let windows = sqlx::query_as::<_, FreezeWindow>(
+        "SELECT * FROM deployment_freeze_windows WHERE org_id = ? ORDER BY id",
+    )
+    .bind(org_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch freeze windows")?;
+
+    Ok(windows)
+}
+
+/// Retrieves the enabled freeze windows of the organization an application
+/// belongs to.
+pub async fn list_app_freeze_windows(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Vec<FreezeWindow>> {
+    let windows = sqlx::query_as::<_, FreezeWindow>(
+        r#"SELECT w.* FROM deployment_freeze_windows w
+        JOIN apps a ON a.org_id = w.org_id
+        WHERE a.id = ? AND w.enabled = 1
+        ORDER BY w.id"#,
+    )
+    .bind(app_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch freeze windows of app")?;
+
+    Ok(windows)
+}
+
+/// Retrieves a freeze window of an organization.
+pub async fn get_freeze_window(pool: &Pool<MySql>, org_id: i64, id: i64) -> anyhow::Result<Option<FreezeWindow>> {
+    let window = sqlx::query_as::<_, FreezeWindow>(
+        "SELECT * FROM deployment_freeze_windows WHERE id = ? AND org_id = ?",
+    )
+    .bind(id)
+    .bind(org_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch freeze window")?;
+
+    Ok(window)
+}
+
+/// Creates a freeze window for an organization.
+pub async fn create_freeze_window(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    window: &FreezeWindowInput,
+    created_by: Option<i64>,
+) -> anyhow::Result<FreezeWindow> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"INSERT INTO deployment_freeze_windows (
+            org_id, name, reason, recurrence, starts_at, ends_at,
+            start_day, start_time, end_day, end_time, enabled, created_by
+        )
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(org_id)
+    .bind(&window.name)
+    .bind(&window.reason)
+    .bind(&window.recurrence)
+    .bind(window.starts_at)
+    .bind(window.ends_at)
+    .bind(window.start_day)
+    .bind(window.start_time)
+    .bind(window.end_day)
+    .bind(window.end_time)
+    .bind(window.enabled)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create freeze window")?;
+
+    let created = sqlx::query_as::<_, FreezeWindow>("SELECT * FROM deployment_freeze_windows WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created freeze window")?;
+
+    tx.commit().await?;
+
+    Ok(created)
+}
+
+/// Replaces the settings of a freeze window.
+pub async fn update_freeze_window(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    id: i64,
+    window: &FreezeWindowInput,
+) -> anyhow::Result<Option<FreezeWindow>> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"UPDATE deployment_freeze_windows
+        SET name = ?, reason = ?, recurrence = ?, starts_at = ?, ends_at = ?,
+            start_day = ?, start_time = ?, end_day = ?, end_time = ?, enabled = ?
+        WHERE id = ? AND org_id = ?"#,
+    )
+    .bind(&window.name)
+    .bind(&window.reason)
+    .bind(&window.recurrence)
+    .bind(window.starts_at)
+    .bind(window.ends_at)
+    .bind(window.start_day)
+    .bind(window.start_time)
+    .bind(window.end_day)
+    .bind(window.end_time)
+    .bind(window.enabled)
+    .bind(id)
+    .bind(org_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update freeze window")?;
+
+    let updated = sqlx::query_as::<_, FreezeWindow>(
+        "SELECT * FROM deployment_freeze_windows WHERE id = ? AND org_id = ?",
+    )
+    .bind(id)
+    .bind(org_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to fetch updated freeze window")?;
+
+    tx.commit().await?;
+
+    Ok(updated)
+}
+
+/// Deletes a freeze window. Returns `false` if it did not exist.
+pub async fn delete_freeze_window(pool: &Pool<MySql>, org_id: i64, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM deployment_freeze_windows WHERE id = ? AND org_id = ?")
+        .bind(id)
+        .bind(org_id)
+        .execute(pool)
+        .await
+        .context("Failed to delete freeze window")?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -4,6 +4,10 @@ use sqlx::{MySql, Pool};
 use libomni::types::db::v1 as types;
 use types::instance::Instance;
 
+/// Instance type of the instances the orchestrator creates for an
+/// application, when starting, scaling or reconciling it.
+pub const DEFAULT_INSTANCE_TYPE: &str = "standard";
+
 /// List instances by `region_id` and `app_id` paginated by `page` and `per_page` using a where clause.
 pub async fn list_instances_by_region(
     pool: &Pool<MySql>,
@@ -69,17 +73,33 @@ pub async fn get_instance_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<I
     Ok(instance)
 }
 
+/// Retrieves the application an instance belongs to, or `None` if there is
+/// no instance with the given ID.
+pub async fn get_instance_app_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<i64>> {
+    let app_id = sqlx::query_scalar::<_, i64>("SELECT app_id FROM instances WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch instance")?;
+
+    Ok(app_id)
+}
+
 /// Creates a new compute instance for an application.
 ///
-/// This function provisions a new compute instance for an application with the
-/// specified instance type. The instance is initially created with a 'provisioning'
-/// status and 'running' instance_status.
+/// This function records a new instance of an application with the
+/// specified instance type. The instance starts out in the 'starting' state
+/// and inherits the application's region and default allocation; a runtime
+/// is expected to pick it up and report its progress through
+/// `update_instance_status`.
 ///
 /// # Arguments
 ///
 /// * `pool` - Database connection pool for executing the query
 /// * `app_id` - Identifier of the application this instance belongs to
 /// * `instance_type` - Type of instance to create (e.g., 'small', 'medium', 'large')
+/// * `deployment_id` - Deployment whose release the instance runs; `None`
+///   runs the application's current deployment
 ///
 /// # Returns
 ///
@@ -89,32 +109,54 @@ pub async fn get_instance_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<I
 /// # Transaction Handling
 ///
 /// This function uses a database transaction to ensure atomicity of the operation.
-/// If any part of the operation fails, the entire operation is rolled back.
-///
-/// # State Model
-///
-/// The instance is created with:
-/// - `status`: 'provisioning' - Indicates the instance is being set up
-/// - `instance_status`: 'running' - Indicates the intended operational state
-///
-/// These states will be updated as the instance progresses through its lifecycle.
+/// The application row is locked while the instance index is assigned, so
+/// concurrently created instances never share an index. Indexes are not
+/// reused: a new instance always takes the next index after the highest one
+/// the application has used.
 pub async fn create_instance(
     pool: &Pool<MySql>,
     app_id: i64,
     instance_type: &str,
+    deployment_id: Option<i64>,
 ) -> anyhow::Result<Instance> {
     let mut tx = pool.begin().await?;
 
-    let instance = sqlx::query_as::<_, Instance>(
-        r#"INSERT INTO instances (
-            app_id, instance_type, status, instance_status
-        ) VALUES (?, ?, 'provisioning', 'running')"#,
+    sqlx::query_scalar::<_, i64>("SELECT id FROM apps WHERE id = ? FOR UPDATE")
+        .bind(app_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to lock app")?
+        .context("App does not exist")?;
+
+    let instance_index = sqlx::query_scalar::<_, i64>(
+        "SELECT COALESCE(MAX(instance_index) + 1, 0) FROM instances WHERE app_id = ?",
     )
     .bind(app_id)
-    .bind(instance_type)
     .fetch_one(&mut *tx)
     .await
-    .context("Failed to create instance")?;
+    .context("Failed to assign instance index")?;
+
+    let id = sqlx::query(
+        r#"INSERT INTO instances (
+            app_id, instance_type, guid, status, instance_index, region_id, allocation_id, deployment_id
+        )
+        SELECT id, ?, UUID(), 'starting', ?, region_id, default_allocation_id, ?
+        FROM apps WHERE id = ?"#,
+    )
+    .bind(instance_type)
+    .bind(instance_index)
+    .bind(deployment_id)
+    .bind(app_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create instance")?
+    .last_insert_id();
+
+    let instance = sqlx::query_as::<_, Instance>("SELECT * FROM instances WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created instance")?;
 
     tx.commit().await?;
     Ok(instance)
@@ -130,21 +172,21 @@ pub async fn create_instance(
 ///
 /// * `pool` - Database connection pool for executing the query
 /// * `id` - Unique identifier of the instance to update
-/// * `status` - New provisioning status (e.g., 'provisioning', 'running', 'failed')
-/// * `instance_status` - New operational status (e.g., 'running', 'stopped', 'terminated')
-/// * `container_id` - Optional identifier of the container running the instance
-/// * `node_name` - Optional name of the node hosting the instance
+/// * `status` - New status (e.g., 'starting', 'running', 'stopped', 'terminated')
+/// * `container_id` - Identifier of the container running the instance, if
+///   known; `None` keeps the current value
+/// * `node_id` - Worker hosting the instance, if known; `None` keeps the
+///   current value
 ///
 /// # Returns
 ///
 /// * `Ok(Instance)` - Successfully updated instance record
 /// * `Err(anyhow::Error)` - Failed to update instance
 ///
-/// # Status Model
+/// # Timestamps
 ///
-/// The instance has two status fields:
-/// - `status`: Represents the provisioning lifecycle (provisioning, running, failed)
-/// - `instance_status`: Represents the operational state (running, stopped, terminated)
+/// `start_time` is set when the instance becomes 'running', and `stop_time`
+/// when it becomes 'stopped', 'terminated' or 'crashed'.
 ///
 /// # Transaction Handling
 ///
@@ -154,27 +196,37 @@ pub async fn update_instance_status(
     pool: &Pool<MySql>,
     id: i64,
     status: &str,
-    instance_status: &str,
     container_id: Option<&str>,
-    node_name: Option<&str>,
+    node_id: Option<i64>,
 ) -> anyhow::Result<Instance> {
     let mut tx = pool.begin().await?;
 
-    let instance = sqlx::query_as::<_, Instance>(
-        r#"UPDATE instances 
-        SET status = ?, instance_status = ?, container_id = ?, node_name = ?, 
-            updated_at = CURRENT_TIMESTAMP 
+    sqlx::query(
+        r#"UPDATE instances
+        SET status = ?,
+            container_id = COALESCE(?, container_id),
+            node_id = COALESCE(?, node_id),
+            start_time = IF(? = 'running', CURRENT_TIMESTAMP, start_time),
+            stop_time = IF(? IN ('stopped', 'terminated', 'crashed'), CURRENT_TIMESTAMP, stop_time),
+            updated_at = CURRENT_TIMESTAMP
         WHERE id = ?"#,
     )
     .bind(status)
-    .bind(instance_status)
     .bind(container_id)
-    .bind(node_name)
+    .bind(node_id)
+    .bind(status)
+    .bind(status)
     .bind(id)
-    .fetch_one(&mut *tx)
+    .execute(&mut *tx)
     .await
     .context("Failed to update instance status")?;
 
+    let instance = sqlx::query_as::<_, Instance>("SELECT * FROM instances WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch updated instance")?;
+
     tx.commit().await?;
     Ok(instance)
 }
@@ -247,7 +299,7 @@ pub async fn get_running_instances(
 ) -> anyhow::Result<Vec<Instance>> {
     let instances = sqlx::query_as::<_, Instance>(
         r#"SELECT * FROM instances 
-        WHERE app_id = ? AND instance_status = 'running'
+        WHERE app_id = ? AND status = 'running'
         ORDER BY created_at DESC"#,
     )
     .bind(app_id)
@@ -284,7 +336,7 @@ pub async fn get_running_instances(
 pub async fn count_running_instances(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<i64> {
     let count = sqlx::query_scalar::<_, i64>(
         r#"SELECT COUNT(*) FROM instances 
-        WHERE app_id = ? AND instance_status = 'running'"#,
+        WHERE app_id = ? AND status = 'running'"#,
     )
     .bind(app_id)
     .fetch_one(pool)
@@ -296,7 +348,8 @@ pub async fn count_running_instances(pool: &Pool<MySql>, app_id: i64) -> anyhow:
 
 /// Terminates all running instances for a specific application.
 ///
-/// This function marks all running instances of an application as 'terminated'.
+/// This function marks all instances of an application that have not
+/// stopped yet as 'terminated'.
 /// It's typically used during application shutdown, maintenance, or redeployment
 /// scenarios when all compute resources need to be released.
 ///
@@ -334,9 +387,9 @@ pub async fn terminate_all_instances(pool: &Pool<MySql>, app_id: i64) -> anyhow:
     sqlx::query(
         r#"UPDATE instances 
         SET status = 'terminated', 
-            instance_status = 'terminated',
+            stop_time = CURRENT_TIMESTAMP,
             updated_at = CURRENT_TIMESTAMP 
-        WHERE app_id = ? AND instance_status = 'running'"#,
+        WHERE app_id = ? AND status NOT IN ('stopped', 'terminated')"#,
     )
     .bind(app_id)
     .execute(&mut *tx)
@@ -345,4 +398,226 @@ pub async fn terminate_all_instances(pool: &Pool<MySql>, app_id: i64) -> anyhow:
 
     tx.commit().await?;
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Retrieves the instances of an application that are meant to be running.
+///
+/// Active instances are all those that have not been stopped or terminated,
+/// including ones that are still starting or have crashed and await a
+/// restart. They are ordered by instance index.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `app_id` - Unique identifier of the application
+///
+/// # Returns
+///
+/// * `Ok(Vec<Instance>)` - Successfully retrieved active instances
+/// * `Err(anyhow::Error)` - Failed to fetch active instances
+pub async fn get_active_instances(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Vec<Instance>> {
+    let instances = sqlx::query_as::<_, Instance>(
+        r#"SELECT * FROM instances
+        WHERE app_id = ? AND status NOT IN ('stopping', 'stopped', 'terminated')
+        ORDER BY instance_index ASC"#,
+    )
+    .bind(app_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch active instances")?;
+
+    Ok(instances)
+}
+
+/// Retrieves every instance of the platform that has not been stopped or
+/// terminated, ordered by application and instance index.
+///
+/// This is the actual state the reconciler compares with the desired state
+/// of each application.
+pub async fn list_unfinished_instances(pool: &Pool<MySql>) -> anyhow::Result<Vec<Instance>> {
+    let instances = sqlx::query_as::<_, Instance>(
+        r#"SELECT * FROM instances
+        WHERE status NOT IN ('stopped', 'terminated')
+        ORDER BY app_id ASC, instance_index ASC"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch unfinished instances")?;
+
+    Ok(instances)
+}
+
+/// Retrieves an instance by its GUID, the identity its containers carry in
+/// the runtime.
+pub async fn get_instance_by_guid(pool: &Pool<MySql>, guid: &str) -> anyhow::Result<Option<Instance>> {
+    let instance = sqlx::query_as::<_, Instance>("SELECT * FROM instances WHERE guid = ?")
+        .bind(guid)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch instance by guid")?;
+
+    Ok(instance)
+}
+
+/// Records that a container was launched for an instance. The instance stays
+/// 'starting' until the runtime reports the container running.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `id` - Unique identifier of the instance
+/// * `container_id` - Runtime identifier of the new container
+/// * `container_ip` - IP address of the new container, if it has one
+/// * `restart_reason` - Why the instance was restarted, when the container
+///   replaces one that crashed. Restarts increment `restart_count`.
+pub async fn record_instance_launch(
+    pool: &Pool<MySql>,
+    id: i64,
+    container_id: &str,
+    container_ip: Option<&str>,
+    restart_reason: Option<&str>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"UPDATE instances
+        SET status = 'starting',
+            container_id = ?,
+            container_ip = ?,
+            exit_code = NULL,
+            exit_reason = NULL,
+            restart_count = restart_count + IF(? IS NULL, 0, 1),
+            last_restart_reason = COALESCE(?, last_restart_reason),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?"#,
+    )
+    .bind(container_id)
+    .bind(container_ip)
+    .bind(restart_reason)
+    .bind(restart_reason)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to record instance launch")?;
+
+    Ok(())
+}
+
+/// Records that the runtime reported the container of a starting instance
+/// running.
+///
+/// Reports about a container the instance no longer uses are ignored.
+///
+/// # Returns
+///
+/// * `Ok(true)` - The instance is now 'running'
+/// * `Ok(false)` - The instance was not starting with this container
+/// * `Err(anyhow::Error)` - Failed to update the instance
+pub async fn record_instance_running(
+    pool: &Pool<MySql>,
+    id: i64,
+    container_id: &str,
+    container_ip: Option<&str>,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE instances
+        SET status = 'running',
+            container_ip = COALESCE(?, container_ip),
+            start_time = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND container_id = ? AND status = 'starting'"#,
+    )
+    .bind(container_ip)
+    .bind(id)
+    .bind(container_id)
+    .execute(pool)
+    .await
+    .context("Failed to record instance running")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records that the container of an instance exited or disappeared, marking
+/// the instance as crashed.
+///
+/// Reports about a container the instance no longer uses, or about an
+/// instance that is already crashed or was being stopped, are ignored.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `id` - Unique identifier of the instance
+/// * `container_id` - Container that exited
+/// * `exit_code` - Exit code of the container's process, if known
+/// * `exit_reason` - Description of what happened
+///
+/// # Returns
+///
+/// * `Ok(true)` - The instance is now 'crashed'
+/// * `Ok(false)` - The report did not apply to the instance
+/// * `Err(anyhow::Error)` - Failed to update the instance
+pub async fn record_instance_exit(
+    pool: &Pool<MySql>,
+    id: i64,
+    container_id: &str,
+    exit_code: Option<i64>,
+    exit_reason: &str,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE instances
+        SET status = 'crashed',
+            exit_code = ?,
+            exit_reason = ?,
+            stop_time = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND container_id = ?
+            AND status NOT IN ('crashed', 'stopping', 'stopped', 'terminated')"#,
+    )
+    .bind(exit_code)
+    .bind(exit_reason)
+    .bind(id)
+    .bind(container_id)
+    .execute(pool)
+    .await
+    .context("Failed to record instance exit")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records the result of the health check of an instance's container.
+///
+/// Reports about a container the instance no longer uses are ignored.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `id` - Unique identifier of the instance
+/// * `container_id` - Container whose health was checked
+/// * `health_status` - New health status: 'healthy', 'degraded', 'critical' or 'unknown'
+///
+/// # Returns
+///
+/// * `Ok(true)` - The health status of the instance changed
+/// * `Ok(false)` - The report did not apply to the instance or changed nothing
+/// * `Err(anyhow::Error)` - Failed to update the instance
+pub async fn record_instance_health(
+    pool: &Pool<MySql>,
+    id: i64,
+    container_id: &str,
+    health_status: &str,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE instances
+        SET health_status = ?,
+            last_health_check = CURRENT_TIMESTAMP,
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ? AND container_id = ? AND NOT (health_status <=> ?)"#,
+    )
+    .bind(health_status)
+    .bind(id)
+    .bind(container_id)
+    .bind(health_status)
+    .execute(pool)
+    .await
+    .context("Failed to record instance health")?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -1,14 +1,34 @@
 pub mod app;
+pub mod app_event;
 pub mod alert;
+pub mod api_key;
+pub mod artifact;
 pub mod audit_log;
+pub mod autoscaling;
 pub mod build;
+pub mod build_log;
+pub mod build_queue;
 pub mod deployment;
+pub mod deployment_approval;
+pub mod deployment_log;
+pub mod freeze_window;
 pub mod instance;
 pub mod metadata;
 pub mod org;
+pub mod pipeline;
+pub mod pipeline_run;
 pub mod permission;
 pub mod region;
+pub mod rollback;
+pub mod role;
+pub mod scheduling;
+pub mod space;
+pub mod sso;
+pub mod task;
+pub mod task_log;
+pub mod task_schedule;
 pub mod user;
+pub mod webhook;
 pub mod worker;
 pub mod backup;
 pub mod metrics;
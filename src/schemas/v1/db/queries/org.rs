@@ -1,9 +1,94 @@
 use anyhow::Context;
-use sqlx::{MySql, Pool};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool, Transaction};
 
 use libomni::types::db::v1 as types;
 use types::org::Org;
 
+/// A membership of a user in an organization (a row of `orgmember`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OrgMember {
+    pub id: i64,
+    pub org_id: i64,
+    pub user_id: i64,
+    pub role: String,
+    pub invitation_status: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// An email invitation to join an organization.
+///
+/// Only the SHA-256 hash of the invitation token is stored; the token is
+/// handed to the inviter once so it can be sent to the invitee.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct OrgInvitation {
+    pub id: i64,
+    pub org_id: i64,
+    pub email: String,
+    pub role: String,
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    pub status: String,
+    pub invited_by: i64,
+    pub accepted_by: Option<i64>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl OrgInvitation {
+    /// Checks whether the invitation can still be accepted.
+    pub fn is_pending(&self) -> bool {
+        self.status == "pending" && self.expires_at > Utc::now()
+    }
+}
+
+/// Membership changes rejected by the rules enforced in this module.
+///
+/// These are returned wrapped in `anyhow::Error`; callers can recover them
+/// with `downcast_ref::<MembershipError>()` to pick a response status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MembershipError {
+    /// The change would leave the organization without an owner
+    LastOwner,
+    /// The user is not an accepted member of the organization
+    NotMember,
+    /// The user is already a member of the organization
+    AlreadyMember,
+    /// The invitation was accepted, revoked or has expired
+    InvitationUnavailable,
+}
+
+impl std::fmt::Display for MembershipError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MembershipError::LastOwner => write!(f, "An organization must keep at least one owner"),
+            MembershipError::NotMember => write!(f, "User is not a member of the organization"),
+            MembershipError::AlreadyMember => write!(f, "User is already a member of the organization"),
+            MembershipError::InvitationUnavailable => write!(f, "Invitation has already been used, was revoked or has expired"),
+        }
+    }
+}
+
+impl std::error::Error for MembershipError {}
+
+/// Membership roles, in the order of the `orgmember.role` column.
+pub const MEMBER_ROLES: &[&str] = &["owner", "admin", "billing", "member", "guest"];
+
+/// Maps a membership role to the system role bound to the member in the
+/// organization's scope, which is what actually grants permissions.
+pub fn system_role_for_member_role(role: &str) -> &'static str {
+    match role {
+        "owner" => "owner",
+        "admin" => "admin",
+        "member" => "developer",
+        _ => "viewer",
+    }
+}
+
 /// Retrieves all organizations in the system, ordered by creation time.
 ///
 /// This function fetches all organization records from the database, with
@@ -93,11 +178,70 @@ pub async fn get_org_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Org> {
 pub async fn create_org(pool: &Pool<MySql>, name: &str) -> anyhow::Result<Org> {
     let mut tx = pool.begin().await?;
 
-    let org = sqlx::query_as::<_, Org>("INSERT INTO orgs (name) VALUES (?)")
+    let result = sqlx::query("INSERT INTO orgs (name) VALUES (?)")
         .bind(name)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to create organization")?;
+
+    let org = sqlx::query_as::<_, Org>("SELECT * FROM orgs WHERE id = ?")
+        .bind(result.last_insert_id())
         .fetch_one(&mut *tx)
         .await
+        .context("Failed to fetch created organization")?;
+
+    tx.commit().await?;
+    Ok(org)
+}
+
+/// Creates a new organization together with its first owner.
+///
+/// The owner is added as an accepted member and bound to the `owner` system
+/// role in the new organization's scope, all in a single transaction.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `name` - Unique name of the new organization
+/// * `display_name` - Optional human readable name
+/// * `description` - Optional description
+/// * `owner_id` - User that becomes the organization's owner
+///
+/// # Returns
+///
+/// * `Ok(Org)` - The newly created organization
+/// * `Err(anyhow::Error)` - Failed to create the organization
+pub async fn create_org_with_owner(
+    pool: &Pool<MySql>,
+    name: &str,
+    display_name: Option<&str>,
+    description: Option<&str>,
+    owner_id: i64,
+) -> anyhow::Result<Org> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query("INSERT INTO orgs (name, display_name, description) VALUES (?, ?, ?)")
+        .bind(name)
+        .bind(display_name)
+        .bind(description)
+        .execute(&mut *tx)
+        .await
         .context("Failed to create organization")?;
+    let org_id = result.last_insert_id() as i64;
+
+    sqlx::query("INSERT INTO orgmember (org_id, user_id, role, invitation_status) VALUES (?, ?, 'owner', 'accepted')")
+        .bind(org_id)
+        .bind(owner_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to add organization owner")?;
+    sync_member_role_binding(&mut tx, org_id, owner_id, Some("owner")).await?;
+
+    let org = sqlx::query_as::<_, Org>("SELECT * FROM orgs WHERE id = ?")
+        .bind(org_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created organization")?;
 
     tx.commit().await?;
     Ok(org)
@@ -105,14 +249,16 @@ pub async fn create_org(pool: &Pool<MySql>, name: &str) -> anyhow::Result<Org> {
 
 /// Updates an existing organization's information.
 ///
-/// This function modifies an organization record with the provided name.
-/// It also updates the `updated_at` timestamp to reflect the modification time.
+/// Only the fields that are given are changed. It also updates the
+/// `updated_at` timestamp to reflect the modification time.
 ///
 /// # Arguments
 ///
 /// * `pool` - Database connection pool for executing the query
 /// * `id` - Unique identifier of the organization to update
 /// * `name` - New name for the organization
+/// * `display_name` - New display name
+/// * `description` - New description
 ///
 /// # Returns
 ///
@@ -128,18 +274,39 @@ pub async fn create_org(pool: &Pool<MySql>, name: &str) -> anyhow::Result<Org> {
 ///
 /// Returns an error if no organization with the given ID exists or if a database
 /// error occurs during the update operation.
-pub async fn update_org(pool: &Pool<MySql>, id: i64, name: &str) -> anyhow::Result<Org> {
+pub async fn update_org(
+    pool: &Pool<MySql>,
+    id: i64,
+    name: Option<&str>,
+    display_name: Option<&str>,
+    description: Option<&str>,
+) -> anyhow::Result<Org> {
     let mut tx = pool.begin().await?;
 
-    let org = sqlx::query_as::<_, Org>(
-        "UPDATE orgs SET name = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?",
+    sqlx::query(
+        r#"
+        UPDATE orgs
+        SET name = COALESCE(?, name),
+            display_name = COALESCE(?, display_name),
+            description = COALESCE(?, description),
+            updated_at = CURRENT_TIMESTAMP
+        WHERE id = ?
+        "#,
     )
     .bind(name)
+    .bind(display_name)
+    .bind(description)
     .bind(id)
-    .fetch_one(&mut *tx)
+    .execute(&mut *tx)
     .await
     .context("Failed to update organization")?;
 
+    let org = sqlx::query_as::<_, Org>("SELECT * FROM orgs WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch updated organization")?;
+
     tx.commit().await?;
     Ok(org)
 }
@@ -167,10 +334,10 @@ pub async fn update_org(pool: &Pool<MySql>, id: i64, name: &str) -> anyhow::Resu
 ///
 /// # Important
 ///
-/// This function only deletes the organization record itself. It does not cascade
-/// delete related records such as organization members, applications, or other
-/// resources associated with the organization. Consider implementing cascading
-/// delete logic or foreign key constraints if needed.
+/// Records referencing the organization through foreign keys (members,
+/// invitations, applications, ...) are removed by the database's cascading
+/// deletes. Role bindings in the organization's scope and the organization's
+/// custom roles are removed here as they are not linked by foreign keys.
 ///
 /// # Transaction Handling
 ///
@@ -179,6 +346,18 @@ pub async fn update_org(pool: &Pool<MySql>, id: i64, name: &str) -> anyhow::Resu
 pub async fn delete_org(pool: &Pool<MySql>, id: i64) -> anyhow::Result<()> {
     let mut tx = pool.begin().await?;
 
+    sqlx::query("DELETE FROM role_user WHERE scope_type = 'organization' AND scope_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete organization role bindings")?;
+
+    sqlx::query("DELETE FROM roles WHERE org_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete organization roles")?;
+
     sqlx::query("DELETE FROM orgs WHERE id = ?")
         .bind(id)
         .execute(&mut *tx)
@@ -207,6 +386,11 @@ pub async fn delete_org(pool: &Pool<MySql>, id: i64) -> anyhow::Result<()> {
 /// * `Ok(())` - Successfully added the user to the organization
 /// * `Err(anyhow::Error)` - Failed to add the user to the organization
 ///
+/// # Role Bindings
+///
+/// The member is also bound to the system role matching their membership
+/// role (see [`system_role_for_member_role`]) in the organization's scope.
+///
 /// # Uniqueness
 ///
 /// This function assumes that the combination of `org_id` and `user_id`
@@ -232,6 +416,7 @@ pub async fn add_org_member(
         .execute(&mut *tx)
         .await
         .context("Failed to add organization member")?;
+    sync_member_role_binding(&mut tx, org_id, user_id, Some(role)).await?;
 
     tx.commit().await?;
     Ok(())
@@ -251,13 +436,15 @@ pub async fn add_org_member(
 /// # Returns
 ///
 /// * `Ok(())` - Successfully removed the user from the organization
-/// * `Err(anyhow::Error)` - Failed to remove the user from the organization
+/// * `Err(anyhow::Error)` - Failed to remove the user from the organization.
+///   `MembershipError::NotMember` and `MembershipError::LastOwner` are
+///   returned when the user is not a member or is the only owner.
 ///
 /// # Important
 ///
-/// This function only removes the membership relationship. It does not perform any
-/// cleanup of resources owned by or associated with the user within the organization.
-/// Additional logic may be needed to handle resource ownership transfer or deletion.
+/// Every role binding the user holds within the organization, its spaces and
+/// its applications is removed, and the user's API keys for the organization
+/// are revoked. Resources the user created are left untouched.
 ///
 /// # Transaction Handling
 ///
@@ -270,13 +457,49 @@ pub async fn remove_org_member(
 ) -> anyhow::Result<()> {
     let mut tx = pool.begin().await?;
 
-    sqlx::query("DELETE FROM orgmember WHERE org_id = ? AND user_id = ?")
+    ensure_not_last_owner(&mut tx, org_id, user_id).await?;
+
+    let result = sqlx::query("DELETE FROM orgmember WHERE org_id = ? AND user_id = ?")
         .bind(org_id)
         .bind(user_id)
         .execute(&mut *tx)
         .await
         .context("Failed to remove organization member")?;
 
+    if result.rows_affected() == 0 {
+        return Err(MembershipError::NotMember.into());
+    }
+
+    sqlx::query(
+        r#"
+        DELETE FROM role_user
+        WHERE user_id = ?
+          AND ((scope_type = 'organization' AND scope_id = ?)
+            OR (scope_type = 'space' AND scope_id IN (SELECT id FROM spaces WHERE org_id = ?))
+            OR (scope_type = 'application' AND scope_id IN (SELECT id FROM apps WHERE org_id = ?)))
+        "#,
+    )
+    .bind(user_id)
+    .bind(org_id)
+    .bind(org_id)
+    .bind(org_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to remove member role bindings")?;
+
+    sqlx::query(
+        r#"
+        UPDATE api_keys
+        SET revoked = 1, revoked_at = CURRENT_TIMESTAMP
+        WHERE org_id = ? AND user_id = ? AND revoked = 0
+        "#,
+    )
+    .bind(org_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to revoke member API keys")?;
+
     tx.commit().await?;
     Ok(())
 }
@@ -301,8 +524,14 @@ pub async fn remove_org_member(
 ///
 /// # Error Handling
 ///
-/// Returns an error if the user is not a member of the organization or if a database
-/// error occurs during the update operation.
+/// Returns `MembershipError::NotMember` if the user is not a member of the
+/// organization and `MembershipError::LastOwner` if the only owner would be
+/// demoted, or an error if a database error occurs during the update operation.
+///
+/// # Role Bindings
+///
+/// The member's system role binding in the organization is replaced to match
+/// the new membership role.
 ///
 /// # Transaction Handling
 ///
@@ -322,14 +551,456 @@ pub async fn update_org_member_role(
 ) -> anyhow::Result<()> {
     let mut tx = pool.begin().await?;
 
-    sqlx::query("UPDATE orgmember SET role = ? WHERE org_id = ? AND user_id = ?")
-        .bind(role)
+    if role != "owner" {
+        ensure_not_last_owner(&mut tx, org_id, user_id).await?;
+    }
+
+    let result = sqlx::query(
+        "UPDATE orgmember SET role = ? WHERE org_id = ? AND user_id = ? AND invitation_status = 'accepted'",
+    )
+    .bind(role)
+    .bind(org_id)
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update organization member role")?;
+
+    if result.rows_affected() == 0 {
+        let exists = sqlx::query_scalar::<_, i64>(
+            "SELECT COUNT(*) FROM orgmember WHERE org_id = ? AND user_id = ? AND invitation_status = 'accepted'",
+        )
         .bind(org_id)
         .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch organization membership")?;
+
+        if exists == 0 {
+            return Err(MembershipError::NotMember.into());
+        }
+    }
+    sync_member_role_binding(&mut tx, org_id, user_id, Some(role)).await?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Retrieves a user's role within an organization.
+///
+/// Only accepted memberships are considered; pending or rejected invitations
+/// do not grant any role.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Unique identifier of the organization
+/// * `user_id` - Unique identifier of the user
+///
+/// # Returns
+///
+/// * `Ok(Some(String))` - The member's role (`owner`, `admin`, `billing`, `member` or `guest`)
+/// * `Ok(None)` - The user is not an accepted member of the organization
+/// * `Err(anyhow::Error)` - Failed to fetch the membership
+pub async fn get_org_member_role(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    user_id: i64,
+) -> anyhow::Result<Option<String>> {
+    let role = sqlx::query_scalar::<_, String>(
+        "SELECT role FROM orgmember WHERE org_id = ? AND user_id = ? AND invitation_status = 'accepted'",
+    )
+    .bind(org_id)
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch organization membership")?;
+
+    Ok(role)
+}
+
+/// Retrieves the organizations a user is an accepted member of.
+pub async fn list_orgs_for_user(pool: &Pool<MySql>, user_id: i64) -> anyhow::Result<Vec<Org>> {
+    let orgs = sqlx::query_as::<_, Org>(
+        r#"
+        SELECT o.* FROM orgs o
+        JOIN orgmember m ON m.org_id = o.id
+        WHERE m.user_id = ? AND m.invitation_status = 'accepted'
+        ORDER BY o.created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch organizations for user")?;
+
+    Ok(orgs)
+}
+
+/// Retrieves the accepted members of an organization, owners first.
+pub async fn list_org_members(pool: &Pool<MySql>, org_id: i64) -> anyhow::Result<Vec<OrgMember>> {
+    let members = sqlx::query_as::<_, OrgMember>(
+        r#"
+        SELECT id, org_id, user_id, role, invitation_status, created_at, updated_at
+        FROM orgmember
+        WHERE org_id = ? AND invitation_status = 'accepted'
+        ORDER BY FIELD(role, 'owner', 'admin', 'billing', 'member', 'guest'), created_at ASC
+        "#,
+    )
+    .bind(org_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch organization members")?;
+
+    Ok(members)
+}
+
+/// Transfers ownership of an organization from one member to another.
+///
+/// The new owner must already be an accepted member. The previous owner is
+/// demoted to `admin`, and both members' system role bindings are updated.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Unique identifier of the organization
+/// * `from_user_id` - Current owner giving up ownership
+/// * `to_user_id` - Member receiving ownership
+///
+/// # Returns
+///
+/// * `Ok(())` - Ownership was transferred
+/// * `Err(anyhow::Error)` - `MembershipError::NotMember` if either user is
+///   not an accepted member (or `from_user_id` is not an owner), or a
+///   database error
+pub async fn transfer_org_ownership(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    from_user_id: i64,
+    to_user_id: i64,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let from_role = sqlx::query_scalar::<_, String>(
+        "SELECT role FROM orgmember WHERE org_id = ? AND user_id = ? AND invitation_status = 'accepted' FOR UPDATE",
+    )
+    .bind(org_id)
+    .bind(from_user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to fetch current owner")?;
+
+    let to_role = sqlx::query_scalar::<_, String>(
+        "SELECT role FROM orgmember WHERE org_id = ? AND user_id = ? AND invitation_status = 'accepted' FOR UPDATE",
+    )
+    .bind(org_id)
+    .bind(to_user_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to fetch new owner")?;
+
+    if from_role.as_deref() != Some("owner") || to_role.is_none() {
+        return Err(MembershipError::NotMember.into());
+    }
+
+    sqlx::query("UPDATE orgmember SET role = 'owner' WHERE org_id = ? AND user_id = ?")
+        .bind(org_id)
+        .bind(to_user_id)
         .execute(&mut *tx)
         .await
-        .context("Failed to update organization member role")?;
+        .context("Failed to promote new owner")?;
+    sync_member_role_binding(&mut tx, org_id, to_user_id, Some("owner")).await?;
+
+    if from_user_id != to_user_id {
+        sqlx::query("UPDATE orgmember SET role = 'admin' WHERE org_id = ? AND user_id = ?")
+            .bind(org_id)
+            .bind(from_user_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to demote previous owner")?;
+        sync_member_role_binding(&mut tx, org_id, from_user_id, Some("admin")).await?;
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+//=============================================================================
+// Invitation Operations
+//=============================================================================
+
+/// Retrieves the invitations of an organization, newest first.
+///
+/// Unless `include_closed` is set only pending, unexpired invitations are returned.
+pub async fn list_org_invitations(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    include_closed: bool,
+) -> anyhow::Result<Vec<OrgInvitation>> {
+    let invitations = sqlx::query_as::<_, OrgInvitation>(
+        r#"
+        SELECT * FROM org_invitations
+        WHERE org_id = ? AND (? OR (status = 'pending' AND expires_at > UTC_TIMESTAMP()))
+        ORDER BY created_at DESC
+        "#,
+    )
+    .bind(org_id)
+    .bind(include_closed)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch organization invitations")?;
+
+    Ok(invitations)
+}
+
+/// Retrieves a specific invitation by its unique identifier.
+pub async fn get_org_invitation_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<OrgInvitation> {
+    let invitation = sqlx::query_as::<_, OrgInvitation>("SELECT * FROM org_invitations WHERE id = ?")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to fetch invitation")?;
+
+    Ok(invitation)
+}
+
+/// Retrieves an invitation by the hash of its token.
+pub async fn get_org_invitation_by_token_hash(
+    pool: &Pool<MySql>,
+    token_hash: &str,
+) -> anyhow::Result<Option<OrgInvitation>> {
+    let invitation = sqlx::query_as::<_, OrgInvitation>("SELECT * FROM org_invitations WHERE token_hash = ?")
+        .bind(token_hash)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch invitation by token")?;
+
+    Ok(invitation)
+}
+
+/// Checks whether an email address has a pending, unexpired invitation.
+pub async fn has_pending_org_invitation(pool: &Pool<MySql>, org_id: i64, email: &str) -> anyhow::Result<bool> {
+    let count = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT COUNT(*) FROM org_invitations
+        WHERE org_id = ? AND email = ? AND status = 'pending' AND expires_at > UTC_TIMESTAMP()
+        "#,
+    )
+    .bind(org_id)
+    .bind(email)
+    .fetch_one(pool)
+    .await
+    .context("Failed to check for pending invitations")?;
+
+    Ok(count > 0)
+}
+
+/// Creates a new invitation to join an organization.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Organization the invitee will join
+/// * `email` - Email address the invitation is addressed to
+/// * `role` - Membership role granted on acceptance
+/// * `token_hash` - Hex encoded SHA-256 digest of the invitation token
+/// * `invited_by` - User sending the invitation
+/// * `expires_at` - Time after which the invitation can no longer be accepted
+///
+/// # Returns
+///
+/// * `Ok(OrgInvitation)` - The newly created invitation
+/// * `Err(anyhow::Error)` - Failed to create the invitation
+pub async fn create_org_invitation(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    email: &str,
+    role: &str,
+    token_hash: &str,
+    invited_by: i64,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<OrgInvitation> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO org_invitations (org_id, email, role, token_hash, invited_by, expires_at)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(org_id)
+    .bind(email)
+    .bind(role)
+    .bind(token_hash)
+    .bind(invited_by)
+    .bind(expires_at)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create invitation")?;
+
+    let invitation = sqlx::query_as::<_, OrgInvitation>("SELECT * FROM org_invitations WHERE id = ?")
+        .bind(result.last_insert_id())
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created invitation")?;
+
+    tx.commit().await?;
+    Ok(invitation)
+}
+
+/// Revokes a pending invitation.
+///
+/// # Returns
+///
+/// * `Ok(true)` - The invitation was pending and has been revoked
+/// * `Ok(false)` - The invitation was already accepted or revoked
+/// * `Err(anyhow::Error)` - Failed to revoke the invitation
+pub async fn revoke_org_invitation(pool: &Pool<MySql>, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("UPDATE org_invitations SET status = 'revoked' WHERE id = ? AND status = 'pending'")
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to revoke invitation")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Accepts an invitation on behalf of a user, making them a member.
+///
+/// The invitation is locked for the duration of the transaction so it can
+/// only ever be accepted once.
+///
+/// # Returns
+///
+/// * `Ok(OrgMember)` - The new membership
+/// * `Err(anyhow::Error)` - `MembershipError::InvitationUnavailable` if the
+///   invitation is no longer pending, `MembershipError::AlreadyMember` if
+///   the user already belongs to the organization, or a database error
+pub async fn accept_org_invitation(
+    pool: &Pool<MySql>,
+    invitation_id: i64,
+    user_id: i64,
+) -> anyhow::Result<OrgMember> {
+    let mut tx = pool.begin().await?;
+
+    let invitation = sqlx::query_as::<_, OrgInvitation>("SELECT * FROM org_invitations WHERE id = ? FOR UPDATE")
+        .bind(invitation_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch invitation")?;
+
+    if !invitation.is_pending() {
+        return Err(MembershipError::InvitationUnavailable.into());
+    }
+
+    let existing = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM orgmember WHERE org_id = ? AND user_id = ?")
+        .bind(invitation.org_id)
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to check existing membership")?;
+
+    if existing > 0 {
+        return Err(MembershipError::AlreadyMember.into());
+    }
+
+    let result = sqlx::query(
+        "INSERT INTO orgmember (org_id, user_id, role, invitation_status) VALUES (?, ?, ?, 'accepted')",
+    )
+    .bind(invitation.org_id)
+    .bind(user_id)
+    .bind(&invitation.role)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to add organization member")?;
+    sync_member_role_binding(&mut tx, invitation.org_id, user_id, Some(&invitation.role)).await?;
+
+    sqlx::query(
+        "UPDATE org_invitations SET status = 'accepted', accepted_by = ?, accepted_at = CURRENT_TIMESTAMP WHERE id = ?",
+    )
+    .bind(user_id)
+    .bind(invitation_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to mark invitation as accepted")?;
+
+    let member = sqlx::query_as::<_, OrgMember>(
+        "SELECT id, org_id, user_id, role, invitation_status, created_at, updated_at FROM orgmember WHERE id = ?",
+    )
+    .bind(result.last_insert_id())
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to fetch new membership")?;
 
     tx.commit().await?;
+    Ok(member)
+}
+
+//=============================================================================
+// Helpers
+//=============================================================================
+
+/// Replaces the system role binding of a member within an organization.
+///
+/// Custom role bindings are left alone; passing `None` only removes the
+/// system role binding.
+async fn sync_member_role_binding(
+    tx: &mut Transaction<'_, MySql>,
+    org_id: i64,
+    user_id: i64,
+    member_role: Option<&str>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        DELETE ru FROM role_user ru
+        JOIN roles r ON r.id = ru.role_id
+        WHERE ru.user_id = ? AND ru.scope_type = 'organization' AND ru.scope_id = ?
+          AND r.is_system_role = 1 AND r.org_id IS NULL
+        "#,
+    )
+    .bind(user_id)
+    .bind(org_id)
+    .execute(&mut **tx)
+    .await
+    .context("Failed to remove member role binding")?;
+
+    if let Some(member_role) = member_role {
+        sqlx::query(
+            r#"
+            INSERT IGNORE INTO role_user (user_id, role_id, scope_type, scope_id)
+            SELECT ?, id, 'organization', ? FROM roles
+            WHERE name = ? AND is_system_role = 1 AND org_id IS NULL
+            "#,
+        )
+        .bind(user_id)
+        .bind(org_id)
+        .bind(system_role_for_member_role(member_role))
+        .execute(&mut **tx)
+        .await
+        .context("Failed to bind member role")?;
+    }
+
     Ok(())
-}
\ No newline at end of file
+}
+
+/// Fails with `MembershipError::LastOwner` if `user_id` is the only owner.
+///
+/// The owner rows are locked for the rest of the transaction so that two
+/// concurrent demotions cannot both pass this check.
+async fn ensure_not_last_owner(
+    tx: &mut Transaction<'_, MySql>,
+    org_id: i64,
+    user_id: i64,
+) -> anyhow::Result<()> {
+    let owners = sqlx::query_scalar::<_, i64>(
+        "SELECT user_id FROM orgmember WHERE org_id = ? AND role = 'owner' AND invitation_status = 'accepted' FOR UPDATE",
+    )
+    .bind(org_id)
+    .fetch_all(&mut **tx)
+    .await
+    .context("Failed to fetch organization owners")?;
+
+    if owners.contains(&user_id) && owners.len() == 1 {
+        return Err(MembershipError::LastOwner.into());
+    }
+    Ok(())
+}
@@ -417,8 +417,13 @@ pub async fn update_permission(
 
     let query = format!("UPDATE permissions{} WHERE id = ?", field_clauses);
 
+    // Nothing to update, just return the current record
+    if field_clauses.is_empty() {
+        return get_permission_by_id(pool, id).await;
+    }
+
     // Start binding parameters
-    let mut db_query = sqlx::query_as::<_, Permission>(&query);
+    let mut db_query = sqlx::query(&query);
 
     // Bind parameters
     if let Some(val) = name {
@@ -436,11 +441,17 @@ pub async fn update_permission(
 
     // Execute the query in a transaction
     let mut tx = pool.begin().await?;
-    let permission = db_query
-        .fetch_one(&mut *tx)
+    db_query
+        .execute(&mut *tx)
         .await
         .context("Failed to update permission")?;
 
+    let permission = sqlx::query_as::<_, Permission>("SELECT * FROM permissions WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch updated permission")?;
+
     tx.commit().await?;
     Ok(permission)
 }
@@ -516,9 +527,9 @@ pub async fn delete_permission(pool: &Pool<MySql>, id: i64) -> anyhow::Result<()
 ///
 /// # Uniqueness
 ///
-/// This function assumes that the combination of `permission_id` and `role_id`
-/// must be unique in the permissions_role table. If this association already
-/// exists, the operation will fail with a unique constraint violation.
+/// The combination of `permission_id` and `role_id` is unique in the
+/// permissions_role table. Assigning a permission the role already has is a
+/// no-op rather than an error.
 ///
 /// # Transaction Handling
 ///
@@ -536,7 +547,7 @@ pub async fn assign_permission_to_role(
 ) -> anyhow::Result<()> {
     let mut tx = pool.begin().await?;
 
-    sqlx::query("INSERT INTO permissions_role (permissions_id, role_id) VALUES (?, ?)")
+    sqlx::query("INSERT IGNORE INTO permissions_role (permissions_id, role_id) VALUES (?, ?)")
         .bind(permission_id)
         .bind(role_id)
         .execute(&mut *tx)
@@ -804,4 +815,54 @@ pub async fn get_user_permissions(
     .context("Failed to fetch user permissions")?;
 
     Ok(permissions)
-}
\ No newline at end of file
+}
+
+/// Retrieves the names of the permissions a user holds within a scope.
+///
+/// Role bindings in `role_user` are scoped: a `global` binding applies to the
+/// whole platform, while `organization`, `space` and `application` bindings
+/// only apply to the resource identified by `scope_id`. This function
+/// collects the permissions granted by every binding that applies to the
+/// given scope, i.e. all global bindings plus any binding on the organization,
+/// space or application passed in.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `user_id` - Unique identifier of the user whose permissions to retrieve
+/// * `org_id` - Organization the request is scoped to, if any
+/// * `space_id` - Space the request is scoped to, if any
+/// * `app_id` - Application the request is scoped to, if any
+///
+/// # Returns
+///
+/// * `Ok(Vec<String>)` - Distinct permission names granted in the scope
+/// * `Err(anyhow::Error)` - Failed to fetch the permissions
+pub async fn get_user_permission_names_in_scope(
+    pool: &Pool<MySql>,
+    user_id: i64,
+    org_id: Option<i64>,
+    space_id: Option<i64>,
+    app_id: Option<i64>,
+) -> anyhow::Result<Vec<String>> {
+    let names = sqlx::query_scalar::<_, String>(
+        r#"SELECT DISTINCT p.name FROM permissions p
+        JOIN permissions_role pr ON p.id = pr.permissions_id
+        JOIN role_user ru ON pr.role_id = ru.role_id
+        WHERE ru.user_id = ?
+          AND (ru.scope_type = 'global'
+            OR (ru.scope_type = 'organization' AND ru.scope_id = ?)
+            OR (ru.scope_type = 'space' AND ru.scope_id = ?)
+            OR (ru.scope_type = 'application' AND ru.scope_id = ?))
+        ORDER BY p.name"#,
+    )
+    .bind(user_id)
+    .bind(org_id)
+    .bind(space_id)
+    .bind(app_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch scoped user permissions")?;
+
+    Ok(names)
+}
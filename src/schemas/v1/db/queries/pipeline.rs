@@ -0,0 +1,166 @@
+// db/queries/pipeline.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// A sequence of stages an application's builds are taken through.
+///
+/// Runs can only be started while the pipeline is 'active'. Runs of a
+/// 'paused' pipeline stop advancing until it is activated again.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Pipeline {
+    pub id: i64,
+    pub app_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    /// 'draft', 'active', 'paused' or 'archived'
+    pub status: Option<String>,
+    /// The stage definitions, see [`crate::pipelines::stages`]
+    pub stages: Option<serde_json::Value>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+    pub updated_by: Option<i64>,
+}
+
+/// The settable fields of a pipeline.
+#[derive(Debug, Clone)]
+pub struct PipelineInput {
+    pub name: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub stages: serde_json::Value,
+}
+
+/// Retrieves the pipelines of an application.
+pub async fn list_app_pipelines(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Vec<Pipeline>> {
+    let pipelines = sqlx::query_as::<_, Pipeline>("SELECT * FROM pipelines WHERE app_id = ? ORDER BY id ASC")
+        .bind(app_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch pipelines")?;
+
+    Ok(pipelines)
+}
+
+/// Retrieves a pipeline of an application.
+pub async fn get_pipeline(pool: &Pool<MySql>, app_id: i64, id: i64) -> anyhow::Result<Option<Pipeline>> {
+    let pipeline = sqlx::query_as::<_, Pipeline>("SELECT * FROM pipelines WHERE id = ? AND app_id = ?")
+        .bind(id)
+        .bind(app_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch pipeline")?;
+
+    Ok(pipeline)
+}
+
+/// Creates a pipeline of an application.
+pub async fn create_pipeline(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    pipeline: &PipelineInput,
+    created_by: Option<i64>,
+) -> anyhow::Result<Pipeline> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"INSERT INTO pipelines (app_id, name, description, status, stages, created_by, updated_by)
+        VALUES (?, ?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(app_id)
+    .bind(&pipeline.name)
+    .bind(&pipeline.description)
+    .bind(&pipeline.status)
+    .bind(&pipeline.stages)
+    .bind(created_by)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create pipeline")?;
+
+    let created = sqlx::query_as::<_, Pipeline>("SELECT * FROM pipelines WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created pipeline")?;
+
+    tx.commit().await?;
+
+    Ok(created)
+}
+
+/// Replaces the fields of a pipeline. Runs in progress keep the stages they
+/// were started with.
+pub async fn update_pipeline(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    id: i64,
+    pipeline: &PipelineInput,
+    updated_by: Option<i64>,
+) -> anyhow::Result<Option<Pipeline>> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"UPDATE pipelines
+        SET name = ?, description = ?, status = ?, stages = ?, updated_by = ?
+        WHERE id = ? AND app_id = ?"#,
+    )
+    .bind(&pipeline.name)
+    .bind(&pipeline.description)
+    .bind(&pipeline.status)
+    .bind(&pipeline.stages)
+    .bind(updated_by)
+    .bind(id)
+    .bind(app_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update pipeline")?;
+
+    let updated = sqlx::query_as::<_, Pipeline>("SELECT * FROM pipelines WHERE id = ? AND app_id = ?")
+        .bind(id)
+        .bind(app_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to fetch updated pipeline")?;
+
+    tx.commit().await?;
+
+    Ok(updated)
+}
+
+/// Deletes a pipeline together with its runs, unless one of them is still
+/// running. Returns `false` if a run is in progress.
+pub async fn delete_pipeline(pool: &Pool<MySql>, app_id: i64, id: i64) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT id FROM pipelines WHERE id = ? AND app_id = ? FOR UPDATE")
+        .bind(id)
+        .bind(app_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to lock pipeline")?;
+
+    let running = sqlx::query_scalar::<_, i64>(
+        "SELECT EXISTS(SELECT 1 FROM pipeline_runs WHERE pipeline_id = ? AND status = 'running')",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to check for running pipeline runs")?;
+    if running != 0 {
+        return Ok(false);
+    }
+
+    sqlx::query("DELETE FROM pipelines WHERE id = ? AND app_id = ?")
+        .bind(id)
+        .bind(app_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete pipeline")?;
+
+    tx.commit().await?;
+
+    Ok(true)
+}
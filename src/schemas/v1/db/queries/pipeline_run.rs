@@ -0,0 +1,673 @@
+// db/queries/pipeline_run.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// An execution of a pipeline.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PipelineRun {
+    pub id: i64,
+    pub pipeline_id: i64,
+    pub app_id: i64,
+    /// 'running', 'succeeded', 'failed' or 'canceled'
+    pub status: Option<String>,
+    /// The stage definitions of the pipeline when the run started
+    pub stages: serde_json::Value,
+    /// The build carried through the stages
+    pub build_id: Option<i64>,
+    pub commit_sha: Option<String>,
+    pub error_message: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The progress of a stage of a run.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PipelineRunStage {
+    pub id: i64,
+    pub run_id: i64,
+    pub stage_index: i32,
+    pub name: String,
+    pub stage_type: String,
+    /// 'pending', 'running', 'succeeded', 'failed', 'canceled' or 'skipped'
+    pub status: Option<String>,
+    pub attempts: i32,
+    pub max_attempts: i32,
+    pub build_id: Option<i64>,
+    pub task_id: Option<i64>,
+    pub deployment_id: Option<i64>,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub waiting_reason: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub error_message: Option<String>,
+}
+
+/// A line of a run's log.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PipelineRunLog {
+    pub id: i64,
+    pub run_id: i64,
+    /// The stage the line is about; unset for lines about the whole run
+    pub stage_id: Option<i64>,
+    pub log_level: Option<String>,
+    pub message: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// A user's decision on an approval stage.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PipelineStageApproval {
+    pub id: i64,
+    pub stage_id: i64,
+    pub user_id: i64,
+    /// 'approved' or 'rejected'
+    pub decision: String,
+    pub comment: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// A stage of a run to be created, see [`create_run`].
+#[derive(Debug, Clone)]
+pub struct NewRunStage {
+    pub name: String,
+    pub stage_type: &'static str,
+    pub max_attempts: i32,
+}
+
+/// Result of starting a run.
+#[derive(Debug, Clone)]
+pub enum StartRunOutcome {
+    Started(PipelineRun),
+    /// The pipeline already has the run with this ID in progress.
+    AlreadyRunning(i64),
+}
+
+/// Result of a stage failing.
+#[derive(Debug, Clone)]
+pub enum StageFailure {
+    /// The stage is attempted again once its retry delay has passed.
+    Retrying,
+    /// The stage is out of attempts, failing the run.
+    Failed,
+    /// The stage or run had already finished.
+    Finished,
+}
+
+/// Result of retrying a run.
+#[derive(Debug, Clone)]
+pub enum RetryRunOutcome {
+    Retried,
+    /// The run has not failed or been canceled.
+    NotFinished,
+    /// Another run of the pipeline is in progress.
+    OtherRunActive(i64),
+}
+
+/// Result of a user deciding on an approval stage.
+#[derive(Debug, Clone)]
+pub enum StageDecisionOutcome {
+    Recorded,
+    /// The user has already approved or rejected the stage.
+    AlreadyDecided,
+    /// The run is not waiting for approval.
+    NotAwaitingApproval,
+}
+
+/// Starts a run of a pipeline with a pending row per stage, unless the
+/// pipeline has a run in progress.
+pub async fn create_run(
+    pool: &Pool<MySql>,
+    pipeline_id: i64,
+    app_id: i64,
+    stages: &serde_json::Value,
+    run_stages: &[NewRunStage],
+    build_id: Option<i64>,
+    commit_sha: Option<&str>,
+    created_by: Option<i64>,
+) -> anyhow::Result<StartRunOutcome> {
+    let mut tx = pool.begin().await?;
+
+    // Serializes runs being started for the pipeline
+    sqlx::query("SELECT id FROM pipelines WHERE id = ? FOR UPDATE")
+        .bind(pipeline_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to lock pipeline")?;
+
+    let running = sqlx::query_scalar::<_, i64>(
+        "SELECT id FROM pipeline_runs WHERE pipeline_id = ? AND status = 'running' LIMIT 1",
+    )
+    .bind(pipeline_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to check for running pipeline runs")?;
+    if let Some(run_id) = running {
+        return Ok(StartRunOutcome::AlreadyRunning(run_id));
+    }
+
+    let result = sqlx::query(
+        r#"INSERT INTO pipeline_runs (pipeline_id, app_id, status, stages, build_id, commit_sha, created_by)
+        VALUES (?, ?, 'running', ?, ?, ?, ?)"#,
+    )
+    .bind(pipeline_id)
+    .bind(app_id)
+    .bind(stages)
+    .bind(build_id)
+    .bind(commit_sha)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create pipeline run")?;
+    let run_id = result.last_insert_id() as i64;
+
+    for (index, stage) in run_stages.iter().enumerate() {
+        sqlx::query(
+            r#"INSERT INTO pipeline_run_stages (run_id, stage_index, name, stage_type, status, max_attempts)
+            VALUES (?, ?, ?, ?, 'pending', ?)"#,
+        )
+        .bind(run_id)
+        .bind(index as i32)
+        .bind(&stage.name)
+        .bind(stage.stage_type)
+        .bind(stage.max_attempts)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to create pipeline run stage")?;
+    }
+
+    let message = match (build_id, created_by) {
+        (Some(build_id), Some(user_id)) => format!("Run started by user {} with build {}", user_id, build_id),
+        (Some(build_id), None) => format!("Run started with build {}", build_id),
+        (None, Some(user_id)) => format!("Run started by user {}", user_id),
+        (None, None) => "Run started".to_string(),
+    };
+    sqlx::query("INSERT INTO pipeline_run_logs (run_id, log_level, message) VALUES (?, 'info', ?)")
+        .bind(run_id)
+        .bind(message)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record pipeline run log line")?;
+
+    let run = sqlx::query_as::<_, PipelineRun>("SELECT * FROM pipeline_runs WHERE id = ?")
+        .bind(run_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created pipeline run")?;
+
+    tx.commit().await?;
+
+    Ok(StartRunOutcome::Started(run))
+}
+
+/// Retrieves a page of the runs of a pipeline, newest first.
+pub async fn list_pipeline_runs(
+    pool: &Pool<MySql>,
+    pipeline_id: i64,
+    page: i64,
+    per_page: i64,
+) -> anyhow::Result<Vec<PipelineRun>> {
+    let runs = sqlx::query_as::<_, PipelineRun>(
+        "SELECT * FROM pipeline_runs WHERE pipeline_id = ? ORDER BY id DESC LIMIT ? OFFSET ?",
+    )
+    .bind(pipeline_id)
+    .bind(per_page)
+    .bind(page * per_page)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch pipeline runs")?;
+
+    Ok(runs)
+}
+
+/// Counts the runs of a pipeline.
+pub async fn count_pipeline_runs(pool: &Pool<MySql>, pipeline_id: i64) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM pipeline_runs WHERE pipeline_id = ?")
+        .bind(pipeline_id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to count pipeline runs")?;
+
+    Ok(count)
+}
+
+/// Retrieves a run of a pipeline.
+pub async fn get_run(pool: &Pool<MySql>, pipeline_id: i64, run_id: i64) -> anyhow::Result<Option<PipelineRun>> {
+    let run = sqlx::query_as::<_, PipelineRun>("SELECT * FROM pipeline_runs WHERE id = ? AND pipeline_id = ?")
+        .bind(run_id)
+        .bind(pipeline_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch pipeline run")?;
+
+    Ok(run)
+}
+
+/// Retrieves the stages of a run in order.
+pub async fn list_run_stages(pool: &Pool<MySql>, run_id: i64) -> anyhow::Result<Vec<PipelineRunStage>> {
+    let stages = sqlx::query_as::<_, PipelineRunStage>(
+        "SELECT * FROM pipeline_run_stages WHERE run_id = ? ORDER BY stage_index ASC",
+    )
+    .bind(run_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch pipeline run stages")?;
+
+    Ok(stages)
+}
+
+/// Retrieves the runs in progress whose pipeline is not paused, oldest
+/// first.
+pub async fn list_runs_to_execute(pool: &Pool<MySql>) -> anyhow::Result<Vec<PipelineRun>> {
+    let runs = sqlx::query_as::<_, PipelineRun>(
+        r#"SELECT r.* FROM pipeline_runs r
+        JOIN pipelines p ON p.id = r.pipeline_id
+        WHERE r.status = 'running' AND p.status <> 'paused'
+        ORDER BY r.id ASC"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch pipeline runs to execute")?;
+
+    Ok(runs)
+}
+
+/// Marks a pending stage of a running run as started, with the build, task
+/// or deployment it waits for. Returns `false` if the stage or run was
+/// changed in the meantime.
+pub async fn start_stage(
+    pool: &Pool<MySql>,
+    stage_id: i64,
+    build_id: Option<i64>,
+    task_id: Option<i64>,
+    deployment_id: Option<i64>,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE pipeline_run_stages s
+        JOIN pipeline_runs r ON r.id = s.run_id
+        SET s.status = 'running', s.attempts = s.attempts + 1, s.started_at = NOW(),
+            s.next_attempt_at = NULL, s.waiting_reason = NULL, s.error_message = NULL,
+            s.build_id = ?, s.task_id = ?, s.deployment_id = ?
+        WHERE s.id = ? AND s.status = 'pending' AND r.status = 'running'"#,
+    )
+    .bind(build_id)
+    .bind(task_id)
+    .bind(deployment_id)
+    .bind(stage_id)
+    .execute(pool)
+    .await
+    .context("Failed to start pipeline run stage")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Marks a started stage as succeeded. If `build_id` is set, the run
+/// carries that build through the following stages.
+pub async fn succeed_stage(
+    pool: &Pool<MySql>,
+    run_id: i64,
+    stage_id: i64,
+    build_id: Option<i64>,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"UPDATE pipeline_run_stages
+        SET status = 'succeeded', completed_at = NOW(), waiting_reason = NULL
+        WHERE id = ? AND status = 'running'"#,
+    )
+    .bind(stage_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to complete pipeline run stage")?;
+    if result.rows_affected() == 0 {
+        return Ok(false);
+    }
+
+    if let Some(build_id) = build_id {
+        sqlx::query("UPDATE pipeline_runs SET build_id = ? WHERE id = ?")
+            .bind(build_id)
+            .bind(run_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to update build of pipeline run")?;
+    }
+
+    tx.commit().await?;
+
+    Ok(true)
+}
+
+/// Records that a started stage failed. The stage is attempted again after
+/// `retry_delay_seconds` while it has attempts left; otherwise it fails the
+/// run, and the stages after it are skipped.
+pub async fn fail_stage(
+    pool: &Pool<MySql>,
+    run_id: i64,
+    stage_id: i64,
+    error_message: &str,
+    retry_delay_seconds: u64,
+) -> anyhow::Result<StageFailure> {
+    let mut tx = pool.begin().await?;
+
+    let stage = sqlx::query_as::<_, PipelineRunStage>(
+        "SELECT * FROM pipeline_run_stages WHERE id = ? AND status = 'running' FOR UPDATE",
+    )
+    .bind(stage_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to fetch pipeline run stage")?;
+    let stage = match stage {
+        Some(stage) => stage,
+        None => return Ok(StageFailure::Finished),
+    };
+
+    if stage.attempts < stage.max_attempts {
+        sqlx::query(
+            r#"UPDATE pipeline_run_stages
+            SET status = 'pending', error_message = ?, next_attempt_at = NOW() + INTERVAL ? SECOND
+            WHERE id = ?"#,
+        )
+        .bind(error_message)
+        .bind(retry_delay_seconds)
+        .bind(stage_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to requeue pipeline run stage")?;
+        tx.commit().await?;
+        return Ok(StageFailure::Retrying);
+    }
+
+    sqlx::query(
+        "UPDATE pipeline_run_stages SET status = 'failed', error_message = ?, completed_at = NOW() WHERE id = ?",
+    )
+    .bind(error_message)
+    .bind(stage_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to fail pipeline run stage")?;
+    sqlx::query("UPDATE pipeline_run_stages SET status = 'skipped' WHERE run_id = ? AND stage_index > ? AND status = 'pending'")
+        .bind(run_id)
+        .bind(stage.stage_index)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to skip pipeline run stages")?;
+    sqlx::query(
+        r#"UPDATE pipeline_runs SET status = 'failed', error_message = ?, completed_at = NOW()
+        WHERE id = ? AND status = 'running'"#,
+    )
+    .bind(format!("Stage '{}' failed: {}", stage.name, error_message))
+    .bind(run_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to fail pipeline run")?;
+
+    tx.commit().await?;
+
+    Ok(StageFailure::Failed)
+}
+
+/// Holds off a pending stage for `seconds`, noting why. Returns whether
+/// the reason changed, so that it is only logged once.
+pub async fn defer_stage(pool: &Pool<MySql>, stage_id: i64, seconds: u64, reason: &str) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let previous = sqlx::query_scalar::<_, Option<String>>(
+        "SELECT waiting_reason FROM pipeline_run_stages WHERE id = ? AND status = 'pending' FOR UPDATE",
+    )
+    .bind(stage_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to fetch pipeline run stage")?;
+    let previous = match previous {
+        Some(previous) => previous,
+        None => return Ok(false),
+    };
+
+    sqlx::query(
+        r#"UPDATE pipeline_run_stages
+        SET next_attempt_at = NOW() + INTERVAL ? SECOND, waiting_reason = LEFT(?, 255)
+        WHERE id = ?"#,
+    )
+    .bind(seconds)
+    .bind(reason)
+    .bind(stage_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to defer pipeline run stage")?;
+
+    tx.commit().await?;
+
+    Ok(previous.as_deref() != Some(reason))
+}
+
+/// Marks a run whose stages all succeeded as succeeded.
+pub async fn complete_run(pool: &Pool<MySql>, run_id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        "UPDATE pipeline_runs SET status = 'succeeded', completed_at = NOW() WHERE id = ? AND status = 'running'",
+    )
+    .bind(run_id)
+    .execute(pool)
+    .await
+    .context("Failed to complete pipeline run")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Cancels a run in progress and its unfinished stages. Returns the stages
+/// that were running, whose builds, tasks and deployments may need to be
+/// stopped, or `None` if the run was not in progress.
+pub async fn cancel_run(pool: &Pool<MySql>, run_id: i64, reason: &str) -> anyhow::Result<Option<Vec<PipelineRunStage>>> {
+    let mut tx = pool.begin().await?;
+
+    let running = sqlx::query_scalar::<_, i64>("SELECT id FROM pipeline_runs WHERE id = ? AND status = 'running' FOR UPDATE")
+        .bind(run_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to lock pipeline run")?;
+    if running.is_none() {
+        return Ok(None);
+    }
+
+    let stages = sqlx::query_as::<_, PipelineRunStage>(
+        "SELECT * FROM pipeline_run_stages WHERE run_id = ? AND status = 'running' ORDER BY stage_index ASC",
+    )
+    .bind(run_id)
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to fetch running pipeline run stages")?;
+
+    sqlx::query(
+        r#"UPDATE pipeline_run_stages SET status = 'canceled', completed_at = NOW(), waiting_reason = NULL
+        WHERE run_id = ? AND status IN ('pending', 'running')"#,
+    )
+    .bind(run_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to cancel pipeline run stages")?;
+    sqlx::query("UPDATE pipeline_runs SET status = 'canceled', error_message = ?, completed_at = NOW() WHERE id = ?")
+        .bind(reason)
+        .bind(run_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to cancel pipeline run")?;
+    sqlx::query("INSERT INTO pipeline_run_logs (run_id, log_level, message) VALUES (?, 'warn', ?)")
+        .bind(run_id)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record pipeline run log line")?;
+
+    tx.commit().await?;
+
+    Ok(Some(stages))
+}
+
+/// Resumes a failed or canceled run from the stage that stopped it. The
+/// stages that did not succeed get all of their attempts again.
+pub async fn retry_run(pool: &Pool<MySql>, pipeline_id: i64, run_id: i64, retried_by: i64) -> anyhow::Result<RetryRunOutcome> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("SELECT id FROM pipelines WHERE id = ? FOR UPDATE")
+        .bind(pipeline_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to lock pipeline")?;
+
+    let running = sqlx::query_scalar::<_, i64>(
+        "SELECT id FROM pipeline_runs WHERE pipeline_id = ? AND status = 'running' LIMIT 1",
+    )
+    .bind(pipeline_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to check for running pipeline runs")?;
+    if let Some(other) = running {
+        return Ok(if other == run_id { RetryRunOutcome::NotFinished } else { RetryRunOutcome::OtherRunActive(other) });
+    }
+
+    let result = sqlx::query(
+        r#"UPDATE pipeline_runs SET status = 'running', error_message = NULL, completed_at = NULL
+        WHERE id = ? AND pipeline_id = ? AND status IN ('failed', 'canceled')"#,
+    )
+    .bind(run_id)
+    .bind(pipeline_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to resume pipeline run")?;
+    if result.rows_affected() == 0 {
+        return Ok(RetryRunOutcome::NotFinished);
+    }
+
+    sqlx::query(
+        r#"UPDATE pipeline_run_stages
+        SET status = 'pending', attempts = 0, next_attempt_at = NULL, waiting_reason = NULL,
+            error_message = NULL, started_at = NULL, completed_at = NULL
+        WHERE run_id = ? AND status IN ('failed', 'canceled', 'skipped')"#,
+    )
+    .bind(run_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to reset pipeline run stages")?;
+    sqlx::query("INSERT INTO pipeline_run_logs (run_id, log_level, message) VALUES (?, 'info', ?)")
+        .bind(run_id)
+        .bind(format!("Run retried by user {}", retried_by))
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record pipeline run log line")?;
+
+    tx.commit().await?;
+
+    Ok(RetryRunOutcome::Retried)
+}
+
+/// Adds a line to the log of a run, about one of its stages if `stage_id`
+/// is set.
+pub async fn record_run_log(
+    pool: &Pool<MySql>,
+    run_id: i64,
+    stage_id: Option<i64>,
+    level: &str,
+    message: &str,
+) -> anyhow::Result<()> {
+    sqlx::query("INSERT INTO pipeline_run_logs (run_id, stage_id, log_level, message) VALUES (?, ?, ?, ?)")
+        .bind(run_id)
+        .bind(stage_id)
+        .bind(level)
+        .bind(message)
+        .execute(pool)
+        .await
+        .context("Failed to record pipeline run log line")?;
+
+    Ok(())
+}
+
+/// Retrieves the lines of a run's log recorded after the line with ID
+/// `after_id`, oldest first, optionally only those of one stage.
+pub async fn list_run_logs_after(
+    pool: &Pool<MySql>,
+    run_id: i64,
+    stage_id: Option<i64>,
+    after_id: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<PipelineRunLog>> {
+    let lines = sqlx::query_as::<_, PipelineRunLog>(
+        r#"SELECT * FROM pipeline_run_logs
+        WHERE run_id = ? AND (? IS NULL OR stage_id = ?) AND id > ?
+        ORDER BY id ASC
+        LIMIT ?"#,
+    )
+    .bind(run_id)
+    .bind(stage_id)
+    .bind(stage_id)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch pipeline run log lines")?;
+
+    Ok(lines)
+}
+
+/// Records a user's decision on the approval stage a run is waiting at.
+pub async fn decide_stage(
+    pool: &Pool<MySql>,
+    stage_id: i64,
+    user_id: i64,
+    approve: bool,
+    comment: Option<&str>,
+) -> anyhow::Result<StageDecisionOutcome> {
+    let mut tx = pool.begin().await?;
+
+    let awaiting = sqlx::query_scalar::<_, i64>(
+        r#"SELECT id FROM pipeline_run_stages
+        WHERE id = ? AND stage_type = 'approval' AND status = 'running'
+        FOR UPDATE"#,
+    )
+    .bind(stage_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to lock pipeline run stage")?;
+    if awaiting.is_none() {
+        return Ok(StageDecisionOutcome::NotAwaitingApproval);
+    }
+
+    let decided = sqlx::query_scalar::<_, i64>(
+        "SELECT EXISTS(SELECT 1 FROM pipeline_stage_approvals WHERE stage_id = ? AND user_id = ?)",
+    )
+    .bind(stage_id)
+    .bind(user_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to check for earlier decision")?;
+    if decided != 0 {
+        return Ok(StageDecisionOutcome::AlreadyDecided);
+    }
+
+    sqlx::query("INSERT INTO pipeline_stage_approvals (stage_id, user_id, decision, comment) VALUES (?, ?, ?, ?)")
+        .bind(stage_id)
+        .bind(user_id)
+        .bind(if approve { "approved" } else { "rejected" })
+        .bind(comment)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to record pipeline stage approval")?;
+
+    tx.commit().await?;
+
+    Ok(StageDecisionOutcome::Recorded)
+}
+
+/// Retrieves the approvals and rejections of a stage, oldest first.
+pub async fn list_stage_approvals(pool: &Pool<MySql>, stage_id: i64) -> anyhow::Result<Vec<PipelineStageApproval>> {
+    let approvals = sqlx::query_as::<_, PipelineStageApproval>(
+        "SELECT * FROM pipeline_stage_approvals WHERE stage_id = ? ORDER BY id ASC",
+    )
+    .bind(stage_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch pipeline stage approvals")?;
+
+    Ok(approvals)
+}
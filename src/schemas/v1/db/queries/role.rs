@@ -0,0 +1,359 @@
+// db/queries/role.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// A role as managed through the role management API.
+///
+/// Unlike the shared `Role` type this includes `org_id`: roles either apply
+/// platform-wide (`org_id` is `None`, which includes every system role) or
+/// are custom roles owned by a single organization.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ScopedRole {
+    pub id: i64,
+    pub org_id: Option<i64>,
+    pub name: String,
+    pub description: Option<String>,
+    pub is_system_role: bool,
+    pub scope: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A role bound to a user within a scope (a row of `role_user`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct RoleBinding {
+    pub user_id: i64,
+    pub role_id: i64,
+    pub role_name: String,
+    pub scope_type: String,
+    pub scope_id: i64,
+    pub created_at: DateTime<Utc>,
+}
+
+//=============================================================================
+// Role Operations
+//=============================================================================
+
+/// Retrieves the roles visible to an organization.
+///
+/// Platform-wide roles (including the built-in system roles) are always
+/// returned. When `org_id` is given, the custom roles of that organization
+/// are returned as well.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Organization whose custom roles should be included
+///
+/// # Returns
+///
+/// * `Ok(Vec<ScopedRole>)` - Roles ordered with system roles first, then by name
+/// * `Err(anyhow::Error)` - Failed to fetch roles
+pub async fn list_roles(pool: &Pool<MySql>, org_id: Option<i64>) -> anyhow::Result<Vec<ScopedRole>> {
+    let roles = sqlx::query_as::<_, ScopedRole>(
+        r#"
+        SELECT id, org_id, name, description, is_system_role, scope, created_at, updated_at
+        FROM roles
+        WHERE org_id IS NULL OR org_id = ?
+        ORDER BY is_system_role DESC, name ASC
+        "#,
+    )
+    .bind(org_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch roles")?;
+
+    Ok(roles)
+}
+
+/// Retrieves a specific role by its unique identifier.
+pub async fn get_role(pool: &Pool<MySql>, id: i64) -> anyhow::Result<ScopedRole> {
+    let role = sqlx::query_as::<_, ScopedRole>(
+        "SELECT id, org_id, name, description, is_system_role, scope, created_at, updated_at FROM roles WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch role")?;
+
+    Ok(role)
+}
+
+/// Retrieves one of the built-in system roles by name.
+pub async fn get_system_role_by_name(pool: &Pool<MySql>, name: &str) -> anyhow::Result<ScopedRole> {
+    let role = sqlx::query_as::<_, ScopedRole>(
+        r#"
+        SELECT id, org_id, name, description, is_system_role, scope, created_at, updated_at
+        FROM roles
+        WHERE name = ? AND is_system_role = 1 AND org_id IS NULL
+        "#,
+    )
+    .bind(name)
+    .fetch_one(pool)
+    .await
+    .context("Failed to fetch system role")?;
+
+    Ok(role)
+}
+
+/// Checks whether a role name is already taken within an organization.
+///
+/// Names of platform-wide roles are reserved in every organization, so a
+/// custom role can never shadow a system role.
+pub async fn role_name_exists(pool: &Pool<MySql>, org_id: Option<i64>, name: &str) -> anyhow::Result<bool> {
+    let count = sqlx::query_scalar::<_, i64>(
+        "SELECT COUNT(*) FROM roles WHERE name = ? AND (org_id IS NULL OR org_id = ?)",
+    )
+    .bind(name)
+    .bind(org_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to check role name")?;
+
+    Ok(count > 0)
+}
+
+/// Creates a new custom role.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Owning organization, or `None` for a platform-wide role
+/// * `name` - Name of the role
+/// * `description` - Optional description
+/// * `scope` - Kind of scope the role is meant to be bound at
+///
+/// # Returns
+///
+/// * `Ok(ScopedRole)` - The newly created role
+/// * `Err(anyhow::Error)` - Failed to create the role
+pub async fn create_role(
+    pool: &Pool<MySql>,
+    org_id: Option<i64>,
+    name: &str,
+    description: Option<&str>,
+    scope: &str,
+) -> anyhow::Result<ScopedRole> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        "INSERT INTO roles (org_id, name, description, is_system_role, scope) VALUES (?, ?, ?, 0, ?)",
+    )
+    .bind(org_id)
+    .bind(name)
+    .bind(description)
+    .bind(scope)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create role")?;
+
+    let role = sqlx::query_as::<_, ScopedRole>(
+        "SELECT id, org_id, name, description, is_system_role, scope, created_at, updated_at FROM roles WHERE id = ?",
+    )
+    .bind(result.last_insert_id())
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to fetch created role")?;
+
+    tx.commit().await?;
+    Ok(role)
+}
+
+/// Updates the name and/or description of a custom role.
+///
+/// System roles are never modified; attempting to update one returns an error.
+pub async fn update_role(
+    pool: &Pool<MySql>,
+    id: i64,
+    name: Option<&str>,
+    description: Option<&str>,
+) -> anyhow::Result<ScopedRole> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        UPDATE roles
+        SET name = COALESCE(?, name), description = COALESCE(?, description)
+        WHERE id = ? AND is_system_role = 0
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update role")?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!("Role {} does not exist or is a system role", id);
+    }
+
+    let role = sqlx::query_as::<_, ScopedRole>(
+        "SELECT id, org_id, name, description, is_system_role, scope, created_at, updated_at FROM roles WHERE id = ?",
+    )
+    .bind(id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to fetch updated role")?;
+
+    tx.commit().await?;
+    Ok(role)
+}
+
+/// Deletes a custom role together with its permission and user bindings.
+///
+/// System roles are never deleted; attempting to delete one returns an error.
+pub async fn delete_role(pool: &Pool<MySql>, id: i64) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query("DELETE FROM roles WHERE id = ? AND is_system_role = 0")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete role")?;
+
+    if result.rows_affected() == 0 {
+        anyhow::bail!("Role {} does not exist or is a system role", id);
+    }
+
+    tx.commit().await?;
+    Ok(())
+}
+
+//=============================================================================
+// Role Binding Operations
+//=============================================================================
+
+/// Retrieves role bindings, optionally filtered by user and scope.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `user_id` - Only return bindings of this user
+/// * `scope_type` - Only return bindings of this scope type
+/// * `scope_id` - Only return bindings on this resource
+///
+/// # Returns
+///
+/// * `Ok(Vec<RoleBinding>)` - Matching bindings, newest first
+/// * `Err(anyhow::Error)` - Failed to fetch bindings
+pub async fn list_role_bindings(
+    pool: &Pool<MySql>,
+    user_id: Option<i64>,
+    scope_type: Option<&str>,
+    scope_id: Option<i64>,
+) -> anyhow::Result<Vec<RoleBinding>> {
+    let bindings = sqlx::query_as::<_, RoleBinding>(
+        r#"
+        SELECT ru.user_id, ru.role_id, r.name AS role_name, ru.scope_type, ru.scope_id, ru.created_at
+        FROM role_user ru
+        JOIN roles r ON r.id = ru.role_id
+        WHERE (? IS NULL OR ru.user_id = ?)
+          AND (? IS NULL OR ru.scope_type = ?)
+          AND (? IS NULL OR ru.scope_id = ?)
+        ORDER BY ru.created_at DESC
+        "#,
+    )
+    .bind(user_id)
+    .bind(user_id)
+    .bind(scope_type)
+    .bind(scope_type)
+    .bind(scope_id)
+    .bind(scope_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch role bindings")?;
+
+    Ok(bindings)
+}
+
+/// Looks up the organization that owns the resource a role is bound to.
+///
+/// # Returns
+///
+/// * `Ok(Some(org_id))` - The owning organization of the space or application
+///   (or `scope_id` itself for organization bindings)
+/// * `Ok(None)` - The scope is global or the resource does not exist
+/// * `Err(anyhow::Error)` - Failed to look up the resource
+pub async fn get_binding_scope_org(pool: &Pool<MySql>, scope_type: &str, scope_id: i64) -> anyhow::Result<Option<i64>> {
+    let query = match scope_type {
+        "organization" => "SELECT id FROM orgs WHERE id = ?",
+        "space" => "SELECT org_id FROM spaces WHERE id = ?",
+        "application" => "SELECT org_id FROM apps WHERE id = ?",
+        _ => return Ok(None),
+    };
+
+    let org_id = sqlx::query_scalar::<_, i64>(query)
+        .bind(scope_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to look up binding scope")?;
+
+    Ok(org_id)
+}
+
+/// Binds a role to a user within a scope.
+///
+/// Binding the same role in the same scope twice is a no-op.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `user_id` - User receiving the role
+/// * `role_id` - Role to bind
+/// * `scope_type` - One of `global`, `organization`, `space` or `application`
+/// * `scope_id` - Identifier of the scoped resource (`0` for `global`)
+pub async fn bind_role(
+    pool: &Pool<MySql>,
+    user_id: i64,
+    role_id: i64,
+    scope_type: &str,
+    scope_id: i64,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO role_user (user_id, role_id, scope_type, scope_id)
+        VALUES (?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE updated_at = updated_at
+        "#,
+    )
+    .bind(user_id)
+    .bind(role_id)
+    .bind(scope_type)
+    .bind(scope_id)
+    .execute(pool)
+    .await
+    .context("Failed to bind role to user")?;
+
+    Ok(())
+}
+
+/// Removes a role binding.
+///
+/// # Returns
+///
+/// * `Ok(true)` - The binding existed and was removed
+/// * `Ok(false)` - No such binding existed
+/// * `Err(anyhow::Error)` - Failed to remove the binding
+pub async fn unbind_role(
+    pool: &Pool<MySql>,
+    user_id: i64,
+    role_id: i64,
+    scope_type: &str,
+    scope_id: i64,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        "DELETE FROM role_user WHERE user_id = ? AND role_id = ? AND scope_type = ? AND scope_id = ?",
+    )
+    .bind(user_id)
+    .bind(role_id)
+    .bind(scope_type)
+    .bind(scope_id)
+    .execute(pool)
+    .await
+    .context("Failed to remove role binding")?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,203 @@
+// db/queries/rollback.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+use libomni::types::db::v1 as types;
+use types::deployment::Deployment;
+
+/// A return of an application to the release of an earlier deployment.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Rollback {
+    pub id: i64,
+    pub app_id: i64,
+    /// Deployment that was rolled back
+    pub from_deployment_id: i64,
+    /// Deployment whose release the application returns to
+    pub to_deployment_id: i64,
+    /// Deployment that redeploys the release of `to_deployment_id`
+    pub deployment_id: Option<i64>,
+    pub status: Option<String>,
+    pub reason: Option<String>,
+    pub automatic: Option<bool>,
+    pub trigger_condition: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub rollback_duration: Option<i64>,
+    pub error_message: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// A rollback to record.
+#[derive(Debug, Clone)]
+pub struct NewRollback<'a> {
+    pub app_id: i64,
+    pub from_deployment_id: i64,
+    pub to_deployment_id: i64,
+    /// Strategy of the deployment that redeploys the earlier release
+    pub deployment_strategy: &'a str,
+    pub reason: &'a str,
+    pub automatic: bool,
+    pub trigger_condition: Option<&'a str>,
+    pub created_by: Option<i64>,
+}
+
+/// Retrieves a specific rollback by its unique identifier.
+pub async fn get_rollback_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<Rollback>> {
+    let rollback = sqlx::query_as::<_, Rollback>("SELECT * FROM rollbacks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch rollback")?;
+
+    Ok(rollback)
+}
+
+/// Retrieves the rollbacks of an application, newest first.
+pub async fn list_rollbacks_by_app(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    page: i64,
+    per_page: i64,
+) -> anyhow::Result<Vec<Rollback>> {
+    let rollbacks = sqlx::query_as::<_, Rollback>(
+        "SELECT * FROM rollbacks WHERE app_id = ? ORDER BY created_at DESC, id DESC LIMIT ? OFFSET ?",
+    )
+    .bind(app_id)
+    .bind(per_page)
+    .bind(page * per_page)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch app rollbacks")?;
+
+    Ok(rollbacks)
+}
+
+/// Counts the rollbacks of an application.
+pub async fn count_rollbacks_by_app(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM rollbacks WHERE app_id = ?")
+        .bind(app_id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to count app rollbacks")?;
+
+    Ok(count)
+}
+
+/// Records a rollback together with the pending deployment that carries it
+/// out. The deployment copies the build, version, environment, annotations
+/// and labels of the deployment rolled back to, and is executed like any
+/// other deployment.
+pub async fn create_rollback(pool: &Pool<MySql>, rollback: &NewRollback<'_>) -> anyhow::Result<Rollback> {
+    let mut tx = pool.begin().await?;
+
+    let deployment = sqlx::query(
+        r#"INSERT INTO deployments (
+            app_id, build_id, version, status, deployment_strategy,
+            previous_deployment_id, canary_percentage, environment_variables,
+            annotations, labels, created_at, created_by
+        )
+        SELECT app_id, build_id, version, 'pending', ?, ?, canary_percentage,
+            environment_variables, annotations, labels, CURRENT_TIMESTAMP, ?
+        FROM deployments WHERE id = ?"#,
+    )
+    .bind(rollback.deployment_strategy)
+    .bind(rollback.from_deployment_id)
+    .bind(rollback.created_by)
+    .bind(rollback.to_deployment_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create rollback deployment")?;
+
+    if deployment.rows_affected() == 0 {
+        anyhow::bail!("Deployment {} to roll back to does not exist", rollback.to_deployment_id);
+    }
+
+    let result = sqlx::query(
+        r#"INSERT INTO rollbacks (
+            app_id, from_deployment_id, to_deployment_id, deployment_id, status,
+            reason, automatic, trigger_condition, created_at, created_by
+        ) VALUES (?, ?, ?, ?, 'pending', ?, ?, ?, CURRENT_TIMESTAMP, ?)"#,
+    )
+    .bind(rollback.app_id)
+    .bind(rollback.from_deployment_id)
+    .bind(rollback.to_deployment_id)
+    .bind(deployment.last_insert_id() as i64)
+    .bind(rollback.reason)
+    .bind(rollback.automatic)
+    .bind(rollback.trigger_condition)
+    .bind(rollback.created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create rollback")?;
+
+    let created = sqlx::query_as::<_, Rollback>("SELECT * FROM rollbacks WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created rollback")?;
+
+    tx.commit().await?;
+
+    Ok(created)
+}
+
+/// Checks whether a deployment has been rolled back.
+pub async fn has_rollback_from(pool: &Pool<MySql>, deployment_id: i64) -> anyhow::Result<bool> {
+    let rolled_back = sqlx::query_scalar::<_, i64>(
+        "SELECT EXISTS(SELECT 1 FROM rollbacks WHERE from_deployment_id = ?)",
+    )
+    .bind(deployment_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to check for rollbacks of deployment")?;
+
+    Ok(rolled_back != 0)
+}
+
+/// Retrieves the deployments in their bake window: completed at most
+/// `bake_window_seconds` ago, still the release their application runs, and
+/// neither rolled back nor carrying out a rollback themselves. Deployments
+/// of applications with another deployment pending or in progress are left
+/// alone.
+pub async fn list_baking_deployments(pool: &Pool<MySql>, bake_window_seconds: u64) -> anyhow::Result<Vec<Deployment>> {
+    let deployments = sqlx::query_as::<_, Deployment>(
+        r#"SELECT d.* FROM deployments d
+        WHERE d.status = 'deployed'
+          AND d.previous_deployment_id IS NOT NULL
+          AND d.completed_at >= NOW() - INTERVAL ? SECOND
+          AND NOT EXISTS (
+              SELECT 1 FROM deployments n
+              WHERE n.app_id = d.app_id AND n.id <> d.id
+                AND (n.status IN ('pending', 'in_progress')
+                     OR (n.status = 'deployed' AND (n.completed_at > d.completed_at
+                         OR (n.completed_at = d.completed_at AND n.id > d.id)))))
+          AND NOT EXISTS (SELECT 1 FROM rollbacks r WHERE r.from_deployment_id = d.id OR r.deployment_id = d.id)
+        ORDER BY d.id ASC"#,
+    )
+    .bind(bake_window_seconds)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch baking deployments")?;
+
+    Ok(deployments)
+}
+
+/// Counts the live instances of an application that crashed or whose health
+/// check fails. Once a deployment completed, all of them run its release.
+pub async fn count_failing_instances(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>(
+        r#"SELECT COUNT(*) FROM instances
+        WHERE app_id = ?
+          AND status NOT IN ('stopping', 'stopped', 'terminated')
+          AND (status = 'crashed' OR health_status = 'critical')"#,
+    )
+    .bind(app_id)
+    .fetch_one(pool)
+    .await
+    .context("Failed to count failing instances of app")?;
+
+    Ok(count)
+}
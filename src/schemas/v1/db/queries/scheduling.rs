@@ -0,0 +1,334 @@
+// db/queries/scheduling.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, MySql, Pool};
+
+use libomni::types::db::v1 as types;
+use types::instance::Instance;
+use types::worker::Worker;
+
+/// Placement rules for the instances of an application.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AppSchedulingPolicy {
+    pub app_id: i64,
+    pub strategy: Option<String>,
+    pub anti_affinity: String,
+    pub node_selector: Option<Value>,
+    pub tolerations: Option<Value>,
+    pub updated_by: Option<i64>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl AppSchedulingPolicy {
+    /// The policy of an application that never configured one.
+    pub fn default_for(app_id: i64) -> Self {
+        Self {
+            app_id,
+            strategy: None,
+            anti_affinity: "preferred".to_string(),
+            node_selector: None,
+            tolerations: None,
+            updated_by: None,
+            created_at: None,
+            updated_at: None,
+        }
+    }
+}
+
+/// A resource size instances can be given, from the `allocations` table.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Allocation {
+    pub id: i64,
+    pub name: String,
+    pub cpu: f64,
+    pub memory: f64,
+    pub uplink: f64,
+    pub downlink: f64,
+    pub disk: f64,
+}
+
+/// Capacity reserved on a worker for one instance.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Reservation {
+    pub cpu: f64,
+    pub memory_mb: f64,
+    pub disk_mb: f64,
+}
+
+/// Retrieves the scheduling policy of an application, if it has one.
+pub async fn get_app_scheduling_policy(
+    pool: &Pool<MySql>,
+    app_id: i64,
+) -> anyhow::Result<Option<AppSchedulingPolicy>> {
+    let policy = sqlx::query_as::<_, AppSchedulingPolicy>(
+        "SELECT * FROM app_scheduling_policies WHERE app_id = ?",
+    )
+    .bind(app_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch app scheduling policy")?;
+
+    Ok(policy)
+}
+
+/// Creates or replaces the scheduling policy of an application.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `app_id` - Application the policy applies to
+/// * `strategy` - `binpack` or `spread`, or `None` for the platform default
+/// * `anti_affinity` - `none`, `preferred` or `required`
+/// * `node_selector` - Worker labels instances require, as an object
+/// * `tolerations` - Worker taints instances tolerate, as an array
+/// * `updated_by` - User making the change
+pub async fn set_app_scheduling_policy(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    strategy: Option<&str>,
+    anti_affinity: &str,
+    node_selector: Option<&Value>,
+    tolerations: Option<&Value>,
+    updated_by: Option<i64>,
+) -> anyhow::Result<AppSchedulingPolicy> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO app_scheduling_policies
+            (app_id, strategy, anti_affinity, node_selector, tolerations, updated_by)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            strategy = VALUES(strategy),
+            anti_affinity = VALUES(anti_affinity),
+            node_selector = VALUES(node_selector),
+            tolerations = VALUES(tolerations),
+            updated_by = VALUES(updated_by)
+        "#,
+    )
+    .bind(app_id)
+    .bind(strategy)
+    .bind(anti_affinity)
+    .bind(node_selector)
+    .bind(tolerations)
+    .bind(updated_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to save app scheduling policy")?;
+
+    let policy = sqlx::query_as::<_, AppSchedulingPolicy>(
+        "SELECT * FROM app_scheduling_policies WHERE app_id = ?",
+    )
+    .bind(app_id)
+    .fetch_one(&mut *tx)
+    .await
+    .context("Failed to fetch saved app scheduling policy")?;
+
+    tx.commit().await?;
+    Ok(policy)
+}
+
+/// Retrieves an allocation by its ID.
+pub async fn get_allocation(pool: &Pool<MySql>, allocation_id: i64) -> anyhow::Result<Option<Allocation>> {
+    let allocation = sqlx::query_as::<_, Allocation>(
+        "SELECT id, name, cpu, memory, uplink, downlink, disk FROM allocations WHERE id = ?",
+    )
+    .bind(allocation_id)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch allocation")?;
+
+    Ok(allocation)
+}
+
+/// Retrieves every worker of the platform, whatever its status, so the
+/// scheduler can explain why it passed over the ones it did not pick.
+pub async fn list_all_workers(pool: &Pool<MySql>) -> anyhow::Result<Vec<Worker>> {
+    let workers = sqlx::query_as::<_, Worker>("SELECT * FROM workers ORDER BY id")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch workers")?;
+
+    Ok(workers)
+}
+
+/// Counts the instances of an application on each worker, ignoring
+/// instances that have been stopped or terminated.
+pub async fn count_app_instances_by_worker(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Vec<(i64, i64)>> {
+    let counts = sqlx::query_as::<_, (i64, i64)>(
+        r#"
+        SELECT node_id, COUNT(*) FROM instances
+        WHERE app_id = ? AND node_id IS NOT NULL AND status NOT IN ('stopped', 'terminated')
+        GROUP BY node_id
+        "#,
+    )
+    .bind(app_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to count app instances per worker")?;
+
+    Ok(counts)
+}
+
+/// Places an instance on a worker, reserving the capacity it needs.
+///
+/// Both updates are guarded, so the placement only happens if the worker is
+/// still active with enough capacity left and the instance has not been
+/// placed in the meantime. Returns whether the instance was placed.
+pub async fn place_instance(
+    pool: &Pool<MySql>,
+    instance_id: i64,
+    worker_id: i64,
+    reservation: &Reservation,
+    metadata: &Value,
+) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let reserved = sqlx::query(
+        r#"
+        UPDATE workers
+        SET cpu_available = cpu_available - ?,
+            memory_available = memory_available - ?,
+            disk_available = disk_available - ?
+        WHERE id = ? AND status = 'active'
+          AND cpu_available >= ? AND memory_available >= ? AND disk_available >= ?
+        "#,
+    )
+    .bind(reservation.cpu)
+    .bind(reservation.memory_mb)
+    .bind(reservation.disk_mb)
+    .bind(worker_id)
+    .bind(reservation.cpu)
+    .bind(reservation.memory_mb)
+    .bind(reservation.disk_mb)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to reserve worker capacity")?;
+
+    if reserved.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    let placed = sqlx::query(
+        "UPDATE instances SET node_id = ?, scheduler_metadata = ? WHERE id = ? AND node_id IS NULL",
+    )
+    .bind(worker_id)
+    .bind(metadata)
+    .bind(instance_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to place instance")?;
+
+    if placed.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(false);
+    }
+
+    tx.commit().await?;
+    Ok(true)
+}
+
+/// Records a scheduling decision that did not place the instance.
+pub async fn record_scheduling_decision(
+    pool: &Pool<MySql>,
+    instance_id: i64,
+    metadata: &Value,
+) -> anyhow::Result<()> {
+    sqlx::query("UPDATE instances SET scheduler_metadata = ? WHERE id = ? AND node_id IS NULL")
+        .bind(metadata)
+        .bind(instance_id)
+        .execute(pool)
+        .await
+        .context("Failed to record scheduling decision")?;
+
+    Ok(())
+}
+
+/// Retrieves stopped and terminated instances that still hold capacity on
+/// their worker.
+pub async fn list_unreleased_reservations(pool: &Pool<MySql>) -> anyhow::Result<Vec<Instance>> {
+    let instances = sqlx::query_as::<_, Instance>(
+        r#"
+        SELECT * FROM instances
+        WHERE status IN ('stopped', 'terminated') AND node_id IS NOT NULL
+          AND JSON_UNQUOTE(JSON_EXTRACT(scheduler_metadata, '$.reservation.released')) = 'false'
+        ORDER BY id
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch unreleased reservations")?;
+
+    Ok(instances)
+}
+
+/// Returns the capacity an instance reserved to its worker. Returns whether
+/// anything was released; a reservation is only ever released once.
+pub async fn release_reservation(pool: &Pool<MySql>, instance_id: i64) -> anyhow::Result<bool> {
+    let mut tx = pool.begin().await?;
+
+    let row = sqlx::query_as::<_, (Option<i64>, Option<Value>)>(
+        "SELECT node_id, scheduler_metadata FROM instances WHERE id = ? FOR UPDATE",
+    )
+    .bind(instance_id)
+    .fetch_optional(&mut *tx)
+    .await
+    .context("Failed to fetch instance reservation")?;
+
+    let (worker_id, reservation) = match row {
+        Some((Some(worker_id), Some(metadata))) => {
+            let reservation = metadata.get("reservation");
+            let released = reservation.and_then(|r| r.get("released")).and_then(Value::as_bool);
+            match (released, reservation.cloned().map(serde_json::from_value::<Reservation>)) {
+                (Some(false), Some(Ok(reservation))) => (worker_id, reservation),
+                _ => {
+                    tx.rollback().await?;
+                    return Ok(false);
+                }
+            }
+        }
+        _ => {
+            tx.rollback().await?;
+            return Ok(false);
+        }
+    };
+
+    sqlx::query(
+        r#"
+        UPDATE workers
+        SET cpu_available = LEAST(cpu_total, cpu_available + ?),
+            memory_available = LEAST(memory_total, memory_available + ?),
+            disk_available = LEAST(disk_total, disk_available + ?)
+        WHERE id = ?
+        "#,
+    )
+    .bind(reservation.cpu)
+    .bind(reservation.memory_mb)
+    .bind(reservation.disk_mb)
+    .bind(worker_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to release worker capacity")?;
+
+    sqlx::query(
+        r#"
+        UPDATE instances
+        SET scheduler_metadata = JSON_SET(scheduler_metadata,
+            '$.reservation.released', CAST('true' AS JSON),
+            '$.reservation.released_at', ?)
+        WHERE id = ?
+        "#,
+    )
+    .bind(Utc::now().to_rfc3339())
+    .bind(instance_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to mark reservation released")?;
+
+    tx.commit().await?;
+    Ok(true)
+}
@@ -0,0 +1,347 @@
+// db/queries/space.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+use libomni::types::db::v1 as types;
+use types::app::App;
+
+/// A space: a named partition of an organization's applications, typically
+/// used to separate environments such as dev, staging and prod.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Space {
+    pub id: i64,
+    pub org_id: i64,
+    pub name: String,
+    pub description: Option<String>,
+    pub status: String,
+    pub isolation_segment: Option<String>,
+    pub network_isolation: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub deleted_at: Option<DateTime<Utc>>,
+}
+
+impl Space {
+    /// Whether the space has been archived. Archived spaces keep their
+    /// applications stopped and accept no new ones.
+    pub fn is_archived(&self) -> bool {
+        self.status == "archived"
+    }
+}
+
+/// Retrieves the spaces of an organization, ordered by name.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Organization whose spaces should be listed
+/// * `include_archived` - Whether archived spaces are included
+///
+/// # Returns
+///
+/// * `Ok(Vec<Space>)` - The organization's spaces
+/// * `Err(anyhow::Error)` - Failed to fetch spaces
+pub async fn list_spaces(pool: &Pool<MySql>, org_id: i64, include_archived: bool) -> anyhow::Result<Vec<Space>> {
+    let spaces = sqlx::query_as::<_, Space>(
+        r#"
+        SELECT * FROM spaces
+        WHERE org_id = ? AND deleted_at IS NULL AND (? OR status <> 'archived')
+        ORDER BY name ASC
+        "#,
+    )
+    .bind(org_id)
+    .bind(include_archived)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch spaces")?;
+
+    Ok(spaces)
+}
+
+/// Retrieves a specific space by its unique identifier.
+pub async fn get_space_by_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Space> {
+    let space = sqlx::query_as::<_, Space>("SELECT * FROM spaces WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to fetch space")?;
+
+    Ok(space)
+}
+
+/// Retrieves the organization a space belongs to.
+///
+/// # Returns
+///
+/// * `Ok(Some(org_id))` - The owning organization
+/// * `Ok(None)` - No such space exists
+/// * `Err(anyhow::Error)` - Failed to look up the space
+pub async fn get_space_org_id(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<i64>> {
+    let org_id = sqlx::query_scalar::<_, i64>("SELECT org_id FROM spaces WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch space organization")?;
+
+    Ok(org_id)
+}
+
+/// Creates a new space in an organization.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `org_id` - Organization the space belongs to
+/// * `name` - Name of the space, unique within the organization
+/// * `description` - Optional description
+/// * `isolation_segment` - Optional isolation segment the space's apps are placed in
+/// * `network_isolation` - Whether the space's apps are network isolated from other spaces
+///
+/// # Returns
+///
+/// * `Ok(Space)` - The newly created space
+/// * `Err(anyhow::Error)` - Failed to create the space
+pub async fn create_space(
+    pool: &Pool<MySql>,
+    org_id: i64,
+    name: &str,
+    description: Option<&str>,
+    isolation_segment: Option<&str>,
+    network_isolation: bool,
+) -> anyhow::Result<Space> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO spaces (org_id, name, description, isolation_segment, network_isolation)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(org_id)
+    .bind(name)
+    .bind(description)
+    .bind(isolation_segment)
+    .bind(network_isolation)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create space")?;
+
+    let space = sqlx::query_as::<_, Space>("SELECT * FROM spaces WHERE id = ?")
+        .bind(result.last_insert_id())
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created space")?;
+
+    tx.commit().await?;
+    Ok(space)
+}
+
+/// Updates a space. Only the fields that are given are changed.
+pub async fn update_space(
+    pool: &Pool<MySql>,
+    id: i64,
+    name: Option<&str>,
+    description: Option<&str>,
+    isolation_segment: Option<&str>,
+    network_isolation: Option<bool>,
+) -> anyhow::Result<Space> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE spaces
+        SET name = COALESCE(?, name),
+            description = COALESCE(?, description),
+            isolation_segment = COALESCE(?, isolation_segment),
+            network_isolation = COALESCE(?, network_isolation)
+        WHERE id = ? AND deleted_at IS NULL
+        "#,
+    )
+    .bind(name)
+    .bind(description)
+    .bind(isolation_segment)
+    .bind(network_isolation)
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update space")?;
+
+    let space = sqlx::query_as::<_, Space>("SELECT * FROM spaces WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch updated space")?;
+
+    tx.commit().await?;
+    Ok(space)
+}
+
+/// Deletes an empty space together with the role bindings scoped to it.
+///
+/// Spaces that still contain applications are not deleted; the function
+/// returns an error instead so that applications are never orphaned.
+pub async fn delete_space(pool: &Pool<MySql>, id: i64) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    let app_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM apps WHERE space_id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to count space applications")?;
+
+    if app_count > 0 {
+        anyhow::bail!("Space {} still contains {} application(s)", id, app_count);
+    }
+
+    sqlx::query("DELETE FROM role_user WHERE scope_type = 'space' AND scope_id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete space role bindings")?;
+
+    sqlx::query("DELETE FROM spaces WHERE id = ?")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to delete space")?;
+
+    tx.commit().await?;
+    Ok(())
+}
+
+/// Archives a space and stops every application in it.
+///
+/// Running, starting and crashed applications are marked `stopped` so that
+/// their instances are shut down, and every application in the space that is
+/// not in maintenance mode yet is put into it, and flagged as frozen by the
+/// space, until the space is restored.
+///
+/// # Returns
+///
+/// * `Ok((Space, u64))` - The archived space and the number of applications stopped
+/// * `Err(anyhow::Error)` - Failed to archive the space
+pub async fn archive_space(pool: &Pool<MySql>, id: i64) -> anyhow::Result<(Space, u64)> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE spaces SET status = 'archived' WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to archive space")?;
+
+    let stopped = sqlx::query(
+        r#"
+        UPDATE apps
+        SET status = 'stopped', updated_at = CURRENT_TIMESTAMP
+        WHERE space_id = ? AND status IN ('started', 'starting', 'crashed', 'staged')
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to stop space applications")?
+    .rows_affected();
+
+    // Apps already in maintenance mode are left alone, so restoring the space
+    // does not take them out of it.
+    sqlx::query(
+        r#"
+        UPDATE apps
+        SET maintenance_mode = 1, frozen_by_space = 1
+        WHERE space_id = ? AND (maintenance_mode IS NULL OR maintenance_mode = 0)
+        "#,
+    )
+    .bind(id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to freeze space applications")?;
+
+    let space = sqlx::query_as::<_, Space>("SELECT * FROM spaces WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch archived space")?;
+
+    tx.commit().await?;
+    Ok((space, stopped))
+}
+
+/// Restores an archived space.
+///
+/// The applications frozen by archiving the space leave maintenance mode but
+/// stay stopped; they have to be started again explicitly. Applications that
+/// were already in maintenance mode before the archive stay in it.
+pub async fn restore_space(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Space> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE spaces SET status = 'active' WHERE id = ? AND deleted_at IS NULL")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to restore space")?;
+
+    sqlx::query("UPDATE apps SET maintenance_mode = 0, frozen_by_space = 0 WHERE space_id = ? AND frozen_by_space = 1")
+        .bind(id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to unfreeze space applications")?;
+
+    let space = sqlx::query_as::<_, Space>("SELECT * FROM spaces WHERE id = ?")
+        .bind(id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch restored space")?;
+
+    tx.commit().await?;
+    Ok(space)
+}
+
+/// Retrieves a paginated list of the applications in a space.
+pub async fn list_space_apps(pool: &Pool<MySql>, space_id: i64, page: i64, per_page: i64) -> anyhow::Result<Vec<App>> {
+    let apps = sqlx::query_as::<_, App>(
+        "SELECT * FROM apps WHERE space_id = ? ORDER BY name ASC LIMIT ? OFFSET ?",
+    )
+    .bind(space_id)
+    .bind(per_page)
+    .bind(page * per_page)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch space applications")?;
+
+    Ok(apps)
+}
+
+/// Counts the applications in a space.
+pub async fn count_space_apps(pool: &Pool<MySql>, space_id: i64) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM apps WHERE space_id = ?")
+        .bind(space_id)
+        .fetch_one(pool)
+        .await
+        .context("Failed to count space applications")?;
+
+    Ok(count)
+}
+
+/// Moves an application into a space, or out of any space when `space_id`
+/// is `None`.
+pub async fn move_app_to_space(pool: &Pool<MySql>, app_id: i64, space_id: Option<i64>) -> anyhow::Result<App> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query("UPDATE apps SET space_id = ?, updated_at = CURRENT_TIMESTAMP WHERE id = ?")
+        .bind(space_id)
+        .bind(app_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to move application")?;
+
+    let app = sqlx::query_as::<_, App>("SELECT * FROM apps WHERE id = ?")
+        .bind(app_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch moved application")?;
+
+    tx.commit().await?;
+    Ok(app)
+}
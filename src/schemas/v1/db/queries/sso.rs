@@ -0,0 +1,274 @@
+// db/queries/sso.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+use libomni::types::db::v1 as types;
+use types::user::User;
+
+/// A login that has been sent to the identity provider and not yet
+/// completed (a row of `oidc_login_states`).
+#[derive(Debug, Clone, FromRow)]
+pub struct OidcLoginState {
+    pub state: String,
+    pub nonce: String,
+    pub code_verifier: String,
+    pub redirect_to: Option<String>,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// An external identity linked to a user (a row of `user_identities`).
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct UserIdentity {
+    pub id: i64,
+    pub user_id: i64,
+    pub issuer: String,
+    pub subject: String,
+    pub email: Option<String>,
+    pub idp_groups: Option<serde_json::Value>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+//=============================================================================
+// Login State Operations
+//=============================================================================
+
+/// Stores the state of a login that is being handed to the identity provider.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `state` - Opaque `state` parameter sent to the provider
+/// * `nonce` - Nonce the ID token has to echo back
+/// * `code_verifier` - PKCE code verifier for the token exchange
+/// * `redirect_to` - Optional location to send the user to after login
+/// * `expires_at` - Time after which the login can no longer be completed
+pub async fn create_oidc_login_state(
+    pool: &Pool<MySql>,
+    state: &str,
+    nonce: &str,
+    code_verifier: &str,
+    redirect_to: Option<&str>,
+    expires_at: DateTime<Utc>,
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO oidc_login_states (state, nonce, code_verifier, redirect_to, expires_at)
+        VALUES (?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(state)
+    .bind(nonce)
+    .bind(code_verifier)
+    .bind(redirect_to)
+    .bind(expires_at)
+    .execute(pool)
+    .await
+    .context("Failed to store OIDC login state")?;
+
+    Ok(())
+}
+
+/// Retrieves and deletes a pending login.
+///
+/// Each state can only be taken once, so a replayed callback never finds
+/// it. Expired states are deleted as well but not returned.
+///
+/// # Returns
+///
+/// * `Ok(Some(OidcLoginState))` - The pending login
+/// * `Ok(None)` - No such login exists or it has expired
+/// * `Err(anyhow::Error)` - Failed to look up the login
+pub async fn take_oidc_login_state(pool: &Pool<MySql>, state: &str) -> anyhow::Result<Option<OidcLoginState>> {
+    let mut tx = pool.begin().await?;
+
+    let login = sqlx::query_as::<_, OidcLoginState>("SELECT * FROM oidc_login_states WHERE state = ? FOR UPDATE")
+        .bind(state)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to fetch OIDC login state")?;
+
+    if login.is_some() {
+        sqlx::query("DELETE FROM oidc_login_states WHERE state = ?")
+            .bind(state)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to delete OIDC login state")?;
+    }
+
+    tx.commit().await?;
+    Ok(login.filter(|login| login.expires_at > Utc::now()))
+}
+
+/// Deletes pending logins that have expired without being completed.
+pub async fn purge_expired_oidc_login_states(pool: &Pool<MySql>) -> anyhow::Result<u64> {
+    let result = sqlx::query("DELETE FROM oidc_login_states WHERE expires_at < ?")
+        .bind(Utc::now())
+        .execute(pool)
+        .await
+        .context("Failed to purge expired OIDC login states")?;
+
+    Ok(result.rows_affected())
+}
+
+//=============================================================================
+// Identity Operations
+//=============================================================================
+
+/// Retrieves the identity with the given issuer and subject.
+pub async fn get_user_identity(
+    pool: &Pool<MySql>,
+    issuer: &str,
+    subject: &str,
+) -> anyhow::Result<Option<UserIdentity>> {
+    let identity = sqlx::query_as::<_, UserIdentity>(
+        "SELECT * FROM user_identities WHERE issuer = ? AND subject = ?",
+    )
+    .bind(issuer)
+    .bind(subject)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch user identity")?;
+
+    Ok(identity)
+}
+
+/// Links an external identity to an existing user and records a login.
+///
+/// Linking an identity that is already linked refreshes its email and groups.
+pub async fn link_user_identity(
+    pool: &Pool<MySql>,
+    user_id: i64,
+    issuer: &str,
+    subject: &str,
+    email: Option<&str>,
+    groups: &[String],
+) -> anyhow::Result<()> {
+    sqlx::query(
+        r#"
+        INSERT INTO user_identities (user_id, issuer, subject, email, idp_groups, last_login_at)
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        ON DUPLICATE KEY UPDATE
+            email = VALUES(email),
+            idp_groups = VALUES(idp_groups),
+            last_login_at = CURRENT_TIMESTAMP
+        "#,
+    )
+    .bind(user_id)
+    .bind(issuer)
+    .bind(subject)
+    .bind(email)
+    .bind(serde_json::json!(groups))
+    .execute(pool)
+    .await
+    .context("Failed to link user identity")?;
+
+    Ok(())
+}
+
+/// Creates a user for an identity signing in for the first time.
+///
+/// The user, its `user_meta` and `user_pii` records and the identity link are
+/// created in one transaction. SSO users are active straight away; their
+/// password is random and never disclosed, so they can only sign in through
+/// the identity provider until they set one.
+///
+/// # Arguments
+///
+/// * `pool` - Database connection pool for executing the query
+/// * `email` - Email address from the ID token
+/// * `email_verified` - Whether the provider vouches for the email address
+/// * `password` - Pre-hashed random password
+/// * `salt` - Salt of the password
+/// * `first_name` - Optional given name from the ID token
+/// * `last_name` - Optional family name from the ID token
+/// * `full_name` - Optional display name from the ID token
+/// * `issuer` - Issuer of the identity
+/// * `subject` - Subject of the identity at the issuer
+/// * `groups` - Groups reported by the provider
+///
+/// # Returns
+///
+/// * `Ok(User)` - The newly created user
+/// * `Err(anyhow::Error)` - Failed to create the user
+pub async fn create_sso_user(
+    pool: &Pool<MySql>,
+    email: &str,
+    email_verified: bool,
+    password: &str,
+    salt: &str,
+    first_name: Option<&str>,
+    last_name: Option<&str>,
+    full_name: Option<&str>,
+    issuer: &str,
+    subject: &str,
+    groups: &[String],
+) -> anyhow::Result<User> {
+    let mut tx = pool.begin().await?;
+
+    let user_id = sqlx::query(
+        r#"
+        INSERT INTO users (
+            email, password, salt, email_verified, active, status,
+            two_factor_enabled, two_factor_verified, login_attempts, last_login_at
+        ) VALUES (?, ?, ?, ?, 1, 'active', 0, 0, 0, CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(email)
+    .bind(password)
+    .bind(salt)
+    .bind(email_verified)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create user")?
+    .last_insert_id();
+
+    sqlx::query(
+        r#"INSERT INTO user_meta (user_id, timezone, language, theme, onboarding_completed)
+           VALUES (?, 'UTC', 'en', 'light', 0)"#,
+    )
+    .bind(user_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create user metadata")?;
+
+    sqlx::query(
+        r#"INSERT INTO user_pii (user_id, first_name, last_name, full_name, identity_verified)
+           VALUES (?, ?, ?, ?, 0)"#,
+    )
+    .bind(user_id)
+    .bind(first_name)
+    .bind(last_name)
+    .bind(full_name)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create user PII record")?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO user_identities (user_id, issuer, subject, email, idp_groups, last_login_at)
+        VALUES (?, ?, ?, ?, ?, CURRENT_TIMESTAMP)
+        "#,
+    )
+    .bind(user_id)
+    .bind(issuer)
+    .bind(subject)
+    .bind(email)
+    .bind(serde_json::json!(groups))
+    .execute(&mut *tx)
+    .await
+    .context("Failed to link user identity")?;
+
+    let user = sqlx::query_as::<_, User>("SELECT * FROM users WHERE id = ?")
+        .bind(user_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created user")?;
+
+    tx.commit().await?;
+    Ok(user)
+}
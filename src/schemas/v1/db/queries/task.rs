@@ -0,0 +1,256 @@
+// db/queries/task.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// A one-off command run against an application.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Task {
+    pub id: i64,
+    pub app_id: i64,
+    pub command: String,
+    pub name: Option<String>,
+    /// 'pending', 'running', 'succeeded', 'failed' or 'canceled'
+    pub status: Option<String>,
+    pub memory_in_mb: Option<i64>,
+    pub disk_in_mb: Option<i64>,
+    pub cpu: Option<f64>,
+    pub timeout_seconds: Option<i64>,
+    pub result: Option<String>,
+    pub exit_code: Option<i64>,
+    pub sequence_id: Option<i64>,
+    pub node_id: Option<i64>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub duration: Option<i64>,
+    pub error_message: Option<String>,
+    /// Deployment whose release the task ran
+    pub deployment_id: Option<i64>,
+    /// Schedule that created the task, if any
+    pub schedule_id: Option<i64>,
+    /// Container running the task, until it is removed
+    pub container_id: Option<String>,
+    /// Lines of container output already copied to `task_logs`
+    #[serde(skip)]
+    pub output_lines: i32,
+    pub created_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The settable fields of a new task.
+#[derive(Debug, Clone)]
+pub struct NewTask {
+    pub command: String,
+    pub name: Option<String>,
+    pub memory_in_mb: Option<i64>,
+    pub disk_in_mb: Option<i64>,
+    pub cpu: Option<f64>,
+    /// Seconds the task may run; the column default if unset
+    pub timeout_seconds: Option<i64>,
+    /// Schedule creating the task, if any
+    pub schedule_id: Option<i64>,
+}
+
+/// Whether a task status is final.
+pub fn is_finished(status: Option<&str>) -> bool {
+    matches!(status, Some("succeeded" | "failed" | "canceled"))
+}
+
+/// Retrieves a page of an application's tasks, newest first, optionally
+/// only those with the given status.
+pub async fn list_app_tasks_paginated(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    status: Option<&str>,
+    per_page: i64,
+    offset: i64,
+) -> anyhow::Result<Vec<Task>> {
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"SELECT * FROM tasks
+        WHERE app_id = ? AND (? IS NULL OR status = ?)
+        ORDER BY id DESC
+        LIMIT ? OFFSET ?"#,
+    )
+    .bind(app_id)
+    .bind(status)
+    .bind(status)
+    .bind(per_page)
+    .bind(offset)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch tasks")?;
+
+    Ok(tasks)
+}
+
+/// Counts an application's tasks, optionally only those with the given
+/// status.
+pub async fn count_app_tasks(pool: &Pool<MySql>, app_id: i64, status: Option<&str>) -> anyhow::Result<i64> {
+    let count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*) FROM tasks WHERE app_id = ? AND (? IS NULL OR status = ?)")
+        .bind(app_id)
+        .bind(status)
+        .bind(status)
+        .fetch_one(pool)
+        .await
+        .context("Failed to count tasks")?;
+
+    Ok(count)
+}
+
+/// Retrieves every task with the given status, oldest first.
+pub async fn list_tasks_with_status(pool: &Pool<MySql>, status: &str) -> anyhow::Result<Vec<Task>> {
+    let tasks = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE status = ? ORDER BY id ASC")
+        .bind(status)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch tasks")?;
+
+    Ok(tasks)
+}
+
+/// Retrieves the finished tasks whose container has not been removed yet,
+/// such as tasks canceled while running.
+pub async fn list_finished_tasks_with_containers(pool: &Pool<MySql>) -> anyhow::Result<Vec<Task>> {
+    let tasks = sqlx::query_as::<_, Task>(
+        r#"SELECT * FROM tasks
+        WHERE container_id IS NOT NULL AND status IN ('succeeded', 'failed', 'canceled')
+        ORDER BY id ASC"#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch tasks")?;
+
+    Ok(tasks)
+}
+
+/// Moves a pending task to 'running' before its container is launched.
+/// Returns `false` if it was no longer pending.
+pub async fn start_task(pool: &Pool<MySql>, id: i64, deployment_id: Option<i64>) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE tasks
+        SET status = 'running', started_at = NOW(), deployment_id = ?
+        WHERE id = ? AND status = 'pending'"#,
+    )
+    .bind(deployment_id)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to start task")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records the container a task runs in, or clears it once the container
+/// has been removed.
+pub async fn set_task_container(pool: &Pool<MySql>, id: i64, container_id: Option<&str>) -> anyhow::Result<()> {
+    sqlx::query("UPDATE tasks SET container_id = ? WHERE id = ?")
+        .bind(container_id)
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to update task container")?;
+
+    Ok(())
+}
+
+/// Records the outcome of a running task. Returns `false` if it was no
+/// longer running, such as when it was canceled in the meantime.
+pub async fn finish_task(
+    pool: &Pool<MySql>,
+    id: i64,
+    status: &str,
+    exit_code: Option<i64>,
+    error_message: Option<&str>,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE tasks
+        SET status = ?, exit_code = ?, error_message = ?, completed_at = NOW(),
+            duration = TIMESTAMPDIFF(SECOND, started_at, NOW())
+        WHERE id = ? AND status = 'running'"#,
+    )
+    .bind(status)
+    .bind(exit_code)
+    .bind(error_message)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to finish task")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Retrieves a task.
+pub async fn get_task(pool: &Pool<MySql>, id: i64) -> anyhow::Result<Option<Task>> {
+    let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch task")?;
+
+    Ok(task)
+}
+
+/// Creates a pending task of an application. Tasks of an application are
+/// numbered in the order they are created.
+pub async fn create_task(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    task: &NewTask,
+    created_by: Option<i64>,
+) -> anyhow::Result<Task> {
+    let mut tx = pool.begin().await?;
+
+    // Lock the application so concurrent tasks get distinct sequence numbers
+    sqlx::query("SELECT id FROM apps WHERE id = ? FOR UPDATE")
+        .bind(app_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to lock app")?;
+
+    let result = sqlx::query(
+        r#"INSERT INTO tasks (app_id, command, name, status, memory_in_mb, disk_in_mb, cpu, timeout_seconds, schedule_id, sequence_id, created_by)
+        SELECT ?, ?, ?, 'pending', ?, ?, ?, COALESCE(?, 3600), ?, COALESCE(MAX(sequence_id), 0) + 1, ?
+        FROM tasks WHERE app_id = ?"#,
+    )
+    .bind(app_id)
+    .bind(&task.command)
+    .bind(&task.name)
+    .bind(task.memory_in_mb)
+    .bind(task.disk_in_mb)
+    .bind(task.cpu)
+    .bind(task.timeout_seconds)
+    .bind(task.schedule_id)
+    .bind(created_by)
+    .bind(app_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create task")?;
+
+    let task = sqlx::query_as::<_, Task>("SELECT * FROM tasks WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created task")?;
+
+    tx.commit().await?;
+
+    Ok(task)
+}
+
+/// Cancels a task that has not finished. Returns `false` if it had.
+pub async fn cancel_task(pool: &Pool<MySql>, id: i64, reason: &str) -> anyhow::Result<bool> {
+    let result = sqlx::query(
+        r#"UPDATE tasks
+        SET status = 'canceled', error_message = ?, completed_at = NOW(),
+            duration = TIMESTAMPDIFF(SECOND, started_at, NOW())
+        WHERE id = ? AND status IN ('pending', 'running')"#,
+    )
+    .bind(reason)
+    .bind(id)
+    .execute(pool)
+    .await
+    .context("Failed to cancel task")?;
+
+    Ok(result.rows_affected() > 0)
+}
@@ -0,0 +1,104 @@
+// db/queries/task_log.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool, QueryBuilder};
+
+/// A line of a task's output.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaskLogLine {
+    pub id: i64,
+    pub task_id: i64,
+    /// 'stdout' or 'stderr' for the container's output, 'system' for lines
+    /// recorded by the orchestrator, such as the task timing out
+    pub stream: String,
+    pub message: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// A line of container output, to be recorded with [`append_task_output`].
+#[derive(Debug, Clone)]
+pub struct NewTaskLogLine {
+    /// 'stdout', 'stderr' or 'system'
+    pub stream: String,
+    pub message: String,
+    /// When the container wrote the line; the time it is recorded if unset
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Records lines of a task's container output and how many lines of that
+/// output have been copied so far, so they are not copied again.
+pub async fn append_task_output(
+    pool: &Pool<MySql>,
+    task_id: i64,
+    lines: &[NewTaskLogLine],
+    output_lines: i32,
+) -> anyhow::Result<()> {
+    let mut tx = pool.begin().await?;
+
+    if !lines.is_empty() {
+        let received_at = Utc::now();
+        let mut query_builder: QueryBuilder<MySql> =
+            QueryBuilder::new("INSERT INTO task_logs (task_id, stream, message, timestamp) ");
+        query_builder.push_values(lines, |mut row, line| {
+            row.push_bind(task_id)
+                .push_bind(&line.stream)
+                .push_bind(&line.message)
+                .push_bind(line.timestamp.unwrap_or(received_at));
+        });
+        query_builder
+            .build()
+            .execute(&mut *tx)
+            .await
+            .context("Failed to record task log lines")?;
+    }
+
+    sqlx::query("UPDATE tasks SET output_lines = ? WHERE id = ?")
+        .bind(output_lines)
+        .bind(task_id)
+        .execute(&mut *tx)
+        .await
+        .context("Failed to update task output position")?;
+
+    tx.commit().await?;
+
+    Ok(())
+}
+
+/// Records a line about a task on behalf of the orchestrator.
+pub async fn record_system_line(pool: &Pool<MySql>, task_id: i64, message: &str) -> anyhow::Result<()> {
+    sqlx::query("INSERT INTO task_logs (task_id, stream, message) VALUES (?, 'system', ?)")
+        .bind(task_id)
+        .bind(message)
+        .execute(pool)
+        .await
+        .context("Failed to record task log line")?;
+
+    Ok(())
+}
+
+/// Retrieves the lines of a task recorded after the line with ID
+/// `after_id`, oldest first, for reading and tailing its output.
+pub async fn list_task_log_lines_after(
+    pool: &Pool<MySql>,
+    task_id: i64,
+    after_id: i64,
+    limit: i64,
+) -> anyhow::Result<Vec<TaskLogLine>> {
+    let lines = sqlx::query_as::<_, TaskLogLine>(
+        r#"
+        SELECT * FROM task_logs
+        WHERE task_id = ? AND id > ?
+        ORDER BY id ASC
+        LIMIT ?
+        "#,
+    )
+    .bind(task_id)
+    .bind(after_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch task log lines")?;
+
+    Ok(lines)
+}
@@ -0,0 +1,217 @@
+// db/queries/task_schedule.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// A cron schedule creating a task of an application whenever it is due.
+///
+/// `next_run_at` is kept up to date by whoever changes the schedule and by
+/// the task runner after each run; it is unset while the schedule is
+/// disabled.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaskSchedule {
+    pub id: i64,
+    pub app_id: i64,
+    pub name: String,
+    pub command: String,
+    /// Five-field cron expression, evaluated in UTC
+    pub cron_expression: String,
+    pub memory_in_mb: Option<i64>,
+    pub disk_in_mb: Option<i64>,
+    pub cpu: Option<f64>,
+    pub timeout_seconds: Option<i64>,
+    pub enabled: bool,
+    /// Whether a run starts a task while the previous one has not finished
+    pub allow_overlap: bool,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_task_id: Option<i64>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The settable fields of a task schedule.
+#[derive(Debug, Clone)]
+pub struct TaskScheduleInput {
+    pub name: String,
+    pub command: String,
+    pub cron_expression: String,
+    pub memory_in_mb: Option<i64>,
+    pub disk_in_mb: Option<i64>,
+    pub cpu: Option<f64>,
+    pub timeout_seconds: Option<i64>,
+    pub enabled: bool,
+    pub allow_overlap: bool,
+    /// First time the schedule is due; unset while it is disabled
+    pub next_run_at: Option<DateTime<Utc>>,
+}
+
+/// Retrieves the task schedules of an application.
+pub async fn list_app_schedules(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Vec<TaskSchedule>> {
+    let schedules = sqlx::query_as::<_, TaskSchedule>("SELECT * FROM task_schedules WHERE app_id = ? ORDER BY id ASC")
+        .bind(app_id)
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch task schedules")?;
+
+    Ok(schedules)
+}
+
+/// Retrieves a task schedule of an application.
+pub async fn get_schedule(pool: &Pool<MySql>, app_id: i64, id: i64) -> anyhow::Result<Option<TaskSchedule>> {
+    let schedule = sqlx::query_as::<_, TaskSchedule>("SELECT * FROM task_schedules WHERE id = ? AND app_id = ?")
+        .bind(id)
+        .bind(app_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch task schedule")?;
+
+    Ok(schedule)
+}
+
+/// Retrieves the enabled schedules that are due at `now`, oldest first.
+pub async fn list_due_schedules(pool: &Pool<MySql>, now: DateTime<Utc>) -> anyhow::Result<Vec<TaskSchedule>> {
+    let schedules = sqlx::query_as::<_, TaskSchedule>(
+        r#"SELECT * FROM task_schedules
+        WHERE enabled = TRUE AND next_run_at IS NOT NULL AND next_run_at <= ?
+        ORDER BY next_run_at ASC, id ASC"#,
+    )
+    .bind(now)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch due task schedules")?;
+
+    Ok(schedules)
+}
+
+/// Creates a task schedule of an application.
+pub async fn create_schedule(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    schedule: &TaskScheduleInput,
+    created_by: Option<i64>,
+) -> anyhow::Result<TaskSchedule> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"INSERT INTO task_schedules
+            (app_id, name, command, cron_expression, memory_in_mb, disk_in_mb, cpu, timeout_seconds,
+             enabled, allow_overlap, next_run_at, created_by)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+    )
+    .bind(app_id)
+    .bind(&schedule.name)
+    .bind(&schedule.command)
+    .bind(&schedule.cron_expression)
+    .bind(schedule.memory_in_mb)
+    .bind(schedule.disk_in_mb)
+    .bind(schedule.cpu)
+    .bind(schedule.timeout_seconds)
+    .bind(schedule.enabled)
+    .bind(schedule.allow_overlap)
+    .bind(schedule.next_run_at)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create task schedule")?;
+
+    let created = sqlx::query_as::<_, TaskSchedule>("SELECT * FROM task_schedules WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created task schedule")?;
+
+    tx.commit().await?;
+
+    Ok(created)
+}
+
+/// Replaces the fields of a task schedule.
+pub async fn update_schedule(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    id: i64,
+    schedule: &TaskScheduleInput,
+) -> anyhow::Result<Option<TaskSchedule>> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"UPDATE task_schedules
+        SET name = ?, command = ?, cron_expression = ?, memory_in_mb = ?, disk_in_mb = ?, cpu = ?,
+            timeout_seconds = ?, enabled = ?, allow_overlap = ?, next_run_at = ?
+        WHERE id = ? AND app_id = ?"#,
+    )
+    .bind(&schedule.name)
+    .bind(&schedule.command)
+    .bind(&schedule.cron_expression)
+    .bind(schedule.memory_in_mb)
+    .bind(schedule.disk_in_mb)
+    .bind(schedule.cpu)
+    .bind(schedule.timeout_seconds)
+    .bind(schedule.enabled)
+    .bind(schedule.allow_overlap)
+    .bind(schedule.next_run_at)
+    .bind(id)
+    .bind(app_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to update task schedule")?;
+
+    let updated = sqlx::query_as::<_, TaskSchedule>("SELECT * FROM task_schedules WHERE id = ? AND app_id = ?")
+        .bind(id)
+        .bind(app_id)
+        .fetch_optional(&mut *tx)
+        .await
+        .context("Failed to fetch updated task schedule")?;
+
+    tx.commit().await?;
+
+    Ok(updated)
+}
+
+/// Deletes a task schedule. Tasks it created are kept. Returns `false` if
+/// there was no such schedule.
+pub async fn delete_schedule(pool: &Pool<MySql>, app_id: i64, id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM task_schedules WHERE id = ? AND app_id = ?")
+        .bind(id)
+        .bind(app_id)
+        .execute(pool)
+        .await
+        .context("Failed to delete task schedule")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Moves a schedule on to its next run once it was due. `due_at` guards
+/// against the schedule having been changed in the meantime; returns
+/// `false` if it was, in which case the run must not happen.
+pub async fn advance_schedule(
+    pool: &Pool<MySql>,
+    id: i64,
+    due_at: DateTime<Utc>,
+    next_run_at: Option<DateTime<Utc>>,
+) -> anyhow::Result<bool> {
+    let result = sqlx::query("UPDATE task_schedules SET next_run_at = ? WHERE id = ? AND enabled = TRUE AND next_run_at = ?")
+        .bind(next_run_at)
+        .bind(id)
+        .bind(due_at)
+        .execute(pool)
+        .await
+        .context("Failed to advance task schedule")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records the task a run of a schedule created.
+pub async fn record_schedule_task(pool: &Pool<MySql>, id: i64, task_id: i64) -> anyhow::Result<()> {
+    sqlx::query("UPDATE task_schedules SET last_run_at = NOW(), last_task_id = ? WHERE id = ?")
+        .bind(task_id)
+        .bind(id)
+        .execute(pool)
+        .await
+        .context("Failed to record task of schedule")?;
+
+    Ok(())
+}
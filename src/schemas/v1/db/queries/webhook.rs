@@ -0,0 +1,166 @@
+// db/queries/webhook.rs
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{FromRow, MySql, Pool};
+
+/// Days the IDs of received deliveries are kept to recognize redeliveries.
+pub const DELIVERY_ID_RETENTION_DAYS: i64 = 30;
+
+/// The repository webhook of an application.
+///
+/// Pushes to the application's `git_branch` delivered to the webhook queue
+/// a build of the pushed commit, which is deployed with
+/// `deployment_strategy` once it succeeds if `auto_deploy` is set.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AppWebhook {
+    pub id: i64,
+    pub app_id: i64,
+    /// Verifies the signature of deliveries; only handed out when set
+    #[serde(skip_serializing)]
+    pub secret: String,
+    pub auto_deploy: Option<bool>,
+    pub deployment_strategy: Option<String>,
+    pub enabled: Option<bool>,
+    pub last_delivery_at: Option<DateTime<Utc>>,
+    pub last_delivery_result: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+}
+
+/// The settable fields of a webhook.
+#[derive(Debug, Clone)]
+pub struct AppWebhookInput {
+    pub auto_deploy: bool,
+    pub deployment_strategy: String,
+    pub enabled: bool,
+}
+
+/// Retrieves the webhook of an application.
+pub async fn get_app_webhook(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<Option<AppWebhook>> {
+    let webhook = sqlx::query_as::<_, AppWebhook>("SELECT * FROM app_webhooks WHERE app_id = ?")
+        .bind(app_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch app webhook")?;
+
+    Ok(webhook)
+}
+
+/// Creates or updates the webhook of an application. The secret of an
+/// existing webhook is only replaced if `secret` is set; a new webhook
+/// requires one.
+pub async fn set_app_webhook(
+    pool: &Pool<MySql>,
+    app_id: i64,
+    webhook: &AppWebhookInput,
+    secret: Option<&str>,
+    created_by: Option<i64>,
+) -> anyhow::Result<AppWebhook> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"INSERT INTO app_webhooks (app_id, secret, auto_deploy, deployment_strategy, enabled, created_by)
+        VALUES (?, ?, ?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            secret = COALESCE(?, secret),
+            auto_deploy = VALUES(auto_deploy),
+            deployment_strategy = VALUES(deployment_strategy),
+            enabled = VALUES(enabled)"#,
+    )
+    .bind(app_id)
+    .bind(secret.unwrap_or_default())
+    .bind(webhook.auto_deploy)
+    .bind(&webhook.deployment_strategy)
+    .bind(webhook.enabled)
+    .bind(created_by)
+    .bind(secret)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to save app webhook")?;
+
+    let saved = sqlx::query_as::<_, AppWebhook>("SELECT * FROM app_webhooks WHERE app_id = ?")
+        .bind(app_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch saved app webhook")?;
+
+    tx.commit().await?;
+
+    Ok(saved)
+}
+
+/// Replaces the secret of an application's webhook. Returns `false` if it
+/// has none.
+pub async fn rotate_app_webhook_secret(pool: &Pool<MySql>, app_id: i64, secret: &str) -> anyhow::Result<bool> {
+    let result = sqlx::query("UPDATE app_webhooks SET secret = ? WHERE app_id = ?")
+        .bind(secret)
+        .bind(app_id)
+        .execute(pool)
+        .await
+        .context("Failed to rotate app webhook secret")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Deletes the webhook of an application. Returns `false` if it had none.
+pub async fn delete_app_webhook(pool: &Pool<MySql>, app_id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("DELETE FROM app_webhooks WHERE app_id = ?")
+        .bind(app_id)
+        .execute(pool)
+        .await
+        .context("Failed to delete app webhook")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Records the outcome of the latest verified delivery to a webhook.
+pub async fn record_webhook_delivery(pool: &Pool<MySql>, webhook_id: i64, result: &str) -> anyhow::Result<()> {
+    sqlx::query(
+        "UPDATE app_webhooks SET last_delivery_at = NOW(), last_delivery_result = LEFT(?, 255) WHERE id = ?",
+    )
+    .bind(result)
+    .bind(webhook_id)
+    .execute(pool)
+    .await
+    .context("Failed to record webhook delivery")?;
+
+    Ok(())
+}
+
+/// Records that a delivery was received, unless it already was. Returns
+/// `false` for a delivery seen before, which must not be acted on again.
+///
+/// Delivery IDs are remembered for `DELIVERY_ID_RETENTION_DAYS`; older ones
+/// are forgotten here as well.
+pub async fn claim_webhook_delivery(pool: &Pool<MySql>, webhook_id: i64, delivery_id: &str) -> anyhow::Result<bool> {
+    sqlx::query("DELETE FROM webhook_deliveries WHERE webhook_id = ? AND received_at < NOW() - INTERVAL ? DAY")
+        .bind(webhook_id)
+        .bind(DELIVERY_ID_RETENTION_DAYS)
+        .execute(pool)
+        .await
+        .context("Failed to forget old webhook deliveries")?;
+
+    let result = sqlx::query("INSERT IGNORE INTO webhook_deliveries (webhook_id, delivery_id) VALUES (?, ?)")
+        .bind(webhook_id)
+        .bind(delivery_id)
+        .execute(pool)
+        .await
+        .context("Failed to record webhook delivery ID")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Forgets a delivery that could not be handled, so that the provider's
+/// redelivery is accepted.
+pub async fn release_webhook_delivery(pool: &Pool<MySql>, webhook_id: i64, delivery_id: &str) -> anyhow::Result<()> {
+    sqlx::query("DELETE FROM webhook_deliveries WHERE webhook_id = ? AND delivery_id = ?")
+        .bind(webhook_id)
+        .bind(delivery_id)
+        .execute(pool)
+        .await
+        .context("Failed to release webhook delivery ID")?;
+
+    Ok(())
+}
@@ -1,8 +1,12 @@
 use anyhow::Context;
-use sqlx::{MySql, Pool};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::{FromRow, MySql, Pool};
 use tracing;
 
 use libomni::types::db::v1 as types;
+use types::instance::Instance;
 use types::worker::Worker;
 /// Retrieves a paginated list of workers from the database.
 ///
@@ -71,4 +75,504 @@ pub async fn get_worker_by_id(
     .await?;
     
     Ok(worker)
-}
\ No newline at end of file
+}
+
+/// A token worker agents present to register themselves. The token's
+/// hash is deliberately not part of it.
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WorkerBootstrapToken {
+    pub id: i64,
+    pub description: Option<String>,
+    pub region_id: Option<i64>,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub created_by: Option<i64>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+/// What a worker agent reports about its worker when it registers.
+#[derive(Debug, Clone)]
+pub struct NewWorker {
+    pub name: String,
+    pub region_id: i64,
+    pub provider_id: Option<String>,
+    pub instance_type: Option<String>,
+    pub cpu_total: f64,
+    pub memory_total: f64,
+    pub disk_total: f64,
+    pub docker_version: Option<String>,
+    pub labels: Option<Value>,
+    pub taints: Option<Value>,
+    pub annotations: Option<Value>,
+    pub agent_version: Option<String>,
+}
+
+/// Creates a bootstrap token. Only the hash of the token is stored.
+pub async fn create_bootstrap_token(
+    pool: &Pool<MySql>,
+    token_hash: &str,
+    description: Option<&str>,
+    region_id: Option<i64>,
+    max_uses: Option<i32>,
+    expires_at: Option<DateTime<Utc>>,
+    created_by: Option<i64>,
+) -> anyhow::Result<WorkerBootstrapToken> {
+    let mut tx = pool.begin().await?;
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO worker_bootstrap_tokens (token_hash, description, region_id, max_uses, expires_at, created_by)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#,
+    )
+    .bind(token_hash)
+    .bind(description)
+    .bind(region_id)
+    .bind(max_uses)
+    .bind(expires_at)
+    .bind(created_by)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to create bootstrap token")?;
+
+    let token = sqlx::query_as::<_, WorkerBootstrapToken>("SELECT * FROM worker_bootstrap_tokens WHERE id = ?")
+        .bind(result.last_insert_id() as i64)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch created bootstrap token")?;
+
+    tx.commit().await?;
+    Ok(token)
+}
+
+/// Retrieves every bootstrap token, newest first.
+pub async fn list_bootstrap_tokens(pool: &Pool<MySql>) -> anyhow::Result<Vec<WorkerBootstrapToken>> {
+    let tokens = sqlx::query_as::<_, WorkerBootstrapToken>("SELECT * FROM worker_bootstrap_tokens ORDER BY id DESC")
+        .fetch_all(pool)
+        .await
+        .context("Failed to fetch bootstrap tokens")?;
+
+    Ok(tokens)
+}
+
+/// Revokes a bootstrap token. Workers that registered with it keep their
+/// own credentials. Returns whether a usable token was revoked.
+pub async fn revoke_bootstrap_token(pool: &Pool<MySql>, token_id: i64) -> anyhow::Result<bool> {
+    let result = sqlx::query("UPDATE worker_bootstrap_tokens SET revoked_at = NOW() WHERE id = ? AND revoked_at IS NULL")
+        .bind(token_id)
+        .execute(pool)
+        .await
+        .context("Failed to revoke bootstrap token")?;
+
+    Ok(result.rows_affected() > 0)
+}
+
+/// Retrieves a bootstrap token by its hash if it can still be used: it is
+/// not revoked, not expired and not used up.
+pub async fn get_usable_bootstrap_token(
+    pool: &Pool<MySql>,
+    token_hash: &str,
+) -> anyhow::Result<Option<WorkerBootstrapToken>> {
+    let token = sqlx::query_as::<_, WorkerBootstrapToken>(
+        r#"
+        SELECT * FROM worker_bootstrap_tokens
+        WHERE token_hash = ? AND revoked_at IS NULL
+          AND (expires_at IS NULL OR expires_at > NOW())
+          AND (max_uses IS NULL OR uses < max_uses)
+        "#,
+    )
+    .bind(token_hash)
+    .fetch_optional(pool)
+    .await
+    .context("Failed to fetch bootstrap token")?;
+
+    Ok(token)
+}
+
+/// Registers a worker, or re-registers the worker of the same name in the
+/// same region, and gives its agent a new credential.
+///
+/// A re-registered worker that was unreachable, provisioning or powered off
+/// becomes active again; one in maintenance or being decommissioned keeps
+/// its status. Returns `None` if the bootstrap token was used up, revoked
+/// or expired in the meantime.
+pub async fn register_worker(
+    pool: &Pool<MySql>,
+    bootstrap_token_id: i64,
+    worker: &NewWorker,
+    agent_token_hash: &str,
+) -> anyhow::Result<Option<Worker>> {
+    let mut tx = pool.begin().await?;
+
+    let consumed = sqlx::query(
+        r#"
+        UPDATE worker_bootstrap_tokens
+        SET uses = uses + 1, last_used_at = NOW()
+        WHERE id = ? AND revoked_at IS NULL
+          AND (expires_at IS NULL OR expires_at > NOW())
+          AND (max_uses IS NULL OR uses < max_uses)
+        "#,
+    )
+    .bind(bootstrap_token_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to consume bootstrap token")?;
+
+    if consumed.rows_affected() == 0 {
+        tx.rollback().await?;
+        return Ok(None);
+    }
+
+    sqlx::query(
+        r#"
+        INSERT INTO workers (
+            name, region_id, provider_id, instance_type, status,
+            cpu_total, cpu_available, memory_total, memory_available, disk_total, disk_available,
+            docker_version, labels, taints, annotations, last_heartbeat
+        )
+        VALUES (?, ?, ?, ?, 'active', ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, NOW())
+        ON DUPLICATE KEY UPDATE
+            provider_id = VALUES(provider_id),
+            instance_type = VALUES(instance_type),
+            status = IF(status IN ('unreachable', 'provisioning', 'powered_off'), 'active', status),
+            cpu_total = VALUES(cpu_total),
+            memory_total = VALUES(memory_total),
+            disk_total = VALUES(disk_total),
+            docker_version = VALUES(docker_version),
+            labels = VALUES(labels),
+            taints = VALUES(taints),
+            annotations = VALUES(annotations),
+            last_heartbeat = NOW()
+        "#,
+    )
+    .bind(&worker.name)
+    .bind(worker.region_id)
+    .bind(&worker.provider_id)
+    .bind(&worker.instance_type)
+    .bind(worker.cpu_total)
+    .bind(worker.cpu_total)
+    .bind(worker.memory_total)
+    .bind(worker.memory_total)
+    .bind(worker.disk_total)
+    .bind(worker.disk_total)
+    .bind(&worker.docker_version)
+    .bind(&worker.labels)
+    .bind(&worker.taints)
+    .bind(&worker.annotations)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to register worker")?;
+
+    let worker_id = sqlx::query_scalar::<_, i64>("SELECT id FROM workers WHERE name = ? AND region_id = ? FOR UPDATE")
+        .bind(&worker.name)
+        .bind(worker.region_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch registered worker")?;
+
+    // A re-registered worker may still run instances, so what it has
+    // available is derived from what is reserved on it
+    recompute_available_capacity(&mut tx, worker_id).await?;
+
+    sqlx::query(
+        r#"
+        INSERT INTO worker_agents (worker_id, token_hash, bootstrap_token_id, agent_version)
+        VALUES (?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            token_hash = VALUES(token_hash),
+            bootstrap_token_id = VALUES(bootstrap_token_id),
+            agent_version = VALUES(agent_version),
+            registered_at = NOW()
+        "#,
+    )
+    .bind(worker_id)
+    .bind(agent_token_hash)
+    .bind(bootstrap_token_id)
+    .bind(&worker.agent_version)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to store worker agent credential")?;
+
+    let registered = sqlx::query_as::<_, Worker>("SELECT * FROM workers WHERE id = ?")
+        .bind(worker_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch registered worker")?;
+
+    tx.commit().await?;
+    Ok(Some(registered))
+}
+
+/// Retrieves the hash of the credential a worker's agent authenticates
+/// with, if the worker has a registered agent.
+pub async fn get_worker_agent_token_hash(pool: &Pool<MySql>, worker_id: i64) -> anyhow::Result<Option<String>> {
+    let hash = sqlx::query_scalar::<_, String>("SELECT token_hash FROM worker_agents WHERE worker_id = ?")
+        .bind(worker_id)
+        .fetch_optional(pool)
+        .await
+        .context("Failed to fetch worker agent credential")?;
+
+    Ok(hash)
+}
+
+/// Records a heartbeat of a worker's agent.
+///
+/// The worker's totals are replaced by the reported ones where given and
+/// its available capacity is recomputed from the reservations of the
+/// instances placed on it. An unreachable worker becomes active again.
+pub async fn record_worker_heartbeat(
+    pool: &Pool<MySql>,
+    worker_id: i64,
+    cpu_total: Option<f64>,
+    memory_total: Option<f64>,
+    disk_total: Option<f64>,
+    agent_version: Option<&str>,
+    report: &Value,
+) -> anyhow::Result<Worker> {
+    let mut tx = pool.begin().await?;
+
+    sqlx::query(
+        r#"
+        UPDATE workers
+        SET last_heartbeat = NOW(),
+            status = IF(status = 'unreachable', 'active', status),
+            cpu_total = COALESCE(?, cpu_total),
+            memory_total = COALESCE(?, memory_total),
+            disk_total = COALESCE(?, disk_total)
+        WHERE id = ?
+        "#,
+    )
+    .bind(cpu_total)
+    .bind(memory_total)
+    .bind(disk_total)
+    .bind(worker_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to record worker heartbeat")?;
+
+    recompute_available_capacity(&mut tx, worker_id).await?;
+
+    sqlx::query(
+        "UPDATE worker_agents SET last_report = ?, agent_version = COALESCE(?, agent_version) WHERE worker_id = ?",
+    )
+    .bind(report)
+    .bind(agent_version)
+    .bind(worker_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to store worker report")?;
+
+    let worker = sqlx::query_as::<_, Worker>("SELECT * FROM workers WHERE id = ?")
+        .bind(worker_id)
+        .fetch_one(&mut *tx)
+        .await
+        .context("Failed to fetch worker")?;
+
+    tx.commit().await?;
+    Ok(worker)
+}
+
+/// Sets a worker's available capacity to its totals minus the capacity
+/// reserved by the instances the scheduler placed on it.
+async fn recompute_available_capacity(
+    tx: &mut sqlx::Transaction<'_, MySql>,
+    worker_id: i64,
+) -> anyhow::Result<()> {
+    // Locks the worker so no placement can reserve capacity meanwhile
+    sqlx::query("SELECT id FROM workers WHERE id = ? FOR UPDATE")
+        .bind(worker_id)
+        .execute(&mut **tx)
+        .await
+        .context("Failed to lock worker")?;
+
+    let (cpu, memory, disk) = sqlx::query_as::<_, (f64, f64, f64)>(
+        r#"
+        SELECT
+            CAST(COALESCE(SUM(JSON_EXTRACT(scheduler_metadata, '$.reservation.cpu')), 0) AS DOUBLE),
+            CAST(COALESCE(SUM(JSON_EXTRACT(scheduler_metadata, '$.reservation.memory_mb')), 0) AS DOUBLE),
+            CAST(COALESCE(SUM(JSON_EXTRACT(scheduler_metadata, '$.reservation.disk_mb')), 0) AS DOUBLE)
+        FROM instances
+        WHERE node_id = ?
+          AND JSON_UNQUOTE(JSON_EXTRACT(scheduler_metadata, '$.reservation.released')) = 'false'
+        "#,
+    )
+    .bind(worker_id)
+    .fetch_one(&mut **tx)
+    .await
+    .context("Failed to sum worker reservations")?;
+
+    sqlx::query(
+        r#"
+        UPDATE workers
+        SET cpu_available = GREATEST(0, cpu_total - ?),
+            memory_available = GREATEST(0, memory_total - ?),
+            disk_available = GREATEST(0, disk_total - ?)
+        WHERE id = ?
+        "#,
+    )
+    .bind(cpu)
+    .bind(memory)
+    .bind(disk)
+    .bind(worker_id)
+    .execute(&mut **tx)
+    .await
+    .context("Failed to update worker capacity")?;
+
+    Ok(())
+}
+
+/// Changes the status of a worker if it currently has one of `from`.
+/// Returns whether the status changed.
+pub async fn transition_worker_status(
+    pool: &Pool<MySql>,
+    worker_id: i64,
+    from: &[&str],
+    to: &str,
+) -> anyhow::Result<bool> {
+    if from.is_empty() {
+        return Ok(false);
+    }
+
+    let placeholders = vec!["?"; from.len()].join(", ");
+    let sql = format!("UPDATE workers SET status = ? WHERE id = ? AND status IN ({})", placeholders);
+    let mut query = sqlx::query(&sql).bind(to).bind(worker_id);
+    for status in from {
+        query = query.bind(*status);
+    }
+
+    let result = query.execute(pool).await.context("Failed to update worker status")?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// Asks the reconciler to move every instance off a worker by marking them
+/// 'stopping'; the reconciler terminates them and places replacements on
+/// other workers. Returns the instances that were marked.
+pub async fn drain_worker_instances(pool: &Pool<MySql>, worker_id: i64) -> anyhow::Result<Vec<Instance>> {
+    let mut tx = pool.begin().await?;
+
+    let instances = sqlx::query_as::<_, Instance>(
+        r#"
+        SELECT * FROM instances
+        WHERE node_id = ? AND status NOT IN ('stopping', 'stopped', 'terminated')
+        ORDER BY id
+        FOR UPDATE
+        "#,
+    )
+    .bind(worker_id)
+    .fetch_all(&mut *tx)
+    .await
+    .context("Failed to fetch instances of worker")?;
+
+    sqlx::query(
+        r#"
+        UPDATE instances SET status = 'stopping', updated_at = NOW()
+        WHERE node_id = ? AND status NOT IN ('stopping', 'stopped', 'terminated')
+        "#,
+    )
+    .bind(worker_id)
+    .execute(&mut *tx)
+    .await
+    .context("Failed to drain instances of worker")?;
+
+    tx.commit().await?;
+    Ok(instances)
+}
+
+/// Retrieves the instances placed on a worker that have not finished.
+pub async fn list_worker_instances(pool: &Pool<MySql>, worker_id: i64) -> anyhow::Result<Vec<Instance>> {
+    let instances = sqlx::query_as::<_, Instance>(
+        "SELECT * FROM instances WHERE node_id = ? AND status NOT IN ('stopped', 'terminated') ORDER BY id",
+    )
+    .bind(worker_id)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch instances of worker")?;
+
+    Ok(instances)
+}
+
+/// Marks active workers whose agent has not sent a heartbeat for
+/// `after_secs` seconds as unreachable, returning them. Workers that never
+/// had an agent, and cordoned or decommissioning workers, are left alone.
+pub async fn mark_unreachable_workers(pool: &Pool<MySql>, after_secs: u64) -> anyhow::Result<Vec<Worker>> {
+    let stale = sqlx::query_as::<_, Worker>(
+        r#"
+        SELECT w.* FROM workers w
+        JOIN worker_agents a ON a.worker_id = w.id
+        WHERE w.status IN ('active', 'degraded')
+          AND w.last_heartbeat < NOW() - INTERVAL ? SECOND
+        "#,
+    )
+    .bind(after_secs)
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch stale workers")?;
+
+    let mut marked = Vec::new();
+    for worker in stale {
+        // Guarded by the heartbeat so a worker that reported meanwhile stays
+        let result = sqlx::query(
+            r#"
+            UPDATE workers SET status = 'unreachable'
+            WHERE id = ? AND status = ? AND last_heartbeat < NOW() - INTERVAL ? SECOND
+            "#,
+        )
+        .bind(worker.id)
+        .bind(&worker.status)
+        .bind(after_secs)
+        .execute(pool)
+        .await
+        .context("Failed to mark worker unreachable")?;
+
+        if result.rows_affected() > 0 {
+            marked.push(worker);
+        }
+    }
+
+    Ok(marked)
+}
+
+/// Powers off decommissioning workers that no longer run any instance and
+/// revokes their agent's credential, returning their IDs.
+pub async fn finish_decommissioned_workers(pool: &Pool<MySql>) -> anyhow::Result<Vec<i64>> {
+    let drained = sqlx::query_scalar::<_, i64>(
+        r#"
+        SELECT w.id FROM workers w
+        WHERE w.status = 'decommissioning'
+          AND NOT EXISTS (
+              SELECT 1 FROM instances i
+              WHERE i.node_id = w.id AND i.status NOT IN ('stopped', 'terminated')
+          )
+        "#,
+    )
+    .fetch_all(pool)
+    .await
+    .context("Failed to fetch drained workers")?;
+
+    let mut finished = Vec::new();
+    for worker_id in drained {
+        let mut tx = pool.begin().await?;
+        let result = sqlx::query("UPDATE workers SET status = 'powered_off' WHERE id = ? AND status = 'decommissioning'")
+            .bind(worker_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to power off worker")?;
+        if result.rows_affected() == 0 {
+            tx.rollback().await?;
+            continue;
+        }
+
+        sqlx::query("DELETE FROM worker_agents WHERE worker_id = ?")
+            .bind(worker_id)
+            .execute(&mut *tx)
+            .await
+            .context("Failed to revoke worker agent credential")?;
+        tx.commit().await?;
+        finished.push(worker_id);
+    }
+
+    Ok(finished)
+}
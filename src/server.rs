@@ -7,6 +7,8 @@ use libomni::types::db::auth::AuthConfig;
 use crate::cluster::ClusterManager;
 use crate::state::SharedState;
 use crate::db_manager::DatabaseManager;
+use crate::runtime::RuntimeDriver;
+use crate::artifacts::ArtifactStore;
 use crate::cors::CORS;
 use crate::endpoints::{health_check, cluster_status};
 use crate::cors::cors_preflight;
@@ -27,6 +29,22 @@ impl RocketExt for Rocket<Build> {
     }
 }
 
+/// Every route the server mounts, by the path it is mounted at.
+fn routes() -> Vec<(&'static str, Vec<rocket::Route>)> {
+    vec![
+        (
+            "/",
+            routes![
+                health_check,
+                api::index::routes_ui,
+                cluster_status,
+                cors_preflight
+            ],
+        ),
+        ("/api/v1", api::routes()),
+    ]
+}
+
 pub fn build_rocket(
     port: u16,
     db_manager: Arc<DatabaseManager>,
@@ -35,6 +53,8 @@ pub fn build_rocket(
     clickhouse_client: clickhouse::Client,
     shared_state: Arc<RwLock<SharedState>>,
     auth_config: AuthConfig,
+    runtime_driver: Arc<dyn RuntimeDriver>,
+    artifact_store: Arc<dyn ArtifactStore>,
 ) -> Rocket<Build> {
     println!(
         "{}",
@@ -50,18 +70,7 @@ pub fn build_rocket(
     );
 
     log::info!("{}", "Defining API routes".cyan());
-    let routes = vec![
-        (
-            "/",
-            routes![
-                health_check,
-                api::index::routes_ui,
-                cluster_status,
-                cors_preflight
-            ],
-        ),
-        ("/api/v1", api::routes()),
-    ];
+    let routes = routes();
 
     log::info!("{}", "Building Rocket instance".cyan());
     let rocket_instance = rocket::build()
@@ -76,7 +85,11 @@ pub fn build_rocket(
         .manage(clickhouse_client)
         .manage(shared_state)
         .manage(auth_config)
-        .attach(CORS);
+        .manage(runtime_driver)
+        .manage(artifact_store)
+        .manage(api::sso::OidcClient::new(crate::config::SERVER_CONFIG.oidc.clone()))
+        .attach(CORS)
+        .attach(api::audit_log::AuditFairing);
 
     log::info!("{}", "Mounting API routes".cyan());
     let rocket_with_routes = rocket_instance.mount_routes(routes);
@@ -84,4 +97,24 @@ pub fn build_rocket(
     api::index::collect_routes(&rocket_with_routes);
 
     rocket_with_routes
+}
+
+#[cfg(test)]
+mod tests {
+    use rocket::error::ErrorKind;
+
+    use super::{routes, RocketExt};
+
+    /// Rocket refuses to launch when two routes of the same rank match the
+    /// same paths, so igniting with every route must not report collisions.
+    /// Igniting without managed state still fails later on, which is fine.
+    #[rocket::async_test]
+    async fn routes_do_not_collide() {
+        let rocket = rocket::build().mount_routes(routes());
+        if let Err(e) = rocket.ignite().await {
+            if matches!(e.kind(), ErrorKind::Collisions(_)) {
+                panic!("Routes collide: {:?}", e);
+            }
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,168 @@
+//! Cron expressions of task schedules.
+//!
+//! Expressions have the usual five fields, `minute hour day-of-month month
+//! day-of-week`, each a `*`, a value, a range `a-b` or a list of those,
+//! optionally with a step such as `*/15` or `0-30/10`. Months and weekdays
+//! may be given by their three-letter English names, and Sunday is both 0
+//! and 7. As in classic cron, a day matches when either day field matches
+//! if both are restricted. The shortcuts `@yearly`, `@annually`,
+//! `@monthly`, `@weekly`, `@daily`, `@midnight` and `@hourly` are accepted
+//! too. Schedules are evaluated in UTC.
+
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+
+/// Minutes searched for the next run before giving up, a little over four
+/// years so that schedules on February 29th are found.
+const MAX_SEARCH_MINUTES: i64 = 4 * 366 * 24 * 60 + 1;
+
+const MONTH_NAMES: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+const WEEKDAY_NAMES: [&str; 7] = ["sun", "mon", "tue", "wed", "thu", "fri", "sat"];
+
+/// A parsed cron expression.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    /// Whether the day-of-month field does not start with `*`
+    days_of_month_restricted: bool,
+    /// Whether the day-of-week field does not start with `*`
+    days_of_week_restricted: bool,
+}
+
+impl CronSchedule {
+    /// Parses a cron expression, describing what is wrong with it if it
+    /// cannot be parsed.
+    pub fn parse(expression: &str) -> Result<Self, String> {
+        let expression = expression.trim();
+        let expanded = match expression.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            other if other.starts_with('@') => return Err(format!("unknown cron shortcut '{}'", expression)),
+            _ => expression,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(format!(
+                "cron expression must have 5 fields (minute hour day-of-month month day-of-week), found {}",
+                fields.len()
+            ));
+        }
+
+        let mut days_of_week = parse_field(fields[4], "day-of-week", 0, 7, &WEEKDAY_NAMES, 0)?;
+        // Sunday may be written as 7
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], "minute", 0, 59, &[], 0)?,
+            hours: parse_field(fields[1], "hour", 0, 23, &[], 0)?,
+            days_of_month: parse_field(fields[2], "day-of-month", 1, 31, &[], 0)?,
+            months: parse_field(fields[3], "month", 1, 12, &MONTH_NAMES, 1)?,
+            days_of_week,
+            days_of_month_restricted: !fields[2].starts_with('*'),
+            days_of_week_restricted: !fields[4].starts_with('*'),
+        })
+    }
+
+    /// The first minute strictly after `after` that the schedule matches,
+    /// or `None` if it never matches, as for `0 0 30 2 *`.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let start = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let limit = start + Duration::minutes(MAX_SEARCH_MINUTES);
+
+        let mut time = start;
+        while time < limit {
+            if !has(self.months, time.month()) {
+                // Skip to the first day of the next month
+                let (year, month) = if time.month() == 12 { (time.year() + 1, 1) } else { (time.year(), time.month() + 1) };
+                time = midnight(NaiveDate::from_ymd_opt(year, month, 1)?);
+                continue;
+            }
+            if !self.day_matches(time) {
+                time = midnight(time.date_naive().succ_opt()?);
+                continue;
+            }
+            if !has(self.hours, time.hour()) {
+                time = time.with_minute(0)? + Duration::hours(1);
+                continue;
+            }
+            if !has(self.minutes, time.minute()) {
+                time += Duration::minutes(1);
+                continue;
+            }
+            return Some(time);
+        }
+        None
+    }
+
+    fn day_matches(&self, time: DateTime<Utc>) -> bool {
+        let day_of_month = has(self.days_of_month, time.day());
+        let day_of_week = has(self.days_of_week, time.weekday().num_days_from_sunday());
+        match (self.days_of_month_restricted, self.days_of_week_restricted) {
+            (true, true) => day_of_month || day_of_week,
+            (true, false) => day_of_month,
+            (false, true) => day_of_week,
+            (false, false) => true,
+        }
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn midnight(date: NaiveDate) -> DateTime<Utc> {
+    Utc.from_utc_datetime(&date.and_hms_opt(0, 0, 0).unwrap_or_default())
+}
+
+/// Parses one field into a bit set of the values it matches. `names` are
+/// accepted for the values from `first_name` on.
+fn parse_field(field: &str, label: &str, min: u32, max: u32, names: &[&str], first_name: u32) -> Result<u64, String> {
+    let value = |text: &str| -> Result<u32, String> {
+        let lower = text.to_ascii_lowercase();
+        if let Some(index) = names.iter().position(|name| *name == lower) {
+            return Ok(first_name + index as u32);
+        }
+        match text.parse::<u32>() {
+            Ok(value) if (min..=max).contains(&value) => Ok(value),
+            _ => Err(format!("invalid {} '{}'; expected a value between {} and {}", label, text, min, max)),
+        }
+    };
+
+    let mut set = 0u64;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (range, step),
+                _ => return Err(format!("invalid step '{}' in {} field", step, label)),
+            },
+            None => (part, 1),
+        };
+
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            (value(start)?, value(end)?)
+        } else {
+            let start = value(range)?;
+            // `5/15` means every 15 starting at 5
+            (start, if part.contains('/') { max } else { start })
+        };
+        if start > end {
+            return Err(format!("invalid {} range '{}'; the start lies after the end", label, range));
+        }
+
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+    Ok(set)
+}
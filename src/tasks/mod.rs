@@ -0,0 +1,449 @@
+//! Execution of one-off and scheduled tasks.
+//!
+//! A task runs a shell command once in a container of its application's
+//! current release, with the release's environment, such as a database
+//! migration. Tasks are created through the API, by pipeline test stages
+//! and by [cron schedules](cron) in `task_schedules`.
+//!
+//! Every pass, the leader first creates the tasks of schedules that are
+//! due. A schedule whose previous task has not finished skips its run
+//! unless it allows overlapping runs, and runs missed while no leader was
+//! executing tasks are made up for only once. It then launches pending
+//! tasks through the runtime driver, copies the output of running tasks to
+//! `task_logs` and records their exit code once their container exits.
+//! Tasks running longer than their `timeout_seconds` are stopped and
+//! failed. Containers of finished and canceled tasks are stopped and
+//! removed, as are task containers the database no longer knows about.
+
+pub mod cron;
+
+use std::collections::HashSet;
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::Utc;
+use sqlx::{MySql, Pool};
+
+use crate::config::TasksConfig;
+use crate::reconciler::environment_of;
+use crate::runtime::{ContainerState, LogStream, RuntimeDriver, TaskSpec};
+use crate::schemas::v1::db::queries as db;
+use crate::DatabaseManager;
+use cron::CronSchedule;
+use db::task::{NewTask, Task};
+use db::task_log::NewTaskLogLine;
+use db::task_schedule::TaskSchedule;
+
+/// Schedules fired and tasks launched, finished or cleaned up by one pass
+/// of the task runner.
+#[derive(Debug, Default, Clone)]
+pub struct TaskSummary {
+    pub schedules_fired: usize,
+    pub schedules_skipped: usize,
+    pub tasks_started: usize,
+    pub tasks_succeeded: usize,
+    pub tasks_failed: usize,
+    pub tasks_timed_out: usize,
+    pub containers_removed: usize,
+    pub failures: usize,
+}
+
+impl TaskSummary {
+    fn changed(&self) -> bool {
+        self.schedules_fired
+            + self.schedules_skipped
+            + self.tasks_started
+            + self.tasks_succeeded
+            + self.tasks_failed
+            + self.tasks_timed_out
+            + self.containers_removed
+            + self.failures
+            > 0
+    }
+}
+
+/// Executes the tasks and task schedules of every platform once.
+pub async fn execute_all_platforms(db_manager: &DatabaseManager, driver: &dyn RuntimeDriver, config: &TasksConfig) {
+    let platforms = match db_manager.get_all_platforms().await {
+        Ok(platforms) => platforms,
+        Err(e) => {
+            log::error!("Failed to list platforms for tasks: {:?}", e);
+            return;
+        }
+    };
+
+    for platform in platforms {
+        let platform_id = match platform.id {
+            Some(id) => id,
+            None => continue,
+        };
+
+        let pool = match db_manager.get_platform_pool(&platform.name, platform_id).await {
+            Ok(pool) => pool,
+            Err(e) => {
+                log::error!("Failed to connect to platform {} for tasks: {:?}", platform_id, e);
+                continue;
+            }
+        };
+
+        match execute_platform(&pool, platform_id, driver, config).await {
+            Ok(Some(summary)) if summary.changed() => {
+                log::info!("Executed tasks of platform {}: {:?}", platform_id, summary);
+            }
+            Ok(_) => {}
+            Err(e) => log::error!("Failed to execute tasks of platform {}: {:#}", platform_id, e),
+        }
+    }
+}
+
+/// Fires the due schedules of a platform, launches its pending tasks and
+/// checks on its running ones. Returns `None` if another node is executing
+/// the platform's tasks.
+pub async fn execute_platform(
+    pool: &Pool<MySql>,
+    platform_id: i64,
+    driver: &dyn RuntimeDriver,
+    config: &TasksConfig,
+) -> anyhow::Result<Option<TaskSummary>> {
+    // Named locks are server wide, so the lock name includes the database
+    let mut lock_conn = pool.acquire().await?;
+    let locked = sqlx::query_scalar::<_, Option<i64>>("SELECT GET_LOCK(CONCAT('omni_tasks:', DATABASE()), 0)")
+        .fetch_one(&mut *lock_conn)
+        .await
+        .context("Failed to acquire task runner lock")?;
+    if locked != Some(1) {
+        return Ok(None);
+    }
+
+    let mut runner = Runner {
+        pool,
+        platform_id,
+        driver,
+        config,
+        summary: TaskSummary::default(),
+    };
+    let result = runner.pass().await.map(|()| Some(runner.summary));
+
+    if let Err(e) = sqlx::query("SELECT RELEASE_LOCK(CONCAT('omni_tasks:', DATABASE()))")
+        .execute(&mut *lock_conn)
+        .await
+    {
+        log::warn!("Failed to release task runner lock: {}", e);
+    }
+
+    result
+}
+
+/// One execution pass over the tasks of a platform.
+struct Runner<'a> {
+    pool: &'a Pool<MySql>,
+    platform_id: i64,
+    driver: &'a dyn RuntimeDriver,
+    config: &'a TasksConfig,
+    summary: TaskSummary,
+}
+
+impl Runner<'_> {
+    async fn pass(&mut self) -> anyhow::Result<()> {
+        for schedule in db::task_schedule::list_due_schedules(self.pool, Utc::now()).await? {
+            if let Err(e) = self.fire(&schedule).await {
+                self.summary.failures += 1;
+                log::error!("Failed to run task schedule {}: {:#}", schedule.id, e);
+            }
+        }
+
+        for task in db::task::list_tasks_with_status(self.pool, "pending").await? {
+            if let Err(e) = self.launch(&task).await {
+                self.summary.failures += 1;
+                log::error!("Failed to launch task {}: {:#}", task.id, e);
+            }
+        }
+
+        for task in db::task::list_tasks_with_status(self.pool, "running").await? {
+            if let Err(e) = self.check(&task).await {
+                self.summary.failures += 1;
+                log::error!("Failed to check on task {}: {:#}", task.id, e);
+            }
+        }
+
+        for task in db::task::list_finished_tasks_with_containers(self.pool).await? {
+            if let Err(e) = self.clean_up(&task).await {
+                self.summary.failures += 1;
+                log::error!("Failed to remove container of task {}: {:#}", task.id, e);
+            }
+        }
+
+        self.remove_stray_containers().await
+    }
+
+    /// Creates the task of a due schedule and moves the schedule on to its
+    /// next run.
+    async fn fire(&mut self, schedule: &TaskSchedule) -> anyhow::Result<()> {
+        let due_at = match schedule.next_run_at {
+            Some(due_at) => due_at,
+            None => return Ok(()),
+        };
+
+        let next_run_at = match CronSchedule::parse(&schedule.cron_expression) {
+            Ok(cron) => cron.next_after(Utc::now()),
+            Err(e) => {
+                log::warn!("Task schedule {} has an invalid cron expression and stops: {}", schedule.id, e);
+                None
+            }
+        };
+        if !db::task_schedule::advance_schedule(self.pool, schedule.id, due_at, next_run_at).await? {
+            // Changed since it was read; its new settings apply next pass
+            return Ok(());
+        }
+
+        if !schedule.allow_overlap {
+            if let Some(last_task_id) = schedule.last_task_id {
+                let last_task = db::task::get_task(self.pool, last_task_id).await?;
+                if let Some(last_task) = last_task.filter(|task| !db::task::is_finished(task.status.as_deref())) {
+                    self.summary.schedules_skipped += 1;
+                    log::info!(
+                        "Skipped run of task schedule {} as its previous task {} is still {}",
+                        schedule.id,
+                        last_task.id,
+                        last_task.status.as_deref().unwrap_or("pending")
+                    );
+                    return Ok(());
+                }
+            }
+        }
+
+        let task = NewTask {
+            command: schedule.command.clone(),
+            name: Some(schedule.name.clone()),
+            memory_in_mb: schedule.memory_in_mb,
+            disk_in_mb: schedule.disk_in_mb,
+            cpu: schedule.cpu,
+            timeout_seconds: schedule.timeout_seconds,
+            schedule_id: Some(schedule.id),
+        };
+        let task = db::task::create_task(self.pool, schedule.app_id, &task, schedule.created_by).await?;
+        db::task_schedule::record_schedule_task(self.pool, schedule.id, task.id).await?;
+        self.summary.schedules_fired += 1;
+
+        let message = format!("Created by schedule '{}' ({}), due at {}", schedule.name, schedule.cron_expression, due_at);
+        db::task_log::record_system_line(self.pool, task.id, &message).await
+    }
+
+    /// Launches the container of a pending task from its application's
+    /// current release.
+    async fn launch(&mut self, task: &Task) -> anyhow::Result<()> {
+        let app = db::app::get_app_by_id(self.pool, task.app_id).await?;
+        let deployment = db::deployment::get_current_deployment(self.pool, app.id).await?;
+        let deployment_id = deployment.as_ref().map(|deployment| deployment.id);
+
+        if !db::task::start_task(self.pool, task.id, deployment_id).await? {
+            // Canceled in the meantime
+            return Ok(());
+        }
+
+        let image = match app.container_image_url.clone() {
+            Some(image) => image,
+            None => {
+                let message = format!("App {} has no container image to run the task with", app.id);
+                return self.fail(task.id, &message).await;
+            }
+        };
+
+        let spec = TaskSpec {
+            platform_id: self.platform_id,
+            app_id: app.id,
+            app_name: app.name.clone(),
+            task_id: task.id,
+            sequence_id: task.sequence_id.unwrap_or(task.id),
+            image,
+            command: task.command.clone(),
+            deployment_id,
+            memory_in_mb: task.memory_in_mb,
+            cpu: task.cpu,
+            environment: environment_of(deployment.as_ref()),
+        };
+
+        let container_id = match self.driver.launch_task(&spec).await {
+            Ok(container_id) => container_id,
+            Err(e) => return self.fail(task.id, &format!("Failed to launch the task's container: {:#}", e)).await,
+        };
+        db::task::set_task_container(self.pool, task.id, Some(&container_id)).await?;
+        self.summary.tasks_started += 1;
+
+        let release = match deployment_id {
+            Some(deployment_id) => format!("deployment {}", deployment_id),
+            None => "no deployment".to_string(),
+        };
+        let message = format!("Started container {} running {} with {}", container_id, spec.image, release);
+        db::task_log::record_system_line(self.pool, task.id, &message).await
+    }
+
+    /// Copies new output of a running task and records its outcome once its
+    /// container has exited or it has run out of time.
+    async fn check(&mut self, task: &Task) -> anyhow::Result<()> {
+        let container_id = match task.container_id.as_deref() {
+            Some(container_id) => container_id,
+            None => return self.fail(task.id, "The task's container was not recorded").await,
+        };
+
+        let container = self.driver.inspect(container_id).await?;
+        let mut output_lines = task.output_lines;
+        if container.is_some() {
+            self.copy_output(task.id, container_id, &mut output_lines).await?;
+        }
+
+        let state = match container {
+            Some(container) => container.state,
+            None => {
+                self.fail(task.id, &format!("Container {} of the task disappeared", container_id)).await?;
+                return db::task::set_task_container(self.pool, task.id, None).await;
+            }
+        };
+
+        if let ContainerState::Exited { exit_code } = state {
+            let (status, error_message) = match exit_code {
+                0 => ("succeeded", None),
+                code => ("failed", Some(format!("Command exited with code {}", code))),
+            };
+            if db::task::finish_task(self.pool, task.id, status, Some(exit_code), error_message.as_deref()).await? {
+                match status {
+                    "succeeded" => self.summary.tasks_succeeded += 1,
+                    _ => self.summary.tasks_failed += 1,
+                }
+                let message = format!("Command exited with code {}", exit_code);
+                db::task_log::record_system_line(self.pool, task.id, &message).await?;
+            }
+            return self.remove_container(task.id, container_id).await;
+        }
+
+        let timed_out = match (task.started_at, task.timeout_seconds) {
+            (Some(started_at), Some(timeout)) => Utc::now() - started_at >= chrono::Duration::seconds(timeout),
+            _ => false,
+        };
+        if !timed_out {
+            return Ok(());
+        }
+
+        let timeout = task.timeout_seconds.unwrap_or_default();
+        self.driver
+            .stop(container_id, Duration::from_secs(self.config.stop_timeout_seconds))
+            .await?;
+        self.copy_output(task.id, container_id, &mut output_lines).await?;
+
+        let message = format!("Timed out after {} seconds", timeout);
+        if db::task::finish_task(self.pool, task.id, "failed", None, Some(&message)).await? {
+            self.summary.tasks_timed_out += 1;
+            db::task_log::record_system_line(self.pool, task.id, &message).await?;
+        }
+        self.remove_container(task.id, container_id).await
+    }
+
+    /// Stops and removes the container of a task that finished without the
+    /// runner removing it, such as a task canceled while running.
+    async fn clean_up(&mut self, task: &Task) -> anyhow::Result<()> {
+        let container_id = match task.container_id.as_deref() {
+            Some(container_id) => container_id,
+            None => return Ok(()),
+        };
+
+        if self.driver.inspect(container_id).await?.is_some() {
+            self.driver
+                .stop(container_id, Duration::from_secs(self.config.stop_timeout_seconds))
+                .await?;
+            let mut output_lines = task.output_lines;
+            self.copy_output(task.id, container_id, &mut output_lines).await?;
+            if task.status.as_deref() == Some("canceled") {
+                db::task_log::record_system_line(self.pool, task.id, "Stopped the container of the canceled task").await?;
+            }
+        }
+        self.remove_container(task.id, container_id).await
+    }
+
+    /// Removes task containers that no running task is using, such as
+    /// those left behind when recording a launched container failed.
+    async fn remove_stray_containers(&mut self) -> anyhow::Result<()> {
+        let in_use: HashSet<String> = db::task::list_tasks_with_status(self.pool, "running")
+            .await?
+            .into_iter()
+            .filter_map(|task| task.container_id)
+            .collect();
+        let containers = self
+            .driver
+            .list(self.platform_id)
+            .await
+            .with_context(|| format!("Failed to list containers of the {} runtime", self.driver.name()))?;
+
+        for container in containers {
+            let task_id = match container.task_id {
+                Some(task_id) if !in_use.contains(&container.container_id) => task_id,
+                _ => continue,
+            };
+            match self.driver.remove(&container.container_id).await {
+                Ok(()) => {
+                    self.summary.containers_removed += 1;
+                    log::info!("Removed stray container {} of task {}", container.container_id, task_id);
+                }
+                Err(e) => {
+                    self.summary.failures += 1;
+                    log::warn!("Failed to remove stray container {} of task {}: {:#}", container.container_id, task_id, e);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies the lines a task's container wrote since the last copy, up to
+    /// the configured maximum, advancing `output_lines` past them.
+    async fn copy_output(&mut self, task_id: i64, container_id: &str, output_lines: &mut i32) -> anyhow::Result<()> {
+        let lines = self.driver.logs(container_id, None).await?;
+        let seen = (*output_lines).max(0) as usize;
+        if lines.len() <= seen {
+            return Ok(());
+        }
+
+        let max = self.config.max_output_lines as usize;
+        let mut new_lines: Vec<NewTaskLogLine> = lines
+            .iter()
+            .enumerate()
+            .skip(seen)
+            .take_while(|(index, _)| *index < max)
+            .map(|(_, line)| NewTaskLogLine {
+                stream: match line.stream {
+                    LogStream::Stdout => "stdout",
+                    LogStream::Stderr => "stderr",
+                }
+                .to_string(),
+                message: line.message.clone(),
+                timestamp: line.timestamp,
+            })
+            .collect();
+        if seen < max && lines.len() > max {
+            new_lines.push(NewTaskLogLine {
+                stream: "system".to_string(),
+                message: format!("Output truncated after {} lines", max),
+                timestamp: None,
+            });
+        }
+
+        let total = i32::try_from(lines.len()).unwrap_or(i32::MAX);
+        db::task_log::append_task_output(self.pool, task_id, &new_lines, total).await?;
+        *output_lines = total;
+        Ok(())
+    }
+
+    async fn remove_container(&mut self, task_id: i64, container_id: &str) -> anyhow::Result<()> {
+        self.driver.remove(container_id).await?;
+        db::task::set_task_container(self.pool, task_id, None).await?;
+        self.summary.containers_removed += 1;
+        Ok(())
+    }
+
+    /// Fails a running task, recording why in its log.
+    async fn fail(&mut self, task_id: i64, message: &str) -> anyhow::Result<()> {
+        if db::task::finish_task(self.pool, task_id, "failed", None, Some(message)).await? {
+            self.summary.tasks_failed += 1;
+            db::task_log::record_system_line(self.pool, task_id, message).await?;
+        }
+        Ok(())
+    }
+}
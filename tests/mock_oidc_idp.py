@@ -0,0 +1,131 @@
+"""Minimal OpenID Connect identity provider for the SSO tests.
+
+Implements discovery, the authorization endpoint (which signs the configured
+test user in without prompting), the token endpoint with PKCE verification,
+and an empty JWKS endpoint. ID tokens are HS256-signed with the client secret.
+Only the Python standard library is used.
+
+Usage:
+    python mock_oidc_idp.py --port 9000 --email sso-user@example.com --groups platform-admins
+"""
+
+import argparse
+import base64
+import hashlib
+import hmac
+import json
+import secrets
+import time
+from http.server import BaseHTTPRequestHandler, HTTPServer
+from urllib.parse import parse_qs, urlencode, urlparse
+
+ARGS = None
+CODES = {}
+
+
+def b64url(data):
+    return base64.urlsafe_b64encode(data).rstrip(b"=").decode()
+
+
+def sign_id_token(claims):
+    header = b64url(json.dumps({"alg": "HS256", "typ": "JWT"}).encode())
+    payload = b64url(json.dumps(claims).encode())
+    signature = hmac.new(ARGS.client_secret.encode(), f"{header}.{payload}".encode(), hashlib.sha256).digest()
+    return f"{header}.{payload}.{b64url(signature)}"
+
+
+class Handler(BaseHTTPRequestHandler):
+    def issuer(self):
+        return f"http://localhost:{ARGS.port}"
+
+    def send_json(self, status, body):
+        data = json.dumps(body).encode()
+        self.send_response(status)
+        self.send_header("Content-Type", "application/json")
+        self.send_header("Content-Length", str(len(data)))
+        self.end_headers()
+        self.wfile.write(data)
+
+    def do_GET(self):
+        url = urlparse(self.path)
+        query = {k: v[0] for k, v in parse_qs(url.query).items()}
+
+        if url.path == "/.well-known/openid-configuration":
+            self.send_json(200, {
+                "issuer": self.issuer(),
+                "authorization_endpoint": f"{self.issuer()}/authorize",
+                "token_endpoint": f"{self.issuer()}/token",
+                "jwks_uri": f"{self.issuer()}/jwks",
+                "response_types_supported": ["code"],
+                "code_challenge_methods_supported": ["S256"],
+                "token_endpoint_auth_methods_supported": ["client_secret_basic"],
+                "id_token_signing_alg_values_supported": ["HS256"],
+            })
+        elif url.path == "/jwks":
+            self.send_json(200, {"keys": []})
+        elif url.path == "/authorize":
+            if query.get("client_id") != ARGS.client_id or query.get("code_challenge_method") != "S256":
+                self.send_json(400, {"error": "invalid_request"})
+                return
+            code = secrets.token_urlsafe(16)
+            CODES[code] = query
+            location = f"{query['redirect_uri']}?{urlencode({'code': code, 'state': query['state']})}"
+            self.send_response(302)
+            self.send_header("Location", location)
+            self.end_headers()
+        else:
+            self.send_json(404, {"error": "not_found"})
+
+    def do_POST(self):
+        if urlparse(self.path).path != "/token":
+            self.send_json(404, {"error": "not_found"})
+            return
+
+        length = int(self.headers.get("Content-Length", 0))
+        form = {k: v[0] for k, v in parse_qs(self.rfile.read(length).decode()).items()}
+
+        expected_auth = "Basic " + base64.b64encode(f"{ARGS.client_id}:{ARGS.client_secret}".encode()).decode()
+        if self.headers.get("Authorization") != expected_auth:
+            self.send_json(401, {"error": "invalid_client"})
+            return
+
+        request = CODES.pop(form.get("code"), None)
+        if request is None or form.get("redirect_uri") != request["redirect_uri"]:
+            self.send_json(400, {"error": "invalid_grant"})
+            return
+
+        challenge = b64url(hashlib.sha256(form.get("code_verifier", "").encode()).digest())
+        if challenge != request["code_challenge"]:
+            self.send_json(400, {"error": "invalid_grant", "error_description": "PKCE verification failed"})
+            return
+
+        now = int(time.time())
+        id_token = sign_id_token({
+            "iss": self.issuer(),
+            "sub": ARGS.subject,
+            "aud": ARGS.client_id,
+            "iat": now,
+            "exp": now + 300,
+            "nonce": request["nonce"],
+            "email": ARGS.email,
+            "email_verified": True,
+            "name": "SSO Test User",
+            "given_name": "SSO",
+            "family_name": "User",
+            "groups": ARGS.groups,
+        })
+        self.send_json(200, {"access_token": secrets.token_urlsafe(16), "token_type": "Bearer", "id_token": id_token})
+
+
+if __name__ == "__main__":
+    parser = argparse.ArgumentParser(description=__doc__.splitlines()[0])
+    parser.add_argument("--port", type=int, default=9000)
+    parser.add_argument("--client-id", default="omni-orchestrator")
+    parser.add_argument("--client-secret", default="mock-idp-secret")
+    parser.add_argument("--email", default="sso-user@example.com")
+    parser.add_argument("--subject", default="mock-subject-1")
+    parser.add_argument("--groups", nargs="*", default=["platform-admins"])
+    ARGS = parser.parse_args()
+
+    print(f"Mock OIDC provider listening on http://localhost:{ARGS.port}")
+    HTTPServer(("127.0.0.1", ARGS.port), Handler).serve_forever()
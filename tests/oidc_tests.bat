@@ -0,0 +1,156 @@
+@echo off
+setlocal EnableDelayedExpansion
+
+echo Single Sign-On (OIDC) Test Script
+echo =================================
+echo.
+:: Requires the mock identity provider and a matching "oidc" section in
+:: config.json, for example:
+::
+::   python tests\mock_oidc_idp.py --port 9000 --email sso-user@example.com
+::
+::   "oidc": {
+::       "issuer": "http://localhost:9000",
+::       "client_id": "omni-orchestrator",
+::       "client_secret": "mock-idp-secret",
+::       "redirect_uri": "http://localhost:8002/api/v1/auth/oidc/callback",
+::       "group_mappings": [
+::           { "group": "platform-admins", "platform_id": 1, "org_id": 1, "role": "admin" }
+::       ]
+::   }
+
+:: Configuration
+set HOST=localhost
+set PORT=8002
+set SSO_EMAIL=sso-user@example.com
+
+:: Parse command line arguments
+:parse_args
+if "%~1"=="" goto :endparse
+if /i "%~1"=="--host" set HOST=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--port" set PORT=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--email" set SSO_EMAIL=%~2& shift & shift & goto :parse_args
+goto :parse_args
+:endparse
+
+set BASE_URL=http://%HOST%:%PORT%/api/v1
+set TOKEN=
+set CALLBACK_URL=
+set TESTS_PASSED=true
+
+echo Using API at %BASE_URL%
+echo.
+
+call :test_sso_login
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_get_current_user
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_callback_replay
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_open_redirect
+if "!TESTS_PASSED!"=="false" goto :end
+
+echo.
+echo All tests completed successfully!
+goto :end
+
+:: ==================
+:: Test Functions
+:: ==================
+
+:test_sso_login
+echo Testing SSO login...
+:: Follow the redirects by hand so the callback URL can be replayed later
+for /f "delims=" %%a in ('curl -s -o nul -w "%%{redirect_url}" "%BASE_URL%/auth/oidc/login"') do set AUTHORIZE_URL=%%a
+if "!AUTHORIZE_URL!"=="" (
+    echo ❌ Login did not redirect to the identity provider
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+for /f "delims=" %%a in ('curl -s -o nul -w "%%{redirect_url}" "!AUTHORIZE_URL!"') do set CALLBACK_URL=%%a
+if "!CALLBACK_URL!"=="" (
+    echo ❌ Identity provider did not redirect back to the callback
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+curl -s "!CALLBACK_URL!" > sso_login_response.json
+
+findstr /C:"token" sso_login_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ SSO login failed
+    type sso_login_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ SSO login successful
+for /f "tokens=2 delims=:," %%a in ('findstr /C:"\"token\"" sso_login_response.json') do (
+    set TOKEN=%%a
+    set TOKEN=!TOKEN:"=!
+    set TOKEN=!TOKEN: =!
+)
+echo   Token: !TOKEN:~0,20!...
+exit /b 0
+
+:test_get_current_user
+echo Testing provisioned user...
+curl -s -X GET %BASE_URL%/auth/me ^
+  -H "Content-Type: application/json" ^
+  -H "Authorization: Bearer !TOKEN!" > sso_me_response.json
+
+findstr /C:"%SSO_EMAIL%" sso_me_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ SSO user was not provisioned
+    type sso_me_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ SSO user provisioned
+exit /b 0
+
+:test_callback_replay
+echo Testing callback replay...
+curl -s "!CALLBACK_URL!" > sso_replay_response.json
+
+findstr /C:"Invalid callback" sso_replay_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Replayed callback was not rejected
+    type sso_replay_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Replayed callback rejected
+exit /b 0
+
+:test_open_redirect
+echo Testing redirect validation...
+curl -s "%BASE_URL%/auth/oidc/login?redirect_to=https://example.com/" > sso_redirect_response.json
+
+findstr /C:"Invalid redirect" sso_redirect_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ External redirect target was accepted
+    type sso_redirect_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ External redirect target rejected
+exit /b 0
+
+:end
+del sso_login_response.json sso_me_response.json sso_replay_response.json sso_redirect_response.json 2>nul
+if "!TESTS_PASSED!"=="true" (
+    echo.
+    echo ✅ All tests passed!
+) else (
+    echo.
+    echo ❌ Some tests failed!
+)
+endlocal
@@ -0,0 +1,440 @@
+@echo off
+setlocal EnableDelayedExpansion
+
+echo RBAC Enforcement Test Script
+echo ============================
+echo.
+echo Every route below requires the listed permission. A request without
+echo credentials must be rejected with 401, and a freshly registered user
+echo (who holds no roles yet) must be rejected with 403.
+echo.
+
+:: Configuration
+set HOST=localhost
+set PORT=8002
+set PLATFORM_ID=1
+set TEST_EMAIL=rbac-%RANDOM%@example.com
+set TEST_PASSWORD=TestPassword123!
+:: Optional: a caller whose roles are confined to one organization and one
+:: of its applications, and IDs of resources outside that application
+set SCOPED_TOKEN=
+set SCOPED_ORG=
+set SCOPED_APP=
+set FOREIGN_DEPLOYMENT=
+set FOREIGN_BUILD=
+set FOREIGN_INSTANCE=
+
+:: Parse command line arguments
+:parse_args
+if "%~1"=="" goto :endparse
+if /i "%~1"=="--host" set HOST=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--port" set PORT=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--platform" set PLATFORM_ID=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--scoped-token" set SCOPED_TOKEN=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--scoped-org" set SCOPED_ORG=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--scoped-app" set SCOPED_APP=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--foreign-deployment" set FOREIGN_DEPLOYMENT=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--foreign-build" set FOREIGN_BUILD=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--foreign-instance" set FOREIGN_INSTANCE=%~2& shift & shift & goto :parse_args
+goto :parse_args
+:endparse
+
+set BASE_URL=http://%HOST%:%PORT%/api/v1
+
+:: Initialize variables
+set TOKEN=
+set TESTS_PASSED=true
+set CHECKED=0
+
+echo Using API at %BASE_URL% (platform %PLATFORM_ID%)
+echo.
+
+:: Routes that read their scope from the request body need a valid body
+echo {"name":"rbac-test","memory":128,"instances":1,"org_id":1}> rbac_app_body.json
+echo {"app_id":1,"build_id":1,"version":"1.0.0","deployment_strategy":"rolling"}> rbac_deployment_body.json
+echo {"org_id":1,"name":"rbac-test","scopes":["apps:read"]}> rbac_api_key_body.json
+echo {"org_id":1,"name":"rbac-test"}> rbac_role_body.json
+echo {"name":"rbac-test-org"}> rbac_org_body.json
+echo {"role":"admin"}> rbac_member_body.json
+echo {"email":"rbac-invitee@example.com","role":"member"}> rbac_invitation_body.json
+echo {"user_id":1}> rbac_transfer_body.json
+echo {"name":"rbac-test"}> rbac_space_body.json
+echo {"space_id":1}> rbac_move_body.json
+echo {"name":"rbac-test","stages":[{"name":"build","type":"build"}]}> rbac_pipeline_body.json
+echo {"command":"echo rbac"}> rbac_task_body.json
+echo {"name":"rbac-test","command":"echo rbac","cron_expression":"@daily"}> rbac_task_schedule_body.json
+echo {"user_id":1,"role_id":1,"scope_type":"organization","scope_id":1}> rbac_role_binding_body.json
+echo {"status":"failed"}> rbac_deployment_status_body.json
+
+:: Register an unprivileged user
+call :register
+if "!TESTS_PASSED!"=="false" goto :end
+
+:: The introspection endpoint must report no permissions for the new user
+call :test_my_permissions
+
+call :expect_denied GET    "/apps/1/logs" "logs:read"
+call :expect_denied GET    "/instances/1/logs" "logs:read"
+call :expect_denied GET    "/logs" "logs:read"
+call :expect_denied POST   "/logs" "logs:write"
+call :expect_denied GET    "/meta/1" "metadata:read"
+call :expect_denied POST   "/meta/1" "metadata:write"
+call :expect_denied GET    "/orgs/1/logs" "logs:read"
+call :expect_denied GET    "/permissions" "permissions:read"
+call :expect_denied POST   "/permissions" "permissions:write"
+call :expect_denied DELETE "/permissions/1" "permissions:write"
+call :expect_denied GET    "/permissions/1" "permissions:read"
+call :expect_denied PUT    "/permissions/1" "permissions:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/alerts" "alerts:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/alerts" "alerts:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/alerts/1" "alerts:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/alerts/1/acknowledge" "alerts:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/alerts/1/escalate" "alerts:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/alerts/1/resolve" "alerts:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/alerts/1/status" "alerts:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/alerts/auto-resolve" "alerts:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/alerts/bulk-status" "alerts:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/alerts/needing-escalation" "alerts:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/alerts/search" "alerts:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/api_keys" "api_keys:write" rbac_api_key_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/app-count" "apps:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/app_with_instances/1" "apps:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps" "apps:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps" "apps:write" rbac_app_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/apps/1" "apps:delete"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1" "apps:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1" "apps:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/alerts" "alerts:read"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/apps/1/approval-policy" "deployments:manage"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/approval-policy" "deployments:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/approval-policy" "deployments:manage"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/apps/1/artifact-retention" "builds:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/artifact-retention" "builds:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/artifact-retention" "builds:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/autoscaling" "apps:control"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/autoscaling/decisions" "apps:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/autoscaling/rules" "apps:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/autoscaling/rules" "apps:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/apps/1/autoscaling/rules/1" "apps:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/autoscaling/rules/1" "apps:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/autoscaling/rules/1" "apps:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/builds" "builds:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/deployments" "deployments:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/events" "apps:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/instances" "instances:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/instances/region/1" "instances:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/releases/1/upload" "builds:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/rollbacks" "deployments:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/pipelines" "pipelines:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/pipelines" "pipelines:write" rbac_pipeline_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/apps/1/pipelines/1" "pipelines:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/pipelines/1" "pipelines:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/pipelines/1" "pipelines:write" rbac_pipeline_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs" "pipelines:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs" "pipelines:run"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs/1" "pipelines:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs/1/cancel" "pipelines:run"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs/1/logs" "pipelines:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs/1/retry" "pipelines:run"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs/1/stages/1/approve" "deployments:approve"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/pipelines/1/runs/1/stages/1/reject" "deployments:approve"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/tasks" "tasks:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/tasks" "tasks:write" rbac_task_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/tasks/1" "tasks:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/tasks/1/cancel" "tasks:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/tasks/1/logs" "tasks:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/task-schedules" "tasks:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/task-schedules" "tasks:write" rbac_task_schedule_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/task-schedules/1" "tasks:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/task-schedules/1" "tasks:write" rbac_task_schedule_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/apps/1/task-schedules/1" "tasks:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/scale" "apps:control"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/scheduling" "apps:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/scheduling" "apps:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/space" "apps:write" rbac_move_body.json
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/start" "apps:control"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/stats" "apps:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/stop" "apps:control"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/apps/1/webhook" "apps:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps/1/webhook" "apps:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/apps/1/webhook" "apps:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/apps/1/webhook/rotate-secret" "apps:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/audit_log" "audit_logs:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs" "audit_logs:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs/1" "audit_logs:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs/search?action=update_app" "audit_logs:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs/count?group_by=action" "audit_logs:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs/verify" "audit_logs:export"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs/export?from=2024-01-01" "audit_logs:export"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs/retention" "audit_logs:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/audit_logs/retention" "audit_logs:manage"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/audit_logs/archive" "audit_logs:manage"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/audit_logs/archives" "audit_logs:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/builds" "builds:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/builds/1" "builds:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/builds/1/artifact" "builds:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/builds/1/cancel" "builds:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/builds/1/complete" "builds:execute"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/builds/1/fail" "builds:execute"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/builds/1/heartbeat" "builds:execute"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/builds/1/logs" "builds:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/builds/1/logs" "builds:execute"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/builds/lease" "builds:execute"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/cost_allocation_tags" "cost:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/cost_allocation_tags/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/cost_allocation_tags/1/1" "cost:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/cost_analysis/by_dimension" "cost:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/cost_analysis/over_time" "cost:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/cost_budgets" "cost:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/cost_budgets" "cost:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/cost_budgets/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/cost_budgets/1" "cost:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/cost_budgets/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/cost_metrics" "cost:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/cost_metrics" "cost:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/cost_metrics/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/cost_metrics/1" "cost:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/cost_projections" "cost:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/cost_projections" "cost:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/cost_projections/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/cost_projections/1" "cost:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/count/deployments" "deployments:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/count/resource_types" "cost:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments" "deployments:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments" "deployments:write" rbac_deployment_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/deployments/1" "deployments:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments/1" "deployments:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments/1/abort" "deployments:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments/1/approvals" "deployments:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments/1/approve" "deployments:approve"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments/1/events" "deployments:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments/1/events/stream" "deployments:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments/1/pause" "deployments:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments/1/progress" "deployments:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments/1/promote" "deployments:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments/1/reject" "deployments:approve"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments/1/resume" "deployments:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/deployments/1/rollback" "deployments:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/deployments/1/status" "deployments:write" rbac_deployment_status_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/instance-count" "instances:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/instances/1" "instances:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/instances/1/logs" "instances:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/metrics" "metrics:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/metrics/1" "metrics:read"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/notifications/1" "notifications:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/notifications/1" "notifications:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/notifications/1/read" "notifications:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/notifications/acknowledge" "notifications:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/notifications/role" "notifications:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/notifications/role/1" "notifications:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/notifications/user" "notifications:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/notifications/user/1" "notifications:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/notifications/user/1/all" "notifications:read"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/notifications/user/1/read" "notifications:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/notifications/user/1/read-all" "notifications:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/notifications/user/count/1" "notifications:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/orgs" "orgs:write" rbac_org_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/orgs/1" "orgs:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1" "orgs:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/orgs/1" "orgs:write" rbac_org_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1/active-alerts" "alerts:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1/alert-stats" "alerts:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1/api_keys" "api_keys:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1/freeze-windows" "deployments:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/orgs/1/freeze-windows" "deployments:manage"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/orgs/1/freeze-windows/1" "deployments:manage"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/orgs/1/freeze-windows/1" "deployments:manage"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1/invitations" "members:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/orgs/1/invitations" "members:write" rbac_invitation_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/orgs/1/invitations/1" "members:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1/members" "members:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/orgs/1/members/1" "members:write" rbac_member_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/orgs/1/members/1" "members:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/orgs/1/spaces" "spaces:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/orgs/1/spaces" "spaces:write" rbac_space_body.json
+call :expect_denied POST   "/platform/%PLATFORM_ID%/orgs/1/transfer_ownership" "orgs:write" rbac_transfer_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/provider_regions" "regions:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/providers" "providers:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/providers/1/audit_logs" "providers:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/providers/1/instances" "providers:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/regions" "regions:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/resource_pricing" "cost:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/resource_pricing" "cost:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/resource_pricing/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/resource_pricing/1" "cost:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/resource_pricing/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/resource_types" "cost:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/resource_types" "cost:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/resource_types/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/resource_types/1" "cost:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/resource_types/1" "cost:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/role_bindings" "permissions:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/role_bindings" "permissions:write" rbac_role_binding_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/role_bindings?user_id=1&role_id=1&scope_type=organization&scope_id=1" "permissions:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/roles" "permissions:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/roles" "permissions:write" rbac_role_body.json
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/roles/1" "permissions:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/roles/1" "permissions:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/roles/1/permissions" "permissions:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/roles/1/permissions/1" "permissions:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/roles/1/permissions/1" "permissions:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/rollbacks/1" "deployments:read"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/spaces/1" "spaces:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/spaces/1" "spaces:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/spaces/1" "spaces:write" rbac_space_body.json
+call :expect_denied GET    "/platform/%PLATFORM_ID%/spaces/1/apps" "apps:read"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/spaces/1/approval-policy" "deployments:manage"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/spaces/1/approval-policy" "deployments:read"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/spaces/1/approval-policy" "deployments:manage"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/spaces/1/archive" "spaces:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/spaces/1/restore" "spaces:write"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/spaces/1/role_bindings" "permissions:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/classes" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/classes/1" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/classes/1/volumes" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/persistence-levels/1/volumes" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/providers/1/volumes" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/qos-policies" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/regions/1/volumes" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/volumes" "storage:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/storage/write-concerns/1/volumes" "storage:read"
+call :expect_denied GET    "/platforms" "platforms:read"
+call :expect_denied POST   "/platforms" "platforms:write"
+call :expect_denied DELETE "/platforms/%PLATFORM_ID%" "platforms:write"
+call :expect_denied GET    "/platforms/%PLATFORM_ID%/logs" "logs:read"
+call :expect_denied GET    "/users" "users:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/workers" "workers:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/workers/1" "workers:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/workers/bootstrap_tokens" "workers:read"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/workers/bootstrap_tokens" "workers:write"
+call :expect_denied DELETE "/platform/%PLATFORM_ID%/workers/bootstrap_tokens/1" "workers:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/workers/1/cordon" "workers:write"
+call :expect_denied PUT    "/platform/%PLATFORM_ID%/workers/1/uncordon" "workers:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/workers/1/drain" "workers:write"
+call :expect_denied POST   "/platform/%PLATFORM_ID%/workers/1/decommission" "workers:write"
+
+:: Scope named in the query must not grant anything the path does not
+call :expect_denied GET    "/platform/%PLATFORM_ID%/apps?page=0&per_page=100&org_id=1" "apps:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments?page=0&per_page=100&app_id=1" "deployments:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/deployments/1?app_id=1" "deployments:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/builds/1?app_id=1" "builds:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/instances/1?app_id=1" "instances:read"
+call :expect_denied GET    "/platform/%PLATFORM_ID%/rollbacks/1?app_id=1" "deployments:read"
+
+if defined SCOPED_TOKEN (
+    call :test_query_scope_bypass
+) else (
+    echo Skipping scoped caller checks, pass --scoped-token, --scoped-org and --scoped-app to run them
+)
+
+echo.
+echo Checked !CHECKED! routes.
+goto :end
+
+:: ==================
+:: Test Functions
+:: ==================
+
+:register
+echo Registering unprivileged test user...
+curl -s -X POST %BASE_URL%/auth/register ^
+  -H "Content-Type: application/json" ^
+  -d "{\"email\":\"%TEST_EMAIL%\",\"password\":\"%TEST_PASSWORD%\"}" > rbac_register_response.json
+
+findstr /C:"token" rbac_register_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Registration failed
+    type rbac_register_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+for /f "tokens=2 delims=:," %%a in ('findstr /C:"\"token\"" rbac_register_response.json') do (
+    set TOKEN=%%a
+    set TOKEN=!TOKEN:"=!
+    set TOKEN=!TOKEN: =!
+)
+echo ✅ Registered %TEST_EMAIL%
+exit /b 0
+
+:test_my_permissions
+echo Testing permission introspection...
+curl -s -X GET "%BASE_URL%/auth/me/permissions?platform_id=%PLATFORM_ID%" ^
+  -H "Authorization: Bearer !TOKEN!" > rbac_me_response.json
+
+findstr /C:"\"permissions\":[]" rbac_me_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ New user unexpectedly holds permissions
+    type rbac_me_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ New user holds no permissions
+exit /b 0
+
+:test_query_scope_bypass
+echo.
+echo Testing that a scoped caller cannot widen its scope with query parameters...
+call :expect_scoped_forbidden "/platform/%PLATFORM_ID%/apps?page=0&per_page=100&org_id=%SCOPED_ORG%"
+call :expect_scoped_forbidden "/platform/%PLATFORM_ID%/app-count?org_id=%SCOPED_ORG%"
+call :expect_scoped_forbidden "/platform/%PLATFORM_ID%/deployments?page=0&per_page=100&app_id=%SCOPED_APP%"
+call :expect_scoped_forbidden "/platform/%PLATFORM_ID%/builds?page=0&per_page=100&app_id=%SCOPED_APP%"
+if defined FOREIGN_DEPLOYMENT call :expect_scoped_forbidden "/platform/%PLATFORM_ID%/deployments/%FOREIGN_DEPLOYMENT%?app_id=%SCOPED_APP%"
+if defined FOREIGN_BUILD call :expect_scoped_forbidden "/platform/%PLATFORM_ID%/builds/%FOREIGN_BUILD%?app_id=%SCOPED_APP%"
+if defined FOREIGN_INSTANCE call :expect_scoped_forbidden "/platform/%PLATFORM_ID%/instances/%FOREIGN_INSTANCE%?app_id=%SCOPED_APP%"
+exit /b 0
+
+:: Usage: call :expect_scoped_forbidden PATH
+:expect_scoped_forbidden
+set /a CHECKED+=1
+set "ROUTE=%~1"
+
+for /f %%s in ('curl -s -o nul -w "%%{http_code}" "%BASE_URL%!ROUTE!" -H "Authorization: Bearer !SCOPED_TOKEN!"') do set SCOPED_STATUS=%%s
+
+if not "!SCOPED_STATUS!"=="403" (
+    echo ❌ GET !ROUTE! as a scoped caller returned !SCOPED_STATUS!, expected 403
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ GET !ROUTE! is not opened up by its query parameters
+exit /b 0
+
+:: Usage: call :expect_denied METHOD PATH PERMISSION [BODY_FILE]
+:expect_denied
+set /a CHECKED+=1
+set METHOD=%~1
+set "ROUTE=%~2"
+set PERMISSION=%~3
+set BODY={}
+if not "%~4"=="" set BODY=@%~4
+
+for /f %%s in ('curl -s -o nul -w "%%{http_code}" -X !METHOD! "%BASE_URL%!ROUTE!" -H "Content-Type: application/json" -d "!BODY!"') do set ANON_STATUS=%%s
+for /f %%s in ('curl -s -o nul -w "%%{http_code}" -X !METHOD! "%BASE_URL%!ROUTE!" -H "Content-Type: application/json" -H "Authorization: Bearer !TOKEN!" -d "!BODY!"') do set USER_STATUS=%%s
+
+if not "!ANON_STATUS!"=="401" (
+    echo ❌ !METHOD! !ROUTE! without credentials returned !ANON_STATUS!, expected 401
+    set TESTS_PASSED=false
+    exit /b 1
+)
+if not "!USER_STATUS!"=="403" (
+    echo ❌ !METHOD! !ROUTE! without !PERMISSION! returned !USER_STATUS!, expected 403
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ !METHOD! !ROUTE! requires !PERMISSION!
+exit /b 0
+
+:end
+if "!TESTS_PASSED!"=="true" (
+    echo.
+    echo ✅ All tests passed!
+) else (
+    echo.
+    echo ❌ Some tests failed!
+)
+
+:: Clean up temp files
+del rbac_register_response.json rbac_me_response.json rbac_app_body.json rbac_deployment_body.json rbac_api_key_body.json rbac_role_body.json rbac_role_binding_body.json rbac_org_body.json rbac_member_body.json rbac_invitation_body.json rbac_transfer_body.json rbac_space_body.json rbac_move_body.json rbac_pipeline_body.json rbac_task_body.json rbac_task_schedule_body.json rbac_deployment_status_body.json 2>nul
@@ -0,0 +1,295 @@
+@echo off
+setlocal EnableDelayedExpansion
+
+echo Repository Webhook Test Script
+echo ==============================
+echo.
+:: Replays the recorded deliveries in tests\webhooks against the webhook of
+:: an application tracking the 'main' branch. Signatures are computed with
+:: Python, so it must be on the PATH. The token must belong to a user
+:: holding apps:write on the application:
+::
+::   tests\webhook_tests.bat --token <token> --app 1
+
+:: Configuration
+set HOST=localhost
+set PORT=8002
+set PLATFORM_ID=1
+set APP_ID=1
+set TOKEN=
+set SECRET=webhook-test-secret-0123456789
+set PAYLOADS=%~dp0webhooks
+
+:: Parse command line arguments
+:parse_args
+if "%~1"=="" goto :endparse
+if /i "%~1"=="--host" set HOST=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--port" set PORT=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--platform" set PLATFORM_ID=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--app" set APP_ID=%~2& shift & shift & goto :parse_args
+if /i "%~1"=="--token" set TOKEN=%~2& shift & shift & goto :parse_args
+goto :parse_args
+:endparse
+
+set BASE_URL=http://%HOST%:%PORT%/api/v1
+set WEBHOOK_URL=%BASE_URL%/platform/%PLATFORM_ID%/apps/%APP_ID%/webhook
+set TESTS_PASSED=true
+
+if "%TOKEN%"=="" (
+    echo ❌ Pass the token of a user holding apps:write with --token
+    set TESTS_PASSED=false
+    goto :end
+)
+
+echo Using webhook %WEBHOOK_URL%
+echo.
+
+call :test_set_webhook
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_secret_hidden
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_github_ping
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_invalid_signature
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_github_push
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_github_redelivery
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_gitlab_push
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_gitea_push
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_untracked_branch
+if "!TESTS_PASSED!"=="false" goto :end
+
+call :test_delete_webhook
+if "!TESTS_PASSED!"=="false" goto :end
+
+echo.
+echo All tests completed successfully!
+goto :end
+
+:: ==================
+:: Test Functions
+:: ==================
+
+:test_set_webhook
+echo Testing webhook setup...
+curl -s -X PUT %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "Authorization: Bearer %TOKEN%" ^
+  -d "{\"auto_deploy\":true,\"deployment_strategy\":\"rolling\",\"secret\":\"%SECRET%\"}" > webhook_set_response.json
+
+findstr /C:"\"url\"" webhook_set_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Webhook setup failed
+    type webhook_set_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Webhook set up
+exit /b 0
+
+:test_secret_hidden
+echo Testing webhook secret is not returned...
+curl -s -X GET %WEBHOOK_URL% ^
+  -H "Authorization: Bearer %TOKEN%" > webhook_get_response.json
+
+findstr /C:"%SECRET%" webhook_get_response.json > nul
+if %ERRORLEVEL% EQU 0 (
+    echo ❌ Webhook secret was returned
+    type webhook_get_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Webhook secret not returned
+exit /b 0
+
+:test_github_ping
+echo Testing GitHub ping...
+call :sign github_ping.json
+curl -s -X POST %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "X-GitHub-Event: ping" ^
+  -H "X-GitHub-Delivery: 7f3c2a10-0000-4000-8000-000000000001" ^
+  -H "X-Hub-Signature-256: sha256=!SIGNATURE!" ^
+  --data-binary "@%PAYLOADS%\github_ping.json" > webhook_ping_response.json
+
+findstr /C:"pong" webhook_ping_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ GitHub ping was not answered
+    type webhook_ping_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ GitHub ping answered
+exit /b 0
+
+:test_invalid_signature
+echo Testing delivery with an invalid signature...
+curl -s -X POST %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "X-GitHub-Event: push" ^
+  -H "X-Hub-Signature-256: sha256=0000000000000000000000000000000000000000000000000000000000000000" ^
+  --data-binary "@%PAYLOADS%\github_push.json" > webhook_forged_response.json
+
+findstr /C:"Unauthorized" webhook_forged_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Delivery with an invalid signature was accepted
+    type webhook_forged_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Delivery with an invalid signature rejected
+exit /b 0
+
+:test_github_push
+echo Testing GitHub push...
+call :sign github_push.json
+curl -s -X POST %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "X-GitHub-Event: push" ^
+  -H "X-GitHub-Delivery: 7f3c2a10-0000-4000-8000-000000000002" ^
+  -H "X-Hub-Signature-256: sha256=!SIGNATURE!" ^
+  --data-binary "@%PAYLOADS%\github_push.json" > webhook_github_response.json
+
+findstr /C:"0d1a26e67d8f5eaf1f6ba5c57fc3c7d91ac0fd1c" webhook_github_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ GitHub push did not queue a build
+    type webhook_github_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ GitHub push queued a build
+exit /b 0
+
+:test_github_redelivery
+echo Testing GitHub redelivery...
+call :sign github_push.json
+curl -s -X POST %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "X-GitHub-Event: push" ^
+  -H "X-GitHub-Delivery: 7f3c2a10-0000-4000-8000-000000000002" ^
+  -H "X-Hub-Signature-256: sha256=!SIGNATURE!" ^
+  --data-binary "@%PAYLOADS%\github_push.json" > webhook_redelivery_response.json
+
+findstr /C:"duplicate" webhook_redelivery_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Redelivered push was acted on again
+    type webhook_redelivery_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Redelivered push ignored
+exit /b 0
+
+:test_gitlab_push
+echo Testing GitLab push...
+curl -s -X POST %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "X-Gitlab-Event: Push Hook" ^
+  -H "X-Gitlab-Token: %SECRET%" ^
+  --data-binary "@%PAYLOADS%\gitlab_push.json" > webhook_gitlab_response.json
+
+findstr /C:"da1560886d4f094c3e6c9ef40349f7d38b5d27d7" webhook_gitlab_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ GitLab push did not queue a build
+    type webhook_gitlab_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ GitLab push queued a build
+exit /b 0
+
+:test_gitea_push
+echo Testing Gitea push...
+call :sign gitea_push.json
+curl -s -X POST %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "X-Gitea-Event: push" ^
+  -H "X-Gitea-Delivery: 5b1e9c2d-0000-4000-8000-000000000003" ^
+  -H "X-Gitea-Signature: !SIGNATURE!" ^
+  --data-binary "@%PAYLOADS%\gitea_push.json" > webhook_gitea_response.json
+
+findstr /C:"bffeb74224043ba2feb48d137756c8a9331c449a" webhook_gitea_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Gitea push did not queue a build
+    type webhook_gitea_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Gitea push queued a build
+exit /b 0
+
+:test_untracked_branch
+echo Testing push to an untracked branch...
+call :sign github_push_feature.json
+curl -s -X POST %WEBHOOK_URL% ^
+  -H "Content-Type: application/json" ^
+  -H "X-GitHub-Event: push" ^
+  -H "X-Hub-Signature-256: sha256=!SIGNATURE!" ^
+  --data-binary "@%PAYLOADS%\github_push_feature.json" > webhook_branch_response.json
+
+findstr /C:"ignored" webhook_branch_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Push to an untracked branch was not ignored
+    type webhook_branch_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Push to an untracked branch ignored
+exit /b 0
+
+:test_delete_webhook
+echo Testing webhook removal...
+curl -s -X DELETE %WEBHOOK_URL% ^
+  -H "Authorization: Bearer %TOKEN%" > webhook_delete_response.json
+
+findstr /C:"deleted" webhook_delete_response.json > nul
+if %ERRORLEVEL% NEQ 0 (
+    echo ❌ Webhook removal failed
+    type webhook_delete_response.json
+    set TESTS_PASSED=false
+    exit /b 1
+)
+
+echo ✅ Webhook removed
+exit /b 0
+
+:: Usage: call :sign PAYLOAD_FILE
+:: Sets SIGNATURE to the hex-encoded HMAC-SHA256 of the payload
+:sign
+set SIGNATURE=
+for /f "delims=" %%a in ('python -c "import hashlib,hmac,sys;print(hmac.new(sys.argv[1].encode(),open(sys.argv[2],'rb').read(),hashlib.sha256).hexdigest())" "%SECRET%" "%PAYLOADS%\%~1"') do set SIGNATURE=%%a
+exit /b 0
+
+:end
+del webhook_set_response.json webhook_get_response.json webhook_ping_response.json webhook_forged_response.json 2>nul
+del webhook_github_response.json webhook_gitlab_response.json webhook_gitea_response.json 2>nul
+del webhook_redelivery_response.json webhook_branch_response.json webhook_delete_response.json 2>nul
+if "!TESTS_PASSED!"=="true" (
+    echo.
+    echo ✅ All tests passed!
+) else (
+    echo.
+    echo ❌ Some tests failed!
+)
+endlocal